@@ -2,7 +2,7 @@ use nu_engine::eval_block;
 use nu_parser::{flatten_block, parse};
 use nu_protocol::{
     engine::{EngineState, Stack, StateWorkingSet},
-    PipelineData,
+    PipelineData, Span,
 };
 use reedline::Completer;
 
@@ -27,41 +27,62 @@ impl Completer for NuCompleter {
         let (output, _err) = parse(&mut working_set, Some("completer"), line.as_bytes(), false);
 
         let flattened = flatten_block(&working_set, &output);
+        let mut call_start = None;
 
         for flat in flattened {
+            if matches!(
+                flat.1,
+                nu_parser::FlatShape::External | nu_parser::FlatShape::InternalCall
+            ) {
+                call_start = Some(flat.0.start);
+            }
+
             if pos >= flat.0.start && pos <= flat.0.end {
-                let prefix = working_set.get_span_contents(flat.0);
+                // A span we can't read the source of has nothing to complete
+                let prefix = match working_set.try_get_span_contents(flat.0) {
+                    Ok(prefix) => prefix,
+                    Err(_) => return vec![],
+                };
                 if prefix.starts_with(b"$") {
-                    let mut output = vec![];
+                    return working_set
+                        .find_variables_by_prefix(prefix)
+                        .into_iter()
+                        .map(move |(name, ..)| {
+                            (
+                                reedline::Span {
+                                    start: flat.0.start - offset,
+                                    end: flat.0.end - offset,
+                                },
+                                String::from_utf8_lossy(&name).to_string(),
+                            )
+                        })
+                        .collect();
+                }
 
-                    for scope in &working_set.delta.scope {
-                        for v in &scope.vars {
-                            if v.0.starts_with(prefix) {
-                                output.push((
-                                    reedline::Span {
-                                        start: flat.0.start - offset,
-                                        end: flat.0.end - offset,
-                                    },
-                                    String::from_utf8_lossy(v.0).to_string(),
-                                ));
-                            }
-                        }
-                    }
-                    for scope in &self.engine_state.scope {
-                        for v in &scope.vars {
-                            if v.0.starts_with(prefix) {
-                                output.push((
+                // A word after a command can be the rest of a longer command name, eg `str foo b`
+                // on the way to `str foo bar`
+                if let Some(start) = call_start.filter(|start| *start < flat.0.start) {
+                    let words = Span {
+                        start,
+                        end: flat.0.end,
+                    };
+                    let prefix = working_set.get_span_contents(words);
+                    let results = working_set.find_commands_by_prefix(prefix);
+
+                    if !results.is_empty() {
+                        return results
+                            .into_iter()
+                            .map(move |x| {
+                                (
                                     reedline::Span {
-                                        start: flat.0.start - offset,
-                                        end: flat.0.end - offset,
+                                        start: words.start - offset,
+                                        end: words.end - offset,
                                     },
-                                    String::from_utf8_lossy(v.0).to_string(),
-                                ));
-                            }
-                        }
+                                    String::from_utf8_lossy(&x).to_string(),
+                                )
+                            })
+                            .collect();
                     }
-
-                    return output;
                 }
 
                 match &flat.1 {
@@ -201,3 +222,33 @@ fn matches(partial: &str, from: &str) -> bool {
     from.to_ascii_lowercase()
         .starts_with(&partial.to_ascii_lowercase())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nu_protocol::{Signature, SyntaxShape};
+
+    #[test]
+    fn completes_the_rest_of_multi_word_commands() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_decl(
+                Signature::build("spam")
+                    .rest("words", SyntaxShape::String, "words")
+                    .predeclare(),
+            );
+            working_set.add_decl(Signature::build("spam eggs bacon").predeclare());
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let completions: Vec<_> = NuCompleter::new(engine_state)
+            .complete("spam eggs b", 11)
+            .into_iter()
+            .map(|(span, completion)| (span.start, span.end, completion))
+            .collect();
+
+        assert_eq!(completions, vec![(0, 11, "spam eggs bacon".to_string())]);
+    }
+}
@@ -1,5 +1,5 @@
 use miette::{LabeledSpan, MietteHandler, ReportHandler, Severity, SourceCode};
-use nu_protocol::engine::StateWorkingSet;
+use nu_protocol::engine::{EngineState, StateWorkingSet};
 use thiserror::Error;
 
 /// This error exists so that we can defer SourceCode handling. It simply
@@ -49,5 +49,107 @@ pub fn report_error(
     working_set: &StateWorkingSet,
     error: &(dyn miette::Diagnostic + Send + Sync + 'static),
 ) {
-    eprintln!("Error: {:?}", CliError(error, working_set));
+    report(working_set, "Error", error);
+}
+
+/// Show the warnings raised since the last time, after the output of the pipeline that raised them
+pub fn report_warnings(engine_state: &EngineState) {
+    let warnings = engine_state.take_warnings();
+    if warnings.is_empty() {
+        return;
+    }
+
+    let working_set = StateWorkingSet::new(engine_state);
+    for warning in &warnings {
+        report(&working_set, "Warning", warning);
+    }
+}
+
+fn report(
+    working_set: &StateWorkingSet,
+    kind: &str,
+    diagnostic: &(dyn miette::Diagnostic + Send + Sync + 'static),
+) {
+    let report = format!("{}: {:?}\n", kind, CliError(diagnostic, working_set));
+    let _ = working_set
+        .permanent_state
+        .output
+        .write_err(report.as_bytes());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nu_protocol::ast::Call;
+    use nu_protocol::engine::{BufferedOutput, Command, Stack};
+    use nu_protocol::{
+        IntoPipelineData, PipelineData, ShellError, ShellWarning, Signature, Span, Value,
+    };
+    use std::sync::Arc;
+
+    /// Passes its input on, warning that it's deprecated
+    #[derive(Clone)]
+    struct Deprecated;
+
+    impl Command for Deprecated {
+        fn name(&self) -> &str {
+            "deprecated"
+        }
+
+        fn signature(&self) -> Signature {
+            Signature::build("deprecated")
+        }
+
+        fn usage(&self) -> &str {
+            "Pass the input on, with a warning."
+        }
+
+        fn run(
+            &self,
+            engine_state: &EngineState,
+            _stack: &mut Stack,
+            call: &Call,
+            input: PipelineData,
+        ) -> Result<PipelineData, ShellError> {
+            engine_state.warn(ShellWarning::Message(
+                "deprecated is deprecated".into(),
+                call.head,
+            ));
+            Ok(input)
+        }
+    }
+
+    #[test]
+    fn reports_each_warning_once() {
+        let mut engine_state = EngineState::new();
+        let output = Arc::new(BufferedOutput::new());
+        engine_state.output = output.clone();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_file("warn.nu".into(), b"deprecated 42");
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+        let mut call = Call::new();
+        call.head = Span { start: 0, end: 10 };
+
+        for _ in 0..2 {
+            let result = Deprecated
+                .run(
+                    &engine_state,
+                    &mut Stack::new(),
+                    &call,
+                    Value::test_int(42).into_pipeline_data(),
+                )
+                .expect("warnings don't fail the pipeline");
+            assert_eq!(result.into_value(Span::unknown()), Value::test_int(42));
+
+            report_warnings(&engine_state);
+        }
+
+        let reported = String::from_utf8(output.take_err()).expect("utf-8 diagnostics");
+        assert_eq!(reported.matches("deprecated is deprecated").count(), 1);
+        assert!(reported.starts_with("Warning: "));
+        assert!(output.take_out().is_empty());
+    }
 }
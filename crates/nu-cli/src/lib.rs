@@ -5,7 +5,7 @@ mod syntax_highlight;
 mod validation;
 
 pub use completions::NuCompleter;
-pub use errors::report_error;
+pub use errors::{report_error, report_warnings};
 pub use prompt::NushellPrompt;
 pub use syntax_highlight::NuHighlighter;
 pub use validation::NuValidator;
@@ -11,6 +11,12 @@ impl Validator for NuValidator {
         let mut working_set = StateWorkingSet::new(&self.engine_state);
         let (_, err) = parse(&mut working_set, None, line.as_bytes(), false);
 
+        // Input that ends early fails in its last statement
+        let err = match err {
+            Some(ParseError::Statements { mut errors }) => errors.pop(),
+            err => err,
+        };
+
         if matches!(err, Some(ParseError::UnexpectedEof(..))) {
             ValidationResult::Incomplete
         } else {
@@ -1,3 +1,4 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::{
     ast::{Call, CellPath},
@@ -47,6 +48,7 @@ impl Command for SubCommand {
                         .to_vec(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a number to a nushell binary primitive",
@@ -55,6 +57,7 @@ impl Command for SubCommand {
                     val: i64::from(1).to_le_bytes().to_vec(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a boolean to a nushell binary primitive",
@@ -63,16 +66,19 @@ impl Command for SubCommand {
                     val: i64::from(1).to_le_bytes().to_vec(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a filesize to a nushell binary primitive",
                 example: "ls | where name == LICENSE | get size | into binary",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "convert a filepath to a nushell binary primitive",
                 example: "ls | where name == LICENSE | get name | path expand | into binary",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "convert a decimal to a nushell binary primitive",
@@ -81,6 +87,7 @@ impl Command for SubCommand {
                     val: 1.234f64.to_le_bytes().to_vec(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -95,25 +102,13 @@ fn into_binary(
     let head = call.head;
     let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
 
-    input.map(
-        move |v| {
-            if column_paths.is_empty() {
-                action(&v, head)
-            } else {
-                let mut ret = v;
-                for path in &column_paths {
-                    let r =
-                        ret.update_cell_path(&path.members, Box::new(move |old| action(old, head)));
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, head)
+        } else {
+            update_cells(v, row, &column_paths, head, move |old| action(old, head))
+        }
+    })
 }
 
 fn int_to_endian(n: i64) -> Vec<u8> {
@@ -0,0 +1,203 @@
+use crate::{map_rows, update_cells};
+use nu_engine::CallExt;
+use nu_protocol::{
+    ast::{Call, CellPath},
+    engine::{Command, EngineState, Stack},
+    Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value,
+};
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "into bool"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("into bool").rest(
+            "rest",
+            SyntaxShape::CellPath,
+            "column paths to convert to boolean (for table input)",
+        )
+    }
+
+    fn usage(&self) -> &str {
+        "Convert value to boolean"
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"Numbers are true unless they're zero, strings have to be "true" or "false" in any case,
+and nothing is false. Conditions, like the ones of `if` and `where`, only take booleans, so this
+is the way to use other values as one."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
+        into_bool(engine_state, stack, call, input)
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Convert numbers to booleans",
+                example: "[0 1 -2 0.0 0.5] | into bool",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::test_bool(false),
+                        Value::test_bool(true),
+                        Value::test_bool(true),
+                        Value::test_bool(false),
+                        Value::test_bool(true),
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Convert a string to a boolean",
+                example: "'TRUE' | into bool",
+                result: Some(Value::test_bool(true)),
+                ..Default::default()
+            },
+            Example {
+                description: "Convert a column of a table to booleans",
+                example: "[[name, done]; [spam, 'false'] [eggs, 1]] | into bool done",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::Record {
+                            cols: vec!["name".into(), "done".into()],
+                            vals: vec![Value::test_string("spam"), Value::test_bool(false)],
+                            span: Span::unknown(),
+                        },
+                        Value::Record {
+                            cols: vec!["name".into(), "done".into()],
+                            vals: vec![Value::test_string("eggs"), Value::test_bool(true)],
+                            span: Span::unknown(),
+                        },
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+fn into_bool(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    call: &Call,
+    input: PipelineData,
+) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
+    let head = call.head;
+    let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
+
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, head)
+        } else {
+            update_cells(v, row, &column_paths, head, move |old| action(old, head))
+        }
+    })
+}
+
+pub fn action(input: &Value, span: Span) -> Value {
+    match input {
+        Value::Bool { .. } | Value::Error { .. } => input.clone(),
+        Value::Int { val, .. } => Value::Bool {
+            val: *val != 0,
+            span,
+        },
+        Value::Float { val, .. } => Value::Bool {
+            val: *val != 0.0,
+            span,
+        },
+        Value::String { val, span: from } => match bool_from_string(val, *from) {
+            Ok(val) => Value::Bool { val, span },
+            Err(error) => Value::Error { error },
+        },
+        Value::Nothing { .. } => Value::Bool { val: false, span },
+        _ => Value::Error {
+            error: ShellError::UnsupportedInput("'into bool' for unsupported type".into(), span),
+        },
+    }
+}
+
+/// "true" or "false", in any case. Anything else is an error pointing at the string
+fn bool_from_string(a_string: &str, span: Span) -> Result<bool, ShellError> {
+    if a_string.eq_ignore_ascii_case("true") {
+        Ok(true)
+    } else if a_string.eq_ignore_ascii_case("false") {
+        Ok(false)
+    } else {
+        Err(ShellError::CantConvert(
+            "bool".into(),
+            format!("string '{}'", a_string),
+            span,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(SubCommand {})
+    }
+
+    #[test]
+    fn converts_each_type() {
+        let head = Span::new(1, 2);
+        let converted = |value: Value| match action(&value, head) {
+            Value::Bool { val, .. } => val,
+            other => panic!("expected a boolean, got {:?}", other),
+        };
+
+        assert!(converted(Value::test_bool(true)));
+        assert!(!converted(Value::test_int(0)));
+        assert!(converted(Value::test_int(-3)));
+        assert!(!converted(Value::Float {
+            val: 0.0,
+            span: Span::unknown(),
+        }));
+        assert!(converted(Value::Float {
+            val: 0.1,
+            span: Span::unknown(),
+        }));
+        assert!(converted(Value::test_string("True")));
+        assert!(!converted(Value::test_string("FALSE")));
+        assert!(!converted(Value::Nothing {
+            span: Span::unknown(),
+        }));
+    }
+
+    #[test]
+    fn other_strings_are_an_error_at_the_string() {
+        let written_at = Span::new(10, 15);
+        let value = Value::String {
+            val: "maybe".into(),
+            span: written_at,
+        };
+
+        match action(&value, Span::new(1, 2)) {
+            Value::Error {
+                error: ShellError::CantConvert(to, from, span),
+            } => {
+                assert_eq!(to, "bool");
+                assert_eq!(from, "string 'maybe'");
+                assert_eq!(span, written_at);
+            }
+            other => panic!("expected a conversion error, got {:?}", other),
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::{
     ast::{Call, CellPath},
@@ -41,6 +42,7 @@ impl Command for SubCommand {
                 description: "Convert string to filesize in table",
                 example: "[[bytes]; ['5'] [3.2] [4] [2kb]] | into filesize bytes",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Convert string to filesize",
@@ -49,6 +51,7 @@ impl Command for SubCommand {
                     val: 2,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Convert decimal to filesize",
@@ -57,6 +60,7 @@ impl Command for SubCommand {
                     val: 8,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Convert int to filesize",
@@ -65,6 +69,7 @@ impl Command for SubCommand {
                     val: 5,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Convert file size to filesize",
@@ -73,6 +78,7 @@ impl Command for SubCommand {
                     val: 4000,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -87,25 +93,13 @@ fn into_filesize(
     let head = call.head;
     let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
 
-    input.map(
-        move |v| {
-            if column_paths.is_empty() {
-                action(&v, head)
-            } else {
-                let mut ret = v;
-                for path in &column_paths {
-                    let r =
-                        ret.update_cell_path(&path.members, Box::new(move |old| action(old, head)));
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, head)
+        } else {
+            update_cells(v, row, &column_paths, head, move |old| action(old, head))
+        }
+    })
 }
 
 pub fn action(input: &Value, span: Span) -> Value {
@@ -1,3 +1,4 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::{
     ast::{Call, CellPath},
@@ -41,21 +42,25 @@ impl Command for SubCommand {
                 description: "Convert string to integer in table",
                 example: "echo [[num]; ['-5'] [4] [1.5]] | into int num",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Convert string to integer",
                 example: "'2' | into int",
                 result: Some(Value::test_int(2)),
+                ..Default::default()
             },
             Example {
                 description: "Convert decimal to integer",
                 example: "5.9 | into int",
                 result: Some(Value::test_int(5)),
+                ..Default::default()
             },
             Example {
                 description: "Convert decimal string to integer",
                 example: "'5.9' | into int",
                 result: Some(Value::test_int(5)),
+                ..Default::default()
             },
             Example {
                 description: "Convert file size to integer",
@@ -64,6 +69,7 @@ impl Command for SubCommand {
                     val: 4000,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Convert bool to integer",
@@ -72,6 +78,7 @@ impl Command for SubCommand {
                     vals: vec![Value::test_int(0), Value::test_int(1)],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -86,25 +93,13 @@ fn into_int(
     let head = call.head;
     let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
 
-    input.map(
-        move |v| {
-            if column_paths.is_empty() {
-                action(&v, head)
-            } else {
-                let mut ret = v;
-                for path in &column_paths {
-                    let r =
-                        ret.update_cell_path(&path.members, Box::new(move |old| action(old, head)));
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, head)
+        } else {
+            update_cells(v, row, &column_paths, head, move |old| action(old, head))
+        }
+    })
 }
 
 pub fn action(input: &Value, span: Span) -> Value {
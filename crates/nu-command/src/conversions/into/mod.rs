@@ -1,9 +1,11 @@
 mod binary;
+mod bool;
 mod command;
 mod filesize;
 mod int;
 mod string;
 
+pub use self::bool::SubCommand as IntoBool;
 pub use self::filesize::SubCommand as IntoFilesize;
 pub use binary::SubCommand as IntoBinary;
 pub use command::Into;
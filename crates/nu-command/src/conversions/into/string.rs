@@ -1,8 +1,9 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::{
     ast::{Call, CellPath},
     engine::{Command, EngineState, Stack},
-    Config, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value,
+    Config, Example, PipelineData, ShellError, Signature, Span, Spanned, SyntaxShape, Value,
 };
 
 // TODO num_format::SystemLocale once platform-specific dependencies are stable (see Cargo.toml)
@@ -54,6 +55,7 @@ impl Command for SubCommand {
                     val: "2".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert decimal to string",
@@ -62,6 +64,7 @@ impl Command for SubCommand {
                     val: "1.7".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert decimal to string and limit to 2 decimals",
@@ -70,6 +73,7 @@ impl Command for SubCommand {
                     val: "1.73".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "try to convert decimal to string and provide negative decimal points",
@@ -82,6 +86,7 @@ impl Command for SubCommand {
                 //         Span::unknown(),
                 //     ),
                 // }),
+                ..Default::default()
             },
             Example {
                 description: "convert decimal to string",
@@ -90,6 +95,7 @@ impl Command for SubCommand {
                     val: "4.3".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert string to string",
@@ -98,6 +104,7 @@ impl Command for SubCommand {
                     val: "1234".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert boolean to string",
@@ -106,21 +113,25 @@ impl Command for SubCommand {
                     val: "true".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert date to string",
                 example: "date now | into string",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "convert filepath to string",
                 example: "ls Cargo.toml | get name | into string",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "convert filesize to string",
                 example: "ls Cargo.toml | get size | into string",
                 result: None,
+                ..Default::default()
             },
         ]
     }
@@ -134,41 +145,31 @@ fn string_helper(
 ) -> Result<nu_protocol::PipelineData, ShellError> {
     let decimals = call.has_flag("decimals");
     let head = call.head;
-    let decimals_value: Option<i64> = call.get_flag(engine_state, stack, "decimals")?;
+    let decimals_value: Option<Spanned<i64>> =
+        call.get_flag_spanned(engine_state, stack, "decimals")?;
     let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
     let config = stack.get_config()?;
 
-    if decimals && decimals_value.is_some() && decimals_value.unwrap().is_negative() {
-        return Err(ShellError::UnsupportedInput(
-            "Cannot accept negative integers for decimals arguments".to_string(),
-            head,
-        ));
+    if let Some(Spanned { item, span }) = &decimals_value {
+        if item.is_negative() {
+            return Err(ShellError::UnsupportedInput(
+                "Cannot accept negative integers for decimals arguments".to_string(),
+                *span,
+            ));
+        }
     }
+    let decimals_value = decimals_value.map(|decimals| decimals.item);
 
-    input.map(
-        move |v| {
-            if column_paths.is_empty() {
-                action(&v, head, decimals, decimals_value, false, &config)
-            } else {
-                let mut ret = v;
-                for path in &column_paths {
-                    let config = config.clone();
-                    let r = ret.update_cell_path(
-                        &path.members,
-                        Box::new(move |old| {
-                            action(old, head, decimals, decimals_value, false, &config)
-                        }),
-                    );
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, head, decimals, decimals_value, false, &config)
+        } else {
+            let config = config.clone();
+            update_cells(v, row, &column_paths, head, move |old| {
+                action(old, head, decimals, decimals_value, false, &config)
+            })
+        }
+    })
 }
 
 pub fn action(
@@ -0,0 +1,353 @@
+use nu_engine::CallExt;
+use nu_parser::parse;
+use nu_protocol::ast::{Block, Call, Expr, Expression, Statement};
+use nu_protocol::engine::{Command, EngineState, Stack, StateWorkingSet};
+use nu_protocol::{
+    DeclId, Example, IntoPipelineData, PipelineData, ShellError, Signature, Span, Spanned,
+    SyntaxShape, Value, VarId,
+};
+
+#[derive(Clone)]
+pub struct Ast;
+
+impl Command for Ast {
+    fn name(&self) -> &str {
+        "ast"
+    }
+
+    fn usage(&self) -> &str {
+        "Parse nu source and show what the parser made of it, without running it."
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("ast").required("source", SyntaxShape::String, "the source code to parse")
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"The result is a record with the parsed pipelines, each a list of expressions with their kind,
+source, type, span and nested expressions. It also lists what parsing would add to the scope:
+the commands, variables and files, and the parse error, if any."#
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Show the kinds of the expressions in a pipeline",
+                example: "ast '1 + 2' | get pipelines.0.0.children.kind",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::test_string("int"),
+                        Value::test_string("operator"),
+                        Value::test_string("int"),
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "List the commands a script defines",
+                example: "ast 'def spam [] { 1 }' | get decls",
+                result: Some(Value::List {
+                    vals: vec![Value::test_string("spam")],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let source: Spanned<String> = call.req(engine_state, stack, 0)?;
+
+        // The delta is dropped, so nothing that's parsed ends up in the engine state
+        let mut working_set = StateWorkingSet::new(engine_state);
+        let (block, err) = parse(&mut working_set, Some("ast"), source.item.as_bytes(), false);
+
+        Ok(dump(
+            &working_set,
+            &block,
+            err.map(|err| err.to_string()),
+            call.head,
+        )
+        .into_pipeline_data())
+    }
+}
+
+/// The parsed block, and what parsing added to the working set
+fn dump(working_set: &StateWorkingSet, block: &Block, error: Option<String>, span: Span) -> Value {
+    let permanent_state = working_set.permanent_state;
+
+    let decls = (permanent_state.num_decls()..working_set.num_decls())
+        .map(|decl_id| Value::string(working_set.get_decl(DeclId::new(decl_id)).name(), span))
+        .collect();
+
+    let vars = (permanent_state.num_vars()..working_set.next_var_id().get())
+        .map(VarId::new)
+        .map(|var_id| {
+            // Only the variables of the outermost scope still have a name to find them by
+            let name = working_set
+                .delta
+                .scope
+                .iter()
+                .flat_map(|frame| frame.vars.iter())
+                .find(|(_, id)| **id == var_id)
+                .map_or(Value::Nothing { span }, |(name, _)| {
+                    Value::string(String::from_utf8_lossy(name), span)
+                });
+
+            Value::Record {
+                cols: vec!["id".into(), "name".into(), "type".into()],
+                vals: vec![
+                    Value::Int {
+                        val: var_id.get() as i64,
+                        span,
+                    },
+                    name,
+                    Value::string(working_set.get_variable(var_id).to_string(), span),
+                ],
+                span,
+            }
+        })
+        .collect();
+
+    let files = working_set
+        .files()
+        .skip(permanent_state.num_files())
+        .map(|file| Value::string(&file.name, span))
+        .collect();
+
+    Value::Record {
+        cols: vec![
+            "pipelines".into(),
+            "decls".into(),
+            "vars".into(),
+            "files".into(),
+            "error".into(),
+        ],
+        vals: vec![
+            block_to_value(working_set, block, span),
+            Value::List { vals: decls, span },
+            Value::List { vals: vars, span },
+            Value::List { vals: files, span },
+            error.map_or(Value::Nothing { span }, |error| Value::string(error, span)),
+        ],
+        span,
+    }
+}
+
+/// A block as a list of its pipelines, each a list of expressions
+fn block_to_value(working_set: &StateWorkingSet, block: &Block, span: Span) -> Value {
+    let pipelines = block
+        .stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Statement::Pipeline(pipeline) => Some(Value::List {
+                vals: pipeline
+                    .expressions
+                    .iter()
+                    .map(|expr| expression_to_value(working_set, expr, span))
+                    .collect(),
+                span,
+            }),
+            Statement::Declaration(_) => None,
+        })
+        .collect();
+
+    Value::List {
+        vals: pipelines,
+        span,
+    }
+}
+
+fn expression_to_value(working_set: &StateWorkingSet, expr: &Expression, span: Span) -> Value {
+    let children = match &expr.expr {
+        Expr::Range(from, next, to, _) => [from, next, to]
+            .iter()
+            .filter_map(|part| part.as_deref())
+            .map(|part| expression_to_value(working_set, part, span))
+            .collect(),
+        Expr::Call(call) => call
+            .positional
+            .iter()
+            .chain(call.named.iter().filter_map(|(_, arg)| arg.as_ref()))
+            .map(|arg| expression_to_value(working_set, arg, span))
+            .collect(),
+        Expr::ExternalCall(_, _, args) | Expr::List(args) => args
+            .iter()
+            .map(|arg| expression_to_value(working_set, arg, span))
+            .collect(),
+        Expr::BinaryOp(lhs, op, rhs) => [lhs, op, rhs]
+            .iter()
+            .map(|part| expression_to_value(working_set, part, span))
+            .collect(),
+        Expr::Table(headers, rows) => headers
+            .iter()
+            .chain(rows.iter().flatten())
+            .map(|cell| expression_to_value(working_set, cell, span))
+            .collect(),
+        Expr::Record(fields) => fields
+            .iter()
+            .flat_map(|(key, val)| [key, val])
+            .map(|part| expression_to_value(working_set, part, span))
+            .collect(),
+        Expr::RowCondition(_, inner)
+        | Expr::Keyword(_, _, inner)
+        | Expr::ValueWithUnit(inner, _) => vec![expression_to_value(working_set, inner, span)],
+        Expr::FullCellPath(path) => vec![expression_to_value(working_set, &path.head, span)],
+        Expr::Subexpression(block_id) | Expr::Block(block_id) => {
+            let block = working_set.get_block(*block_id);
+            block
+                .stmts
+                .iter()
+                .filter_map(|stmt| match stmt {
+                    Statement::Pipeline(pipeline) => Some(&pipeline.expressions),
+                    Statement::Declaration(_) => None,
+                })
+                .flatten()
+                .map(|inner| expression_to_value(working_set, inner, span))
+                .collect()
+        }
+        _ => vec![],
+    };
+
+    let source = working_set
+        .try_get_span_contents(expr.span)
+        .map(|source| String::from_utf8_lossy(source).to_string())
+        .unwrap_or_default();
+
+    Value::Record {
+        cols: vec![
+            "kind".into(),
+            "source".into(),
+            "type".into(),
+            "span".into(),
+            "children".into(),
+        ],
+        vals: vec![
+            Value::string(expr_kind(&expr.expr), span),
+            Value::string(source, span),
+            Value::string(expr.ty.to_string(), span),
+            Value::Record {
+                cols: vec!["start".into(), "end".into()],
+                vals: vec![
+                    Value::Int {
+                        val: expr.span.start as i64,
+                        span,
+                    },
+                    Value::Int {
+                        val: expr.span.end as i64,
+                        span,
+                    },
+                ],
+                span,
+            },
+            Value::List {
+                vals: children,
+                span,
+            },
+        ],
+        span,
+    }
+}
+
+fn expr_kind(expr: &Expr) -> &'static str {
+    match expr {
+        Expr::Bool(_) => "bool",
+        Expr::Int(_) => "int",
+        Expr::Float(_) => "float",
+        Expr::Range(..) => "range",
+        Expr::Var(_) => "var",
+        Expr::VarDecl(_) => "var_decl",
+        Expr::Call(_) => "call",
+        Expr::ExternalCall(..) => "external_call",
+        Expr::Operator(_) => "operator",
+        Expr::RowCondition(..) => "row_condition",
+        Expr::BinaryOp(..) => "binary_op",
+        Expr::Subexpression(_) => "subexpression",
+        Expr::Block(_) => "block",
+        Expr::List(_) => "list",
+        Expr::Table(..) => "table",
+        Expr::Record(_) => "record",
+        Expr::Keyword(..) => "keyword",
+        Expr::ValueWithUnit(..) => "value_with_unit",
+        Expr::Filepath(_) => "filepath",
+        Expr::GlobPattern(_) => "glob_pattern",
+        Expr::String(_) => "string",
+        Expr::CellPath(_) => "cell_path",
+        Expr::FullCellPath(_) => "full_cell_path",
+        Expr::Signature(_) => "signature",
+        Expr::Garbage => "garbage",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nu_engine::test_support::{column, items};
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Ast {})
+    }
+
+    #[test]
+    fn dumps_nested_expressions_with_their_spans() {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        let (block, err) = parse(&mut working_set, None, b"[1 (2 * 3)]", false);
+        assert!(err.is_none(), "parse error: {:?}", err);
+
+        let dump = dump(&working_set, &block, None, Span::unknown());
+        let path = &items(&items(column(&dump, "pipelines"))[0])[0];
+        assert_eq!(column(path, "kind"), &Value::test_string("full_cell_path"));
+
+        let list = &items(column(path, "children"))[0];
+        assert_eq!(column(list, "kind"), &Value::test_string("list"));
+
+        let path = &items(column(list, "children"))[1];
+        let subexpression = &items(column(path, "children"))[0];
+        assert_eq!(
+            column(subexpression, "kind"),
+            &Value::test_string("subexpression")
+        );
+        assert_eq!(
+            column(subexpression, "source"),
+            &Value::test_string("(2 * 3)")
+        );
+        assert_eq!(
+            column(column(subexpression, "span"), "start"),
+            &Value::test_int(3)
+        );
+
+        let product = &items(column(subexpression, "children"))[0];
+        assert_eq!(column(product, "kind"), &Value::test_string("binary_op"));
+        assert_eq!(column(product, "type"), &Value::test_string("int"));
+        let kinds: Vec<_> = items(column(product, "children"))
+            .iter()
+            .map(|operand| column(operand, "kind").clone())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                Value::test_string("int"),
+                Value::test_string("operator"),
+                Value::test_string("int"),
+            ]
+        );
+        assert_eq!(
+            column(&dump, "files"),
+            &Value::List {
+                vals: vec![Value::test_string("source")],
+                span: Span::unknown(),
+            }
+        );
+    }
+}
@@ -53,11 +53,13 @@ impl Command for Echo {
                 description: "Put a hello message in the pipeline",
                 example: "echo 'hello'",
                 result: Some(Value::test_string("hello")),
+                ..Default::default()
             },
             Example {
                 description: "Print the value of the special '$nu' variable",
                 example: "echo $nu",
                 result: None,
+                ..Default::default()
             },
         ]
     }
@@ -110,6 +110,7 @@ impl Command for For {
                     ],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Work with elements of a range",
@@ -122,6 +123,7 @@ impl Command for For {
                     ],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             // FIXME? Numbered `for` is kinda strange, but was supported in previous nushell
             // Example {
@@ -1,11 +1,12 @@
 use nu_protocol::{
     ast::Call,
-    engine::{Command, EngineState, Stack},
+    engine::{Command, EngineState, Stack, StateWorkingSet},
     span, Example, IntoInterruptiblePipelineData, IntoPipelineData, PipelineData, ShellError,
-    Signature, Spanned, SyntaxShape, Value,
+    Signature, Span, Spanned, SyntaxShape, Value,
 };
 
-use nu_engine::{get_full_help, CallExt};
+use nu_engine::{get_full_help, write_markdown_docs, CallExt};
+use std::path::Path;
 
 #[derive(Clone)]
 pub struct Help;
@@ -28,6 +29,17 @@ impl Command for Help {
                 "string to find in command usage",
                 Some('f'),
             )
+            .switch(
+                "examples",
+                "list the command's examples as a table, with their results",
+                Some('e'),
+            )
+            .named(
+                "generate-docs",
+                SyntaxShape::Filepath,
+                "write markdown documentation for every command into the given directory",
+                None,
+            )
     }
 
     fn usage(&self) -> &str {
@@ -50,26 +62,43 @@ impl Command for Help {
                 description: "show all commands and sub-commands",
                 example: "help commands",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "generate documentation",
                 example: "help generate_docs",
                 result: None,
+                ..Default::default()
+            },
+            Example {
+                description: "write markdown documentation for every command into a directory",
+                example: "help --generate-docs docs/commands",
+                result: None,
+                ..Default::default()
             },
             Example {
                 description: "show help for single command",
                 example: "help match",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "show help for single sub-command",
                 example: "help str lpad",
                 result: None,
+                ..Default::default()
+            },
+            Example {
+                description: "list the examples of a command with their results",
+                example: "help str lpad --examples",
+                result: None,
+                ..Default::default()
             },
             Example {
                 description: "search for string in command usage",
                 example: "help --find char",
                 result: None,
+                ..Default::default()
             },
         ]
     }
@@ -83,8 +112,31 @@ fn help(
     let head = call.head;
     let find: Option<Spanned<String>> = call.get_flag(engine_state, stack, "find")?;
     let rest: Vec<Spanned<String>> = call.rest(engine_state, stack, 0)?;
+    let examples = call.has_flag("examples");
+
+    let generate_docs: Option<Spanned<String>> =
+        call.get_flag(engine_state, stack, "generate-docs")?;
+
+    if let Some(dir) = generate_docs {
+        let config = stack.get_config()?;
+        let written =
+            write_markdown_docs(engine_state, Path::new(&dir.item), &config).map_err(|e| {
+                ShellError::CreateNotPossible(
+                    format!("failed to write documentation: {}", e),
+                    dir.span,
+                )
+            })?;
+
+        return Ok(written
+            .into_iter()
+            .map(move |path| Value::String {
+                val: path.to_string_lossy().to_string(),
+                span: head,
+            })
+            .into_pipeline_data(engine_state.ctrlc.clone()));
+    }
 
-    let full_commands = engine_state.get_signatures_with_examples();
+    let full_commands = engine_state.get_signatures_with_examples(true);
 
     if let Some(f) = find {
         let search_string = f.item;
@@ -130,6 +182,11 @@ fn help(
         let mut found_cmds_vec = Vec::new();
 
         if rest[0].item == "commands" {
+            // Grouped by category, and sorted by name within each
+            let mut full_commands = full_commands;
+            full_commands
+                .sort_by(|(a, _), (b, _)| (a.category, &a.name).cmp(&(b.category, &b.name)));
+
             for cmd in full_commands {
                 let mut cols = vec![];
                 let mut vals = vec![];
@@ -144,6 +201,12 @@ fn help(
                     span: head,
                 });
 
+                cols.push("category".into());
+                vals.push(Value::String {
+                    val: cmd.0.category.to_string(),
+                    span: head,
+                });
+
                 cols.push("usage".into());
                 vals.push(Value::String { val: c, span: head });
 
@@ -171,6 +234,18 @@ fn help(
                 name.push_str(&r.item);
             }
 
+            if examples {
+                return match full_commands.into_iter().find(|(sig, _)| sig.name == name) {
+                    Some((_, examples)) => Ok(examples_to_values(examples, head)
+                        .into_iter()
+                        .into_pipeline_data(engine_state.ctrlc.clone())),
+                    None => Err(ShellError::CommandNotFound(span(&[
+                        rest[0].span,
+                        rest[rest.len() - 1].span,
+                    ]))),
+                };
+            }
+
             for cmd in full_commands {
                 if cmd.0.name == name {
                     let help = get_full_help(&cmd.0, &cmd.1, engine_state);
@@ -178,6 +253,13 @@ fn help(
                 }
             }
 
+            if output.is_empty() {
+                let working_set = StateWorkingSet::new(engine_state);
+                if let Some(template) = working_set.find_alias_template(name.as_bytes()) {
+                    output = format!("Alias for:\n  {}\n", template);
+                }
+            }
+
             if !output.is_empty() {
                 Ok(Value::String {
                     val: output,
@@ -366,6 +448,23 @@ You can also learn more at https://www.nushell.sh/book/"#;
     }
 }
 
+/// A command's examples as rows of their description, source text and result, the result being
+/// nothing for examples that don't give one
+fn examples_to_values(examples: Vec<Example>, span: Span) -> Vec<Value> {
+    examples
+        .into_iter()
+        .map(|example| Value::Record {
+            cols: vec!["description".into(), "example".into(), "result".into()],
+            vals: vec![
+                Value::string(example.description, span),
+                Value::string(example.example, span),
+                example.result.unwrap_or(Value::Nothing { span }),
+            ],
+            span,
+        })
+        .collect()
+}
+
 /*
 fn for_spec(name: &str, ty: &str, required: bool, tag: impl Into<Tag>) -> Value {
     let tag = tag.into();
@@ -1,7 +1,7 @@
 use nu_engine::{eval_block, eval_expression};
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{IntoPipelineData, PipelineData, ShellError, Signature, SyntaxShape, Value};
+use nu_protocol::{IntoPipelineData, PipelineData, Signature, SyntaxShape};
 
 #[derive(Clone)]
 pub struct If;
@@ -39,36 +39,26 @@ impl Command for If {
             .expect("internal error: expected block");
         let else_case = call.positional.get(2);
 
-        let result = eval_expression(engine_state, stack, cond)?;
-        match &result {
-            Value::Bool { val, .. } => {
-                if *val {
-                    let block = engine_state.get_block(then_block);
+        // Only a boolean will do, see `Value::as_bool`
+        let cond = eval_expression(engine_state, stack, cond)?.as_bool()?;
+        if cond {
+            let block = engine_state.get_block(then_block);
+            let mut stack = stack.collect_captures(&block.captures);
+            eval_block(engine_state, &mut stack, block, input)
+        } else if let Some(else_case) = else_case {
+            if let Some(else_expr) = else_case.as_keyword() {
+                if let Some(block_id) = else_expr.as_block() {
+                    let block = engine_state.get_block(block_id);
                     let mut stack = stack.collect_captures(&block.captures);
                     eval_block(engine_state, &mut stack, block, input)
-                } else if let Some(else_case) = else_case {
-                    if let Some(else_expr) = else_case.as_keyword() {
-                        if let Some(block_id) = else_expr.as_block() {
-                            let block = engine_state.get_block(block_id);
-                            let mut stack = stack.collect_captures(&block.captures);
-                            eval_block(engine_state, &mut stack, block, input)
-                        } else {
-                            eval_expression(engine_state, stack, else_expr)
-                                .map(|x| x.into_pipeline_data())
-                        }
-                    } else {
-                        eval_expression(engine_state, stack, else_case)
-                            .map(|x| x.into_pipeline_data())
-                    }
                 } else {
-                    Ok(PipelineData::new(call.head))
+                    eval_expression(engine_state, stack, else_expr).map(|x| x.into_pipeline_data())
                 }
+            } else {
+                eval_expression(engine_state, stack, else_case).map(|x| x.into_pipeline_data())
             }
-            x => Err(ShellError::CantConvert(
-                "bool".into(),
-                x.get_type().to_string(),
-                result.span()?,
-            )),
+        } else {
+            Ok(PipelineData::new(call.head))
         }
     }
 }
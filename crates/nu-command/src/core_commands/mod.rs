@@ -1,8 +1,11 @@
 mod alias;
+mod ast;
 mod def;
 mod do_;
 mod echo;
+mod export_alias;
 mod export_def;
+mod export_env;
 mod for_;
 mod help;
 mod hide;
@@ -13,10 +16,13 @@ mod source;
 mod use_;
 
 pub use alias::Alias;
+pub use ast::Ast;
 pub use def::Def;
 pub use do_::Do;
 pub use echo::Echo;
+pub use export_alias::ExportAlias;
 pub use export_def::ExportDef;
+pub use export_env::ExportEnv;
 pub use for_::For;
 pub use help::Help;
 pub use hide::Hide;
@@ -1,7 +1,7 @@
 use nu_engine::{eval_block, CallExt};
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{PipelineData, ShellError, Signature, SyntaxShape};
+use nu_protocol::{BlockId, PipelineData, ShellError, Signature, SyntaxShape};
 
 /// Source a file for environment variables.
 #[derive(Clone)]
@@ -35,7 +35,9 @@ impl Command for Source {
         // it is put here by the parser
         let block_id: i64 = call.req(engine_state, stack, 1)?;
 
-        let block = engine_state.get_block(block_id as usize).clone();
+        let block = engine_state
+            .get_block(BlockId::new(block_id as usize))
+            .clone();
         eval_block(engine_state, stack, &block, input)
     }
 }
@@ -52,6 +52,7 @@ impl Command for SubCommand {
                     val: Local::now().format("%Y-%m-%d").to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Format a given date using the given format string.",
@@ -60,6 +61,7 @@ impl Command for SubCommand {
                     val: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Format a given date using the given format string.",
@@ -68,6 +70,7 @@ impl Command for SubCommand {
                     val: "2021-10-22".into(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
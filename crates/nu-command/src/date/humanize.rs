@@ -40,11 +40,13 @@ impl Command for SubCommand {
                     val: "now".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Print a 'humanized' format for the date, relative to now.",
                 example: r#""2021-10-22 20:00:12 +01:00" | date humanize"#,
                 result: None,
+                ..Default::default()
             },
         ]
     }
@@ -6,7 +6,7 @@ mod now;
 mod parser;
 mod to_table;
 mod to_timezone;
-mod utils;
+pub(crate) mod utils;
 
 pub use command::Date;
 pub use format::SubCommand as DateFormat;
@@ -37,11 +37,13 @@ impl Command for SubCommand {
                 description: "Print the date in a structured table.",
                 example: "date to-table",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Print the date in a structured table.",
                 example: "date now | date to-table",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Print the date in a structured table.",
@@ -74,6 +76,7 @@ impl Command for SubCommand {
                         span,
                     })
                 },
+                ..Default::default()
             },
         ]
     }
@@ -57,16 +57,19 @@ impl Command for SubCommand {
                 description: "Get the current date in UTC+05:00",
                 example: "date now | date to-timezone +0500",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Get the current local date",
                 example: "date now | date to-timezone local",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Get the current date in Hawaii",
                 example: "date now | date to-timezone US/Hawaii",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Get the current date in Hawaii",
@@ -83,6 +86,7 @@ impl Command for SubCommand {
                         span: Span::unknown(),
                     })
                 },
+                ..Default::default()
             },
         ]
     }
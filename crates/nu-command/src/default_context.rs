@@ -23,10 +23,15 @@ pub fn create_default_context() -> EngineState {
         // TODO: sort default context items categorically
         bind_command!(
             Alias,
+            Ansi,
+            AnsiGradient,
+            AnsiLink,
+            Ast,
             Benchmark,
             BuildString,
             Cd,
             Collect,
+            Columns,
             Cp,
             Date,
             DateFormat,
@@ -36,14 +41,21 @@ pub fn create_default_context() -> EngineState {
             DateToTable,
             DateToTimezone,
             Def,
+            Diff,
             Do,
+            Du,
             Each,
+            EachWhile,
             Echo,
+            ExportAlias,
             ExportDef,
+            ExportEnv,
             External,
+            Fetch,
             First,
             For,
             Format,
+            FormatFilesize,
             From,
             FromCsv,
             FromJson,
@@ -54,11 +66,14 @@ pub fn create_default_context() -> EngineState {
             FromEml,
             Get,
             Griddle,
+            GroupBy,
             Help,
             Hide,
             If,
+            Inspect,
             Into,
             IntoBinary,
+            IntoBool,
             IntoFilesize,
             IntoInt,
             IntoString,
@@ -84,28 +99,47 @@ pub fn create_default_context() -> EngineState {
             MathStddev,
             MathSum,
             MathVariance,
+            Merge,
             Mkdir,
             Module,
             Mv,
+            Page,
             ParEach,
+            PathCommand,
+            PathJoin,
+            Post,
             Ps,
             Range,
+            Reject,
             Reverse,
             Rm,
+            Save,
             Select,
             Shuffle,
+            Skip,
+            SkipWhile,
             Size,
+            Sort,
+            SortBy,
+            Stash,
+            StashGet,
+            TakeWhile,
+            StashList,
+            StashRemove,
+            StashSet,
             Split,
             SplitChars,
             SplitColumn,
             SplitRow,
             Str,
             StrCamelCase,
+            StrCase,
             StrCapitalize,
             StrCollect,
             StrContains,
             StrDowncase,
             StrEndswith,
+            StrStartsWith,
             StrIndexOf,
             StrLength,
             StrFindReplace,
@@ -115,13 +149,18 @@ pub fn create_default_context() -> EngineState {
             StrSnakeCase,
             StrLpad,
             StrRpad,
+            StrTrim,
+            StrUpcase,
             Sys,
             Table,
+            Timeout,
             To,
             ToJson,
+            ToYaml,
             Touch,
             Use,
             Update,
+            Values,
             Where,
             WithEnv,
             Wrap,
@@ -131,6 +170,9 @@ pub fn create_default_context() -> EngineState {
         #[cfg(feature = "plugin")]
         bind_command!(Register);
 
+        #[cfg(feature = "spreadsheets")]
+        bind_command!(FromOds, FromXlsx);
+
         // This is a WIP proof of concept
         bind_command!(ListGitBranches, Git, GitCheckout, Source);
 
@@ -144,3 +186,23 @@ pub fn create_default_context() -> EngineState {
 
     engine_state
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn flag_constraints_name_existing_flags() {
+        let engine_state = create_default_context();
+
+        for signature in engine_state.get_signatures(false) {
+            let unknown = signature.unknown_constraint_flags();
+            assert!(
+                unknown.is_empty(),
+                "`{}` has flag constraints on flags it doesn't have: {:?}",
+                signature.name,
+                unknown
+            );
+        }
+    }
+}
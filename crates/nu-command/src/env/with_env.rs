@@ -1,7 +1,4 @@
-use std::{
-    collections::HashMap,
-    convert::{TryFrom, TryInto},
-};
+use std::collections::HashMap;
 
 use nu_engine::{eval_block, CallExt};
 use nu_protocol::{
@@ -52,50 +49,44 @@ impl Command for WithEnv {
                 description: "Set the MYENV environment variable",
                 example: r#"with-env [MYENV "my env value"] { $nu.env.MYENV }"#,
                 result: Some(Value::test_string("my env value")),
+                ..Default::default()
             },
             Example {
                 description: "Set by primitive value list",
                 example: r#"with-env [X Y W Z] { $nu.env.X }"#,
                 result: Some(Value::test_string("Y")),
+                ..Default::default()
             },
             Example {
                 description: "Set by single row table",
                 example: r#"with-env [[X W]; [Y Z]] { $nu.env.W }"#,
                 result: Some(Value::test_string("Z")),
+                ..Default::default()
             },
             Example {
                 description: "Set by row(e.g. `open x.json` or `from json`)",
                 example: r#"echo '{"X":"Y","W":"Z"}'|from json|with-env $it { echo $nu.env.X $nu.env.W }"#,
                 result: None,
+                ..Default::default()
             },
         ]
     }
 }
 
+/// What a variable is set to. Values are kept as they are, and only converted to strings for the
+/// external commands the block runs, see `env_conversions` in the config
 #[derive(Debug, Clone)]
 pub enum EnvVar {
-    Proper(String),
+    Proper(Value),
     Nothing,
 }
 
-impl TryFrom<&Value> for EnvVar {
-    type Error = ShellError;
-
-    fn try_from(value: &Value) -> Result<Self, Self::Error> {
-        if matches!(value, Value::Nothing { .. }) {
-            Ok(EnvVar::Nothing)
-        } else if let Ok(s) = value.as_string() {
-            if s.is_empty() {
-                Ok(EnvVar::Nothing)
-            } else {
-                Ok(EnvVar::Proper(s))
-            }
-        } else {
-            Err(ShellError::CantConvert(
-                "string".into(),
-                value.get_type().to_string(),
-                value.span()?,
-            ))
+impl From<&Value> for EnvVar {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Nothing { .. } => EnvVar::Nothing,
+            Value::String { val, .. } if val.is_empty() => EnvVar::Nothing,
+            value => EnvVar::Proper(value.clone()),
         }
     }
 }
@@ -124,7 +115,7 @@ fn with_env(
                 match &table[0] {
                     Value::Record { cols, vals, .. } => {
                         for (k, v) in cols.iter().zip(vals.iter()) {
-                            env.insert(k.to_string(), v.try_into()?);
+                            env.insert(k.to_string(), v.into());
                         }
                     }
                     x => {
@@ -139,7 +130,7 @@ fn with_env(
                 // primitive values([X Y W Z])
                 for row in table.chunks(2) {
                     if row.len() == 2 {
-                        env.insert(row[0].as_string()?, (&row[1]).try_into()?);
+                        env.insert(row[0].as_string()?, (&row[1]).into());
                     }
                 }
             }
@@ -147,7 +138,7 @@ fn with_env(
         // when get object by `open x.json` or `from json`
         Value::Record { cols, vals, .. } => {
             for (k, v) in cols.iter().zip(vals) {
-                env.insert(k.clone(), v.try_into()?);
+                env.insert(k.clone(), v.into());
             }
         }
         x => {
@@ -164,8 +155,8 @@ fn with_env(
             EnvVar::Nothing => {
                 stack.env_vars.remove(&k);
             }
-            EnvVar::Proper(s) => {
-                stack.env_vars.insert(k, s);
+            EnvVar::Proper(value) => {
+                stack.env_vars.insert(k, value);
             }
         }
     }
@@ -177,10 +168,84 @@ fn with_env(
 mod test {
     use super::*;
 
+    use crate::{To, ToJson};
+    use nu_engine::env_vars_as_strings;
+    use nu_engine::test_support::{engine_state_with, eval_source, stack_with_config};
+    use nu_protocol::{IntoPipelineData, Span, CONFIG_VARIABLE_ID};
+
+    /// Gives the variable as an external command would get it in its environment
+    #[derive(Clone)]
+    struct EchoEnv;
+
+    impl Command for EchoEnv {
+        fn name(&self) -> &str {
+            "echo_env"
+        }
+
+        fn signature(&self) -> Signature {
+            Signature::build("echo_env").required("name", SyntaxShape::String, "the variable")
+        }
+
+        fn usage(&self) -> &str {
+            "Echo an environment variable as external commands get it."
+        }
+
+        fn run(
+            &self,
+            engine_state: &EngineState,
+            stack: &mut Stack,
+            call: &Call,
+            _input: PipelineData,
+        ) -> Result<PipelineData, ShellError> {
+            let name: String = call.req(engine_state, stack, 0)?;
+            let env = env_vars_as_strings(engine_state, stack);
+
+            Ok(Value::String {
+                val: env.get(&name).cloned().unwrap_or_default(),
+                span: call.head,
+            }
+            .into_pipeline_data())
+        }
+    }
+
     #[test]
     fn test_examples() {
         use crate::test_examples;
 
         test_examples(WithEnv {})
     }
+
+    #[test]
+    fn structured_values_are_converted_for_externals() {
+        let mut engine_state = engine_state_with(vec![
+            Box::new(WithEnv),
+            Box::new(EchoEnv),
+            Box::new(To),
+            Box::new(ToJson),
+        ]);
+        let mut stack = stack_with_config();
+
+        let config = eval_source(
+            &mut engine_state,
+            &mut stack,
+            "{env_conversions: {FOO: {to_string: { to json }}}}",
+            PipelineData::new(Span::unknown()),
+        )
+        .expect("the config evaluates")
+        .into_value(Span::unknown());
+        stack.add_var(CONFIG_VARIABLE_ID, config);
+
+        let foo = eval_source(
+            &mut engine_state,
+            &mut stack,
+            "with-env {FOO: {a: [4, 5]}} { echo_env FOO }",
+            PipelineData::new(Span::unknown()),
+        )
+        .expect("the block runs")
+        .into_value(Span::unknown());
+
+        let foo = foo.as_string().expect("externals get strings");
+        let foo: String = foo.split_whitespace().collect();
+        assert_eq!(foo, r#"{"a":[4,5]}"#);
+    }
 }
@@ -1,16 +1,71 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use miette::Diagnostic;
 use nu_engine::eval_block;
+use nu_engine::test_support::stack_with_config;
 use nu_parser::parse;
 use nu_protocol::{
-    engine::{Command, EngineState, Stack, StateWorkingSet},
-    PipelineData, Span, Value, CONFIG_VARIABLE_ID,
+    engine::{Command, EngineState, StateWorkingSet},
+    FixtureEntry, PipelineData, ShellError, Span, Value,
 };
 
 use crate::To;
 
-use super::{Date, From, Into, Math, Split, Str};
+use super::{Date, Def, From, Get, If, Into, Let, Math, Split, Str};
+
+/// A uniquely named scratch directory holding an example's fixture.
+/// It is removed again when dropped, so a failing (panicking) example still cleans up after itself
+struct Sandbox {
+    path: PathBuf,
+}
+
+impl Sandbox {
+    fn new(name: &str, entries: &[FixtureEntry]) -> Sandbox {
+        // Tests run in parallel threads of the same process, so the pid alone isn't unique enough
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let sandbox = Sandbox {
+            path: std::env::temp_dir().join(format!(
+                "nu-example-{}-{}-{}",
+                name.replace(' ', "_"),
+                std::process::id(),
+                NEXT_ID.fetch_add(1, Ordering::SeqCst)
+            )),
+        };
+
+        // Leftovers from an earlier, aborted run with the same pid
+        let _ = std::fs::remove_dir_all(&sandbox.path);
+        std::fs::create_dir_all(&sandbox.path).expect("could not create example sandbox");
+
+        for entry in entries {
+            match entry {
+                FixtureEntry::Dir(dir) => std::fs::create_dir_all(sandbox.path.join(dir))
+                    .expect("could not create fixture directory"),
+                FixtureEntry::File { path, contents } => {
+                    let path = sandbox.path.join(path);
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent)
+                            .expect("could not create fixture directory");
+                    }
+                    std::fs::write(path, contents).expect("could not create fixture file");
+                }
+            }
+        }
+
+        sandbox
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
 
 pub fn test_examples(cmd: impl Command + 'static) {
     let examples = cmd.examples();
+    let name = cmd.name().to_string();
     let mut engine_state = Box::new(EngineState::new());
 
     let delta = {
@@ -24,6 +79,10 @@ pub fn test_examples(cmd: impl Command + 'static) {
         working_set.add_decl(Box::new(Split));
         working_set.add_decl(Box::new(Math));
         working_set.add_decl(Box::new(Date));
+        working_set.add_decl(Box::new(Get));
+        working_set.add_decl(Box::new(If));
+        working_set.add_decl(Box::new(Let));
+        working_set.add_decl(Box::new(Def));
 
         use super::Echo;
         working_set.add_decl(Box::new(Echo));
@@ -55,43 +114,113 @@ pub fn test_examples(cmd: impl Command + 'static) {
 
         engine_state.merge_delta(delta);
 
-        let mut stack = Stack::new();
+        // Examples that touch the filesystem run inside their own scratch directory
+        let sandbox = example.fixture.map(|entries| Sandbox::new(&name, entries));
 
-        // Set up our initial config to start from
-        stack.vars.insert(
-            CONFIG_VARIABLE_ID,
-            Value::Record {
-                cols: vec![],
-                vals: vec![],
-                span: Span::unknown(),
-            },
-        );
+        // Start from an empty config
+        let mut stack = stack_with_config();
 
-        match eval_block(
+        if let Some(sandbox) = &sandbox {
+            stack.add_env_var(
+                "PWD".into(),
+                Value::string(sandbox.path.to_string_lossy(), Span::unknown()),
+            );
+        }
+
+        let result = match eval_block(
             &engine_state,
             &mut stack,
             &block,
             PipelineData::new(Span::unknown()),
         ) {
+            // Streams are collected, so examples give the rows they expect as a list
+            Ok(result) => result.into_value(Span::unknown()),
+            Err(error) if matches!(example.result, Some(Value::Error { .. })) => {
+                Value::Error { error }
+            }
             Err(err) => panic!("test eval error in `{}`: {:?}", example.example, err),
-            Ok(result) => {
-                let result = result.into_value(Span::unknown());
-                println!("input: {}", example.example);
-                println!("result: {:?}", result);
-                println!("done: {:?}", start.elapsed());
-
-                // Note. Value implements PartialEq for Bool, Int, Float, String and Block
-                // If the command you are testing requires to compare another case, then
-                // you need to define its equality in the Value struct
-                if let Some(expected) = example.result {
-                    if result != expected {
-                        panic!(
-                            "the example result is different to expected value: {:?} != {:?}",
-                            result, expected
-                        )
-                    }
-                }
+        };
+        println!("input: {}", example.example);
+        println!("result: {:?}", result);
+        println!("done: {:?}", start.elapsed());
+
+        // Note. Value implements PartialEq for Bool, Int, Float, String and Block
+        // If the command you are testing requires to compare another case, then
+        // you need to define its equality in the Value struct
+        if let Some(expected) = example.result {
+            if !matches_expected(&result, &expected) {
+                panic!(
+                    "the example result is different to expected value: {:?} != {:?}",
+                    result, expected
+                )
             }
         }
     }
 }
+
+/// Whether an example's result is the one it expects. Lists and records are compared item by
+/// item, so that expected errors can be anywhere inside them
+fn matches_expected(result: &Value, expected: &Value) -> bool {
+    match (result, expected) {
+        (Value::Error { error }, Value::Error { error: expected }) => {
+            matches_expected_error(error, expected)
+        }
+        (Value::List { vals, .. }, Value::List { vals: expected, .. }) => {
+            vals.len() == expected.len()
+                && vals
+                    .iter()
+                    .zip(expected)
+                    .all(|(val, expected)| matches_expected(val, expected))
+        }
+        (
+            Value::Record { cols, vals, .. },
+            Value::Record {
+                cols: expected_cols,
+                vals: expected_vals,
+                ..
+            },
+        ) => {
+            cols == expected_cols
+                && vals
+                    .iter()
+                    .zip(expected_vals)
+                    .all(|(val, expected)| matches_expected(val, expected))
+        }
+        (result, expected) => result == expected,
+    }
+}
+
+/// Expected errors match errors of the same kind with labels containing the expected labels, so
+/// examples only need to spell out the telling part of a message and no spans
+fn matches_expected_error(error: &ShellError, expected: &ShellError) -> bool {
+    std::mem::discriminant(error) == std::mem::discriminant(expected)
+        && matches_expected_diagnostic(error, expected)
+}
+
+/// The same comparison for any diagnostic, by code. The related diagnostics expected, like the
+/// cause of a failed row, each have to match one of the related diagnostics given
+fn matches_expected_diagnostic(error: &dyn Diagnostic, expected: &dyn Diagnostic) -> bool {
+    let code = |error: &dyn Diagnostic| error.code().map(|code| code.to_string());
+    let labels = |error: &dyn Diagnostic| -> Vec<String> {
+        error
+            .labels()
+            .into_iter()
+            .flatten()
+            .filter_map(|label| label.label().map(String::from))
+            .collect()
+    };
+    let error_labels = labels(error);
+    let error_related: Vec<&dyn Diagnostic> = error.related().into_iter().flatten().collect();
+
+    code(error) == code(expected)
+        && labels(expected).iter().all(|expected| {
+            error_labels
+                .iter()
+                .any(|label| label.contains(expected.as_str()))
+        })
+        && expected.related().into_iter().flatten().all(|expected| {
+            error_related
+                .iter()
+                .any(|error| matches_expected_diagnostic(*error, expected))
+        })
+}
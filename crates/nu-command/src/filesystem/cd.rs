@@ -1,7 +1,7 @@
 use nu_engine::CallExt;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{PipelineData, Signature, SyntaxShape};
+use nu_protocol::{Category, PipelineData, ShellError, Signature, Spanned, SyntaxShape, Value};
 
 #[derive(Clone)]
 pub struct Cd;
@@ -16,7 +16,9 @@ impl Command for Cd {
     }
 
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("cd").optional("path", SyntaxShape::Filepath, "the path to change to")
+        Signature::build("cd")
+            .optional("path", SyntaxShape::Filepath, "the path to change to")
+            .category(Category::FileSystem)
     }
 
     fn run(
@@ -26,23 +28,60 @@ impl Command for Cd {
         call: &Call,
         _input: PipelineData,
     ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
-        let path: Option<String> = call.opt(engine_state, stack, 0)?;
+        let path: Option<Spanned<String>> = call.opt_spanned(engine_state, stack, 0)?;
 
-        let path = match path {
+        let (path, span) = match path {
             Some(path) => {
-                let path = nu_path::expand_path(path);
-                path.to_string_lossy().to_string()
+                let span = path.span;
+                let path = nu_path::expand_path(path.item);
+                (path.to_string_lossy().to_string(), span)
             }
             None => {
                 let path = nu_path::expand_tilde("~");
-                path.to_string_lossy().to_string()
+                (path.to_string_lossy().to_string(), call.head)
             }
         };
-        let _ = std::env::set_current_dir(&path);
+        if let Err(err) = std::env::set_current_dir(&path) {
+            return Err(ShellError::DirectoryNotFoundCustom(
+                format!("can't change to it: {}", err),
+                span,
+            ));
+        }
 
         //FIXME: this only changes the current scope, but instead this environment variable
         //should probably be a block that loads the information from the state in the overlay
-        stack.add_env_var("PWD".into(), path);
+        stack.add_env_var("PWD".into(), Value::string(path, call.head));
         Ok(PipelineData::new(call.head))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nu_protocol::ast::{Expr, Expression};
+    use nu_protocol::{Span, Type};
+
+    #[test]
+    fn missing_directories_are_an_error_at_the_path() {
+        let path_span = Span::new(3, 30);
+        let mut call = Call::new();
+        call.head = Span::new(0, 2);
+        call.positional.push(Expression {
+            expr: Expr::Filepath("/no/such/directory/to/cd/to".into()),
+            span: path_span,
+            ty: Type::String,
+            custom_completion: None,
+        });
+
+        let result = Cd.run(
+            &EngineState::new(),
+            &mut Stack::new(),
+            &call,
+            PipelineData::new(call.head),
+        );
+        assert!(matches!(
+            result,
+            Err(ShellError::DirectoryNotFoundCustom(_, span)) if span == path_span
+        ));
+    }
+}
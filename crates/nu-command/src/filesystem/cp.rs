@@ -1,12 +1,15 @@
-use std::env::current_dir;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use super::util::get_interactive_confirmation;
-use nu_engine::CallExt;
+use super::util::{
+    check_interactive, destination_path, glob_arg, report_action, report_skipped, Report,
+};
+use nu_engine::{current_dir, CallExt};
 use nu_path::canonicalize_with;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{PipelineData, ShellError, Signature, SyntaxShape};
+use nu_protocol::{
+    Category, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, SyntaxShape,
+};
 
 use crate::filesystem::util::FileStructure;
 
@@ -33,7 +36,22 @@ impl Command for Cp {
                 Some('r'),
             )
             .switch("force", "suppress error when no file", Some('f'))
-            .switch("interactive", "ask user to confirm action", Some('i'))
+            .switch(
+                "interactive",
+                "ask before overwriting existing files",
+                Some('i'),
+            )
+            .switch(
+                "verbose",
+                "report each path as a {path, action, status} row",
+                Some('v'),
+            )
+            .switch(
+                "continue-on-error",
+                "report failures as rows instead of stopping",
+                None,
+            )
+            .category(Category::FileSystem)
     }
 
     fn run(
@@ -47,13 +65,18 @@ impl Command for Cp {
         let destination: String = call.req(engine_state, stack, 1)?;
         let interactive = call.has_flag("interactive");
         let force = call.has_flag("force");
-
-        let path: PathBuf = current_dir().unwrap();
-        let source = path.join(source.as_str());
+        let report = if call.has_flag("verbose") {
+            Report::Rows
+        } else {
+            Report::Quiet
+        };
+        let continue_on_error = call.has_flag("continue-on-error");
+
+        let path = current_dir(engine_state, stack)?;
         let destination = path.join(destination.as_str());
 
-        let mut sources =
-            glob::glob(&source.to_string_lossy()).map_or_else(|_| Vec::new(), Iterator::collect);
+        let sources: Vec<_> =
+            glob_arg(engine_state, &path, &call.positional[0], &source)?.collect();
         if sources.is_empty() {
             return Err(ShellError::FileNotFound(call.positional[0].span));
         }
@@ -76,134 +99,147 @@ impl Command for Cp {
             ));
         }
 
-        if interactive && !force {
-            let mut remove: Vec<usize> = vec![];
-            for (index, file) in sources.iter().enumerate() {
-                let prompt = format!(
-                    "Are you shure that you want to copy {} to {}?",
-                    file.as_ref()
-                        .unwrap()
-                        .file_name()
-                        .unwrap()
-                        .to_str()
-                        .unwrap(),
-                    destination.file_name().unwrap().to_str().unwrap()
-                );
-
-                let input = get_interactive_confirmation(prompt)?;
-
-                if !input {
-                    remove.push(index);
+        let interactive = interactive && !force;
+        if interactive {
+            check_interactive(engine_state, call)?;
+        }
+
+        let mut output = vec![];
+        for entry in sources.into_iter().flatten() {
+            let target = destination_path(&entry, &destination);
+            if interactive && target.exists() {
+                let prompt = format!("Overwrite {}?", target.to_string_lossy());
+
+                if !engine_state.confirm.confirm(&prompt)? {
+                    output.extend(report_skipped(
+                        &entry,
+                        "copy",
+                        report,
+                        call.positional[0].span,
+                    ));
+                    continue;
                 }
             }
 
-            remove.reverse();
+            let result = copy_entry(call, &entry, &destination, &path);
 
-            for index in remove {
-                sources.remove(index);
+            if let Some(row) = report_action(
+                &entry,
+                "copy",
+                result,
+                report,
+                continue_on_error,
+                call.positional[0].span,
+            )? {
+                output.push(row);
             }
+        }
+
+        Ok(output
+            .into_iter()
+            .into_pipeline_data(engine_state.ctrlc.clone()))
+    }
+}
 
-            if sources.is_empty() {
-                return Err(ShellError::NoFileToBeCopied());
+fn copy_entry(
+    call: &Call,
+    entry: &Path,
+    destination: &Path,
+    path: &Path,
+) -> Result<(), ShellError> {
+    let mut sources = FileStructure::new();
+    sources.walk_decorate(entry)?;
+
+    if entry.is_file() {
+        let sources = sources.paths_applying_with(|(source_file, _depth_level)| {
+            if destination.is_dir() {
+                let mut dest = canonicalize_with(&destination, &path)?;
+                if let Some(name) = entry.file_name() {
+                    dest.push(name);
+                }
+                Ok((source_file, dest))
+            } else {
+                Ok((source_file, destination.to_path_buf()))
             }
-        }
+        })?;
 
-        for entry in sources.into_iter().flatten() {
-            let mut sources = FileStructure::new();
-            sources.walk_decorate(&entry)?;
-
-            if entry.is_file() {
-                let sources = sources.paths_applying_with(|(source_file, _depth_level)| {
-                    if destination.is_dir() {
-                        let mut dest = canonicalize_with(&destination, &path)?;
-                        if let Some(name) = entry.file_name() {
-                            dest.push(name);
-                        }
-                        Ok((source_file, dest))
-                    } else {
-                        Ok((source_file, destination.clone()))
-                    }
+        for (src, dst) in sources {
+            if src.is_file() {
+                std::fs::copy(&src, dst).map_err(|e| {
+                    ShellError::MoveNotPossibleSingle(
+                        format!(
+                            "failed to move containing file \"{}\": {}",
+                            src.to_string_lossy(),
+                            e
+                        ),
+                        call.positional[0].span,
+                    )
                 })?;
-
-                for (src, dst) in sources {
-                    if src.is_file() {
-                        std::fs::copy(&src, dst).map_err(|e| {
-                            ShellError::MoveNotPossibleSingle(
-                                format!(
-                                    "failed to move containing file \"{}\": {}",
-                                    src.to_string_lossy(),
-                                    e
-                                ),
-                                call.positional[0].span,
-                            )
-                        })?;
-                    }
+            }
+        }
+    } else if entry.is_dir() {
+        let destination = if !destination.exists() {
+            destination.to_path_buf()
+        } else {
+            match entry.file_name() {
+                Some(name) => destination.join(name),
+                None => {
+                    return Err(ShellError::FileNotFoundCustom(
+                        format!("containing \"{:?}\" is not a valid path", entry),
+                        call.positional[0].span,
+                    ))
                 }
-            } else if entry.is_dir() {
-                let destination = if !destination.exists() {
-                    destination.clone()
-                } else {
-                    match entry.file_name() {
-                        Some(name) => destination.join(name),
-                        None => {
-                            return Err(ShellError::FileNotFoundCustom(
-                                format!("containing \"{:?}\" is not a valid path", entry),
-                                call.positional[0].span,
-                            ))
-                        }
-                    }
-                };
-
-                std::fs::create_dir_all(&destination).map_err(|e| {
+            }
+        };
+
+        std::fs::create_dir_all(&destination).map_err(|e| {
+            ShellError::MoveNotPossibleSingle(
+                format!("failed to recursively fill destination: {}", e),
+                call.positional[1].span,
+            )
+        })?;
+
+        let sources = sources.paths_applying_with(|(source_file, depth_level)| {
+            let mut dest = destination.clone();
+            let path = canonicalize_with(&source_file, &path)?;
+            let components = path
+                .components()
+                .map(|fragment| fragment.as_os_str())
+                .rev()
+                .take(1 + depth_level);
+
+            components.for_each(|fragment| dest.push(fragment));
+            Ok((PathBuf::from(&source_file), dest))
+        })?;
+
+        for (src, dst) in sources {
+            if src.is_dir() && !dst.exists() {
+                std::fs::create_dir_all(&dst).map_err(|e| {
                     ShellError::MoveNotPossibleSingle(
-                        format!("failed to recursively fill destination: {}", e),
+                        format!(
+                            "failed to create containing directory \"{}\": {}",
+                            dst.to_string_lossy(),
+                            e
+                        ),
                         call.positional[1].span,
                     )
                 })?;
+            }
 
-                let sources = sources.paths_applying_with(|(source_file, depth_level)| {
-                    let mut dest = destination.clone();
-                    let path = canonicalize_with(&source_file, &path)?;
-                    let components = path
-                        .components()
-                        .map(|fragment| fragment.as_os_str())
-                        .rev()
-                        .take(1 + depth_level);
-
-                    components.for_each(|fragment| dest.push(fragment));
-                    Ok((PathBuf::from(&source_file), dest))
+            if src.is_file() {
+                std::fs::copy(&src, &dst).map_err(|e| {
+                    ShellError::MoveNotPossibleSingle(
+                        format!(
+                            "failed to move containing file \"{}\": {}",
+                            src.to_string_lossy(),
+                            e
+                        ),
+                        call.positional[0].span,
+                    )
                 })?;
-
-                for (src, dst) in sources {
-                    if src.is_dir() && !dst.exists() {
-                        std::fs::create_dir_all(&dst).map_err(|e| {
-                            ShellError::MoveNotPossibleSingle(
-                                format!(
-                                    "failed to create containing directory \"{}\": {}",
-                                    dst.to_string_lossy(),
-                                    e
-                                ),
-                                call.positional[1].span,
-                            )
-                        })?;
-                    }
-
-                    if src.is_file() {
-                        std::fs::copy(&src, &dst).map_err(|e| {
-                            ShellError::MoveNotPossibleSingle(
-                                format!(
-                                    "failed to move containing file \"{}\": {}",
-                                    src.to_string_lossy(),
-                                    e
-                                ),
-                                call.positional[0].span,
-                            )
-                        })?;
-                    }
-                }
             }
         }
-
-        Ok(PipelineData::new(call.head))
     }
+
+    Ok(())
 }
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Category, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, SyntaxShape,
+    Value,
+};
+use rayon::prelude::*;
+
+use super::ls::glob_pattern;
+
+#[derive(Clone)]
+pub struct Du;
+
+impl Command for Du {
+    fn name(&self) -> &str {
+        "du"
+    }
+
+    fn usage(&self) -> &str {
+        "Find the disk usage of files and directories."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("du")
+            .optional(
+                "pattern",
+                SyntaxShape::GlobPattern,
+                "the glob pattern to use",
+            )
+            .category(Category::FileSystem)
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let pattern = glob_pattern(engine_state, stack, call)?;
+        let call_span = call.head;
+
+        let paths: Vec<PathBuf> = pattern.paths()?.flatten().collect();
+        let sizes = entry_sizes(&paths, &engine_state.ctrlc, dir_size);
+
+        Ok(paths
+            .into_iter()
+            .zip(sizes)
+            .map(move |(path, size)| Value::Record {
+                cols: vec!["name".into(), "size".into()],
+                vals: vec![
+                    Value::String {
+                        val: pattern.name(&path),
+                        span: call_span,
+                    },
+                    match size {
+                        Some(size) => Value::Filesize {
+                            val: size as i64,
+                            span: call_span,
+                        },
+                        None => Value::Nothing { span: call_span },
+                    },
+                ],
+                span: call_span,
+            })
+            .into_pipeline_data(engine_state.ctrlc.clone()))
+    }
+}
+
+/// Size of each of the entries, walking them in parallel on the rayon pool. The sizes come back
+/// in the order of `paths`, whichever walk finishes first
+pub(crate) fn entry_sizes<W>(
+    paths: &[PathBuf],
+    ctrlc: &Option<Arc<AtomicBool>>,
+    walk: W,
+) -> Vec<Option<u64>>
+where
+    W: Fn(&Path, &Option<Arc<AtomicBool>>) -> Option<u64> + Sync,
+{
+    paths.par_iter().map(|path| walk(path, ctrlc)).collect()
+}
+
+/// Total size of the files under `path`, without following symlinks. Files hard linked more than
+/// once in the tree count once. `None` if `path` can't be read or Ctrl-C cut the walk short
+pub(crate) fn dir_size(path: &Path, ctrlc: &Option<Arc<AtomicBool>>) -> Option<u64> {
+    let metadata = std::fs::symlink_metadata(path).ok()?;
+
+    let mut seen = HashSet::new();
+    let mut total = 0;
+    let mut pending = vec![(path.to_path_buf(), metadata)];
+
+    while let Some((path, metadata)) = pending.pop() {
+        if let Some(ctrlc) = ctrlc {
+            if ctrlc.load(Ordering::SeqCst) {
+                return None;
+            }
+        }
+
+        if metadata.is_dir() {
+            // Unreadable directories are skipped, like `du` does after warning about them
+            if let Ok(entries) = std::fs::read_dir(&path) {
+                for entry in entries.flatten() {
+                    if let Ok(metadata) = entry.metadata() {
+                        pending.push((entry.path(), metadata));
+                    }
+                }
+            }
+        } else if first_link(&metadata, &mut seen) {
+            total += metadata.len();
+        }
+    }
+
+    Some(total)
+}
+
+#[cfg(unix)]
+fn first_link(metadata: &Metadata, seen: &mut HashSet<(u64, u64)>) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    metadata.nlink() <= 1 || seen.insert((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn first_link(_metadata: &Metadata, _seen: &mut HashSet<(u64, u64)>) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    struct TempTree(PathBuf);
+
+    impl TempTree {
+        /// A directory per entry, each holding `files` files of 100 bytes, half of them nested
+        fn new(name: &str, entries: usize, files: usize) -> TempTree {
+            let root = std::env::temp_dir().join(format!("nu-du-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&root);
+
+            for entry in 0..entries {
+                let nested = root.join(format!("entry{}", entry)).join("nested");
+                std::fs::create_dir_all(&nested).expect("could not create test tree");
+
+                for file in 0..files {
+                    let dir = if file % 2 == 0 {
+                        nested.parent().expect("nested has a parent")
+                    } else {
+                        nested.as_path()
+                    };
+                    std::fs::write(dir.join(format!("file{}", file)), [0u8; 100])
+                        .expect("could not create test file");
+                }
+            }
+
+            TempTree(root)
+        }
+
+        fn entries(&self) -> Vec<PathBuf> {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(&self.0)
+                .expect("could not read test tree")
+                .map(|entry| entry.expect("could not read test entry").path())
+                .collect();
+            entries.sort();
+            entries
+        }
+    }
+
+    impl Drop for TempTree {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn parallel_totals_match_serial() {
+        let tree = TempTree::new("totals", 6, 5);
+        #[cfg(unix)]
+        std::fs::hard_link(
+            tree.0.join("entry0").join("file0"),
+            tree.0.join("entry0").join("nested").join("link"),
+        )
+        .expect("could not create hard link");
+
+        let entries = tree.entries();
+        let serial: Vec<Option<u64>> = entries.iter().map(|path| dir_size(path, &None)).collect();
+        let parallel = entry_sizes(&entries, &None, dir_size);
+
+        assert_eq!(parallel, serial);
+        assert_eq!(parallel, vec![Some(500); 6]);
+    }
+
+    #[test]
+    fn walks_run_in_parallel() {
+        let tree = TempTree::new("parallel", 8, 1);
+        let entries = tree.entries();
+
+        let walks = AtomicUsize::new(0);
+        let running = AtomicUsize::new(0);
+        let most_running = AtomicUsize::new(0);
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .expect("could not build thread pool");
+        let sizes = pool.install(|| {
+            entry_sizes(&entries, &None, |path, ctrlc| {
+                walks.fetch_add(1, Ordering::SeqCst);
+                let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                most_running.fetch_max(now_running, Ordering::SeqCst);
+
+                // Give the other threads time to pick up walks of their own
+                std::thread::sleep(Duration::from_millis(50));
+                let size = dir_size(path, ctrlc);
+
+                running.fetch_sub(1, Ordering::SeqCst);
+                size
+            })
+        });
+
+        assert_eq!(sizes, vec![Some(100); 8]);
+        assert_eq!(walks.load(Ordering::SeqCst), 8);
+        assert!(most_running.load(Ordering::SeqCst) > 1);
+    }
+
+    #[test]
+    fn ctrlc_abandons_walks() {
+        let tree = TempTree::new("ctrlc", 3, 2);
+        let entries = tree.entries();
+        let ctrlc = Some(Arc::new(AtomicBool::new(true)));
+
+        assert_eq!(entry_sizes(&entries, &ctrlc, dir_size), vec![None; 3]);
+    }
+}
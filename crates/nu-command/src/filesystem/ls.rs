@@ -1,8 +1,19 @@
+use std::fs::Metadata;
+use std::io;
+use std::path::{Path, PathBuf};
+
 use chrono::{DateTime, Utc};
-use nu_engine::eval_expression;
+use glob::GlobError;
+use nu_engine::{current_dir, eval_expression};
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{IntoInterruptiblePipelineData, PipelineData, Signature, SyntaxShape, Value};
+use nu_protocol::{
+    Category, DataSource, IntoInterruptiblePipelineData, PipelineData, PipelineMetadata,
+    ShellError, Signature, Span, SyntaxShape, Value,
+};
+
+use super::du::{dir_size, entry_sizes};
+use super::util::{glob_paths, glob_patterns, in_dir};
 
 #[derive(Clone)]
 pub struct Ls;
@@ -17,12 +28,30 @@ impl Command for Ls {
         "List the files in a directory."
     }
 
+    fn extra_usage(&self) -> &str {
+        r#"The pattern can have character classes like `[ab]*.txt`, `**` for any number of
+directories, and braces for alternatives, eg `*.{rs,toml}`. Quote the pattern to match braces
+literally."#
+    }
+
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("ls").optional(
-            "pattern",
-            SyntaxShape::GlobPattern,
-            "the glob pattern to use",
-        )
+        Signature::build("ls")
+            .optional(
+                "pattern",
+                SyntaxShape::GlobPattern,
+                "the glob pattern to use",
+            )
+            .switch(
+                "du",
+                "show the size of everything inside directories, like `du` does",
+                Some('d'),
+            )
+            .switch(
+                "strict",
+                "fail on the first entry that can't be read, instead of showing the error in its row",
+                Some('s'),
+            )
+            .category(Category::FileSystem)
     }
 
     fn run(
@@ -32,86 +61,256 @@ impl Command for Ls {
         call: &Call,
         _input: PipelineData,
     ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
-        let pattern = if let Some(expr) = call.positional.get(0) {
-            let result = eval_expression(engine_state, stack, expr)?;
-            let mut result = result.as_string()?;
-
-            let path = std::path::Path::new(&result);
-            if path.is_dir() {
-                if !result.ends_with(std::path::MAIN_SEPARATOR) {
-                    result.push(std::path::MAIN_SEPARATOR);
+        let pattern = glob_pattern(engine_state, stack, call)?;
+        let call_span = call.head;
+        let glob = pattern.paths()?;
+        let metadata = Some(PipelineMetadata {
+            data_source: Some(DataSource::Ls),
+            positions: None,
+            byte_len: None,
+        });
+
+        let rows: Box<dyn Iterator<Item = Value> + Send> = if call.has_flag("du") {
+            let entries: Vec<Result<PathBuf, GlobError>> = glob.collect();
+            let paths: Vec<PathBuf> = entries
+                .iter()
+                .filter_map(|entry| entry.as_ref().ok().cloned())
+                .collect();
+            let mut sizes = entry_sizes(&paths, &engine_state.ctrlc, dir_size).into_iter();
+
+            Box::new(entries.into_iter().map(move |entry| {
+                // The sizes are of the entries that were found, in the same order
+                let size = match entry {
+                    Ok(_) => sizes.next().flatten(),
+                    Err(_) => None,
+                };
+                entry_row(entry, &pattern, size, call_span)
+            }))
+        } else {
+            Box::new(glob.map(move |entry| entry_row(entry, &pattern, None, call_span)))
+        };
+
+        if call.has_flag("strict") {
+            let rows = rows.map(strict_row).collect::<Result<Vec<_>, _>>()?;
+
+            Ok(rows
+                .into_iter()
+                .into_pipeline_data(engine_state.ctrlc.clone())
+                .set_metadata(metadata))
+        } else {
+            Ok(rows
+                .into_pipeline_data(engine_state.ctrlc.clone())
+                .set_metadata(metadata))
+        }
+    }
+}
+
+/// The pattern `ls` and `du` glob for: everything in the current directory by default, and
+/// everything inside the directory if the pattern names one
+pub(super) struct GlobPattern {
+    /// The patterns the braces of the pattern expand to, resolved against the current directory
+    patterns: Vec<String>,
+    /// The current directory, if the pattern was relative to it
+    relative_to: Option<PathBuf>,
+    /// Where the pattern was given, or the call if it wasn't
+    span: Span,
+}
+
+impl GlobPattern {
+    /// The paths the pattern matches
+    pub fn paths(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<PathBuf, GlobError>> + Send, ShellError> {
+        glob_paths(&self.patterns, self.span)
+    }
+
+    /// The name to show for a path the pattern matched, relative if the pattern was
+    pub fn name(&self, path: &Path) -> String {
+        let path = match &self.relative_to {
+            Some(cwd) => path.strip_prefix(cwd).unwrap_or(path),
+            None => path,
+        };
+
+        path.to_string_lossy().to_string()
+    }
+}
+
+pub(super) fn glob_pattern(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    call: &Call,
+) -> Result<GlobPattern, ShellError> {
+    let cwd = current_dir(engine_state, stack)?;
+    let (given, patterns, span) = match call.positional.get(0) {
+        Some(expr) => {
+            let given = eval_expression(engine_state, stack, expr)?.as_string()?;
+            let patterns = glob_patterns(engine_state, expr, &given)?;
+            (given, patterns, expr.span)
+        }
+        None => (String::new(), vec![String::new()], call.head),
+    };
+
+    let patterns = patterns
+        .iter()
+        .map(|expanded| {
+            let mut pattern = in_dir(&cwd, expanded);
+            if cwd.join(expanded).is_dir() {
+                if !pattern.ends_with(std::path::MAIN_SEPARATOR) {
+                    pattern.push(std::path::MAIN_SEPARATOR);
                 }
-                result.push('*');
+                pattern.push('*');
             }
+            pattern
+        })
+        .collect();
 
-            result
+    Ok(GlobPattern {
+        patterns,
+        relative_to: if Path::new(&given).is_absolute() {
+            None
         } else {
-            "*".into()
-        };
+            Some(cwd)
+        },
+        span,
+    })
+}
 
-        let call_span = call.head;
-        let glob = glob::glob(&pattern).unwrap();
-
-        Ok(glob
-            .into_iter()
-            .map(move |x| match x {
-                Ok(path) => match std::fs::symlink_metadata(&path) {
-                    Ok(metadata) => {
-                        let is_file = metadata.is_file();
-                        let is_dir = metadata.is_dir();
-                        let filesize = metadata.len();
-
-                        let mut cols = vec!["name".into(), "type".into(), "size".into()];
-
-                        let mut vals = vec![
-                            Value::String {
-                                val: path.to_string_lossy().to_string(),
-                                span: call_span,
-                            },
-                            if is_file {
-                                Value::string("file", call_span)
-                            } else if is_dir {
-                                Value::string("dir", call_span)
-                            } else {
-                                Value::Nothing { span: call_span }
-                            },
-                            Value::Filesize {
-                                val: filesize as i64,
-                                span: call_span,
-                            },
-                        ];
-
-                        if let Ok(date) = metadata.modified() {
-                            let utc: DateTime<Utc> = date.into();
-
-                            cols.push("modified".into());
-                            vals.push(Value::Date {
-                                val: utc.into(),
-                                span: call_span,
-                            });
-                        }
-
-                        Value::Record {
-                            cols,
-                            vals,
-                            span: call_span,
-                        }
-                    }
-                    Err(_) => Value::Record {
-                        cols: vec!["name".into(), "type".into(), "size".into()],
-                        vals: vec![
-                            Value::String {
-                                val: path.to_string_lossy().to_string(),
-                                span: call_span,
-                            },
-                            Value::Nothing { span: call_span },
-                            Value::Nothing { span: call_span },
-                        ],
-                        span: call_span,
+/// The row of an entry the glob found, or of a directory it couldn't read
+fn entry_row(
+    entry: Result<PathBuf, GlobError>,
+    pattern: &GlobPattern,
+    du_size: Option<u64>,
+    call_span: Span,
+) -> Value {
+    match entry {
+        Ok(path) => {
+            let name = pattern.name(&path);
+            ls_row(path, name, du_size, call_span)
+        }
+        Err(err) => {
+            let name = pattern.name(err.path());
+            let error = metadata_error(err.path(), &name, err.error(), call_span);
+            error_row(name, error, call_span)
+        }
+    }
+}
+
+/// A row of the `ls` table, showing `path` as `name`. `du_size`, if given, replaces the size of
+/// the entry itself
+fn ls_row(path: PathBuf, name: String, du_size: Option<u64>, call_span: Span) -> Value {
+    match std::fs::symlink_metadata(&path) {
+        Ok(metadata) => {
+            // A link has the type of what it points to
+            let entry_type = if metadata.file_type().is_symlink() {
+                match std::fs::metadata(&path) {
+                    Ok(target) => file_type(&target, call_span),
+                    Err(err) => Value::Error {
+                        error: metadata_error(&path, &name, &err, call_span),
                     },
+                }
+            } else {
+                file_type(&metadata, call_span)
+            };
+            let filesize = du_size.unwrap_or_else(|| metadata.len());
+
+            let mut cols = vec!["name".into(), "type".into(), "size".into()];
+
+            let mut vals = vec![
+                Value::String {
+                    val: name,
+                    span: call_span,
                 },
-                _ => Value::Nothing { span: call_span },
-            })
-            .into_pipeline_data(engine_state.ctrlc.clone()))
+                entry_type,
+                Value::Filesize {
+                    val: filesize as i64,
+                    span: call_span,
+                },
+            ];
+
+            if let Ok(date) = metadata.modified() {
+                let utc: DateTime<Utc> = date.into();
+
+                cols.push("modified".into());
+                vals.push(Value::Date {
+                    val: utc.into(),
+                    span: call_span,
+                });
+            }
+
+            Value::Record {
+                cols,
+                vals,
+                span: call_span,
+            }
+        }
+        Err(err) => {
+            let error = metadata_error(&path, &name, &err, call_span);
+            error_row(name, error, call_span)
+        }
+    }
+}
+
+fn file_type(metadata: &Metadata, span: Span) -> Value {
+    if metadata.is_file() {
+        Value::string("file", span)
+    } else if metadata.is_dir() {
+        Value::string("dir", span)
+    } else {
+        Value::Nothing { span }
+    }
+}
+
+/// The row of an entry whose metadata can't be read: its name, and the error in every other
+/// column, so that the row can still be filtered and shown
+fn error_row(name: String, error: ShellError, call_span: Span) -> Value {
+    Value::Record {
+        cols: vec![
+            "name".into(),
+            "type".into(),
+            "size".into(),
+            "modified".into(),
+        ],
+        vals: vec![
+            Value::String {
+                val: name,
+                span: call_span,
+            },
+            Value::Error {
+                error: error.clone(),
+            },
+            Value::Error {
+                error: error.clone(),
+            },
+            Value::Error { error },
+        ],
+        span: call_span,
+    }
+}
+
+/// Why the metadata of `path`, shown as `name`, can't be read
+fn metadata_error(path: &Path, name: &str, err: &io::Error, call_span: Span) -> ShellError {
+    match err.kind() {
+        io::ErrorKind::PermissionDenied => ShellError::PermissionDenied(name.into(), call_span),
+        io::ErrorKind::NotFound if is_link(path) => ShellError::BrokenLink(name.into(), call_span),
+        _ => ShellError::FileNotFoundCustom(format!("can't read {}: {}", name, err), call_span),
     }
 }
+
+fn is_link(path: &Path) -> bool {
+    std::fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// The row as it is, or the first error in it for `--strict`
+fn strict_row(row: Value) -> Result<Value, ShellError> {
+    if let Value::Record { vals, .. } = &row {
+        if let Some(Value::Error { error }) =
+            vals.iter().find(|val| matches!(val, Value::Error { .. }))
+        {
+            return Err(error.clone());
+        }
+    }
+
+    Ok(row)
+}
@@ -1,11 +1,12 @@
 use std::collections::VecDeque;
-use std::env::current_dir;
 
-use nu_engine::CallExt;
+use super::util::{report_action, Report};
+use nu_engine::{current_dir, CallExt};
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{
-    IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, SyntaxShape, Value,
+    Category, Example, FixtureEntry, IntoInterruptiblePipelineData, PipelineData, ShellError,
+    Signature, Span, SyntaxShape, Type, Value,
 };
 
 #[derive(Clone)]
@@ -24,6 +25,20 @@ impl Command for Mkdir {
                 "the name(s) of the path(s) to create",
             )
             .switch("show-created-paths", "show the path(s) created.", Some('s'))
+            .switch(
+                "verbose",
+                "report each path as a {path, action, status} row",
+                Some('v'),
+            )
+            .switch(
+                "continue-on-error",
+                "report failures as rows instead of stopping",
+                None,
+            )
+            .conflicts_with("show-created-paths", "verbose")
+            .input_type(Type::Nothing)
+            .output_type(Type::Table)
+            .category(Category::FileSystem)
     }
 
     fn usage(&self) -> &str {
@@ -37,14 +52,21 @@ impl Command for Mkdir {
         call: &Call,
         _input: PipelineData,
     ) -> Result<PipelineData, ShellError> {
-        let path = current_dir()?;
+        let path = current_dir(engine_state, stack)?;
         let mut directories = call
-            .rest::<String>(engine_state, stack, 0)?
+            .rest_spanned::<String>(engine_state, stack, 0)?
             .into_iter()
-            .map(|dir| path.join(dir))
+            .map(|dir| (path.join(dir.item), dir.span))
             .peekable();
 
-        let show_created_paths = call.has_flag("show-created-paths");
+        let report = if call.has_flag("verbose") {
+            Report::Rows
+        } else if call.has_flag("show-created-paths") {
+            Report::Paths
+        } else {
+            Report::Quiet
+        };
+        let continue_on_error = call.has_flag("continue-on-error");
         let mut stream: VecDeque<Value> = VecDeque::new();
 
         if directories.peek().is_none() {
@@ -54,20 +76,18 @@ impl Command for Mkdir {
             ));
         }
 
-        for (i, dir) in directories.enumerate() {
-            let span = call.positional[i].span;
-            let dir_res = std::fs::create_dir_all(&dir);
-
-            if let Err(reason) = dir_res {
-                return Err(ShellError::CreateNotPossible(
+        for (dir, span) in directories {
+            let dir_res = std::fs::create_dir_all(&dir).map_err(|reason| {
+                ShellError::CreateNotPossible(
                     format!("failed to create directory: {}", reason),
-                    call.positional[i].span,
-                ));
-            }
+                    span,
+                )
+            });
 
-            if show_created_paths {
-                let val = format!("{:}", dir.to_string_lossy());
-                stream.push_back(Value::String { val, span });
+            if let Some(row) =
+                report_action(&dir, "create", dir_res, report, continue_on_error, span)?
+            {
+                stream.push_back(row);
             }
         }
 
@@ -75,4 +95,61 @@ impl Command for Mkdir {
             .into_iter()
             .into_pipeline_data(engine_state.ctrlc.clone()))
     }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Make a directory named foo",
+                example: "mkdir foo",
+                result: Some(Value::List {
+                    vals: vec![],
+                    span: Span::unknown(),
+                }),
+                fixture: Some(&[]),
+            },
+            Example {
+                description: "Make multiple directories and report each one",
+                example: "mkdir foo bar -v | get action",
+                result: Some(Value::List {
+                    vals: vec![Value::test_string("create"), Value::test_string("create")],
+                    span: Span::unknown(),
+                }),
+                fixture: Some(&[]),
+            },
+            Example {
+                description:
+                    "Make nested directories, creating intermediary directories as required",
+                example: "mkdir src/a/b -v | get status",
+                result: Some(Value::List {
+                    vals: vec![Value::test_string("ok")],
+                    span: Span::unknown(),
+                }),
+                fixture: Some(&[FixtureEntry::Dir("src")]),
+            },
+            Example {
+                description: "Report paths that can't be created as rows instead of stopping",
+                example: "mkdir -v --continue-on-error notes.txt/sub fresh | get status",
+                result: Some(Value::List {
+                    vals: vec![Value::test_string("error"), Value::test_string("ok")],
+                    span: Span::unknown(),
+                }),
+                fixture: Some(&[FixtureEntry::File {
+                    path: "notes.txt",
+                    contents: "not a directory",
+                }]),
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Mkdir {})
+    }
 }
@@ -1,16 +1,20 @@
 mod cd;
 mod cp;
+mod du;
 mod ls;
 mod mkdir;
 mod mv;
 mod rm;
+mod save;
 mod touch;
 mod util;
 
 pub use cd::Cd;
 pub use cp::Cp;
+pub use du::Du;
 pub use ls::Ls;
 pub use mkdir::Mkdir;
 pub use mv::Mv;
 pub use rm::Rm;
+pub use save::Save;
 pub use touch::Touch;
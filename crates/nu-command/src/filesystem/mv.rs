@@ -1,11 +1,14 @@
-use std::env::current_dir;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
-use super::util::get_interactive_confirmation;
-use nu_engine::CallExt;
+use super::util::{
+    check_interactive, destination_path, glob_arg, report_action, report_skipped, Report,
+};
+use nu_engine::{current_dir, CallExt};
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{PipelineData, ShellError, Signature, SyntaxShape};
+use nu_protocol::{
+    Category, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, SyntaxShape,
+};
 
 #[derive(Clone)]
 pub struct Mv;
@@ -32,8 +35,23 @@ impl Command for Mv {
                 SyntaxShape::Filepath,
                 "the location to move files/directories to",
             )
-            .switch("interactive", "ask user to confirm action", Some('i'))
+            .switch(
+                "interactive",
+                "ask before overwriting existing files",
+                Some('i'),
+            )
             .switch("force", "suppress error when no file", Some('f'))
+            .switch(
+                "verbose",
+                "report each path as a {path, action, status} row",
+                Some('v'),
+            )
+            .switch(
+                "continue-on-error",
+                "report failures as rows instead of stopping",
+                None,
+            )
+            .category(Category::FileSystem)
     }
 
     fn run(
@@ -48,13 +66,18 @@ impl Command for Mv {
         let destination: String = call.req(engine_state, stack, 1)?;
         let interactive = call.has_flag("interactive");
         let force = call.has_flag("force");
+        let report = if call.has_flag("verbose") {
+            Report::Rows
+        } else {
+            Report::Quiet
+        };
+        let continue_on_error = call.has_flag("continue-on-error");
 
-        let path: PathBuf = current_dir().unwrap();
-        let source = path.join(source.as_str());
+        let path = current_dir(engine_state, stack)?;
         let destination = path.join(destination.as_str());
 
-        let mut sources =
-            glob::glob(&source.to_string_lossy()).map_or_else(|_| Vec::new(), Iterator::collect);
+        let mut sources: Vec<_> =
+            glob_arg(engine_state, &path, &call.positional[0], &source)?.collect();
 
         if sources.is_empty() {
             return Err(ShellError::FileNotFound(
@@ -62,36 +85,9 @@ impl Command for Mv {
             ));
         }
 
-        if interactive && !force {
-            let mut remove: Vec<usize> = vec![];
-            for (index, file) in sources.iter().enumerate() {
-                let prompt = format!(
-                    "Are you shure that you want to move {} to {}?",
-                    file.as_ref()
-                        .unwrap()
-                        .file_name()
-                        .unwrap()
-                        .to_str()
-                        .unwrap(),
-                    destination.file_name().unwrap().to_str().unwrap()
-                );
-
-                let input = get_interactive_confirmation(prompt)?;
-
-                if !input {
-                    remove.push(index);
-                }
-            }
-
-            remove.reverse();
-
-            for index in remove {
-                sources.remove(index);
-            }
-
-            if sources.is_empty() {
-                return Err(ShellError::NoFileToBeMoved());
-            }
+        let interactive = interactive && !force;
+        if interactive {
+            check_interactive(engine_state, call)?;
         }
 
         if (destination.exists() && !destination.is_dir() && sources.len() > 1)
@@ -126,11 +122,40 @@ impl Command for Mv {
                 .collect();
         }
 
+        let mut output = vec![];
         for entry in sources.into_iter().flatten() {
-            move_file(call, &entry, &destination)?
+            let target = destination_path(&entry, &destination);
+            if interactive && target.exists() {
+                let prompt = format!("Overwrite {}?", target.to_string_lossy());
+
+                if !engine_state.confirm.confirm(&prompt)? {
+                    output.extend(report_skipped(
+                        &entry,
+                        "move",
+                        report,
+                        call.positional[0].span,
+                    ));
+                    continue;
+                }
+            }
+
+            let result = move_file(call, &entry, &destination);
+
+            if let Some(row) = report_action(
+                &entry,
+                "move",
+                result,
+                report,
+                continue_on_error,
+                call.positional[0].span,
+            )? {
+                output.push(row);
+            }
         }
 
-        Ok(PipelineData::new(call.head))
+        Ok(output
+            .into_iter()
+            .into_pipeline_data(engine_state.ctrlc.clone()))
     }
 }
 
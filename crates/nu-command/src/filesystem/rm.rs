@@ -1,15 +1,15 @@
-use std::env::current_dir;
 #[cfg(unix)]
 use std::os::unix::prelude::FileTypeExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use super::util::get_interactive_confirmation;
+use super::util::{check_interactive, glob_arg, report_action, report_skipped, Report};
 
-use nu_engine::CallExt;
+use nu_engine::{current_dir, CallExt};
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{
-    IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, SyntaxShape, Value,
+    Category, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, Span,
+    SyntaxShape, Value,
 };
 
 #[derive(Clone)]
@@ -24,6 +24,9 @@ struct RmArgs {
     trash: bool,
     permanent: bool,
     force: bool,
+    interactive: bool,
+    report: Report,
+    continue_on_error: bool,
 }
 
 impl Command for Rm {
@@ -49,12 +52,32 @@ impl Command for Rm {
             )
             .switch("recursive", "delete subdirectories recursively", Some('r'))
             .switch("force", "suppress error when no file", Some('f'))
-            .switch("interactive", "ask user to confirm action", Some('i'))
+            .switch(
+                "allow-protected",
+                "together with --force, also remove protected paths (the current directory and its ancestors, the home directory and rm_protected_paths)",
+                None,
+            )
+            .switch("interactive", "ask before removing each path", Some('i'))
+            .switch("quiet", "don't report the removed paths", Some('q'))
+            .switch(
+                "verbose",
+                "report each path as a {path, action, status} row",
+                Some('v'),
+            )
+            .switch(
+                "continue-on-error",
+                "report failures as rows instead of stopping",
+                None,
+            )
             .rest(
                 "rest",
                 SyntaxShape::GlobPattern,
                 "the file path(s) to remove",
             )
+            .conflicts_with("trash", "permanent")
+            .conflicts_with("quiet", "verbose")
+            .requires("allow-protected", "force")
+            .category(Category::FileSystem)
     }
 
     fn run(
@@ -77,52 +100,21 @@ fn rm(
     let permanent = call.has_flag("permanent");
     let interactive = call.has_flag("interactive");
 
-    if trash && permanent {
-        return Err(ShellError::IncompatibleParametersSingle(
-            "Can't use \"--trash\" with \"--permanent\"".to_string(),
-            call.head,
-        ));
-
-        //     let trash_span = call.get_flag_expr("trash").unwrap().span;
-        //     let perm_span = call.get_flag_expr("permanent").unwrap().span;
-
-        //     let left_message = "cannot use".to_string();
-        //     let right_message = "with".to_string();
-        //     let (left_span, right_span) = match trash_span.start < perm_span.start {
-        //         true => (trash_span, perm_span),
-        //         false => (perm_span, trash_span),
-        //     };
-
-        //     return Err(ShellError::IncompatibleParameters {
-        //         left_message,
-        //         left_span,
-        //         right_message,
-        //         right_span,
-        //     });
-    }
-
-    let current_path = current_dir()?;
-    let mut paths = call
-        .rest::<String>(engine_state, stack, 0)?
-        .into_iter()
-        .map(|path| current_path.join(path))
-        .peekable();
+    let current_path = current_dir(engine_state, stack)?;
+    let paths: Vec<String> = call.rest(engine_state, stack, 0)?;
 
-    if paths.peek().is_none() {
+    if paths.is_empty() {
         return Err(ShellError::FileNotFound(call.positional[0].span));
     }
 
     // Expand and flatten files
-    let resolve_path = |i: usize, path: PathBuf| {
-        glob::glob(&path.to_string_lossy()).map_or_else(
-            |_| Vec::new(),
-            |path_iter| path_iter.flatten().map(|f| Target(i, f)).collect(),
-        )
-    };
-
     let mut targets: Vec<Target> = vec![];
-    for (i, path) in paths.enumerate() {
-        let mut paths: Vec<Target> = resolve_path(i, path);
+    for (i, path) in paths.iter().enumerate() {
+        let mut paths: Vec<Target> =
+            glob_arg(engine_state, &current_path, &call.positional[i], path)?
+                .flatten()
+                .map(|f| Target(i, f))
+                .collect();
 
         if paths.is_empty() {
             return Err(ShellError::FileNotFound(call.positional[i].span));
@@ -134,31 +126,36 @@ fn rm(
     let recursive = call.has_flag("recursive");
     let force = call.has_flag("force");
 
-    if interactive && !force {
-        let mut remove: Vec<usize> = vec![];
-        for (index, file) in targets.iter().enumerate() {
-            let prompt: String = format!(
-                "Are you sure that you what to delete {}?",
-                file.1.file_name().unwrap().to_str().unwrap()
-            );
-
-            let input = get_interactive_confirmation(prompt)?;
+    let interactive = interactive && !force;
+    if interactive {
+        check_interactive(engine_state, call)?;
+    }
 
-            if !input {
-                remove.push(index);
+    if !call.has_flag("allow-protected") {
+        let config = stack.get_config().unwrap_or_default();
+        let protected = protected_paths(
+            &current_path,
+            nu_path::home_dir(),
+            &config.rm_protected_paths,
+        );
+
+        for target in &targets {
+            if let Some(reason) = protection_reason(&target.1, &protected) {
+                return Err(ShellError::RemoveProtectedPath(
+                    format!("{} is {}", target.1.to_string_lossy(), reason),
+                    call.positional[target.0].span,
+                ));
             }
         }
-
-        remove.reverse();
-
-        for index in remove {
-            targets.remove(index);
-        }
-
-        if targets.is_empty() {
-            return Err(ShellError::NoFileToBeRemoved());
-        }
     }
+    let report = if call.has_flag("verbose") {
+        Report::Rows
+    } else if call.has_flag("quiet") {
+        Report::Quiet
+    } else {
+        Report::Lines("deleted")
+    };
+    let continue_on_error = call.has_flag("continue-on-error");
 
     let args = RmArgs {
         targets,
@@ -166,8 +163,11 @@ fn rm(
         trash,
         permanent,
         force,
+        interactive,
+        report,
+        continue_on_error,
     };
-    let response = rm_helper(call, args);
+    let response = rm_helper(engine_state, call, args)?;
 
     // let temp = rm_helper(call, args).flatten();
     // let temp = input.flatten(call.head, move |_| rm_helper(call, args));
@@ -178,110 +178,348 @@ fn rm(
     // Ok(Value::Nothing { span })
 }
 
-fn rm_helper(call: &Call, args: RmArgs) -> Vec<Value> {
-    let (targets, recursive, trash, _permanent, force) = (
+/// The paths rm refuses to remove (along with their ancestors) and why
+fn protected_paths(
+    current_path: &Path,
+    home: Option<PathBuf>,
+    configured: &[String],
+) -> Vec<(PathBuf, &'static str)> {
+    let mut protected = vec![(canonical_path(current_path), "the current directory")];
+
+    if let Some(home) = home {
+        protected.push((canonical_path(&home), "the home directory"));
+    }
+
+    for path in configured {
+        protected.push((
+            canonical_path(&nu_path::expand_path(path)),
+            "listed in rm_protected_paths",
+        ));
+    }
+
+    protected
+}
+
+/// Why removing `target` must be refused, if it must. Paths are compared after canonicalization so
+/// `..` and symlinked parents can't be used to sneak around the check, and since removing a
+/// directory removes everything below it, ancestors of a protected path are protected too
+fn protection_reason(target: &Path, protected: &[(PathBuf, &'static str)]) -> Option<String> {
+    // Only resolve the parent: removing a symlink doesn't touch what it points to
+    let target = match (target.parent(), target.file_name()) {
+        (Some(parent), Some(name)) => canonical_path(parent).join(name),
+        _ => canonical_path(target),
+    };
+
+    if target.parent().is_none() {
+        return Some("the root directory".into());
+    }
+
+    protected.iter().find_map(|(path, reason)| {
+        if *path == target {
+            Some(reason.to_string())
+        } else if path.starts_with(&target) {
+            Some(format!("an ancestor of a path that is {}", reason))
+        } else {
+            None
+        }
+    })
+}
+
+fn canonical_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn rm_helper(
+    engine_state: &EngineState,
+    call: &Call,
+    args: RmArgs,
+) -> Result<Vec<Value>, ShellError> {
+    let (targets, recursive, trash, _permanent, force, interactive, report, continue_on_error) = (
         args.targets,
         args.recursive,
         args.trash,
         args.permanent,
         args.force,
+        args.interactive,
+        args.report,
+        args.continue_on_error,
     );
 
     #[cfg(not(feature = "trash-support"))]
     {
         if trash {
-            return vec![Value::Error {
-                error: ShellError::FeatureNotEnabled(call.get_flag_expr("trash").unwrap().span),
-            }];
+            return Err(ShellError::FeatureNotEnabled(
+                call.get_flag_expr("trash").unwrap().span,
+            ));
         }
     }
 
     if targets.is_empty() && !force {
-        return vec![Value::Error {
-            error: ShellError::FileNotFound(call.head),
-        }];
+        return Err(ShellError::FileNotFound(call.head));
     }
 
-    targets
-        .into_iter()
-        .map(move |target| {
-            let (i, f) = (target.0, target.1);
-
-            let is_empty = || match f.read_dir() {
-                Ok(mut p) => p.next().is_none(),
-                Err(_) => false,
-            };
-
-            if let Ok(metadata) = f.symlink_metadata() {
-                #[cfg(unix)]
-                let is_socket = metadata.file_type().is_socket();
-                #[cfg(unix)]
-                let is_fifo = metadata.file_type().is_fifo();
-
-                #[cfg(not(unix))]
-                let is_socket = false;
-                #[cfg(not(unix))]
-                let is_fifo = false;
-
-                if metadata.is_file()
-                    || metadata.file_type().is_symlink()
-                    || recursive
-                    || is_socket
-                    || is_fifo
-                    || is_empty()
-                {
-                    let result;
-                    #[cfg(feature = "trash-support")]
-                    {
-                        use std::io::Error;
-                        result = if trash {
-                            trash::delete(&f).map_err(|e: trash::Error| {
-                                Error::new(ErrorKind::Other, format!("{:?}", e))
-                            })
-                        } else if metadata.is_file() {
-                            std::fs::remove_file(&f)
-                        } else {
-                            std::fs::remove_dir_all(&f)
-                        };
-                    }
-                    #[cfg(not(feature = "trash-support"))]
-                    {
-                        result = if metadata.is_file() || is_socket || is_fifo {
-                            std::fs::remove_file(&f)
-                        } else {
-                            std::fs::remove_dir_all(&f)
-                        };
-                    }
-
-                    if let Err(e) = result {
-                        Value::Error {
-                            error: ShellError::RemoveNotPossible(
-                                format!("Could not delete because: {:}\nTry '--trash' flag", e),
-                                call.head,
-                            ),
-                        }
-                    } else {
-                        Value::String {
-                            val: format!("deleted {:}", f.to_string_lossy()),
-                            span: call.positional[i].span,
-                        }
-                    }
+    let mut output = vec![];
+    for target in targets {
+        let span = call.positional[target.0].span;
+
+        if interactive {
+            let prompt = format!("Remove {}?", target.1.to_string_lossy());
+
+            if !engine_state.confirm.confirm(&prompt)? {
+                output.extend(report_skipped(&target.1, "remove", report, span));
+                continue;
+            }
+        }
+
+        let result = remove_target(call, &target.1, span, recursive, trash);
+
+        if let Some(row) =
+            report_action(&target.1, "remove", result, report, continue_on_error, span)?
+        {
+            output.push(row);
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg_attr(not(feature = "trash-support"), allow(unused_variables))]
+fn remove_target(
+    call: &Call,
+    f: &Path,
+    span: Span,
+    recursive: bool,
+    trash: bool,
+) -> Result<(), ShellError> {
+    let is_empty = || match f.read_dir() {
+        Ok(mut p) => p.next().is_none(),
+        Err(_) => false,
+    };
+
+    if let Ok(metadata) = f.symlink_metadata() {
+        #[cfg(unix)]
+        let is_socket = metadata.file_type().is_socket();
+        #[cfg(unix)]
+        let is_fifo = metadata.file_type().is_fifo();
+
+        #[cfg(not(unix))]
+        let is_socket = false;
+        #[cfg(not(unix))]
+        let is_fifo = false;
+
+        if metadata.is_file()
+            || metadata.file_type().is_symlink()
+            || recursive
+            || is_socket
+            || is_fifo
+            || is_empty()
+        {
+            let result;
+            #[cfg(feature = "trash-support")]
+            {
+                use std::io::Error;
+                result = if trash {
+                    trash::delete(&f)
+                        .map_err(|e: trash::Error| Error::new(ErrorKind::Other, format!("{:?}", e)))
+                } else if metadata.is_file() {
+                    std::fs::remove_file(&f)
+                } else {
+                    std::fs::remove_dir_all(&f)
+                };
+            }
+            #[cfg(not(feature = "trash-support"))]
+            {
+                result = if metadata.is_file() || is_socket || is_fifo {
+                    std::fs::remove_file(&f)
                 } else {
-                    Value::Error {
-                        error: ShellError::RemoveNotPossible(
-                            "Cannot remove. try --recursive".to_string(),
-                            call.positional[i].span,
-                        ),
-                    }
-                }
-            } else {
-                Value::Error {
-                    error: ShellError::RemoveNotPossible(
-                        "no such file or directory".to_string(),
-                        call.positional[i].span,
-                    ),
-                }
+                    std::fs::remove_dir_all(&f)
+                };
             }
-        })
-        .collect()
+
+            result.map_err(|e| {
+                ShellError::RemoveNotPossible(
+                    format!("Could not delete because: {:}\nTry '--trash' flag", e),
+                    call.head,
+                )
+            })
+        } else {
+            Err(ShellError::RemoveNotPossible(
+                "Cannot remove. try --recursive".to_string(),
+                span,
+            ))
+        }
+    } else {
+        Err(ShellError::RemoveNotPossible(
+            "no such file or directory".to_string(),
+            span,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use nu_engine::test_support::{add_decls, eval_source, stack_with_config};
+    use nu_protocol::engine::Confirm;
+
+    /// Answers prompts from a script instead of a terminal
+    struct Scripted(Mutex<Vec<bool>>);
+
+    impl Confirm for Scripted {
+        fn is_interactive(&self) -> bool {
+            true
+        }
+
+        fn confirm(&self, _prompt: &str) -> Result<bool, ShellError> {
+            Ok(self.0.lock().expect("poisoned answers").remove(0))
+        }
+    }
+
+    fn run_rm(engine_state: &mut EngineState, source: &str) -> Result<Value, ShellError> {
+        add_decls(engine_state, vec![Box::new(Rm)]);
+
+        eval_source(
+            engine_state,
+            &mut stack_with_config(),
+            source,
+            PipelineData::new(Span::unknown()),
+        )
+        .map(|output| output.into_value(Span::unknown()))
+    }
+
+    fn lines(output: Value) -> Vec<String> {
+        match output {
+            Value::List { vals, .. } => vals
+                .into_iter()
+                .map(|line| line.as_string().expect("rm reports lines"))
+                .collect(),
+            other => panic!("expected a list of lines, got {:?}", other),
+        }
+    }
+
+    struct TempTree(PathBuf);
+
+    impl TempTree {
+        fn new(name: &str, dirs: &[&str]) -> TempTree {
+            let root = std::env::temp_dir().join(format!("nu-rm-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&root);
+            std::fs::create_dir_all(&root).expect("could not create test tree");
+            for dir in dirs {
+                std::fs::create_dir_all(root.join(dir)).expect("could not create test tree");
+            }
+
+            TempTree(canonical_path(&root))
+        }
+    }
+
+    impl Drop for TempTree {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn interactive_skips_declined_paths() {
+        let tree = TempTree::new("interactive", &[]);
+        for name in ["a", "b", "c"] {
+            std::fs::write(tree.0.join(name), "").expect("could not create test file");
+        }
+
+        let mut engine_state = EngineState::new();
+        engine_state.confirm = Arc::new(Scripted(Mutex::new(vec![true, false, true])));
+
+        let source = format!(
+            "rm -i {} {} {}",
+            tree.0.join("a").to_string_lossy(),
+            tree.0.join("b").to_string_lossy(),
+            tree.0.join("c").to_string_lossy()
+        );
+        let output = run_rm(&mut engine_state, &source).expect("rm should succeed");
+
+        assert!(!tree.0.join("a").exists());
+        assert!(tree.0.join("b").exists());
+        assert!(!tree.0.join("c").exists());
+
+        let expected: Vec<String> = [("deleted", "a"), ("skipped", "b"), ("deleted", "c")]
+            .iter()
+            .map(|(done, name)| format!("{} {}", done, tree.0.join(name).to_string_lossy()))
+            .collect();
+        assert_eq!(lines(output), expected);
+    }
+
+    #[test]
+    fn quiet_removes_without_reporting() {
+        let tree = TempTree::new("quiet", &[]);
+        std::fs::write(tree.0.join("a"), "").expect("could not create test file");
+
+        let source = format!("rm -q {}", tree.0.join("a").to_string_lossy());
+        let output = run_rm(&mut EngineState::new(), &source).expect("rm should succeed");
+
+        assert!(!tree.0.join("a").exists());
+        assert!(lines(output).is_empty());
+    }
+
+    #[test]
+    fn interactive_needs_someone_to_ask() {
+        let tree = TempTree::new("noninteractive", &[]);
+        std::fs::write(tree.0.join("a"), "").expect("could not create test file");
+
+        let mut engine_state = EngineState::new();
+        let source = format!("rm -i {}", tree.0.join("a").to_string_lossy());
+
+        assert!(matches!(
+            run_rm(&mut engine_state, &source),
+            Err(ShellError::ConfirmationNotAvailable(_))
+        ));
+        assert!(tree.0.join("a").exists());
+    }
+
+    #[test]
+    fn refuses_current_directory_and_ancestors() {
+        let tree = TempTree::new("cwd", &["a/b/c"]);
+        let cwd = tree.0.join("a/b");
+        let protected = protected_paths(&cwd, None, &[]);
+
+        assert_eq!(
+            protection_reason(&cwd.join("."), &protected),
+            Some("the current directory".into())
+        );
+        assert_eq!(
+            protection_reason(&cwd.join("../.."), &protected),
+            Some("an ancestor of a path that is the current directory".into())
+        );
+        assert_eq!(
+            protection_reason(Path::new("/"), &protected),
+            Some("the root directory".into())
+        );
+        assert_eq!(protection_reason(&cwd.join("c"), &protected), None);
+    }
+
+    #[test]
+    fn refuses_home_and_configured_paths() {
+        let tree = TempTree::new("configured", &["home", "mnt/volume", "work"]);
+        let configured = vec![tree.0.join("mnt/volume").to_string_lossy().to_string()];
+        let protected =
+            protected_paths(&tree.0.join("work"), Some(tree.0.join("home")), &configured);
+
+        assert_eq!(
+            protection_reason(&tree.0.join("home"), &protected),
+            Some("the home directory".into())
+        );
+        assert_eq!(
+            protection_reason(&tree.0.join("work/../mnt/volume"), &protected),
+            Some("listed in rm_protected_paths".into())
+        );
+        assert_eq!(
+            protection_reason(&tree.0.join("mnt"), &protected),
+            Some("an ancestor of a path that is listed in rm_protected_paths".into())
+        );
+        assert_eq!(
+            protection_reason(&tree.0.join("work/file"), &protected),
+            None
+        );
+    }
 }
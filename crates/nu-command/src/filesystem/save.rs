@@ -0,0 +1,225 @@
+use std::fs::File;
+use std::io::Write;
+
+use nu_engine::{current_dir, CallExt};
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, Spanned, SyntaxShape, Type, Value,
+};
+
+/// Clears the line the progress was reported on, leaving the cursor at its start
+const ERASE_LINE: &str = "\r\x1b[2K";
+
+#[derive(Clone)]
+pub struct Save;
+
+impl Command for Save {
+    fn name(&self) -> &str {
+        "save"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("save")
+            .required("filename", SyntaxShape::Filepath, "the file to write to")
+            .switch(
+                "quiet",
+                "don't report how much of a stream was written on a terminal",
+                Some('q'),
+            )
+            .input_type(Type::Unknown)
+            .output_type(Type::Nothing)
+            .category(Category::FileSystem)
+    }
+
+    fn usage(&self) -> &str {
+        "Save strings or binary data into a file."
+    }
+
+    fn extra_usage(&self) -> &str {
+        "Streams are written a chunk at a time as they come in, so they are never held in memory as a whole."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let filename: Spanned<String> = call.req(engine_state, stack, 0)?;
+        let path = current_dir(engine_state, stack)?.join(&filename.item);
+        let quiet = call.has_flag("quiet");
+
+        let mut file = File::create(&path).map_err(|err| {
+            ShellError::CreateNotPossible(format!("can't create it: {}", err), filename.span)
+        })?;
+
+        // Single values are written at once, so there's nothing to report for them. The progress
+        // is redrawn on the same line, which only works on a terminal
+        let report_progress = !quiet
+            && matches!(input, PipelineData::Stream(..))
+            && engine_state.output.err_is_terminal();
+        let total = input.metadata().and_then(|metadata| metadata.byte_len);
+        let mut written = 0;
+
+        for chunk in input {
+            let bytes = match &chunk {
+                Value::Binary { val, .. } => val.as_slice(),
+                Value::String { val, .. } => val.as_bytes(),
+                Value::Error { error } => return Err(error.clone()),
+                other => {
+                    return Err(ShellError::UnsupportedInput(
+                        format!(
+                            "can't save {} data, only strings and binary",
+                            other.get_type()
+                        ),
+                        other.span().unwrap_or(call.head),
+                    ))
+                }
+            };
+
+            file.write_all(bytes).map_err(|err| {
+                ShellError::CreateNotPossible(format!("can't write to it: {}", err), filename.span)
+            })?;
+            written += bytes.len();
+
+            if report_progress {
+                let progress = match total {
+                    Some(total) => format!("\rsaving: {} / {} bytes", written, total),
+                    None => format!("\rsaving: {} bytes", written),
+                };
+                let _ = engine_state.output.write_err(progress.as_bytes());
+            }
+        }
+
+        if report_progress {
+            let _ = engine_state.output.write_err(ERASE_LINE.as_bytes());
+        }
+
+        Ok(PipelineData::new(call.head))
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example {
+            description: "Save a string to a file",
+            example: r#""hello" | save hello.txt"#,
+            result: Some(Value::Nothing {
+                span: Span::unknown(),
+            }),
+            fixture: Some(&[]),
+        }]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use nu_engine::test_support::{engine_state_with, eval_source, stack_with_config};
+    use nu_protocol::engine::BufferedOutput;
+    use nu_protocol::IntoInterruptiblePipelineData;
+
+    const CHUNK_SIZE: usize = 64 * 1024;
+    const CHUNKS: usize = 64;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Save {})
+    }
+
+    /// Gives `CHUNKS` chunks of binary data, checking before each one that everything given so
+    /// far is already in the file, ie that the stream isn't collected before it's written
+    struct Chunks {
+        path: PathBuf,
+        given: usize,
+    }
+
+    impl Iterator for Chunks {
+        type Item = Value;
+
+        fn next(&mut self) -> Option<Value> {
+            if self.given > 0 {
+                let len = std::fs::metadata(&self.path).map_or(0, |meta| meta.len());
+                assert_eq!(
+                    len as usize,
+                    self.given * CHUNK_SIZE,
+                    "chunks were held back"
+                );
+            }
+            if self.given == CHUNKS {
+                return None;
+            }
+
+            self.given += 1;
+            Some(Value::Binary {
+                val: vec![self.given as u8; CHUNK_SIZE],
+                span: Span::unknown(),
+            })
+        }
+    }
+
+    fn save_chunks(name: &str, quiet: bool, terminal: bool) -> (PathBuf, Vec<u8>) {
+        let path = std::env::temp_dir().join(format!("nu-save-{}-{}", name, std::process::id()));
+        let mut engine_state = engine_state_with(vec![Box::new(Save)]);
+        let output = Arc::new(if terminal {
+            BufferedOutput::terminal()
+        } else {
+            BufferedOutput::new()
+        });
+        engine_state.output = output.clone();
+
+        let input = Chunks {
+            path: path.clone(),
+            given: 0,
+        }
+        .into_pipeline_data(None);
+        let source = format!(
+            "save {} {}",
+            if quiet { "--quiet" } else { "" },
+            path.to_string_lossy()
+        );
+        eval_source(&mut engine_state, &mut stack_with_config(), &source, input)
+            .expect("save should succeed");
+
+        (path, output.take_err())
+    }
+
+    #[test]
+    fn streams_are_written_as_they_come() {
+        let (path, progress) = save_chunks("stream", false, true);
+        let contents = std::fs::read(&path).expect("the file is saved");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(contents.len(), CHUNKS * CHUNK_SIZE);
+        assert!(contents
+            .chunks(CHUNK_SIZE)
+            .enumerate()
+            .all(|(index, chunk)| chunk.iter().all(|byte| *byte as usize == index + 1)));
+
+        let progress = String::from_utf8(progress).expect("progress is text");
+        let last = format!("saving: {} bytes", CHUNKS * CHUNK_SIZE);
+        assert!(progress.contains(&last), "progress was {:?}", progress);
+        assert!(progress.ends_with(ERASE_LINE));
+    }
+
+    #[test]
+    fn quiet_saves_dont_report_progress() {
+        for (name, quiet, terminal) in [("quiet", true, true), ("piped", false, false)] {
+            let (path, progress) = save_chunks(name, quiet, terminal);
+            let saved = std::fs::metadata(&path).map(|meta| meta.len());
+            let _ = std::fs::remove_file(&path);
+
+            assert_eq!(
+                saved.expect("the file is saved") as usize,
+                CHUNKS * CHUNK_SIZE
+            );
+            assert!(progress.is_empty(), "{} save reported progress", name);
+        }
+    }
+}
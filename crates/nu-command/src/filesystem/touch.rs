@@ -1,9 +1,12 @@
 use std::fs::OpenOptions;
 
-use nu_engine::CallExt;
+use super::util::{report_action, Report};
+use nu_engine::{current_dir, CallExt};
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{PipelineData, ShellError, Signature, SyntaxShape};
+use nu_protocol::{
+    Category, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, SyntaxShape,
+};
 
 #[derive(Clone)]
 pub struct Touch;
@@ -21,6 +24,17 @@ impl Command for Touch {
                 "the path of the file you want to create",
             )
             .rest("rest", SyntaxShape::Filepath, "additional files to create")
+            .switch(
+                "verbose",
+                "report each path as a {path, action, status} row",
+                Some('v'),
+            )
+            .switch(
+                "continue-on-error",
+                "report failures as rows instead of stopping",
+                None,
+            )
+            .category(Category::FileSystem)
     }
 
     fn usage(&self) -> &str {
@@ -37,18 +51,36 @@ impl Command for Touch {
         let target: String = call.req(engine_state, stack, 0)?;
         let rest: Vec<String> = call.rest(engine_state, stack, 1)?;
 
+        let report = if call.has_flag("verbose") {
+            Report::Rows
+        } else {
+            Report::Quiet
+        };
+        let continue_on_error = call.has_flag("continue-on-error");
+        let cwd = current_dir(engine_state, stack)?;
+
+        let mut output = vec![];
         for (index, item) in vec![target].into_iter().chain(rest).enumerate() {
-            match OpenOptions::new().write(true).create(true).open(&item) {
-                Ok(_) => continue,
-                Err(err) => {
-                    return Err(ShellError::CreateNotPossible(
-                        format!("Failed to create file: {}", err),
-                        call.positional[index].span,
-                    ));
-                }
+            let span = call.positional[index].span;
+            let path = cwd.join(item);
+            let result = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .open(&path)
+                .map(|_| ())
+                .map_err(|err| {
+                    ShellError::CreateNotPossible(format!("Failed to create file: {}", err), span)
+                });
+
+            if let Some(row) =
+                report_action(&path, "create", result, report, continue_on_error, span)?
+            {
+                output.push(row);
             }
         }
 
-        Ok(PipelineData::new(call.head))
+        Ok(output
+            .into_iter()
+            .into_pipeline_data(engine_state.ctrlc.clone()))
     }
 }
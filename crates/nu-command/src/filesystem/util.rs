@@ -1,10 +1,11 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
+use glob::GlobError;
 use nu_path::canonicalize_with;
-use nu_protocol::ShellError;
-
-use dialoguer::Input;
-use std::error::Error;
+use nu_protocol::ast::{Call, Expr, Expression};
+use nu_protocol::engine::EngineState;
+use nu_protocol::{ShellError, Span, Value};
 
 #[derive(Default)]
 pub struct FileStructure {
@@ -83,26 +84,316 @@ pub struct Resource {
 
 impl Resource {}
 
-pub fn get_interactive_confirmation(prompt: String) -> Result<bool, Box<dyn Error>> {
-    let input = Input::new()
-        .with_prompt(prompt)
-        .validate_with(|c_input: &String| -> Result<(), String> {
-            if c_input.len() == 1
-                && (c_input == "y" || c_input == "Y" || c_input == "n" || c_input == "N")
-            {
-                Ok(())
-            } else if c_input.len() > 1 {
-                Err("Enter only one letter (Y/N)".to_string())
-            } else {
-                Err("Input not valid".to_string())
+/// Fail up front when `--interactive` was given but there is nobody to ask, rather than hanging
+/// on a prompt or silently going ahead
+pub fn check_interactive(engine_state: &EngineState, call: &Call) -> Result<(), ShellError> {
+    if engine_state.confirm.is_interactive() {
+        Ok(())
+    } else {
+        let span = call
+            .named
+            .iter()
+            .find(|(name, _)| name.item == "interactive")
+            .map_or(call.head, |(name, _)| name.span);
+
+        Err(ShellError::ConfirmationNotAvailable(span))
+    }
+}
+
+/// Where `source` ends up when it is copied or moved to `destination`
+pub fn destination_path(source: &Path, destination: &Path) -> PathBuf {
+    match source.file_name() {
+        Some(name) if destination.is_dir() => destination.join(name),
+        _ => destination.to_path_buf(),
+    }
+}
+
+/// The columns of the rows reported by filesystem commands when asked to describe what they did.
+/// Every filesystem command builds its rows through `action_record` so these never diverge.
+pub const ACTION_COLUMNS: [&str; 3] = ["path", "action", "status"];
+
+/// Build a `{path, action, status}` row describing an action taken on `path`.
+pub fn action_record(path: &Path, action: &str, succeeded: bool, span: Span) -> Value {
+    action_row(path, action, if succeeded { "ok" } else { "error" }, span)
+}
+
+/// Build the row for an action the user declined when asked to confirm it.
+fn skipped_record(path: &Path, action: &str, span: Span) -> Value {
+    action_row(path, action, "skipped", span)
+}
+
+fn action_row(path: &Path, action: &str, status: &str, span: Span) -> Value {
+    Value::Record {
+        cols: ACTION_COLUMNS.iter().map(|col| col.to_string()).collect(),
+        vals: vec![
+            Value::String {
+                val: path.to_string_lossy().to_string(),
+                span,
+            },
+            Value::string(action, span),
+            Value::string(status, span),
+        ],
+        span,
+    }
+}
+
+/// What a filesystem command outputs for each path it acted on
+#[derive(Clone, Copy)]
+pub enum Report {
+    /// Nothing
+    Quiet,
+    /// The path
+    Paths,
+    /// A line like `deleted foo.txt`, starting with what was done to the path
+    Lines(&'static str),
+    /// A `{path, action, status}` row
+    Rows,
+}
+
+/// Turn the outcome of an action into the output for its path, if there is any.
+///
+/// Successes are output the way `report` says. Failures are reported as rows when
+/// `continue_on_error` is set, and are otherwise returned as the error that stops the command.
+pub fn report_action(
+    path: &Path,
+    action: &str,
+    result: Result<(), ShellError>,
+    report: Report,
+    continue_on_error: bool,
+    span: Span,
+) -> Result<Option<Value>, ShellError> {
+    match result {
+        Ok(()) => Ok(match report {
+            Report::Quiet => None,
+            Report::Paths => Some(Value::String {
+                val: path.to_string_lossy().to_string(),
+                span,
+            }),
+            Report::Lines(done) => Some(Value::String {
+                val: format!("{} {}", done, path.to_string_lossy()),
+                span,
+            }),
+            Report::Rows => Some(action_record(path, action, true, span)),
+        }),
+        Err(_) if continue_on_error => Ok(Some(action_record(path, action, false, span))),
+        Err(error) => Err(error),
+    }
+}
+
+/// The output for a path the user declined to have `action` taken on, if there is any
+pub fn report_skipped(path: &Path, action: &str, report: Report, span: Span) -> Option<Value> {
+    match report {
+        Report::Quiet | Report::Paths => None,
+        Report::Lines(_) => Some(Value::String {
+            val: format!("skipped {}", path.to_string_lossy()),
+            span,
+        }),
+        Report::Rows => Some(skipped_record(path, action, span)),
+    }
+}
+
+/// The patterns a glob argument stands for. Braces are only expanded in a pattern written out
+/// bare, so quoted patterns, and strings from elsewhere like variables, match braces literally
+pub fn glob_patterns(
+    engine_state: &EngineState,
+    arg: &Expression,
+    pattern: &str,
+) -> Result<Vec<String>, ShellError> {
+    let written_bare = matches!(arg.expr, Expr::GlobPattern(_))
+        && engine_state
+            .try_get_span_contents(&arg.span)
+            .map_or(false, |contents| {
+                !matches!(contents.first(), Some(b'\'') | Some(b'"') | Some(b'`'))
+            });
+
+    if written_bare {
+        expand_braces(pattern, arg.span)
+    } else {
+        Ok(vec![pattern.to_string()])
+    }
+}
+
+/// The patterns `pattern` stands for once its braces are expanded, eg `*.{rs,toml}` is `*.rs`
+/// and `*.toml`. Braces nest, and the ones in a character class like `[{]` are literal.
+/// Unbalanced braces are an error at `span`
+pub fn expand_braces(pattern: &str, span: Span) -> Result<Vec<String>, ShellError> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut open = 0;
+    let mut depth = 0;
+    let mut commas = vec![];
+
+    let mut idx = 0;
+    while idx < chars.len() {
+        match chars[idx] {
+            '[' => idx = class_end(&chars, idx),
+            '{' => {
+                if depth == 0 {
+                    open = idx;
+                }
+                depth += 1;
+            }
+            ',' if depth == 1 => commas.push(idx),
+            '}' if depth == 0 => {
+                return Err(ShellError::InvalidGlobPattern(
+                    "there is a '}' without a '{'".into(),
+                    span,
+                ))
+            }
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    // Expand the first group, and the rest of the pattern with each alternative
+                    let prefix: String = chars[..open].iter().collect();
+                    let suffix: String = chars[idx + 1..].iter().collect();
+                    let mut bounds = vec![open];
+                    bounds.append(&mut commas);
+                    bounds.push(idx);
+
+                    let mut patterns = vec![];
+                    for alternative in bounds.windows(2) {
+                        let alternative: String =
+                            chars[alternative[0] + 1..alternative[1]].iter().collect();
+                        patterns.append(&mut expand_braces(
+                            &format!("{}{}{}", prefix, alternative, suffix),
+                            span,
+                        )?);
+                    }
+
+                    return Ok(patterns);
+                }
             }
+            _ => {}
+        }
+
+        idx += 1;
+    }
+
+    if depth > 0 {
+        Err(ShellError::InvalidGlobPattern(
+            "there is a '{' without a '}'".into(),
+            span,
+        ))
+    } else {
+        Ok(vec![pattern.to_string()])
+    }
+}
+
+/// The index of the `]` closing the character class opened at `start`. A `]` right after the
+/// `[` (or `[!`) is part of the class. Without a closing `]`, the `[` is just a character
+fn class_end(chars: &[char], start: usize) -> usize {
+    let mut idx = start + 1;
+    if chars.get(idx) == Some(&'!') {
+        idx += 1;
+    }
+    if chars.get(idx) == Some(&']') {
+        idx += 1;
+    }
+
+    match chars[idx.min(chars.len())..].iter().position(|c| *c == ']') {
+        Some(offset) => idx + offset,
+        None => start,
+    }
+}
+
+/// The paths matching any of `patterns`, each once, in the order of the patterns. A pattern glob
+/// can't parse, like `[a`, is an error at `span`
+pub fn glob_paths(
+    patterns: &[String],
+    span: Span,
+) -> Result<impl Iterator<Item = Result<PathBuf, GlobError>> + Send, ShellError> {
+    let globs = patterns
+        .iter()
+        .map(|pattern| {
+            glob::glob(pattern)
+                .map_err(|err| ShellError::InvalidGlobPattern(err.msg.to_string(), span))
         })
-        .default("Y/N".into())
-        .interact_text()?;
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut seen = HashSet::new();
+    Ok(globs
+        .into_iter()
+        .flatten()
+        .filter(move |entry| match entry {
+            Ok(path) => seen.insert(path.clone()),
+            Err(_) => true,
+        }))
+}
 
-    if input == "y" || input == "Y" {
-        Ok(true)
+/// The paths a glob argument matches, with its patterns resolved against `cwd`
+pub fn glob_arg(
+    engine_state: &EngineState,
+    cwd: &Path,
+    arg: &Expression,
+    pattern: &str,
+) -> Result<impl Iterator<Item = Result<PathBuf, GlobError>> + Send, ShellError> {
+    let patterns: Vec<String> = glob_patterns(engine_state, arg, pattern)?
+        .iter()
+        .map(|pattern| in_dir(cwd, pattern))
+        .collect();
+
+    glob_paths(&patterns, arg.span)
+}
+
+/// `pattern` resolved against `dir`. The directory is escaped, so a name like `[draft]` in it
+/// is matched as it is rather than as a character class
+pub fn in_dir(dir: &Path, pattern: &str) -> String {
+    if Path::new(pattern).is_absolute() {
+        pattern.to_string()
     } else {
-        Ok(false)
+        let dir = glob::Pattern::escape(&dir.to_string_lossy());
+        Path::new(&dir).join(pattern).to_string_lossy().to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn expanded(pattern: &str) -> Vec<String> {
+        expand_braces(pattern, Span::unknown()).expect("balanced braces")
+    }
+
+    #[test]
+    fn expands_each_alternative() {
+        assert_eq!(expanded("*.rs"), vec!["*.rs"]);
+        assert_eq!(expanded("*.{rs,toml}"), vec!["*.rs", "*.toml"]);
+        assert_eq!(
+            expanded("{src,tests}/*.{rs,md}"),
+            vec!["src/*.rs", "src/*.md", "tests/*.rs", "tests/*.md"]
+        );
+        assert_eq!(expanded("a{b,c{d,e}}"), vec!["ab", "acd", "ace"]);
+        assert_eq!(expanded("file{,.bak}"), vec!["file", "file.bak"]);
+    }
+
+    #[test]
+    fn braces_in_character_classes_are_literal() {
+        assert_eq!(expanded("[{]*"), vec!["[{]*"]);
+        assert_eq!(expanded("[]{]{a,b}"), vec!["[]{]a", "[]{]b"]);
+        assert_eq!(expanded("[!}]"), vec!["[!}]"]);
+    }
+
+    #[test]
+    fn unbalanced_braces_are_an_error() {
+        for pattern in ["*.{rs,toml", "*.rs}", "{a}}"] {
+            assert!(matches!(
+                expand_braces(pattern, Span::unknown()),
+                Err(ShellError::InvalidGlobPattern(..))
+            ));
+        }
+    }
+
+    #[test]
+    fn directories_are_matched_as_they_are() {
+        let dir = std::env::temp_dir().join(format!("nu-glob-[{}]", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("the directory is created");
+        std::fs::write(dir.join("a.txt"), "").expect("the file is created");
+
+        let found: Vec<PathBuf> = glob_paths(&[in_dir(&dir, "*.txt")], Span::unknown())
+            .expect("the pattern is valid")
+            .filter_map(Result::ok)
+            .collect();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(found, vec![dir.join("a.txt")]);
     }
 }
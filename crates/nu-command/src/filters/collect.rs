@@ -58,6 +58,7 @@ impl Command for Collect {
             description: "Use the second value in the stream",
             example: "echo 1 2 3 | collect { |x| echo $x.1 }",
             result: Some(Value::test_int(2)),
+            ..Default::default()
         }]
     }
 }
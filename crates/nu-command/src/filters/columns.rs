@@ -0,0 +1,110 @@
+use crate::table_columns;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{Example, IntoPipelineData, PipelineData, ShellError, Signature, Span, Value};
+
+#[derive(Clone)]
+pub struct Columns;
+
+impl Command for Columns {
+    fn name(&self) -> &str {
+        "columns"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("columns").switch(
+            "all",
+            "use the columns of every row of a table, not just the first",
+            Some('a'),
+        )
+    }
+
+    fn usage(&self) -> &str {
+        "Show the column names of a record or table."
+    }
+
+    fn run(
+        &self,
+        _engine_state: &EngineState,
+        _stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let span = call.head;
+        let all = call.has_flag("all");
+
+        let columns = match input {
+            PipelineData::Value(Value::Record { cols, .. }) => cols,
+            PipelineData::Value(Value::List { vals, .. }) => table_columns(&vals, all),
+            PipelineData::Stream(stream) => table_columns(&stream.collect::<Vec<_>>(), all),
+            PipelineData::Value(other) => {
+                return Err(ShellError::UnsupportedInput(
+                    "only records and tables have columns".into(),
+                    other.span().unwrap_or(span),
+                ))
+            }
+        };
+
+        Ok(Value::List {
+            vals: columns
+                .into_iter()
+                .map(|col| Value::String { val: col, span })
+                .collect(),
+            span,
+        }
+        .into_pipeline_data())
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Get the column names of a record",
+                example: "{name: nu, age: 3} | columns",
+                result: Some(Value::List {
+                    vals: vec![Value::test_string("name"), Value::test_string("age")],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Get the column names of a table, from its first row",
+                example: "[[a b]; [1 2]] | columns",
+                result: Some(Value::List {
+                    vals: vec![Value::test_string("a"), Value::test_string("b")],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Get the column names used by any row of a table",
+                example: "[{a: 1} {b: 2, a: 3}] | columns --all",
+                result: Some(Value::List {
+                    vals: vec![Value::test_string("a"), Value::test_string("b")],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "An empty table has no columns",
+                example: "[] | columns",
+                result: Some(Value::List {
+                    vals: vec![],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Columns {})
+    }
+}
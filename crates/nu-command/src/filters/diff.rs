@@ -0,0 +1,436 @@
+use nu_engine::CallExt;
+use nu_protocol::ast::{Call, CellPath, PathMember};
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Example, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, Span, SyntaxShape,
+    Value,
+};
+
+/// How deep nested records and lists are compared when no `--depth` is given
+const DEFAULT_DEPTH: usize = 64;
+
+#[derive(Clone)]
+pub struct Diff;
+
+impl Command for Diff {
+    fn name(&self) -> &str {
+        "diff"
+    }
+
+    fn usage(&self) -> &str {
+        "Compare the input with another value, and list where they differ."
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("diff")
+            .required("other", SyntaxShape::Any, "the value to compare with")
+            .switch(
+                "unordered",
+                "match list items by equality instead of by position",
+                Some('u'),
+            )
+            .named(
+                "depth",
+                SyntaxShape::Int,
+                "how many levels of nested records and lists to descend into, defaults to 64",
+                Some('d'),
+            )
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"Each difference is a row with the cell path it was found at, the value in the input (left) and
+in the other value (right), and whether it was added, removed or changed. Values nested deeper
+than --depth are compared as a whole."#
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Compare two records",
+                example: "{a: 1, b: {c: 2}} | diff {a: 1, b: {c: 3}, d: 4}",
+                result: Some(Value::List {
+                    vals: vec![
+                        difference("b.c", Value::test_int(2), Value::test_int(3), "changed"),
+                        difference("d", nothing(), Value::test_int(4), "added"),
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Compare lists without caring about the order of their items",
+                example: "[1 2 3] | diff --unordered [3 1 4]",
+                result: Some(Value::List {
+                    vals: vec![
+                        difference("1", Value::test_int(2), nothing(), "removed"),
+                        difference("2", nothing(), Value::test_int(4), "added"),
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let head = call.head;
+        let other: Value = call.req(engine_state, stack, 0)?;
+        let unordered = call.has_flag("unordered");
+        let depth = match call.get_flag::<i64>(engine_state, stack, "depth")? {
+            Some(depth) if depth < 0 => {
+                return Err(ShellError::UnsupportedInput(
+                    "the depth can't be negative".into(),
+                    call.get_flag_expr("depth").map_or(head, |expr| expr.span),
+                ))
+            }
+            Some(depth) => depth as usize,
+            None => DEFAULT_DEPTH,
+        };
+
+        let value = input.into_value(head);
+        if let Value::Error { error } = value {
+            return Err(error);
+        }
+
+        let mut differ = Differ {
+            unordered,
+            max_depth: depth,
+            path: vec![],
+            differences: vec![],
+            span: head,
+        };
+        differ.compare(&value, &other);
+
+        Ok(differ
+            .differences
+            .into_iter()
+            .into_pipeline_data(engine_state.ctrlc.clone()))
+    }
+}
+
+struct Differ {
+    unordered: bool,
+    max_depth: usize,
+    /// Where the values being compared are, from the top
+    path: Vec<PathMember>,
+    differences: Vec<Value>,
+    span: Span,
+}
+
+impl Differ {
+    fn compare(&mut self, left: &Value, right: &Value) {
+        let descend = self.path.len() < self.max_depth;
+
+        match (left, right) {
+            (
+                Value::Record {
+                    cols: left_cols,
+                    vals: left_vals,
+                    ..
+                },
+                Value::Record {
+                    cols: right_cols,
+                    vals: right_vals,
+                    ..
+                },
+            ) if descend => {
+                for (col, left) in left_cols.iter().zip(left_vals) {
+                    self.path.push(PathMember::String {
+                        val: col.clone(),
+                        span: self.span,
+                    });
+                    match right_cols.iter().position(|right_col| right_col == col) {
+                        Some(idx) => self.compare(left, &right_vals[idx]),
+                        None => self.removed(left),
+                    }
+                    self.path.pop();
+                }
+
+                for (col, right) in right_cols.iter().zip(right_vals) {
+                    if !left_cols.contains(col) {
+                        self.path.push(PathMember::String {
+                            val: col.clone(),
+                            span: self.span,
+                        });
+                        self.added(right);
+                        self.path.pop();
+                    }
+                }
+            }
+            (Value::List { vals: left, .. }, Value::List { vals: right, .. }) if descend => {
+                if self.unordered {
+                    self.compare_unordered(left, right)
+                } else {
+                    self.compare_positional(left, right)
+                }
+            }
+            (left, right) => {
+                if !same(left, right) {
+                    self.push(left.clone(), right.clone(), "changed");
+                }
+            }
+        }
+    }
+
+    fn compare_positional(&mut self, left: &[Value], right: &[Value]) {
+        for idx in 0..left.len().max(right.len()) {
+            self.path.push(PathMember::Int {
+                val: idx,
+                span: self.span,
+            });
+            match (left.get(idx), right.get(idx)) {
+                (Some(left), Some(right)) => self.compare(left, right),
+                (Some(left), None) => self.removed(left),
+                (None, Some(right)) => self.added(right),
+                (None, None) => {}
+            }
+            self.path.pop();
+        }
+    }
+
+    /// Items are matched with an equal item on the other side, if there's one left. The ones that
+    /// aren't are reported at their own index
+    fn compare_unordered(&mut self, left: &[Value], right: &[Value]) {
+        let mut matched = vec![false; right.len()];
+
+        for (idx, left) in left.iter().enumerate() {
+            let found = right
+                .iter()
+                .enumerate()
+                .position(|(right_idx, right)| !matched[right_idx] && same(left, right));
+
+            match found {
+                Some(right_idx) => matched[right_idx] = true,
+                None => {
+                    self.path.push(PathMember::Int {
+                        val: idx,
+                        span: self.span,
+                    });
+                    self.removed(left);
+                    self.path.pop();
+                }
+            }
+        }
+
+        for (idx, right) in right.iter().enumerate() {
+            if !matched[idx] {
+                self.path.push(PathMember::Int {
+                    val: idx,
+                    span: self.span,
+                });
+                self.added(right);
+                self.path.pop();
+            }
+        }
+    }
+
+    fn added(&mut self, right: &Value) {
+        self.push(Value::Nothing { span: self.span }, right.clone(), "added");
+    }
+
+    fn removed(&mut self, left: &Value) {
+        self.push(left.clone(), Value::Nothing { span: self.span }, "removed");
+    }
+
+    fn push(&mut self, left: Value, right: Value, change: &str) {
+        let path = CellPath {
+            members: self.path.clone(),
+        };
+
+        self.differences.push(Value::Record {
+            cols: vec![
+                "path".into(),
+                "left".into(),
+                "right".into(),
+                "change".into(),
+            ],
+            vals: vec![
+                Value::string(path.into_string(), self.span),
+                left,
+                right,
+                Value::string(change, self.span),
+            ],
+            span: self.span,
+        });
+    }
+}
+
+/// Values are the same if they are equal and of the same type, so that eg `1` and `1.0` differ
+fn same(left: &Value, right: &Value) -> bool {
+    left.get_type() == right.get_type() && left == right
+}
+
+fn nothing() -> Value {
+    Value::Nothing {
+        span: Span::unknown(),
+    }
+}
+
+fn difference(path: &str, left: Value, right: Value, change: &str) -> Value {
+    Value::Record {
+        cols: vec![
+            "path".into(),
+            "left".into(),
+            "right".into(),
+            "change".into(),
+        ],
+        vals: vec![
+            Value::test_string(path),
+            left,
+            right,
+            Value::test_string(change),
+        ],
+        span: Span::unknown(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn diff(left: Value, right: Value, unordered: bool, max_depth: usize) -> Vec<Value> {
+        let mut differ = Differ {
+            unordered,
+            max_depth,
+            path: vec![],
+            differences: vec![],
+            span: Span::unknown(),
+        };
+        differ.compare(&left, &right);
+        differ.differences
+    }
+
+    fn record(cols: &[&str], vals: Vec<Value>) -> Value {
+        Value::Record {
+            cols: cols.iter().map(|col| col.to_string()).collect(),
+            vals,
+            span: Span::unknown(),
+        }
+    }
+
+    fn list(vals: Vec<Value>) -> Value {
+        Value::List {
+            vals,
+            span: Span::unknown(),
+        }
+    }
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Diff {})
+    }
+
+    #[test]
+    fn finds_nested_additions_and_removals() {
+        let left = record(
+            &["a"],
+            vec![record(
+                &["b", "c"],
+                vec![Value::test_int(1), list(vec![Value::test_int(1)])],
+            )],
+        );
+        let right = record(
+            &["a"],
+            vec![record(
+                &["c", "d"],
+                vec![
+                    list(vec![Value::test_int(1), Value::test_int(2)]),
+                    Value::test_int(3),
+                ],
+            )],
+        );
+
+        assert_eq!(
+            diff(left, right, false, DEFAULT_DEPTH),
+            vec![
+                difference("a.b", Value::test_int(1), nothing(), "removed"),
+                difference("a.c.1", nothing(), Value::test_int(2), "added"),
+                difference("a.d", nothing(), Value::test_int(3), "added"),
+            ]
+        );
+    }
+
+    #[test]
+    fn type_changes_are_changes() {
+        let left = record(&["a", "b"], vec![Value::test_int(1), Value::test_int(2)]);
+        let right = record(
+            &["a", "b"],
+            vec![
+                Value::Float {
+                    val: 1.0,
+                    span: Span::unknown(),
+                },
+                list(vec![Value::test_int(2)]),
+            ],
+        );
+
+        assert_eq!(
+            diff(left, right, false, DEFAULT_DEPTH),
+            vec![
+                difference(
+                    "a",
+                    Value::test_int(1),
+                    Value::Float {
+                        val: 1.0,
+                        span: Span::unknown(),
+                    },
+                    "changed"
+                ),
+                difference(
+                    "b",
+                    Value::test_int(2),
+                    list(vec![Value::test_int(2)]),
+                    "changed"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unordered_lists_match_items_by_equality() {
+        let left = list(vec![
+            Value::test_string("a"),
+            Value::test_string("b"),
+            Value::test_string("b"),
+        ]);
+        let right = list(vec![
+            Value::test_string("b"),
+            Value::test_string("c"),
+            Value::test_string("a"),
+        ]);
+
+        assert_eq!(
+            diff(left.clone(), right.clone(), true, DEFAULT_DEPTH),
+            vec![
+                difference("2", Value::test_string("b"), nothing(), "removed"),
+                difference("1", nothing(), Value::test_string("c"), "added"),
+            ]
+        );
+        assert_eq!(diff(left, right, false, DEFAULT_DEPTH).len(), 3);
+    }
+
+    #[test]
+    fn values_deeper_than_the_depth_are_compared_whole() {
+        let left = record(&["a"], vec![record(&["b"], vec![Value::test_int(1)])]);
+        let right = record(&["a"], vec![record(&["b"], vec![Value::test_int(2)])]);
+
+        assert_eq!(
+            diff(left.clone(), right.clone(), false, 1),
+            vec![difference(
+                "a",
+                record(&["b"], vec![Value::test_int(1)]),
+                record(&["b"], vec![Value::test_int(2)]),
+                "changed"
+            )]
+        );
+        assert_eq!(diff(left.clone(), left, false, 1), vec![]);
+    }
+}
@@ -1,9 +1,13 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use super::row_block::RowBlock;
 use nu_engine::eval_block;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{
-    Example, IntoInterruptiblePipelineData, IntoPipelineData, PipelineData, Signature, Span,
-    SyntaxShape, Value,
+    Example, IntoInterruptiblePipelineData, IntoPipelineData, PipelineData, ShellError, Signature,
+    Span, SyntaxShape, Value,
 };
 
 #[derive(Clone)]
@@ -26,6 +30,17 @@ impl Command for Each {
                 "the block to run",
             )
             .switch("numbered", "iterate with an index", Some('n'))
+            .switch(
+                "keep-going",
+                "turn rows the block fails on into errors and carry on with the rest",
+                Some('k'),
+            )
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"The first row the block fails on stops the pipeline, with an error saying which row it
+was. With --keep-going, the failures are passed on as error values instead, so every row is
+processed and the errors can be filtered out further down the pipeline."#
     }
 
     fn examples(&self) -> Vec<Example> {
@@ -44,14 +59,42 @@ impl Command for Each {
             },
         ];
 
-        vec![Example {
-            example: "[1 2 3] | each { 2 * $it }",
-            description: "Multiplies elements in list",
-            result: Some(Value::List {
-                vals: stream_test_1,
-                span: Span::unknown(),
-            }),
-        }]
+        vec![
+            Example {
+                example: "[1 2 3] | each { 2 * $it }",
+                description: "Multiplies elements in list",
+                result: Some(Value::List {
+                    vals: stream_test_1,
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                example: "[2 0 4] | each { 4 / $it }",
+                description: "Fails at the first row the block fails on",
+                result: None,
+                ..Default::default()
+            },
+            Example {
+                example: "[2 0 4] | each --keep-going { 4 / $it }",
+                description: "Carries on past the rows the block fails on, which become errors",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::test_int(2),
+                        Value::Error {
+                            error: ShellError::InRow {
+                                row: 1,
+                                span: Span::unknown(),
+                                cause: vec![ShellError::DivisionByZero(Span::unknown())],
+                            },
+                        },
+                        Value::test_int(1),
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
     }
 
     fn run(
@@ -66,56 +109,62 @@ impl Command for Each {
             .expect("internal error: expected block");
 
         let numbered = call.has_flag("numbered");
+        let keep_going = call.has_flag("keep-going");
         let ctrlc = engine_state.ctrlc.clone();
-        let engine_state = engine_state.clone();
-        let block = engine_state.get_block(block_id).clone();
-        let mut stack = stack.collect_captures(&block.captures);
         let span = call.head;
 
         match input {
             PipelineData::Value(Value::Range { .. })
             | PipelineData::Value(Value::List { .. })
-            | PipelineData::Stream { .. } => Ok(input
-                .into_iter()
-                .enumerate()
-                .map(move |(idx, x)| {
-                    if let Some(var) = block.signature.get_positional(0) {
-                        if let Some(var_id) = &var.var_id {
-                            if numbered {
-                                stack.add_var(
-                                    *var_id,
-                                    Value::Record {
-                                        cols: vec!["index".into(), "item".into()],
-                                        vals: vec![
-                                            Value::Int {
-                                                val: idx as i64,
-                                                span,
-                                            },
-                                            x,
-                                        ],
+            | PipelineData::Stream { .. } => {
+                let mut row_block = RowBlock::new(engine_state, stack, call);
+                let endless = Arc::new(AtomicBool::new(input.is_endless()));
+                let still_endless = endless.clone();
+                let engine_state = engine_state.clone();
+
+                Ok(input
+                    .into_iter()
+                    .enumerate()
+                    .scan((), move |_, (idx, x)| {
+                        let param = if numbered {
+                            Value::Record {
+                                cols: vec!["index".into(), "item".into()],
+                                vals: vec![
+                                    Value::Int {
+                                        val: idx as i64,
                                         span,
                                     },
-                                );
-                            } else {
-                                stack.add_var(*var_id, x);
+                                    x,
+                                ],
+                                span,
                             }
-                        }
-                    }
+                        } else {
+                            x
+                        };
 
-                    match eval_block(&engine_state, &mut stack, &block, PipelineData::new(span)) {
-                        Ok(v) => v.into_value(span),
-                        Err(error) => Value::Error { error },
-                    }
-                })
-                .into_pipeline_data(ctrlc)),
+                        // Nothing after a failed row is evaluated, unless asked to keep going.
+                        // The stream was already handed on, so the error fails what drains it
+                        match row_block.eval(idx, param) {
+                            Ok(v) => Some(v),
+                            Err(error) if keep_going => Some(Value::Error { error }),
+                            Err(error) => {
+                                still_endless.store(false, Ordering::SeqCst);
+                                engine_state.abort(error);
+                                None
+                            }
+                        }
+                    })
+                    .into_pipeline_data(ctrlc)
+                    .share_endless(endless))
+            }
             PipelineData::Value(Value::Record { cols, vals, .. }) => {
                 let mut output_cols = vec![];
                 let mut output_vals = vec![];
+                let block = engine_state.get_block(block_id);
+                let captured = stack.collect_captures(&block.captures);
 
                 for (col, val) in cols.into_iter().zip(vals.into_iter()) {
-                    let block = engine_state.get_block(block_id);
-
-                    let mut stack = stack.clone();
+                    let mut stack = captured.clone();
 
                     if let Some(var) = block.signature.get_positional(0) {
                         if let Some(var_id) = &var.var_id {
@@ -136,7 +185,7 @@ impl Command for Each {
                         }
                     }
 
-                    match eval_block(&engine_state, &mut stack, block, PipelineData::new(span))? {
+                    match eval_block(engine_state, &mut stack, block, PipelineData::new(span))? {
                         PipelineData::Value(Value::Record {
                             mut cols, mut vals, ..
                         }) => {
@@ -160,6 +209,7 @@ impl Command for Each {
             }
             PipelineData::Value(x) => {
                 let block = engine_state.get_block(block_id);
+                let mut stack = stack.collect_captures(&block.captures);
 
                 if let Some(var) = block.signature.get_positional(0) {
                     if let Some(var_id) = &var.var_id {
@@ -167,7 +217,7 @@ impl Command for Each {
                     }
                 }
 
-                eval_block(&engine_state, &mut stack, block, PipelineData::new(span))
+                eval_block(engine_state, &mut stack, block, PipelineData::new(span))
             }
         }
     }
@@ -177,10 +227,83 @@ impl Command for Each {
 mod test {
     use super::*;
 
+    use nu_engine::test_support::{engine_state_with, eval_source, stack_with_config};
+
     #[test]
     fn test_examples() {
         use crate::test_examples;
 
         test_examples(Each {})
     }
+
+    #[test]
+    fn a_failed_row_aborts_what_drains_the_rows() {
+        let mut engine_state = engine_state_with(vec![Box::new(Each)]);
+        let rows: Vec<Value> = eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            "[1 0 2] | each { 10 / $it }",
+            PipelineData::new(Span::unknown()),
+        )
+        .expect("the command runs")
+        .into_iter()
+        .collect();
+
+        assert_eq!(rows, vec![Value::test_int(10)]);
+        assert!(matches!(
+            engine_state.take_abort(),
+            Some(ShellError::InRow { row: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn failed_rows_are_kept_with_keep_going() {
+        let mut engine_state = engine_state_with(vec![Box::new(Each)]);
+        let rows: Vec<Value> = eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            "[1 0 2] | each --keep-going { 10 / $it }",
+            PipelineData::new(Span::unknown()),
+        )
+        .expect("the command runs")
+        .into_iter()
+        .collect();
+
+        assert!(matches!(
+            rows.as_slice(),
+            [
+                _,
+                Value::Error {
+                    error: ShellError::InRow { row: 1, .. }
+                },
+                _
+            ]
+        ));
+        assert_eq!(rows[2], Value::test_int(5));
+        assert!(engine_state.take_abort().is_none());
+    }
+
+    #[test]
+    fn rows_of_a_range_without_an_end_come_to_one_at_a_failed_row() {
+        let mut engine_state = engine_state_with(vec![Box::new(Each)]);
+        let mut output = eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            "1.. | each { 10 / (3 - $it) }",
+            PipelineData::new(Span::unknown()),
+        )
+        .expect("the command runs");
+        assert!(output.is_endless());
+
+        match &mut output {
+            PipelineData::Stream(stream) => assert_eq!(stream.by_ref().count(), 2),
+            PipelineData::Value(value) => panic!("expected a stream, got {:?}", value),
+        }
+
+        assert!(!output.is_endless());
+        assert!(matches!(
+            engine_state.take_abort(),
+            Some(ShellError::InRow { row: 2, .. })
+        ));
+    }
 }
@@ -0,0 +1,120 @@
+use super::row_block::RowBlock;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Example, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, Span, SyntaxShape,
+    Value,
+};
+
+#[derive(Clone)]
+pub struct EachWhile;
+
+impl Command for EachWhile {
+    fn name(&self) -> &str {
+        "each while"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("each while").required(
+            "block",
+            SyntaxShape::Block(Some(vec![SyntaxShape::Any])),
+            "the block to run",
+        )
+    }
+
+    fn usage(&self) -> &str {
+        "Run a block on each element of input, until it gives nothing or false."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"The output stops before the nothing or false, and the rest of the input isn't read. A row
+the block fails on ends the output with an error saying which row it was."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let mut row_block = RowBlock::new(engine_state, stack, call);
+        let mut rows = input.into_iter().enumerate();
+        let mut done = false;
+
+        Ok(std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+
+            let (idx, row) = rows.next()?;
+            match row_block.eval(idx, row) {
+                Ok(Value::Nothing { .. }) | Ok(Value::Bool { val: false, .. }) => {
+                    done = true;
+                    None
+                }
+                Ok(value) => Some(value),
+                Err(error) => {
+                    done = true;
+                    Some(Value::Error { error })
+                }
+            }
+        })
+        .into_pipeline_data(engine_state.ctrlc.clone())
+        // Stopping is the point, so even the rows of a range without an end come to one
+        .set_endless(false))
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example {
+            description: "Double the leading positive numbers",
+            example: "[1 2 3 -1 4] | each while { if $it > 0 { $it * 2 } }",
+            result: Some(Value::List {
+                vals: vec![Value::test_int(2), Value::test_int(4), Value::test_int(6)],
+                span: Span::unknown(),
+            }),
+            ..Default::default()
+        }]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::filters::row_block::test_support::run_on_counter;
+    use crate::Length;
+    use nu_engine::test_support::{engine_state_with, eval_source, stack_with_config};
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(EachWhile {})
+    }
+
+    #[test]
+    fn stops_reading_when_the_block_gives_false() {
+        let rows =
+            run_on_counter(EachWhile, "each while { $it < 2 }", 2, 10).expect("the command runs");
+
+        assert_eq!(rows, vec![Value::test_bool(true), Value::test_bool(true)]);
+    }
+
+    #[test]
+    fn what_stops_a_range_without_an_end_can_be_counted() {
+        let mut engine_state = engine_state_with(vec![Box::new(EachWhile), Box::new(Length)]);
+        let output = eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            "1.. | each while { $it < 4 } | length",
+            PipelineData::new(Span::unknown()),
+        );
+
+        assert_eq!(
+            output
+                .expect("the rows are counted")
+                .into_value(Span::unknown()),
+            Value::test_int(3)
+        );
+    }
+}
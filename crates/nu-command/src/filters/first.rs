@@ -41,6 +41,7 @@ impl Command for First {
                 description: "Return the first item of a list/table",
                 example: "[1 2 3] | first",
                 result: Some(Value::test_int(1)),
+                ..Default::default()
             },
             Example {
                 description: "Return the first 2 items of a list/table",
@@ -49,6 +50,7 @@ impl Command for First {
                     vals: vec![Value::test_int(1), Value::test_int(2)],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -1,7 +1,10 @@
+use crate::cell_path_from_value;
 use nu_engine::CallExt;
-use nu_protocol::ast::{Call, CellPath};
+use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{IntoPipelineData, PipelineData, Signature, SyntaxShape};
+use nu_protocol::{
+    Example, IntoPipelineData, PipelineData, ShellError, Signature, Span, SyntaxShape, Value,
+};
 
 #[derive(Clone)]
 pub struct Get;
@@ -16,11 +19,17 @@ impl Command for Get {
     }
 
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("get").required(
-            "cell_path",
-            SyntaxShape::CellPath,
-            "the cell path to the data",
-        )
+        Signature::build("get")
+            .required(
+                "cell_path",
+                SyntaxShape::CellPath,
+                "the cell path to the data, or a list of them",
+            )
+            .switch(
+                "literal",
+                "don't split column names from a list on dots",
+                Some('l'),
+            )
     }
 
     fn run(
@@ -30,10 +39,66 @@ impl Command for Get {
         call: &Call,
         input: PipelineData,
     ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
-        let cell_path: CellPath = call.req(engine_state, stack, 0)?;
+        let literal = call.has_flag("literal");
+        let target: Value = call.req(engine_state, stack, 0)?;
 
-        input
-            .follow_cell_path(&cell_path.members)
-            .map(|x| x.into_pipeline_data())
+        match target {
+            // Several cell paths computed at runtime give a list with the data of each of them
+            Value::List { vals, span } => {
+                let input = input.into_value(call.head);
+
+                let output = vals
+                    .into_iter()
+                    .map(|val| {
+                        let cell_path = cell_path_from_value(val, literal)?;
+                        input.clone().follow_cell_path(&cell_path.members)
+                    })
+                    .collect::<Result<Vec<_>, ShellError>>()?;
+
+                Ok(Value::List { vals: output, span }.into_pipeline_data())
+            }
+            target => {
+                let cell_path = cell_path_from_value(target, literal)?;
+
+                input
+                    .follow_cell_path(&cell_path.members)
+                    .map(|x| x.into_pipeline_data())
+            }
+        }
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Get a column from a table",
+                example: "[[name size]; [a.txt 10] [b.txt 20]] | get size",
+                result: Some(Value::List {
+                    vals: vec![Value::test_int(10), Value::test_int(20)],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Get the data at each cell path of a list",
+                example: "let paths = [a b.c]; {a: 1, b: {c: 2}} | get $paths",
+                result: Some(Value::List {
+                    vals: vec![Value::test_int(1), Value::test_int(2)],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Get {})
     }
 }
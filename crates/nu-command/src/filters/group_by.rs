@@ -0,0 +1,477 @@
+use std::cmp::Ordering;
+use std::fmt::Write;
+
+use crate::date::utils::parse_date_from_string;
+use crate::ensure_bounded;
+use nu_engine::CallExt;
+use nu_protocol::ast::{Call, CellPath};
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Config, Example, IntoPipelineData, PipelineData, ShellError, Signature, Span, Spanned,
+    SyntaxShape, Value,
+};
+
+/// What the values are grouped by
+enum Grouping {
+    /// Each distinct value is a group
+    Value,
+    /// Dates formatted with a strftime format
+    DateFormat(Spanned<String>),
+    /// Numbers split into this many equal-width ranges
+    Blocks(usize),
+}
+
+struct Group {
+    key: String,
+    /// What the groups are sorted by with --sorted: the smallest value in the group, or the
+    /// start of its range
+    order: Value,
+    members: Vec<Value>,
+}
+
+#[derive(Clone)]
+pub struct GroupBy;
+
+impl Command for GroupBy {
+    fn name(&self) -> &str {
+        "group-by"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("group-by")
+            .optional(
+                "grouper",
+                SyntaxShape::CellPath,
+                "the column to group by, or the values themselves if it's left out",
+            )
+            .named(
+                "format",
+                SyntaxShape::String,
+                "group dates by their formatted string, eg '%Y-%m-%d' for days",
+                Some('f'),
+            )
+            .named(
+                "blocks",
+                SyntaxShape::Int,
+                "group numbers into this many equal-width ranges",
+                Some('b'),
+            )
+            .switch(
+                "sorted",
+                "order the groups by their values instead of by first occurrence",
+                Some('s'),
+            )
+            .conflicts_with("format", "blocks")
+    }
+
+    fn usage(&self) -> &str {
+        "Split a list or table into groups, as a record with a list of rows for each key."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"The groups are in the order their first rows come in, or ordered by value with --sorted.
+With --blocks, the numbers from the smallest to the largest are split into ranges with keys
+like "lo..hi". Each range includes its start, and the last one includes the largest number.
+With --format, strings are read as dates the way `date format` reads them."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let grouper: Option<CellPath> = call.opt(engine_state, stack, 0)?;
+        let format: Option<Spanned<String>> = call.get_flag(engine_state, stack, "format")?;
+        let blocks: Option<Spanned<i64>> = call.get_flag(engine_state, stack, "blocks")?;
+        let sorted = call.has_flag("sorted");
+        let config = stack.get_config()?;
+        ensure_bounded(&input, call.head)?;
+
+        let grouping = match (format, blocks) {
+            (Some(format), _) => Grouping::DateFormat(format),
+            (None, Some(Spanned { item, .. })) if item > 0 => Grouping::Blocks(item as usize),
+            (None, Some(Spanned { span, .. })) => {
+                return Err(ShellError::UnsupportedInput(
+                    "--blocks needs a positive number".into(),
+                    span,
+                ))
+            }
+            (None, None) => Grouping::Value,
+        };
+
+        let rows: Vec<Value> = input.into_iter().collect();
+        group(
+            rows,
+            grouper.as_ref(),
+            &grouping,
+            sorted,
+            &config,
+            call.head,
+        )
+        .map(|grouped| grouped.into_pipeline_data())
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Group a list of strings by their values",
+                example: "[a b a] | group-by",
+                result: Some(Value::Record {
+                    cols: vec!["a".into(), "b".into()],
+                    vals: vec![
+                        Value::List {
+                            vals: vec![Value::test_string("a"), Value::test_string("a")],
+                            span: Span::unknown(),
+                        },
+                        Value::List {
+                            vals: vec![Value::test_string("b")],
+                            span: Span::unknown(),
+                        },
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Group numbers into two ranges",
+                example: "[4 1 3 2] | group-by --blocks 2 --sorted",
+                result: Some(Value::Record {
+                    cols: vec!["1..2.5".into(), "2.5..4".into()],
+                    vals: vec![
+                        Value::List {
+                            vals: vec![Value::test_int(1), Value::test_int(2)],
+                            span: Span::unknown(),
+                        },
+                        Value::List {
+                            vals: vec![Value::test_int(4), Value::test_int(3)],
+                            span: Span::unknown(),
+                        },
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Group the times of a log by day",
+                example: "['2021-10-22 10:00:00 +00:00' '2021-10-23 08:00:00 +00:00'] | group-by --format '%d %b'",
+                result: Some(Value::Record {
+                    cols: vec!["22 Oct".into(), "23 Oct".into()],
+                    vals: vec![
+                        Value::List {
+                            vals: vec![Value::test_string("2021-10-22 10:00:00 +00:00")],
+                            span: Span::unknown(),
+                        },
+                        Value::List {
+                            vals: vec![Value::test_string("2021-10-23 08:00:00 +00:00")],
+                            span: Span::unknown(),
+                        },
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+fn group(
+    rows: Vec<Value>,
+    grouper: Option<&CellPath>,
+    grouping: &Grouping,
+    sorted: bool,
+    config: &Config,
+    head: Span,
+) -> Result<Value, ShellError> {
+    let keyed = rows
+        .into_iter()
+        .map(|row| {
+            let value = match grouper {
+                Some(grouper) => row.clone().follow_cell_path(&grouper.members)?,
+                None => row.clone(),
+            };
+            Ok((value, row))
+        })
+        .collect::<Result<Vec<_>, ShellError>>()?;
+
+    let ranges = match grouping {
+        Grouping::Blocks(count) => Some(Ranges::new(keyed.iter().map(|(value, _)| value), *count)?),
+        _ => None,
+    };
+
+    let mut groups: Vec<Group> = vec![];
+    for (value, row) in keyed {
+        let (key, order) = match (grouping, &ranges) {
+            (Grouping::Blocks(_), Some(ranges)) => ranges.key(&value)?,
+            (Grouping::DateFormat(format), _) => (format_date(&value, format)?, value),
+            _ => (value.clone().into_string(", ", config), value),
+        };
+
+        match groups.iter_mut().find(|group| group.key == key) {
+            Some(group) => {
+                if order.partial_cmp(&group.order) == Some(Ordering::Less) {
+                    group.order = order;
+                }
+                group.members.push(row);
+            }
+            None => groups.push(Group {
+                key,
+                order,
+                members: vec![row],
+            }),
+        }
+    }
+
+    if sorted {
+        // A stable sort, so groups that don't compare keep their order of first occurrence
+        groups.sort_by(|lhs, rhs| lhs.order.partial_cmp(&rhs.order).unwrap_or(Ordering::Equal));
+    }
+
+    let (cols, vals) = groups
+        .into_iter()
+        .map(|group| {
+            (
+                group.key,
+                Value::List {
+                    vals: group.members,
+                    span: head,
+                },
+            )
+        })
+        .unzip();
+
+    Ok(Value::Record {
+        cols,
+        vals,
+        span: head,
+    })
+}
+
+fn format_date(value: &Value, format: &Spanned<String>) -> Result<String, ShellError> {
+    let date = match value {
+        Value::Date { val, .. } => *val,
+        Value::String { val, span } => parse_date_from_string(val.clone())
+            .map_err(|_| ShellError::UnsupportedInput(format!("'{}' isn't a date", val), *span))?,
+        other => {
+            return Err(ShellError::UnsupportedInput(
+                format!("--format groups dates, not {}", other.get_type()),
+                other.span_or(format.span),
+            ))
+        }
+    };
+
+    // An unknown specifier in the format only shows up when it's written out
+    let mut formatted = String::new();
+    write!(formatted, "{}", date.format(&format.item))
+        .map_err(|_| ShellError::UnsupportedInput("invalid date format".into(), format.span))?;
+
+    Ok(formatted)
+}
+
+/// Equal-width ranges from the smallest to the largest number
+struct Ranges {
+    min: f64,
+    width: f64,
+    count: usize,
+}
+
+impl Ranges {
+    fn new<'a>(values: impl Iterator<Item = &'a Value>, count: usize) -> Result<Self, ShellError> {
+        let mut bounds: Option<(f64, f64)> = None;
+        for value in values {
+            let number = as_number(value)?;
+            bounds = Some(match bounds {
+                Some((min, max)) => (min.min(number), max.max(number)),
+                None => (number, number),
+            });
+        }
+
+        let (min, max) = bounds.unwrap_or((0.0, 0.0));
+        Ok(Ranges {
+            min,
+            width: (max - min) / count as f64,
+            count,
+        })
+    }
+
+    /// The key of the range a number falls in, and where the range starts
+    fn key(&self, value: &Value) -> Result<(String, Value), ShellError> {
+        let number = as_number(value)?;
+        let index = if self.width > 0.0 {
+            (((number - self.min) / self.width) as usize).min(self.count - 1)
+        } else {
+            0
+        };
+
+        let lo = self.min + self.width * index as f64;
+        let hi = self.min + self.width * (index + 1) as f64;
+        Ok((
+            format!("{}..{}", lo, hi),
+            Value::Float {
+                val: lo,
+                span: Span::unknown(),
+            },
+        ))
+    }
+}
+
+fn as_number(value: &Value) -> Result<f64, ShellError> {
+    match value {
+        Value::Int { val, .. } => Ok(*val as f64),
+        Value::Float { val, .. } => Ok(*val),
+        other => Err(ShellError::UnsupportedInput(
+            format!("--blocks groups numbers, not {}", other.get_type()),
+            other.span_or(Span::unknown()),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{DateTime, FixedOffset};
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(GroupBy {})
+    }
+
+    fn row(col: &str, val: Value) -> Value {
+        Value::Record {
+            cols: vec![col.into()],
+            vals: vec![val],
+            span: Span::unknown(),
+        }
+    }
+
+    fn column(name: &str) -> CellPath {
+        CellPath {
+            members: vec![nu_protocol::ast::PathMember::String {
+                val: name.into(),
+                span: Span::unknown(),
+            }],
+        }
+    }
+
+    /// The keys of a grouping, with the number of rows in each group
+    fn counts(grouped: Value) -> Vec<(String, usize)> {
+        match grouped {
+            Value::Record { cols, vals, .. } => cols
+                .into_iter()
+                .zip(vals)
+                .map(|(key, val)| match val {
+                    Value::List { vals, .. } => (key, vals.len()),
+                    _ => panic!("not a list"),
+                })
+                .collect(),
+            _ => panic!("not a record"),
+        }
+    }
+
+    #[test]
+    fn groups_timestamps_by_day() {
+        let log: Vec<Value> = [
+            "2021-10-23T09:00:00+00:00",
+            "2021-10-22T23:59:59+00:00",
+            "2021-10-23T12:30:00+00:00",
+            "2021-10-22T00:00:00+00:00",
+            "2021-10-24T08:15:00+00:00",
+            "2021-10-23T18:45:00+00:00",
+        ]
+        .iter()
+        .map(|time| {
+            let time: DateTime<FixedOffset> =
+                DateTime::parse_from_rfc3339(time).expect("valid date");
+            row(
+                "time",
+                Value::Date {
+                    val: time,
+                    span: Span::unknown(),
+                },
+            )
+        })
+        .collect();
+
+        let by_day = Grouping::DateFormat(Spanned {
+            item: "%Y-%m-%d".to_string(),
+            span: Span::unknown(),
+        });
+        let grouped = |sorted| {
+            group(
+                log.clone(),
+                Some(&column("time")),
+                &by_day,
+                sorted,
+                &Config::default(),
+                Span::unknown(),
+            )
+            .map(counts)
+            .expect("groups by day")
+        };
+
+        assert_eq!(
+            grouped(false),
+            vec![
+                ("2021-10-23".to_string(), 3),
+                ("2021-10-22".to_string(), 2),
+                ("2021-10-24".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            grouped(true),
+            vec![
+                ("2021-10-22".to_string(), 2),
+                ("2021-10-23".to_string(), 3),
+                ("2021-10-24".to_string(), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_numbers_into_ranges() {
+        let sizes: Vec<Value> = [30, 3, 12, 27, 0, 20, 9, 19]
+            .iter()
+            .map(|size| row("size", Value::test_int(*size)))
+            .collect();
+
+        let grouped = group(
+            sizes,
+            Some(&column("size")),
+            &Grouping::Blocks(3),
+            true,
+            &Config::default(),
+            Span::unknown(),
+        )
+        .map(counts)
+        .expect("groups by size");
+
+        assert_eq!(
+            grouped,
+            vec![
+                ("0..10".to_string(), 3),
+                ("10..20".to_string(), 2),
+                ("20..30".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn ranges_need_numbers() {
+        let grouped = group(
+            vec![Value::test_int(1), Value::test_string("spam")],
+            None,
+            &Grouping::Blocks(2),
+            false,
+            &Config::default(),
+            Span::unknown(),
+        );
+
+        assert!(matches!(
+            grouped,
+            Err(ShellError::UnsupportedInput(message, _)) if message.contains("string")
+        ));
+    }
+}
@@ -0,0 +1,323 @@
+use std::io::Write;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, ErrWriter, Stack};
+use nu_protocol::{Config, Example, PipelineData, ShellError, Signature, Span, Value};
+
+/// How deep into nested lists and records the printed form goes
+const MAX_DEPTH: usize = 3;
+/// How many items of a list or record are printed
+const MAX_WIDTH: usize = 10;
+/// How many characters of a string are printed
+const MAX_STRING_LEN: usize = 80;
+
+#[derive(Clone)]
+pub struct Inspect;
+
+impl Command for Inspect {
+    fn name(&self) -> &str {
+        "inspect"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("inspect").switch(
+            "raw",
+            "print the full debug representation of each value",
+            Some('r'),
+        )
+    }
+
+    fn usage(&self) -> &str {
+        "Print the values flowing through the pipeline to stderr, passing them on unchanged."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"Each value is printed with its index as it's pulled through, so streams stay lazy. Big
+values are shortened: nested lists and records are cut off after a few levels and items,
+marked with '…'. Use --raw to print them in full."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let raw = call.has_flag("raw");
+        let config = stack.get_config()?;
+
+        inspect_values(
+            input,
+            ErrWriter(engine_state.output.clone()),
+            raw,
+            config,
+            engine_state.ctrlc.clone(),
+        )
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "See what's passed between two commands",
+                example: "[[name size]; [a 1] [b 2]] | inspect | get size",
+                result: Some(Value::List {
+                    vals: vec![Value::test_int(1), Value::test_int(2)],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Print the full debug representation of the values",
+                example: "[1 2] | inspect --raw",
+                result: Some(Value::List {
+                    vals: vec![Value::test_int(1), Value::test_int(2)],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+/// Pass the input through, writing each value to `writer` with its index as it goes by
+fn inspect_values<W>(
+    input: PipelineData,
+    mut writer: W,
+    raw: bool,
+    config: Config,
+    ctrlc: Option<Arc<AtomicBool>>,
+) -> Result<PipelineData, ShellError>
+where
+    W: Write + Send + 'static,
+{
+    let metadata = input.metadata();
+    let mut index = 0;
+
+    input
+        .map(
+            move |value| {
+                let form = if raw {
+                    format!("{:?}", value)
+                } else {
+                    preview(&value, 0, &config)
+                };
+                // Inspecting is best effort, it mustn't break the pipeline it's looking at
+                let _ = writeln!(writer, "[{}] {}", index, form);
+                index += 1;
+
+                value
+            },
+            ctrlc,
+        )
+        .map(|output| output.set_metadata(metadata))
+}
+
+/// The printed form of a value, with nested structures and long strings cut short
+fn preview(value: &Value, depth: usize, config: &Config) -> String {
+    match value {
+        Value::List { vals, .. } => {
+            if vals.is_empty() {
+                "[]".into()
+            } else if depth >= MAX_DEPTH {
+                "[…]".into()
+            } else {
+                let items = vals.iter().map(|val| preview(val, depth + 1, config));
+                format!("[{}]", elide(items, vals.len()))
+            }
+        }
+        Value::Record { cols, vals, .. } => {
+            if cols.is_empty() {
+                "{}".into()
+            } else if depth >= MAX_DEPTH {
+                "{…}".into()
+            } else {
+                let items = cols
+                    .iter()
+                    .zip(vals)
+                    .map(|(col, val)| format!("{}: {}", col, preview(val, depth + 1, config)));
+                format!("{{{}}}", elide(items, cols.len()))
+            }
+        }
+        Value::String { val, .. } => {
+            if val.chars().count() > MAX_STRING_LEN {
+                let start: String = val.chars().take(MAX_STRING_LEN).collect();
+                format!("{:?}…", start)
+            } else {
+                format!("{:?}", val)
+            }
+        }
+        Value::Binary { val, .. } => {
+            let bytes = val.iter().map(|byte| format!("{:02x}", byte));
+            format!("0x[{}]", elide(bytes, val.len()))
+        }
+        Value::Nothing { .. } => "nothing".into(),
+        Value::Error { error } => format!("error: {}", error),
+        other => other.clone().debug_string(", ", config),
+    }
+}
+
+/// Join the first items, noting how many more were left out
+fn elide(items: impl Iterator<Item = String>, len: usize) -> String {
+    let mut shown: Vec<String> = items.take(MAX_WIDTH).collect();
+    if len > MAX_WIDTH {
+        shown.push(format!("… {} more", len - MAX_WIDTH));
+    }
+
+    shown.join(", ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use nu_protocol::{IntoInterruptiblePipelineData, IntoPipelineData};
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Inspect {})
+    }
+
+    /// Stands in for stderr, keeping what's written for the test to look at
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Captured {
+        fn lines(&self) -> Vec<String> {
+            let bytes = self.0.lock().expect("capture lock poisoned");
+            String::from_utf8_lossy(&bytes)
+                .lines()
+                .map(String::from)
+                .collect()
+        }
+    }
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0
+                .lock()
+                .expect("capture lock poisoned")
+                .extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn record(name: &str, size: i64) -> Value {
+        Value::Record {
+            cols: vec!["name".into(), "size".into()],
+            vals: vec![Value::test_string(name), Value::test_int(size)],
+            span: Span::unknown(),
+        }
+    }
+
+    fn inspected(input: PipelineData, raw: bool) -> (PipelineData, Captured) {
+        let captured = Captured::default();
+        let output = inspect_values(input, captured.clone(), raw, Config::default(), None)
+            .expect("inspect should pass the input through");
+
+        (output, captured)
+    }
+
+    #[test]
+    fn passes_values_through() {
+        let rows = vec![record("a", 1), record("b", 2)];
+        let input = Value::List {
+            vals: rows.clone(),
+            span: Span::unknown(),
+        };
+
+        let (output, captured) = inspected(input.into_pipeline_data(), false);
+        let output: Vec<Value> = output.into_iter().collect();
+
+        assert_eq!(output, rows);
+        assert_eq!(
+            captured.lines(),
+            vec![
+                r#"[0] {name: "a", size: 1}"#.to_string(),
+                r#"[1] {name: "b", size: 2}"#.to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn passes_single_values_through() {
+        let (output, captured) = inspected(Value::test_int(5).into_pipeline_data(), false);
+
+        assert!(matches!(
+            output,
+            PipelineData::Value(Value::Int { val: 5, .. })
+        ));
+        assert_eq!(captured.lines(), vec!["[0] 5".to_string()]);
+    }
+
+    #[test]
+    fn prints_as_values_are_pulled() {
+        let input = (0..).map(Value::test_int).into_pipeline_data(None);
+        let (output, captured) = inspected(input, false);
+        assert!(captured.lines().is_empty());
+
+        let mut output = output.into_iter();
+        assert_eq!(output.next(), Some(Value::test_int(0)));
+        assert_eq!(output.next(), Some(Value::test_int(1)));
+        assert_eq!(
+            captured.lines(),
+            vec!["[0] 0".to_string(), "[1] 1".to_string()]
+        );
+    }
+
+    #[test]
+    fn shortens_big_values() {
+        let wide = Value::List {
+            vals: (0..1000).map(Value::test_int).collect(),
+            span: Span::unknown(),
+        };
+        assert_eq!(
+            preview(&wide, 0, &Config::default()),
+            "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, … 990 more]"
+        );
+
+        let mut deep = Value::test_int(1);
+        for _ in 0..5 {
+            deep = Value::List {
+                vals: vec![deep],
+                span: Span::unknown(),
+            };
+        }
+        assert_eq!(preview(&deep, 0, &Config::default()), "[[[[…]]]]");
+
+        let long = Value::test_string("a".repeat(1000));
+        assert_eq!(
+            preview(&long, 0, &Config::default()),
+            format!("{:?}…", "a".repeat(MAX_STRING_LEN))
+        );
+    }
+
+    #[test]
+    fn raw_prints_everything() {
+        let input = Value::List {
+            vals: vec![Value::List {
+                vals: (0..100).map(Value::test_int).collect(),
+                span: Span::unknown(),
+            }],
+            span: Span::unknown(),
+        };
+
+        let (output, captured) = inspected(input.into_pipeline_data(), true);
+        let output: Vec<Value> = output.into_iter().collect();
+
+        assert_eq!(output.len(), 1);
+        let lines = captured.lines();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].starts_with("[0] List { vals: [Int { val: 0"));
+        assert!(lines[0].contains("Int { val: 99"));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::ensure_bounded;
 use nu_engine::CallExt;
 
 use nu_protocol::ast::Call;
@@ -36,6 +37,7 @@ impl Command for Last {
                 vals: vec![Value::test_int(2), Value::test_int(3)],
                 span: Span::unknown(),
             }),
+            ..Default::default()
         }]
     }
 
@@ -47,6 +49,8 @@ impl Command for Last {
         input: PipelineData,
     ) -> Result<PipelineData, ShellError> {
         let rows: Option<i64> = call.opt(engine_state, stack, 0)?;
+        ensure_bounded(&input, call.head)?;
+
         let v: Vec<_> = input.into_iter().collect();
         let vlen: i64 = v.len() as i64;
         let beginning_rows_to_skip = rows_to_skip(vlen, rows);
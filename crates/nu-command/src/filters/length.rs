@@ -1,3 +1,4 @@
+use crate::ensure_bounded;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{IntoPipelineData, PipelineData, Signature, Value};
@@ -25,6 +26,8 @@ impl Command for Length {
         call: &Call,
         input: PipelineData,
     ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
+        ensure_bounded(&input, call.head)?;
+
         match input {
             PipelineData::Value(Value::Nothing { .. }) => Ok(Value::Int {
                 val: 0,
@@ -0,0 +1,188 @@
+use nu_engine::eval_block_as_stream;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Example, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, Span, SyntaxShape,
+    Value,
+};
+
+#[derive(Clone)]
+pub struct Merge;
+
+impl Command for Merge {
+    fn name(&self) -> &str {
+        "merge"
+    }
+
+    fn usage(&self) -> &str {
+        "Merge the rows of a table with the rows given by a block."
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("merge").required(
+            "block",
+            SyntaxShape::Block(Some(vec![])),
+            "the block giving the rows to merge in",
+        )
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"The block runs once, when the first row is needed, and its rows are merged with the
+input rows in order. Columns of the block's rows replace the input's columns of the same name.
+Input rows past the end of the block's output are passed on unchanged."#
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example {
+            example: "[[name]; [a] [b]] | merge { [[size]; [1] [2]] }",
+            description: "Add the columns of another table",
+            result: Some(Value::List {
+                vals: vec![
+                    Value::Record {
+                        cols: vec!["name".into(), "size".into()],
+                        vals: vec![Value::test_string("a"), Value::test_int(1)],
+                        span: Span::unknown(),
+                    },
+                    Value::Record {
+                        cols: vec!["name".into(), "size".into()],
+                        vals: vec![Value::test_string("b"), Value::test_int(2)],
+                        span: Span::unknown(),
+                    },
+                ],
+                span: Span::unknown(),
+            }),
+            ..Default::default()
+        }]
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
+        let block_id = call.positional[0]
+            .as_block()
+            .expect("internal error: expected block");
+        let head = call.head;
+        let ctrlc = engine_state.ctrlc.clone();
+
+        let mut other =
+            eval_block_as_stream(engine_state, stack, block_id, PipelineData::new(head));
+
+        Ok(input
+            .into_iter()
+            .map(move |row| match other.next() {
+                Some(other) => merge_rows(row, other, head),
+                None => row,
+            })
+            .into_pipeline_data(ctrlc))
+    }
+}
+
+fn merge_rows(row: Value, other: Value, head: Span) -> Value {
+    match (row, other) {
+        (
+            Value::Record {
+                mut cols,
+                mut vals,
+                span,
+            },
+            Value::Record {
+                cols: other_cols,
+                vals: other_vals,
+                ..
+            },
+        ) => {
+            for (col, val) in other_cols.into_iter().zip(other_vals) {
+                match cols.iter().position(|c| *c == col) {
+                    Some(idx) => vals[idx] = val,
+                    None => {
+                        cols.push(col);
+                        vals.push(val);
+                    }
+                }
+            }
+
+            Value::Record { cols, vals, span }
+        }
+        (error @ Value::Error { .. }, _) | (_, error @ Value::Error { .. }) => error,
+        (Value::Record { .. }, other) | (other, _) => Value::Error {
+            error: ShellError::UnsupportedInput(
+                format!(
+                    "Can't merge a {}, only rows of tables can be merged.",
+                    other.get_type()
+                ),
+                other.span().unwrap_or(head),
+            ),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Merge {})
+    }
+
+    fn record(cols: &[&str], vals: Vec<Value>) -> Value {
+        Value::Record {
+            cols: cols.iter().map(|col| col.to_string()).collect(),
+            vals,
+            span: Span::unknown(),
+        }
+    }
+
+    #[test]
+    fn other_columns_replace_the_input_ones() {
+        let merged = merge_rows(
+            record(
+                &["name", "size"],
+                vec![Value::test_string("a"), Value::test_int(1)],
+            ),
+            record(
+                &["size", "kind"],
+                vec![Value::test_int(2), Value::test_string("file")],
+            ),
+            Span::unknown(),
+        );
+
+        assert_eq!(
+            merged.columns(),
+            vec!["name".to_string(), "size".to_string(), "kind".to_string()]
+        );
+        assert_eq!(
+            merged,
+            record(
+                &["name", "size", "kind"],
+                vec![
+                    Value::test_string("a"),
+                    Value::test_int(2),
+                    Value::test_string("file")
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn only_rows_merge() {
+        let merged = merge_rows(
+            record(&["name"], vec![Value::test_string("a")]),
+            Value::test_int(1),
+            Span::unknown(),
+        );
+
+        assert!(matches!(
+            merged,
+            Value::Error {
+                error: ShellError::UnsupportedInput(..)
+            }
+        ));
+    }
+}
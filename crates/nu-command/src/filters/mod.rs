@@ -1,33 +1,60 @@
 mod collect;
+mod columns;
+mod diff;
 mod each;
+mod each_while;
 mod first;
 mod get;
+mod group_by;
+mod inspect;
 mod last;
 mod length;
 mod lines;
+mod merge;
 mod par_each;
 mod range;
+mod reject;
 mod reverse;
+mod row_block;
 mod select;
 mod shuffle;
+mod skip;
+mod skip_while;
+mod sort;
+mod sort_by;
+mod take_while;
 mod update;
+mod values;
 mod where_;
 mod wrap;
 mod zip;
 
 pub use collect::Collect;
+pub use columns::Columns;
+pub use diff::Diff;
 pub use each::Each;
+pub use each_while::EachWhile;
 pub use first::First;
 pub use get::Get;
+pub use group_by::GroupBy;
+pub use inspect::Inspect;
 pub use last::Last;
 pub use length::Length;
 pub use lines::Lines;
+pub use merge::Merge;
 pub use par_each::ParEach;
 pub use range::Range;
+pub use reject::Reject;
 pub use reverse::Reverse;
 pub use select::Select;
 pub use shuffle::Shuffle;
+pub use skip::Skip;
+pub use skip_while::SkipWhile;
+pub use sort::Sort;
+pub use sort_by::SortBy;
+pub use take_while::TakeWhile;
 pub use update::Update;
+pub use values::Values;
 pub use where_::Where;
 pub use wrap::Wrap;
 pub use zip::Zip;
@@ -1,9 +1,12 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
 use nu_engine::eval_block;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{
-    Example, IntoInterruptiblePipelineData, IntoPipelineData, PipelineData, Signature, SyntaxShape,
-    Value,
+    Example, IntoInterruptiblePipelineData, IntoPipelineData, PipelineData, ShellError, Signature,
+    SyntaxShape, Value,
 };
 use rayon::prelude::*;
 
@@ -27,6 +30,16 @@ impl Command for ParEach {
                 "the block to run",
             )
             .switch("numbered", "iterate with an index", Some('n'))
+            .switch(
+                "keep-going",
+                "turn rows the block fails on into errors and carry on with the rest",
+                Some('k'),
+            )
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"A row the block fails on stops the pipeline, with an error saying which row it was. With
+--keep-going, the failures are passed on as error values instead."#
     }
 
     fn examples(&self) -> Vec<Example> {
@@ -34,6 +47,7 @@ impl Command for ParEach {
             example: "[1 2 3] | par-each { 2 * $it }",
             description: "Multiplies elements in list",
             result: None,
+            ..Default::default()
         }]
     }
 
@@ -49,6 +63,7 @@ impl Command for ParEach {
             .expect("internal error: expected block");
 
         let numbered = call.has_flag("numbered");
+        let keep_going = call.has_flag("keep-going");
         let ctrlc = engine_state.ctrlc.clone();
         let engine_state = engine_state.clone();
         let block = engine_state.get_block(block_id);
@@ -56,128 +71,135 @@ impl Command for ParEach {
         let span = call.head;
 
         match input {
-            PipelineData::Value(Value::Range { val, .. }) => Ok(val
-                .into_range_iter()?
-                .enumerate()
-                .par_bridge()
-                .map(move |(idx, x)| {
-                    let block = engine_state.get_block(block_id);
+            PipelineData::Value(Value::Range { val, .. }) => rows_output(
+                val.into_range_iter()?
+                    .enumerate()
+                    .par_bridge()
+                    .map(move |(idx, x)| {
+                        let block = engine_state.get_block(block_id);
 
-                    let mut stack = stack.clone();
+                        let mut stack = stack.clone();
 
-                    if let Some(var) = block.signature.get_positional(0) {
-                        if let Some(var_id) = &var.var_id {
-                            if numbered {
-                                stack.add_var(
-                                    *var_id,
-                                    Value::Record {
-                                        cols: vec!["index".into(), "item".into()],
-                                        vals: vec![
-                                            Value::Int {
-                                                val: idx as i64,
-                                                span,
-                                            },
-                                            x,
-                                        ],
-                                        span,
-                                    },
-                                );
-                            } else {
-                                stack.add_var(*var_id, x);
+                        if let Some(var) = block.signature.get_positional(0) {
+                            if let Some(var_id) = &var.var_id {
+                                if numbered {
+                                    stack.add_var(
+                                        *var_id,
+                                        Value::Record {
+                                            cols: vec!["index".into(), "item".into()],
+                                            vals: vec![
+                                                Value::Int {
+                                                    val: idx as i64,
+                                                    span,
+                                                },
+                                                x,
+                                            ],
+                                            span,
+                                        },
+                                    );
+                                } else {
+                                    stack.add_var(*var_id, x);
+                                }
                             }
                         }
-                    }
 
-                    match eval_block(&engine_state, &mut stack, block, PipelineData::new(span)) {
-                        Ok(v) => v,
-                        Err(error) => Value::Error { error }.into_pipeline_data(),
-                    }
-                })
-                .collect::<Vec<_>>()
-                .into_iter()
-                .flatten()
-                .into_pipeline_data(ctrlc)),
-            PipelineData::Value(Value::List { vals: val, .. }) => Ok(val
-                .into_iter()
-                .enumerate()
-                .par_bridge()
-                .map(move |(idx, x)| {
-                    let block = engine_state.get_block(block_id);
+                        eval_block(&engine_state, &mut stack, block, PipelineData::new(span))
+                            .map_err(|error| ShellError::InRow {
+                                row: idx,
+                                span,
+                                cause: vec![error],
+                            })
+                    })
+                    .collect::<Vec<_>>(),
+                keep_going,
+                ctrlc,
+            ),
+            PipelineData::Value(Value::List { vals: val, .. }) => rows_output(
+                val.into_iter()
+                    .enumerate()
+                    .par_bridge()
+                    .map(move |(idx, x)| {
+                        let block = engine_state.get_block(block_id);
 
-                    let mut stack = stack.clone();
+                        let mut stack = stack.clone();
 
-                    if let Some(var) = block.signature.get_positional(0) {
-                        if let Some(var_id) = &var.var_id {
-                            if numbered {
-                                stack.add_var(
-                                    *var_id,
-                                    Value::Record {
-                                        cols: vec!["index".into(), "item".into()],
-                                        vals: vec![
-                                            Value::Int {
-                                                val: idx as i64,
-                                                span,
-                                            },
-                                            x,
-                                        ],
-                                        span,
-                                    },
-                                );
-                            } else {
-                                stack.add_var(*var_id, x);
+                        if let Some(var) = block.signature.get_positional(0) {
+                            if let Some(var_id) = &var.var_id {
+                                if numbered {
+                                    stack.add_var(
+                                        *var_id,
+                                        Value::Record {
+                                            cols: vec!["index".into(), "item".into()],
+                                            vals: vec![
+                                                Value::Int {
+                                                    val: idx as i64,
+                                                    span,
+                                                },
+                                                x,
+                                            ],
+                                            span,
+                                        },
+                                    );
+                                } else {
+                                    stack.add_var(*var_id, x);
+                                }
                             }
                         }
-                    }
 
-                    match eval_block(&engine_state, &mut stack, block, PipelineData::new(span)) {
-                        Ok(v) => v,
-                        Err(error) => Value::Error { error }.into_pipeline_data(),
-                    }
-                })
-                .collect::<Vec<_>>()
-                .into_iter()
-                .flatten()
-                .into_pipeline_data(ctrlc)),
-            PipelineData::Stream(stream) => Ok(stream
-                .enumerate()
-                .par_bridge()
-                .map(move |(idx, x)| {
-                    let block = engine_state.get_block(block_id);
+                        eval_block(&engine_state, &mut stack, block, PipelineData::new(span))
+                            .map_err(|error| ShellError::InRow {
+                                row: idx,
+                                span,
+                                cause: vec![error],
+                            })
+                    })
+                    .collect::<Vec<_>>(),
+                keep_going,
+                ctrlc,
+            ),
+            PipelineData::Stream(stream) => rows_output(
+                stream
+                    .enumerate()
+                    .par_bridge()
+                    .map(move |(idx, x)| {
+                        let block = engine_state.get_block(block_id);
 
-                    let mut stack = stack.clone();
+                        let mut stack = stack.clone();
 
-                    if let Some(var) = block.signature.get_positional(0) {
-                        if let Some(var_id) = &var.var_id {
-                            if numbered {
-                                stack.add_var(
-                                    *var_id,
-                                    Value::Record {
-                                        cols: vec!["index".into(), "item".into()],
-                                        vals: vec![
-                                            Value::Int {
-                                                val: idx as i64,
-                                                span,
-                                            },
-                                            x,
-                                        ],
-                                        span,
-                                    },
-                                );
-                            } else {
-                                stack.add_var(*var_id, x);
+                        if let Some(var) = block.signature.get_positional(0) {
+                            if let Some(var_id) = &var.var_id {
+                                if numbered {
+                                    stack.add_var(
+                                        *var_id,
+                                        Value::Record {
+                                            cols: vec!["index".into(), "item".into()],
+                                            vals: vec![
+                                                Value::Int {
+                                                    val: idx as i64,
+                                                    span,
+                                                },
+                                                x,
+                                            ],
+                                            span,
+                                        },
+                                    );
+                                } else {
+                                    stack.add_var(*var_id, x);
+                                }
                             }
                         }
-                    }
 
-                    match eval_block(&engine_state, &mut stack, block, PipelineData::new(span)) {
-                        Ok(v) => v,
-                        Err(error) => Value::Error { error }.into_pipeline_data(),
-                    }
-                })
-                .collect::<Vec<_>>()
-                .into_iter()
-                .flatten()
-                .into_pipeline_data(ctrlc)),
+                        eval_block(&engine_state, &mut stack, block, PipelineData::new(span))
+                            .map_err(|error| ShellError::InRow {
+                                row: idx,
+                                span,
+                                cause: vec![error],
+                            })
+                    })
+                    .collect::<Vec<_>>(),
+                keep_going,
+                ctrlc,
+            ),
             PipelineData::Value(Value::Record { cols, vals, .. }) => {
                 let mut output_cols = vec![];
                 let mut output_vals = vec![];
@@ -243,6 +265,27 @@ impl Command for ParEach {
     }
 }
 
+/// Pass on what the block gave back for each row. Unless asked to keep going, a failed row
+/// fails the whole command
+fn rows_output(
+    outputs: Vec<Result<PipelineData, ShellError>>,
+    keep_going: bool,
+    ctrlc: Option<Arc<AtomicBool>>,
+) -> Result<PipelineData, ShellError> {
+    if keep_going {
+        Ok(outputs
+            .into_iter()
+            .flat_map(|output| {
+                output.unwrap_or_else(|error| Value::Error { error }.into_pipeline_data())
+            })
+            .into_pipeline_data(ctrlc))
+    } else {
+        let outputs = outputs.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+        Ok(outputs.into_iter().flatten().into_pipeline_data(ctrlc))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
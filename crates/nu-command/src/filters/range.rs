@@ -1,3 +1,4 @@
+use crate::ensure_bounded;
 use nu_engine::CallExt;
 
 use nu_protocol::ast::Call;
@@ -36,6 +37,7 @@ impl Command for Range {
                     vals: vec![Value::test_int(4), Value::test_int(5)],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 example: "[0,1,2,3,4,5] | range (-2)..",
@@ -44,6 +46,7 @@ impl Command for Range {
                     vals: vec![Value::test_int(4), Value::test_int(5)],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 example: "[0,1,2,3,4,5] | range (-3)..-2",
@@ -52,6 +55,7 @@ impl Command for Range {
                     vals: vec![Value::test_int(3), Value::test_int(4)],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -65,11 +69,17 @@ impl Command for Range {
     ) -> Result<PipelineData, ShellError> {
         let rows: nu_protocol::Range = call.req(engine_state, stack, 0)?;
 
+        let rows_to = if rows.is_unbounded() {
+            i64::MAX
+        } else {
+            get_range_val(rows.to)
+        };
         let rows_from = get_range_val(rows.from);
-        let rows_to = get_range_val(rows.to);
 
         // only collect the input if we have any negative indices
         if rows_from < 0 || rows_to < 0 {
+            ensure_bounded(&input, call.head)?;
+
             let v: Vec<_> = input.into_iter().collect();
             let vlen: i64 = v.len() as i64;
 
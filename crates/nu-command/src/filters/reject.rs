@@ -0,0 +1,111 @@
+use crate::{cell_paths_arg, map_values};
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+
+#[derive(Clone)]
+pub struct Reject;
+
+impl Command for Reject {
+    fn name(&self) -> &str {
+        "reject"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("reject")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "the columns to remove from the table, or a single list of column names",
+            )
+            .switch(
+                "literal",
+                "don't split column names from a list on dots",
+                Some('l'),
+            )
+    }
+
+    fn usage(&self) -> &str {
+        "Remove the given columns from the table."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
+        let columns = cell_paths_arg(engine_state, stack, call, 0, call.has_flag("literal"))?;
+
+        if columns.is_empty() {
+            return Err(ShellError::CantFindColumn(call.head, call.head));
+        }
+
+        map_values(input, engine_state.ctrlc.clone(), move |mut value| {
+            for column in &columns {
+                if let Err(error) = value.remove_data_at_cell_path(&column.members) {
+                    return Value::Error { error };
+                }
+            }
+
+            value
+        })
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Remove a column from a table",
+                example: "[[a b c]; [1 2 3]] | reject b",
+                result: Some(Value::List {
+                    vals: vec![Value::Record {
+                        cols: vec!["a".to_string(), "c".to_string()],
+                        vals: vec![Value::test_int(1), Value::test_int(3)],
+                        span: Span::unknown(),
+                    }],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Remove the columns named in a list",
+                example: "let cols = [a c]; [[a b c]; [1 2 3]] | reject $cols",
+                result: Some(Value::List {
+                    vals: vec![Value::Record {
+                        cols: vec!["b".to_string()],
+                        vals: vec![Value::test_int(2)],
+                        span: Span::unknown(),
+                    }],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Remove a column whose name contains a dot",
+                example: r#"let cols = ["a.b"]; [["a.b" c]; [1 2]] | reject --literal $cols"#,
+                result: Some(Value::List {
+                    vals: vec![Value::Record {
+                        cols: vec!["c".to_string()],
+                        vals: vec![Value::test_int(2)],
+                        span: Span::unknown(),
+                    }],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Reject {})
+    }
+}
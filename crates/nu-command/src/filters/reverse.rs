@@ -1,3 +1,4 @@
+use crate::ensure_bounded;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{
@@ -33,6 +34,7 @@ impl Command for Reverse {
                 ],
                 span: Span::unknown(),
             }),
+            ..Default::default()
         }]
     }
 
@@ -40,9 +42,11 @@ impl Command for Reverse {
         &self,
         engine_state: &EngineState,
         _stack: &mut Stack,
-        _call: &Call,
+        call: &Call,
         input: PipelineData,
     ) -> Result<PipelineData, ShellError> {
+        ensure_bounded(&input, call.head)?;
+
         #[allow(clippy::needless_collect)]
         let v: Vec<_> = input.into_iter().collect();
         let iter = v.into_iter().rev();
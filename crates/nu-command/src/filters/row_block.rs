@@ -0,0 +1,177 @@
+use nu_engine::{eval_block, eval_expression};
+use nu_protocol::ast::{Block, Call, Expr, Expression};
+use nu_protocol::engine::{EngineState, Stack};
+use nu_protocol::{PipelineData, ShellError, Span, Value, VarId};
+
+/// The block a filter like `each` runs on the rows, one at a time, with the row as the block's
+/// parameter. It owns what it needs, so that it can go along with the stream of rows
+pub struct RowBlock {
+    engine_state: EngineState,
+    stack: Stack,
+    body: Body,
+    span: Span,
+}
+
+/// What runs on the rows: a block, or a row condition like the one of `where`, which has the row
+/// as `$it`
+enum Body {
+    Block(Block),
+    Condition(VarId, Expression),
+}
+
+impl RowBlock {
+    /// The block given as the first positional of the call
+    pub fn new(engine_state: &EngineState, stack: &Stack, call: &Call) -> RowBlock {
+        let block_id = call.positional[0]
+            .as_block()
+            .expect("internal error: expected block");
+        let block = engine_state.get_block(block_id).clone();
+
+        RowBlock {
+            engine_state: engine_state.clone(),
+            stack: stack.collect_captures(&block.captures),
+            body: Body::Block(block),
+            span: call.head,
+        }
+    }
+
+    /// The row condition given as the first positional of the call
+    pub fn condition(
+        engine_state: &EngineState,
+        stack: &Stack,
+        call: &Call,
+    ) -> Result<RowBlock, ShellError> {
+        let (var_id, cond) = match &call.positional[0].expr {
+            Expr::RowCondition(var_id, cond) => (*var_id, *cond.clone()),
+            _ => return Err(ShellError::InternalError("Expected row condition".into())),
+        };
+
+        Ok(RowBlock {
+            engine_state: engine_state.clone(),
+            // FIXME: expensive
+            stack: stack.clone(),
+            body: Body::Condition(var_id, cond),
+            span: call.head,
+        })
+    }
+
+    /// Run the block with `param` as its parameter, usually the row itself. Errors say which row
+    /// they happened in
+    pub fn eval(&mut self, idx: usize, param: Value) -> Result<Value, ShellError> {
+        let span = self.span;
+
+        self.run(param).map_err(|error| in_row(idx, span, error))
+    }
+
+    /// Run the block as a condition on the row. Like the one of `if`, it has to give a boolean.
+    /// A condition that fails on a cell of the row holding an error, like the ones `ls` gives for
+    /// entries it can't read, doesn't hold
+    pub fn test(&mut self, idx: usize, row: Value) -> Result<bool, ShellError> {
+        let span = self.span;
+        let cell_errors = cell_errors(&row);
+
+        match self.run(row) {
+            Ok(value) => value.as_bool().map_err(|error| in_row(idx, span, error)),
+            Err(error) if cell_errors.contains(&error) => Ok(false),
+            Err(error) => Err(in_row(idx, span, error)),
+        }
+    }
+
+    fn run(&mut self, param: Value) -> Result<Value, ShellError> {
+        let span = self.span;
+        match &self.body {
+            Body::Block(block) => {
+                if let Some(var_id) = block.signature.get_positional(0).and_then(|var| var.var_id) {
+                    self.stack.add_var(var_id, param);
+                }
+
+                eval_block(
+                    &self.engine_state,
+                    &mut self.stack,
+                    block,
+                    PipelineData::new(span),
+                )
+                .and_then(|output| {
+                    let value = output.into_value(span);
+
+                    // The block's output may have stopped at an error, eg of an inner `each`
+                    match self.engine_state.take_abort() {
+                        Some(error) => Err(error),
+                        None => Ok(value),
+                    }
+                })
+            }
+            Body::Condition(var_id, cond) => {
+                self.stack.add_var(*var_id, param);
+                eval_expression(&self.engine_state, &mut self.stack, cond)
+            }
+        }
+    }
+}
+
+/// The errors held in the cells of a row
+fn cell_errors(row: &Value) -> Vec<ShellError> {
+    match row {
+        Value::Record { vals, .. } => vals
+            .iter()
+            .filter_map(|val| match val {
+                Value::Error { error } => Some(error.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn in_row(idx: usize, span: Span, error: ShellError) -> ShellError {
+    ShellError::InRow {
+        row: idx,
+        span,
+        cause: vec![error],
+    }
+}
+
+#[cfg(test)]
+pub mod test_support {
+    use nu_engine::test_support::{engine_state_with, parse_delta, stack_with_config};
+    use nu_protocol::ast::{Expr, Statement};
+    use nu_protocol::engine::Command;
+    use nu_protocol::{IntoInterruptiblePipelineData, ShellError, Value};
+
+    /// Run `source`, a call to `cmd`, on the rows 0, 1, 2... and read up to `wanted` rows of its
+    /// output. Pulling a row past `last` panics, to show the command stopped reading in time
+    pub fn run_on_counter(
+        cmd: impl Command + 'static,
+        source: &str,
+        last: i64,
+        wanted: usize,
+    ) -> Result<Vec<Value>, ShellError> {
+        let mut engine_state = engine_state_with(vec![Box::new(cmd)]);
+        let (block, delta) = parse_delta(&engine_state, source.as_bytes());
+        engine_state.merge_delta(delta);
+
+        let call = match &block.stmts[..] {
+            [Statement::Pipeline(pipeline)] => match &pipeline.expressions[..] {
+                [expression] => match &expression.expr {
+                    Expr::Call(call) => call.clone(),
+                    other => panic!("expected a call, got {:?}", other),
+                },
+                other => panic!("expected a single call, got {:?}", other),
+            },
+            other => panic!("expected a single pipeline, got {:?}", other),
+        };
+
+        let rows = (0..).map(move |n| {
+            assert!(n <= last, "row {} was read past the stopping point", n);
+            Value::test_int(n)
+        });
+        let output = engine_state.get_decl(call.decl_id).run(
+            &engine_state,
+            &mut stack_with_config(),
+            &call,
+            rows.into_pipeline_data(None),
+        )?;
+
+        Ok(output.into_iter().take(wanted).collect())
+    }
+}
@@ -1,4 +1,4 @@
-use nu_engine::CallExt;
+use crate::cell_paths_arg;
 use nu_protocol::ast::{Call, CellPath};
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{
@@ -15,11 +15,17 @@ impl Command for Select {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("select").rest(
-            "rest",
-            SyntaxShape::CellPath,
-            "the columns to select from the table",
-        )
+        Signature::build("select")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "the columns to select from the table, or a single list of column names",
+            )
+            .switch(
+                "literal",
+                "don't split column names from a list on dots",
+                Some('l'),
+            )
     }
 
     fn usage(&self) -> &str {
@@ -33,7 +39,7 @@ impl Command for Select {
         call: &Call,
         input: PipelineData,
     ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
-        let columns: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
+        let columns = cell_paths_arg(engine_state, stack, call, 0, call.has_flag("literal"))?;
         let span = call.head;
 
         select(engine_state, span, columns, input)
@@ -45,11 +51,26 @@ impl Command for Select {
                 description: "Select just the name column",
                 example: "ls | select name",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Select the name and size columns",
                 example: "ls | select name size",
                 result: None,
+                ..Default::default()
+            },
+            Example {
+                description: "Select the columns named in a list",
+                example: "let cols = [a c]; [[a b c]; [1 2 3]] | select $cols",
+                result: Some(Value::List {
+                    vals: vec![Value::Record {
+                        cols: vec!["a".to_string(), "c".to_string()],
+                        vals: vec![Value::test_int(1), Value::test_int(3)],
+                        span: Span::unknown(),
+                    }],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
             },
         ]
     }
@@ -128,6 +149,18 @@ fn select(
     }
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Select {})
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use nu_protocol::ColumnPath;
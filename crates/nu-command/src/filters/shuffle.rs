@@ -1,3 +1,4 @@
+use crate::ensure_bounded;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{IntoInterruptiblePipelineData, PipelineData, ShellError, Signature};
@@ -24,9 +25,11 @@ impl Command for Shuffle {
         &self,
         engine_state: &EngineState,
         _stack: &mut Stack,
-        _call: &Call,
+        call: &Call,
         input: PipelineData,
     ) -> Result<PipelineData, ShellError> {
+        ensure_bounded(&input, call.head)?;
+
         let mut v: Vec<_> = input.into_iter().collect();
         v.shuffle(&mut thread_rng());
         let iter = v.into_iter();
@@ -0,0 +1,75 @@
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Example, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, Span, SyntaxShape,
+    Value,
+};
+
+#[derive(Clone)]
+pub struct Skip;
+
+impl Command for Skip {
+    fn name(&self) -> &str {
+        "skip"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("skip").optional("n", SyntaxShape::Int, "the number of rows to skip")
+    }
+
+    fn usage(&self) -> &str {
+        "Skip the first n rows."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let n: Option<i64> = call.opt(engine_state, stack, 0)?;
+        let n = n.unwrap_or(1).max(0) as usize;
+
+        let endless = input.is_endless();
+
+        Ok(input
+            .into_iter()
+            .skip(n)
+            .into_pipeline_data(engine_state.ctrlc.clone())
+            .set_endless(endless))
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Skip the first row of a list",
+                example: "[1 2 3] | skip",
+                result: Some(Value::List {
+                    vals: vec![Value::test_int(2), Value::test_int(3)],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Skip into a range that never ends",
+                example: "1.. | skip 10 | first 2",
+                result: None,
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Skip {})
+    }
+}
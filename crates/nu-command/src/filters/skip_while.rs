@@ -0,0 +1,147 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use super::row_block::RowBlock;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Example, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, Span, SyntaxShape,
+    Value,
+};
+
+#[derive(Clone)]
+pub struct SkipWhile;
+
+impl Command for SkipWhile {
+    fn name(&self) -> &str {
+        "skip while"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("skip while").required(
+            "predicate",
+            SyntaxShape::Block(Some(vec![SyntaxShape::Any])),
+            "the condition the rows to skip meet",
+        )
+    }
+
+    fn usage(&self) -> &str {
+        "Skip rows while the condition holds, and pass on the rest from the first row it doesn't."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"The condition isn't run on the rows after the first one it doesn't hold for. Like the one
+of `if`, it has to give a boolean. A row the block fails on ends the output with an error saying
+which row it was."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let mut predicate = RowBlock::new(engine_state, stack, call);
+        let endless = Arc::new(AtomicBool::new(input.is_endless()));
+        let still_endless = endless.clone();
+        let mut rows = input.into_iter().enumerate();
+        let mut skipping = true;
+        let mut failed = false;
+
+        Ok(std::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+
+            while skipping {
+                let (idx, row) = rows.next()?;
+                match predicate.test(idx, row.clone()) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        skipping = false;
+                        return Some(row);
+                    }
+                    Err(error) => {
+                        failed = true;
+                        still_endless.store(false, Ordering::SeqCst);
+                        return Some(Value::Error { error });
+                    }
+                }
+            }
+
+            rows.next().map(|(_, row)| row)
+        })
+        .into_pipeline_data(engine_state.ctrlc.clone())
+        .share_endless(endless))
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Skip the leading positive numbers",
+                example: "[1 2 3 -1 4] | skip while { $it > 0 }",
+                result: Some(Value::List {
+                    vals: vec![Value::test_int(-1), Value::test_int(4)],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Skip into a range that never ends",
+                example: "1.. | skip while { $it < 10 } | first 2",
+                result: None,
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::filters::row_block::test_support::run_on_counter;
+    use crate::Length;
+    use nu_engine::test_support::{engine_state_with, eval_source, stack_with_config};
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(SkipWhile {})
+    }
+
+    #[test]
+    fn passes_on_the_rest_without_reading_ahead() {
+        let rows =
+            run_on_counter(SkipWhile, "skip while { $it < 3 }", 4, 2).expect("the command runs");
+
+        assert_eq!(rows, vec![Value::test_int(3), Value::test_int(4)]);
+    }
+
+    #[test]
+    fn errors_say_which_row_they_happened_in() {
+        let rows = run_on_counter(SkipWhile, "skip while { 6 / (2 - $it) > 0 }", 2, 10)
+            .expect("the command runs");
+
+        assert!(matches!(
+            rows.as_slice(),
+            [Value::Error {
+                error: ShellError::InRow { row: 2, .. },
+            }]
+        ));
+    }
+
+    #[test]
+    fn skipping_into_a_range_without_an_end_never_ends() {
+        let mut engine_state = engine_state_with(vec![Box::new(SkipWhile), Box::new(Length)]);
+        let output = eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            "1.. | skip while { $it < 3 } | length",
+            PipelineData::new(Span::unknown()),
+        );
+
+        assert!(matches!(output, Err(ShellError::UnboundedRange(_))));
+    }
+}
@@ -0,0 +1,350 @@
+use std::cmp::Ordering;
+
+use crate::ensure_bounded;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Example, IntoInterruptiblePipelineData, IntoPipelineData, PipelineData, ShellError, Signature,
+    Span, Value,
+};
+
+#[derive(Clone)]
+pub struct Sort;
+
+impl Command for Sort {
+    fn name(&self) -> &str {
+        "sort"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("sort")
+            .switch("reverse", "sort in decreasing order", Some('r'))
+            .switch(
+                "insensitive",
+                "compare strings without regard to case",
+                Some('i'),
+            )
+            .switch(
+                "values",
+                "sort a record by its values instead of its keys",
+                Some('v'),
+            )
+    }
+
+    fn usage(&self) -> &str {
+        "Sort a list, or the fields of a record, in increasing order."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"Values of different types don't fail the sort: they're grouped by type, in the order bools,
+numbers, file sizes, durations, dates, strings, binary, lists, records and anything else,
+with nothings last. Values that compare equal keep their order."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        _stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let reverse = call.has_flag("reverse");
+        let insensitive = call.has_flag("insensitive");
+        let by_values = call.has_flag("values");
+        let metadata = input.metadata();
+        ensure_bounded(&input, call.head)?;
+
+        let compare = |lhs: &Value, rhs: &Value| {
+            let ordering = compare_values(lhs, rhs, insensitive);
+            if reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        };
+
+        match input {
+            PipelineData::Value(Value::Record { cols, vals, span }) => {
+                let mut fields: Vec<(String, Value)> = cols.into_iter().zip(vals).collect();
+                if by_values {
+                    fields.sort_by(|(_, lhs), (_, rhs)| compare(lhs, rhs));
+                } else {
+                    fields.sort_by(|(lhs, _), (rhs, _)| {
+                        compare(&Value::string(lhs, span), &Value::string(rhs, span))
+                    });
+                }
+                let (cols, vals) = fields.into_iter().unzip();
+
+                Ok(Value::Record { cols, vals, span }
+                    .into_pipeline_data()
+                    .set_metadata(metadata))
+            }
+            input => {
+                let mut values: Vec<Value> = input.into_iter().collect();
+                values.sort_by(|lhs, rhs| compare(lhs, rhs));
+
+                Ok(values
+                    .into_iter()
+                    .into_pipeline_data(engine_state.ctrlc.clone())
+                    .set_metadata(metadata))
+            }
+        }
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Sort a list of numbers",
+                example: "[2 0 1.5] | sort",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::test_int(0),
+                        Value::Float {
+                            val: 1.5,
+                            span: Span::unknown(),
+                        },
+                        Value::test_int(2),
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Sort strings in decreasing order, without regard to case",
+                example: "[b A c] | sort -ri",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::test_string("c"),
+                        Value::test_string("b"),
+                        Value::test_string("A"),
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Sort values of different types",
+                example: "[b 2 a 1 $true] | sort",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::Bool {
+                            val: true,
+                            span: Span::unknown(),
+                        },
+                        Value::test_int(1),
+                        Value::test_int(2),
+                        Value::test_string("a"),
+                        Value::test_string("b"),
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Sort a record by its values",
+                example: "{b: 1, a: 2} | sort --values",
+                result: Some(Value::Record {
+                    cols: vec!["b".into(), "a".into()],
+                    vals: vec![Value::test_int(1), Value::test_int(2)],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+/// Order values of the same type by their value, and values of different types by their type.
+/// It's a total order, so the sort can rely on it: NaN goes after the other numbers, and values
+/// that can't be compared, like two records, are equal
+fn compare_values(lhs: &Value, rhs: &Value, insensitive: bool) -> Ordering {
+    let by_type = type_rank(lhs).cmp(&type_rank(rhs));
+    if by_type != Ordering::Equal {
+        return by_type;
+    }
+
+    match (lhs, rhs) {
+        (Value::String { val: lhs, .. }, Value::String { val: rhs, .. }) if insensitive => {
+            lhs.to_lowercase().cmp(&rhs.to_lowercase())
+        }
+        (Value::Int { val: lhs, .. }, Value::Int { val: rhs, .. }) => lhs.cmp(rhs),
+        (Value::Int { .. } | Value::Float { .. }, _) => compare_numbers(as_f64(lhs), as_f64(rhs)),
+        // Element by element, so that lists are ordered the way their elements are
+        (Value::List { vals: lhs, .. }, Value::List { vals: rhs, .. }) => lhs
+            .iter()
+            .zip(rhs)
+            .map(|(lhs, rhs)| compare_values(lhs, rhs, insensitive))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or_else(|| lhs.len().cmp(&rhs.len())),
+        _ => lhs.partial_cmp(rhs).unwrap_or(Ordering::Equal),
+    }
+}
+
+fn compare_numbers(lhs: f64, rhs: f64) -> Ordering {
+    match (lhs.is_nan(), rhs.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => lhs
+            .partial_cmp(&rhs)
+            .expect("numbers that aren't NaN compare"),
+    }
+}
+
+fn as_f64(number: &Value) -> f64 {
+    match number {
+        Value::Int { val, .. } => *val as f64,
+        Value::Float { val, .. } => *val,
+        _ => f64::NAN,
+    }
+}
+
+/// Where the values of a type go in a sorted list. Ints and floats compare with each other, so
+/// they're in the same group
+fn type_rank(value: &Value) -> u8 {
+    match value {
+        Value::Bool { .. } => 0,
+        Value::Int { .. } | Value::Float { .. } => 1,
+        Value::Filesize { .. } => 2,
+        Value::Duration { .. } => 3,
+        Value::Date { .. } => 4,
+        Value::String { .. } => 5,
+        Value::Binary { .. } => 6,
+        Value::List { .. } => 7,
+        Value::Record { .. } => 8,
+        Value::Range { .. } | Value::Block { .. } | Value::CellPath { .. } => 9,
+        Value::Error { .. } => 10,
+        Value::Nothing { .. } => 11,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sorted(mut values: Vec<Value>, insensitive: bool) -> Vec<Value> {
+        values.sort_by(|lhs, rhs| compare_values(lhs, rhs, insensitive));
+        values
+    }
+
+    fn nothing() -> Value {
+        Value::Nothing {
+            span: Span::unknown(),
+        }
+    }
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Sort {})
+    }
+
+    #[test]
+    fn groups_mixed_types() {
+        let values = vec![
+            Value::test_string("b"),
+            nothing(),
+            Value::Bool {
+                val: true,
+                span: Span::unknown(),
+            },
+            Value::test_int(3),
+            Value::test_string("a"),
+            Value::test_int(-1),
+        ];
+
+        assert_eq!(
+            sorted(values, false),
+            vec![
+                Value::Bool {
+                    val: true,
+                    span: Span::unknown(),
+                },
+                Value::test_int(-1),
+                Value::test_int(3),
+                Value::test_string("a"),
+                Value::test_string("b"),
+                nothing(),
+            ]
+        );
+    }
+
+    #[test]
+    fn equal_values_keep_their_order() {
+        let first = Value::test_string("A");
+        let second = Value::test_string("a");
+
+        assert_eq!(
+            sorted(vec![first.clone(), second.clone()], true),
+            vec![first.clone(), second.clone()]
+        );
+        assert_eq!(
+            sorted(vec![second.clone(), first.clone()], true),
+            vec![second, first]
+        );
+    }
+
+    #[test]
+    fn incomparable_values_keep_their_order() {
+        let record = |col: &str| Value::Record {
+            cols: vec![col.into()],
+            vals: vec![Value::test_int(1)],
+            span: Span::unknown(),
+        };
+
+        assert_eq!(
+            sorted(vec![record("b"), record("a")], false),
+            vec![record("b"), record("a")]
+        );
+    }
+
+    #[test]
+    fn nan_goes_after_the_other_numbers() {
+        let float = |val: f64| Value::Float {
+            val,
+            span: Span::unknown(),
+        };
+        let values = vec![
+            float(f64::NAN),
+            Value::test_int(2),
+            float(f64::NAN),
+            float(-1.5),
+            Value::test_int(1),
+        ];
+
+        let sorted = sorted(values, false);
+
+        assert_eq!(
+            sorted[..3],
+            [float(-1.5), Value::test_int(1), Value::test_int(2)]
+        );
+        assert!(sorted[3..]
+            .iter()
+            .all(|value| matches!(value, Value::Float { val, .. } if val.is_nan())));
+    }
+
+    #[test]
+    fn lists_are_ordered_by_their_elements() {
+        let list = |vals: Vec<Value>| Value::List {
+            vals,
+            span: Span::unknown(),
+        };
+        let values = vec![
+            list(vec![Value::test_string("b")]),
+            list(vec![Value::test_int(1), Value::test_int(2)]),
+            list(vec![Value::test_string("a")]),
+            list(vec![Value::test_int(1)]),
+        ];
+
+        assert_eq!(
+            sorted(values, false),
+            vec![
+                list(vec![Value::test_int(1)]),
+                list(vec![Value::test_int(1), Value::test_int(2)]),
+                list(vec![Value::test_string("a")]),
+                list(vec![Value::test_string("b")]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use std::cmp::Ordering;
+
+use crate::ensure_bounded;
+use nu_engine::CallExt;
+use nu_protocol::ast::{Call, CellPath};
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Example, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, Span, SyntaxShape,
+    Value,
+};
+
+#[derive(Clone)]
+pub struct SortBy;
+
+impl Command for SortBy {
+    fn name(&self) -> &str {
+        "sort-by"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("sort-by")
+            .rest("columns", SyntaxShape::CellPath, "the column(s) to sort by")
+            .switch(
+                "natural",
+                "compare embedded numbers in strings numerically (eg, file2 before file10)",
+                Some('n'),
+            )
+    }
+
+    fn usage(&self) -> &str {
+        "Sort by the given columns, in increasing order."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let columns: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
+        let natural = call.has_flag("natural");
+        let metadata = input.metadata();
+        ensure_bounded(&input, call.head)?;
+
+        let mut keyed = vec![];
+        for value in input.into_iter() {
+            let mut keys = vec![];
+            if columns.is_empty() {
+                keys.push(value.clone());
+            } else {
+                for column in &columns {
+                    keys.push(value.clone().follow_cell_path(&column.members)?);
+                }
+            }
+            keyed.push((keys, value));
+        }
+
+        keyed.sort_by(|(lhs, _), (rhs, _)| compare_keys(lhs, rhs, natural));
+
+        Ok(keyed
+            .into_iter()
+            .map(|(_, value)| value)
+            .into_pipeline_data(engine_state.ctrlc.clone())
+            .set_metadata(metadata))
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Sort a list of numbers",
+                example: "[2 0 1] | sort-by",
+                result: Some(Value::List {
+                    vals: vec![Value::test_int(0), Value::test_int(1), Value::test_int(2)],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Sort file names with embedded numbers in natural order",
+                example: "[file10 file2 file1 file03] | sort-by --natural",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::test_string("file1"),
+                        Value::test_string("file2"),
+                        Value::test_string("file03"),
+                        Value::test_string("file10"),
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Sort a table by a column in natural order",
+                example: "[[name]; [img10.png] [img2.png]] | sort-by name -n",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::Record {
+                            cols: vec!["name".to_string()],
+                            vals: vec![Value::test_string("img2.png")],
+                            span: Span::unknown(),
+                        },
+                        Value::Record {
+                            cols: vec!["name".to_string()],
+                            vals: vec![Value::test_string("img10.png")],
+                            span: Span::unknown(),
+                        },
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+fn compare_keys(lhs: &[Value], rhs: &[Value], natural: bool) -> Ordering {
+    for (lhs, rhs) in lhs.iter().zip(rhs) {
+        let ordering = if natural {
+            lhs.natural_cmp(rhs)
+        } else {
+            lhs.partial_cmp(rhs)
+        };
+
+        match ordering {
+            Some(Ordering::Equal) | None => {}
+            Some(ordering) => return ordering,
+        }
+    }
+
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(SortBy {})
+    }
+}
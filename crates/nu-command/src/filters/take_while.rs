@@ -0,0 +1,153 @@
+use super::row_block::RowBlock;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Example, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, Span, SyntaxShape,
+    Value,
+};
+
+#[derive(Clone)]
+pub struct TakeWhile;
+
+impl Command for TakeWhile {
+    fn name(&self) -> &str {
+        "take while"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("take while").required(
+            "predicate",
+            SyntaxShape::Block(Some(vec![SyntaxShape::Any])),
+            "the condition the rows have to meet",
+        )
+    }
+
+    fn usage(&self) -> &str {
+        "Take rows while the condition holds, up to the first row it doesn't."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"The rest of the input isn't read. Like the one of `if`, the condition has to give a
+boolean. A row the block fails on ends the output with an error saying which row it was."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let mut predicate = RowBlock::new(engine_state, stack, call);
+        let mut rows = input.into_iter().enumerate();
+        let mut done = false;
+
+        // Checking before pulling the next row, so that none is read past the stopping point
+        Ok(std::iter::from_fn(move || {
+            if done {
+                return None;
+            }
+
+            let (idx, row) = rows.next()?;
+            match predicate.test(idx, row.clone()) {
+                Ok(true) => Some(row),
+                Ok(false) => {
+                    done = true;
+                    None
+                }
+                Err(error) => {
+                    done = true;
+                    Some(Value::Error { error })
+                }
+            }
+        })
+        .into_pipeline_data(engine_state.ctrlc.clone())
+        // Stopping is the point, so even the rows of a range without an end come to one
+        .set_endless(false))
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Take the leading positive numbers",
+                example: "[1 2 3 -1 4] | take while { $it > 0 }",
+                result: Some(Value::List {
+                    vals: vec![Value::test_int(1), Value::test_int(2), Value::test_int(3)],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Take from a range that never ends",
+                example: "1.. | take while { $it < 4 }",
+                result: Some(Value::List {
+                    vals: vec![Value::test_int(1), Value::test_int(2), Value::test_int(3)],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::filters::row_block::test_support::run_on_counter;
+    use crate::Length;
+    use nu_engine::test_support::{engine_state_with, eval_source, stack_with_config};
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(TakeWhile {})
+    }
+
+    #[test]
+    fn stops_reading_at_the_first_row_that_fails_the_condition() {
+        let rows =
+            run_on_counter(TakeWhile, "take while { $it < 3 }", 3, 10).expect("the command runs");
+
+        assert_eq!(
+            rows,
+            vec![Value::test_int(0), Value::test_int(1), Value::test_int(2)]
+        );
+    }
+
+    #[test]
+    fn errors_say_which_row_they_happened_in() {
+        let rows = run_on_counter(TakeWhile, "take while { 6 / (2 - $it) > 0 }", 2, 10)
+            .expect("the command runs");
+
+        match rows.as_slice() {
+            [first, second, Value::Error {
+                error: ShellError::InRow { row, cause, .. },
+            }] => {
+                assert_eq!(first, &Value::test_int(0));
+                assert_eq!(second, &Value::test_int(1));
+                assert_eq!(*row, 2);
+                assert!(matches!(cause.as_slice(), [ShellError::DivisionByZero(_)]));
+            }
+            other => panic!("expected two rows and an error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn what_stops_a_range_without_an_end_can_be_counted() {
+        let mut engine_state = engine_state_with(vec![Box::new(TakeWhile), Box::new(Length)]);
+        let output = eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            "1.. | take while { $it < 4 } | length",
+            PipelineData::new(Span::unknown()),
+        );
+
+        assert_eq!(
+            output
+                .expect("the rows are counted")
+                .into_value(Span::unknown()),
+            Value::test_int(3)
+        );
+    }
+}
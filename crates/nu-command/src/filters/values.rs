@@ -0,0 +1,125 @@
+use crate::table_columns;
+use nu_protocol::ast::{Call, PathMember};
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{Example, IntoPipelineData, PipelineData, ShellError, Signature, Span, Value};
+
+#[derive(Clone)]
+pub struct Values;
+
+impl Command for Values {
+    fn name(&self) -> &str {
+        "values"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("values").switch(
+            "all",
+            "use the columns of every row of a table, not just the first",
+            Some('a'),
+        )
+    }
+
+    fn usage(&self) -> &str {
+        "Show the values of a record, or the values of each column of a table."
+    }
+
+    fn run(
+        &self,
+        _engine_state: &EngineState,
+        _stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let span = call.head;
+        let all = call.has_flag("all");
+
+        let values = match input {
+            PipelineData::Value(Value::Record { vals, .. }) => vals,
+            PipelineData::Value(Value::List { vals, .. }) => column_values(&vals, all, span),
+            PipelineData::Stream(stream) => column_values(&stream.collect::<Vec<_>>(), all, span),
+            PipelineData::Value(other) => {
+                return Err(ShellError::UnsupportedInput(
+                    "only records and tables have values".into(),
+                    other.span().unwrap_or(span),
+                ))
+            }
+        };
+
+        Ok(Value::List { vals: values, span }.into_pipeline_data())
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Get the values of a record",
+                example: "{name: nu, age: 3} | values",
+                result: Some(Value::List {
+                    vals: vec![Value::test_string("nu"), Value::test_int(3)],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Get the values of each column of a table",
+                example: "[[a b]; [1 2] [3 4]] | values",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::List {
+                            vals: vec![Value::test_int(1), Value::test_int(3)],
+                            span: Span::unknown(),
+                        },
+                        Value::List {
+                            vals: vec![Value::test_int(2), Value::test_int(4)],
+                            span: Span::unknown(),
+                        },
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "An empty record has no values",
+                example: "{} | values",
+                result: Some(Value::List {
+                    vals: vec![],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+/// For each column, the list of its cells. Rows without the column get an empty cell
+fn column_values(rows: &[Value], all: bool, span: Span) -> Vec<Value> {
+    table_columns(rows, all)
+        .into_iter()
+        .map(|col| {
+            let member = [PathMember::String { val: col, span }];
+            let vals = rows
+                .iter()
+                .map(|row| match row {
+                    Value::Record { .. } => row
+                        .clone()
+                        .follow_cell_path(&member)
+                        .unwrap_or_else(|_| Value::nothing(span)),
+                    _ => Value::nothing(span),
+                })
+                .collect();
+
+            Value::List { vals, span }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Values {})
+    }
+}
@@ -1,7 +1,14 @@
-use nu_engine::eval_expression;
-use nu_protocol::ast::{Call, Expr, Expression};
+use super::row_block::RowBlock;
+use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{PipelineData, ShellError, Signature, SyntaxShape};
+use nu_protocol::{
+    IntoPipelineData, PipelineData, PipelineMetadata, RowPositions, ShellError, Signature, Span,
+    SyntaxShape, Value,
+};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::INDEX_COLUMN;
 
 #[derive(Clone)]
 pub struct Where;
@@ -15,8 +22,20 @@ impl Command for Where {
         "Filter values based on a condition."
     }
 
+    fn extra_usage(&self) -> &str {
+        r#"Like the one of `if`, the condition has to give a boolean. A row it fails on is output as
+an error saying which row it was, unless it failed on a cell holding an error, like the ones `ls`
+gives for entries it can't read. Those rows are left out."#
+    }
+
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("where").required("cond", SyntaxShape::RowCondition, "condition")
+        Signature::build("where")
+            .required("cond", SyntaxShape::RowCondition, "condition")
+            .switch(
+                "numbered",
+                "number the rows in a `#` column before filtering them, so they keep their index",
+                Some('n'),
+            )
     }
 
     fn run(
@@ -26,34 +45,264 @@ impl Command for Where {
         call: &Call,
         input: PipelineData,
     ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
-        let cond = call.positional[0].clone();
-
+        let numbered = call.has_flag("numbered");
+        let head = call.head;
         let ctrlc = engine_state.ctrlc.clone();
-        let engine_state = engine_state.clone();
-
-        // FIXME: expensive
-        let mut stack = stack.clone();
+        let mut cond = RowBlock::condition(engine_state, stack, call)?;
 
-        let (var_id, cond) = match cond {
-            Expression {
-                expr: Expr::RowCondition(var_id, expr),
-                ..
-            } => (var_id, expr),
-            _ => return Err(ShellError::InternalError("Expected row condition".into())),
+        let input = if numbered {
+            // Mapping a stream drops its metadata, but numbering only adds a column
+            let metadata = input.metadata();
+            let mut idx = 0;
+            input
+                .map(
+                    move |value| {
+                        let row = with_index(value, idx, head);
+                        idx += 1;
+                        row
+                    },
+                    ctrlc.clone(),
+                )?
+                .set_metadata(metadata)
+        } else {
+            input
         };
 
-        input.filter(
-            move |value| {
-                stack.add_var(var_id, value.clone());
+        let test = move |idx: usize, row: &Value| cond.test(idx, row.clone());
 
-                let result = eval_expression(&engine_state, &mut stack, &cond);
+        filter_rows(input, test, ctrlc)
+    }
+}
 
-                match result {
-                    Ok(result) => result.is_true(),
-                    _ => false,
+/// The rows `test` holds for, followed by an error for each row it fails on. A single value is
+/// kept or left out whole, and an error on it is the command's error
+fn filter_rows<F>(
+    input: PipelineData,
+    mut test: F,
+    ctrlc: Option<Arc<AtomicBool>>,
+) -> Result<PipelineData, ShellError>
+where
+    F: FnMut(usize, &Value) -> Result<bool, ShellError> + 'static + Send,
+{
+    match input {
+        PipelineData::Value(Value::List { .. })
+        | PipelineData::Value(Value::Range { .. })
+        | PipelineData::Stream(..) => {
+            // Filtering keeps the shape of the rows, so where they came from still applies. Rows
+            // are named by where they were before any filter, so that's what's recorded
+            let metadata = input.metadata().unwrap_or_default();
+            let upstream = metadata.positions;
+            let positions = RowPositions::default();
+            let metadata = PipelineMetadata {
+                data_source: metadata.data_source,
+                positions: Some(positions.clone()),
+                byte_len: None,
+            };
+            let mut idx = 0;
+            Ok(input
+                .flat_map(
+                    move |row| {
+                        let position = upstream.as_ref().map_or(idx, |up| up.get(idx));
+                        idx += 1;
+                        let row = match test(position, &row) {
+                            Ok(true) => row,
+                            Ok(false) => return None,
+                            Err(error) => Value::Error { error },
+                        };
+                        positions.push(position);
+                        Some(row)
+                    },
+                    ctrlc,
+                )?
+                .set_metadata(Some(metadata)))
+        }
+        PipelineData::Value(value) => {
+            if test(0, &value)? {
+                Ok(value.into_pipeline_data())
+            } else {
+                Ok(Value::Nothing {
+                    span: value.span()?,
                 }
+                .into_pipeline_data())
+            }
+        }
+    }
+}
+
+/// The row with the index it had, in front of its columns. Other values become an `item` next to it
+fn with_index(value: Value, idx: usize, head: Span) -> Value {
+    let index = Value::Int {
+        val: idx as i64,
+        span: head,
+    };
+
+    match value {
+        Value::Record {
+            mut cols,
+            mut vals,
+            span,
+        } => {
+            cols.insert(0, INDEX_COLUMN.into());
+            vals.insert(0, index);
+            Value::Record { cols, vals, span }
+        }
+        Value::Error { error } => Value::Error { error },
+        item => Value::Record {
+            cols: vec![INDEX_COLUMN.into(), "item".into()],
+            vals: vec![index, item],
+            span: head,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::filters::row_block::test_support::run_on_counter;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use nu_engine::test_support::{column, engine_state_with, eval_source, stack_with_config};
+    use nu_protocol::engine::RegexCache;
+    use nu_protocol::IntoInterruptiblePipelineData;
+
+    #[test]
+    fn errors_say_which_row_they_happened_in() {
+        let rows =
+            run_on_counter(Where, "where 6 / (2 - $it) > 0", 2, 3).expect("the command runs");
+
+        match rows.as_slice() {
+            [first, second, Value::Error {
+                error: ShellError::InRow { row, cause, .. },
+            }] => {
+                assert_eq!(first, &Value::test_int(0));
+                assert_eq!(second, &Value::test_int(1));
+                assert_eq!(*row, 2);
+                assert!(matches!(cause.as_slice(), [ShellError::DivisionByZero(_)]));
+            }
+            other => panic!("expected two rows and an error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn conditions_have_to_give_booleans() {
+        let rows = run_on_counter(Where, "where $it", 0, 1).expect("the command runs");
+
+        assert!(matches!(
+            rows.as_slice(),
+            [Value::Error {
+                error: ShellError::InRow { row: 0, .. }
+            }]
+        ));
+    }
+
+    #[test]
+    fn regexes_are_compiled_once_for_all_rows() {
+        let compiled = Arc::new(AtomicUsize::new(0));
+        let counter = compiled.clone();
+        let mut engine_state = engine_state_with(vec![Box::new(Where)]);
+        engine_state.regex_cache = Arc::new(RegexCache::with_compiler(4, move |pattern| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            regex::Regex::new(pattern)
+        }));
+
+        let rows = (0..100).map(|n| Value::Record {
+            cols: vec!["name".to_string()],
+            vals: vec![Value::test_string(format!(
+                "file{}.{}",
+                n,
+                ["rs", "md"][n % 2]
+            ))],
+            span: Span::unknown(),
+        });
+        let kept: Vec<Value> = eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            r#"where name =~ '^file\d+\.rs$'"#,
+            rows.into_pipeline_data(None),
+        )
+        .expect("the command runs")
+        .into_iter()
+        .collect();
+
+        assert_eq!(kept.len(), 50);
+        assert_eq!(column(&kept[1], "name"), &Value::test_string("file2.rs"));
+        assert_eq!(compiled.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn rows_with_errors_in_the_tested_cell_are_left_out() {
+        let mut engine_state = engine_state_with(vec![Box::new(Where)]);
+        let row = |name: &str, kind: Value| Value::Record {
+            cols: vec!["name".to_string(), "type".to_string()],
+            vals: vec![Value::test_string(name), kind],
+            span: Span::unknown(),
+        };
+        let rows = vec![
+            row("src", Value::test_string("dir")),
+            row(
+                "dangling",
+                Value::Error {
+                    error: ShellError::FileNotFound(Span::unknown()),
+                },
+            ),
+            row("lib", Value::test_string("dir")),
+        ];
+
+        let kept: Vec<Value> = eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            "where type == dir",
+            rows.into_iter().into_pipeline_data(None),
+        )
+        .expect("the command runs")
+        .into_iter()
+        .collect();
+
+        assert_eq!(kept.len(), 2);
+        assert_eq!(column(&kept[0], "name"), &Value::test_string("src"));
+        assert_eq!(column(&kept[1], "name"), &Value::test_string("lib"));
+    }
+
+    #[test]
+    fn failed_rows_can_be_searched() {
+        let mut engine_state = engine_state_with(vec![Box::new(Where)]);
+        let failed = |row: usize| Value::Error {
+            error: ShellError::InCell {
+                row,
+                path: "ColA".to_string(),
+                span: Span::unknown(),
+                cause: vec![ShellError::UnsupportedInput(
+                    "Input's type is int.".into(),
+                    Span::unknown(),
+                )],
             },
-            ctrlc,
+        };
+        let rows = vec![
+            Value::Record {
+                cols: vec!["ColA".to_string()],
+                vals: vec![Value::test_string("row 7")],
+                span: Span::unknown(),
+            },
+            failed(7),
+            failed(9),
+        ];
+
+        let kept: Vec<Value> = eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            r#"where $it =~ "ColA in row 7""#,
+            rows.into_iter().into_pipeline_data(None),
         )
+        .expect("the command runs")
+        .into_iter()
+        .collect();
+
+        assert!(matches!(
+            kept.as_slice(),
+            [Value::Error {
+                error: ShellError::InCell { row: 7, .. }
+            }]
+        ));
     }
 }
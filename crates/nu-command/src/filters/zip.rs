@@ -1,9 +1,9 @@
-use nu_engine::CallExt;
+use nu_engine::{eval_block_as_stream, CallExt};
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{
-    Example, IntoInterruptiblePipelineData, IntoPipelineData, PipelineData, Signature, SyntaxShape,
-    Value,
+    Example, IntoInterruptiblePipelineData, IntoPipelineData, PipelineData, Signature, Span,
+    SyntaxShape, Value,
 };
 
 #[derive(Clone)]
@@ -19,15 +19,45 @@ impl Command for Zip {
     }
 
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("zip").required("other", SyntaxShape::Any, "the other input")
+        Signature::build("zip").required(
+            "other",
+            SyntaxShape::Any,
+            "the other input, or a block giving it",
+        )
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"A block given as the other input runs once, when the first pair is needed, and its output
+is streamed alongside the input."#
     }
 
     fn examples(&self) -> Vec<Example> {
-        vec![Example {
-            example: "1..3 | zip 4..6",
-            description: "Zip multiple streams and get one of the results",
-            result: None,
-        }]
+        vec![
+            Example {
+                example: "1..3 | zip 4..6",
+                description: "Zip multiple streams and get one of the results",
+                result: None,
+                ..Default::default()
+            },
+            Example {
+                example: "[1 2] | zip { [3 4] }",
+                description: "Zip the input with the output of a block",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::List {
+                            vals: vec![Value::test_int(1), Value::test_int(3)],
+                            span: Span::unknown(),
+                        },
+                        Value::List {
+                            vals: vec![Value::test_int(2), Value::test_int(4)],
+                            span: Span::unknown(),
+                        },
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
     }
 
     fn run(
@@ -37,13 +67,26 @@ impl Command for Zip {
         call: &Call,
         input: PipelineData,
     ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
-        let other: Value = call.req(engine_state, stack, 0)?;
         let head = call.head;
         let ctrlc = engine_state.ctrlc.clone();
 
+        let other: Box<dyn Iterator<Item = Value> + Send> =
+            match call.positional.get(0).and_then(|arg| arg.as_block()) {
+                Some(block_id) => Box::new(eval_block_as_stream(
+                    engine_state,
+                    stack,
+                    block_id,
+                    PipelineData::new(head),
+                )),
+                None => {
+                    let other: Value = call.req(engine_state, stack, 0)?;
+                    Box::new(other.into_pipeline_data().into_iter())
+                }
+            };
+
         Ok(input
             .into_iter()
-            .zip(other.into_pipeline_data().into_iter())
+            .zip(other)
             .map(move |(x, y)| Value::List {
                 vals: vec![x, y],
                 span: head,
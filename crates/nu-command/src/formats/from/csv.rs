@@ -26,6 +26,11 @@ impl Command for FromCsv {
                 "don't treat the first row as column names",
                 Some('n'),
             )
+            .switch(
+                "flexible",
+                "fill in missing fields with nothing and drop extra ones, instead of failing",
+                None,
+            )
     }
 
     fn usage(&self) -> &str {
@@ -48,21 +53,31 @@ impl Command for FromCsv {
                 description: "Convert comma-separated data to a table",
                 example: "open data.txt | from csv",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Convert comma-separated data to a table, ignoring headers",
                 example: "open data.txt | from csv --noheaders",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Convert comma-separated data to a table, ignoring headers",
                 example: "open data.txt | from csv -n",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Convert semicolon-separated data to a table",
                 example: "open data.txt | from csv --separator ';'",
                 result: None,
+                ..Default::default()
+            },
+            Example {
+                description: "Convert comma-separated data with ragged rows to a table",
+                example: "open data.txt | from csv --flexible",
+                result: None,
+                ..Default::default()
             },
         ]
     }
@@ -77,6 +92,7 @@ fn from_csv(
     let name = call.head;
 
     let noheaders = call.has_flag("noheaders");
+    let flexible = call.has_flag("flexible");
     let separator: Option<Value> = call.get_flag(engine_state, stack, "separator")?;
     let config = stack.get_config()?;
 
@@ -98,7 +114,7 @@ fn from_csv(
         _ => ',',
     };
 
-    from_delimited_data(noheaders, sep, input, name, &config)
+    from_delimited_data(noheaders, flexible, sep, input, name, &config)
 }
 
 #[cfg(test)]
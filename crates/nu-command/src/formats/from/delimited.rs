@@ -2,13 +2,15 @@ use csv::ReaderBuilder;
 use nu_protocol::{Config, IntoPipelineData, PipelineData, ShellError, Span, Value};
 
 fn from_delimited_string_to_value(
-    s: String,
+    s: &str,
     noheaders: bool,
+    flexible: bool,
     separator: char,
     span: Span,
 ) -> Result<Value, csv::Error> {
     let mut reader = ReaderBuilder::new()
         .has_headers(!noheaders)
+        .flexible(flexible)
         .delimiter(separator as u8)
         .from_reader(s.as_bytes());
 
@@ -23,7 +25,7 @@ fn from_delimited_string_to_value(
     let mut rows = vec![];
     for row in reader.records() {
         let mut output_row = vec![];
-        for value in row?.iter() {
+        for value in row?.iter().take(headers.len()) {
             if let Ok(i) = value.parse::<i64>() {
                 output_row.push(Value::Int { val: i, span });
             } else if let Ok(f) = value.parse::<f64>() {
@@ -35,6 +37,9 @@ fn from_delimited_string_to_value(
                 });
             }
         }
+        // Only a flexible reader lets through rows that are short of a field or have extra ones
+        output_row.resize(headers.len(), Value::Nothing { span });
+
         rows.push(Value::Record {
             cols: headers.clone(),
             vals: output_row,
@@ -45,18 +50,124 @@ fn from_delimited_string_to_value(
     Ok(Value::List { vals: rows, span })
 }
 
+/// The csv error, saying which record it's in and where that record starts. Records are counted
+/// from 1, the header included
+fn delimited_error(err: csv::Error, span: Span) -> ShellError {
+    let msg = match (err.kind(), err.position()) {
+        (
+            csv::ErrorKind::UnequalLengths {
+                expected_len, len, ..
+            },
+            Some(pos),
+        ) => format!(
+            "record {} (line {}, byte {}) has {} fields, but the first one has {}. \
+             --flexible fills in or cuts off the fields instead",
+            pos.record() + 1,
+            pos.line(),
+            pos.byte(),
+            len,
+            expected_len
+        ),
+        // The other errors already say where they are
+        _ => err.to_string(),
+    };
+
+    ShellError::DelimiterError(msg, span)
+}
+
+/// Parse the input as a table. A `flexible` parse fills rows with too few fields with nothing,
+/// and cuts off the extra fields of rows with too many, instead of failing
 pub fn from_delimited_data(
     noheaders: bool,
+    flexible: bool,
     sep: char,
     input: PipelineData,
     name: Span,
     config: &Config,
 ) -> Result<PipelineData, ShellError> {
+    // Errors point at the text, if it's a single value with a span of its own
+    let error_span = match &input {
+        PipelineData::Value(value) => value.span_or(name),
+        PipelineData::Stream(_) => name,
+    };
     let concat_string = input.collect_string("", config);
 
     Ok(
-        from_delimited_string_to_value(concat_string, noheaders, sep, name)
-            .map_err(|x| ShellError::DelimiterError(x.to_string(), name))?
+        from_delimited_string_to_value(&concat_string, noheaders, flexible, sep, name)
+            .map_err(|err| delimited_error(err, error_span))?
             .into_pipeline_data(),
     )
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn from_csv(text: &str, flexible: bool) -> Result<Value, ShellError> {
+        let input = Value::String {
+            val: text.into(),
+            span: Span::new(10, 20),
+        };
+
+        from_delimited_data(
+            false,
+            flexible,
+            ',',
+            input.into_pipeline_data(),
+            Span::new(1, 2),
+            &Config::default(),
+        )
+        .map(|data| data.into_value(Span::unknown()))
+    }
+
+    fn row(name: Value, size: Value, kind: Value) -> Value {
+        Value::Record {
+            cols: vec!["name".into(), "size".into(), "kind".into()],
+            vals: vec![name, size, kind],
+            span: Span::unknown(),
+        }
+    }
+
+    #[test]
+    fn malformed_records_are_an_error_saying_where_they_are() {
+        // The quote is never closed, so the rest of the text is one field
+        let text = "name,size,kind\na,1,file\n\"b,2,file\nc,3,file\n";
+
+        match from_csv(text, false) {
+            Err(ShellError::DelimiterError(msg, span)) => {
+                assert!(
+                    msg.starts_with(
+                        "record 3 (line 3, byte 24) has 1 fields, but the first one has 3"
+                    ),
+                    "unexpected message: {}",
+                    msg
+                );
+                assert_eq!(span, Span::new(10, 20));
+            }
+            other => panic!("expected a delimiter error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn flexible_parses_fill_in_and_cut_off_fields() {
+        let text = "name,size,kind\na,1\nb,2,dir,extra\n";
+        let nothing = || Value::Nothing {
+            span: Span::unknown(),
+        };
+
+        assert_eq!(
+            from_csv(text, true).expect("ragged rows are fine"),
+            Value::List {
+                vals: vec![
+                    row(Value::test_string("a"), Value::test_int(1), nothing()),
+                    row(
+                        Value::test_string("b"),
+                        Value::test_int(2),
+                        Value::test_string("dir")
+                    ),
+                ],
+                span: Span::unknown(),
+            }
+        );
+    }
+}
@@ -84,6 +84,7 @@ Test' | from eml",
                     ],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Convert eml structured data into table",
@@ -121,6 +122,7 @@ Test' | from eml -b 1",
                     ],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -18,11 +18,13 @@ impl Command for FromJson {
     }
 
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("from json").switch(
-            "objects",
-            "treat each line as a separate value",
-            Some('o'),
-        )
+        Signature::build("from json")
+            .switch("objects", "treat each line as a separate value", Some('o'))
+            .switch(
+                "strict",
+                "refuse comments and trailing commas instead of ignoring them",
+                Some('s'),
+            )
     }
 
     fn examples(&self) -> Vec<Example> {
@@ -38,6 +40,7 @@ impl Command for FromJson {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 example: "'{ a:1, b: [1, 2] }' | from json",
@@ -65,6 +68,36 @@ impl Command for FromJson {
                     ],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
+            },
+            Example {
+                example: r#"'{ "tabSize": 4, // spaces
+"urls": ["http://localhost",], }' | from json"#,
+                description:
+                    "Comments and trailing commas are ignored, like in many settings files",
+                result: Some(Value::Record {
+                    cols: vec!["tabSize".to_string(), "urls".to_string()],
+                    vals: vec![
+                        Value::test_int(4),
+                        Value::List {
+                            vals: vec![Value::test_string("http://localhost")],
+                            span: Span::unknown(),
+                        },
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                example: r#"'[1, 2,]' | from json --strict"#,
+                description: "Refuse anything that isn't plain json",
+                result: Some(Value::Error {
+                    error: ShellError::UnsupportedInput(
+                        "trailing comma at line 1, column 6".into(),
+                        Span::unknown(),
+                    ),
+                }),
+                ..Default::default()
             },
         ]
     }
@@ -81,7 +114,8 @@ impl Command for FromJson {
         let mut string_input = input.collect_string("", &config);
         string_input.push('\n');
 
-        // TODO: turn this into a structured underline of the nu_json error
+        let strict = call.has_flag("strict");
+
         if call.has_flag("objects") {
             #[allow(clippy::needless_collect)]
             let lines: Vec<String> = string_input.lines().map(|x| x.to_string()).collect();
@@ -89,14 +123,14 @@ impl Command for FromJson {
                 .into_iter()
                 .map(move |mut x| {
                     x.push('\n');
-                    match convert_string_to_value(x, span) {
+                    match convert_string_to_value(x, strict, span) {
                         Ok(v) => v,
                         Err(error) => Value::Error { error },
                     }
                 })
                 .into_pipeline_data(engine_state.ctrlc.clone()))
         } else {
-            Ok(convert_string_to_value(string_input, span)?.into_pipeline_data())
+            Ok(convert_string_to_value(string_input, strict, span)?.into_pipeline_data())
         }
     }
 }
@@ -149,11 +183,30 @@ fn convert_nujson_to_value(value: &nu_json::Value, span: Span) -> Value {
     }
 }
 
-fn convert_string_to_value(string_input: String, span: Span) -> Result<Value, ShellError> {
+/// Parse the text as json. The parser ignores comments and trailing commas, so unless that's fine
+/// (`strict` is off), they are looked for first. Errors tell where in the text they are
+fn convert_string_to_value(
+    string_input: String,
+    strict: bool,
+    span: Span,
+) -> Result<Value, ShellError> {
+    if strict {
+        if let Some((offset, found)) = find_relaxed_syntax(&string_input) {
+            let (line, column) = line_and_column(&string_input, offset);
+            return Err(ShellError::UnsupportedInput(
+                format!("{} at line {}, column {}", found, line, column),
+                span,
+            ));
+        }
+    }
+
     let result: Result<nu_json::Value, nu_json::Error> = nu_json::from_str(&string_input);
     match result {
         Ok(value) => Ok(convert_nujson_to_value(&value, span)),
-
+        Err(nu_json::Error::Syntax(code, line, column)) => Err(ShellError::UnsupportedInput(
+            format!("{:?} at line {}, column {}", code, line, column),
+            span,
+        )),
         Err(_x) => Err(ShellError::CantConvert(
             "structured data from json".into(),
             "string".into(),
@@ -162,9 +215,50 @@ fn convert_string_to_value(string_input: String, span: Span) -> Result<Value, Sh
     }
 }
 
+/// The first comment or trailing comma in the text, as its byte offset and what it is. Strings are
+/// skipped, so eg the `//` of a url isn't mistaken for a comment
+fn find_relaxed_syntax(text: &str) -> Option<(usize, &'static str)> {
+    let bytes = text.as_bytes();
+    let mut in_string = false;
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        match (bytes[idx], in_string) {
+            (b'\\', true) => idx += 1,
+            (b'"', _) => in_string = !in_string,
+            (b'#', false) => return Some((idx, "comment")),
+            (b'/', false) if matches!(bytes.get(idx + 1), Some(b'/') | Some(b'*')) => {
+                return Some((idx, "comment"))
+            }
+            (b',', false) => {
+                let next = bytes[idx + 1..]
+                    .iter()
+                    .find(|byte| !byte.is_ascii_whitespace());
+                if matches!(next, Some(b']') | Some(b'}')) {
+                    return Some((idx, "trailing comma"));
+                }
+            }
+            _ => {}
+        }
+        idx += 1;
+    }
+
+    None
+}
+
+/// The 1-based line and column of a byte offset
+fn line_and_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+
+    (line, column)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use nu_protocol::ast::PathMember;
 
     #[test]
     fn test_examples() {
@@ -172,4 +266,57 @@ mod test {
 
         test_examples(FromJson {})
     }
+
+    /// A settings file the way editors write them
+    const SETTINGS: &str = r#"{
+    // Editor settings
+    "editor.tabSize": 4,
+    /* Paths, "quoted" */
+    "files.exclude": ["**/.git", "**/node_modules",],
+    "http.proxy": "http://localhost:8080", # the corporate proxy
+}
+"#;
+
+    #[test]
+    fn ignores_comments_and_trailing_commas() {
+        let value = convert_string_to_value(SETTINGS.into(), false, Span::unknown())
+            .expect("settings should parse");
+
+        assert_eq!(
+            value
+                .follow_cell_path(&[PathMember::String {
+                    val: "http.proxy".into(),
+                    span: Span::unknown(),
+                }])
+                .expect("the proxy is set"),
+            Value::test_string("http://localhost:8080")
+        );
+    }
+
+    #[test]
+    fn strict_mode_points_at_the_first_comment() {
+        let result = convert_string_to_value(SETTINGS.into(), true, Span::unknown());
+
+        assert!(matches!(
+            result,
+            Err(ShellError::UnsupportedInput(message, _)) if message == "comment at line 2, column 5"
+        ));
+    }
+
+    #[test]
+    fn comment_like_strings_are_kept() {
+        let text = r##"{"url": "http://example.com/*path*/", "tag": "#1", "list": "[1,]"}"##;
+
+        assert_eq!(find_relaxed_syntax(text), None);
+        assert!(convert_string_to_value(text.into(), true, Span::unknown()).is_ok());
+    }
+
+    #[test]
+    fn escaped_quotes_dont_end_strings() {
+        let text = r#"["say \"hi\" // not a comment", 1,
+]"#;
+
+        assert_eq!(find_relaxed_syntax(text), Some((33, "trailing comma")));
+        assert_eq!(line_and_column(text, 33), (1, 34));
+    }
 }
@@ -3,15 +3,25 @@ mod csv;
 mod delimited;
 mod eml;
 mod json;
+#[cfg(feature = "spreadsheets")]
+mod ods;
+#[cfg(feature = "spreadsheets")]
+mod spreadsheet;
 mod tsv;
 mod url;
+#[cfg(feature = "spreadsheets")]
+mod xlsx;
 mod yaml;
 
 pub use self::csv::FromCsv;
 pub use command::From;
 pub use eml::FromEml;
 pub use json::FromJson;
+#[cfg(feature = "spreadsheets")]
+pub use ods::FromOds;
 pub use tsv::FromTsv;
 pub use url::FromUrl;
+#[cfg(feature = "spreadsheets")]
+pub use xlsx::FromXlsx;
 pub use yaml::FromYaml;
 pub use yaml::FromYml;
@@ -0,0 +1,50 @@
+use calamine::Ods;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{Example, PipelineData, ShellError, Signature};
+
+use super::spreadsheet::{from_spreadsheet, spreadsheet_signature};
+
+#[derive(Clone)]
+pub struct FromOds;
+
+impl Command for FromOds {
+    fn name(&self) -> &str {
+        "from ods"
+    }
+
+    fn signature(&self) -> Signature {
+        spreadsheet_signature("from ods")
+    }
+
+    fn usage(&self) -> &str {
+        "Parse binary OpenDocument Spreadsheet (.ods) data and create a record of sheet name to table."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        from_spreadsheet::<Ods<_>>(engine_state, stack, call, input, "ods")
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Convert binary .ods data to a record of tables",
+                example: "open test.ods | from ods",
+                result: None,
+                ..Default::default()
+            },
+            Example {
+                description: "Convert only the given sheets",
+                example: "open test.ods | from ods -s [Spreadsheet1]",
+                result: None,
+                ..Default::default()
+            },
+        ]
+    }
+}
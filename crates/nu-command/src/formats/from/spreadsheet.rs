@@ -0,0 +1,275 @@
+use std::fmt::Display;
+use std::io::Cursor;
+
+use calamine::{DataType, Range, Reader};
+use chrono::{DateTime, FixedOffset};
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{EngineState, Stack};
+use nu_protocol::{
+    IntoPipelineData, PipelineData, ShellError, Signature, Span, SyntaxShape, Value,
+};
+
+/// The flags shared by the spreadsheet readers (`from xlsx` and `from ods`)
+pub fn spreadsheet_signature(name: &str) -> Signature {
+    Signature::build(name)
+        .named(
+            "sheets",
+            SyntaxShape::List(Box::new(SyntaxShape::String)),
+            "only convert the sheets with the given names",
+            Some('s'),
+        )
+        .switch(
+            "noheaders",
+            "don't treat the first row of each sheet as column names",
+            Some('n'),
+        )
+}
+
+/// Read the spreadsheet held by the binary input into a record of sheet name -> table
+pub fn from_spreadsheet<R>(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    call: &Call,
+    input: PipelineData,
+    format: &str,
+) -> Result<PipelineData, ShellError>
+where
+    R: Reader<RS = Cursor<Vec<u8>>>,
+    R::Error: Display,
+{
+    let head = call.head;
+    let sheets: Option<Vec<String>> = call.get_flag(engine_state, stack, "sheets")?;
+    let noheaders = call.has_flag("noheaders");
+
+    let bytes = collect_binary(input, head)?;
+
+    read_workbook::<R>(bytes, sheets.as_deref(), noheaders, format, head)
+        .map(|value| value.into_pipeline_data())
+}
+
+/// Convert every (selected) sheet of the workbook into a table, keyed by sheet name
+fn read_workbook<R>(
+    bytes: Vec<u8>,
+    sheets: Option<&[String]>,
+    noheaders: bool,
+    format: &str,
+    span: Span,
+) -> Result<Value, ShellError>
+where
+    R: Reader<RS = Cursor<Vec<u8>>>,
+    R::Error: Display,
+{
+    let mut workbook = R::new(Cursor::new(bytes)).map_err(|e| {
+        ShellError::UnsupportedInput(format!("could not read {} data: {}", format, e), span)
+    })?;
+
+    let mut cols = vec![];
+    let mut vals = vec![];
+
+    for sheet_name in workbook.sheet_names().to_vec() {
+        if let Some(sheets) = sheets {
+            if !sheets.contains(&sheet_name) {
+                continue;
+            }
+        }
+
+        if let Some(Ok(range)) = workbook.worksheet_range(&sheet_name) {
+            cols.push(sheet_name);
+            vals.push(range_to_table(&range, noheaders, span));
+        }
+    }
+
+    Ok(Value::Record { cols, vals, span })
+}
+
+fn collect_binary(input: PipelineData, span: Span) -> Result<Vec<u8>, ShellError> {
+    let mut bytes = vec![];
+
+    for value in input {
+        match value {
+            Value::Binary { val, .. } => bytes.extend(val),
+            Value::Error { error } => return Err(error),
+            other => {
+                return Err(ShellError::UnsupportedInput(
+                    format!("Expected binary input, found {}", other.get_type()),
+                    other.span().unwrap_or(span),
+                ))
+            }
+        }
+    }
+
+    Ok(bytes)
+}
+
+fn range_to_table(range: &Range<DataType>, noheaders: bool, span: Span) -> Value {
+    let mut rows: Vec<&[DataType]> = range.rows().collect();
+
+    // Sheets often carry formatted but empty cells past the data, so trim those off
+    while matches!(rows.last(), Some(row) if row.iter().all(|cell| cell.is_empty())) {
+        rows.pop();
+    }
+    let width = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .rposition(|cell| !cell.is_empty())
+                .map_or(0, |idx| idx + 1)
+        })
+        .max()
+        .unwrap_or(0);
+
+    let mut rows = rows.into_iter();
+
+    let headers: Vec<String> = if noheaders {
+        (1..=width).map(|i| format!("Column{}", i)).collect()
+    } else {
+        match rows.next() {
+            Some(header_row) => header_row[..width]
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| match cell {
+                    DataType::Empty => format!("Column{}", i + 1),
+                    cell => cell.to_string(),
+                })
+                .collect(),
+            None => vec![],
+        }
+    };
+
+    let vals = rows
+        .map(|row| Value::Record {
+            cols: headers.clone(),
+            vals: row[..width]
+                .iter()
+                .map(|cell| cell_to_value(cell, span))
+                .collect(),
+            span,
+        })
+        .collect();
+
+    Value::List { vals, span }
+}
+
+fn cell_to_value(cell: &DataType, span: Span) -> Value {
+    match cell {
+        DataType::Empty => Value::Nothing { span },
+        DataType::String(val) => Value::String {
+            val: val.clone(),
+            span,
+        },
+        DataType::Float(val) => Value::Float { val: *val, span },
+        DataType::Int(val) => Value::Int { val: *val, span },
+        DataType::Bool(val) => Value::Bool { val: *val, span },
+        DataType::DateTime(_) => match cell.as_datetime() {
+            Some(naive) => Value::Date {
+                val: DateTime::<FixedOffset>::from_utc(naive, FixedOffset::east(0)),
+                span,
+            },
+            None => Value::String {
+                val: cell.to_string(),
+                span,
+            },
+        },
+        DataType::Error(error) => Value::String {
+            val: format!("{:?}", error),
+            span,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use calamine::{Ods, Xlsx};
+
+    const XLSX: &[u8] = include_bytes!("../../../tests/fixtures/formats/sample_data.xlsx");
+    const ODS: &[u8] = include_bytes!("../../../tests/fixtures/formats/sample_data.ods");
+
+    fn sheet<'a>(workbook: &'a Value, name: &str) -> &'a [Value] {
+        match workbook {
+            Value::Record { cols, vals, .. } => {
+                let idx = cols
+                    .iter()
+                    .position(|col| col == name)
+                    .expect("missing sheet");
+                match &vals[idx] {
+                    Value::List { vals, .. } => vals,
+                    other => panic!("expected a table, found {:?}", other),
+                }
+            }
+            other => panic!("expected a record, found {:?}", other),
+        }
+    }
+
+    fn check_sample(workbook: &Value) {
+        let people = sheet(workbook, "People");
+        // The trailing empty row and column in the fixture are trimmed away
+        assert_eq!(people.len(), 2);
+
+        match &people[0] {
+            Value::Record { cols, vals, .. } => {
+                assert_eq!(cols, &["name", "age", "member", "joined"]);
+                assert!(matches!(&vals[0], Value::String { val, .. } if val == "Ada"));
+                assert!(matches!(vals[1], Value::Float { val, .. } if val == 36.0));
+                assert!(matches!(vals[2], Value::Bool { val: true, .. }));
+                assert!(matches!(vals[3], Value::Date { .. }));
+            }
+            other => panic!("expected a record, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reads_xlsx_sheets_into_tables() {
+        let workbook =
+            read_workbook::<Xlsx<_>>(XLSX.to_vec(), None, false, "xlsx", Span::unknown())
+                .expect("fixture should parse");
+
+        check_sample(&workbook);
+        assert_eq!(sheet(&workbook, "Notes").len(), 1);
+    }
+
+    #[test]
+    fn reads_ods_sheets_into_tables() {
+        let workbook = read_workbook::<Ods<_>>(ODS.to_vec(), None, false, "ods", Span::unknown())
+            .expect("fixture should parse");
+
+        check_sample(&workbook);
+    }
+
+    #[test]
+    fn selects_sheets_by_name() {
+        let sheets = vec!["Notes".to_string()];
+        let workbook =
+            read_workbook::<Xlsx<_>>(XLSX.to_vec(), Some(&sheets), false, "xlsx", Span::unknown())
+                .expect("fixture should parse");
+
+        match workbook {
+            Value::Record { cols, .. } => assert_eq!(cols, vec!["Notes".to_string()]),
+            other => panic!("expected a record, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn noheaders_names_columns_by_position() {
+        let workbook = read_workbook::<Xlsx<_>>(XLSX.to_vec(), None, true, "xlsx", Span::unknown())
+            .expect("fixture should parse");
+
+        let people = sheet(&workbook, "People");
+        assert_eq!(people.len(), 3);
+        match &people[0] {
+            Value::Record { cols, vals, .. } => {
+                assert_eq!(cols, &["Column1", "Column2", "Column3", "Column4"]);
+                assert!(matches!(&vals[0], Value::String { val, .. } if val == "name"));
+            }
+            other => panic!("expected a record, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_non_binary_input() {
+        let input = PipelineData::Value(Value::test_string("nope"));
+
+        assert!(collect_binary(input, Span::unknown()).is_err());
+    }
+}
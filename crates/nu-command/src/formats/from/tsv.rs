@@ -13,11 +13,17 @@ impl Command for FromTsv {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("from csv").switch(
-            "noheaders",
-            "don't treat the first row as column names",
-            Some('n'),
-        )
+        Signature::build("from csv")
+            .switch(
+                "noheaders",
+                "don't treat the first row as column names",
+                Some('n'),
+            )
+            .switch(
+                "flexible",
+                "fill in missing fields with nothing and drop extra ones, instead of failing",
+                None,
+            )
     }
 
     fn usage(&self) -> &str {
@@ -40,8 +46,9 @@ fn from_tsv(call: &Call, input: PipelineData, config: &Config) -> Result<Pipelin
     let name = call.head;
 
     let noheaders = call.has_flag("noheaders");
+    let flexible = call.has_flag("flexible");
 
-    from_delimited_data(noheaders, '\t', input, name, config)
+    from_delimited_data(noheaders, flexible, '\t', input, name, config)
 }
 
 #[cfg(test)]
@@ -49,6 +49,7 @@ impl Command for FromUrl {
                 ],
                 span: Span::unknown(),
             }),
+            ..Default::default()
         }]
     }
 }
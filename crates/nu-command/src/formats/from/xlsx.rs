@@ -0,0 +1,50 @@
+use calamine::Xlsx;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{Example, PipelineData, ShellError, Signature};
+
+use super::spreadsheet::{from_spreadsheet, spreadsheet_signature};
+
+#[derive(Clone)]
+pub struct FromXlsx;
+
+impl Command for FromXlsx {
+    fn name(&self) -> &str {
+        "from xlsx"
+    }
+
+    fn signature(&self) -> Signature {
+        spreadsheet_signature("from xlsx")
+    }
+
+    fn usage(&self) -> &str {
+        "Parse binary Excel (.xlsx) data and create a record of sheet name to table."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        from_spreadsheet::<Xlsx<_>>(engine_state, stack, call, input, "xlsx")
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Convert binary .xlsx data to a record of tables",
+                example: "open test.xlsx | from xlsx",
+                result: None,
+                ..Default::default()
+            },
+            Example {
+                description: "Convert only the given sheets",
+                example: "open test.xlsx | from xlsx -s [Spreadsheet1]",
+                result: None,
+                ..Default::default()
+            },
+        ]
+    }
+}
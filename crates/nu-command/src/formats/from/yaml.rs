@@ -36,6 +36,7 @@ impl Command for FromYaml {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 example: "'[ a: 1, b: [1, 2] ]' | from yaml",
@@ -58,6 +59,7 @@ impl Command for FromYaml {
                     ],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
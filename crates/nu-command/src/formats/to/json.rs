@@ -43,6 +43,7 @@ impl Command for ToJson {
                 "Outputs an unformatted JSON string representing the contents of this table",
             example: "[1 2 3] | to json",
             result: Some(Value::test_string("[\n  1,\n  2,\n  3\n]")),
+            ..Default::default()
         }]
     }
 }
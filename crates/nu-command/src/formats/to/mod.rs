@@ -1,5 +1,7 @@
 mod command;
 mod json;
+mod yaml;
 
 pub use command::To;
 pub use json::ToJson;
+pub use yaml::ToYaml;
@@ -0,0 +1,217 @@
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{Example, IntoPipelineData, PipelineData, ShellError, Signature, Span, Value};
+
+#[derive(Clone)]
+pub struct ToYaml;
+
+impl Command for ToYaml {
+    fn name(&self) -> &str {
+        "to yaml"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("to yaml")
+    }
+
+    fn usage(&self) -> &str {
+        "Converts table data into YAML text."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"Errors, blocks, ranges and binary data have no YAML form, so converting them is an error
+naming where they are in the input, eg `rows.3.payload`."#
+    }
+
+    fn run(
+        &self,
+        _engine_state: &EngineState,
+        _stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let head = call.head;
+        let value = input.into_value(head);
+        let yaml_value = value_to_yaml_value(&value, head)?;
+
+        match serde_yaml::to_string(&yaml_value) {
+            Ok(val) => Ok(Value::String { val, span: head }.into_pipeline_data()),
+            Err(_) => Err(ShellError::CantConvert(
+                "YAML".into(),
+                value.get_type().to_string(),
+                head,
+            )),
+        }
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example {
+            description: "Outputs a YAML string representing the contents of this table",
+            example: "[[name size]; [spam 3]] | to yaml",
+            result: Some(Value::test_string("---\n- name: spam\n  size: 3\n")),
+            ..Default::default()
+        }]
+    }
+}
+
+/// Convert a value to its YAML form. Values YAML can't express are an error with the cell path to
+/// them, and their own span if they have one (`head` otherwise)
+pub fn value_to_yaml_value(v: &Value, head: Span) -> Result<serde_yaml::Value, ShellError> {
+    to_yaml_value(v, head, &mut vec![])
+}
+
+fn to_yaml_value(
+    v: &Value,
+    head: Span,
+    path: &mut Vec<String>,
+) -> Result<serde_yaml::Value, ShellError> {
+    Ok(match v {
+        Value::Bool { val, .. } => serde_yaml::Value::Bool(*val),
+        Value::Int { val, .. } | Value::Filesize { val, .. } | Value::Duration { val, .. } => {
+            serde_yaml::Value::Number(serde_yaml::Number::from(*val))
+        }
+        Value::Float { val, .. } => serde_yaml::Value::Number(serde_yaml::Number::from(*val)),
+        Value::Date { val, .. } => serde_yaml::Value::String(val.to_rfc3339()),
+        Value::String { val, .. } => serde_yaml::Value::String(val.clone()),
+        Value::CellPath { val, .. } => serde_yaml::Value::String(val.into_string()),
+        Value::Nothing { .. } => serde_yaml::Value::Null,
+        Value::List { vals, .. } => {
+            let mut sequence = Vec::with_capacity(vals.len());
+            for (idx, val) in vals.iter().enumerate() {
+                path.push(idx.to_string());
+                sequence.push(to_yaml_value(val, head, path)?);
+                path.pop();
+            }
+            serde_yaml::Value::Sequence(sequence)
+        }
+        Value::Record { cols, vals, .. } => {
+            let mut mapping = serde_yaml::Mapping::new();
+            for (col, val) in cols.iter().zip(vals) {
+                path.push(col.clone());
+                mapping.insert(
+                    serde_yaml::Value::String(col.clone()),
+                    to_yaml_value(val, head, path)?,
+                );
+                path.pop();
+            }
+            serde_yaml::Value::Mapping(mapping)
+        }
+        Value::Error { .. } | Value::Block { .. } | Value::Range { .. } | Value::Binary { .. } => {
+            let from = v.get_type().to_string();
+            let span = v.span_or(head);
+
+            return Err(if path.is_empty() {
+                ShellError::CantConvert("YAML".into(), from, span)
+            } else {
+                ShellError::CantConvertNested {
+                    to: "YAML".into(),
+                    from,
+                    path: path.join("."),
+                    span,
+                }
+            });
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nu_protocol::ast::{RangeInclusion, RangeOperator};
+    use nu_protocol::{BlockId, Range};
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(ToYaml {})
+    }
+
+    /// `{rows: [{payload: 0}, {payload: 1}, {payload: 2}, {payload: <payload>}]}`
+    fn nested(payload: Value) -> Value {
+        let row = |payload| Value::Record {
+            cols: vec!["payload".into()],
+            vals: vec![payload],
+            span: Span::unknown(),
+        };
+        let mut rows: Vec<Value> = (0..3).map(|idx| row(Value::test_int(idx))).collect();
+        rows.push(row(payload));
+
+        Value::Record {
+            cols: vec!["rows".into()],
+            vals: vec![Value::List {
+                vals: rows,
+                span: Span::unknown(),
+            }],
+            span: Span::unknown(),
+        }
+    }
+
+    fn assert_unsupported_at_path(payload: Value, expected_type: &str) {
+        match value_to_yaml_value(&nested(payload), Span::unknown()) {
+            Err(ShellError::CantConvertNested { from, path, .. }) => {
+                assert_eq!(from, expected_type);
+                assert_eq!(path, "rows.3.payload");
+            }
+            other => panic!("expected a nested conversion error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn errors_name_the_path_to_errors() {
+        assert_unsupported_at_path(
+            Value::Error {
+                error: ShellError::DivisionByZero(Span::unknown()),
+            },
+            "error",
+        );
+    }
+
+    #[test]
+    fn errors_name_the_path_to_blocks() {
+        assert_unsupported_at_path(
+            Value::Block {
+                val: BlockId::new(0),
+                span: Span::unknown(),
+            },
+            "block",
+        );
+    }
+
+    #[test]
+    fn errors_name_the_path_to_ranges() {
+        let range = Range::new(
+            Span::unknown(),
+            Value::test_int(1),
+            Value::Nothing {
+                span: Span::unknown(),
+            },
+            Value::test_int(3),
+            &RangeOperator {
+                inclusion: RangeInclusion::Inclusive,
+                span: Span::unknown(),
+                next_op_span: Span::unknown(),
+            },
+        )
+        .expect("a valid range");
+
+        assert_unsupported_at_path(
+            Value::Range {
+                val: Box::new(range),
+                span: Span::unknown(),
+            },
+            "range",
+        );
+    }
+
+    #[test]
+    fn errors_name_the_path_to_binary() {
+        assert_unsupported_at_path(
+            Value::Binary {
+                val: vec![1, 2],
+                span: Span::unknown(),
+            },
+            "binary",
+        );
+    }
+}
@@ -9,8 +9,13 @@ mod filesystem;
 mod filters;
 mod formats;
 mod math;
+mod network;
+mod path;
+mod platform;
+mod stash;
 mod strings;
 mod system;
+mod util;
 mod viewers;
 
 pub use conversions::*;
@@ -24,6 +29,14 @@ pub use filesystem::*;
 pub use filters::*;
 pub use formats::*;
 pub use math::*;
+pub use network::*;
+pub use path::*;
+pub use platform::*;
+pub use stash::*;
 pub use strings::*;
 pub use system::*;
+pub use util::{
+    cell_path_from_value, cell_paths_arg, ensure_bounded, map_rows, map_values, table_columns,
+    update_cells,
+};
 pub use viewers::*;
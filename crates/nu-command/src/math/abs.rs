@@ -47,6 +47,7 @@ impl Command for SubCommand {
                 ],
                 span: Span::unknown(),
             }),
+            ..Default::default()
         }]
     }
 }
@@ -38,6 +38,7 @@ impl Command for SubCommand {
                 val: 25.0,
                 span: Span::unknown(),
             }),
+            ..Default::default()
         }]
     }
 }
@@ -44,6 +44,7 @@ impl Command for SubCommand {
                 val: 2.5,
                 span: Span::unknown(),
             }),
+            ..Default::default()
         }]
     }
 }
@@ -35,6 +35,7 @@ impl Command for SubCommand {
             description: "Find the maximum of list of numbers",
             example: "[-50 100 25] | math max",
             result: Some(Value::test_int(100)),
+            ..Default::default()
         }]
     }
 }
@@ -38,6 +38,7 @@ impl Command for SubCommand {
                 val: 10.5,
                 span: Span::unknown(),
             }),
+            ..Default::default()
         }]
     }
 }
@@ -35,6 +35,7 @@ impl Command for SubCommand {
             description: "Get the minimum of a list of numbers",
             example: "[-50 100 25] | math min",
             result: Some(Value::test_int(-50)),
+            ..Default::default()
         }]
     }
 }
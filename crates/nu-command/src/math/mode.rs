@@ -61,6 +61,7 @@ impl Command for SubCommand {
                 vals: vec![Value::test_int(3), Value::test_int(12)],
                 span: Span::unknown(),
             }),
+            ..Default::default()
         }]
     }
 }
@@ -35,6 +35,7 @@ impl Command for SubCommand {
             description: "Get the product of a list of numbers",
             example: "[2 3 3 4] | math product",
             result: Some(Value::test_int(72)),
+            ..Default::default()
         }]
     }
 }
@@ -48,6 +48,7 @@ impl Command for SubCommand {
                     vals: vec![Value::test_int(2), Value::test_int(2), Value::test_int(-3)],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Apply the round function with precision specified",
@@ -69,6 +70,7 @@ impl Command for SubCommand {
                     ],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
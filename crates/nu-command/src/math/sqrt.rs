@@ -40,6 +40,7 @@ impl Command for SubCommand {
                 vals: vec![Value::test_int(3), Value::test_int(4)],
                 span: Span::unknown(),
             }),
+            ..Default::default()
         }]
     }
 }
@@ -44,6 +44,7 @@ impl Command for SubCommand {
                     val: std::f64::consts::SQRT_2,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Get the sample stddev of a list of numbers",
@@ -52,6 +53,7 @@ impl Command for SubCommand {
                     val: 1.5811388300841898,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
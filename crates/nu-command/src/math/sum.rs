@@ -36,11 +36,13 @@ impl Command for SubCommand {
                 description: "Sum a list of numbers",
                 example: "[1 2 3] | math sum",
                 result: Some(Value::test_int(6)),
+                ..Default::default()
             },
             Example {
                 description: "Get the disk usage for the current directory",
                 example: "ls | get size | math sum",
                 result: None,
+                ..Default::default()
             },
         ]
     }
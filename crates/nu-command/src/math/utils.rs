@@ -1,3 +1,4 @@
+use crate::ensure_bounded;
 use nu_protocol::ast::Call;
 use nu_protocol::{IntoPipelineData, PipelineData, ShellError, Span, Value};
 use std::collections::HashMap;
@@ -8,6 +9,8 @@ pub fn run_with_function(
     mf: impl Fn(&[Value], &Span) -> Result<Value, ShellError>,
 ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
     let name = call.head;
+    ensure_bounded(&input, name)?;
+
     let res = calculate(input, name, mf);
     match res {
         Ok(v) => Ok(v.into_pipeline_data()),
@@ -39,6 +39,7 @@ impl Command for SubCommand {
                     val: 2.0,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Get the sample variance of a list of numbers",
@@ -47,6 +48,7 @@ impl Command for SubCommand {
                     val: 2.5,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
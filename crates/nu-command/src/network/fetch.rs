@@ -0,0 +1,158 @@
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Spanned, SyntaxShape, Type,
+};
+
+use super::http;
+
+#[derive(Clone)]
+pub struct Fetch;
+
+impl Command for Fetch {
+    fn name(&self) -> &str {
+        "fetch"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("fetch")
+            .required("url", SyntaxShape::String, "the http:// URL to fetch")
+            .output_type(Type::Binary)
+            .category(Category::Network)
+    }
+
+    fn usage(&self) -> &str {
+        "Fetch the contents of a URL."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"The body of the response is streamed as chunks of binary data as it comes in, so that eg
+`save` writes a large download without holding all of it. Only http:// URLs are supported."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let url: Spanned<String> = call.req(engine_state, stack, 0)?;
+
+        http::request("GET", &url, None, call.head, engine_state.ctrlc.clone())
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example {
+            description: "Download a file",
+            example: "fetch http://example.com/file.bin | save file.bin",
+            result: None,
+            ..Default::default()
+        }]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use super::super::http::test_support::serve_once;
+    use crate::Save;
+    use nu_engine::test_support::{engine_state_with, eval_source, stack_with_config};
+    use nu_protocol::engine::BufferedOutput;
+    use nu_protocol::{Span, Value};
+
+    const BODY_LEN: usize = 4 * 1024 * 1024;
+
+    fn body() -> Vec<u8> {
+        (0..BODY_LEN).map(|idx| (idx % 251) as u8).collect()
+    }
+
+    fn ok_head() -> String {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\n\r\n",
+            BODY_LEN
+        )
+    }
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Fetch {})
+    }
+
+    #[test]
+    fn bodies_are_streamed_in_chunks() {
+        let (url, _, server) = serve_once(ok_head(), body(), 64 * 1024);
+        let mut engine_state = engine_state_with(vec![Box::new(Fetch)]);
+
+        let output = eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            &format!("fetch {}", url),
+            PipelineData::new(Span::unknown()),
+        )
+        .expect("the fetch succeeds");
+        assert_eq!(
+            output.metadata().and_then(|metadata| metadata.byte_len),
+            Some(BODY_LEN as u64)
+        );
+
+        let mut chunks = 0;
+        let mut largest = 0;
+        let mut fetched = Vec::new();
+        for chunk in output {
+            match chunk {
+                Value::Binary { val, .. } => {
+                    chunks += 1;
+                    largest = largest.max(val.len());
+                    fetched.extend_from_slice(&val);
+                }
+                other => panic!("expected binary, got {:?}", other),
+            }
+        }
+        let request = server.join().expect("the server finishes");
+
+        assert!(String::from_utf8_lossy(&request).starts_with("GET /file.bin HTTP/1.1\r\n"));
+        assert!(
+            largest <= 64 * 1024,
+            "a chunk of {} bytes was read",
+            largest
+        );
+        assert!(chunks >= BODY_LEN / (64 * 1024));
+        assert!(fetched == body());
+    }
+
+    #[test]
+    fn downloads_are_saved_with_their_progress() {
+        let (url, written, server) = serve_once(ok_head(), body(), 64 * 1024);
+        let path = std::env::temp_dir().join(format!("nu-fetch-{}", std::process::id()));
+        let mut engine_state = engine_state_with(vec![Box::new(Fetch), Box::new(Save)]);
+        let output = Arc::new(BufferedOutput::terminal());
+        engine_state.output = output.clone();
+
+        eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            &format!("fetch {} | save {}", url, path.to_string_lossy()),
+            PipelineData::new(Span::unknown()),
+        )
+        .expect("the download is saved");
+        server.join().expect("the server finishes");
+        let saved = std::fs::read(&path).expect("the file is saved");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(written.load(Ordering::SeqCst), BODY_LEN / (64 * 1024));
+        assert!(saved == body());
+
+        let progress = String::from_utf8(output.take_err()).expect("progress is text");
+        let last = format!("saving: {} / {} bytes", BODY_LEN, BODY_LEN);
+        assert!(progress.contains(&last), "progress was {:?}", progress);
+        assert!(progress.ends_with("\r\x1b[2K"));
+    }
+}
@@ -0,0 +1,470 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nu_protocol::{
+    IntoInterruptiblePipelineData, PipelineData, PipelineMetadata, ShellError, Span, Spanned, Value,
+};
+
+/// How much of a body is read into each binary chunk of the stream
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// How long a read waits for data before checking for Ctrl-C
+const READ_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Send a request to `url` and stream back the body of the response as binary chunks.
+///
+/// Only plain `http://` URLs can be reached: the shell has no TLS support yet. The stream says
+/// how long the body is when the response has a Content-Length, eg for `save` to show progress.
+pub fn request(
+    method: &str,
+    url: &Spanned<String>,
+    body: Option<(&str, &[u8])>,
+    head: Span,
+    ctrlc: Option<Arc<AtomicBool>>,
+) -> Result<PipelineData, ShellError> {
+    let (host, port, path) = parse_url(url)?;
+    let failure = |err: io::Error| ShellError::NetworkFailure(err.to_string(), url.span);
+
+    let mut stream = TcpStream::connect((host.as_str(), port)).map_err(failure)?;
+    stream
+        .set_read_timeout(Some(READ_TIMEOUT))
+        .map_err(failure)?;
+
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: nushell\r\nAccept: */*\r\nConnection: close\r\n",
+        method, path, host
+    );
+    if let Some((content_type, body)) = body {
+        request.push_str(&format!(
+            "Content-Type: {}\r\nContent-Length: {}\r\n",
+            content_type,
+            body.len()
+        ));
+    }
+    request.push_str("\r\n");
+
+    stream.write_all(request.as_bytes()).map_err(failure)?;
+    if let Some((_, body)) = body {
+        stream.write_all(body).map_err(failure)?;
+    }
+
+    let mut reader = Reader {
+        inner: BufReader::with_capacity(CHUNK_SIZE, stream),
+        ctrlc: ctrlc.clone(),
+    };
+    let response = read_head(&mut reader).map_err(failure)?;
+
+    if !(200..300).contains(&response.status) {
+        return Err(ShellError::NetworkFailure(
+            format!("the server answered {}", response.status_line),
+            url.span,
+        ));
+    }
+
+    let framing = if response.chunked {
+        Framing::Chunked { left: 0 }
+    } else if let Some(len) = response.content_length {
+        Framing::Length { left: len }
+    } else {
+        Framing::UntilClose
+    };
+
+    let body = Body {
+        reader,
+        framing,
+        head,
+        span: url.span,
+        done: false,
+    };
+
+    Ok(body
+        .into_pipeline_data(ctrlc)
+        .set_metadata(Some(PipelineMetadata {
+            byte_len: response.content_length,
+            ..Default::default()
+        })))
+}
+
+/// The host, port and path of an `http://` URL
+fn parse_url(url: &Spanned<String>) -> Result<(String, u16, String), ShellError> {
+    let rest = match url.item.strip_prefix("http://") {
+        Some(rest) => rest,
+        None if url.item.starts_with("https://") => {
+            return Err(ShellError::UnsupportedInput(
+                "https isn't supported yet, only http".into(),
+                url.span,
+            ))
+        }
+        None => {
+            return Err(ShellError::UnsupportedInput(
+                "expected an http:// URL".into(),
+                url.span,
+            ))
+        }
+    };
+
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, "/"),
+    };
+
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => match port.parse() {
+            Ok(port) => (host, port),
+            Err(_) => {
+                return Err(ShellError::UnsupportedInput(
+                    format!("'{}' isn't a port", port),
+                    url.span,
+                ))
+            }
+        },
+        None => (authority, 80),
+    };
+
+    if host.is_empty() {
+        return Err(ShellError::UnsupportedInput(
+            "the URL has no host".into(),
+            url.span,
+        ));
+    }
+
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+/// The status and the headers of a response that matter to reading its body
+struct ResponseHead {
+    status: u16,
+    status_line: String,
+    content_length: Option<u64>,
+    chunked: bool,
+}
+
+fn read_head(reader: &mut Reader) -> io::Result<ResponseHead> {
+    let status_line = reader.read_line()?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| invalid(format!("unexpected status line '{}'", status_line)))?;
+
+    let mut content_length = None;
+    let mut chunked = false;
+    loop {
+        let line = reader.read_line()?;
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(
+                    value
+                        .parse()
+                        .map_err(|_| invalid(format!("bad Content-Length '{}'", value)))?,
+                );
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                chunked = value.to_ascii_lowercase().contains("chunked");
+            }
+        }
+    }
+
+    Ok(ResponseHead {
+        status,
+        status_line: status_line.trim_start_matches("HTTP/1.1 ").to_string(),
+        content_length: if chunked { None } else { content_length },
+        chunked,
+    })
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The connection, read so that waiting on the server can be interrupted with Ctrl-C
+struct Reader {
+    inner: BufReader<TcpStream>,
+    ctrlc: Option<Arc<AtomicBool>>,
+}
+
+impl Reader {
+    /// Retry `read` as long as it only timed out, until Ctrl-C is pressed
+    fn retry<T>(
+        &mut self,
+        mut read: impl FnMut(&mut BufReader<TcpStream>) -> io::Result<T>,
+    ) -> io::Result<T> {
+        loop {
+            match read(&mut self.inner) {
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    if let Some(ctrlc) = &self.ctrlc {
+                        if ctrlc.load(Ordering::SeqCst) {
+                            return Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"));
+                        }
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// A line of the head of the response, without its line break
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = Vec::new();
+        self.retry(|inner| inner.read_until(b'\n', &mut line))?;
+
+        while matches!(line.last(), Some(b'\n') | Some(b'\r')) {
+            line.pop();
+        }
+
+        String::from_utf8(line).map_err(|_| invalid("the response head isn't text".into()))
+    }
+
+    /// Up to `max` bytes of the body. None once the connection is closed
+    fn read_chunk(&mut self, max: usize) -> io::Result<Option<Vec<u8>>> {
+        let mut chunk = vec![0; max.min(CHUNK_SIZE)];
+        let read = self.retry(|inner| inner.read(&mut chunk))?;
+        if read == 0 {
+            return Ok(None);
+        }
+
+        chunk.truncate(read);
+        Ok(Some(chunk))
+    }
+}
+
+/// How the end of the body is found
+enum Framing {
+    /// It's as long as the Content-Length said, and this much of it is left
+    Length { left: u64 },
+    /// It comes in chunks, each with its length in front, and this much of the current one is left
+    Chunked { left: u64 },
+    /// It ends when the server closes the connection
+    UntilClose,
+}
+
+/// The body of a response, read a chunk at a time as the stream is pulled
+struct Body {
+    reader: Reader,
+    framing: Framing,
+    head: Span,
+    span: Span,
+    done: bool,
+}
+
+impl Body {
+    fn next_chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        match &mut self.framing {
+            Framing::Length { left } => {
+                if *left == 0 {
+                    return Ok(None);
+                }
+
+                let chunk = self
+                    .reader
+                    .read_chunk(*left as usize)?
+                    .ok_or_else(|| invalid("the body ended early".into()))?;
+                *left -= chunk.len() as u64;
+                Ok(Some(chunk))
+            }
+            Framing::Chunked { left } => {
+                if *left == 0 {
+                    let line = self.reader.read_line()?;
+                    let size = line.split(';').next().unwrap_or_default().trim();
+                    *left = u64::from_str_radix(size, 16)
+                        .map_err(|_| invalid(format!("bad chunk size '{}'", size)))?;
+
+                    if *left == 0 {
+                        return Ok(None);
+                    }
+                }
+
+                let chunk = self
+                    .reader
+                    .read_chunk(*left as usize)?
+                    .ok_or_else(|| invalid("the body ended early".into()))?;
+                *left -= chunk.len() as u64;
+                if *left == 0 {
+                    // Each chunk is followed by a line break
+                    self.reader.read_line()?;
+                }
+                Ok(Some(chunk))
+            }
+            Framing::UntilClose => self.reader.read_chunk(CHUNK_SIZE),
+        }
+    }
+}
+
+impl Iterator for Body {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_chunk() {
+            Ok(Some(chunk)) => Some(Value::Binary {
+                val: chunk,
+                span: self.head,
+            }),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Value::Error {
+                    error: ShellError::NetworkFailure(err.to_string(), self.span),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod test_support {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread::JoinHandle;
+
+    /// Serve one request on a local port with `head` and `body`, written `chunk` bytes at a time.
+    /// Gives the URL to request, the count of body chunks written so far, and the server thread,
+    /// which gives back the request it got
+    pub fn serve_once(
+        head: String,
+        body: Vec<u8>,
+        chunk: usize,
+    ) -> (String, Arc<AtomicUsize>, JoinHandle<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("a local port is free");
+        let url = format!(
+            "http://{}/file.bin",
+            listener.local_addr().expect("the port is bound")
+        );
+        let written = Arc::new(AtomicUsize::new(0));
+        let counter = written.clone();
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("the client connects");
+
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            while !ends_request(&request) {
+                let read = stream.read(&mut buf).expect("the request is read");
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..read]);
+            }
+
+            stream.write_all(head.as_bytes()).expect("the head is sent");
+            for part in body.chunks(chunk) {
+                if stream.write_all(part).is_err() {
+                    break;
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+
+            request
+        });
+
+        (url, written, server)
+    }
+
+    /// Whether all of a request was read: its head, and as much body as it says it has
+    fn ends_request(request: &[u8]) -> bool {
+        let text = String::from_utf8_lossy(request);
+        match text.find("\r\n\r\n") {
+            Some(end) => {
+                let len = text[..end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        if name.eq_ignore_ascii_case("content-length") {
+                            value.trim().parse::<usize>().ok()
+                        } else {
+                            None
+                        }
+                    })
+                    .unwrap_or(0);
+                request.len() >= end + 4 + len
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn url(url: &str) -> Spanned<String> {
+        Spanned {
+            item: url.to_string(),
+            span: Span::unknown(),
+        }
+    }
+
+    #[test]
+    fn urls_give_host_port_and_path() {
+        assert_eq!(
+            parse_url(&url("http://localhost:8080/a/b?c=d")).expect("the URL is valid"),
+            ("localhost".to_string(), 8080, "/a/b?c=d".to_string())
+        );
+        assert_eq!(
+            parse_url(&url("http://example.com")).expect("the URL is valid"),
+            ("example.com".to_string(), 80, "/".to_string())
+        );
+        assert!(parse_url(&url("https://example.com")).is_err());
+        assert!(parse_url(&url("example.com")).is_err());
+    }
+
+    #[test]
+    fn chunked_bodies_are_joined() {
+        let head = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n".to_string();
+        let body = b"5\r\nhello\r\n7;ext=1\r\n, world\r\n0\r\n\r\n".to_vec();
+        let (url, _, server) = test_support::serve_once(head, body, 3);
+
+        let output = request("GET", &self::url(&url), None, Span::unknown(), None)
+            .expect("the request succeeds");
+        let bytes: Vec<u8> = output
+            .into_iter()
+            .flat_map(|chunk| match chunk {
+                Value::Binary { val, .. } => val,
+                other => panic!("expected binary, got {:?}", other),
+            })
+            .collect();
+        server.join().expect("the server finishes");
+
+        assert_eq!(bytes, b"hello, world");
+    }
+
+    #[test]
+    fn failed_requests_say_what_the_server_answered() {
+        let head = "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string();
+        let (url, _, server) = test_support::serve_once(head, vec![], 1);
+
+        let result = request("GET", &self::url(&url), None, Span::unknown(), None);
+        server.join().expect("the server finishes");
+
+        match result {
+            Err(ShellError::NetworkFailure(message, _)) => {
+                assert_eq!(message, "the server answered 404 Not Found")
+            }
+            other => panic!("expected a network failure, got {:?}", other.map(|_| ())),
+        }
+    }
+}
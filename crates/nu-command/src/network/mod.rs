@@ -0,0 +1,6 @@
+mod fetch;
+mod http;
+mod post;
+
+pub use fetch::Fetch;
+pub use post::Post;
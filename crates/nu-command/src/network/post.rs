@@ -0,0 +1,131 @@
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Spanned, SyntaxShape, Type, Value,
+};
+
+use super::http;
+
+#[derive(Clone)]
+pub struct Post;
+
+impl Command for Post {
+    fn name(&self) -> &str {
+        "post"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("post")
+            .required("url", SyntaxShape::String, "the http:// URL to post to")
+            .required("body", SyntaxShape::Any, "the string or binary data to send")
+            .named(
+                "content-type",
+                SyntaxShape::String,
+                "the type of the body, text/plain for strings and application/octet-stream for binary data unless given",
+                Some('t'),
+            )
+            .output_type(Type::Binary)
+            .category(Category::Network)
+    }
+
+    fn usage(&self) -> &str {
+        "Post a body to a URL."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"Like with `fetch`, the body of the response is streamed as chunks of binary data as it
+comes in. Only http:// URLs are supported."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let url: Spanned<String> = call.req(engine_state, stack, 0)?;
+        let body: Value = call.req(engine_state, stack, 1)?;
+        let content_type: Option<String> = call.get_flag(engine_state, stack, "content-type")?;
+
+        let (default_type, bytes) = match &body {
+            Value::String { val, .. } => ("text/plain", val.as_bytes()),
+            Value::Binary { val, .. } => ("application/octet-stream", val.as_slice()),
+            other => {
+                return Err(ShellError::UnsupportedInput(
+                    format!(
+                        "can't post {} data, only strings and binary",
+                        other.get_type()
+                    ),
+                    other.span().unwrap_or(call.head),
+                ))
+            }
+        };
+        let content_type = content_type.as_deref().unwrap_or(default_type);
+
+        http::request(
+            "POST",
+            &url,
+            Some((content_type, bytes)),
+            call.head,
+            engine_state.ctrlc.clone(),
+        )
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example {
+            description: "Post JSON and save the answer",
+            example: r#"post -t application/json http://example.com/api '{"name": "nu"}' | save answer.json"#,
+            result: None,
+            ..Default::default()
+        }]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use super::super::http::test_support::serve_once;
+    use nu_engine::test_support::{engine_state_with, eval_source, stack_with_config};
+    use nu_protocol::Span;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Post {})
+    }
+
+    #[test]
+    fn bodies_are_sent_with_their_type() {
+        let head = "HTTP/1.1 201 Created\r\nContent-Length: 2\r\n\r\n".to_string();
+        let (url, _, server) = serve_once(head, b"ok".to_vec(), 2);
+        let mut engine_state = engine_state_with(vec![Box::new(Post)]);
+
+        let answer: Vec<Value> = eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            &format!("post -t application/json {} '[1, 2]'", url),
+            PipelineData::new(Span::unknown()),
+        )
+        .expect("the post succeeds")
+        .into_iter()
+        .collect();
+        let request = server.join().expect("the server finishes");
+        let request = String::from_utf8(request).expect("the request is text");
+
+        assert!(request.starts_with("POST /file.bin HTTP/1.1\r\n"));
+        assert!(request.contains("Content-Type: application/json\r\n"));
+        assert!(request.contains("Content-Length: 6\r\n"));
+        assert!(request.ends_with("\r\n\r\n[1, 2]"));
+        assert_eq!(
+            answer,
+            vec![Value::Binary {
+                val: b"ok".to_vec(),
+                span: Span::unknown(),
+            }]
+        );
+    }
+}
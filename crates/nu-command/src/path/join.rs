@@ -0,0 +1,202 @@
+use std::path::{Path, PathBuf};
+
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Example, IntoPipelineData, PipelineData, ShellError, Signature, Spanned, SyntaxShape, Value,
+};
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "path join"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("path join").rest(
+            "append",
+            SyntaxShape::String,
+            "path components to append to the input",
+        )
+    }
+
+    fn usage(&self) -> &str {
+        "Join the input path, or a list of path components, with more components."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"The components are joined with the separator of the platform. A component that is an
+absolute path replaces everything before it, so '/usr | path join /etc' gives '/etc'. On
+Windows, a component with a root but no drive ('\data') only replaces the part after the
+drive. Drive letters and UNC prefixes ('\\server\share') are kept as they are."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let head = call.head;
+        let append: Vec<Spanned<String>> = call.rest(engine_state, stack, 0)?;
+
+        let mut path = PathBuf::new();
+        for value in input.into_iter() {
+            match value {
+                Value::String { val, .. } => path.push(val),
+                Value::Error { error } => return Err(error),
+                other => {
+                    return Err(ShellError::UnsupportedInput(
+                        format!(
+                            "Input's type is {}. This command only works with strings.",
+                            other.get_type()
+                        ),
+                        other.span().unwrap_or(head),
+                    ))
+                }
+            }
+        }
+
+        for component in append {
+            path.push(component.item);
+        }
+
+        Ok(Value::String {
+            val: path.to_string_lossy().to_string(),
+            span: head,
+        }
+        .into_pipeline_data())
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Build a path from a list of components",
+                example: "[usr local bin] | path join",
+                result: Some(Value::test_string(
+                    Path::new("usr").join("local").join("bin").to_string_lossy(),
+                )),
+                ..Default::default()
+            },
+            Example {
+                description: "Append several components to a path",
+                example: "'docs' | path join subdir file.txt",
+                result: Some(Value::test_string(
+                    Path::new("docs")
+                        .join("subdir")
+                        .join("file.txt")
+                        .to_string_lossy(),
+                )),
+                ..Default::default()
+            },
+            Example {
+                description: "An absolute component replaces the path it's appended to",
+                example: "'/usr' | path join /etc hosts",
+                result: Some(Value::test_string(
+                    Path::new("/usr")
+                        .join("/etc")
+                        .join("hosts")
+                        .to_string_lossy(),
+                )),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use nu_engine::test_support::{engine_state_with, eval_source, stack_with_config};
+    use nu_protocol::Span;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(SubCommand {})
+    }
+
+    fn path_join(input: Value, source: &str) -> Result<Value, ShellError> {
+        let mut engine_state = engine_state_with(vec![Box::new(SubCommand)]);
+
+        eval_source(
+            &mut engine_state,
+            &mut stack_with_config(),
+            source,
+            input.into_pipeline_data(),
+        )
+        .map(|output| output.into_value(Span::unknown()))
+    }
+
+    fn joined(input: &[&str], source: &str) -> String {
+        let input = Value::List {
+            vals: input.iter().map(|s| Value::test_string(*s)).collect(),
+            span: Span::unknown(),
+        };
+
+        path_join(input, source)
+            .and_then(|value| value.as_string())
+            .expect("path join should succeed")
+    }
+
+    #[test]
+    fn joins_list_and_rest_components() {
+        let sep = std::path::MAIN_SEPARATOR;
+
+        assert_eq!(
+            joined(&["usr", "local"], "path join bin nu"),
+            format!("usr{0}local{0}bin{0}nu", sep)
+        );
+        assert_eq!(joined(&[], "path join a"), "a");
+        assert_eq!(joined(&[], "path join"), "");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn absolute_components_reset_the_path() {
+        assert_eq!(joined(&["usr", "/etc"], "path join hosts"), "/etc/hosts");
+        assert_eq!(joined(&["/usr"], "path join bin /opt"), "/opt");
+        // Only the separator matters on unix, so Windows style paths are plain components
+        assert_eq!(joined(&[r"C:\Users"], "path join nu"), r"C:\Users/nu");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn absolute_components_reset_the_path() {
+        assert_eq!(
+            joined(&[r"C:\Users", r"D:\data"], "path join nu"),
+            r"D:\data\nu"
+        );
+        // A root without a drive keeps the drive
+        assert_eq!(joined(&[r"C:\Users"], r"path join \data"), r"C:\data");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn keeps_drives_and_unc_prefixes() {
+        assert_eq!(joined(&[r"C:\", "Users"], "path join nu"), r"C:\Users\nu");
+        assert_eq!(
+            joined(&[r"\\server\share"], "path join dir file.txt"),
+            r"\\server\share\dir\file.txt"
+        );
+    }
+
+    #[test]
+    fn rejects_non_string_components() {
+        let input = Value::List {
+            vals: vec![Value::test_string("usr"), Value::test_int(1)],
+            span: Span::unknown(),
+        };
+
+        assert!(matches!(
+            path_join(input, "path join"),
+            Err(ShellError::UnsupportedInput(..))
+        ));
+    }
+}
@@ -0,0 +1,5 @@
+mod command;
+mod join;
+
+pub use command::PathCommand;
+pub use join::SubCommand as PathJoin;
@@ -0,0 +1,98 @@
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{EngineState, Stack};
+use nu_protocol::{ShellError, Spanned};
+
+/// A 24-bit color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    /// The color `t` of the way from `self` to `end`, with `t` between 0 and 1
+    pub fn lerp(self, end: Rgb, t: f64) -> Rgb {
+        let channel = |start: u8, end: u8| {
+            (start as f64 + (end as f64 - start as f64) * t)
+                .round()
+                .clamp(0.0, 255.0) as u8
+        };
+
+        Rgb {
+            r: channel(self.r, end.r),
+            g: channel(self.g, end.g),
+            b: channel(self.b, end.b),
+        }
+    }
+}
+
+/// Read a hex color like `40c9ff`, with or without a `0x` or `#` in front
+pub fn parse_hex_color(hex: &str) -> Option<Rgb> {
+    let digits = hex
+        .strip_prefix("0x")
+        .or_else(|| hex.strip_prefix('#'))
+        .unwrap_or(hex);
+
+    if digits.len() != 6 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |idx: usize| u8::from_str_radix(&digits[idx..idx + 2], 16).ok();
+
+    Some(Rgb {
+        r: channel(0)?,
+        g: channel(2)?,
+        b: channel(4)?,
+    })
+}
+
+/// The color given to a flag, failing at the flag's value if it isn't a hex color
+pub fn color_flag(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    call: &Call,
+    name: &str,
+) -> Result<Option<Rgb>, ShellError> {
+    let color: Option<Spanned<String>> = call.get_flag(engine_state, stack, name)?;
+
+    color
+        .map(|color| {
+            parse_hex_color(&color.item).ok_or_else(|| {
+                ShellError::UnsupportedInput(
+                    format!("'{}' isn't a hex color, eg 0x40c9ff", color.item),
+                    color.span,
+                )
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reads_hex_colors() {
+        let color = Some(Rgb {
+            r: 0x40,
+            g: 0xc9,
+            b: 0xff,
+        });
+
+        assert_eq!(parse_hex_color("40c9ff"), color);
+        assert_eq!(parse_hex_color("0x40c9ff"), color);
+        assert_eq!(parse_hex_color("#40C9FF"), color);
+    }
+
+    #[test]
+    fn rejects_other_colors() {
+        assert_eq!(parse_hex_color(""), None);
+        assert_eq!(parse_hex_color("0x40c9f"), None);
+        assert_eq!(parse_hex_color("#40c9ffaa"), None);
+        assert_eq!(parse_hex_color("red"), None);
+        assert_eq!(parse_hex_color("0x#40c9ff"), None);
+        assert_eq!(parse_hex_color("+40c9ff"), None);
+    }
+}
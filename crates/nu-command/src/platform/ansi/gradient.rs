@@ -0,0 +1,233 @@
+use nu_engine::CallExt;
+use nu_protocol::ast::{Call, CellPath};
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+
+use super::color::{color_flag, Rgb};
+use crate::{map_rows, update_cells};
+
+/// The start and end colors of a gradient
+type Gradient = (Rgb, Rgb);
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "ansi gradient"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("ansi gradient")
+            .named(
+                "fgstart",
+                SyntaxShape::String,
+                "foreground gradient start color in hex, eg 0x40c9ff",
+                Some('a'),
+            )
+            .named(
+                "fgend",
+                SyntaxShape::String,
+                "foreground gradient end color in hex",
+                Some('b'),
+            )
+            .named(
+                "bgstart",
+                SyntaxShape::String,
+                "background gradient start color in hex",
+                Some('c'),
+            )
+            .named(
+                "bgend",
+                SyntaxShape::String,
+                "background gradient end color in hex",
+                Some('d'),
+            )
+            .rest(
+                "column path",
+                SyntaxShape::CellPath,
+                "optionally color text by column paths",
+            )
+    }
+
+    fn usage(&self) -> &str {
+        "Color text with a gradient, one 24-bit color per character."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"A gradient missing its start or end color is a single color. The text is followed by a
+reset, so the colors don't run on into what comes after it."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let head = call.head;
+        let fg = gradient(
+            color_flag(engine_state, stack, call, "fgstart")?,
+            color_flag(engine_state, stack, call, "fgend")?,
+        );
+        let bg = gradient(
+            color_flag(engine_state, stack, call, "bgstart")?,
+            color_flag(engine_state, stack, call, "bgend")?,
+        );
+        if fg.is_none() && bg.is_none() {
+            return Err(ShellError::MissingParameter(
+                "a foreground or background color".into(),
+                head,
+            ));
+        }
+        let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
+
+        map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+            if column_paths.is_empty() {
+                action(&v, fg, bg, head)
+            } else {
+                update_cells(v, row, &column_paths, head, move |old| {
+                    action(old, fg, bg, head)
+                })
+            }
+        })
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Color text with a foreground gradient",
+                example: "'Hello, Nushell!' | ansi gradient --fgstart 0x40c9ff --fgend 0xe81cff",
+                result: None,
+                ..Default::default()
+            },
+            Example {
+                description: "Color text with foreground and background gradients",
+                example: "'Hello, Nushell!' | ansi gradient --fgstart 0x40c9ff --fgend 0xe81cff --bgstart 0xe81cff --bgend 0x40c9ff",
+                result: None,
+                ..Default::default()
+            },
+            Example {
+                description: "Color a column of a table",
+                example: "[[name]; [nushell]] | ansi gradient --fgstart '#40c9ff' name",
+                result: None,
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+fn gradient(start: Option<Rgb>, end: Option<Rgb>) -> Option<Gradient> {
+    match (start, end) {
+        (Some(start), Some(end)) => Some((start, end)),
+        (Some(color), None) | (None, Some(color)) => Some((color, color)),
+        (None, None) => None,
+    }
+}
+
+fn action(input: &Value, fg: Option<Gradient>, bg: Option<Gradient>, head: Span) -> Value {
+    match input {
+        Value::String { val, .. } => Value::String {
+            val: paint(val, fg, bg),
+            span: head,
+        },
+        other => Value::Error {
+            error: ShellError::UnsupportedInput(
+                format!(
+                    "Input's type is {}. This command only works with strings.",
+                    other.get_type()
+                ),
+                head,
+            ),
+        },
+    }
+}
+
+/// Color each character of `text` with its step along the gradients
+fn paint(text: &str, fg: Option<Gradient>, bg: Option<Gradient>) -> String {
+    let len = text.chars().count();
+    if len == 0 {
+        return String::new();
+    }
+
+    let mut output = String::new();
+    for (idx, c) in text.chars().enumerate() {
+        let t = if len == 1 {
+            0.0
+        } else {
+            idx as f64 / (len - 1) as f64
+        };
+
+        let mut codes = vec![];
+        if let Some((start, end)) = fg {
+            let Rgb { r, g, b } = start.lerp(end, t);
+            codes.push(format!("38;2;{};{};{}", r, g, b));
+        }
+        if let Some((start, end)) = bg {
+            let Rgb { r, g, b } = start.lerp(end, t);
+            codes.push(format!("48;2;{};{};{}", r, g, b));
+        }
+
+        output.push_str(&format!("\x1b[{}m{}", codes.join(";"), c));
+    }
+    output.push_str("\x1b[0m");
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use super::super::color::parse_hex_color;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(SubCommand {})
+    }
+
+    fn color(hex: &str) -> Rgb {
+        parse_hex_color(hex).expect("test color should be valid")
+    }
+
+    #[test]
+    fn paints_a_foreground_gradient() {
+        let fg = Some((color("000000"), color("ff0000")));
+
+        assert_eq!(
+            paint("abc", fg, None),
+            "\x1b[38;2;0;0;0ma\x1b[38;2;128;0;0mb\x1b[38;2;255;0;0mc\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn paints_both_gradients() {
+        let fg = Some((color("40c9ff"), color("e81cff")));
+        let bg = Some((color("ffffff"), color("000000")));
+
+        assert_eq!(
+            paint("ab", fg, bg),
+            "\x1b[38;2;64;201;255;48;2;255;255;255ma\x1b[38;2;232;28;255;48;2;0;0;0mb\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn paints_single_characters_in_the_start_color() {
+        let fg = Some((color("102030"), color("ffffff")));
+
+        assert_eq!(paint("x", fg, None), "\x1b[38;2;16;32;48mx\x1b[0m");
+        assert_eq!(paint("", fg, None), "");
+    }
+
+    #[test]
+    fn one_color_makes_a_solid_gradient() {
+        let red = color("ff0000");
+
+        assert_eq!(gradient(Some(red), None), Some((red, red)));
+        assert_eq!(gradient(None, Some(red)), Some((red, red)));
+        assert_eq!(gradient(None, None), None);
+    }
+}
@@ -0,0 +1,148 @@
+use nu_engine::CallExt;
+use nu_protocol::ast::{Call, CellPath};
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+
+use crate::{map_rows, update_cells};
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "ansi link"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("ansi link")
+            .named(
+                "text",
+                SyntaxShape::String,
+                "the text to show for the link, instead of the link itself",
+                Some('t'),
+            )
+            .rest(
+                "column path",
+                SyntaxShape::CellPath,
+                "optionally turn links into hyperlinks by column paths",
+            )
+    }
+
+    fn usage(&self) -> &str {
+        "Turn links into terminal hyperlinks (OSC 8)."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"Terminals without support for hyperlinks show just the text, so links are safe to use in
+prompts everywhere."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let head = call.head;
+        let text: Option<String> = call.get_flag(engine_state, stack, "text")?;
+        let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
+
+        map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+            if column_paths.is_empty() {
+                action(&v, text.as_deref(), head)
+            } else {
+                let text = text.clone();
+                update_cells(v, row, &column_paths, head, move |old| {
+                    action(old, text.as_deref(), head)
+                })
+            }
+        })
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Show a link as a hyperlink",
+                example: "'https://www.nushell.sh' | ansi link",
+                result: Some(Value::test_string(
+                    "\x1b]8;;https://www.nushell.sh\x1b\\https://www.nushell.sh\x1b]8;;\x1b\\",
+                )),
+                ..Default::default()
+            },
+            Example {
+                description: "Show a hyperlink with its own text",
+                example: "'https://www.nushell.sh' | ansi link --text 'Nushell'",
+                result: Some(Value::test_string(
+                    "\x1b]8;;https://www.nushell.sh\x1b\\Nushell\x1b]8;;\x1b\\",
+                )),
+                ..Default::default()
+            },
+            Example {
+                description: "Turn a column of links into hyperlinks",
+                example: "[[url]; ['https://www.nushell.sh']] | ansi link url",
+                result: None,
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+fn action(input: &Value, text: Option<&str>, head: Span) -> Value {
+    match input {
+        Value::String { val, .. } => Value::String {
+            val: hyperlink(val, text.unwrap_or(val)),
+            span: head,
+        },
+        other => Value::Error {
+            error: ShellError::UnsupportedInput(
+                format!(
+                    "Input's type is {}. This command only works with strings.",
+                    other.get_type()
+                ),
+                head,
+            ),
+        },
+    }
+}
+
+/// An OSC 8 hyperlink to `url` showing `text`
+fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(SubCommand {})
+    }
+
+    #[test]
+    fn wraps_the_text_in_osc8() {
+        assert_eq!(
+            hyperlink("file:///tmp", "tmp"),
+            "\x1b]8;;file:///tmp\x1b\\tmp\x1b]8;;\x1b\\"
+        );
+    }
+
+    #[test]
+    fn shows_the_link_without_text() {
+        let linked = action(
+            &Value::test_string("https://example.com"),
+            None,
+            Span::unknown(),
+        );
+
+        assert_eq!(
+            linked,
+            Value::test_string(
+                "\x1b]8;;https://example.com\x1b\\https://example.com\x1b]8;;\x1b\\"
+            )
+        );
+    }
+}
@@ -0,0 +1,8 @@
+mod color;
+mod command;
+mod gradient;
+mod link;
+
+pub use command::Ansi;
+pub use gradient::SubCommand as AnsiGradient;
+pub use link::SubCommand as AnsiLink;
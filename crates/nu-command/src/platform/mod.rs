@@ -0,0 +1,3 @@
+mod ansi;
+
+pub use ansi::{Ansi, AnsiGradient, AnsiLink};
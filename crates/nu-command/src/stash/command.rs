@@ -0,0 +1,37 @@
+use nu_engine::get_full_help;
+use nu_protocol::{
+    ast::Call,
+    engine::{Command, EngineState, Stack},
+    IntoPipelineData, PipelineData, ShellError, Signature, Value,
+};
+
+#[derive(Clone)]
+pub struct Stash;
+
+impl Command for Stash {
+    fn name(&self) -> &str {
+        "stash"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("stash")
+    }
+
+    fn usage(&self) -> &str {
+        "Keep values around for the rest of the session."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        _stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        Ok(Value::String {
+            val: get_full_help(&Stash.signature(), &Stash.examples(), engine_state),
+            span: call.head,
+        }
+        .into_pipeline_data())
+    }
+}
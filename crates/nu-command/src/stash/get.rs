@@ -0,0 +1,53 @@
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Example, IntoPipelineData, PipelineData, ShellError, Signature, Spanned, SyntaxShape,
+};
+
+use super::utils::lock_stash;
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "stash get"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("stash get").required(
+            "name",
+            SyntaxShape::String,
+            "the name the value was stashed as",
+        )
+    }
+
+    fn usage(&self) -> &str {
+        "Get back a stashed value."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let name: Spanned<String> = call.req(engine_state, stack, 0)?;
+
+        match lock_stash(engine_state)?.get(&name.item) {
+            Some(value) => Ok(value.clone().into_pipeline_data()),
+            None => Err(ShellError::StashNotFound(name.item, name.span)),
+        }
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example {
+            description: "Get back the value stashed as 'project'",
+            example: "stash get project",
+            result: None,
+            ..Default::default()
+        }]
+    }
+}
@@ -0,0 +1,64 @@
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Example, IntoInterruptiblePipelineData, PipelineData, ShellError, Signature, Value,
+};
+
+use super::utils::lock_stash;
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "stash list"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("stash list")
+    }
+
+    fn usage(&self) -> &str {
+        "List the names of the stashed values, with their types."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        _stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let span = call.head;
+
+        let mut entries: Vec<_> = lock_stash(engine_state)?
+            .iter()
+            .map(|(name, value)| (name.clone(), value.get_type().to_string()))
+            .collect();
+        entries.sort();
+
+        Ok(entries
+            .into_iter()
+            .map(move |(name, type_name)| Value::Record {
+                cols: vec!["name".into(), "type".into()],
+                vals: vec![
+                    Value::String { val: name, span },
+                    Value::String {
+                        val: type_name,
+                        span,
+                    },
+                ],
+                span,
+            })
+            .into_pipeline_data(engine_state.ctrlc.clone()))
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example {
+            description: "List what's been stashed so far",
+            example: "stash list",
+            result: None,
+            ..Default::default()
+        }]
+    }
+}
@@ -0,0 +1,12 @@
+mod command;
+mod get;
+mod list;
+mod remove;
+mod set;
+mod utils;
+
+pub use command::Stash;
+pub use get::SubCommand as StashGet;
+pub use list::SubCommand as StashList;
+pub use remove::SubCommand as StashRemove;
+pub use set::SubCommand as StashSet;
@@ -0,0 +1,51 @@
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{Example, PipelineData, ShellError, Signature, Spanned, SyntaxShape};
+
+use super::utils::lock_stash;
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "stash remove"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("stash remove").required(
+            "name",
+            SyntaxShape::String,
+            "the name the value was stashed as",
+        )
+    }
+
+    fn usage(&self) -> &str {
+        "Drop a stashed value."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let name: Spanned<String> = call.req(engine_state, stack, 0)?;
+
+        match lock_stash(engine_state)?.remove(&name.item) {
+            Some(_) => Ok(PipelineData::new(call.head)),
+            None => Err(ShellError::StashNotFound(name.item, name.span)),
+        }
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![Example {
+            description: "Drop the value stashed as 'project'",
+            example: "stash remove project",
+            result: None,
+            ..Default::default()
+        }]
+    }
+}
@@ -0,0 +1,104 @@
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{Example, PipelineData, ShellError, Signature, Spanned, SyntaxShape, Value};
+
+use super::utils::lock_stash;
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "stash set"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("stash set")
+            .required(
+                "name",
+                SyntaxShape::String,
+                "the name to stash the input as",
+            )
+            .switch(
+                "no-clobber",
+                "refuse to overwrite a value already stashed under the name",
+                Some('n'),
+            )
+    }
+
+    fn usage(&self) -> &str {
+        "Stash the input under a name, for the rest of the session."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let name: Spanned<String> = call.req(engine_state, stack, 0)?;
+        let no_clobber = call.has_flag("no-clobber");
+
+        // Streams are collected, so that what's stashed doesn't depend on who else pulls from them
+        let value = input.into_value(call.head);
+        if let Value::Error { error } = value {
+            return Err(error);
+        }
+
+        let mut stash = lock_stash(engine_state)?;
+        if no_clobber && stash.contains_key(&name.item) {
+            return Err(ShellError::StashExists(name.item, name.span));
+        }
+        stash.insert(name.item, value);
+
+        Ok(PipelineData::new(call.head))
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Stash a record",
+                example: "{name: nu, tags: [shell]} | stash set project",
+                result: None,
+                ..Default::default()
+            },
+            Example {
+                description: "Stash a value, unless the name is already in use",
+                example: "[1 2 3] | stash set numbers --no-clobber",
+                result: None,
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use nu_engine::test_support::{engine_state_with, eval_source, stack_with_config};
+    use nu_protocol::{IntoInterruptiblePipelineData, Span};
+
+    #[test]
+    fn streams_are_collected() {
+        let mut engine_state = engine_state_with(vec![Box::new(SubCommand)]);
+        let mut stack = stack_with_config();
+
+        let input = (1..=3)
+            .map(Value::test_int)
+            .into_pipeline_data(engine_state.ctrlc.clone());
+        eval_source(&mut engine_state, &mut stack, "stash set numbers", input)
+            .expect("stash set should succeed");
+
+        let stash = lock_stash(&engine_state).expect("stash is not poisoned");
+        assert_eq!(
+            stash.get("numbers"),
+            Some(&Value::List {
+                vals: vec![Value::test_int(1), Value::test_int(2), Value::test_int(3)],
+                span: Span::unknown(),
+            })
+        );
+    }
+}
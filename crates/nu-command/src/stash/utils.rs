@@ -0,0 +1,14 @@
+use std::collections::HashMap;
+use std::sync::MutexGuard;
+
+use nu_protocol::engine::EngineState;
+use nu_protocol::{ShellError, Value};
+
+pub fn lock_stash(
+    engine_state: &EngineState,
+) -> Result<MutexGuard<HashMap<String, Value>>, ShellError> {
+    engine_state
+        .stash
+        .lock()
+        .map_err(|_| ShellError::InternalError("the stash was poisoned".into()))
+}
@@ -2,7 +2,7 @@ use nu_engine::CallExt;
 use nu_protocol::ast::{Call, PathMember};
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{
-    Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value, ValueStream,
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value, ValueStream,
 };
 
 #[derive(Clone)]
@@ -14,11 +14,13 @@ impl Command for Format {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("format").required(
-            "pattern",
-            SyntaxShape::String,
-            "the pattern to output. e.g.) \"{foo}: {bar}\"",
-        )
+        Signature::build("format")
+            .required(
+                "pattern",
+                SyntaxShape::String,
+                "the pattern to output. e.g.) \"{foo}: {bar}\"",
+            )
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -49,6 +51,7 @@ impl Command for Format {
                 description: "Print filenames with their sizes",
                 example: "ls | format '{name}: {size}'",
                 result: None,
+                ..Default::default()
             },
             Example {
                 description: "Print elements from some columns of a table",
@@ -57,6 +60,7 @@ impl Command for Format {
                     vals: vec![Value::test_string("v2"), Value::test_string("v4")],
                     span: Span::new(0, 0),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -0,0 +1,220 @@
+use crate::{map_rows, update_cells};
+use nu_engine::CallExt;
+use nu_protocol::ast::{Call, CellPath};
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, Spanned, SyntaxShape, Value,
+};
+
+/// The units sizes can be shown in, with the number of bytes in each
+const UNITS: [(&str, i64); 11] = [
+    ("B", 1),
+    ("KB", 1000),
+    ("KiB", 1 << 10),
+    ("MB", 1_000_000),
+    ("MiB", 1 << 20),
+    ("GB", 1_000_000_000),
+    ("GiB", 1 << 30),
+    ("TB", 1_000_000_000_000),
+    ("TiB", 1 << 40),
+    ("PB", 1_000_000_000_000_000),
+    ("PiB", 1 << 50),
+];
+
+#[derive(Clone)]
+pub struct FormatFilesize;
+
+impl Command for FormatFilesize {
+    fn name(&self) -> &str {
+        "format filesize"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("format filesize")
+            .required(
+                "unit",
+                SyntaxShape::String,
+                "the unit to show the sizes in, eg KB or MiB",
+            )
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally format filesizes by column paths",
+            )
+            .category(Category::Strings)
+    }
+
+    fn usage(&self) -> &str {
+        "Show filesizes in the given unit, however big they are."
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let head = call.head;
+        let unit: Spanned<String> = call.req(engine_state, stack, 0)?;
+        let unit = parse_unit(&unit)?;
+        let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 1)?;
+
+        map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+            if column_paths.is_empty() {
+                action(&v, unit, head)
+            } else {
+                update_cells(v, row, &column_paths, head, move |old| {
+                    action(old, unit, head)
+                })
+            }
+        })
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Show a filesize in kilobytes",
+                example: "1kib | format filesize KB",
+                result: Some(Value::test_string("1.02 KB")),
+                ..Default::default()
+            },
+            Example {
+                description: "Show the sizes of a table column in the same unit",
+                example: "[[name size]; [a 2mb] [b 300kb]] | format filesize mib size",
+                result: Some(Value::List {
+                    vals: vec![
+                        Value::Record {
+                            cols: vec!["name".to_string(), "size".to_string()],
+                            vals: vec![Value::test_string("a"), Value::test_string("1.91 MiB")],
+                            span: Span::unknown(),
+                        },
+                        Value::Record {
+                            cols: vec!["name".to_string(), "size".to_string()],
+                            vals: vec![Value::test_string("b"), Value::test_string("0.29 MiB")],
+                            span: Span::unknown(),
+                        },
+                    ],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+/// Find the unit, whatever the case it's written in
+fn parse_unit(unit: &Spanned<String>) -> Result<(&'static str, i64), ShellError> {
+    UNITS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(&unit.item))
+        .copied()
+        .ok_or_else(|| {
+            let names: Vec<&str> = UNITS.iter().map(|(name, _)| *name).collect();
+
+            ShellError::UnsupportedInput(
+                format!(
+                    "unknown unit '{}', expected one of {}",
+                    unit.item,
+                    names.join(", ")
+                ),
+                unit.span,
+            )
+        })
+}
+
+fn format_in_unit(num_bytes: i64, (name, bytes_per_unit): (&str, i64)) -> String {
+    if bytes_per_unit == 1 {
+        format!("{} {}", num_bytes, name)
+    } else {
+        format!("{:.2} {}", num_bytes as f64 / bytes_per_unit as f64, name)
+    }
+}
+
+fn action(input: &Value, unit: (&'static str, i64), head: Span) -> Value {
+    match input {
+        Value::Filesize { val, .. } => Value::String {
+            val: format_in_unit(*val, unit),
+            span: head,
+        },
+        other => Value::Error {
+            error: ShellError::UnsupportedInput(
+                format!(
+                    "Input's type is {}. This command only works with filesizes.",
+                    other.get_type()
+                ),
+                head,
+            ),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(FormatFilesize {})
+    }
+
+    fn format_as(num_bytes: i64, unit: &str) -> String {
+        let unit = parse_unit(&Spanned {
+            item: unit.to_string(),
+            span: Span::unknown(),
+        })
+        .expect("unit should be known");
+
+        format_in_unit(num_bytes, unit)
+    }
+
+    #[test]
+    fn formats_boundary_values() {
+        assert_eq!(format_as(0, "B"), "0 B");
+        assert_eq!(format_as(0, "KB"), "0.00 KB");
+        assert_eq!(format_as(999, "B"), "999 B");
+        assert_eq!(format_as(1000, "KB"), "1.00 KB");
+        assert_eq!(format_as(1000, "KiB"), "0.98 KiB");
+        assert_eq!(format_as(1024, "KB"), "1.02 KB");
+        assert_eq!(format_as(1024, "KiB"), "1.00 KiB");
+        assert_eq!(format_as(1 << 20, "MB"), "1.05 MB");
+        assert_eq!(format_as(1 << 20, "KiB"), "1024.00 KiB");
+        assert_eq!(format_as(1_000_000_000, "GB"), "1.00 GB");
+        assert_eq!(format_as(1_000_000_000, "MiB"), "953.67 MiB");
+    }
+
+    #[test]
+    fn formats_very_large_sizes() {
+        assert_eq!(format_as(i64::MAX, "B"), "9223372036854775807 B");
+        assert_eq!(format_as(i64::MAX, "GB"), "9223372036.85 GB");
+        assert_eq!(format_as(i64::MAX, "GiB"), "8589934592.00 GiB");
+        assert_eq!(format_as(i64::MAX, "PiB"), "8192.00 PiB");
+    }
+
+    #[test]
+    fn units_ignore_case() {
+        assert_eq!(format_as(1 << 20, "mib"), "1.00 MiB");
+        assert_eq!(format_as(1 << 20, "MIB"), "1.00 MiB");
+        assert_eq!(format_as(2000, "kb"), "2.00 KB");
+    }
+
+    #[test]
+    fn unknown_units_list_the_valid_ones() {
+        let result = parse_unit(&Spanned {
+            item: "XB".to_string(),
+            span: Span::unknown(),
+        });
+
+        match result {
+            Err(ShellError::UnsupportedInput(msg, _)) => {
+                assert_eq!(
+                    msg,
+                    "unknown unit 'XB', expected one of B, KB, KiB, MB, MiB, GB, GiB, TB, TiB, PB, PiB"
+                )
+            }
+            other => panic!("expected an unsupported input error, got {:?}", other),
+        }
+    }
+}
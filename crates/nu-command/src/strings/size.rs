@@ -1,10 +1,11 @@
 extern crate unicode_segmentation;
 
+use crate::map_values;
 use unicode_segmentation::UnicodeSegmentation;
 
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, Type, Value};
+use nu_protocol::{Category, Example, PipelineData, ShellError, Signature, Span, Type, Value};
 
 #[derive(Clone)]
 pub struct Size;
@@ -15,7 +16,7 @@ impl Command for Size {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("size")
+        Signature::build("size").category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -64,6 +65,7 @@ impl Command for Size {
                     ],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Counts Unicode characters correctly in a string",
@@ -95,6 +97,7 @@ impl Command for Size {
                     ],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -106,8 +109,8 @@ fn size(
     input: PipelineData,
 ) -> Result<PipelineData, ShellError> {
     let span = call.head;
-    input.map(
-        move |v| match v.as_string() {
+    map_values(input, engine_state.ctrlc.clone(), move |v| {
+        match v.as_string() {
             Ok(s) => count(&s, span),
             Err(_) => Value::Error {
                 error: ShellError::PipelineMismatch {
@@ -116,9 +119,8 @@ fn size(
                     origin: span,
                 },
             },
-        },
-        engine_state.ctrlc.clone(),
-    )
+        }
+    })
 }
 
 fn count(contents: &str, span: Span) -> Value {
@@ -1,7 +1,7 @@
 use nu_protocol::{
     ast::Call,
     engine::{Command, EngineState, Stack},
-    Example, PipelineData, ShellError, Signature, Span, Type, Value,
+    Category, Example, PipelineData, ShellError, Signature, Span, Type, Value,
 };
 
 #[derive(Clone)]
@@ -13,7 +13,7 @@ impl Command for SubCommand {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("split chars")
+        Signature::build("split chars").category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -34,6 +34,7 @@ impl Command for SubCommand {
                 ],
                 span: Span::unknown(),
             }),
+            ..Default::default()
         }]
     }
 
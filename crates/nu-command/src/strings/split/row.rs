@@ -2,7 +2,7 @@ use nu_engine::CallExt;
 use nu_protocol::{
     ast::Call,
     engine::{Command, EngineState, Stack},
-    PipelineData, ShellError, Signature, Span, Spanned, SyntaxShape, Type, Value,
+    Category, PipelineData, ShellError, Signature, Span, Spanned, SyntaxShape, Type, Value,
 };
 
 #[derive(Clone)]
@@ -14,11 +14,13 @@ impl Command for SubCommand {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("split row").required(
-            "separator",
-            SyntaxShape::String,
-            "the character that denotes what separates rows",
-        )
+        Signature::build("split row")
+            .required(
+                "separator",
+                SyntaxShape::String,
+                "the character that denotes what separates rows",
+            )
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
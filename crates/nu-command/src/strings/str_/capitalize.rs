@@ -1,8 +1,11 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::ast::Call;
 use nu_protocol::ast::CellPath;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
 
 #[derive(Clone)]
 pub struct SubCommand;
@@ -13,11 +16,15 @@ impl Command for SubCommand {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("str capitalize").rest(
-            "rest",
-            SyntaxShape::CellPath,
-            "optionally capitalize text by column paths",
-        )
+        Signature::build("str capitalize")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally capitalize text by column paths",
+            )
+            .input_type(Type::String)
+            .output_type(Type::String)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -43,6 +50,7 @@ impl Command for SubCommand {
                     val: "Good day".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Capitalize contents",
@@ -51,6 +59,7 @@ impl Command for SubCommand {
                     val: "Anton".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Capitalize a column in a table",
@@ -69,6 +78,7 @@ impl Command for SubCommand {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -82,31 +92,20 @@ fn operate(
 ) -> Result<PipelineData, ShellError> {
     let head = call.head;
     let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
-    input.map(
-        move |v| {
-            if column_paths.is_empty() {
-                action(&v, head)
-            } else {
-                let mut ret = v;
-                for path in &column_paths {
-                    let r =
-                        ret.update_cell_path(&path.members, Box::new(move |old| action(old, head)));
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, head)
+        } else {
+            update_cells(v, row, &column_paths, head, move |old| action(old, head))
+        }
+    })
 }
 
 fn action(input: &Value, head: Span) -> Value {
     match input {
         Value::String { val, .. } => Value::String {
             val: uppercase_helper(val),
-            span: head,
+            span: input.span_or(head),
         },
         other => Value::Error {
             error: ShellError::UnsupportedInput(
@@ -114,7 +113,7 @@ fn action(input: &Value, head: Span) -> Value {
                     "Input's type is {}. This command only works with strings.",
                     other.get_type()
                 ),
-                Span::unknown(),
+                other.span_or(head),
             ),
         },
     }
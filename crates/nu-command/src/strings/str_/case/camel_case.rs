@@ -1,9 +1,10 @@
-use inflector::cases::camelcase::to_camel_case;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
 
-use crate::operate;
+use crate::operate_in_style;
 
 #[derive(Clone)]
 pub struct SubCommand;
@@ -14,11 +15,15 @@ impl Command for SubCommand {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("str camel-case").rest(
-            "rest",
-            SyntaxShape::CellPath,
-            "optionally convert text to camelCase by column paths",
-        )
+        Signature::build("str camel-case")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally convert text to camelCase by column paths",
+            )
+            .input_type(Type::String)
+            .output_type(Type::String)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -32,7 +37,7 @@ impl Command for SubCommand {
         call: &Call,
         input: PipelineData,
     ) -> Result<PipelineData, ShellError> {
-        operate(engine_state, stack, call, input, &to_camel_case)
+        operate_in_style(engine_state, stack, call, input, "camel")
     }
 
     fn examples(&self) -> Vec<Example> {
@@ -44,6 +49,7 @@ impl Command for SubCommand {
                     val: "nuShell".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a string to camelCase",
@@ -52,6 +58,7 @@ impl Command for SubCommand {
                     val: "thisIsTheFirstCase".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a string to camelCase",
@@ -60,6 +67,7 @@ impl Command for SubCommand {
                     val: "thisIsTheSecondCase".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a column from a table to camelCase",
@@ -78,6 +86,7 @@ impl Command for SubCommand {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
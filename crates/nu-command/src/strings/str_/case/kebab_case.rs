@@ -1,9 +1,10 @@
-use inflector::cases::kebabcase::to_kebab_case;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
 
-use crate::operate;
+use crate::operate_in_style;
 
 #[derive(Clone)]
 pub struct SubCommand;
@@ -14,11 +15,15 @@ impl Command for SubCommand {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("str kebab-case").rest(
-            "rest",
-            SyntaxShape::CellPath,
-            "optionally convert text to kebab-case by column paths",
-        )
+        Signature::build("str kebab-case")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally convert text to kebab-case by column paths",
+            )
+            .input_type(Type::String)
+            .output_type(Type::String)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -32,7 +37,7 @@ impl Command for SubCommand {
         call: &Call,
         input: PipelineData,
     ) -> Result<PipelineData, ShellError> {
-        operate(engine_state, stack, call, input, &to_kebab_case)
+        operate_in_style(engine_state, stack, call, input, "kebab")
     }
 
     fn examples(&self) -> Vec<Example> {
@@ -44,6 +49,7 @@ impl Command for SubCommand {
                     val: "nu-shell".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a string to kebab-case",
@@ -52,6 +58,7 @@ impl Command for SubCommand {
                     val: "this-is-the-first-case".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a string to kebab-case",
@@ -60,6 +67,7 @@ impl Command for SubCommand {
                     val: "this-is-the-second-case".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a column from a table to kebab-case",
@@ -78,6 +86,7 @@ impl Command for SubCommand {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
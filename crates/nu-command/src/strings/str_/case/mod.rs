@@ -4,6 +4,7 @@ pub mod kebab_case;
 pub mod pascal_case;
 pub mod screaming_snake_case;
 pub mod snake_case;
+pub mod style;
 
 pub use camel_case::SubCommand as StrCamelCase;
 pub use command::Str;
@@ -11,13 +12,54 @@ pub use kebab_case::SubCommand as StrKebabCase;
 pub use pascal_case::SubCommand as StrPascalCase;
 pub use screaming_snake_case::SubCommand as StrScreamingSnakeCase;
 pub use snake_case::SubCommand as StrSnakeCase;
+pub use style::SubCommand as StrCase;
 
+use crate::{map_rows, update_cells};
+use inflector::cases::{
+    camelcase::to_camel_case, kebabcase::to_kebab_case, pascalcase::to_pascal_case,
+    screamingsnakecase::to_screaming_snake_case, snakecase::to_snake_case,
+    titlecase::to_title_case,
+};
 use nu_engine::CallExt;
 
 use nu_protocol::ast::{Call, CellPath};
 use nu_protocol::engine::{EngineState, Stack};
 use nu_protocol::{PipelineData, ShellError, Span, Value};
 
+/// The styles `str case` converts to, by name. The dedicated subcommands (`str kebab-case` etc)
+/// look their conversion up here too
+pub const CASE_STYLES: &[(&str, fn(&str) -> String)] = &[
+    ("kebab", to_kebab_case),
+    ("snake", to_snake_case),
+    ("camel", to_camel_case),
+    ("pascal", to_pascal_case),
+    ("screaming-snake", to_screaming_snake_case),
+    ("title", to_title_case),
+];
+
+/// The conversion for the style called `name`, if there is one
+pub fn case_style(name: &str) -> Option<&'static fn(&str) -> String> {
+    CASE_STYLES
+        .iter()
+        .find(|(style, _)| *style == name)
+        .map(|(_, case_operation)| case_operation)
+}
+
+/// Convert the input, or the cells at the call's column paths, to the style called `name`, which
+/// has to be one of `CASE_STYLES`
+pub fn operate_in_style(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    call: &Call,
+    input: PipelineData,
+    name: &str,
+) -> Result<PipelineData, ShellError> {
+    let case_operation =
+        case_style(name).unwrap_or_else(|| panic!("'{}' isn't one of CASE_STYLES", name));
+
+    operate(engine_state, stack, call, input, case_operation)
+}
+
 pub fn operate<F>(
     engine_state: &EngineState,
     stack: &mut Stack,
@@ -28,29 +70,31 @@ pub fn operate<F>(
 where
     F: Fn(&str) -> String + Send + Sync + 'static,
 {
-    let head = call.head;
     let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
 
-    input.map(
-        move |v| {
-            if column_paths.is_empty() {
-                action(&v, case_operation, head)
-            } else {
-                let mut ret = v;
-                for path in &column_paths {
-                    let r = ret.update_cell_path(
-                        &path.members,
-                        Box::new(move |old| action(old, case_operation, head)),
-                    );
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    operate_on_paths(engine_state, call.head, column_paths, input, case_operation)
+}
+
+/// Convert the input, or the cells at `column_paths` if there are any
+pub fn operate_on_paths<F>(
+    engine_state: &EngineState,
+    head: Span,
+    column_paths: Vec<CellPath>,
+    input: PipelineData,
+    case_operation: &'static F,
+) -> Result<PipelineData, ShellError>
+where
+    F: Fn(&str) -> String + Send + Sync + 'static,
+{
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, case_operation, head)
+        } else {
+            update_cells(v, row, &column_paths, head, move |old| {
+                action(old, case_operation, head)
+            })
+        }
+    })
 }
 
 pub fn action<F>(input: &Value, case_operation: &F, head: Span) -> Value
@@ -60,7 +104,7 @@ where
     match input {
         Value::String { val, .. } => Value::String {
             val: case_operation(val),
-            span: head,
+            span: input.span_or(head),
         },
         other => Value::Error {
             error: ShellError::UnsupportedInput(
@@ -68,7 +112,7 @@ where
                     "Input's type is {}. This command only works with strings.",
                     other.get_type()
                 ),
-                Span::unknown(),
+                other.span_or(head),
             ),
         },
     }
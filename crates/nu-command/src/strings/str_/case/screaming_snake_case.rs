@@ -1,9 +1,10 @@
-use inflector::cases::screamingsnakecase::to_screaming_snake_case;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
 
-use crate::operate;
+use crate::operate_in_style;
 #[derive(Clone)]
 pub struct SubCommand;
 
@@ -13,11 +14,15 @@ impl Command for SubCommand {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("str screaming-snake-case").rest(
-            "rest",
-            SyntaxShape::CellPath,
-            "optionally convert text to SCREAMING_SNAKE_CASE by column paths",
-        )
+        Signature::build("str screaming-snake-case")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally convert text to SCREAMING_SNAKE_CASE by column paths",
+            )
+            .input_type(Type::String)
+            .output_type(Type::String)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -31,7 +36,7 @@ impl Command for SubCommand {
         call: &Call,
         input: PipelineData,
     ) -> Result<PipelineData, ShellError> {
-        operate(engine_state, stack, call, input, &to_screaming_snake_case)
+        operate_in_style(engine_state, stack, call, input, "screaming-snake")
     }
 
     fn examples(&self) -> Vec<Example> {
@@ -43,6 +48,7 @@ impl Command for SubCommand {
                     val: "NU_SHELL".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a string to camelCase",
@@ -51,6 +57,7 @@ impl Command for SubCommand {
                     val: "THIS_IS_THE_SECOND_CASE".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a string to camelCase",
@@ -59,6 +66,7 @@ impl Command for SubCommand {
                     val: "THIS_IS_THE_FIRST_CASE".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a column from a table to SCREAMING_SNAKE_CASE",
@@ -77,6 +85,7 @@ impl Command for SubCommand {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -1,9 +1,10 @@
-use inflector::cases::snakecase::to_snake_case;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
 
-use crate::operate;
+use crate::operate_in_style;
 #[derive(Clone)]
 pub struct SubCommand;
 
@@ -13,11 +14,15 @@ impl Command for SubCommand {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("str snake-case").rest(
-            "rest",
-            SyntaxShape::CellPath,
-            "optionally convert text to snake_case by column paths",
-        )
+        Signature::build("str snake-case")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally convert text to snake_case by column paths",
+            )
+            .input_type(Type::String)
+            .output_type(Type::String)
+            .category(Category::Strings)
     }
     fn usage(&self) -> &str {
         "converts a string to snake_case"
@@ -30,7 +35,7 @@ impl Command for SubCommand {
         call: &Call,
         input: PipelineData,
     ) -> Result<PipelineData, ShellError> {
-        operate(engine_state, stack, call, input, &to_snake_case)
+        operate_in_style(engine_state, stack, call, input, "snake")
     }
 
     fn examples(&self) -> Vec<Example> {
@@ -42,6 +47,7 @@ impl Command for SubCommand {
                     val: "nu_shell".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a string to camelCase",
@@ -50,6 +56,7 @@ impl Command for SubCommand {
                     val: "this_is_the_second_case".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a string to camelCase",
@@ -58,6 +65,7 @@ impl Command for SubCommand {
                     val: "this_is_the_first_case".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "convert a column from a table to snake-case",
@@ -76,6 +84,7 @@ impl Command for SubCommand {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -0,0 +1,135 @@
+use nu_engine::CallExt;
+use nu_protocol::ast::{Call, CellPath};
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, Spanned, SyntaxShape, Type, Value,
+};
+
+use super::{case_style, operate_on_paths, CASE_STYLES};
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "str case"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("str case")
+            .required(
+                "style",
+                SyntaxShape::String,
+                "kebab, snake, camel, pascal, screaming-snake or title",
+            )
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally convert text by column paths",
+            )
+            .input_type(Type::String)
+            .output_type(Type::String)
+            .category(Category::Strings)
+    }
+
+    fn usage(&self) -> &str {
+        "converts a string to the case style given by name"
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"Each style converts the way its dedicated command does, eg `str case kebab` like
+`str kebab-case`, so that the style can be picked at runtime."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let style: Spanned<String> = call.req(engine_state, stack, 0)?;
+        let case_operation = find_style(&style)?;
+        let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 1)?;
+
+        operate_on_paths(engine_state, call.head, column_paths, input, case_operation)
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "convert a string to kebab-case",
+                example: "'NuShell' | str case kebab",
+                result: Some(Value::test_string("nu-shell")),
+                ..Default::default()
+            },
+            Example {
+                description: "convert a string to Title Case",
+                example: "'this_is_the_first_case' | str case title",
+                result: Some(Value::test_string("This Is The First Case")),
+                ..Default::default()
+            },
+            Example {
+                description: "convert a column from a table to SCREAMING_SNAKE_CASE",
+                example: r#"[[lang, gems]; [nuTest, 100]] | str case screaming-snake lang"#,
+                result: Some(Value::List {
+                    vals: vec![Value::Record {
+                        span: Span::unknown(),
+                        cols: vec!["lang".to_string(), "gems".to_string()],
+                        vals: vec![Value::test_string("NU_TEST"), Value::test_int(100)],
+                    }],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+/// Find the conversion for a style name
+fn find_style(style: &Spanned<String>) -> Result<&'static fn(&str) -> String, ShellError> {
+    case_style(&style.item).ok_or_else(|| {
+        let names: Vec<&str> = CASE_STYLES.iter().map(|(name, _)| *name).collect();
+
+        ShellError::UnsupportedInput(
+            format!(
+                "unknown case style '{}', expected one of {}",
+                style.item,
+                names.join(", ")
+            ),
+            style.span,
+        )
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(SubCommand {})
+    }
+
+    fn style(name: &str) -> Spanned<String> {
+        Spanned {
+            item: name.to_string(),
+            span: Span::unknown(),
+        }
+    }
+
+    #[test]
+    fn unknown_styles_list_the_valid_ones() {
+        match find_style(&style("sponge")) {
+            Err(ShellError::UnsupportedInput(msg, _)) => assert_eq!(
+                msg,
+                "unknown case style 'sponge', expected one of kebab, snake, camel, pascal, \
+                 screaming-snake, title"
+            ),
+            Err(other) => panic!("expected an unsupported input error, got {:?}", other),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+}
@@ -2,7 +2,8 @@ use nu_engine::CallExt;
 use nu_protocol::ast::Call;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{
-    Example, IntoPipelineData, PipelineData, ShellError, Signature, Span, SyntaxShape, Value,
+    Category, Example, IntoPipelineData, PipelineData, ShellError, Signature, Span, SyntaxShape,
+    Type, Value,
 };
 
 #[derive(Clone)]
@@ -14,11 +15,15 @@ impl Command for StrCollect {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("str collect").optional(
-            "separator",
-            SyntaxShape::String,
-            "optional separator to use when creating string",
-        )
+        Signature::build("str collect")
+            .optional(
+                "separator",
+                SyntaxShape::String,
+                "optional separator to use when creating string",
+            )
+            .input_type(Type::List(Box::new(Type::String)))
+            .output_type(Type::String)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -66,6 +71,7 @@ impl Command for StrCollect {
                     val: "nushell".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Create a string from input with a separator",
@@ -74,6 +80,7 @@ impl Command for StrCollect {
                     val: "nu-shell".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
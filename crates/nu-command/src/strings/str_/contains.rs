@@ -1,11 +1,15 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::ast::Call;
 use nu_protocol::ast::CellPath;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::{
-    Example, PipelineData, ShellError, Signature, Span, Spanned, SyntaxShape, Value,
+    Category, Config, Example, PipelineData, ShellError, Signature, Span, Spanned, SyntaxShape,
+    Type, Value,
 };
 
+use super::predicate_text;
+
 #[derive(Clone)]
 pub struct SubCommand;
 
@@ -23,6 +27,14 @@ impl Command for SubCommand {
                 "optionally check if string contains pattern by column paths",
             )
             .switch("insensitive", "search is case insensitive", Some('i'))
+            .switch(
+                "render",
+                "match values that aren't strings (like dates and file sizes) by their display form",
+                Some('r'),
+            )
+            .input_type(Type::String)
+            .output_type(Type::Bool)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -48,6 +60,7 @@ impl Command for SubCommand {
                     val: true,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Check if string contains pattern case insensitive",
@@ -56,6 +69,7 @@ impl Command for SubCommand {
                     val: true,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Check if string contains pattern in a table",
@@ -74,6 +88,7 @@ impl Command for SubCommand {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Check if string contains pattern in a table",
@@ -92,6 +107,7 @@ impl Command for SubCommand {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Check if string contains pattern in a table",
@@ -113,6 +129,7 @@ impl Command for SubCommand {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Check if string contains pattern",
@@ -121,6 +138,16 @@ impl Command for SubCommand {
                     val: false,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
+            },
+            Example {
+                description: "Check if the display form of a file size contains pattern",
+                example: "2kb | str contains '.0' --render",
+                result: Some(Value::Bool {
+                    val: true,
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
             },
         ]
     }
@@ -136,44 +163,45 @@ fn operate(
     let pattern: Spanned<String> = call.req(engine_state, stack, 0)?;
     let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 1)?;
     let case_insensitive = call.has_flag("insensitive");
+    let render = call.has_flag("render");
+    let config = stack.get_config()?;
 
-    input.map(
-        move |v| {
-            if column_paths.is_empty() {
-                action(&v, case_insensitive, &pattern.item, head)
-            } else {
-                let mut ret = v;
-                for path in &column_paths {
-                    let p = pattern.item.clone();
-                    let r = ret.update_cell_path(
-                        &path.members,
-                        Box::new(move |old| action(old, case_insensitive, &p, head)),
-                    );
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, case_insensitive, &pattern.item, render, &config, head)
+        } else {
+            let p = pattern.item.clone();
+            let config = config.clone();
+            update_cells(v, row, &column_paths, head, move |old| {
+                action(old, case_insensitive, &p, render, &config, head)
+            })
+        }
+    })
 }
 
-fn action(input: &Value, case_insensitive: bool, pattern: &str, head: Span) -> Value {
-    match input {
-        Value::String { val, .. } => Value::Bool {
+fn action(
+    input: &Value,
+    case_insensitive: bool,
+    pattern: &str,
+    render: bool,
+    config: &Config,
+    head: Span,
+) -> Value {
+    match predicate_text(input, render, config) {
+        Some(text) => Value::Bool {
             val: match case_insensitive {
-                true => val.to_lowercase().contains(pattern.to_lowercase().as_str()),
-                false => val.contains(pattern),
+                true => text
+                    .to_lowercase()
+                    .contains(pattern.to_lowercase().as_str()),
+                false => text.contains(pattern),
             },
             span: head,
         },
-        other => Value::Error {
+        None => Value::Error {
             error: ShellError::UnsupportedInput(
                 format!(
                     "Input's type is {}. This command only works with strings.",
-                    other.get_type()
+                    input.get_type()
                 ),
                 Span::unknown(),
             ),
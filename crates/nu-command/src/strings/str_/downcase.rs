@@ -1,8 +1,11 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::ast::Call;
 use nu_protocol::ast::CellPath;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
 
 #[derive(Clone)]
 pub struct SubCommand;
@@ -13,11 +16,15 @@ impl Command for SubCommand {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("str downcase").rest(
-            "rest",
-            SyntaxShape::CellPath,
-            "optionally downcase text by column paths",
-        )
+        Signature::build("str downcase")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally downcase text by column paths",
+            )
+            .input_type(Type::String)
+            .output_type(Type::String)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -43,6 +50,7 @@ impl Command for SubCommand {
                     val: "nu".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Downcase contents",
@@ -51,6 +59,16 @@ impl Command for SubCommand {
                     val: "testa".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
+            },
+            Example {
+                description: "Downcase non-ASCII contents",
+                example: "'ÉCOLE' | str downcase",
+                result: Some(Value::String {
+                    val: "école".to_string(),
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
             },
             Example {
                 description: "Downcase contents",
@@ -72,6 +90,7 @@ impl Command for SubCommand {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Downcase contents",
@@ -93,6 +112,18 @@ impl Command for SubCommand {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
+            },
+            Example {
+                description: "Downcase something that isn't a string",
+                example: "42 | str downcase",
+                result: Some(Value::Error {
+                    error: ShellError::UnsupportedInput(
+                        "This command only works with strings.".into(),
+                        Span::unknown(),
+                    ),
+                }),
+                ..Default::default()
             },
         ]
     }
@@ -106,31 +137,20 @@ fn operate(
 ) -> Result<PipelineData, ShellError> {
     let head = call.head;
     let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
-    input.map(
-        move |v| {
-            if column_paths.is_empty() {
-                action(&v, head)
-            } else {
-                let mut ret = v;
-                for path in &column_paths {
-                    let r =
-                        ret.update_cell_path(&path.members, Box::new(move |old| action(old, head)));
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, head)
+        } else {
+            update_cells(v, row, &column_paths, head, move |old| action(old, head))
+        }
+    })
 }
 
 fn action(input: &Value, head: Span) -> Value {
     match input {
         Value::String { val, .. } => Value::String {
-            val: val.to_ascii_lowercase(),
-            span: head,
+            val: val.to_lowercase(),
+            span: input.span_or(head),
         },
         other => Value::Error {
             error: ShellError::UnsupportedInput(
@@ -138,7 +158,7 @@ fn action(input: &Value, head: Span) -> Value {
                     "Input's type is {}. This command only works with strings.",
                     other.get_type()
                 ),
-                Span::unknown(),
+                other.span_or(head),
             ),
         },
     }
@@ -153,4 +173,69 @@ mod test {
 
         test_examples(SubCommand {})
     }
+
+    #[test]
+    fn downcases_column_with_non_ascii() {
+        let mut row = Value::Record {
+            cols: vec!["name".to_string()],
+            vals: vec![Value::test_string("ÀÉÎÕÜ")],
+            span: Span::unknown(),
+        };
+        let path = [nu_protocol::ast::PathMember::String {
+            val: "name".to_string(),
+            span: Span::unknown(),
+        }];
+
+        row.update_cell_path(&path, Box::new(|v| action(v, Span::unknown())))
+            .expect("column should be updated");
+
+        let name = row.follow_cell_path(&path).expect("column should exist");
+        assert_eq!(name, Value::test_string("àéîõü"));
+    }
+
+    #[test]
+    fn turkish_i_follows_unicode_default_mapping() {
+        // We use the locale-independent Unicode mapping: dotted capital I keeps its dot as a
+        // combining character, and plain I maps to a dotted i rather than the Turkish dotless ı
+        assert_eq!(
+            action(&Value::test_string("İ"), Span::unknown()),
+            Value::test_string("i\u{307}")
+        );
+        assert_eq!(
+            action(&Value::test_string("I"), Span::unknown()),
+            Value::test_string("i")
+        );
+    }
+
+    #[test]
+    fn keeps_the_span_of_the_input() {
+        let head = Span::new(20, 33);
+        let literal = Span::new(5, 10);
+
+        let output = action(
+            &Value::String {
+                val: "ABC".into(),
+                span: literal,
+            },
+            head,
+        );
+        assert_eq!(output.span_or(Span::unknown()), literal);
+
+        let output = action(&Value::test_string("ABC"), head);
+        assert_eq!(output.span_or(Span::unknown()), head);
+
+        let output = action(
+            &Value::Int {
+                val: 1,
+                span: literal,
+            },
+            head,
+        );
+        assert!(matches!(
+            output,
+            Value::Error {
+                error: ShellError::UnsupportedInput(_, span)
+            } if span == literal
+        ));
+    }
 }
@@ -1,9 +1,14 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::ast::Call;
 use nu_protocol::ast::CellPath;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::Spanned;
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+use nu_protocol::{
+    Category, Config, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
+
+use super::predicate_text;
 
 #[derive(Clone)]
 pub struct SubCommand;
@@ -21,6 +26,14 @@ impl Command for SubCommand {
                 SyntaxShape::CellPath,
                 "optionally matches suffix of text by column paths",
             )
+            .switch(
+                "render",
+                "match values that aren't strings (like dates and file sizes) by their display form",
+                Some('r'),
+            )
+            .input_type(Type::String)
+            .output_type(Type::Bool)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -46,6 +59,7 @@ impl Command for SubCommand {
                     val: true,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Checks if string ends with '.txt' pattern",
@@ -54,6 +68,16 @@ impl Command for SubCommand {
                     val: false,
                     span: Span::unknown(),
                 }),
+                ..Default::default()
+            },
+            Example {
+                description: "Checks the display form of a file size",
+                example: "2kb | str ends-with 'KB' --render",
+                result: Some(Value::Bool {
+                    val: true,
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
             },
         ]
     }
@@ -68,41 +92,33 @@ fn operate(
     let head = call.head;
     let pattern: Spanned<String> = call.req(engine_state, stack, 0)?;
     let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 1)?;
+    let render = call.has_flag("render");
+    let config = stack.get_config()?;
 
-    input.map(
-        move |v| {
-            if column_paths.is_empty() {
-                action(&v, &pattern.item, head)
-            } else {
-                let mut ret = v;
-                for path in &column_paths {
-                    let p = pattern.item.clone();
-                    let r = ret.update_cell_path(
-                        &path.members,
-                        Box::new(move |old| action(old, &p, head)),
-                    );
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, &pattern.item, render, &config, head)
+        } else {
+            let p = pattern.item.clone();
+            let config = config.clone();
+            update_cells(v, row, &column_paths, head, move |old| {
+                action(old, &p, render, &config, head)
+            })
+        }
+    })
 }
 
-fn action(input: &Value, pattern: &str, head: Span) -> Value {
-    match input {
-        Value::String { val, .. } => Value::Bool {
-            val: val.ends_with(pattern),
+fn action(input: &Value, pattern: &str, render: bool, config: &Config, head: Span) -> Value {
+    match predicate_text(input, render, config) {
+        Some(text) => Value::Bool {
+            val: text.ends_with(pattern),
             span: head,
         },
-        other => Value::Error {
+        None => Value::Error {
             error: ShellError::UnsupportedInput(
                 format!(
                     "Input's type is {}. This command only works with strings.",
-                    other.get_type()
+                    input.get_type()
                 ),
                 Span::unknown(),
             ),
@@ -1,15 +1,19 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::ast::Call;
 use nu_protocol::ast::CellPath;
-use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::engine::{Command, EngineState, RegexCache, Stack};
 use nu_protocol::Spanned;
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
-use regex::Regex;
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
 use std::sync::Arc;
 
 struct Arguments {
     all: bool,
     find: String,
+    /// Where the regex to find was given, for the error if it doesn't compile
+    find_span: Span,
     replace: String,
     column_paths: Vec<CellPath>,
 }
@@ -32,6 +36,9 @@ impl Command for SubCommand {
                 "optionally find and replace text by column paths",
             )
             .switch("all", "replace all occurrences of find string", Some('a'))
+            .input_type(Type::String)
+            .output_type(Type::String)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -57,6 +64,7 @@ impl Command for SubCommand {
                     val: "my_library.nu".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Find and replace all occurrences of find string",
@@ -65,6 +73,7 @@ impl Command for SubCommand {
                     val: "azc azc azc".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Find and replace all occurrences of find string in table",
@@ -91,6 +100,7 @@ impl Command for SubCommand {
                     }],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -105,34 +115,29 @@ fn operate(
     let find: Spanned<String> = call.req(engine_state, stack, 0)?;
     let replace: Spanned<String> = call.req(engine_state, stack, 1)?;
 
+    // A bad regex fails the command, even when there's no input to find it in
+    let regexes = engine_state.regex_cache.clone();
+    regexes.get(&find.item, find.span)?;
+
     let options = Arc::new(Arguments {
         all: call.has_flag("all"),
         find: find.item,
+        find_span: find.span,
         replace: replace.item,
         column_paths: call.rest(engine_state, stack, 2)?,
     });
     let head = call.head;
-    input.map(
-        move |v| {
-            if options.column_paths.is_empty() {
-                action(&v, &options, head)
-            } else {
-                let mut ret = v;
-                for path in &options.column_paths {
-                    let opt = options.clone();
-                    let r = ret.update_cell_path(
-                        &path.members,
-                        Box::new(move |old| action(old, &opt, head)),
-                    );
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if options.column_paths.is_empty() {
+            action(&v, &options, head, &regexes)
+        } else {
+            let opt = options.clone();
+            let regexes = regexes.clone();
+            update_cells(v, row, &options.column_paths, head, move |old| {
+                action(old, &opt, head, &regexes)
+            })
+        }
+    })
 }
 
 struct FindReplace<'a>(&'a str, &'a str);
@@ -140,33 +145,35 @@ struct FindReplace<'a>(&'a str, &'a str);
 fn action(
     input: &Value,
     Arguments {
-        find, replace, all, ..
+        find,
+        find_span,
+        replace,
+        all,
+        ..
     }: &Arguments,
     head: Span,
+    regexes: &RegexCache,
 ) -> Value {
     match input {
         Value::String { val, .. } => {
             let FindReplace(find, replacement) = FindReplace(find, replace);
-            let regex = Regex::new(find);
+            let regex = regexes.get(find, *find_span);
 
             match regex {
                 Ok(re) => {
                     if *all {
                         Value::String {
                             val: re.replace_all(val, replacement).to_string(),
-                            span: head,
+                            span: input.span_or(head),
                         }
                     } else {
                         Value::String {
                             val: re.replace(val, replacement).to_string(),
-                            span: head,
+                            span: input.span_or(head),
                         }
                     }
                 }
-                Err(_) => Value::String {
-                    val: val.to_string(),
-                    span: head,
-                },
+                Err(error) => Value::Error { error },
             }
         }
         other => Value::Error {
@@ -175,7 +182,7 @@ fn action(
                     "Input's type is {}. This command only works with strings.",
                     other.get_type()
                 ),
-                Span::unknown(),
+                other.span_or(head),
             ),
         },
     }
@@ -185,6 +192,7 @@ fn action(
 mod tests {
     use super::*;
     use super::{action, Arguments, SubCommand};
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_examples() {
@@ -202,12 +210,13 @@ mod tests {
 
         let options = Arguments {
             find: String::from("Cargo.(.+)"),
+            find_span: Span::unknown(),
             replace: String::from("Carga.$1"),
             column_paths: vec![],
             all: false,
         };
 
-        let actual = action(&word, &options, Span::unknown());
+        let actual = action(&word, &options, Span::unknown(), &RegexCache::new(1));
         assert_eq!(
             actual,
             Value::String {
@@ -216,4 +225,53 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn compiles_the_regex_once_for_all_rows() {
+        let compiled = Arc::new(AtomicUsize::new(0));
+        let counter = compiled.clone();
+        let regexes = RegexCache::with_compiler(1, move |pattern| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            regex::Regex::new(pattern)
+        });
+        let options = Arguments {
+            find: String::from("row"),
+            find_span: Span::unknown(),
+            replace: String::from("line"),
+            column_paths: vec![],
+            all: false,
+        };
+
+        for idx in 0..100 {
+            let row = Value::test_string(format!("row {}", idx));
+            let actual = action(&row, &options, Span::unknown(), &regexes);
+            assert_eq!(actual, Value::test_string(format!("line {}", idx)));
+        }
+
+        assert_eq!(compiled.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn bad_regexes_are_an_error_at_the_argument() {
+        let find_span = Span::new(20, 25);
+        let options = Arguments {
+            find: String::from("a(b"),
+            find_span,
+            replace: String::new(),
+            column_paths: vec![],
+            all: false,
+        };
+
+        match action(
+            &Value::test_string("ab"),
+            &options,
+            Span::unknown(),
+            &RegexCache::new(1),
+        ) {
+            Value::Error {
+                error: ShellError::InvalidRegex(_, span),
+            } => assert_eq!(span, find_span),
+            other => panic!("expected an invalid regex error, got {:?}", other),
+        }
+    }
 }
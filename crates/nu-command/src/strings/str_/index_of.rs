@@ -1,9 +1,12 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::ast::Call;
 use nu_protocol::ast::CellPath;
 use nu_protocol::engine::{Command, EngineState, Stack};
 use nu_protocol::Spanned;
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
 use std::sync::Arc;
 
 struct Arguments {
@@ -43,6 +46,9 @@ impl Command for SubCommand {
                 Some('r'),
             )
             .switch("end", "search from the end of the string", Some('e'))
+            .input_type(Type::String)
+            .output_type(Type::Int)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -65,31 +71,37 @@ impl Command for SubCommand {
                 description: "Returns index of pattern in string",
                 example: " 'my_library.rb' | str index-of '.rb'",
                 result: Some(Value::test_int(10)),
+                ..Default::default()
             },
             Example {
                 description: "Returns index of pattern in string with start index",
                 example: " '.rb.rb' | str index-of '.rb' -r '1,'",
                 result: Some(Value::test_int(3)),
+                ..Default::default()
             },
             Example {
                 description: "Returns index of pattern in string with end index",
                 example: " '123456' | str index-of '6' -r ',4'",
                 result: Some(Value::test_int(-1)),
+                ..Default::default()
             },
             Example {
                 description: "Returns index of pattern in string with start and end index",
                 example: " '123456' | str index-of '3' -r '1,4'",
                 result: Some(Value::test_int(2)),
+                ..Default::default()
             },
             Example {
                 description: "Alternatively you can use this form",
                 example: " '123456' | str index-of '3' -r [1 4]",
                 result: Some(Value::test_int(2)),
+                ..Default::default()
             },
             Example {
                 description: "Returns index of pattern in string",
                 example: " '/this/is/some/path/file.txt' | str index-of '/' -e",
                 result: Some(Value::test_int(18)),
+                ..Default::default()
             },
         ]
     }
@@ -110,27 +122,16 @@ fn operate(
         column_paths: call.rest(engine_state, stack, 1)?,
     });
     let head = call.head;
-    input.map(
-        move |v| {
-            if options.column_paths.is_empty() {
-                action(&v, &options, head)
-            } else {
-                let mut ret = v;
-                for path in &options.column_paths {
-                    let opt = options.clone();
-                    let r = ret.update_cell_path(
-                        &path.members,
-                        Box::new(move |old| action(old, &opt, head)),
-                    );
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if options.column_paths.is_empty() {
+            action(&v, &options, head)
+        } else {
+            let opt = options.clone();
+            update_cells(v, row, &options.column_paths, head, move |old| {
+                action(old, &opt, head)
+            })
+        }
+    })
 }
 
 fn action(
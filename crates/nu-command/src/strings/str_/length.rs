@@ -1,8 +1,11 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::ast::Call;
 use nu_protocol::ast::CellPath;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
 
 #[derive(Clone)]
 pub struct SubCommand;
@@ -13,11 +16,15 @@ impl Command for SubCommand {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("str length").rest(
-            "rest",
-            SyntaxShape::CellPath,
-            "optionally find length of text by column paths",
-        )
+        Signature::build("str length")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally find length of text by column paths",
+            )
+            .input_type(Type::String)
+            .output_type(Type::Int)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -40,6 +47,7 @@ impl Command for SubCommand {
                 description: "Return the lengths of multiple strings",
                 example: "'hello' | str length",
                 result: Some(Value::test_int(5)),
+                ..Default::default()
             },
             Example {
                 description: "Return the lengths of multiple strings",
@@ -48,6 +56,7 @@ impl Command for SubCommand {
                     vals: vec![Value::test_int(2), Value::test_int(5)],
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -61,24 +70,13 @@ fn operate(
 ) -> Result<PipelineData, ShellError> {
     let head = call.head;
     let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
-    input.map(
-        move |v| {
-            if column_paths.is_empty() {
-                action(&v, head)
-            } else {
-                let mut ret = v;
-                for path in &column_paths {
-                    let r =
-                        ret.update_cell_path(&path.members, Box::new(move |old| action(old, head)));
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, head)
+        } else {
+            update_cells(v, row, &column_paths, head, move |old| action(old, head))
+        }
+    })
 }
 
 fn action(input: &Value, head: Span) -> Value {
@@ -9,6 +9,9 @@ mod index_of;
 mod length;
 mod lpad;
 mod rpad;
+mod starts_with;
+mod trim;
+mod upcase;
 
 pub use capitalize::SubCommand as StrCapitalize;
 pub use case::*;
@@ -21,3 +24,22 @@ pub use index_of::SubCommand as StrIndexOf;
 pub use length::SubCommand as StrLength;
 pub use lpad::SubCommand as StrLpad;
 pub use rpad::SubCommand as StrRpad;
+pub use starts_with::SubCommand as StrStartsWith;
+pub use trim::SubCommand as StrTrim;
+pub use upcase::SubCommand as StrUpcase;
+
+use std::borrow::Cow;
+
+use nu_protocol::{Config, Value};
+
+/// The text the string predicates (`str contains`, `str starts-with`, `str ends-with`) test.
+/// Strings are used as they are. Other values only qualify when `render` is set (the commands'
+/// `--render` flag), in which case they are matched against their full display form
+fn predicate_text<'a>(input: &'a Value, render: bool, config: &Config) -> Option<Cow<'a, str>> {
+    match input {
+        Value::String { val, .. } => Some(Cow::Borrowed(val)),
+        Value::Error { .. } => None,
+        other if render => Some(Cow::Owned(other.clone().debug_string(", ", config))),
+        _ => None,
+    }
+}
@@ -1,8 +1,11 @@
+use crate::{map_rows, update_cells};
 use nu_engine::CallExt;
 use nu_protocol::ast::Call;
 use nu_protocol::ast::CellPath;
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Value};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
 use std::sync::Arc;
 
 struct Arguments {
@@ -33,6 +36,9 @@ impl Command for SubCommand {
                 SyntaxShape::CellPath,
                 "optionally check if string contains pattern by column paths",
             )
+            .input_type(Type::String)
+            .output_type(Type::String)
+            .category(Category::Strings)
     }
 
     fn usage(&self) -> &str {
@@ -58,6 +64,7 @@ impl Command for SubCommand {
                     val: "nushell***".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Right pad a string with a character a number of places",
@@ -66,6 +73,7 @@ impl Command for SubCommand {
                     val: "1230000000".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Use rpad to truncate a string",
@@ -74,6 +82,7 @@ impl Command for SubCommand {
                     val: "123".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
             Example {
                 description: "Use rpad to pad Unicode",
@@ -82,6 +91,7 @@ impl Command for SubCommand {
                     val: "▉▉▉▉▉▉▉▉▉▉".to_string(),
                     span: Span::unknown(),
                 }),
+                ..Default::default()
             },
         ]
     }
@@ -100,27 +110,16 @@ fn operate(
     });
 
     let head = call.head;
-    input.map(
-        move |v| {
-            if options.column_paths.is_empty() {
-                action(&v, &options, head)
-            } else {
-                let mut ret = v;
-                for path in &options.column_paths {
-                    let opt = options.clone();
-                    let r = ret.update_cell_path(
-                        &path.members,
-                        Box::new(move |old| action(old, &opt, head)),
-                    );
-                    if let Err(error) = r {
-                        return Value::Error { error };
-                    }
-                }
-                ret
-            }
-        },
-        engine_state.ctrlc.clone(),
-    )
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if options.column_paths.is_empty() {
+            action(&v, &options, head)
+        } else {
+            let opt = options.clone();
+            update_cells(v, row, &options.column_paths, head, move |old| {
+                action(old, &opt, head)
+            })
+        }
+    })
 }
 
 fn action(
@@ -137,14 +136,14 @@ fn action(
                 if s < val.len() {
                     Value::String {
                         val: val.chars().take(s).collect::<String>(),
-                        span: head,
+                        span: input.span_or(head),
                     }
                 } else {
                     let mut res = val.to_string();
                     res += &character.as_ref().unwrap().repeat(s - val.chars().count());
                     Value::String {
                         val: res,
-                        span: head,
+                        span: input.span_or(head),
                     }
                 }
             }
@@ -161,7 +160,7 @@ fn action(
                     "Input's type is {}. This command only works with strings.",
                     other.get_type()
                 ),
-                Span::unknown(),
+                other.span_or(head),
             ),
         },
     }
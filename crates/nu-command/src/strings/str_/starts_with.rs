@@ -0,0 +1,139 @@
+use crate::{map_rows, update_cells};
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::ast::CellPath;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::Spanned;
+use nu_protocol::{
+    Category, Config, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
+
+use super::predicate_text;
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "str starts-with"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("str starts-with")
+            .required("pattern", SyntaxShape::String, "the pattern to match")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally matches prefix of text by column paths",
+            )
+            .switch(
+                "render",
+                "match values that aren't strings (like dates and file sizes) by their display form",
+                Some('r'),
+            )
+            .input_type(Type::String)
+            .output_type(Type::Bool)
+            .category(Category::Strings)
+    }
+
+    fn usage(&self) -> &str {
+        "checks if string starts with pattern"
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        operate(engine_state, stack, call, input)
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Checks if string starts with 'my' pattern",
+                example: "'my_library.rb' | str starts-with 'my'",
+                result: Some(Value::Bool {
+                    val: true,
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Checks if string starts with 'my_app' pattern",
+                example: "'my_library.rb' | str starts-with 'my_app'",
+                result: Some(Value::Bool {
+                    val: false,
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Checks the display form of a file size",
+                example: "2kb | str starts-with '2.0' --render",
+                result: Some(Value::Bool {
+                    val: true,
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+fn operate(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    call: &Call,
+    input: PipelineData,
+) -> Result<PipelineData, ShellError> {
+    let head = call.head;
+    let pattern: Spanned<String> = call.req(engine_state, stack, 0)?;
+    let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 1)?;
+    let render = call.has_flag("render");
+    let config = stack.get_config()?;
+
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, &pattern.item, render, &config, head)
+        } else {
+            let p = pattern.item.clone();
+            let config = config.clone();
+            update_cells(v, row, &column_paths, head, move |old| {
+                action(old, &p, render, &config, head)
+            })
+        }
+    })
+}
+
+fn action(input: &Value, pattern: &str, render: bool, config: &Config, head: Span) -> Value {
+    match predicate_text(input, render, config) {
+        Some(text) => Value::Bool {
+            val: text.starts_with(pattern),
+            span: head,
+        },
+        None => Value::Error {
+            error: ShellError::UnsupportedInput(
+                format!(
+                    "Input's type is {}. This command only works with strings.",
+                    input.get_type()
+                ),
+                Span::unknown(),
+            ),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(SubCommand {})
+    }
+}
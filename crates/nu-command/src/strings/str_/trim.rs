@@ -0,0 +1,270 @@
+use crate::{map_rows, update_cells};
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::ast::CellPath;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, Spanned, SyntaxShape, Type, Value,
+};
+use std::sync::Arc;
+
+struct Arguments {
+    /// What's trimmed instead of whitespace
+    character: Option<char>,
+    left: bool,
+    right: bool,
+    /// Collapse the runs of trimmed characters inside the string
+    all: bool,
+    /// Collapse the runs inside the string and trim the ends
+    format: bool,
+    column_paths: Vec<CellPath>,
+}
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "str trim"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("str trim")
+            .named(
+                "char",
+                SyntaxShape::String,
+                "character to trim instead of whitespace",
+                Some('c'),
+            )
+            .switch("left", "trim the start of the string only", Some('l'))
+            .switch("right", "trim the end of the string only", Some('r'))
+            .switch(
+                "all",
+                "collapse runs of whitespace inside the string to a single space",
+                Some('a'),
+            )
+            .switch(
+                "format",
+                "collapse runs of whitespace inside the string and trim both ends",
+                Some('f'),
+            )
+            .conflicts_with("all", "left")
+            .conflicts_with("all", "right")
+            .conflicts_with("format", "left")
+            .conflicts_with("format", "right")
+            .conflicts_with("all", "format")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally trim the strings at these column paths",
+            )
+            .input_type(Type::String)
+            .output_type(Type::String)
+            .category(Category::Strings)
+    }
+
+    fn usage(&self) -> &str {
+        "Trim whitespace or a specific character from strings."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"Whitespace is what Unicode calls whitespace, so tabs, newlines and non-breaking spaces (U+00A0)
+are trimmed too. --all and --format replace each run of whitespace inside the string with a
+single space, or a run of the --char character with a single one of it."#
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        operate(engine_state, stack, call, input)
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Trim whitespace from both ends of a string",
+                example: "'  nu shell  ' | str trim",
+                result: Some(Value::test_string("nu shell")),
+                ..Default::default()
+            },
+            Example {
+                description: "Trim a specific character from the start of a string",
+                example: "'--nushell--' | str trim --char '-' --left",
+                result: Some(Value::test_string("nushell--")),
+                ..Default::default()
+            },
+            Example {
+                description: "Collapse the whitespace inside a string",
+                example: "' a   b  c ' | str trim --all",
+                result: Some(Value::test_string(" a b c ")),
+                ..Default::default()
+            },
+            Example {
+                description: "Collapse the whitespace inside a string and trim its ends",
+                example: "' a   b  c ' | str trim --format",
+                result: Some(Value::test_string("a b c")),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+fn operate(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    call: &Call,
+    input: PipelineData,
+) -> Result<PipelineData, ShellError> {
+    let character: Option<Spanned<String>> = call.get_flag(engine_state, stack, "char")?;
+    let character = match character {
+        Some(Spanned { item, span }) => {
+            let mut chars = item.chars();
+            match (chars.next(), chars.next()) {
+                (Some(character), None) => Some(character),
+                _ => {
+                    return Err(ShellError::UnsupportedInput(
+                        "--char needs a single character".into(),
+                        span,
+                    ))
+                }
+            }
+        }
+        None => None,
+    };
+
+    let options = Arc::new(Arguments {
+        character,
+        left: call.has_flag("left"),
+        right: call.has_flag("right"),
+        all: call.has_flag("all"),
+        format: call.has_flag("format"),
+        column_paths: call.rest(engine_state, stack, 0)?,
+    });
+
+    let head = call.head;
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if options.column_paths.is_empty() {
+            action(&v, &options, head)
+        } else {
+            let opt = options.clone();
+            update_cells(v, row, &options.column_paths, head, move |old| {
+                action(old, &opt, head)
+            })
+        }
+    })
+}
+
+fn action(input: &Value, options: &Arguments, head: Span) -> Value {
+    match input {
+        Value::String { val, span } => Value::String {
+            val: trim(val, options),
+            span: *span,
+        },
+        other => Value::Error {
+            error: ShellError::UnsupportedInput(
+                format!(
+                    "Input's type is {}. This command only works with strings.",
+                    other.get_type()
+                ),
+                other.span_or(head),
+            ),
+        },
+    }
+}
+
+fn trim(input: &str, options: &Arguments) -> String {
+    let is_trimmed = |c: char| match options.character {
+        Some(character) => c == character,
+        None => c.is_whitespace(),
+    };
+
+    if options.all || options.format {
+        let collapsed = collapse(input, is_trimmed, options.character.unwrap_or(' '));
+        return if options.format {
+            collapsed.trim_matches(is_trimmed).to_string()
+        } else {
+            collapsed
+        };
+    }
+
+    match (options.left, options.right) {
+        (true, false) => input.trim_start_matches(is_trimmed),
+        (false, true) => input.trim_end_matches(is_trimmed),
+        _ => input.trim_matches(is_trimmed),
+    }
+    .to_string()
+}
+
+/// Replace each run of the characters `is_trimmed` matches with a single `replacement`
+fn collapse(input: &str, is_trimmed: impl Fn(char) -> bool, replacement: char) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut in_run = false;
+
+    for c in input.chars() {
+        if !is_trimmed(c) {
+            output.push(c);
+            in_run = false;
+        } else if !in_run {
+            output.push(replacement);
+            in_run = true;
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(character: Option<char>, flags: &[&str]) -> Arguments {
+        Arguments {
+            character,
+            left: flags.contains(&"left"),
+            right: flags.contains(&"right"),
+            all: flags.contains(&"all"),
+            format: flags.contains(&"format"),
+            column_paths: vec![],
+        }
+    }
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(SubCommand {})
+    }
+
+    #[test]
+    fn trims_tabs_and_newlines() {
+        assert_eq!(trim("\t a b \n", &options(None, &[])), "a b");
+        assert_eq!(trim("\t a b \n", &options(None, &["left"])), "a b \n");
+        assert_eq!(trim("\t a b \n", &options(None, &["right"])), "\t a b");
+    }
+
+    #[test]
+    fn collapses_mixed_whitespace_to_a_space() {
+        assert_eq!(trim("a \t\t b\n\nc", &options(None, &["all"])), "a b c");
+        assert_eq!(trim("\t a  b \t", &options(None, &["format"])), "a b");
+    }
+
+    #[test]
+    fn non_breaking_spaces_are_whitespace() {
+        assert_eq!(trim("\u{a0}a\u{a0}", &options(None, &[])), "a");
+        assert_eq!(trim("a\u{a0} \u{a0}b", &options(None, &["all"])), "a b");
+    }
+
+    #[test]
+    fn collapses_runs_of_the_given_character() {
+        assert_eq!(
+            trim("--a---b-- c", &options(Some('-'), &["all"])),
+            "-a-b- c"
+        );
+        assert_eq!(trim("--a---b--", &options(Some('-'), &["format"])), "a-b");
+        assert_eq!(trim("--a  b--", &options(Some('-'), &[])), "a  b");
+    }
+}
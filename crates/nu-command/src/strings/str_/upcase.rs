@@ -0,0 +1,167 @@
+use crate::{map_rows, update_cells};
+use nu_engine::CallExt;
+use nu_protocol::ast::Call;
+use nu_protocol::ast::CellPath;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    Category, Example, PipelineData, ShellError, Signature, Span, SyntaxShape, Type, Value,
+};
+
+#[derive(Clone)]
+pub struct SubCommand;
+
+impl Command for SubCommand {
+    fn name(&self) -> &str {
+        "str upcase"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("str upcase")
+            .rest(
+                "rest",
+                SyntaxShape::CellPath,
+                "optionally upcase text by column paths",
+            )
+            .input_type(Type::String)
+            .output_type(Type::String)
+            .category(Category::Strings)
+    }
+
+    fn usage(&self) -> &str {
+        "upcases text"
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        operate(engine_state, stack, call, input)
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                description: "Upcase contents",
+                example: "'nu' | str upcase",
+                result: Some(Value::String {
+                    val: "NU".to_string(),
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Upcase non-ASCII contents, which may change the length of the string",
+                example: "'straße' | str upcase",
+                result: Some(Value::String {
+                    val: "STRASSE".to_string(),
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+            Example {
+                description: "Upcase contents",
+                example: "[[ColA ColB]; [Test abc]] | str upcase ColA",
+                result: Some(Value::List {
+                    vals: vec![Value::Record {
+                        cols: vec!["ColA".to_string(), "ColB".to_string()],
+                        vals: vec![
+                            Value::String {
+                                val: "TEST".to_string(),
+                                span: Span::unknown(),
+                            },
+                            Value::String {
+                                val: "abc".to_string(),
+                                span: Span::unknown(),
+                            },
+                        ],
+                        span: Span::unknown(),
+                    }],
+                    span: Span::unknown(),
+                }),
+                ..Default::default()
+            },
+        ]
+    }
+}
+
+fn operate(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    call: &Call,
+    input: PipelineData,
+) -> Result<PipelineData, ShellError> {
+    let head = call.head;
+    let column_paths: Vec<CellPath> = call.rest(engine_state, stack, 0)?;
+    map_rows(input, engine_state.ctrlc.clone(), move |row, v| {
+        if column_paths.is_empty() {
+            action(&v, head)
+        } else {
+            update_cells(v, row, &column_paths, head, move |old| action(old, head))
+        }
+    })
+}
+
+fn action(input: &Value, head: Span) -> Value {
+    match input {
+        Value::String { val, .. } => Value::String {
+            val: val.to_uppercase(),
+            span: input.span_or(head),
+        },
+        other => Value::Error {
+            error: ShellError::UnsupportedInput(
+                format!(
+                    "Input's type is {}. This command only works with strings.",
+                    other.get_type()
+                ),
+                other.span_or(head),
+            ),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(SubCommand {})
+    }
+
+    #[test]
+    fn upcases_column_with_non_ascii() {
+        let mut row = Value::Record {
+            cols: vec!["name".to_string()],
+            vals: vec![Value::test_string("àéîõü ß")],
+            span: Span::unknown(),
+        };
+        let path = [nu_protocol::ast::PathMember::String {
+            val: "name".to_string(),
+            span: Span::unknown(),
+        }];
+
+        row.update_cell_path(&path, Box::new(|v| action(v, Span::unknown())))
+            .expect("column should be updated");
+
+        let name = row.follow_cell_path(&path).expect("column should exist");
+        assert_eq!(name, Value::test_string("ÀÉÎÕÜ SS"));
+    }
+
+    #[test]
+    fn turkish_i_follows_unicode_default_mapping() {
+        // We use the locale-independent Unicode mapping: plain i maps to I rather than the
+        // Turkish dotted İ, and dotless ı maps to plain I
+        assert_eq!(
+            action(&Value::test_string("i"), Span::unknown()),
+            Value::test_string("I")
+        );
+        assert_eq!(
+            action(&Value::test_string("ı"), Span::unknown()),
+            Value::test_string("I")
+        );
+    }
+}
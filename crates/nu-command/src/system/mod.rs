@@ -2,8 +2,10 @@ mod benchmark;
 mod ps;
 mod run_external;
 mod sys;
+mod timeout;
 
 pub use benchmark::Benchmark;
 pub use ps::Ps;
 pub use run_external::{External, ExternalCommand};
 pub use sys::Sys;
+pub use timeout::Timeout;
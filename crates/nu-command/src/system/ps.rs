@@ -43,6 +43,7 @@ impl Command for Ps {
             description: "List the system processes",
             example: "ps",
             result: None,
+            ..Default::default()
         }]
     }
 }
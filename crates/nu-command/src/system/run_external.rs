@@ -1,18 +1,20 @@
-use std::borrow::Cow;
 use std::collections::HashMap;
 use std::env;
-use std::io::{BufRead, BufReader, Write};
-use std::process::{Command as CommandSys, Stdio};
-use std::sync::atomic::Ordering;
-use std::sync::mpsc;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::process::{Child, Command as CommandSys, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
 
 use nu_protocol::engine::{EngineState, Stack};
 use nu_protocol::{ast::Call, engine::Command, ShellError, Signature, SyntaxShape, Value};
 use nu_protocol::{Config, IntoInterruptiblePipelineData, PipelineData, Span, Spanned};
 
-use nu_engine::CallExt;
+use nu_engine::{env_vars_as_strings, CallExt};
 
 const OUTPUT_BUFFER_SIZE: usize = 8192;
+/// How often a running external checks if the pipeline it's in was cancelled
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 #[derive(Clone)]
 pub struct External;
@@ -42,7 +44,7 @@ impl Command for External {
         let name: Spanned<String> = call.req(engine_state, stack, 0)?;
         let args: Vec<String> = call.rest(engine_state, stack, 1)?;
         let last_expression = call.has_flag("last_expression");
-        let env_vars = stack.get_env_vars();
+        let env_vars = env_vars_as_strings(engine_state, stack);
 
         let config = stack.get_config()?;
 
@@ -71,6 +73,7 @@ impl ExternalCommand {
         config: Config,
     ) -> Result<PipelineData, ShellError> {
         let mut process = self.create_command();
+        let invalid_utf8 = InvalidUtf8::from_config(&config);
 
         let ctrlc = engine_state.ctrlc.clone();
 
@@ -147,41 +150,60 @@ impl ExternalCommand {
                                 )
                             })
                             .unwrap();
+                        let ctrlc = ctrlc.clone();
+
+                        // Reading happens on a thread of its own, so that the child can still be
+                        // killed while a read is waiting for output
+                        std::thread::spawn(move || {
+                            // Stdout is read using the Buffer reader. It will do so until there is an
+                            // error or there are no more bytes to read
+                            let mut buf_read = BufReader::with_capacity(OUTPUT_BUFFER_SIZE, stdout);
+                            let mut decoder = Utf8Decoder::new(invalid_utf8);
+                            loop {
+                                let bytes = match buf_read.fill_buf() {
+                                    Ok(bytes) if bytes.is_empty() => {
+                                        if let Some(data) = decoder.finish() {
+                                            let _ = tx.send(data);
+                                        }
+                                        break;
+                                    }
+                                    Ok(bytes) => bytes,
+                                    Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                                    Err(_) => {
+                                        if let Some(data) = decoder.abandon() {
+                                            let _ = tx.send(data);
+                                        }
+                                        break;
+                                    }
+                                };
 
-                        // Stdout is read using the Buffer reader. It will do so until there is an
-                        // error or there are no more bytes to read
-                        let mut buf_read = BufReader::with_capacity(OUTPUT_BUFFER_SIZE, stdout);
-                        while let Ok(bytes) = buf_read.fill_buf() {
-                            if bytes.is_empty() {
-                                break;
-                            }
+                                let output = decoder.decode(bytes);
 
-                            // The Cow generated from the function represents the conversion
-                            // from bytes to String. If no replacements are required, then the
-                            // borrowed value is a proper UTF-8 string. The Owned option represents
-                            // a string where the values had to be replaced, thus marking it as bytes
-                            let data = match String::from_utf8_lossy(bytes) {
-                                Cow::Borrowed(s) => Data::String(s.into()),
-                                Cow::Owned(_) => Data::Bytes(bytes.to_vec()),
-                            };
+                                let length = bytes.len();
+                                buf_read.consume(length);
 
-                            let length = bytes.len();
-                            buf_read.consume(length);
+                                if let Some(ctrlc) = &ctrlc {
+                                    if ctrlc.load(Ordering::SeqCst) {
+                                        break;
+                                    }
+                                }
 
-                            if let Some(ctrlc) = &ctrlc {
-                                if ctrlc.load(Ordering::SeqCst) {
+                                let mut stop = false;
+                                for data in output {
+                                    let failed = matches!(data, Data::InvalidUtf8);
+                                    if tx.send(data).is_err() || failed {
+                                        stop = true;
+                                        break;
+                                    }
+                                }
+                                if stop {
                                     break;
                                 }
                             }
-
-                            match tx.send(data) {
-                                Ok(_) => continue,
-                                Err(_) => break,
-                            }
-                        }
+                        });
                     }
 
-                    match child.wait() {
+                    match wait_or_kill(&mut child, &ctrlc) {
                         Err(err) => Err(ShellError::ExternalCommand(format!("{}", err), span)),
                         Ok(_) => Ok(()),
                     }
@@ -221,12 +243,153 @@ impl ExternalCommand {
     }
 }
 
+/// Wait for the child to exit. If the pipeline it's in is cancelled first, by Ctrl-C or by
+/// `timeout` running out, the child is killed rather than left running
+fn wait_or_kill(child: &mut Child, ctrlc: &Option<Arc<AtomicBool>>) -> std::io::Result<ExitStatus> {
+    let ctrlc = match ctrlc {
+        Some(ctrlc) => ctrlc,
+        None => return child.wait(),
+    };
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+
+        if ctrlc.load(Ordering::SeqCst) {
+            // It may have exited in the meantime, in which case there's nothing to kill
+            let _ = child.kill();
+            return child.wait();
+        }
+
+        std::thread::sleep(CANCEL_POLL_INTERVAL);
+    }
+}
+
 // The piped data from stdout from the external command can be either String
 // or binary. We use this enum to pass the data from the spawned process
 #[derive(Debug)]
 enum Data {
     String(String),
     Bytes(Vec<u8>),
+    // Output that isn't valid UTF-8, when the config makes that an error
+    InvalidUtf8,
+}
+
+// What's done with output that isn't valid UTF-8, set by the config's `external_invalid_utf8`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InvalidUtf8 {
+    Bytes,
+    Lossy,
+    Error,
+}
+
+impl Default for InvalidUtf8 {
+    fn default() -> Self {
+        InvalidUtf8::Bytes
+    }
+}
+
+impl InvalidUtf8 {
+    fn from_config(config: &Config) -> InvalidUtf8 {
+        match config.external_invalid_utf8.as_str() {
+            "lossy" => InvalidUtf8::Lossy,
+            "error" => InvalidUtf8::Error,
+            _ => InvalidUtf8::Bytes,
+        }
+    }
+
+    fn apply(self, bytes: Vec<u8>) -> Data {
+        match self {
+            InvalidUtf8::Bytes => Data::Bytes(bytes),
+            InvalidUtf8::Lossy => Data::String(String::from_utf8_lossy(&bytes).into_owned()),
+            InvalidUtf8::Error => Data::InvalidUtf8,
+        }
+    }
+}
+
+// Decodes the chunks read from the stdout of an external command. Reads can end in the middle of
+// a multi-byte character, so the start of such a character is held back until the next chunk
+// completes it. Only the bytes that aren't valid UTF-8 are handled as the config says, the text
+// around them is passed on as text
+#[derive(Debug, Default)]
+struct Utf8Decoder {
+    pending: Vec<u8>,
+    invalid: InvalidUtf8,
+}
+
+impl Utf8Decoder {
+    fn new(invalid: InvalidUtf8) -> Self {
+        Utf8Decoder {
+            pending: vec![],
+            invalid,
+        }
+    }
+
+    fn decode(&mut self, bytes: &[u8]) -> Vec<Data> {
+        let mut chunk = std::mem::take(&mut self.pending);
+        chunk.extend_from_slice(bytes);
+
+        let mut output = vec![];
+        let mut invalid = vec![];
+        let mut rest = chunk.as_slice();
+        loop {
+            let (valid, error) = match std::str::from_utf8(rest) {
+                Ok(text) => (text, None),
+                Err(err) => (
+                    std::str::from_utf8(&rest[..err.valid_up_to()])
+                        .expect("the start of the chunk is valid UTF-8"),
+                    Some(err),
+                ),
+            };
+
+            // Invalid bytes next to each other are handled together
+            if !valid.is_empty() {
+                if !invalid.is_empty() {
+                    output.push(self.invalid.apply(std::mem::take(&mut invalid)));
+                }
+                output.push(Data::String(valid.to_string()));
+            }
+            rest = &rest[valid.len()..];
+
+            match error.and_then(|err| err.error_len()) {
+                Some(len) => {
+                    invalid.extend_from_slice(&rest[..len]);
+                    rest = &rest[len..];
+                }
+                None => {
+                    // Either all of it was text, or what's left is a character cut short
+                    self.pending = rest.to_vec();
+                    break;
+                }
+            }
+        }
+
+        if !invalid.is_empty() {
+            output.push(self.invalid.apply(invalid));
+        }
+
+        output
+    }
+
+    // The output ended, so a character still waiting for the rest of its bytes never gets them
+    fn finish(self) -> Option<Data> {
+        if self.pending.is_empty() {
+            None
+        } else {
+            Some(self.invalid.apply(self.pending))
+        }
+    }
+
+    // Reading the output failed. What was held back is passed on with the cut character replaced,
+    // so the text read before the failure isn't lost, whatever the config says
+    fn abandon(self) -> Option<Data> {
+        if self.pending.is_empty() {
+            None
+        } else {
+            Some(InvalidUtf8::Lossy.apply(self.pending))
+        }
+    }
 }
 
 // Receiver used for the ValueStream
@@ -256,8 +419,117 @@ impl Iterator for ChannelReceiver {
                     val: b,
                     span: self.span,
                 }),
+                Data::InvalidUtf8 => Some(Value::Error {
+                    error: ShellError::NonUtf8(self.span),
+                }),
             },
             Err(_) => None,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decode_all(chunks: &[&[u8]]) -> Vec<Data> {
+        let mut decoder = Utf8Decoder::default();
+        let mut output: Vec<Data> = chunks
+            .iter()
+            .flat_map(|chunk| decoder.decode(chunk))
+            .collect();
+        output.extend(decoder.finish());
+        output
+    }
+
+    fn strings(output: &[Data]) -> Vec<&str> {
+        output
+            .iter()
+            .map(|data| match data {
+                Data::String(s) => s.as_str(),
+                other => panic!("expected text, got {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_characters_split_between_chunks() {
+        // "añb€c": 'ñ' is two bytes and '€' three, both cut in the middle
+        let text = "añb€c".as_bytes();
+        let output = decode_all(&[&text[..2], &text[2..5], &text[5..6], &text[6..]]);
+
+        assert_eq!(strings(&output), vec!["a", "ñb", "€c"]);
+    }
+
+    #[test]
+    fn decodes_each_chunk_as_it_arrives() {
+        let mut decoder = Utf8Decoder::default();
+        let text = "éé".as_bytes();
+
+        assert!(matches!(decoder.decode(&text[..3]).as_slice(), [Data::String(s)] if s == "é"));
+        assert_eq!(decoder.pending.len(), 1);
+        assert!(matches!(decoder.decode(&text[3..]).as_slice(), [Data::String(s)] if s == "é"));
+        assert!(decoder.pending.is_empty());
+    }
+
+    #[test]
+    fn passes_invalid_utf8_on_as_bytes() {
+        let output = decode_all(&[b"ok", b"\xffnot text", "€".as_bytes()[..2].as_ref()]);
+
+        assert!(matches!(&output[0], Data::String(s) if s == "ok"));
+        assert!(matches!(&output[1], Data::Bytes(b) if b == b"\xff"));
+        assert!(matches!(&output[2], Data::String(s) if s == "not text"));
+        assert!(matches!(&output[3], Data::Bytes(b) if b == &"€".as_bytes()[..2]));
+        assert_eq!(output.len(), 4);
+    }
+
+    #[test]
+    fn keeps_the_text_around_invalid_bytes() {
+        let mut decoder = Utf8Decoder::default();
+        let euro = "€".as_bytes();
+        let mut chunk = b"ok\xfe\xffgo".to_vec();
+        chunk.extend_from_slice(&euro[..2]);
+
+        match decoder.decode(&chunk).as_slice() {
+            [Data::String(before), Data::Bytes(invalid), Data::String(after)] => {
+                assert_eq!(before, "ok");
+                assert_eq!(invalid, b"\xfe\xff");
+                assert_eq!(after, "go");
+            }
+            other => panic!("expected text around bytes, got {:?}", other),
+        }
+        assert!(matches!(decoder.decode(&euro[2..]).as_slice(), [Data::String(s)] if s == "€"));
+    }
+
+    #[test]
+    fn replaces_invalid_utf8_when_lossy() {
+        let mut decoder = Utf8Decoder::new(InvalidUtf8::Lossy);
+
+        assert!(matches!(
+            decoder.decode(b"\xffok").as_slice(),
+            [Data::String(invalid), Data::String(s)] if invalid == "\u{FFFD}" && s == "ok"
+        ));
+        assert!(decoder.decode(&"€".as_bytes()[..2]).is_empty());
+        assert!(matches!(decoder.finish(), Some(Data::String(s)) if s == "\u{FFFD}"));
+    }
+
+    #[test]
+    fn invalid_utf8_can_be_an_error() {
+        let mut decoder = Utf8Decoder::new(InvalidUtf8::Error);
+
+        assert!(matches!(
+            decoder.decode(b"ok\xff").as_slice(),
+            [Data::String(s), Data::InvalidUtf8] if s == "ok"
+        ));
+    }
+
+    #[test]
+    fn failed_reads_keep_what_was_held_back() {
+        let mut decoder = Utf8Decoder::default();
+
+        assert!(
+            matches!(decoder.decode("a€".as_bytes()[..3].as_ref()).as_slice(), [Data::String(s)] if s == "a")
+        );
+        assert!(matches!(decoder.abandon(), Some(Data::String(s)) if s == "\u{FFFD}"));
+    }
+}
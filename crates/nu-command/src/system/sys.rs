@@ -34,11 +34,20 @@ impl Command for Sys {
     }
 
     fn examples(&self) -> Vec<Example> {
-        vec![Example {
-            description: "Show info about the system",
-            example: "sys",
-            result: None,
-        }]
+        vec![
+            Example {
+                description: "Show info about the system",
+                example: "sys",
+                result: None,
+                ..Default::default()
+            },
+            Example {
+                description: "Show the resource limits, like the number of open files (Unix only)",
+                example: "sys | get limits",
+                result: None,
+                ..Default::default()
+            },
+        ]
     }
 }
 
@@ -73,6 +82,10 @@ fn run_sys(call: &Call) -> Result<PipelineData, ShellError> {
         headers.push("net".into());
         values.push(value);
     }
+    if let Some(value) = limits(span) {
+        headers.push("limits".into());
+        values.push(value);
+    }
 
     Ok(Value::Record {
         cols: headers,
@@ -358,3 +371,114 @@ pub fn temp(sys: &mut System, span: Span) -> Option<Value> {
         None
     }
 }
+
+/// The resource limits of the shell (and so of the externals it runs), like `ulimit` shows them
+#[cfg(unix)]
+pub fn limits(span: Span) -> Option<Value> {
+    let resources = [
+        ("open files", libc::RLIMIT_NOFILE, false),
+        ("max processes", libc::RLIMIT_NPROC, false),
+        ("stack size", libc::RLIMIT_STACK, true),
+        ("core size", libc::RLIMIT_CORE, true),
+    ];
+
+    let mut output = vec![];
+    for &(name, resource, is_size) in resources.iter() {
+        let mut limit = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        // SAFETY: getrlimit only writes to the rlimit it's given
+        if unsafe { libc::getrlimit(resource, &mut limit) } != 0 {
+            continue;
+        }
+
+        let cols = vec!["name".into(), "soft".into(), "hard".into()];
+        let vals = vec![
+            Value::String {
+                val: name.into(),
+                span,
+            },
+            limit_value(limit.rlim_cur, is_size, span),
+            limit_value(limit.rlim_max, is_size, span),
+        ];
+
+        output.push(Value::Record { cols, vals, span });
+    }
+    if !output.is_empty() {
+        Some(Value::List { vals: output, span })
+    } else {
+        None
+    }
+}
+
+/// Limits are only known on Unix, elsewhere `sys` goes without them
+#[cfg(not(unix))]
+pub fn limits(_span: Span) -> Option<Value> {
+    None
+}
+
+#[cfg(unix)]
+fn limit_value(limit: libc::rlim_t, is_size: bool, span: Span) -> Value {
+    if limit == libc::RLIM_INFINITY {
+        Value::String {
+            val: "unlimited".into(),
+            span,
+        }
+    } else if is_size {
+        Value::Filesize {
+            val: limit as i64,
+            span,
+        }
+    } else {
+        Value::Int {
+            val: limit as i64,
+            span,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn limits_have_known_names() {
+        let limits = limits(Span::unknown()).expect("limits should be known on unix");
+        let names = match limits {
+            Value::List { vals, .. } => vals
+                .into_iter()
+                .filter_map(|limit| match limit {
+                    Value::Record { vals, .. } => {
+                        vals.first().and_then(|name| name.as_string().ok())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+            other => panic!("limits should be a table, got {:?}", other),
+        };
+
+        assert!(names.contains(&"open files".to_string()));
+        assert!(names.contains(&"stack size".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn infinite_limits_are_unlimited() {
+        assert_eq!(
+            limit_value(libc::RLIM_INFINITY, true, Span::unknown()),
+            Value::test_string("unlimited")
+        );
+        assert_eq!(
+            limit_value(1024, false, Span::unknown()),
+            Value::test_int(1024)
+        );
+    }
+
+    #[cfg(not(unix))]
+    #[test]
+    fn limits_are_absent() {
+        assert!(limits(Span::unknown()).is_none());
+    }
+}
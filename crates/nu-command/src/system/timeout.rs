@@ -0,0 +1,317 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nu_engine::{eval_block, CallExt};
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{
+    format_duration, Example, IntoPipelineData, PipelineData, ShellError, Signature, Span,
+    SyntaxShape, Value,
+};
+
+/// How often the wait for the block checks for Ctrl-C
+const CTRLC_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Clone)]
+pub struct Timeout;
+
+impl Command for Timeout {
+    fn name(&self) -> &str {
+        "timeout"
+    }
+
+    fn usage(&self) -> &str {
+        "Run a block, giving up on it if it takes longer than the given duration"
+    }
+
+    fn signature(&self) -> nu_protocol::Signature {
+        Signature::build("timeout")
+            .required(
+                "duration",
+                SyntaxShape::Duration,
+                "how long the block may run",
+            )
+            .required(
+                "block",
+                SyntaxShape::Block(Some(vec![])),
+                "the block to run",
+            )
+            .switch(
+                "partial",
+                "give back the rows produced in time instead of failing",
+                Some('p'),
+            )
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"The block runs in the background, and its output is collected as it comes. When time
+runs out, the block is cancelled like Ctrl-C would, killing any externals it started."#
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        vec![
+            Example {
+                example: "timeout 10sec { 1 + 1 }",
+                description: "Run a block that finishes in time",
+                result: Some(Value::test_int(2)),
+                ..Default::default()
+            },
+            Example {
+                example: "timeout --partial 100ms { 1.. | each { $it } }",
+                description: "Keep the rows an endless block gives back in time",
+                result: None,
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        _input: PipelineData,
+    ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
+        let (nanos, duration_span) = match call.req(engine_state, stack, 0)? {
+            Value::Duration { val, span } => (val, span),
+            other => {
+                return Err(ShellError::UnsupportedInput(
+                    format!("Expected a duration, got a {}.", other.get_type()),
+                    other.span().unwrap_or(call.head),
+                ))
+            }
+        };
+        let block_id = call.positional[1]
+            .as_block()
+            .expect("internal error: expected block");
+        let partial = call.has_flag("partial");
+        let head = call.head;
+
+        // The block gets a Ctrl-C flag of its own, so cancelling it leaves the rest of the
+        // pipeline running
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut engine_state = engine_state.clone();
+        let ctrlc = engine_state.ctrlc.replace(cancel.clone());
+        let block = engine_state.get_block(block_id);
+        let mut stack = stack.collect_captures(&block.captures);
+
+        let work = move |events: Sender<Event>| {
+            let block = engine_state.get_block(block_id);
+
+            let event = match eval_block(&engine_state, &mut stack, block, PipelineData::new(head))
+            {
+                Ok(PipelineData::Value(Value::List { vals, .. })) => {
+                    send_rows(&events, vals);
+                    Event::Finished(None)
+                }
+                Ok(PipelineData::Stream(stream)) => {
+                    send_rows(&events, stream);
+                    Event::Finished(None)
+                }
+                Ok(PipelineData::Value(value)) => Event::Finished(Some(value)),
+                Err(error) => Event::Failed(error),
+            };
+            // Nobody's listening anymore if the block was cancelled
+            let _ = events.send(event);
+        };
+
+        let timeout = Duration::from_nanos(nanos.max(0) as u64);
+
+        match collect_within(work, timeout, cancel, ctrlc, head) {
+            Collected::Done(output) => output,
+            Collected::TimedOut(vals) if partial => {
+                Ok(Value::List { vals, span: head }.into_pipeline_data())
+            }
+            Collected::TimedOut(_) => {
+                Err(ShellError::TimedOut(format_duration(nanos), duration_span))
+            }
+            Collected::Interrupted => Ok(PipelineData::new(head)),
+        }
+    }
+}
+
+/// What the block running in the background reports back
+enum Event {
+    Row(Value),
+    /// The block is done, with the value it gave back if it wasn't rows
+    Finished(Option<Value>),
+    Failed(ShellError),
+}
+
+/// What came of a block given a deadline
+enum Collected {
+    Done(Result<PipelineData, ShellError>),
+    /// Time ran out, with the rows given back until then
+    TimedOut(Vec<Value>),
+    /// Ctrl-C was pressed while waiting
+    Interrupted,
+}
+
+fn send_rows(events: &Sender<Event>, rows: impl IntoIterator<Item = Value>) {
+    for row in rows {
+        if events.send(Event::Row(row)).is_err() {
+            break;
+        }
+    }
+}
+
+/// Run `work` on a thread of its own, collecting what it sends until it's done or `timeout` runs
+/// out. Either way it's left to end on its own: when time runs out, `cancel` is set for it to
+/// stop at
+fn collect_within<F>(
+    work: F,
+    timeout: Duration,
+    cancel: Arc<AtomicBool>,
+    ctrlc: Option<Arc<AtomicBool>>,
+    head: Span,
+) -> Collected
+where
+    F: FnOnce(Sender<Event>) + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || work(tx));
+
+    let deadline = Instant::now() + timeout;
+    let mut rows = vec![];
+
+    loop {
+        if let Some(ctrlc) = &ctrlc {
+            if ctrlc.load(Ordering::SeqCst) {
+                cancel.store(true, Ordering::SeqCst);
+                return Collected::Interrupted;
+            }
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(remaining.min(CTRLC_POLL_INTERVAL)) {
+            Ok(Event::Row(row)) => rows.push(row),
+            Ok(Event::Finished(Some(value))) => {
+                return Collected::Done(Ok(value.into_pipeline_data()))
+            }
+            Ok(Event::Finished(None)) | Err(RecvTimeoutError::Disconnected) => {
+                return Collected::Done(Ok(Value::List {
+                    vals: rows,
+                    span: head,
+                }
+                .into_pipeline_data()))
+            }
+            Ok(Event::Failed(error)) => return Collected::Done(Err(error)),
+            Err(RecvTimeoutError::Timeout) => {
+                if Instant::now() >= deadline {
+                    cancel.store(true, Ordering::SeqCst);
+                    return Collected::TimedOut(rows);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_examples() {
+        use crate::test_examples;
+
+        test_examples(Timeout {})
+    }
+
+    fn rows(collected: Collected) -> Vec<Value> {
+        match collected {
+            Collected::Done(Ok(output)) => output.into_iter().collect(),
+            Collected::TimedOut(rows) => rows,
+            _ => panic!("expected rows"),
+        }
+    }
+
+    #[test]
+    fn fast_work_finishes() {
+        let start = Instant::now();
+        let collected = collect_within(
+            |events| {
+                send_rows(&events, vec![Value::test_int(1), Value::test_int(2)]);
+                let _ = events.send(Event::Finished(None));
+            },
+            Duration::from_secs(10),
+            Arc::new(AtomicBool::new(false)),
+            None,
+            Span::unknown(),
+        );
+
+        assert!(matches!(collected, Collected::Done(Ok(_))));
+        assert_eq!(
+            rows(collected),
+            vec![Value::test_int(1), Value::test_int(2)]
+        );
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn slow_work_times_out_and_is_cancelled() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let work_cancel = cancel.clone();
+        let work_stopped = stopped.clone();
+
+        let collected = collect_within(
+            move |events| {
+                let _ = events.send(Event::Row(Value::test_int(1)));
+                while !work_cancel.load(Ordering::SeqCst) {
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+                work_stopped.store(true, Ordering::SeqCst);
+            },
+            Duration::from_millis(100),
+            cancel.clone(),
+            None,
+            Span::unknown(),
+        );
+
+        assert!(matches!(collected, Collected::TimedOut(_)));
+        assert_eq!(rows(collected), vec![Value::test_int(1)]);
+        assert!(cancel.load(Ordering::SeqCst));
+
+        // The work notices it was cancelled and ends, rather than being left running
+        let waited = Instant::now();
+        while !stopped.load(Ordering::SeqCst) {
+            assert!(waited.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn failures_are_passed_on() {
+        let collected = collect_within(
+            |events| {
+                let _ = events.send(Event::Failed(ShellError::DivisionByZero(Span::unknown())));
+            },
+            Duration::from_secs(10),
+            Arc::new(AtomicBool::new(false)),
+            None,
+            Span::unknown(),
+        );
+
+        assert!(matches!(
+            collected,
+            Collected::Done(Err(ShellError::DivisionByZero(..)))
+        ));
+    }
+
+    #[test]
+    fn ctrlc_cancels_the_work() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let collected = collect_within(
+            |_| std::thread::sleep(Duration::from_secs(1)),
+            Duration::from_secs(10),
+            cancel.clone(),
+            Some(Arc::new(AtomicBool::new(true))),
+            Span::unknown(),
+        );
+
+        assert!(matches!(collected, Collected::Interrupted));
+        assert!(cancel.load(Ordering::SeqCst));
+    }
+}
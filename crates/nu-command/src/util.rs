@@ -0,0 +1,345 @@
+use std::sync::{atomic::AtomicBool, Arc};
+
+use nu_engine::CallExt;
+use nu_protocol::ast::{Call, CellPath, PathMember};
+use nu_protocol::engine::{EngineState, Stack};
+use nu_protocol::{PipelineData, PipelineMetadata, ShellError, Span, Value};
+
+/// Apply `f` to each value of an element-wise command's input without collecting it.
+///
+/// Streams, lists and ranges are mapped lazily, so `f` only runs for the values that are
+/// actually pulled further down the pipeline. Any other single value is mapped directly,
+/// and an error it is mapped to is returned as the command's error.
+pub fn map_values<F>(
+    input: PipelineData,
+    ctrlc: Option<Arc<AtomicBool>>,
+    f: F,
+) -> Result<PipelineData, ShellError>
+where
+    F: FnMut(Value) -> Value + 'static + Send,
+{
+    input.map(f, ctrlc)
+}
+
+/// Like `map_values`, but `f` is also given the position of each row in the input, counted
+/// before anything up the pipeline, like `where`, left rows out.
+///
+/// The positions stay attached to the output, so a command further down names rows the same way.
+pub fn map_rows<F>(
+    input: PipelineData,
+    ctrlc: Option<Arc<AtomicBool>>,
+    mut f: F,
+) -> Result<PipelineData, ShellError>
+where
+    F: FnMut(usize, Value) -> Value + 'static + Send,
+{
+    let positions = input.metadata().and_then(|metadata| metadata.positions);
+    let metadata = positions.clone().map(|positions| PipelineMetadata {
+        data_source: None,
+        positions: Some(positions),
+        byte_len: None,
+    });
+
+    let mut idx = 0;
+    let output = map_values(input, ctrlc, move |value| {
+        let row = positions
+            .as_ref()
+            .map_or(idx, |positions| positions.get(idx));
+        idx += 1;
+        f(row, value)
+    })?;
+
+    Ok(output.set_metadata(metadata))
+}
+
+/// Replace the cells at `column_paths` of `row` with what `action` makes of them.
+///
+/// `row` is the position of the value in the input, as given by `map_rows`. When a cell can't be
+/// replaced, the row becomes an error that names the row and the column path, so it can be found
+/// in a long table, eg with `where $it =~ "ColA in row 7"`.
+pub fn update_cells<F>(
+    mut value: Value,
+    row: usize,
+    column_paths: &[CellPath],
+    head: Span,
+    action: F,
+) -> Value
+where
+    F: Fn(&Value) -> Value + Clone + 'static,
+{
+    for path in column_paths {
+        if let Err(error) = value.update_cell_path(&path.members, Box::new(action.clone())) {
+            let spans: Vec<Span> = path
+                .members
+                .iter()
+                .map(|member| match member {
+                    PathMember::String { span, .. } | PathMember::Int { span, .. } => *span,
+                })
+                .collect();
+
+            return Value::Error {
+                error: ShellError::InCell {
+                    row,
+                    path: path.into_string(),
+                    span: if spans.is_empty() {
+                        head
+                    } else {
+                        nu_protocol::span(&spans)
+                    },
+                    cause: vec![error],
+                },
+            };
+        }
+    }
+
+    value
+}
+
+/// Fail when the input never ends, like a range without an end or the rows `each` or `where`
+/// make of one, for commands that need all of their input before they can produce anything
+pub fn ensure_bounded(input: &PipelineData, head: Span) -> Result<(), ShellError> {
+    if input.is_endless() {
+        Err(ShellError::UnboundedRange(head))
+    } else {
+        Ok(())
+    }
+}
+
+/// Read the cell paths given to a command like `select`, starting at positional `starting_pos`.
+///
+/// Besides cell paths written out at parse time, this takes a single list of column names
+/// computed at runtime, eg `select $cols`. Runtime names are split on dots unless `literal`
+/// is set, in which case each one names a single column.
+pub fn cell_paths_arg(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    call: &Call,
+    starting_pos: usize,
+    literal: bool,
+) -> Result<Vec<CellPath>, ShellError> {
+    let args: Vec<Value> = call.rest(engine_state, stack, starting_pos)?;
+    let arg_count = args.len();
+
+    let mut output = vec![];
+    for arg in args {
+        match arg {
+            Value::CellPath { val, .. } => output.push(val),
+            Value::List { vals, span } => {
+                if arg_count > 1 {
+                    return Err(ShellError::IncompatibleParametersSingle(
+                        "a list of columns can't be combined with other columns".into(),
+                        span,
+                    ));
+                }
+
+                for val in vals {
+                    output.push(cell_path_from_value(val, literal)?);
+                }
+            }
+            other => output.push(cell_path_from_value(other, literal)?),
+        }
+    }
+
+    Ok(output)
+}
+
+/// Turn a column name (or index) computed at runtime into a cell path, see [`cell_paths_arg`]
+pub fn cell_path_from_value(value: Value, literal: bool) -> Result<CellPath, ShellError> {
+    match value {
+        Value::CellPath { val, .. } => Ok(val),
+        Value::String { val, span } if literal => Ok(CellPath {
+            members: vec![PathMember::String { val, span }],
+        }),
+        Value::String { val, span } => Ok(CellPath {
+            members: val
+                .split('.')
+                .map(|member| match member.parse::<usize>() {
+                    Ok(val) => PathMember::Int { val, span },
+                    Err(_) => PathMember::String {
+                        val: member.to_string(),
+                        span,
+                    },
+                })
+                .collect(),
+        }),
+        Value::Int { val, span } if val >= 0 => Ok(CellPath {
+            members: vec![PathMember::Int {
+                val: val as usize,
+                span,
+            }],
+        }),
+        other => Err(ShellError::CantConvert(
+            "cell path".into(),
+            other.get_type().to_string(),
+            other.span()?,
+        )),
+    }
+}
+
+/// The column names of a table: those of its first row, or with `all` the union of the columns
+/// of every row, in the order they are first seen
+pub fn table_columns(rows: &[Value], all: bool) -> Vec<String> {
+    let mut columns: Vec<String> = vec![];
+
+    let rows = if all {
+        rows
+    } else {
+        &rows[..rows.len().min(1)]
+    };
+    for row in rows {
+        if let Value::Record { cols, .. } = row {
+            for col in cols {
+                if !columns.contains(col) {
+                    columns.push(col.clone());
+                }
+            }
+        }
+    }
+
+    columns
+}
+
+#[cfg(test)]
+mod test {
+    use nu_engine::test_support::{engine_state_with, eval_source, stack_with_config};
+    use nu_protocol::{IntoInterruptiblePipelineData, Span, Value};
+
+    use super::update_cells;
+    use crate::{First, StrDowncase, Where};
+    use nu_protocol::ast::{CellPath, PathMember};
+    use nu_protocol::ShellError;
+
+    /// A stream that fails the test if it is pulled past `limit` items
+    struct PanicAfter {
+        pulled: usize,
+        limit: usize,
+    }
+
+    impl Iterator for PanicAfter {
+        type Item = Value;
+
+        fn next(&mut self) -> Option<Value> {
+            self.pulled += 1;
+            if self.pulled > self.limit {
+                panic!("stream pulled past {} items", self.limit);
+            }
+
+            Some(Value::test_string(format!("ITEM {}", self.pulled)))
+        }
+    }
+
+    #[test]
+    fn string_commands_stay_lazy() {
+        let mut engine_state = engine_state_with(vec![Box::new(StrDowncase), Box::new(First)]);
+        let mut stack = stack_with_config();
+
+        // The stream is endless, so this only finishes if the pipeline is lazy end-to-end
+        let input = PanicAfter {
+            pulled: 0,
+            limit: 3,
+        }
+        .into_pipeline_data(None);
+
+        let source = "str downcase | first 3";
+        let result = eval_source(&mut engine_state, &mut stack, source, input)
+            .expect("pipeline should evaluate")
+            .into_value(Span::unknown());
+
+        assert_eq!(
+            result,
+            Value::List {
+                vals: vec![
+                    Value::test_string("item 1"),
+                    Value::test_string("item 2"),
+                    Value::test_string("item 3"),
+                ],
+                span: Span::unknown(),
+            }
+        );
+    }
+
+    fn row(cols: &[&str], vals: Vec<Value>) -> Value {
+        Value::Record {
+            cols: cols.iter().map(|col| col.to_string()).collect(),
+            vals,
+            span: Span::unknown(),
+        }
+    }
+
+    fn column_path(name: &str) -> CellPath {
+        CellPath {
+            members: vec![PathMember::String {
+                val: name.into(),
+                span: Span::new(10, 14),
+            }],
+        }
+    }
+
+    fn upcase(value: &Value) -> Value {
+        match value {
+            Value::String { val, span } => Value::String {
+                val: val.to_uppercase(),
+                span: *span,
+            },
+            _ => Value::Error {
+                error: ShellError::UnsupportedInput("not a string".into(), Span::unknown()),
+            },
+        }
+    }
+
+    #[test]
+    fn failed_cells_name_their_row_and_column() {
+        let updated = update_cells(
+            row(&["ColA"], vec![Value::test_int(3)]),
+            7,
+            &[column_path("ColA")],
+            Span::unknown(),
+            upcase,
+        );
+
+        match updated {
+            Value::Error {
+                error:
+                    ShellError::InCell {
+                        row,
+                        path,
+                        span,
+                        cause,
+                    },
+            } => {
+                assert_eq!(row, 7);
+                assert_eq!(path, "ColA");
+                assert_eq!(span, Span::new(10, 14));
+                assert!(matches!(cause[..], [ShellError::UnsupportedInput(..)]));
+            }
+            other => panic!("expected an error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rows_keep_their_position_through_where() {
+        let mut engine_state = engine_state_with(vec![Box::new(Where), Box::new(StrDowncase)]);
+        let mut stack = stack_with_config();
+
+        let input = ["A", "B", "C", "D", "E", "F", "G", "7", "I", "J"]
+            .iter()
+            .map(|val| match val.parse::<i64>() {
+                Ok(val) => row(&["ColA"], vec![Value::test_int(val)]),
+                Err(_) => row(&["ColA"], vec![Value::test_string(*val)]),
+            })
+            .into_pipeline_data(None);
+
+        let source = "where ColA != C | where ColA != E | str downcase ColA";
+        let result = eval_source(&mut engine_state, &mut stack, source, input)
+            .expect("pipeline should evaluate");
+        let rows: Vec<Value> = result.into_iter().collect();
+
+        assert_eq!(rows.len(), 8);
+        assert!(matches!(
+            rows[5],
+            Value::Error {
+                error: ShellError::InCell { row: 7, .. }
+            }
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+use std::convert::TryFrom;
+
 use lscolors::{LsColors, Style};
 use nu_engine::CallExt;
 use nu_protocol::{
@@ -5,6 +7,7 @@ use nu_protocol::{
     engine::{Command, EngineState, Stack},
     Config, IntoPipelineData, PipelineData, Signature, Span, SyntaxShape, Value,
 };
+use nu_table::truncate_to_width;
 use nu_term_grid::grid::{Alignment, Cell, Direction, Filling, Grid, GridOptions};
 use terminal_size::{Height, Width};
 
@@ -53,7 +56,7 @@ prints out the list properly."#
         call: &Call,
         input: PipelineData,
     ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
-        let width_param: Option<String> = call.get_flag(engine_state, stack, "width")?;
+        let width_param: Option<i64> = call.get_flag(engine_state, stack, "width")?;
         let color_param: bool = call.has_flag("color");
         let separator_param: Option<String> = call.get_flag(engine_state, stack, "separator")?;
 
@@ -119,13 +122,13 @@ prints out the list properly."#
 fn create_grid_output2(
     items: Vec<(usize, String, String)>,
     call: &Call,
-    width_param: Option<String>,
+    width_param: Option<i64>,
     color_param: bool,
     separator_param: Option<String>,
 ) -> PipelineData {
     let ls_colors = LsColors::from_env().unwrap_or_default();
     let cols = if let Some(col) = width_param {
-        col.parse::<u16>().unwrap_or(80)
+        u16::try_from(col).unwrap_or(80)
     } else if let Some((Width(w), Height(_h))) = terminal_size::terminal_size() {
         w
     } else {
@@ -145,17 +148,17 @@ fn create_grid_output2(
     for (_row_index, header, value) in items {
         // only output value if the header name is 'name'
         if header == "name" {
-            if color_param {
+            let contents = if color_param {
                 let style = ls_colors.style_for_path(value.clone());
                 let ansi_style = style.map(Style::to_crossterm_style).unwrap_or_default();
-                let mut cell = Cell::from(ansi_style.apply(value).to_string());
-                cell.alignment = Alignment::Right;
-                grid.add(cell);
+                ansi_style.apply(value).to_string()
             } else {
-                let mut cell = Cell::from(value);
-                cell.alignment = Alignment::Right;
-                grid.add(cell);
-            }
+                value
+            };
+            // A name too wide for the whole grid is cut short, instead of the grid not fitting
+            let mut cell = Cell::from(truncate_to_width(&contents, cols as usize));
+            cell.alignment = Alignment::Right;
+            grid.add(cell);
         }
     }
 
@@ -0,0 +1,57 @@
+use lscolors::{LsColors, Style};
+
+/// Load the file name colors from an `LS_COLORS` value, falling back to the built-in default
+/// theme when it isn't set. Malformed entries are skipped rather than rejecting the whole value
+pub fn get_ls_colors(ls_colors_env: Option<String>) -> LsColors {
+    match ls_colors_env {
+        Some(s) => LsColors::from_string(&s),
+        None => LsColors::default(),
+    }
+}
+
+/// Wrap a path in the ANSI style LS_COLORS assigns to it (based on its file type and extension)
+pub fn style_path(ls_colors: &LsColors, path: &str) -> String {
+    match ls_colors.style_for_path(path) {
+        Some(style) => Style::to_crossterm_style(style).apply(path).to_string(),
+        None => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn colors_by_extension() {
+        let ls_colors = get_ls_colors(Some("*.rs=31".into()));
+
+        let styled = style_path(&ls_colors, "no-such-dir/main.rs");
+        assert!(styled.starts_with('\u{1b}'));
+        assert!(styled.contains("no-such-dir/main.rs"));
+        assert_eq!(
+            style_path(&ls_colors, "no-such-dir/notes.txt"),
+            "no-such-dir/notes.txt"
+        );
+    }
+
+    #[test]
+    fn skips_malformed_entries() {
+        let ls_colors = get_ls_colors(Some("garbage:*.md=zz:=:*.rs=31".into()));
+
+        assert_eq!(
+            style_path(&ls_colors, "no-such-dir/README.md"),
+            "no-such-dir/README.md"
+        );
+        assert_ne!(
+            style_path(&ls_colors, "no-such-dir/main.rs"),
+            "no-such-dir/main.rs"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_default_theme() {
+        let ls_colors = get_ls_colors(None);
+
+        assert_ne!(style_path(&ls_colors, "."), ".");
+    }
+}
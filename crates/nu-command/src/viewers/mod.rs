@@ -1,5 +1,9 @@
 mod griddle;
+mod ls_colors;
+mod page;
 mod table;
 
 pub use griddle::Griddle;
-pub use table::Table;
+pub use ls_colors::{get_ls_colors, style_path};
+pub use page::Page;
+pub use table::{Table, INDEX_COLUMN};
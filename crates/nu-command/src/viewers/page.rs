@@ -0,0 +1,304 @@
+use std::io::{self, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::{cursor, queue, terminal};
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{Command, EngineState, Stack};
+use nu_protocol::{Category, IntoPipelineData, PipelineData, ShellError, Signature, Value};
+use terminal_size::{Height, Width};
+
+#[derive(Clone)]
+pub struct Page;
+
+impl Command for Page {
+    fn name(&self) -> &str {
+        "page"
+    }
+
+    fn usage(&self) -> &str {
+        "Show the input as a table, in a scrollable view if it doesn't fit the terminal."
+    }
+
+    fn extra_usage(&self) -> &str {
+        r#"Scroll with up/down (or k/j), page up/page down (or space), and quit with q or escape. When
+the output isn't a terminal, or the table fits, the table is output as it is. Setting `use_pager`
+in the config pages every result this way."#
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("page").category(Category::Viewers)
+    }
+
+    fn run(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        call: &Call,
+        input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        let text = render_table(engine_state, stack, input)?;
+
+        // There is no terminal size when stdout isn't a terminal
+        let term_height = terminal_size::terminal_size().map(|(Width(_), Height(h))| h as usize);
+        let lines: Vec<&str> = text.lines().collect();
+
+        if let Some(height) = pager_height(term_height, lines.len()) {
+            let _screen = PagerScreen::enter()?;
+            let stdout = io::stdout();
+            run_pager(&lines, height, &mut stdout.lock(), &mut CrosstermKeys)?;
+
+            Ok(PipelineData::new(call.head))
+        } else {
+            Ok(Value::String {
+                val: text,
+                span: call.head,
+            }
+            .into_pipeline_data())
+        }
+    }
+}
+
+/// The input the way `table` renders it, or as a plain string if there is no `table` command
+fn render_table(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    input: PipelineData,
+) -> Result<String, ShellError> {
+    let config = stack.get_config()?;
+
+    match engine_state.find_decl(b"table") {
+        Some(decl_id) => Ok(engine_state
+            .get_decl(decl_id)
+            .run(engine_state, stack, &Call::new(), input)?
+            .collect_string("\n", &config)),
+        None => Ok(input.collect_string(", ", &config)),
+    }
+}
+
+/// The terminal height to page `num_lines` lines at, if they don't fit the terminal. Without a
+/// terminal they are never paged
+pub fn pager_height(term_height: Option<usize>, num_lines: usize) -> Option<usize> {
+    term_height.filter(|height| num_lines > *height)
+}
+
+/// What the user asked the pager to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PagerKey {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    /// The terminal is now this many lines high
+    Resize(usize),
+    Quit,
+    Other,
+}
+
+/// Where the pager gets its keys from. Tests give it a scripted list of keys instead of a terminal
+pub trait PagerKeys {
+    fn next_key(&mut self) -> io::Result<PagerKey>;
+}
+
+/// The keys pressed in the terminal
+struct CrosstermKeys;
+
+impl PagerKeys for CrosstermKeys {
+    fn next_key(&mut self) -> io::Result<PagerKey> {
+        Ok(match event::read()? {
+            Event::Key(KeyEvent { code, modifiers }) => match code {
+                KeyCode::Up | KeyCode::Char('k') => PagerKey::Up,
+                KeyCode::Down | KeyCode::Char('j') | KeyCode::Enter => PagerKey::Down,
+                KeyCode::PageUp | KeyCode::Char('b') => PagerKey::PageUp,
+                KeyCode::PageDown | KeyCode::Char(' ') => PagerKey::PageDown,
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => PagerKey::Quit,
+                KeyCode::Char('q') | KeyCode::Esc => PagerKey::Quit,
+                _ => PagerKey::Other,
+            },
+            Event::Resize(_, height) => PagerKey::Resize(height as usize),
+            _ => PagerKey::Other,
+        })
+    }
+}
+
+/// The terminal in raw mode on the alternate screen while the pager runs. Dropping it, also when
+/// unwinding from a panic, gives the user their terminal back the way it was
+struct PagerScreen;
+
+impl PagerScreen {
+    fn enter() -> io::Result<PagerScreen> {
+        terminal::enable_raw_mode()?;
+        // From here on, dropping the guard undoes whatever was done
+        let screen = PagerScreen;
+        let mut stdout = io::stdout();
+        queue!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+        stdout.flush()?;
+
+        Ok(screen)
+    }
+}
+
+impl Drop for PagerScreen {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = queue!(stdout, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = stdout.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Which lines are on screen, and how many fit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PagerView {
+    /// The index of the first line on screen
+    pub top: usize,
+    /// How many lines are shown at once. The terminal's last line is the status line
+    pub page_height: usize,
+    pub num_lines: usize,
+}
+
+impl PagerView {
+    pub fn new(num_lines: usize, term_height: usize) -> PagerView {
+        PagerView {
+            top: 0,
+            page_height: page_height(term_height),
+            num_lines,
+        }
+    }
+
+    /// The lines on screen
+    pub fn visible<'a>(&self, lines: &'a [&'a str]) -> &'a [&'a str] {
+        let end = (self.top + self.page_height).min(lines.len());
+        &lines[self.top.min(end)..end]
+    }
+
+    /// Move the view for a key. Returns false when the pager should stop
+    pub fn handle(&mut self, key: PagerKey) -> bool {
+        match key {
+            PagerKey::Up => self.top = self.top.saturating_sub(1),
+            PagerKey::Down => self.top += 1,
+            PagerKey::PageUp => self.top = self.top.saturating_sub(self.page_height),
+            PagerKey::PageDown => self.top += self.page_height,
+            PagerKey::Resize(height) => self.page_height = page_height(height),
+            PagerKey::Quit => return false,
+            PagerKey::Other => {}
+        }
+
+        // The last page stays full
+        self.top = self
+            .top
+            .min(self.num_lines.saturating_sub(self.page_height));
+        true
+    }
+}
+
+fn page_height(term_height: usize) -> usize {
+    term_height.saturating_sub(1).max(1)
+}
+
+/// Show the lines a page at a time until the user quits. Returns the view it was left at
+pub fn run_pager(
+    lines: &[&str],
+    term_height: usize,
+    out: &mut impl Write,
+    keys: &mut impl PagerKeys,
+) -> io::Result<PagerView> {
+    let mut view = PagerView::new(lines.len(), term_height);
+
+    loop {
+        draw(&view, lines, out)?;
+
+        if !view.handle(keys.next_key()?) {
+            return Ok(view);
+        }
+    }
+}
+
+fn draw(view: &PagerView, lines: &[&str], out: &mut impl Write) -> io::Result<()> {
+    queue!(
+        out,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(terminal::ClearType::All)
+    )?;
+
+    let visible = view.visible(lines);
+    for line in visible {
+        // Raw mode doesn't return the cursor to the start of the line
+        write!(out, "{}\r\n", line)?;
+    }
+    write!(
+        out,
+        "lines {}-{} of {} (q to quit)",
+        view.top + 1,
+        view.top + visible.len(),
+        view.num_lines
+    )?;
+
+    out.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct ScriptedKeys(std::vec::IntoIter<PagerKey>);
+
+    impl PagerKeys for ScriptedKeys {
+        fn next_key(&mut self) -> io::Result<PagerKey> {
+            Ok(self.0.next().unwrap_or(PagerKey::Quit))
+        }
+    }
+
+    fn numbered_lines(count: usize) -> Vec<String> {
+        (0..count).map(|idx| format!("line {}", idx)).collect()
+    }
+
+    #[test]
+    fn pages_only_on_a_terminal() {
+        assert_eq!(pager_height(None, 1000), None);
+        assert_eq!(pager_height(Some(40), 40), None);
+        assert_eq!(pager_height(Some(40), 41), Some(40));
+    }
+
+    #[test]
+    fn shows_a_page_of_lines_above_the_status_line() {
+        let lines = numbered_lines(10);
+        let lines: Vec<&str> = lines.iter().map(|line| line.as_str()).collect();
+        let mut view = PagerView::new(lines.len(), 5);
+
+        assert_eq!(view.visible(&lines), &lines[0..4]);
+        view.handle(PagerKey::PageDown);
+        assert_eq!(view.visible(&lines), &lines[4..8]);
+        // The last page is kept full rather than scrolling past the end
+        view.handle(PagerKey::PageDown);
+        assert_eq!(view.visible(&lines), &lines[6..10]);
+        view.handle(PagerKey::Down);
+        assert_eq!(view.top, 6);
+        view.handle(PagerKey::PageUp);
+        view.handle(PagerKey::PageUp);
+        view.handle(PagerKey::Up);
+        assert_eq!(view.visible(&lines), &lines[0..4]);
+    }
+
+    #[test]
+    fn runs_until_quit() {
+        let lines = numbered_lines(20);
+        let lines: Vec<&str> = lines.iter().map(|line| line.as_str()).collect();
+        let mut keys = ScriptedKeys(
+            vec![
+                PagerKey::Down,
+                PagerKey::Down,
+                PagerKey::PageDown,
+                PagerKey::Quit,
+                PagerKey::Down,
+            ]
+            .into_iter(),
+        );
+        let mut out = vec![];
+
+        let view = run_pager(&lines, 6, &mut out, &mut keys).expect("drawing into a buffer works");
+
+        assert_eq!(view.top, 7);
+        let out = String::from_utf8(out).expect("utf-8 output");
+        assert!(out.ends_with("line 11\r\nlines 8-12 of 20 (q to quit)"));
+    }
+}
@@ -1,12 +1,24 @@
+use lscolors::LsColors;
+use nu_engine::CallExt;
 use nu_protocol::ast::{Call, PathMember};
 use nu_protocol::engine::{Command, EngineState, Stack};
-use nu_protocol::{Config, IntoPipelineData, PipelineData, ShellError, Signature, Span, Value};
+use nu_protocol::{
+    Config, DataSource, IntoPipelineData, PipelineData, PipelineMetadata, ShellError, Signature,
+    Span, SyntaxShape, Value,
+};
 use nu_table::{StyledString, Theme};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use terminal_size::{Height, Width};
 
+use super::{get_ls_colors, style_path};
+
+/// A column of rows numbered before they were filtered, eg by `where --numbered`. Tables show it as
+/// the rows' index, so they keep the numbers they had. It's named after the index header rather
+/// than something like `index`, so a column the data has of its own is shown as it is
+pub const INDEX_COLUMN: &str = "#";
+
 #[derive(Clone)]
 pub struct Table;
 
@@ -20,8 +32,18 @@ impl Command for Table {
         "Render the table."
     }
 
+    fn extra_usage(&self) -> &str {
+        r#"The index column can be hidden by setting `table_show_index` to false in the config. Giving a
+start number shows it anyway."#
+    }
+
     fn signature(&self) -> nu_protocol::Signature {
-        Signature::build("table")
+        Signature::build("table").named(
+            "start-number",
+            SyntaxShape::Int,
+            "the number of the first row in the index column",
+            Some('n'),
+        )
     }
 
     fn run(
@@ -33,16 +55,38 @@ impl Command for Table {
     ) -> Result<nu_protocol::PipelineData, nu_protocol::ShellError> {
         let ctrlc = engine_state.ctrlc.clone();
         let config = stack.get_config()?;
+        let start_number: Option<i64> = call.get_flag(engine_state, stack, "start-number")?;
+        let start_number = match start_number {
+            Some(start_number) => Some(start_number),
+            None if config.table_show_index => Some(0),
+            None => None,
+        };
 
-        let term_width = if let Some((Width(w), Height(_h))) = terminal_size::terminal_size() {
+        let term_size = terminal_size::terminal_size();
+        let term_width = if let Some((Width(w), Height(_h))) = term_size {
             w as usize
         } else {
             80usize
         };
 
+        // File names from ls are colored, unless we aren't writing to a terminal
+        let ls_colors = match input.metadata() {
+            Some(PipelineMetadata {
+                data_source: Some(DataSource::Ls),
+                ..
+            }) if config.use_ls_colors && term_size.is_some() => {
+                let ls_colors_env = stack
+                    .get_env_var("LS_COLORS")
+                    .and_then(|ls_colors| ls_colors.as_string().ok());
+                Some(get_ls_colors(ls_colors_env))
+            }
+            _ => None,
+        };
+
         match input {
             PipelineData::Value(Value::List { vals, .. }) => {
-                let table = convert_to_table(vals, ctrlc, &config)?;
+                let table =
+                    convert_to_table(vals, start_number, ctrlc, &config, ls_colors.as_ref())?;
 
                 if let Some(table) = table {
                     let result = nu_table::draw_table(&table, term_width, &HashMap::new());
@@ -57,7 +101,8 @@ impl Command for Table {
                 }
             }
             PipelineData::Stream(stream) => {
-                let table = convert_to_table(stream, ctrlc, &config)?;
+                let table =
+                    convert_to_table(stream, start_number, ctrlc, &config, ls_colors.as_ref())?;
 
                 if let Some(table) = table {
                     let result = nu_table::draw_table(&table, term_width, &HashMap::new());
@@ -81,7 +126,7 @@ impl Command for Table {
                             style: nu_table::TextStyle::default_field(),
                         },
                         StyledString {
-                            contents: v.into_string(", ", &config),
+                            contents: cell_string(v, &config),
                             style: nu_table::TextStyle::default(),
                         },
                     ])
@@ -107,17 +152,38 @@ impl Command for Table {
     }
 }
 
+/// How a value shows in a cell. An error, eg of a file `ls` can't read, only shows what went wrong,
+/// so that it fits in the cell, while the rest of the table is still shown
+fn cell_string(value: Value, config: &Config) -> String {
+    match value {
+        Value::Error { error } => format!("<error: {}>", error),
+        value => value.into_string(", ", config),
+    }
+}
+
+/// The rows as a table, with an index column numbered from `start_number` unless it's `None`
 fn convert_to_table(
     iter: impl IntoIterator<Item = Value>,
+    start_number: Option<i64>,
     ctrlc: Option<Arc<AtomicBool>>,
     config: &Config,
+    ls_colors: Option<&LsColors>,
 ) -> Result<Option<nu_table::Table>, ShellError> {
     let mut iter = iter.into_iter().peekable();
 
     if let Some(first) = iter.peek() {
         let mut headers = first.columns();
+        let show_index = start_number.is_some();
+
+        let has_index_column = match headers.iter().position(|header| header == INDEX_COLUMN) {
+            Some(pos) => {
+                headers.remove(pos);
+                true
+            }
+            None => false,
+        };
 
-        if !headers.is_empty() {
+        if show_index && !headers.is_empty() {
             headers.insert(0, "#".into());
         }
 
@@ -132,12 +198,19 @@ fn convert_to_table(
             if let Value::Error { error } = item {
                 return Err(error);
             }
-            let mut row = vec![row_num.to_string()];
+            let mut row = vec![];
+            if let Some(start_number) = start_number {
+                if has_index_column {
+                    row.push(index_of(&item, config));
+                } else {
+                    row.push((start_number + row_num as i64).to_string());
+                }
+            }
 
             if headers.is_empty() {
-                row.push(item.into_string(", ", config))
+                row.push(cell_string(item, config))
             } else {
-                for header in headers.iter().skip(1) {
+                for header in headers.iter().skip(if show_index { 1 } else { 0 }) {
                     let result = match item {
                         Value::Record { .. } => {
                             item.clone().follow_cell_path(&[PathMember::String {
@@ -148,9 +221,12 @@ fn convert_to_table(
                         _ => Ok(item.clone()),
                     };
 
-                    match result {
-                        Ok(value) => row.push(value.into_string(", ", config)),
-                        Err(_) => row.push(String::new()),
+                    match (result, ls_colors) {
+                        (Ok(value), Some(ls_colors)) if header == "name" => {
+                            row.push(style_path(ls_colors, &value.into_string(", ", config)))
+                        }
+                        (Ok(value), _) => row.push(cell_string(value, config)),
+                        (Err(_), _) => row.push(String::new()),
                     }
                 }
             }
@@ -172,7 +248,7 @@ fn convert_to_table(
                     x.into_iter()
                         .enumerate()
                         .map(|(col, y)| {
-                            if col == 0 {
+                            if col == 0 && show_index {
                                 StyledString {
                                     contents: y,
                                     style: nu_table::TextStyle::default_header(),
@@ -194,6 +270,18 @@ fn convert_to_table(
     }
 }
 
+/// The number a row had before it was filtered, if it has one
+fn index_of(item: &Value, config: &Config) -> String {
+    match item {
+        Value::Record { cols, vals, .. } => cols
+            .iter()
+            .position(|col| col == INDEX_COLUMN)
+            .map(|pos| vals[pos].clone().into_string(", ", config))
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
 fn load_theme_from_config(config: &Config) -> Theme {
     match config.table_mode.as_str() {
         "basic" => nu_table::Theme::basic(),
@@ -208,3 +296,119 @@ fn load_theme_from_config(config: &Config) -> Theme {
         _ => nu_table::Theme::rounded(),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn index_column(rows: Vec<Value>, start_number: Option<i64>) -> Vec<String> {
+        let table = convert_to_table(rows, start_number, None, &Config::default(), None)
+            .expect("rows should make a table")
+            .expect("rows should make a table");
+
+        table
+            .data
+            .into_iter()
+            .map(|row| row[0].contents.clone())
+            .collect()
+    }
+
+    fn row(cols: &[&str], vals: Vec<Value>) -> Value {
+        Value::Record {
+            cols: cols.iter().map(|col| col.to_string()).collect(),
+            vals,
+            span: Span::unknown(),
+        }
+    }
+
+    #[test]
+    fn shows_errors_in_cells_compactly() {
+        let rows = vec![row(
+            &["name", "size"],
+            vec![
+                Value::test_string("locked"),
+                Value::Error {
+                    error: ShellError::PermissionDenied("locked".into(), Span::unknown()),
+                },
+            ],
+        )];
+
+        let table = convert_to_table(rows, None, None, &Config::default(), None)
+            .expect("rows should make a table")
+            .expect("rows should make a table");
+
+        assert_eq!(table.data[0][1].contents, "<error: Permission denied>");
+    }
+
+    #[test]
+    fn numbers_rows_from_the_start_number() {
+        let rows = vec![
+            row(&["name"], vec![Value::test_string("a")]),
+            row(&["name"], vec![Value::test_string("b")]),
+        ];
+
+        assert_eq!(index_column(rows.clone(), Some(0)), vec!["0", "1"]);
+        assert_eq!(index_column(rows, Some(5)), vec!["5", "6"]);
+    }
+
+    #[test]
+    fn hides_the_index() {
+        let rows = vec![
+            row(&["name"], vec![Value::test_string("a")]),
+            row(&["name"], vec![Value::test_string("b")]),
+        ];
+
+        assert_eq!(index_column(rows, None), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn keeps_the_numbers_of_filtered_rows() {
+        let rows = vec![
+            row(
+                &[INDEX_COLUMN, "name"],
+                vec![Value::test_int(1), Value::test_string("b")],
+            ),
+            row(
+                &[INDEX_COLUMN, "name"],
+                vec![Value::test_int(3), Value::test_string("d")],
+            ),
+        ];
+
+        let table = convert_to_table(rows.clone(), Some(10), None, &Config::default(), None)
+            .expect("rows should make a table")
+            .expect("rows should make a table");
+        let headers: Vec<_> = table.headers.into_iter().map(|h| h.contents).collect();
+
+        assert_eq!(headers, vec!["#", "name"]);
+        assert_eq!(index_column(rows.clone(), Some(10)), vec!["1", "3"]);
+        assert_eq!(index_column(rows, None), vec!["b", "d"]);
+    }
+
+    #[test]
+    fn own_index_columns_are_shown_as_they_are() {
+        let rows = vec![
+            row(
+                &["index", "name"],
+                vec![Value::test_int(7), Value::test_string("a")],
+            ),
+            row(
+                &["index", "name"],
+                vec![Value::test_int(9), Value::test_string("b")],
+            ),
+        ];
+
+        let table = convert_to_table(rows.clone(), Some(0), None, &Config::default(), None)
+            .expect("rows should make a table")
+            .expect("rows should make a table");
+        let headers: Vec<_> = table.headers.into_iter().map(|h| h.contents).collect();
+        let own_index: Vec<_> = table
+            .data
+            .iter()
+            .map(|row| row[1].contents.clone())
+            .collect();
+
+        assert_eq!(headers, vec!["#", "index", "name"]);
+        assert_eq!(index_column(rows, Some(0)), vec!["0", "1"]);
+        assert_eq!(own_index, vec!["7", "9"]);
+    }
+}
@@ -0,0 +1,179 @@
+use nu_protocol::engine::{EngineState, Stack};
+use nu_protocol::{BlockId, PipelineData, PipelineIterator, Value};
+
+use crate::eval_block;
+
+/// Evaluate a block that's a second source of data for a command (eg the block given to `zip`).
+///
+/// The block runs exactly once, on a child stack holding its captures, and only when the first
+/// value is asked for. Its output is then streamed, so the command decides whether to consume it
+/// in lockstep with its input or all at once.
+pub fn eval_block_as_stream(
+    engine_state: &EngineState,
+    stack: &Stack,
+    block_id: BlockId,
+    input: PipelineData,
+) -> BlockStream {
+    let block = engine_state.get_block(block_id);
+
+    BlockStream::Pending {
+        engine_state: Box::new(engine_state.clone()),
+        stack: stack.collect_captures(&block.captures),
+        block_id,
+        input,
+    }
+}
+
+/// The output of a block given to [`eval_block_as_stream`], running it when first pulled
+pub enum BlockStream {
+    Pending {
+        engine_state: Box<EngineState>,
+        stack: Stack,
+        block_id: BlockId,
+        input: PipelineData,
+    },
+    Running(PipelineIterator),
+    /// The block failed, and the error has been passed on
+    Done,
+}
+
+impl Iterator for BlockStream {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let BlockStream::Running(output) = self {
+            return output.next();
+        }
+
+        match std::mem::replace(self, BlockStream::Done) {
+            BlockStream::Pending {
+                engine_state,
+                mut stack,
+                block_id,
+                input,
+            } => {
+                let block = engine_state.get_block(block_id);
+
+                match eval_block(&engine_state, &mut stack, block, input) {
+                    Ok(output) => {
+                        let mut output = output.into_iter();
+                        let first = output.next();
+                        *self = BlockStream::Running(output);
+
+                        first
+                    }
+                    Err(error) => Some(Value::Error { error }),
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::test_support::{engine_state_with, parse_delta, stack_with_config};
+    use nu_protocol::ast::Call;
+    use nu_protocol::engine::{Command, StateWorkingSet};
+    use nu_protocol::{IntoInterruptiblePipelineData, ShellError, Signature, Span};
+
+    /// Counts how many times it runs, giving back the values 1, 2, 3
+    #[derive(Clone)]
+    struct Counter(Arc<AtomicUsize>);
+
+    impl Command for Counter {
+        fn name(&self) -> &str {
+            "counter"
+        }
+
+        fn signature(&self) -> Signature {
+            Signature::build("counter")
+        }
+
+        fn usage(&self) -> &str {
+            "Count the runs"
+        }
+
+        fn run(
+            &self,
+            _engine_state: &EngineState,
+            _stack: &mut Stack,
+            _call: &Call,
+            _input: PipelineData,
+        ) -> Result<PipelineData, ShellError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+
+            Ok((1..=3).map(Value::test_int).into_pipeline_data(None))
+        }
+    }
+
+    fn block_stream(source: &str) -> (BlockStream, Arc<AtomicUsize>) {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mut engine_state = engine_state_with(vec![Box::new(Counter(runs.clone()))]);
+
+        let (block, delta) = parse_delta(&engine_state, source.as_bytes());
+        engine_state.merge_delta(delta);
+        let block_id = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            let block_id = working_set.add_block(block);
+            let delta = working_set.render();
+            engine_state.merge_delta(delta);
+            block_id
+        };
+
+        let stack = stack_with_config();
+
+        let stream = eval_block_as_stream(
+            &engine_state,
+            &stack,
+            block_id,
+            PipelineData::new(Span::unknown()),
+        );
+
+        (stream, runs)
+    }
+
+    #[test]
+    fn runs_once_when_first_pulled() {
+        let (stream, runs) = block_stream("counter");
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+        let pairs: Vec<(i64, Value)> = (10..).zip(stream).collect();
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            pairs,
+            vec![
+                (10, Value::test_int(1)),
+                (11, Value::test_int(2)),
+                (12, Value::test_int(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn stays_finished() {
+        let (mut stream, runs) = block_stream("counter");
+
+        assert_eq!(stream.by_ref().count(), 3);
+        assert_eq!(stream.next(), None);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn errors_end_the_stream() {
+        let (mut stream, _) = block_stream("1 / 0");
+
+        assert!(matches!(
+            stream.next(),
+            Some(Value::Error {
+                error: ShellError::DivisionByZero(..)
+            })
+        ));
+        assert_eq!(stream.next(), None);
+    }
+}
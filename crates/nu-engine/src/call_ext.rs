@@ -1,9 +1,11 @@
 use nu_protocol::{
-    ast::Call,
+    ast::{Call, Expression},
     engine::{EngineState, Stack},
-    ShellError,
+    ShellError, Spanned,
 };
 
+use std::fmt::Debug;
+
 use crate::{eval_expression, FromValue};
 
 pub trait CallExt {
@@ -14,6 +16,15 @@ pub trait CallExt {
         name: &str,
     ) -> Result<Option<T>, ShellError>;
 
+    /// Like `get_flag`, with the span of the flag's value as it was written, eg the variable it
+    /// came from, so that errors about the value can point at it
+    fn get_flag_spanned<T: FromValue + Clone + Debug>(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        name: &str,
+    ) -> Result<Option<Spanned<T>>, ShellError>;
+
     fn rest<T: FromValue>(
         &self,
         engine_state: &EngineState,
@@ -21,6 +32,15 @@ pub trait CallExt {
         starting_pos: usize,
     ) -> Result<Vec<T>, ShellError>;
 
+    /// Like `rest`, with the span of each argument as it was written, eg the variable it came
+    /// from, so that errors about one of them can point at it
+    fn rest_spanned<T: FromValue + Clone + Debug>(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        starting_pos: usize,
+    ) -> Result<Vec<Spanned<T>>, ShellError>;
+
     fn opt<T: FromValue>(
         &self,
         engine_state: &EngineState,
@@ -34,6 +54,22 @@ pub trait CallExt {
         stack: &mut Stack,
         pos: usize,
     ) -> Result<T, ShellError>;
+
+    /// Like `opt`, with the span of the argument as it was written
+    fn opt_spanned<T: FromValue + Clone + Debug>(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        pos: usize,
+    ) -> Result<Option<Spanned<T>>, ShellError>;
+
+    /// Like `req`, with the span of the argument as it was written
+    fn req_spanned<T: FromValue + Clone + Debug>(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        pos: usize,
+    ) -> Result<Spanned<T>, ShellError>;
 }
 
 impl CallExt for Call {
@@ -51,6 +87,19 @@ impl CallExt for Call {
         }
     }
 
+    fn get_flag_spanned<T: FromValue + Clone + Debug>(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        name: &str,
+    ) -> Result<Option<Spanned<T>>, ShellError> {
+        if let Some(expr) = self.get_flag_expr(name) {
+            eval_spanned(engine_state, stack, &expr).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
     fn rest<T: FromValue>(
         &self,
         engine_state: &EngineState,
@@ -67,6 +116,19 @@ impl CallExt for Call {
         Ok(output)
     }
 
+    fn rest_spanned<T: FromValue + Clone + Debug>(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        starting_pos: usize,
+    ) -> Result<Vec<Spanned<T>>, ShellError> {
+        self.positional
+            .iter()
+            .skip(starting_pos)
+            .map(|expr| eval_spanned(engine_state, stack, expr))
+            .collect()
+    }
+
     fn opt<T: FromValue>(
         &self,
         engine_state: &EngineState,
@@ -97,4 +159,191 @@ impl CallExt for Call {
             ))
         }
     }
+
+    fn opt_spanned<T: FromValue + Clone + Debug>(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        pos: usize,
+    ) -> Result<Option<Spanned<T>>, ShellError> {
+        if let Some(expr) = self.nth(pos) {
+            eval_spanned(engine_state, stack, &expr).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn req_spanned<T: FromValue + Clone + Debug>(
+        &self,
+        engine_state: &EngineState,
+        stack: &mut Stack,
+        pos: usize,
+    ) -> Result<Spanned<T>, ShellError> {
+        if let Some(expr) = self.nth(pos) {
+            eval_spanned(engine_state, stack, &expr)
+        } else {
+            Err(ShellError::AccessBeyondEnd(
+                self.positional.len(),
+                self.head,
+            ))
+        }
+    }
+}
+
+/// The value of the expression, with the expression's span. A value of the wrong type is an error
+/// at the expression too, rather than where the value was made
+fn eval_spanned<T: FromValue + Clone + Debug>(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    expr: &Expression,
+) -> Result<Spanned<T>, ShellError> {
+    let result = eval_expression(engine_state, stack, expr)?;
+
+    match FromValue::from_value(&result) {
+        Ok(item) => Ok(Spanned {
+            item,
+            span: expr.span,
+        }),
+        Err(ShellError::CantConvert(to, from, _)) => {
+            Err(ShellError::CantConvert(to, from, expr.span))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nu_protocol::ast::Expr;
+    use nu_protocol::{Span, Type, Value, VarId};
+
+    fn call_with_flag(name: &str, value: Expression) -> Call {
+        let mut call = Call::new();
+        call.named.push((
+            Spanned {
+                item: name.into(),
+                span: Span::new(3, 13),
+            },
+            Some(value),
+        ));
+        call
+    }
+
+    #[test]
+    fn spanned_flags_have_the_span_of_their_value() {
+        let call = call_with_flag(
+            "decimals",
+            Expression {
+                expr: Expr::Int(2),
+                span: Span::new(14, 15),
+                ty: Type::Int,
+                custom_completion: None,
+            },
+        );
+        let (engine_state, mut stack) = (EngineState::new(), Stack::new());
+
+        let decimals: Option<Spanned<i64>> = call
+            .get_flag_spanned(&engine_state, &mut stack, "decimals")
+            .expect("an int flag");
+        assert!(matches!(
+            decimals,
+            Some(Spanned { item: 2, span }) if span == Span::new(14, 15)
+        ));
+        assert_eq!(call.get_flag_span("decimals"), Some(Span::new(3, 13)));
+
+        let missing: Option<Spanned<i64>> = call
+            .get_flag_spanned(&engine_state, &mut stack, "precision")
+            .expect("a missing flag is fine");
+        assert!(missing.is_none());
+        assert_eq!(call.get_flag_span("precision"), None);
+    }
+
+    fn expression(expr: Expr, span: Span) -> Expression {
+        Expression {
+            expr,
+            span,
+            ty: Type::Unknown,
+            custom_completion: None,
+        }
+    }
+
+    #[test]
+    fn spanned_rest_arguments_have_the_span_they_were_written_at() {
+        let var_id = VarId::new(10);
+        let mut call = Call::new();
+        call.positional = vec![
+            expression(Expr::Int(1), Span::new(6, 7)),
+            expression(Expr::String("a".into()), Span::new(8, 9)),
+            expression(Expr::Var(var_id), Span::new(10, 12)),
+        ];
+        let engine_state = EngineState::new();
+        let mut stack = Stack::new();
+        stack.add_var(
+            var_id,
+            Value::String {
+                val: "b".into(),
+                span: Span::new(100, 103),
+            },
+        );
+
+        let rest: Vec<Spanned<String>> = call
+            .rest_spanned(&engine_state, &mut stack, 1)
+            .expect("strings");
+        let rest: Vec<(&str, Span)> = rest
+            .iter()
+            .map(|arg| (arg.item.as_str(), arg.span))
+            .collect();
+        assert_eq!(rest, vec![("a", Span::new(8, 9)), ("b", Span::new(10, 12))]);
+    }
+
+    #[test]
+    fn variables_of_the_wrong_type_are_an_error_at_the_variable() {
+        let var_id = VarId::new(10);
+        let mut call = Call::new();
+        call.positional = vec![expression(Expr::Var(var_id), Span::new(10, 12))];
+        let engine_state = EngineState::new();
+        let mut stack = Stack::new();
+        // Where the value was made, eg in a `let` further up
+        stack.add_var(
+            var_id,
+            Value::Int {
+                val: 5,
+                span: Span::new(100, 101),
+            },
+        );
+
+        let rest: Result<Vec<Spanned<String>>, _> = call.rest_spanned(&engine_state, &mut stack, 0);
+        assert!(matches!(
+            rest,
+            Err(ShellError::CantConvert(_, _, span)) if span == Span::new(10, 12)
+        ));
+    }
+
+    #[test]
+    fn spanned_positionals_have_the_span_they_were_written_at() {
+        let mut call = Call::new();
+        call.head = Span::new(0, 5);
+        call.positional = vec![expression(Expr::Int(1), Span::new(6, 7))];
+        let (engine_state, mut stack) = (EngineState::new(), Stack::new());
+
+        let first: Spanned<i64> = call
+            .req_spanned(&engine_state, &mut stack, 0)
+            .expect("an int");
+        assert!(matches!(first, Spanned { item: 1, span } if span == Span::new(6, 7)));
+        let first: Option<Spanned<i64>> = call
+            .opt_spanned(&engine_state, &mut stack, 0)
+            .expect("an int");
+        assert!(matches!(first, Some(Spanned { item: 1, span }) if span == Span::new(6, 7)));
+
+        // A missing one is the same error as with `req`
+        let second: Result<Spanned<i64>, _> = call.req_spanned(&engine_state, &mut stack, 1);
+        assert!(matches!(
+            second,
+            Err(ShellError::AccessBeyondEnd(1, span)) if span == Span::new(0, 5)
+        ));
+        let second: Option<Spanned<i64>> = call
+            .opt_spanned(&engine_state, &mut stack, 1)
+            .expect("a missing optional is fine");
+        assert!(second.is_none());
+    }
 }
@@ -0,0 +1,41 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+
+use nu_protocol::engine::Confirm;
+use nu_protocol::ShellError;
+
+/// Asks on the controlling terminal, so the question still reaches the user when stdin or stdout
+/// are redirected
+pub struct TerminalPrompt;
+
+impl TerminalPrompt {
+    fn open_terminal() -> std::io::Result<(File, File)> {
+        #[cfg(windows)]
+        let (input, output) = ("CONIN$", "CONOUT$");
+        #[cfg(not(windows))]
+        let (input, output) = ("/dev/tty", "/dev/tty");
+
+        Ok((
+            File::open(input)?,
+            OpenOptions::new().write(true).open(output)?,
+        ))
+    }
+}
+
+impl Confirm for TerminalPrompt {
+    fn is_interactive(&self) -> bool {
+        TerminalPrompt::open_terminal().is_ok()
+    }
+
+    fn confirm(&self, prompt: &str) -> Result<bool, ShellError> {
+        let (input, mut output) = TerminalPrompt::open_terminal()?;
+
+        write!(output, "{} [y/N] ", prompt)?;
+        output.flush()?;
+
+        let mut answer = String::new();
+        BufReader::new(input).read_line(&mut answer)?;
+
+        Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+    }
+}
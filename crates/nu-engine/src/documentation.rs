@@ -1,6 +1,7 @@
 use itertools::Itertools;
-use nu_protocol::{engine::EngineState, Example, Signature, Span, Value};
+use nu_protocol::{engine::EngineState, Category, Config, Example, Signature, Span, Value};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 const COMMANDS_DOCS_DIR: &str = "docs/commands";
 
@@ -62,7 +63,7 @@ fn generate_doc(name: &str, engine_state: &EngineState) -> (Vec<String>, Vec<Val
 
 // generate_docs gets the documentation from each command and returns a Table as output
 pub fn generate_docs(engine_state: &EngineState) -> Value {
-    let signatures = engine_state.get_signatures();
+    let signatures = engine_state.get_signatures(false);
 
     // cmap will map parent commands to it's subcommands e.g. to -> [to csv, to yaml, to bson]
     let mut cmap: HashMap<String, Vec<String>> = HashMap::new();
@@ -156,44 +157,27 @@ pub fn get_documentation(
         long_desc.push_str("\n\n");
     }
 
-    let mut subcommands = vec![];
-    if !config.no_subcommands {
-        let signatures = engine_state.get_signatures();
-        for sig in signatures {
-            if sig.name.starts_with(&format!("{} ", cmd_name)) {
-                subcommands.push(format!("  {} - {}", sig.name, sig.usage));
-            }
-        }
+    if let Some(notice) = &sig.deprecated {
+        long_desc.push_str(&format!("Deprecated: {}\n\n", notice));
     }
 
-    let mut one_liner = String::new();
-    one_liner.push_str(&sig.name);
-    one_liner.push(' ');
-
-    for positional in &sig.required_positional {
-        one_liner.push_str(&format!("<{}> ", positional.name));
-    }
-    for positional in &sig.optional_positional {
-        one_liner.push_str(&format!("({}) ", positional.name));
-    }
-
-    if sig.rest_positional.is_some() {
-        one_liner.push_str("...args ");
+    if sig.category != Category::Default {
+        long_desc.push_str(&format!("Category: {}\n\n", sig.category));
     }
 
-    if !subcommands.is_empty() {
-        one_liner.push_str("<subcommand> ");
+    let mut subcommands = vec![];
+    if !config.no_subcommands {
+        for (name, usage) in get_subcommands(cmd_name, engine_state) {
+            subcommands.push(format!("  {} - {}", name, usage));
+        }
     }
 
-    if !sig.named.is_empty() {
-        one_liner.push_str("{flags} ");
-    }
+    let one_liner = get_one_liner(sig, !subcommands.is_empty());
 
     long_desc.push_str(&format!("Usage:\n  > {}\n", one_liner));
 
     if !subcommands.is_empty() {
         long_desc.push_str("\nSubcommands:\n");
-        subcommands.sort();
         long_desc.push_str(&subcommands.join("\n"));
         long_desc.push('\n');
     }
@@ -241,6 +225,33 @@ pub fn get_documentation(
     long_desc
 }
 
+fn get_one_liner(sig: &Signature, has_subcommands: bool) -> String {
+    let mut one_liner = String::new();
+    one_liner.push_str(&sig.name);
+    one_liner.push(' ');
+
+    for positional in &sig.required_positional {
+        one_liner.push_str(&format!("<{}> ", positional.name));
+    }
+    for positional in &sig.optional_positional {
+        one_liner.push_str(&format!("({}) ", positional.name));
+    }
+
+    if sig.rest_positional.is_some() {
+        one_liner.push_str("...args ");
+    }
+
+    if has_subcommands {
+        one_liner.push_str("<subcommand> ");
+    }
+
+    if !sig.named.is_empty() {
+        one_liner.push_str("{flags} ");
+    }
+
+    one_liner
+}
+
 fn get_flags_section(signature: &Signature) -> String {
     let mut long_desc = String::new();
     long_desc.push_str("\nFlags:\n");
@@ -330,3 +341,276 @@ pub fn get_brief_help(sig: &Signature, examples: &[Example], engine_state: &Engi
 pub fn get_full_help(sig: &Signature, examples: &[Example], engine_state: &EngineState) -> String {
     get_documentation(sig, examples, engine_state, &DocumentationConfig::default())
 }
+
+/// The visible commands named `<prefix> <something>`, with their usage, sorted by name. Parent
+/// commands like `str` use it to list whatever subcommands are registered at the time
+pub fn get_subcommands(prefix: &str, engine_state: &EngineState) -> Vec<(String, String)> {
+    engine_state
+        .find_commands_by_prefix(format!("{} ", prefix).as_bytes())
+        .into_iter()
+        .unique()
+        .filter_map(|name| {
+            // Hidden commands are left out, and a redefined name shows the definition in scope
+            let decl = engine_state.get_decl(engine_state.find_decl(&name)?);
+            Some((
+                String::from_utf8_lossy(&name).to_string(),
+                decl.usage().to_string(),
+            ))
+        })
+        .sorted()
+        .collect()
+}
+
+/// The markdown file name used for a command's documentation, eg `str lpad` -> `str_lpad.md`
+pub fn markdown_doc_filename(name: &str) -> String {
+    let sanitized: String = name
+        .split_whitespace()
+        .join("_")
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    format!("{}.md", sanitized)
+}
+
+/// Render the documentation of a single command as markdown. Example results are rendered from
+/// the values the examples declare, so the documentation stays in sync with the example tests.
+pub fn get_markdown_documentation(
+    sig: &Signature,
+    examples: &[Example],
+    engine_state: &EngineState,
+    config: &Config,
+) -> String {
+    let mut doc = format!("# `{}`\n\n", sig.name);
+
+    if !sig.usage.is_empty() {
+        doc.push_str(&sig.usage);
+        doc.push_str("\n\n");
+    }
+
+    if !sig.extra_usage.is_empty() {
+        doc.push_str(&sig.extra_usage);
+        doc.push_str("\n\n");
+    }
+
+    if let Some(notice) = &sig.deprecated {
+        doc.push_str(&format!("**Deprecated:** {}\n\n", notice));
+    }
+
+    if sig.category != Category::Default {
+        doc.push_str(&format!("Category: {}\n\n", sig.category));
+    }
+
+    let subcommands: Vec<Signature> = engine_state
+        .get_signatures(false)
+        .into_iter()
+        .filter(|sub| sub.name.starts_with(&format!("{} ", sig.name)))
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect();
+
+    doc.push_str("## Signature\n\n");
+    doc.push_str(&format!(
+        "```> {}```\n",
+        get_one_liner(sig, !subcommands.is_empty())
+    ));
+
+    if !subcommands.is_empty() {
+        doc.push_str("\n## Subcommands\n\n");
+        for sub in &subcommands {
+            doc.push_str(&format!(
+                " -  [`{}`]({}) - {}\n",
+                sub.name,
+                markdown_doc_filename(&sub.name),
+                sub.usage
+            ));
+        }
+    }
+
+    if !sig.required_positional.is_empty()
+        || !sig.optional_positional.is_empty()
+        || sig.rest_positional.is_some()
+    {
+        doc.push_str("\n## Parameters\n\n");
+        for positional in &sig.required_positional {
+            doc.push_str(&format!(
+                " -  `<{}>`: {}\n",
+                positional.name, positional.desc
+            ));
+        }
+        for positional in &sig.optional_positional {
+            doc.push_str(&format!(
+                " -  `({})`: {}\n",
+                positional.name, positional.desc
+            ));
+        }
+        if let Some(rest_positional) = &sig.rest_positional {
+            doc.push_str(&format!(" -  `...args`: {}\n", rest_positional.desc));
+        }
+    }
+
+    if !sig.named.is_empty() {
+        doc.push_str("\n## Flags\n\n");
+        doc.push_str("| flag | short | argument | description |\n");
+        doc.push_str("| ---- | ----- | -------- | ----------- |\n");
+        for flag in &sig.named {
+            doc.push_str(&format!(
+                "| `--{}` | {} | {} | {}{} |\n",
+                flag.long,
+                flag.short.map(|c| format!("`-{}`", c)).unwrap_or_default(),
+                flag.arg
+                    .as_ref()
+                    .map(|arg| format!("`{:?}`", arg))
+                    .unwrap_or_default(),
+                if flag.required { "(required) " } else { "" },
+                flag.desc.replace('|', "\\|")
+            ));
+        }
+    }
+
+    if !examples.is_empty() {
+        doc.push_str("\n## Examples\n");
+    }
+    for example in examples {
+        doc.push_str(&format!("\n{}\n", example.description));
+        doc.push_str(&format!("```shell\n> {}\n", example.example));
+        match &example.result {
+            Some(Value::Error { error }) => doc.push_str(&format!("Error: {}\n", error)),
+            Some(result) => {
+                doc.push_str(&result.clone().into_string(", ", config));
+                doc.push('\n');
+            }
+            None => {}
+        }
+        doc.push_str("```\n");
+    }
+
+    doc
+}
+
+/// Write one markdown file per command into `dir`, returning the paths of the written files
+pub fn write_markdown_docs(
+    engine_state: &EngineState,
+    dir: &Path,
+    config: &Config,
+) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+
+    let mut written = vec![];
+    for (sig, examples) in engine_state.get_signatures_with_examples(false) {
+        let path = dir.join(markdown_doc_filename(&sig.name));
+        std::fs::write(
+            &path,
+            get_markdown_documentation(&sig, &examples, engine_state, config),
+        )?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_support::engine_state_with;
+    use nu_protocol::ast::Call;
+    use nu_protocol::engine::{Command, Stack, StateWorkingSet};
+    use nu_protocol::{PipelineData, ShellError};
+
+    #[derive(Clone)]
+    struct TestCommand {
+        name: &'static str,
+        usage: &'static str,
+    }
+
+    impl Command for TestCommand {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn signature(&self) -> Signature {
+            Signature::build(self.name)
+        }
+
+        fn usage(&self) -> &str {
+            self.usage
+        }
+
+        fn run(
+            &self,
+            _engine_state: &EngineState,
+            _stack: &mut Stack,
+            _call: &Call,
+            input: PipelineData,
+        ) -> Result<PipelineData, ShellError> {
+            Ok(input)
+        }
+    }
+
+    fn state_with(commands: &[TestCommand]) -> EngineState {
+        engine_state_with(
+            commands
+                .iter()
+                .map(|command| Box::new(command.clone()) as Box<dyn Command>)
+                .collect(),
+        )
+    }
+
+    const STR: TestCommand = TestCommand {
+        name: "str",
+        usage: "Various commands for working with string data.",
+    };
+
+    #[test]
+    fn lists_registered_subcommands() {
+        let engine_state = state_with(&[
+            STR,
+            TestCommand {
+                name: "str fake",
+                usage: "A subcommand only this test knows about.",
+            },
+            TestCommand {
+                name: "strange",
+                usage: "Not a subcommand.",
+            },
+        ]);
+
+        let help = get_full_help(&STR.signature(), &[], &engine_state);
+
+        assert!(
+            help.contains("Subcommands:\n  str fake - A subcommand only this test knows about.\n")
+        );
+        assert!(!help.contains("strange"));
+    }
+
+    #[test]
+    fn leaves_hidden_subcommands_out() {
+        let mut engine_state = state_with(&[
+            STR,
+            TestCommand {
+                name: "str fake",
+                usage: "A subcommand only this test knows about.",
+            },
+            TestCommand {
+                name: "str kept",
+                usage: "Still visible.",
+            },
+        ]);
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.hide_decl(b"str fake");
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        assert_eq!(
+            get_subcommands("str", &engine_state),
+            vec![("str kept".to_string(), "Still visible.".to_string())]
+        );
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use nu_protocol::engine::{EngineState, Stack};
+use nu_protocol::{BlockId, Config, IntoPipelineData, ShellError, ShellWarning, Span, Value};
+
+use crate::eval_block;
+
+/// The directory relative paths are resolved against: PWD from the environment, or the process's
+/// current directory if PWD isn't set.
+///
+/// Filesystem commands use this rather than the process's current directory, which only follows
+/// PWD as long as nothing but `cd` changes it.
+pub fn current_dir(_engine_state: &EngineState, stack: &Stack) -> Result<PathBuf, ShellError> {
+    let cwd = match stack.get_env_var("PWD") {
+        Some(pwd) => PathBuf::from(pwd.as_string()?),
+        None => std::env::current_dir()?,
+    };
+
+    if cwd.is_dir() {
+        Ok(cwd)
+    } else {
+        Err(ShellError::CurrentDirNotFound(
+            cwd.to_string_lossy().to_string(),
+        ))
+    }
+}
+
+/// Turn the env vars that are still the strings they came from the process environment as into
+/// the values the shell works with, by their `from_string` conversion in the config. PATH has a
+/// built-in one, splitting it into a list. A conversion that fails leaves the string in place,
+/// with a warning naming the variable
+pub fn convert_env_values(engine_state: &EngineState, stack: &mut Stack) {
+    let config = stack.get_config().unwrap_or_default();
+    let mut converted = vec![];
+
+    for (name, value) in &stack.env_vars {
+        let (val, span) = match value {
+            Value::String { val, span } => (val, *span),
+            _ => continue,
+        };
+
+        let result = match config.env_conversions.get(name) {
+            Some(conversion) => match conversion.from_string {
+                Some(block_id) => run_conversion(engine_state, stack, block_id, value.clone()),
+                None => continue,
+            },
+            None if is_path_var(name) => Ok(Value::List {
+                vals: std::env::split_paths(val)
+                    .map(|path| Value::string(path.to_string_lossy(), span))
+                    .collect(),
+                span,
+            }),
+            None => continue,
+        };
+
+        match result {
+            Ok(value) => converted.push((name.clone(), value)),
+            Err(err) => warn_conversion_failed(engine_state, name, &err),
+        }
+    }
+
+    stack.env_vars.extend(converted);
+}
+
+/// The env vars the way external commands get them, as strings. A variable is converted by its
+/// `to_string` conversion in the config, or the built-in one for PATH, and otherwise shown the way
+/// it would be as a string. A conversion that fails is warned about, naming the variable, and the
+/// variable is passed as it would be shown
+pub fn env_vars_as_strings(engine_state: &EngineState, stack: &Stack) -> HashMap<String, String> {
+    let config = stack.get_config().unwrap_or_default();
+
+    stack
+        .env_vars
+        .iter()
+        .map(|(name, value)| {
+            let converted = match config.env_conversions.get(name) {
+                Some(conversion) => match conversion.to_string {
+                    Some(block_id) => run_conversion(engine_state, stack, block_id, value.clone())
+                        .and_then(|converted| converted.as_string()),
+                    None => Ok(value_as_string(value, &config)),
+                },
+                None if is_path_var(name) => join_paths(value, &config),
+                None => Ok(value_as_string(value, &config)),
+            };
+
+            let converted = converted.unwrap_or_else(|err| {
+                warn_conversion_failed(engine_state, name, &err);
+                value_as_string(value, &config)
+            });
+
+            (name.clone(), converted)
+        })
+        .collect()
+}
+
+/// Whether `name` is a program external commands can run: a path to a file, or a file in one of
+/// the directories in PATH
+pub fn is_program(stack: &Stack, name: &str) -> bool {
+    if name.contains('/') || name.contains(std::path::MAIN_SEPARATOR) {
+        return Path::new(name).is_file();
+    }
+
+    let config = stack.get_config().unwrap_or_default();
+    let path = match stack.env_vars.iter().find(|(var, _)| is_path_var(var)) {
+        Some((_, value)) => match join_paths(value, &config) {
+            Ok(path) => path,
+            Err(_) => return false,
+        },
+        None => return false,
+    };
+
+    std::env::split_paths(&path).any(|dir| {
+        let candidate = dir.join(name);
+        candidate.is_file() || (cfg!(windows) && candidate.with_extension("exe").is_file())
+    })
+}
+
+fn is_path_var(name: &str) -> bool {
+    name == "PATH" || name == "Path"
+}
+
+fn join_paths(value: &Value, config: &Config) -> Result<String, ShellError> {
+    match value {
+        Value::List { vals, span } => {
+            let paths = vals
+                .iter()
+                .map(|path| path.as_string())
+                .collect::<Result<Vec<_>, _>>()?;
+
+            std::env::join_paths(paths)
+                .map(|joined| joined.to_string_lossy().to_string())
+                .map_err(|err| ShellError::UnsupportedInput(err.to_string(), *span))
+        }
+        value => Ok(value_as_string(value, config)),
+    }
+}
+
+fn value_as_string(value: &Value, config: &Config) -> String {
+    value.clone().into_string(", ", config)
+}
+
+/// Run a conversion block with the variable's value as its input
+fn run_conversion(
+    engine_state: &EngineState,
+    stack: &Stack,
+    block_id: BlockId,
+    value: Value,
+) -> Result<Value, ShellError> {
+    let span = value.span_or(Span::unknown());
+    let block = engine_state.get_block(block_id);
+    let mut stack = stack.collect_captures(&block.captures);
+
+    match eval_block(engine_state, &mut stack, block, value.into_pipeline_data())?.into_value(span)
+    {
+        Value::Error { error } => Err(error),
+        converted => Ok(converted),
+    }
+}
+
+fn warn_conversion_failed(engine_state: &EngineState, name: &str, err: &ShellError) {
+    engine_state.warn(ShellWarning::EnvConversionFailed {
+        name: name.to_string(),
+        reason: err.to_string(),
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_support::stack_with_config;
+
+    #[test]
+    fn uses_pwd() {
+        let dir = std::env::temp_dir();
+        let mut stack = Stack::new();
+        stack.add_env_var(
+            "PWD".into(),
+            Value::string(dir.to_string_lossy(), Span::unknown()),
+        );
+
+        assert_eq!(current_dir(&EngineState::new(), &stack).unwrap(), dir);
+    }
+
+    #[test]
+    fn converts_path_to_a_list_and_back() {
+        let joined = std::env::join_paths(["/usr/bin", "/bin"].iter())
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let mut stack = stack_with_config();
+        stack.add_env_var("PATH".into(), Value::string(&joined, Span::unknown()));
+        let engine_state = EngineState::new();
+
+        convert_env_values(&engine_state, &mut stack);
+        assert_eq!(
+            stack.get_env_var("PATH"),
+            Some(Value::List {
+                vals: vec![
+                    Value::string("/usr/bin", Span::unknown()),
+                    Value::string("/bin", Span::unknown()),
+                ],
+                span: Span::unknown(),
+            })
+        );
+
+        let env_vars = env_vars_as_strings(&engine_state, &stack);
+        assert_eq!(env_vars.get("PATH"), Some(&joined));
+    }
+
+    #[test]
+    fn falls_back_to_the_process_directory() {
+        assert_eq!(
+            current_dir(&EngineState::new(), &Stack::new()).unwrap(),
+            std::env::current_dir().unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_missing_directories() {
+        let missing = std::env::temp_dir().join("nu-current-dir-that-does-not-exist");
+        let mut stack = Stack::new();
+        stack.add_env_var(
+            "PWD".into(),
+            Value::string(missing.to_string_lossy(), Span::unknown()),
+        );
+
+        assert!(matches!(
+            current_dir(&EngineState::new(), &stack),
+            Err(ShellError::CurrentDirNotFound(..))
+        ));
+    }
+}
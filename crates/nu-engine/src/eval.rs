@@ -1,10 +1,14 @@
-use nu_protocol::ast::{Block, Call, Expr, Expression, Operator, Statement};
+use std::time::Instant;
+
+use nu_protocol::ast::{Block, Call, Expr, Expression, Operator, Pipeline, Statement};
 use nu_protocol::engine::{EngineState, Stack};
 use nu_protocol::{
-    IntoPipelineData, PipelineData, Range, ShellError, Span, Spanned, Type, Unit, Value, VarId,
+    IntoPipelineData, PipelineData, Range, ShellError, ShellWarning, Span, Spanned, Type, Unit,
+    Value, VarId,
 };
 
-use crate::get_full_help;
+use crate::env::is_program;
+use crate::{check_flag_constraints, get_full_help};
 
 pub fn eval_operator(op: &Expression) -> Result<Operator, ShellError> {
     match op {
@@ -26,13 +30,25 @@ fn eval_call(
 ) -> Result<PipelineData, ShellError> {
     let decl = engine_state.get_decl(call.decl_id);
     if call.named.iter().any(|(flag, _)| flag.item == "help") {
-        let full_help = get_full_help(&decl.signature(), &decl.examples(), engine_state);
-        Ok(Value::String {
+        let mut signature = decl.signature();
+        signature.deprecated = decl.deprecated();
+        let full_help = get_full_help(&signature, &decl.examples(), engine_state);
+        return Ok(Value::String {
             val: full_help,
             span: call.head,
         }
-        .into_pipeline_data())
-    } else if let Some(block_id) = decl.get_block_id() {
+        .into_pipeline_data());
+    }
+
+    if let Some(notice) = decl.deprecated() {
+        engine_state.warn(ShellWarning::DeprecatedCommand {
+            name: decl.name().to_string(),
+            notice: notice.to_string(),
+            span: call.head,
+        });
+    }
+
+    if let Some(block_id) = decl.get_block_id() {
         let block = engine_state.get_block(block_id);
 
         let mut stack = stack.collect_captures(&block.captures);
@@ -113,6 +129,10 @@ fn eval_call(
         }
         eval_block(engine_state, &mut stack, block, input)
     } else {
+        // Constraints are only about flags, so calls without any can skip building the signature
+        if !call.named.is_empty() {
+            check_flag_constraints(&decl.signature(), call)?;
+        }
         decl.run(engine_state, stack, call, input)
     }
 }
@@ -130,6 +150,10 @@ fn eval_external(
         .find_decl("run_external".as_bytes())
         .ok_or_else(|| ShellError::ExternalNotSupported(*name_span))?;
 
+    if let Some(err) = misspelled_command(engine_state, stack, name, name_span, args) {
+        return Err(err);
+    }
+
     let command = engine_state.get_decl(decl_id);
 
     let mut call = Call::new();
@@ -158,6 +182,49 @@ fn eval_external(
     command.run(engine_state, stack, &call, input)
 }
 
+/// The error for an external call that's a command with the wrong case or separators, eg
+/// `Str Downcase` or `str_downcase` for `str downcase`, unless there's a program by that name.
+/// Calls marked external with `^` are left alone
+fn misspelled_command(
+    engine_state: &EngineState,
+    stack: &Stack,
+    name: &str,
+    name_span: &Span,
+    args: &[Expression],
+) -> Option<ShellError> {
+    if name.starts_with('^') {
+        return None;
+    }
+
+    // The words a command name can be made of: the name and the bare words right after it
+    let mut words = vec![name];
+    let mut spans = vec![*name_span];
+    for arg in args {
+        match &arg.expr {
+            Expr::String(word) => {
+                words.push(word);
+                spans.push(arg.span);
+            }
+            _ => break,
+        }
+    }
+
+    // The longest command wins, the way it does when the name is spelled right
+    let (known_as, span) = (1..=words.len()).rev().find_map(|len| {
+        let (_, known_as) = engine_state.find_decl_relaxed(words[..len].join(" ").as_bytes())?;
+        Some((known_as, spans[0].merge(spans[len - 1])))
+    })?;
+
+    if is_program(stack, name) {
+        None
+    } else {
+        Some(ShellError::DidYouMean(
+            String::from_utf8_lossy(&known_as).to_string(),
+            span,
+        ))
+    }
+}
+
 pub fn eval_expression(
     engine_state: &EngineState,
     stack: &mut Stack,
@@ -249,11 +316,13 @@ pub fn eval_expression(
         Expr::Operator(_) => Ok(Value::Nothing { span: expr.span }),
         Expr::BinaryOp(lhs, op, rhs) => {
             let op_span = op.span;
+            // The result was written as the whole operation, whatever the operands were from
+            let span = lhs.span.merge(rhs.span);
             let lhs = eval_expression(engine_state, stack, lhs)?;
             let op = eval_operator(op)?;
             let rhs = eval_expression(engine_state, stack, rhs)?;
 
-            match op {
+            let result = match op {
                 Operator::Plus => lhs.add(op_span, &rhs),
                 Operator::Minus => lhs.sub(op_span, &rhs),
                 Operator::Multiply => lhs.mul(op_span, &rhs),
@@ -266,22 +335,31 @@ pub fn eval_expression(
                 Operator::NotEqual => lhs.ne(op_span, &rhs),
                 Operator::In => lhs.r#in(op_span, &rhs),
                 Operator::NotIn => lhs.not_in(op_span, &rhs),
-                Operator::Contains => lhs.contains(op_span, &rhs),
-                Operator::NotContains => lhs.not_contains(op_span, &rhs),
+                Operator::Contains => lhs.regex_match(op_span, &rhs, &engine_state.regex_cache),
+                Operator::NotContains => {
+                    lhs.not_regex_match(op_span, &rhs, &engine_state.regex_cache)
+                }
                 Operator::Modulo => lhs.modulo(op_span, &rhs),
                 Operator::And => lhs.and(op_span, &rhs),
                 Operator::Or => lhs.or(op_span, &rhs),
                 Operator::Pow => lhs.pow(op_span, &rhs),
-            }
+            };
+
+            result.map(|value| value.with_span(span))
         }
         Expr::Subexpression(block_id) => {
             let block = engine_state.get_block(*block_id);
 
             // FIXME: protect this collect with ctrl-c
-            Ok(
+            let value =
                 eval_subexpression(engine_state, stack, block, PipelineData::new(expr.span))?
-                    .into_value(expr.span),
-            )
+                    .into_value(expr.span);
+
+            // A stream that stopped at an error, like the row `each` failed on, fails it here
+            match engine_state.take_abort() {
+                Some(error) => Err(error),
+                None => Ok(value),
+            }
         }
         Expr::Block(block_id) => Ok(Value::Block {
             val: *block_id,
@@ -360,33 +438,98 @@ pub fn eval_block(
 ) -> Result<PipelineData, ShellError> {
     for stmt in block.stmts.iter() {
         if let Statement::Pipeline(pipeline) = stmt {
-            for (i, elem) in pipeline.expressions.iter().enumerate() {
-                match elem {
-                    Expression {
-                        expr: Expr::Call(call),
-                        ..
-                    } => {
-                        input = eval_call(engine_state, stack, call, input)?;
-                    }
-                    Expression {
-                        expr: Expr::ExternalCall(name, name_span, args),
-                        ..
-                    } => {
-                        input = eval_external(
-                            engine_state,
-                            stack,
-                            name,
-                            name_span,
-                            args,
-                            input,
-                            i == pipeline.expressions.len() - 1,
-                        )?;
-                    }
+            input = eval_pipeline(engine_state, stack, pipeline, input)?;
+        }
+    }
 
-                    elem => {
-                        input = eval_expression(engine_state, stack, elem)?.into_pipeline_data();
-                    }
-                }
+    Ok(input)
+}
+
+/// Evaluate a block the shell was given to run, eg a line at the prompt or a script, recording
+/// after each pipeline how long it took and its status, 1 if it failed and 0 otherwise. They
+/// go in `CMD_DURATION_MS` and `LAST_EXIT_CODE` for the prompt and the pipelines after it.
+///
+/// As in `eval_block`, each pipeline's output is the next one's input. The last one's is handed
+/// to `finish`, eg to print it, so its time includes draining it. A `finish` that fails, or a
+/// stream that is aborted while it drains it, fails the pipeline
+pub fn eval_block_recording<T>(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    block: &Block,
+    mut input: PipelineData,
+    finish: impl FnOnce(&mut Stack, PipelineData) -> Result<T, ShellError>,
+) -> Result<T, ShellError> {
+    let pipelines: Vec<&Pipeline> = block
+        .stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Statement::Pipeline(pipeline) => Some(pipeline),
+            Statement::Declaration(_) => None,
+        })
+        .collect();
+
+    let (last, pipelines) = match pipelines.split_last() {
+        Some(split) => split,
+        None => return finish(stack, input),
+    };
+
+    for pipeline in pipelines {
+        let start = Instant::now();
+        let result = eval_pipeline(engine_state, stack, pipeline, input);
+        let exit_code = if result.is_ok() { 0 } else { FAILED_EXIT_CODE };
+        stack.record_pipeline(start.elapsed(), exit_code);
+
+        input = result?;
+    }
+
+    // The output is drained by `finish`, so that's when a stream in it can stop at an error
+    let start = Instant::now();
+    let result = eval_pipeline(engine_state, stack, last, input)
+        .and_then(|output| finish(stack, output))
+        .and_then(|finished| match engine_state.take_abort() {
+            Some(error) => Err(error),
+            None => Ok(finished),
+        });
+    let exit_code = if result.is_ok() { 0 } else { FAILED_EXIT_CODE };
+    stack.record_pipeline(start.elapsed(), exit_code);
+
+    result
+}
+
+/// The status of a pipeline that failed in the shell, rather than in an external command
+const FAILED_EXIT_CODE: i64 = 1;
+
+fn eval_pipeline(
+    engine_state: &EngineState,
+    stack: &mut Stack,
+    pipeline: &Pipeline,
+    mut input: PipelineData,
+) -> Result<PipelineData, ShellError> {
+    for (i, elem) in pipeline.expressions.iter().enumerate() {
+        match elem {
+            Expression {
+                expr: Expr::Call(call),
+                ..
+            } => {
+                input = eval_call(engine_state, stack, call, input)?;
+            }
+            Expression {
+                expr: Expr::ExternalCall(name, name_span, args),
+                ..
+            } => {
+                input = eval_external(
+                    engine_state,
+                    stack,
+                    name,
+                    name_span,
+                    args,
+                    input,
+                    i == pipeline.expressions.len() - 1,
+                )?;
+            }
+
+            elem => {
+                input = eval_expression(engine_state, stack, elem)?.into_pipeline_data();
             }
         }
     }
@@ -466,14 +609,7 @@ pub fn eval_variable(
         let mut output_vals = vec![];
 
         let env_columns: Vec<_> = stack.get_env_vars().keys().map(|x| x.to_string()).collect();
-        let env_values: Vec<_> = stack
-            .get_env_vars()
-            .values()
-            .map(|x| Value::String {
-                val: x.to_string(),
-                span,
-            })
-            .collect();
+        let env_values: Vec<_> = stack.get_env_vars().values().cloned().collect();
 
         output_cols.push("env".into());
         output_vals.push(Value::Record {
@@ -515,75 +651,7 @@ pub fn eval_variable(
             span,
         })
     } else if var_id == nu_protocol::SCOPE_VARIABLE_ID {
-        let mut output_cols = vec![];
-        let mut output_vals = vec![];
-
-        let mut var_names = vec![];
-        let mut var_types = vec![];
-        let mut commands = vec![];
-        let mut aliases = vec![];
-        let mut modules = vec![];
-
-        for frame in &engine_state.scope {
-            for var in &frame.vars {
-                var_names.push(String::from_utf8_lossy(var.0).to_string());
-
-                let var = engine_state.get_var(*var.1);
-                var_types.push(Value::string(var.to_string(), span));
-            }
-
-            for command in &frame.decls {
-                commands.push(Value::String {
-                    val: String::from_utf8_lossy(command.0).to_string(),
-                    span,
-                });
-            }
-
-            for alias in &frame.aliases {
-                aliases.push(Value::String {
-                    val: String::from_utf8_lossy(alias.0).to_string(),
-                    span,
-                });
-            }
-
-            for module in &frame.modules {
-                modules.push(Value::String {
-                    val: String::from_utf8_lossy(module.0).to_string(),
-                    span,
-                });
-            }
-        }
-
-        output_cols.push("vars".to_string());
-        output_vals.push(Value::Record {
-            cols: var_names,
-            vals: var_types,
-            span,
-        });
-
-        output_cols.push("commands".to_string());
-        output_vals.push(Value::List {
-            vals: commands,
-            span,
-        });
-
-        output_cols.push("aliases".to_string());
-        output_vals.push(Value::List {
-            vals: aliases,
-            span,
-        });
-
-        output_cols.push("modules".to_string());
-        output_vals.push(Value::List {
-            vals: modules,
-            span,
-        });
-
-        Ok(Value::Record {
-            cols: output_cols,
-            vals: output_vals,
-            span,
-        })
+        Ok(engine_state.get_scope_snapshot(span))
     } else {
         stack
             .get_var(var_id)
@@ -667,3 +735,299 @@ pub fn compute(size: i64, unit: Unit, span: Span) -> Value {
         },
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_support::{engine_state_with, parse_delta, stack_with_config};
+    use nu_parser::parse;
+    use nu_protocol::engine::{
+        Command, StateDelta, StateWorkingSet, CMD_DURATION_ENV, LAST_EXIT_CODE_ENV,
+    };
+    use nu_protocol::{DeprecationNotice, Signature, SyntaxShape};
+
+    fn engine_state_with_def() -> EngineState {
+        engine_state_with(vec![Signature::build("def")
+            .required("def_name", SyntaxShape::String, "definition name")
+            .required("params", SyntaxShape::Signature, "parameters")
+            .required(
+                "block",
+                SyntaxShape::Block(Some(vec![])),
+                "body of the definition",
+            )
+            .predeclare()])
+    }
+
+    #[test]
+    fn cached_deltas_can_be_merged_and_run() {
+        let mut engine_state = engine_state_with_def();
+        let (_, delta) = parse_delta(&engine_state, b"def double [x] { $x * 2 }");
+
+        let cached = delta.serialize();
+        let delta = StateDelta::deserialize(&cached, &engine_state).expect("a fresh cache");
+        engine_state.merge_delta(delta);
+
+        let (block, delta) = parse_delta(&engine_state, b"double 21");
+        engine_state.merge_delta(delta);
+        let mut stack = stack_with_config();
+
+        let result = eval_block(
+            &engine_state,
+            &mut stack,
+            &block,
+            PipelineData::new(Span::unknown()),
+        )
+        .expect("the cached command runs");
+        assert_eq!(result.into_value(Span::unknown()), Value::test_int(42));
+
+        // The engine state has grown since, so the cache no longer fits
+        assert!(matches!(
+            StateDelta::deserialize(&cached, &engine_state),
+            Err(ShellError::StaleParseCache(_))
+        ));
+    }
+
+    fn eval_recording(
+        engine_state: &mut EngineState,
+        stack: &mut Stack,
+        source: &[u8],
+    ) -> Result<Value, ShellError> {
+        let (block, delta) = parse_delta(engine_state, source);
+        engine_state.merge_delta(delta);
+
+        eval_block_recording(
+            engine_state,
+            stack,
+            &block,
+            PipelineData::new(Span::unknown()),
+            |_, output| Ok(output.into_value(Span::unknown())),
+        )
+    }
+
+    #[test]
+    fn each_pipeline_is_recorded_for_the_next() {
+        let mut engine_state = EngineState::new();
+        let mut stack = Stack::new();
+
+        assert!(eval_recording(&mut engine_state, &mut stack, b"3; 1 / 0").is_err());
+        assert!(matches!(
+            stack.get_env_var(CMD_DURATION_ENV),
+            Some(Value::Int { .. })
+        ));
+
+        let last_exit_code = b"$nu.env.LAST_EXIT_CODE";
+        let result = eval_recording(&mut engine_state, &mut stack, last_exit_code);
+        assert_eq!(result.expect("the status is there"), Value::test_int(1));
+        let result = eval_recording(&mut engine_state, &mut stack, last_exit_code);
+        assert_eq!(result.expect("the status is there"), Value::test_int(0));
+
+        // Pipelines in the same block see the ones before them too
+        let result = eval_recording(&mut engine_state, &mut stack, b"1 / 0");
+        assert!(result.is_err());
+        let result = eval_recording(
+            &mut engine_state,
+            &mut stack,
+            b"$nu.env.LAST_EXIT_CODE; $nu.env.LAST_EXIT_CODE",
+        );
+        assert_eq!(result.expect("the status is there"), Value::test_int(0));
+        assert_eq!(
+            stack.get_env_var(LAST_EXIT_CODE_ENV),
+            Some(Value::test_int(0))
+        );
+    }
+
+    /// Evaluate `source` with the variables set to the values given, expecting a type mismatch.
+    /// Returns the span of the operation's left-hand side
+    fn mismatch_lhs_span(source: &str, variables: Vec<(&str, Value)>) -> Span {
+        let mut engine_state = EngineState::new();
+        let mut stack = Stack::new();
+        let block = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            for (name, value) in variables {
+                let var_id = working_set.add_variable(name.as_bytes().to_vec(), Type::Unknown);
+                stack.add_var(var_id, value);
+            }
+            let (block, err) = parse(&mut working_set, None, source.as_bytes(), false);
+            assert!(err.is_none(), "parse error: {:?}", err);
+            let delta = working_set.render();
+            engine_state.merge_delta(delta);
+            block
+        };
+
+        match eval_block(
+            &engine_state,
+            &mut stack,
+            &block,
+            PipelineData::new(Span::unknown()),
+        ) {
+            Err(ShellError::OperatorMismatch { lhs_span, .. }) => lhs_span,
+            Err(err) => panic!("expected a type mismatch, got {:?}", err),
+            Ok(_) => panic!("expected a type mismatch"),
+        }
+    }
+
+    /// Where `part` is in `source`, which starts the engine state's first file
+    fn span_of(source: &str, part: &str) -> Span {
+        let start = source.find(part).expect("part of the source");
+        Span::new(start, start + part.len())
+    }
+
+    #[test]
+    fn results_of_operations_span_both_operands() {
+        // Values from elsewhere, eg where the variables were set
+        let variables = vec![
+            (
+                "$a",
+                Value::Int {
+                    val: 1,
+                    span: Span::new(200, 201),
+                },
+            ),
+            (
+                "$b",
+                Value::Int {
+                    val: 2,
+                    span: Span::new(100, 101),
+                },
+            ),
+        ];
+        let source = r#"[($a + $b)].0 + "spam""#;
+
+        let span = mismatch_lhs_span(source, variables);
+        assert_eq!(span, span_of(source, "$a + $b"));
+    }
+
+    #[test]
+    fn cells_keep_the_span_they_were_written_at() {
+        let written_at = Span::new(100, 101);
+        let variables = vec![(
+            "$rec",
+            Value::Record {
+                cols: vec!["field".into()],
+                vals: vec![Value::Int {
+                    val: 3,
+                    span: written_at,
+                }],
+                span: Span::new(98, 104),
+            },
+        )];
+
+        let span = mismatch_lhs_span(r#"$rec.field + "spam""#, variables);
+        assert_eq!(span, written_at);
+    }
+
+    /// `new-name` under its old name: passes its input on
+    #[derive(Clone)]
+    struct OldName;
+
+    impl Command for OldName {
+        fn name(&self) -> &str {
+            "old-name"
+        }
+
+        fn signature(&self) -> Signature {
+            Signature::build("old-name")
+        }
+
+        fn usage(&self) -> &str {
+            "Pass the input on."
+        }
+
+        fn deprecated(&self) -> Option<DeprecationNotice> {
+            Some(DeprecationNotice::new("old-name was renamed.").replaced_by("new-name"))
+        }
+
+        fn run(
+            &self,
+            _engine_state: &EngineState,
+            _stack: &mut Stack,
+            _call: &Call,
+            input: PipelineData,
+        ) -> Result<PipelineData, ShellError> {
+            Ok(input)
+        }
+    }
+
+    #[test]
+    fn deprecated_commands_run_and_warn_once() {
+        let mut engine_state = engine_state_with(vec![Box::new(OldName)]);
+        let mut stack = Stack::new();
+
+        for _ in 0..2 {
+            let result = eval_recording(&mut engine_state, &mut stack, b"42 | old-name");
+            assert_eq!(result.expect("the command still runs"), Value::test_int(42));
+        }
+
+        match engine_state.take_warnings().as_slice() {
+            [ShellWarning::DeprecatedCommand { name, notice, .. }] => {
+                assert_eq!(name, "old-name");
+                assert_eq!(notice, "old-name was renamed. Use `new-name` instead.");
+            }
+            other => panic!("expected one deprecation warning, got {:?}", other),
+        }
+
+        let signature = engine_state
+            .get_signatures(false)
+            .into_iter()
+            .find(|signature| signature.name == "old-name")
+            .expect("the signature is listed");
+        assert_eq!(
+            signature.deprecated.and_then(|notice| notice.replacement),
+            Some("new-name".to_string())
+        );
+    }
+
+    #[test]
+    fn compacting_keeps_only_the_live_definitions() {
+        let mut engine_state = engine_state_with_def();
+        for count in 1..=100 {
+            let source = format!(
+                "def helper [] {{ {} }}; def double [x] {{ $x * (helper) }}",
+                count
+            );
+            let (_, delta) = parse_delta(&engine_state, source.as_bytes());
+            engine_state.merge_delta(delta);
+        }
+        assert_eq!(engine_state.num_decls(), 201);
+
+        engine_state.compact();
+
+        // `def`, and the last `helper` and `double`
+        assert_eq!(engine_state.num_decls(), 3);
+        // The bodies of `helper` and `double`, and the subexpression in `double`
+        assert_eq!(engine_state.num_blocks(), 3);
+
+        let mut stack = stack_with_config();
+        let result = eval_recording(&mut engine_state, &mut stack, b"double 21");
+        assert_eq!(
+            result.expect("the command still runs"),
+            Value::test_int(2100)
+        );
+    }
+
+    #[test]
+    fn caches_with_builtins_name_the_builtin() {
+        let engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_decl(Signature::build("spam").predeclare());
+            working_set.render()
+        };
+
+        match StateDelta::deserialize(&delta.serialize(), &engine_state) {
+            Err(ShellError::StaleParseCache(reason)) => {
+                assert!(reason.contains("`spam`"), "the reason was {:?}", reason)
+            }
+            other => panic!("expected the cache to be rejected, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn caches_that_arent_deltas_are_rejected() {
+        assert!(matches!(
+            StateDelta::deserialize(b"not a delta", &EngineState::new()),
+            Err(ShellError::StaleParseCache(_))
+        ));
+    }
+}
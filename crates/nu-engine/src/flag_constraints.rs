@@ -0,0 +1,141 @@
+use nu_protocol::ast::Call;
+use nu_protocol::{FlagConstraint, ShellError, Signature, Span};
+
+/// Check a call against the flag constraints of the signature it calls, so commands don't have to
+/// check which flags go together in `run`
+pub fn check_flag_constraints(signature: &Signature, call: &Call) -> Result<(), ShellError> {
+    for constraint in &signature.flag_constraints {
+        match constraint {
+            FlagConstraint::ConflictsWith(flag, other) => {
+                if let (Some(flag_span), Some(other_span)) =
+                    (call.get_flag_span(flag), call.get_flag_span(other))
+                {
+                    return Err(conflicting_flags(flag, flag_span, other, other_span));
+                }
+            }
+            FlagConstraint::Requires(flag, required) => {
+                if let (Some(span), None) = (call.get_flag_span(flag), call.get_flag_span(required))
+                {
+                    return Err(ShellError::FlagRequiresFlag(
+                        flag.clone(),
+                        required.clone(),
+                        span,
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn conflicting_flags(flag: &str, flag_span: Span, other: &str, other_span: Span) -> ShellError {
+    // Short flags given together, like `-pt`, share their span and can't be labeled apart
+    if flag_span == other_span {
+        return ShellError::IncompatibleParametersSingle(
+            format!("can't use --{} with --{}", flag, other),
+            flag_span,
+        );
+    }
+
+    let ((left, left_span), (right, right_span)) = if flag_span.start < other_span.start {
+        ((flag, flag_span), (other, other_span))
+    } else {
+        ((other, other_span), (flag, flag_span))
+    };
+
+    ShellError::IncompatibleParameters {
+        left_message: format!("can't use --{}", left),
+        left_span,
+        right_message: format!("with --{}", right),
+        right_span,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nu_protocol::Spanned;
+
+    fn signature() -> Signature {
+        Signature::build("rm")
+            .switch("trash", "use the recycle bin", Some('t'))
+            .switch("permanent", "delete permanently", Some('p'))
+            .switch("force", "suppress errors", Some('f'))
+            .switch("allow-protected", "remove protected paths", None)
+            .conflicts_with("trash", "permanent")
+            .requires("allow-protected", "force")
+    }
+
+    fn call_with(flags: &[(&str, Span)]) -> Call {
+        let mut call = Call::new();
+        for (name, span) in flags {
+            call.named.push((
+                Spanned {
+                    item: name.to_string(),
+                    span: *span,
+                },
+                None,
+            ));
+        }
+        call
+    }
+
+    fn span(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    #[test]
+    fn allows_calls_following_the_constraints() {
+        for flags in [
+            vec![],
+            vec![("trash", span(3, 5))],
+            vec![("permanent", span(3, 5)), ("force", span(6, 8))],
+            vec![("allow-protected", span(3, 20)), ("force", span(21, 23))],
+        ] {
+            assert!(check_flag_constraints(&signature(), &call_with(&flags)).is_ok());
+        }
+    }
+
+    #[test]
+    fn labels_both_conflicting_flags() {
+        let call = call_with(&[("permanent", span(3, 5)), ("trash", span(6, 8))]);
+
+        match check_flag_constraints(&signature(), &call) {
+            Err(ShellError::IncompatibleParameters {
+                left_message,
+                left_span,
+                right_message,
+                right_span,
+            }) => {
+                assert_eq!(left_message, "can't use --permanent");
+                assert_eq!(left_span, span(3, 5));
+                assert_eq!(right_message, "with --trash");
+                assert_eq!(right_span, span(6, 8));
+            }
+            other => panic!("expected incompatible parameters, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn conflicting_short_flags_given_together() {
+        let call = call_with(&[("trash", span(3, 6)), ("permanent", span(3, 6))]);
+
+        assert!(matches!(
+            check_flag_constraints(&signature(), &call),
+            Err(ShellError::IncompatibleParametersSingle(message, _))
+                if message == "can't use --trash with --permanent"
+        ));
+    }
+
+    #[test]
+    fn refuses_a_flag_without_the_one_it_requires() {
+        let call = call_with(&[("allow-protected", span(3, 20))]);
+
+        assert!(matches!(
+            check_flag_constraints(&signature(), &call),
+            Err(ShellError::FlagRequiresFlag(flag, required, s))
+                if flag == "allow-protected" && required == "force" && s == span(3, 20)
+        ));
+    }
+}
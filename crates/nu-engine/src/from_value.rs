@@ -126,11 +126,26 @@ impl FromValue for Spanned<String> {
     }
 }
 
+impl FromValue for Vec<String> {
+    fn from_value(v: &Value) -> Result<Self, ShellError> {
+        // FIXME: we may want to fail a little nicer here
+        match v {
+            Value::List { vals, .. } => vals.iter().map(String::from_value).collect(),
+            v => Err(ShellError::CantConvert(
+                "list of strings".into(),
+                v.get_type().to_string(),
+                v.span()?,
+            )),
+        }
+    }
+}
+
 impl FromValue for CellPath {
     fn from_value(v: &Value) -> Result<Self, ShellError> {
         let span = v.span()?;
         match v {
             Value::CellPath { val, .. } => Ok(val.clone()),
+            // A string names a single column, even if it has dots or spaces in it
             Value::String { val, .. } => Ok(CellPath {
                 members: vec![PathMember::String {
                     val: val.clone(),
@@ -1,9 +1,24 @@
+mod block_stream;
 mod call_ext;
+mod confirm;
 mod documentation;
+mod env;
 mod eval;
+mod flag_constraints;
 mod from_value;
+mod print;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 
+pub use block_stream::{eval_block_as_stream, BlockStream};
 pub use call_ext::CallExt;
-pub use documentation::{generate_docs, get_brief_help, get_documentation, get_full_help};
-pub use eval::{eval_block, eval_expression, eval_operator};
+pub use confirm::TerminalPrompt;
+pub use documentation::{
+    generate_docs, get_brief_help, get_documentation, get_full_help, get_markdown_documentation,
+    get_subcommands, markdown_doc_filename, write_markdown_docs,
+};
+pub use env::{convert_env_values, current_dir, env_vars_as_strings};
+pub use eval::{eval_block, eval_block_recording, eval_expression, eval_operator};
+pub use flag_constraints::check_flag_constraints;
 pub use from_value::FromValue;
+pub use print::print_value;
@@ -0,0 +1,122 @@
+use nu_protocol::ast::Call;
+use nu_protocol::engine::{EngineState, Stack};
+use nu_protocol::{IntoPipelineData, ShellError, Value};
+
+/// Show a value the way the REPL does, written to the engine state's output. If the `table`
+/// command is in the declarations, it renders the value. With `use_pager` set in the config, the
+/// `page` command does instead, so long results can be scrolled through
+pub fn print_value(
+    value: Value,
+    engine_state: &EngineState,
+    stack: &mut Stack,
+) -> Result<(), ShellError> {
+    let config = stack.get_config()?;
+
+    let viewer = if config.use_pager {
+        engine_state.find_decl(b"page")
+    } else {
+        None
+    };
+
+    let output = match viewer.or_else(|| engine_state.find_decl(b"table")) {
+        Some(decl_id) => {
+            let table = engine_state.get_decl(decl_id).run(
+                engine_state,
+                stack,
+                &Call::new(),
+                value.into_pipeline_data(),
+            )?;
+            table.collect_string("\n", &config)
+        }
+        None => value.into_string(", ", &config),
+    };
+
+    if let Err(err) = engine_state.output.write_out(output.as_bytes()) {
+        let _ = engine_state
+            .output
+            .write_err(format!("{}\n", err).as_bytes());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_support::{add_decls, stack_with_config};
+    use nu_protocol::engine::{BufferedOutput, Command};
+    use nu_protocol::{PipelineData, Signature, Span};
+    use std::sync::Arc;
+
+    /// Renders lists as one `| item |` line per item
+    #[derive(Clone)]
+    struct TestTable;
+
+    impl Command for TestTable {
+        fn name(&self) -> &str {
+            "table"
+        }
+
+        fn signature(&self) -> Signature {
+            Signature::build("table")
+        }
+
+        fn usage(&self) -> &str {
+            "Render a test table."
+        }
+
+        fn run(
+            &self,
+            _engine_state: &EngineState,
+            _stack: &mut Stack,
+            _call: &Call,
+            input: PipelineData,
+        ) -> Result<PipelineData, ShellError> {
+            let config = nu_protocol::Config::default();
+            let rows: String = input
+                .into_iter()
+                .map(|item| format!("| {} |\n", item.into_string(", ", &config)))
+                .collect();
+
+            Ok(Value::test_string(rows).into_pipeline_data())
+        }
+    }
+
+    fn buffered_state(with_table: bool) -> (EngineState, Arc<BufferedOutput>) {
+        let mut engine_state = EngineState::new();
+        let output = Arc::new(BufferedOutput::new());
+        engine_state.output = output.clone();
+
+        if with_table {
+            add_decls(&mut engine_state, vec![Box::new(TestTable)]);
+        }
+
+        (engine_state, output)
+    }
+
+    fn list() -> Value {
+        Value::List {
+            vals: vec![Value::test_int(1), Value::test_string("two")],
+            span: Span::unknown(),
+        }
+    }
+
+    #[test]
+    fn renders_values_with_the_table_command() {
+        let (engine_state, output) = buffered_state(true);
+
+        print_value(list(), &engine_state, &mut stack_with_config()).expect("printing should work");
+
+        assert_eq!(output.take_out(), b"| 1 |\n| two |\n");
+        assert!(output.take_err().is_empty());
+    }
+
+    #[test]
+    fn prints_values_as_strings_without_a_table_command() {
+        let (engine_state, output) = buffered_state(false);
+
+        print_value(list(), &engine_state, &mut stack_with_config()).expect("printing should work");
+
+        assert_eq!(output.take_out(), b"[1, two]");
+    }
+}
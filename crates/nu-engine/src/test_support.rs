@@ -0,0 +1,34 @@
+//! Helpers for tests that parse and run source. Includes the helpers of nu-protocol, so tests
+//! only need this one module
+
+use nu_parser::parse;
+use nu_protocol::ast::Block;
+use nu_protocol::engine::{EngineState, Stack, StateDelta, StateWorkingSet};
+use nu_protocol::{PipelineData, ShellError};
+
+pub use nu_protocol::test_support::*;
+
+use crate::eval_block;
+
+/// Parse `source`, failing the test if it doesn't parse. Gives back the block, and the delta to
+/// merge before running it
+pub fn parse_delta(engine_state: &EngineState, source: &[u8]) -> (Block, StateDelta) {
+    let mut working_set = StateWorkingSet::new(engine_state);
+    let (block, err) = parse(&mut working_set, None, source, false);
+    assert!(err.is_none(), "parse error: {:?}", err);
+
+    (block, working_set.render())
+}
+
+/// Parse `source`, merge what it defines into `engine_state`, and run it on `input`
+pub fn eval_source(
+    engine_state: &mut EngineState,
+    stack: &mut Stack,
+    source: &str,
+    input: PipelineData,
+) -> Result<PipelineData, ShellError> {
+    let (block, delta) = parse_delta(engine_state, source.as_bytes());
+    engine_state.merge_delta(delta);
+
+    eval_block(engine_state, stack, &block, input)
+}
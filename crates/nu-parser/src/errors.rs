@@ -1,5 +1,5 @@
 use miette::Diagnostic;
-use nu_protocol::{Span, Type};
+use nu_protocol::{ShellError, Span, Type};
 use thiserror::Error;
 
 #[derive(Clone, Debug, Error, Diagnostic)]
@@ -107,7 +107,15 @@ pub enum ParseError {
 
     #[error("Missing flag param.")]
     #[diagnostic(code(nu::parser::missing_flag_param), url(docsrs))]
-    MissingFlagParam(#[label = "flag missing param"] Span),
+    MissingFlagParam(String, #[label("flag missing {0} param")] Span),
+
+    #[error("The `{0}` flag doesn't take a value.")]
+    #[diagnostic(
+        code(nu::parser::switch_with_value),
+        url(docsrs),
+        help("`--{0}` is a switch, it's either given or left out")
+    )]
+    SwitchWithValue(String, #[label = "unexpected value"] Span),
 
     #[error("Batches of short flags can't take arguments.")]
     #[diagnostic(code(nu::parser::short_flag_arg_cant_take_arg), url(docsrs))]
@@ -186,4 +194,37 @@ pub enum ParseError {
     #[error("Plugin error")]
     #[diagnostic(code(nu::parser::export_not_found), url(docsrs))]
     PluginError(String),
+
+    #[error("Alias has more than one placeholder.")]
+    #[diagnostic(
+        code(nu::parser::multiple_alias_placeholders),
+        url(docsrs),
+        help("the arguments of a call to the alias go in one place: keep a single {{}}")
+    )]
+    MultipleAliasPlaceholders(#[label = "second placeholder"] Span),
+
+    /// More than one statement of a block failed to parse. Each one's error is shown, in the order
+    /// of the statements
+    #[error("{} statements failed to parse.", .errors.len())]
+    #[diagnostic(code(nu::parser::statement_errors), url(docsrs))]
+    Statements {
+        #[related]
+        errors: Vec<ParseError>,
+    },
+
+    /// Errors reported by the engine state while parsing, eg when a name can't be hidden
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ShellError(#[from] ShellError),
+}
+
+impl ParseError {
+    /// The errors of the statements of a block as one, if any of them failed
+    pub fn from_statements(mut errors: Vec<ParseError>) -> Option<ParseError> {
+        match errors.len() {
+            0 => None,
+            1 => errors.pop(),
+            _ => Some(ParseError::Statements { errors }),
+        }
+    }
 }
@@ -279,7 +279,7 @@ pub fn lex(
                 if *input == b'\n' || *input == b'\r' {
                     output.push(Token::new(
                         TokenContents::Comment,
-                        Span::new(start, curr_offset - 1),
+                        Span::new(span_offset + start, span_offset + curr_offset - 1),
                     ));
 
                     // Adding an end of line token after a comment
@@ -287,7 +287,7 @@ pub fn lex(
                     // in a statement
                     output.push(Token::new(
                         TokenContents::Eol,
-                        Span::new(curr_offset - 1, curr_offset),
+                        Span::new(span_offset + curr_offset - 1, span_offset + curr_offset),
                     ));
                     start = curr_offset;
 
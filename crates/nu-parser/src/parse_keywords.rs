@@ -1,7 +1,7 @@
 use nu_protocol::{
     ast::{Block, Call, Expr, Expression, ImportPattern, ImportPatternMember, Pipeline, Statement},
-    engine::StateWorkingSet,
-    span, DeclId, Span, SyntaxShape, Type, CONFIG_VARIABLE_ID,
+    engine::{Alias, FileSource, Overlay, StateWorkingSet},
+    span, DeclId, Span, SyntaxShape, Type, Value, CONFIG_VARIABLE_ID,
 };
 use std::path::Path;
 
@@ -17,8 +17,15 @@ use crate::{
     ParseError,
 };
 
-pub fn parse_def_predecl(working_set: &mut StateWorkingSet, spans: &[Span]) -> Option<ParseError> {
+/// Declare a `def` before its body is parsed, so calls to it can be parsed anywhere in the block.
+/// The comments attached to the definition become its usage
+pub fn parse_def_predecl(
+    working_set: &mut StateWorkingSet,
+    spans: &[Span],
+    comments: &[Span],
+) -> Option<ParseError> {
     let name = working_set.get_span_contents(spans[0]);
+    let def_span = span(spans);
 
     // handle "export def" same as "def"
     let (name, spans) = if name == b"export" && spans.len() >= 2 {
@@ -44,9 +51,10 @@ pub fn parse_def_predecl(working_set: &mut StateWorkingSet, spans: &[Span]) -> O
 
         if let (Some(name), Some(mut signature)) = (name, signature) {
             signature.name = name;
+            signature.usage = usage_from_comments(working_set, comments);
             let decl = signature.predeclare();
 
-            if working_set.add_predecl(decl).is_some() {
+            if working_set.add_predecl(decl, def_span).is_some() {
                 return Some(ParseError::DuplicateCommandDef(spans[1]));
             }
         }
@@ -55,6 +63,18 @@ pub fn parse_def_predecl(working_set: &mut StateWorkingSet, spans: &[Span]) -> O
     None
 }
 
+/// The text of `# ...` comments, one line each
+fn usage_from_comments(working_set: &StateWorkingSet, comments: &[Span]) -> String {
+    comments
+        .iter()
+        .map(|comment| {
+            let contents = String::from_utf8_lossy(working_set.get_span_contents(*comment));
+            contents.trim_start_matches('#').trim().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 pub fn parse_def(
     working_set: &mut StateWorkingSet,
     spans: &[Span],
@@ -109,6 +129,8 @@ pub fn parse_def(
                             let declaration = working_set.get_decl_mut(decl_id);
 
                             signature.name = name.clone();
+                            // Keep the usage the predeclaration found in the comments
+                            signature.usage = declaration.usage().to_string();
 
                             *declaration = signature.into_block_command(block_id);
                         } else {
@@ -219,9 +241,27 @@ pub fn parse_alias(
                 };
                 let _equals = working_set.get_span_contents(spans[2]);
 
-                let replacement = spans[3..].to_vec();
+                let mut replacement = vec![];
+                let mut placeholder = None;
+                for word in &spans[3..] {
+                    if working_set.get_span_contents(*word) == b"{}" {
+                        if placeholder.is_some() {
+                            return (
+                                Statement::Pipeline(Pipeline::from_vec(vec![garbage(span(spans))])),
+                                Some(ParseError::MultipleAliasPlaceholders(*word)),
+                            );
+                        }
+                        placeholder = Some(replacement.len());
+                    } else {
+                        replacement.push(*word);
+                    }
+                }
 
-                working_set.add_alias(alias_name, replacement);
+                let alias = match placeholder {
+                    Some(placeholder) => Alias::with_placeholder(replacement, placeholder),
+                    None => Alias::new(replacement),
+                };
+                working_set.add_alias(alias_name, alias);
             }
 
             return (
@@ -262,38 +302,23 @@ pub fn parse_export(
                     .find_decl(b"export def")
                     .expect("internal error: missing 'export def' command");
 
-                // Trying to warp the 'def' call into the 'export def' in a very clumsy way
-                let stmt = if let Statement::Pipeline(ref pipe) = stmt {
-                    if !pipe.expressions.is_empty() {
-                        if let Expr::Call(ref call) = pipe.expressions[0].expr {
-                            let mut call = call.clone();
-
-                            call.head = span(&spans[0..=1]);
-                            call.decl_id = export_def_decl_id;
+                (into_export(stmt, export_def_decl_id, spans), err)
+            }
+            b"alias" => {
+                let (stmt, err) = parse_alias(working_set, &spans[1..]);
 
-                            Statement::Pipeline(Pipeline::from_vec(vec![Expression {
-                                expr: Expr::Call(call),
-                                span: span(spans),
-                                ty: Type::Unknown,
-                                custom_completion: None,
-                            }]))
-                        } else {
-                            stmt
-                        }
-                    } else {
-                        stmt
-                    }
-                } else {
-                    stmt
-                };
+                let export_alias_decl_id = working_set
+                    .find_decl(b"export alias")
+                    .expect("internal error: missing 'export alias' command");
 
-                (stmt, err)
+                (into_export(stmt, export_alias_decl_id, spans), err)
             }
+            b"env" => parse_export_env(working_set, spans),
             _ => (
                 garbage_statement(spans),
                 Some(ParseError::Expected(
                     // TODO: Fill in more as they come
-                    "def keyword".into(),
+                    "def, alias or env keyword".into(),
                     spans[1],
                 )),
             ),
@@ -303,13 +328,84 @@ pub fn parse_export(
             garbage_statement(spans),
             Some(ParseError::UnknownState(
                 // TODO: fill in more export types as they come
-                "Expected structure: export def [] {}".into(),
+                "Expected structure: export def [] {}, export alias <name> = <value> or export env <name> = <value>".into(),
                 span(spans),
             )),
         )
     }
 }
 
+/// Turn the call of a `def` or `alias` into a call of its `export` version, eg `export def`
+fn into_export(stmt: Statement, export_decl_id: DeclId, spans: &[Span]) -> Statement {
+    if let Statement::Pipeline(ref pipe) = stmt {
+        if let Some(Expression {
+            expr: Expr::Call(call),
+            ..
+        }) = pipe.expressions.first()
+        {
+            let mut call = call.clone();
+
+            call.head = span(&spans[0..=1]);
+            call.decl_id = export_decl_id;
+
+            return Statement::Pipeline(Pipeline::from_vec(vec![Expression {
+                expr: Expr::Call(call),
+                span: span(spans),
+                ty: Type::Unknown,
+                custom_completion: None,
+            }]));
+        }
+    }
+
+    stmt
+}
+
+/// `export env <name> = <value>`. The variable is added to the current scope, where the module
+/// that exports it finds it
+fn parse_export_env(
+    working_set: &mut StateWorkingSet,
+    spans: &[Span],
+) -> (Statement, Option<ParseError>) {
+    let export_env_decl_id = working_set
+        .find_decl(b"export env")
+        .expect("internal error: missing 'export env' command");
+
+    let (call, call_span, err) = parse_internal_call(
+        working_set,
+        span(&spans[0..=1]),
+        &spans[2..],
+        export_env_decl_id,
+    );
+
+    if err.is_none() {
+        let name = call.positional.get(0).and_then(|name| name.as_string());
+        let value = match call.positional.get(1) {
+            Some(Expression {
+                expr: Expr::Keyword(_, _, value),
+                ..
+            }) => value.as_string().map(|val| Value::String {
+                val,
+                span: value.span,
+            }),
+            _ => None,
+        };
+
+        if let (Some(name), Some(value)) = (name, value) {
+            working_set.add_env_var(name.into_bytes(), value);
+        }
+    }
+
+    (
+        Statement::Pipeline(Pipeline::from_vec(vec![Expression {
+            expr: Expr::Call(call),
+            span: call_span,
+            ty: Type::Unknown,
+            custom_completion: None,
+        }])),
+        err,
+    )
+}
+
 pub fn parse_module_block(
     working_set: &mut StateWorkingSet,
     span: Span,
@@ -328,11 +424,15 @@ pub fn parse_module_block(
 
     for pipeline in &output.block {
         if pipeline.commands.len() == 1 {
-            parse_def_predecl(working_set, &pipeline.commands[0].parts);
+            parse_def_predecl(
+                working_set,
+                &pipeline.commands[0].parts,
+                &pipeline.commands[0].comments,
+            );
         }
     }
 
-    let mut exports: Vec<(Vec<u8>, DeclId)> = vec![];
+    let mut exports = Overlay::new();
 
     let block: Block = output
         .block
@@ -353,17 +453,30 @@ pub fn parse_module_block(
                         let (stmt, err) = parse_export(working_set, &pipeline.commands[0].parts);
 
                         if err.is_none() {
-                            let decl_name =
-                                // parts[2] is safe since it's checked in parse_export already
-                                working_set.get_span_contents(pipeline.commands[0].parts[2]);
-
-                            let decl_name = trim_quotes(decl_name);
-
-                            let decl_id = working_set
-                                .find_decl(decl_name)
-                                .expect("internal error: failed to find added declaration");
+                            let parts = &pipeline.commands[0].parts;
+                            // parts[2] is safe since it's checked in parse_export already
+                            let name =
+                                trim_quotes(working_set.get_span_contents(parts[2])).to_vec();
+
+                            match working_set.get_span_contents(parts[1]) {
+                                b"alias" => {
+                                    if let Some(alias) = working_set.find_alias(&name).cloned() {
+                                        exports.aliases.push((name, alias));
+                                    }
+                                }
+                                b"env" => {
+                                    if let Some(value) = working_set.find_env_var(&name).cloned() {
+                                        exports.env_vars.push((name, value));
+                                    }
+                                }
+                                _ => {
+                                    let decl_id = working_set
+                                        .find_decl(&name)
+                                        .expect("internal error: failed to find added declaration");
 
-                            exports.push((decl_name.into(), decl_id));
+                                    exports.decls.push((name, decl_id));
+                                }
+                            }
                         }
 
                         (stmt, err)
@@ -500,74 +613,69 @@ pub fn parse_use(
         let (import_pattern, err) = parse_import_pattern(working_set, &spans[1..]);
         error = error.or(err);
 
-        let (import_pattern, exports) =
-            if let Some(block_id) = working_set.find_module(&import_pattern.head) {
-                (
-                    import_pattern,
-                    working_set.get_block(block_id).exports.clone(),
-                )
-            } else {
-                // TODO: Do not close over when loading module from file
-                // It could be a file
-                if let Ok(module_filename) = String::from_utf8(import_pattern.head) {
-                    let module_path = Path::new(&module_filename);
-                    let module_name = if let Some(stem) = module_path.file_stem() {
-                        stem.to_string_lossy().to_string()
-                    } else {
-                        return (
-                            garbage_statement(spans),
-                            Some(ParseError::ModuleNotFound(spans[1])),
-                        );
-                    };
+        // A hidden module comes back into scope when it's used again
+        let block_id = working_set
+            .find_module(&import_pattern.head)
+            .or_else(|| working_set.unhide_module(&import_pattern.head));
 
-                    if let Ok(contents) = std::fs::read(module_path) {
-                        let span_start = working_set.next_span_start();
-                        working_set.add_file(module_filename, &contents);
-                        let span_end = working_set.next_span_start();
+        let (import_pattern, exports) = if let Some(block_id) = block_id {
+            (
+                import_pattern,
+                working_set.get_block(block_id).exports.clone(),
+            )
+        } else {
+            // TODO: Do not close over when loading module from file
+            // It could be a file
+            if let Ok(module_filename) = String::from_utf8(import_pattern.head) {
+                let module_path = Path::new(&module_filename);
+                let module_name = if let Some(stem) = module_path.file_stem() {
+                    stem.to_string_lossy().to_string()
+                } else {
+                    return (
+                        garbage_statement(spans),
+                        Some(ParseError::ModuleNotFound(spans[1])),
+                    );
+                };
 
-                        let (block, err) =
-                            parse_module_block(working_set, Span::new(span_start, span_end));
-                        error = error.or(err);
+                if let Ok(contents) = std::fs::read(module_path) {
+                    let source = FileSource::Script(module_path.to_path_buf());
+                    let file_id =
+                        working_set.add_file_with_source(module_filename, &contents, source);
+                    let span = working_set.get_span_for_file(file_id);
 
-                        let block_id = working_set.add_module(&module_name, block);
+                    let (block, err) = parse_module_block(working_set, span);
+                    error = error.or(err);
 
-                        (
-                            ImportPattern {
-                                head: module_name.into(),
-                                members: import_pattern.members,
-                            },
-                            working_set.get_block(block_id).exports.clone(),
-                        )
-                    } else {
-                        return (
-                            garbage_statement(spans),
-                            Some(ParseError::ModuleNotFound(spans[1])),
-                        );
-                    }
+                    let block_id = working_set.add_module(&module_name, block);
+
+                    (
+                        ImportPattern {
+                            head: module_name.into(),
+                            members: import_pattern.members,
+                        },
+                        working_set.get_block(block_id).exports.clone(),
+                    )
                 } else {
                     return (
                         garbage_statement(spans),
-                        Some(ParseError::NonUtf8(spans[1])),
+                        Some(ParseError::ModuleNotFound(spans[1])),
                     );
                 }
-            };
+            } else {
+                return (
+                    garbage_statement(spans),
+                    Some(ParseError::NonUtf8(spans[1])),
+                );
+            }
+        };
 
         let exports = if import_pattern.members.is_empty() {
-            exports
-                .into_iter()
-                .map(|(name, id)| {
-                    let mut new_name = import_pattern.head.to_vec();
-                    new_name.push(b' ');
-                    new_name.extend(&name);
-                    (new_name, id)
-                })
-                .collect()
+            exports.with_prefix(&import_pattern.head)
         } else {
             match &import_pattern.members[0] {
                 ImportPatternMember::Glob { .. } => exports,
                 ImportPatternMember::Name { name, span } => {
-                    let new_exports: Vec<(Vec<u8>, usize)> =
-                        exports.into_iter().filter(|x| &x.0 == name).collect();
+                    let new_exports = exports.only(name);
 
                     if new_exports.is_empty() {
                         error = error.or(Some(ParseError::ExportNotFound(*span)))
@@ -576,18 +684,15 @@ pub fn parse_use(
                     new_exports
                 }
                 ImportPatternMember::List { names } => {
-                    let mut output = vec![];
+                    let mut output = Overlay::new();
 
                     for (name, span) in names {
-                        let mut new_exports: Vec<(Vec<u8>, usize)> = exports
-                            .iter()
-                            .filter_map(|x| if &x.0 == name { Some(x.clone()) } else { None })
-                            .collect();
+                        let new_exports = exports.only(name);
 
                         if new_exports.is_empty() {
                             error = error.or(Some(ParseError::ExportNotFound(*span)))
                         } else {
-                            output.append(&mut new_exports)
+                            output.extend(new_exports)
                         }
                     }
 
@@ -652,8 +757,8 @@ pub fn parse_hide(
                     working_set
                         .get_block(block_id)
                         .exports
-                        .iter()
-                        .map(|(name, _)| name.clone())
+                        .callable_names()
+                        .cloned()
                         .collect(),
                 )
             } else if import_pattern.members.is_empty() {
@@ -737,14 +842,43 @@ pub fn parse_hide(
             }
         };
 
+        let hides_whole_module = is_module && import_pattern.members.is_empty();
+        let mut hidden_any = false;
+        let mut missing = false;
+
         for name in names_to_hide {
+            // Aliases are expanded before commands are looked up, so they're the ones to hide first
+            if working_set.hide_alias(&name) {
+                hidden_any = true;
+                continue;
+            }
+
             // TODO: `use spam; use spam foo; hide foo` will hide both `foo` and `spam foo` since
             // they point to the same DeclId. Do we want to keep it that way?
-            if working_set.hide_decl(&name).is_none() {
-                error = error.or_else(|| Some(ParseError::UnknownCommand(spans[1])));
+            match working_set.resolve_and_hide_decl(&name, spans[1]) {
+                Ok(Some(_)) => hidden_any = true,
+                Ok(None) => missing = true,
+                Err(err) => error = error.or_else(|| Some(err.into())),
             }
         }
 
+        if hides_whole_module && !hidden_any {
+            // Nothing the module exports is imported under its name, so it's the module itself
+            // (or a command with the same name) that is being hidden
+            missing = false;
+            match working_set.resolve_and_hide_decl(&import_pattern.head, spans[1]) {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    working_set.hide_module(&import_pattern.head);
+                }
+                Err(err) => error = error.or_else(|| Some(err.into())),
+            }
+        }
+
+        if missing {
+            error = error.or_else(|| Some(ParseError::UnknownCommand(spans[1])));
+        }
+
         // Create the Hide command call
         let hide_decl_id = working_set
             .find_decl(b"hide")
@@ -880,7 +1014,7 @@ pub fn parse_source(
                             // Adding this expression to the positional creates a syntax highlighting error
                             // after writing `source example.nu`
                             call_with_block.positional.push(Expression {
-                                expr: Expr::Int(block_id as i64),
+                                expr: Expr::Int(block_id.get() as i64),
                                 span: spans[1],
                                 ty: Type::Unknown,
                                 custom_completion: None,
@@ -10,9 +10,9 @@ use nu_protocol::{
         Block, Call, CellPath, Expr, Expression, FullCellPath, ImportPattern, ImportPatternMember,
         Operator, PathMember, Pipeline, RangeInclusion, RangeOperator, Statement,
     },
-    engine::StateWorkingSet,
-    span, Flag, PositionalArg, Signature, Span, Spanned, SyntaxShape, Type, Unit, VarId,
-    CONFIG_VARIABLE_ID,
+    engine::{FileSource, StateWorkingSet},
+    span, DeclId, Flag, PositionalArg, ShellWarning, Signature, Span, Spanned, SyntaxShape, Type,
+    Unit, VarId, CONFIG_VARIABLE_ID,
 };
 
 use crate::parse_keywords::{
@@ -79,6 +79,44 @@ pub fn trim_quotes(bytes: &[u8]) -> &[u8] {
     }
 }
 
+/// Warn about each `$name` in a double-quoted string. Unlike in other shells, they're passed as
+/// they are, so they're more likely a mistake than meant. A `\$` is taken to be on purpose, eg in
+/// a regex, and single-quoted strings are left alone
+fn warn_about_uninterpolated_variables(working_set: &mut StateWorkingSet, span: Span) {
+    let bytes = working_set.get_span_contents(span);
+    if !(bytes.starts_with(b"\"") && bytes.ends_with(b"\"") && bytes.len() > 1) {
+        return;
+    }
+    let inner = &bytes[..bytes.len() - 1];
+
+    let mut names = vec![];
+    let mut idx = 1;
+    while idx < inner.len() {
+        if inner[idx] == b'$' {
+            let name = &inner[idx + 1..];
+            let len = name
+                .iter()
+                .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+                .count();
+
+            if len > 0 && !name[0].is_ascii_digit() {
+                names.push(Span {
+                    start: span.start + idx,
+                    end: span.start + idx + 1 + len,
+                });
+            }
+            idx += len + 1;
+        } else {
+            idx += 1;
+        }
+    }
+
+    for span in names {
+        let name = String::from_utf8_lossy(working_set.get_span_contents(span)).to_string();
+        working_set.warn(ShellWarning::UninterpolatedVariable { name, span });
+    }
+}
+
 fn check_call(command: Span, sig: &Signature, call: &Call) -> Option<ParseError> {
     // Allow the call to pass if they pass in the help flag
     if call.named.iter().any(|(n, _)| n.item == "help") {
@@ -153,8 +191,10 @@ pub fn parse_external_call(
             error = error.or(err);
             args.push(arg);
         } else {
+            let contents = String::from_utf8_lossy(contents).to_string();
+            warn_about_uninterpolated_variables(working_set, *span);
             args.push(Expression {
-                expr: Expr::String(String::from_utf8_lossy(contents).to_string()),
+                expr: Expr::String(contents),
                 span: *span,
                 ty: Type::String,
                 custom_completion: None,
@@ -192,11 +232,14 @@ fn parse_long_flag(
                     if split.len() > 1 {
                         // and we also have the argument
                         let mut span = arg_span;
-                        span.start += long_name.len() + 1; //offset by long flag and '='
+                        span.start += long_name.len() + 3; //offset by '--', long flag and '='
                         let (arg, err) = parse_value(working_set, span, arg_shape);
 
                         (Some(long_name), Some(arg), err)
-                    } else if let Some(arg) = spans.get(*spans_idx + 1) {
+                    } else if let Some(arg) = spans
+                        .get(*spans_idx + 1)
+                        .filter(|arg| !is_flag(working_set, **arg, sig))
+                    {
                         let (arg, err) = parse_value(working_set, *arg, arg_shape);
 
                         *spans_idx += 1;
@@ -205,9 +248,22 @@ fn parse_long_flag(
                         (
                             Some(long_name),
                             None,
-                            Some(ParseError::MissingFlagParam(arg_span)),
+                            Some(ParseError::MissingFlagParam(
+                                arg_shape.to_type().to_string(),
+                                arg_span,
+                            )),
                         )
                     }
+                } else if split.len() > 1 {
+                    // A switch given a value, like --bar=5
+                    let mut span = arg_span;
+                    span.start += long_name.len() + 2; //offset by '--' and long flag
+
+                    (
+                        Some(long_name.clone()),
+                        None,
+                        Some(ParseError::SwitchWithValue(long_name, span)),
+                    )
                 } else {
                     // A flag with no argument
                     (Some(long_name), None, None)
@@ -231,6 +287,23 @@ fn parse_long_flag(
     }
 }
 
+/// Whether the span is a flag of the signature rather than a value, so that a flag missing its
+/// value doesn't take the next flag as the value
+fn is_flag(working_set: &StateWorkingSet, span: Span, sig: &Signature) -> bool {
+    let contents = working_set.get_span_contents(span);
+
+    if contents.starts_with(b"--") {
+        contents.len() > 2
+    } else if let Some(short_flags) = contents.strip_prefix(b"-") {
+        !short_flags.is_empty()
+            && String::from_utf8_lossy(short_flags)
+                .chars()
+                .all(|short| sig.get_short_flag(short).is_some())
+    } else {
+        false
+    }
+}
+
 fn parse_short_flags(
     working_set: &mut StateWorkingSet,
     spans: &[Span],
@@ -483,7 +556,7 @@ pub fn parse_internal_call(
     working_set: &mut StateWorkingSet,
     command_span: Span,
     spans: &[Span],
-    decl_id: usize,
+    decl_id: DeclId,
 ) -> (Box<Call>, Span, Option<ParseError>) {
     let mut error = None;
 
@@ -536,7 +609,10 @@ pub fn parse_internal_call(
             error = error.or(err);
             for flag in short_flags {
                 if let Some(arg_shape) = flag.arg {
-                    if let Some(arg) = spans.get(spans_idx + 1) {
+                    if let Some(arg) = spans
+                        .get(spans_idx + 1)
+                        .filter(|arg| !is_flag(working_set, **arg, &signature))
+                    {
                         let (arg, err) = parse_value(working_set, *arg, &arg_shape);
                         error = error.or(err);
 
@@ -549,7 +625,10 @@ pub fn parse_internal_call(
                         ));
                         spans_idx += 1;
                     } else {
-                        error = error.or(Some(ParseError::MissingFlagParam(arg_span)))
+                        error = error.or(Some(ParseError::MissingFlagParam(
+                            arg_shape.to_type().to_string(),
+                            arg_span,
+                        )))
                     }
                 } else {
                     call.named.push((
@@ -618,6 +697,16 @@ pub fn parse_internal_call(
     (Box::new(call), span(spans), error)
 }
 
+/// The name of the command the words make up. The words of a command name are separated by one
+/// space, however far apart they're written
+fn command_name(working_set: &StateWorkingSet, words: &[Span]) -> Vec<u8> {
+    words
+        .iter()
+        .map(|word| working_set.get_span_contents(*word))
+        .collect::<Vec<_>>()
+        .join(&b' ')
+}
+
 pub fn parse_call(
     working_set: &mut StateWorkingSet,
     spans: &[Span],
@@ -647,18 +736,16 @@ pub fn parse_call(
 
         name_spans.push(*word_span);
 
-        let name = working_set.get_span_contents(span(&name_spans));
+        let name = command_name(working_set, &name_spans);
 
         if expand_aliases {
             // If the word is an alias, expand it and re-parse the expression
-            if let Some(expansion) = working_set.find_alias(name) {
-                let orig_span = spans[pos];
+            if let Some(expansion) = working_set.find_alias(&name) {
+                // The name can be several words, eg `spam ll` for an alias imported from a module
+                let orig_span = span(&name_spans);
                 let mut new_spans: Vec<Span> = vec![];
-                new_spans.extend(&spans[0..pos]);
-                new_spans.extend(expansion);
-                if spans.len() > pos {
-                    new_spans.extend(&spans[(pos + 1)..]);
-                }
+                new_spans.extend(&spans[0..cmd_start]);
+                new_spans.extend(expansion.expand(&spans[(pos + 1)..]));
 
                 let (result, err) = parse_expression(working_set, &new_spans, false);
 
@@ -687,8 +774,8 @@ pub fn parse_call(
         pos += 1;
     }
 
-    let name = working_set.get_span_contents(span(&name_spans));
-    let mut maybe_decl_id = working_set.find_decl(name);
+    let name = command_name(working_set, &name_spans);
+    let mut maybe_decl_id = working_set.find_decl(&name);
 
     while maybe_decl_id.is_none() {
         // Find the longest command match
@@ -700,8 +787,8 @@ pub fn parse_call(
         name_spans.pop();
         pos -= 1;
 
-        let name = working_set.get_span_contents(span(&name_spans));
-        maybe_decl_id = working_set.find_decl(name);
+        let name = command_name(working_set, &name_spans);
+        maybe_decl_id = working_set.find_decl(&name);
     }
 
     if let Some(decl_id) = maybe_decl_id {
@@ -712,7 +799,7 @@ pub fn parse_call(
 
             if test_equal == [b'='] {
                 return (
-                    garbage(Span::unknown()),
+                    garbage(span(spans)),
                     Some(ParseError::UnknownState(
                         "Incomplete statement".into(),
                         span(spans),
@@ -1432,6 +1519,8 @@ pub fn parse_filepath(
     working_set: &mut StateWorkingSet,
     span: Span,
 ) -> (Expression, Option<ParseError>) {
+    warn_about_uninterpolated_variables(working_set, span);
+
     let bytes = working_set.get_span_contents(span);
     let bytes = trim_quotes(bytes);
 
@@ -1644,6 +1733,8 @@ pub fn parse_glob_pattern(
     working_set: &mut StateWorkingSet,
     span: Span,
 ) -> (Expression, Option<ParseError>) {
+    warn_about_uninterpolated_variables(working_set, span);
+
     let bytes = working_set.get_span_contents(span);
     let bytes = trim_quotes(bytes);
 
@@ -1669,6 +1760,8 @@ pub fn parse_string(
     working_set: &mut StateWorkingSet,
     span: Span,
 ) -> (Expression, Option<ParseError>) {
+    warn_about_uninterpolated_variables(working_set, span);
+
     let bytes = working_set.get_span_contents(span);
     let bytes = trim_quotes(bytes);
 
@@ -1789,7 +1882,8 @@ pub fn parse_import_pattern(
     let mut error = None;
 
     let head = if let Some(head_span) = spans.get(0) {
-        working_set.get_span_contents(*head_span).to_vec()
+        // A quoted head can name a multi-word command, eg `hide "str foo"`
+        trim_quotes(working_set.get_span_contents(*head_span)).to_vec()
     } else {
         return (
             ImportPattern {
@@ -2547,6 +2641,7 @@ pub fn parse_block_expression(
         error = error.or_else(|| Some(ParseError::Unclosed("}".into(), Span { start: end, end })));
     }
 
+    let block_span = span;
     let span = Span { start, end };
 
     let source = working_set.get_span_contents(span);
@@ -2644,6 +2739,7 @@ pub fn parse_block_expression(
     let captures = find_captures_in_block(working_set, &output, &mut seen);
 
     output.captures = captures;
+    output.span = Some(block_span);
 
     working_set.exit_scope();
 
@@ -2677,12 +2773,28 @@ pub fn parse_value(
         return parse_variable_expr(working_set, span);
     } else if bytes.starts_with(b"$") {
         return parse_dollar_expr(working_set, span);
+    } else if shape == &SyntaxShape::GlobPattern
+        && (bytes.starts_with(b"{") || bytes.starts_with(b"["))
+    {
+        // Alternatives and character classes, eg `{src,tests}/*.rs` or `[ab]*.txt`
+        return parse_glob_pattern(working_set, span);
     } else if bytes.starts_with(b"(") {
         if let (expr, None) = parse_range(working_set, span) {
             return (expr, None);
         } else {
             return parse_full_cell_path(working_set, None, span);
         }
+    } else if bytes == b"{}"
+        && !matches!(
+            shape,
+            SyntaxShape::Any
+                | SyntaxShape::FullCellPath
+                | SyntaxShape::Expression
+                | SyntaxShape::Block(_)
+        )
+    {
+        // An empty record only goes where a record can, elsewhere the shape says what `{}` is,
+        // eg the string `{}`
     } else if bytes.starts_with(b"{") {
         if !matches!(shape, SyntaxShape::Block(..)) {
             if let (expr, None) = parse_full_cell_path(working_set, None, span) {
@@ -3258,22 +3370,29 @@ pub fn parse_block(
         working_set.enter_scope();
     }
 
-    let mut error = None;
+    // The first error of each statement. A statement that fails still becomes part of the block,
+    // and the ones after it are parsed, so that all of their errors are reported at once
+    let mut errors: Vec<Option<ParseError>> = vec![None; lite_block.block.len()];
 
     // Pre-declare any definition so that definitions
     // that share the same block can see each other
-    for pipeline in &lite_block.block {
+    for (idx, pipeline) in lite_block.block.iter().enumerate() {
         if pipeline.commands.len() == 1 {
-            if let Some(err) = parse_def_predecl(working_set, &pipeline.commands[0].parts) {
-                error = error.or(Some(err));
-            }
+            errors[idx] = parse_def_predecl(
+                working_set,
+                &pipeline.commands[0].parts,
+                &pipeline.commands[0].comments,
+            );
         }
     }
 
     let block: Block = lite_block
         .block
         .iter()
-        .map(|pipeline| {
+        .enumerate()
+        .map(|(idx, pipeline)| {
+            let error = &mut errors[idx];
+
             if pipeline.commands.len() > 1 {
                 let mut output = pipeline
                     .commands
@@ -3282,7 +3401,7 @@ pub fn parse_block(
                         let (expr, err) = parse_expression(working_set, &command.parts, true);
 
                         if error.is_none() {
-                            error = err;
+                            *error = err;
                         }
 
                         expr
@@ -3302,7 +3421,7 @@ pub fn parse_block(
                 let (stmt, err) = parse_statement(working_set, &pipeline.commands[0].parts);
 
                 if error.is_none() {
-                    error = err;
+                    *error = err;
                 }
 
                 stmt
@@ -3314,7 +3433,10 @@ pub fn parse_block(
         working_set.exit_scope();
     }
 
-    (block, error)
+    (
+        block,
+        ParseError::from_statements(errors.into_iter().flatten().collect()),
+    )
 }
 
 fn find_captures_in_block(
@@ -3559,16 +3681,24 @@ pub fn parse(
     contents: &[u8],
     scoped: bool,
 ) -> (Block, Option<ParseError>) {
-    let mut error = None;
+    let name = fname.unwrap_or("source");
 
-    let span_offset = working_set.next_span_start();
+    parse_with_source(working_set, name, FileSource::Internal, contents, scoped)
+}
 
-    let name = match fname {
-        Some(fname) => fname.to_string(),
-        None => "source".to_string(),
-    };
+/// Like `parse`, for contents that came from `source`, eg a script or what was typed in at the
+/// prompt, so that errors in them can be shown accordingly
+pub fn parse_with_source(
+    working_set: &mut StateWorkingSet,
+    fname: &str,
+    source: FileSource,
+    contents: &[u8],
+    scoped: bool,
+) -> (Block, Option<ParseError>) {
+    let mut error = None;
 
-    working_set.add_file(name, contents);
+    let file_id = working_set.add_file_with_source(fname.to_string(), contents, source);
+    let span_offset = working_set.get_span_for_file(file_id).start;
 
     let (output, err) = lex(contents, span_offset, &[], &[]);
     error = error.or(err);
@@ -1,9 +1,9 @@
 use nu_parser::ParseError;
 use nu_parser::*;
 use nu_protocol::{
-    ast::{Expr, Expression, Pipeline, Statement},
+    ast::{Block, Expr, Expression, Pipeline, Statement},
     engine::{Command, EngineState, Stack, StateWorkingSet},
-    Signature, SyntaxShape,
+    DeclId, ShellWarning, Signature, Span, SyntaxShape,
 };
 
 #[cfg(test)]
@@ -87,7 +87,7 @@ pub fn parse_call() {
                 ..
             } = &expressions[0]
             {
-                assert_eq!(call.decl_id, 0);
+                assert_eq!(call.decl_id, DeclId::new(0));
             }
         }
         _ => panic!("not a call"),
@@ -118,6 +118,164 @@ pub fn parse_call_missing_short_flag_arg() {
     assert!(matches!(err, Some(ParseError::MissingFlagParam(..))));
 }
 
+#[test]
+pub fn parse_call_flag_arg_wrong_shape() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+
+    let sig = Signature::build("foo").named("jazz", SyntaxShape::Int, "jazz!!", Some('j'));
+    working_set.add_decl(sig.predeclare());
+
+    for source in [&b"foo --jazz abc"[..], b"foo -j abc", b"foo --jazz=abc"] {
+        let (_, err) = parse(&mut working_set, None, source, true);
+        match err {
+            Some(ParseError::Expected(shape, span)) => {
+                assert_eq!(shape, "int");
+                assert_eq!(working_set.get_span_contents(span), b"abc");
+            }
+            other => panic!("expected a shape error, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+pub fn parse_call_flag_arg_runtime_checked() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+
+    let sig = Signature::build("foo").named("jazz", SyntaxShape::Int, "jazz!!", Some('j'));
+    working_set.add_decl(sig.predeclare());
+
+    let (_, err) = parse(&mut working_set, None, b"foo --jazz (1 + 1)", true);
+    assert!(err.is_none());
+
+    let (_, err) = parse(&mut working_set, None, b"foo --jazz -5", true);
+    assert!(err.is_none());
+}
+
+#[test]
+pub fn parse_glob_patterns_starting_with_braces_or_classes() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+
+    let sig = Signature::build("foo").required("pattern", SyntaxShape::GlobPattern, "pattern");
+    working_set.add_decl(sig.predeclare());
+
+    for pattern in ["{src,tests}/*.rs", "[ab]*.txt"] {
+        let (block, err) = parse(
+            &mut working_set,
+            None,
+            format!("foo {}", pattern).as_bytes(),
+            true,
+        );
+        assert!(err.is_none(), "{} doesn't parse: {:?}", pattern, err);
+
+        match &block[0] {
+            Statement::Pipeline(Pipeline { expressions }) => match &expressions[0].expr {
+                Expr::Call(call) => assert!(matches!(
+                    &call.positional[0].expr,
+                    Expr::GlobPattern(parsed) if parsed == pattern
+                )),
+                other => panic!("expected a call, got {:?}", other),
+            },
+            _ => panic!("No match"),
+        }
+    }
+}
+
+#[test]
+pub fn empty_braces_are_a_record_only_where_one_can_go() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    working_set.add_decl(
+        Signature::build("text")
+            .required("text", SyntaxShape::String, "text")
+            .predeclare(),
+    );
+    working_set.add_decl(
+        Signature::build("value")
+            .required("value", SyntaxShape::Any, "value")
+            .predeclare(),
+    );
+
+    let argument = |working_set: &mut StateWorkingSet, source: &[u8]| {
+        let (block, err) = parse(working_set, None, source, true);
+        assert!(err.is_none(), "parse error: {:?}", err);
+
+        match &block[0] {
+            Statement::Pipeline(Pipeline { expressions }) => match &expressions[0].expr {
+                Expr::Call(call) => call.positional[0].expr.clone(),
+                other => panic!("expected a call, got {:?}", other),
+            },
+            _ => panic!("No match"),
+        }
+    };
+
+    assert!(matches!(
+        argument(&mut working_set, b"text {}"),
+        Expr::String(text) if text == "{}"
+    ));
+    assert!(matches!(
+        argument(&mut working_set, b"value {}"),
+        Expr::FullCellPath(path) if matches!(&path.head.expr, Expr::Record(fields) if fields.is_empty())
+    ));
+}
+
+#[test]
+pub fn parse_call_switch_with_value() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+
+    let sig = Signature::build("foo").switch("jazz", "jazz!!", Some('j'));
+    working_set.add_decl(sig.predeclare());
+
+    let (_, err) = parse(&mut working_set, None, b"foo --jazz=5", true);
+    match err {
+        Some(ParseError::SwitchWithValue(name, span)) => {
+            assert_eq!(name, "jazz");
+            assert_eq!(working_set.get_span_contents(span), b"=5");
+        }
+        other => panic!("expected a switch error, got {:?}", other),
+    }
+}
+
+#[test]
+pub fn parse_call_missing_flag_arg_before_flag() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+
+    let sig = Signature::build("foo")
+        .named("jazz", SyntaxShape::Int, "jazz!!", Some('j'))
+        .switch("blues", "blues!!", Some('b'));
+    working_set.add_decl(sig.predeclare());
+
+    for (source, flag) in [
+        (&b"foo --jazz --blues"[..], &b"--jazz"[..]),
+        (b"foo -j --blues", b"-j"),
+        (b"foo --jazz -b", b"--jazz"),
+    ] {
+        let (block, err) = parse(&mut working_set, None, source, true);
+        match err {
+            Some(ParseError::MissingFlagParam(shape, span)) => {
+                assert_eq!(shape, "int");
+                assert_eq!(working_set.get_span_contents(span), flag);
+            }
+            other => panic!("expected a missing param error, got {:?}", other),
+        }
+
+        // The following flag is still parsed as a flag
+        match &block[0] {
+            Statement::Pipeline(Pipeline { expressions }) => match &expressions[0].expr {
+                Expr::Call(call) => {
+                    assert!(call.named.iter().any(|(name, _)| name.item == "blues"));
+                }
+                _ => panic!("not a call"),
+            },
+            _ => panic!("not a pipeline"),
+        }
+    }
+}
+
 #[test]
 pub fn parse_call_too_many_shortflag_args() {
     let engine_state = EngineState::new();
@@ -134,6 +292,35 @@ pub fn parse_call_too_many_shortflag_args() {
     ));
 }
 
+#[test]
+pub fn parse_reports_the_errors_of_every_statement() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    add_def(&mut working_set);
+
+    let sig = Signature::build("foo").named("jazz", SyntaxShape::Int, "jazz!!", Some('j'));
+    working_set.add_decl(sig.predeclare());
+
+    let (block, err) = parse(
+        &mut working_set,
+        None,
+        b"foo --jazz\ndef eggs [] { 2 }\nfoo --spam",
+        false,
+    );
+
+    match err {
+        Some(ParseError::Statements { errors }) => match errors.as_slice() {
+            [ParseError::MissingFlagParam(..), ParseError::UnknownFlag(_, _, span)] => {
+                assert_eq!(working_set.get_span_contents(*span), b"--spam")
+            }
+            errors => panic!("expected a missing and an unknown flag, got {:?}", errors),
+        },
+        err => panic!("expected the errors of two statements, got {:?}", err),
+    }
+    assert_eq!(block.len(), 3);
+    assert!(working_set.find_decl(b"eggs").is_some());
+}
+
 #[test]
 pub fn parse_call_unknown_shorthand() {
     let engine_state = EngineState::new();
@@ -516,3 +703,442 @@ mod range {
         assert!(err.is_some());
     }
 }
+
+mod cell_path {
+    use super::*;
+    use nu_protocol::ast::{Call, PathMember};
+
+    fn members(path: &[PathMember]) -> Vec<String> {
+        path.iter()
+            .map(|member| match member {
+                PathMember::String { val, .. } => format!("s:{}", val),
+                PathMember::Int { val, .. } => format!("i:{}", val),
+            })
+            .collect()
+    }
+
+    fn parse_call(source: &str) -> Call {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+
+        let sig = Signature::build("foo")
+            .rest("columns", SyntaxShape::CellPath, "columns")
+            .named("column", SyntaxShape::CellPath, "column", Some('c'));
+        working_set.add_decl(sig.predeclare());
+
+        let (block, err) = parse(&mut working_set, None, source.as_bytes(), true);
+
+        assert!(err.is_none(), "parse error: {:?}", err);
+        match &block[0] {
+            Statement::Pipeline(Pipeline { expressions }) => match &expressions[0].expr {
+                Expr::Call(call) => (**call).clone(),
+                _ => panic!("not a call"),
+            },
+            _ => panic!("No match"),
+        }
+    }
+
+    fn arg_members(expression: &Expression) -> Vec<String> {
+        match &expression.expr {
+            Expr::CellPath(cell_path) => members(&cell_path.members),
+            _ => panic!("not a cell path"),
+        }
+    }
+
+    #[test]
+    fn quoted_members_in_arguments() {
+        let call = parse_call(r#"foo "Last Modified" a."b.c".0 "1st".x 'héllo wörld' "2""#);
+
+        let parsed: Vec<_> = call.positional.iter().map(arg_members).collect();
+        assert_eq!(
+            parsed,
+            vec![
+                vec!["s:Last Modified"],
+                vec!["s:a", "s:b.c", "i:0"],
+                vec!["s:1st", "s:x"],
+                vec!["s:héllo wörld"],
+                vec!["s:2"],
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_members_in_flags() {
+        for source in [
+            r#"foo --column "Last Modified".x"#,
+            r#"foo --column="Last Modified".x"#,
+            r#"foo -c "Last Modified".x"#,
+        ] {
+            let call = parse_call(source);
+            let column = call.named[0].1.as_ref().expect("flag has a value");
+
+            assert_eq!(arg_members(column), vec!["s:Last Modified", "s:x"]);
+        }
+    }
+
+    #[test]
+    fn quoted_members_after_a_head() {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+
+        let (block, err) = parse(
+            &mut working_set,
+            None,
+            r#"{a: 1}."x y"."ü.ñ".0"#.as_bytes(),
+            true,
+        );
+
+        assert!(err.is_none(), "parse error: {:?}", err);
+        match &block[0] {
+            Statement::Pipeline(Pipeline { expressions }) => match &expressions[0].expr {
+                Expr::FullCellPath(path) => {
+                    assert_eq!(members(&path.tail), vec!["s:x y", "s:ü.ñ", "i:0"])
+                }
+                _ => panic!("not a cell path"),
+            },
+            _ => panic!("No match"),
+        }
+    }
+}
+
+fn add_def(working_set: &mut StateWorkingSet) {
+    working_set.add_decl(
+        Signature::build("def")
+            .required("def_name", SyntaxShape::String, "definition name")
+            .required("params", SyntaxShape::Signature, "parameters")
+            .required(
+                "block",
+                SyntaxShape::Block(Some(vec![])),
+                "body of the definition",
+            )
+            .predeclare(),
+    );
+}
+
+#[test]
+pub fn parse_drops_the_predeclarations_of_a_failed_block() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    add_def(&mut working_set);
+    let num_decls = working_set.num_decls();
+
+    let (_, err) = parse(
+        &mut working_set,
+        None,
+        b"{ export def spam [] { 1 } }",
+        false,
+    );
+
+    assert!(err.is_some(), "export is only allowed in modules");
+    assert_eq!(working_set.num_decls(), num_decls);
+    assert!(working_set.find_decl(b"spam").is_none());
+}
+
+#[test]
+pub fn parse_def_comments_become_usage() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    add_def(&mut working_set);
+
+    let (_, err) = parse(
+        &mut working_set,
+        None,
+        b"ls\n# Greet someone\n# by name\ndef greet [] { 1 }",
+        false,
+    );
+
+    assert!(err.is_none(), "parse error: {:?}", err);
+    let decl_id = working_set.find_decl(b"greet").expect("greet is defined");
+    assert_eq!(
+        working_set.get_decl(decl_id).usage(),
+        "Greet someone\nby name"
+    );
+}
+
+#[test]
+pub fn parse_def_records_its_span() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    add_def(&mut working_set);
+
+    let (_, err) = parse(
+        &mut working_set,
+        Some("spam.nu"),
+        b"ls\ndef spam [x: int] { $x }\nspam 1",
+        false,
+    );
+
+    assert!(err.is_none(), "parse error: {:?}", err);
+    let decl_id = working_set.find_decl(b"spam").expect("spam is defined");
+    let span = working_set
+        .get_decl_span(decl_id)
+        .expect("custom commands have a span");
+    assert_eq!(
+        working_set.get_span_contents(span),
+        b"def spam [x: int] { $x }"
+    );
+    assert_eq!(working_set.get_decl_span(DeclId::new(0)), None);
+}
+
+#[test]
+pub fn parse_def_keeps_the_source_of_its_block() {
+    let mut engine_state = EngineState::new();
+    let (block_id, synthesized, delta) = {
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        add_def(&mut working_set);
+
+        let (_, err) = parse(
+            &mut working_set,
+            Some("greet.nu"),
+            b"def greet [] { \"hi\" }",
+            false,
+        );
+        assert!(err.is_none(), "parse error: {:?}", err);
+
+        let decl_id = working_set.find_decl(b"greet").expect("greet is defined");
+        let block_id = working_set
+            .get_decl(decl_id)
+            .get_block_id()
+            .expect("custom commands have a block");
+        assert_eq!(
+            working_set.get_block_source(block_id),
+            Some("{ \"hi\" }".to_string())
+        );
+
+        let synthesized = working_set.add_block(Block::new());
+        assert_eq!(working_set.get_block_source(synthesized), None);
+
+        (block_id, synthesized, working_set.render())
+    };
+    engine_state.merge_delta(delta);
+
+    assert_eq!(
+        engine_state.get_block_source(block_id),
+        Some("{ \"hi\" }".to_string())
+    );
+    assert_eq!(engine_state.get_block_source(synthesized), None);
+}
+
+#[test]
+pub fn parsed_spans_point_into_the_files() {
+    let mut engine_state = EngineState::new();
+    let delta = {
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        add_def(&mut working_set);
+        working_set.add_decl(Box::new(Let));
+        working_set.add_decl(
+            Signature::build("alias")
+                .required("name", SyntaxShape::String, "name of the alias")
+                .required(
+                    "initial_value",
+                    SyntaxShape::Keyword(b"=".to_vec(), Box::new(SyntaxShape::Expression)),
+                    "equals sign followed by value",
+                )
+                .predeclare(),
+        );
+
+        let (_, err) = parse(
+            &mut working_set,
+            Some("spam.nu"),
+            b"def spam [x: int] { $x }\nalias ll = spam 1\nll",
+            false,
+        );
+        assert!(err.is_none(), "parse error: {:?}", err);
+
+        // The blocks of statements that fail to parse are still kept
+        let (_, err) = parse(&mut working_set, None, b"def broken [] { spam = 1 }", false);
+        assert!(err.is_some());
+
+        working_set.render()
+    };
+    engine_state.merge_delta(delta);
+
+    assert!(engine_state.validate_spans().is_ok());
+}
+
+fn add_module_keywords(working_set: &mut StateWorkingSet) {
+    let assignment = |name: &str, shape: SyntaxShape| {
+        Signature::build(name)
+            .required("name", SyntaxShape::String, "name")
+            .required(
+                "initial_value",
+                SyntaxShape::Keyword(b"=".to_vec(), Box::new(shape)),
+                "value",
+            )
+            .predeclare()
+    };
+
+    add_def(working_set);
+    for name in ["module", "use", "export def"] {
+        working_set.add_decl(Signature::build(name).predeclare());
+    }
+    working_set.add_decl(assignment("alias", SyntaxShape::Expression));
+    working_set.add_decl(assignment("export alias", SyntaxShape::Expression));
+    working_set.add_decl(assignment("export env", SyntaxShape::String));
+}
+
+#[test]
+pub fn modules_export_commands_aliases_and_env_vars() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    add_module_keywords(&mut working_set);
+
+    let (_, err) = parse(
+        &mut working_set,
+        None,
+        b"def eggs [] { 2 }\nmodule spam {\n export def foo [] { 1 }\n export alias bar = eggs\n export env BAZ = eggs\n}\nuse spam",
+        false,
+    );
+    assert!(err.is_none(), "parse error: {:?}", err);
+
+    assert!(working_set.find_decl(b"spam foo").is_some());
+    assert!(working_set.find_alias(b"spam bar").is_some());
+    assert!(working_set.find_alias(b"bar").is_none());
+    assert_eq!(
+        working_set
+            .find_env_var(b"BAZ")
+            .and_then(|value| value.as_string().ok()),
+        Some("eggs".to_string())
+    );
+
+    // Aliases expand where they're used, so they call what's visible there
+    let eggs = working_set.find_decl(b"eggs").expect("eggs is defined");
+    let (block, err) = parse(&mut working_set, None, b"spam bar", false);
+    assert!(err.is_none(), "parse error: {:?}", err);
+    match &block[0] {
+        Statement::Pipeline(Pipeline { expressions }) => match &expressions[0].expr {
+            Expr::Call(call) => assert_eq!(call.decl_id, eggs),
+            expr => panic!("expected a call, got {:?}", expr),
+        },
+        _ => panic!("expected a pipeline"),
+    }
+}
+
+#[test]
+pub fn importing_a_single_export_by_name() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    add_module_keywords(&mut working_set);
+
+    let (_, err) = parse(
+        &mut working_set,
+        None,
+        b"module spam {\n export def foo [] { 1 }\n export alias bar = foo\n}\nuse spam bar",
+        false,
+    );
+    assert!(err.is_none(), "parse error: {:?}", err);
+
+    assert!(working_set.find_alias(b"bar").is_some());
+    assert!(working_set.find_decl(b"foo").is_none());
+}
+
+#[test]
+pub fn hide_without_a_name_is_an_error() {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    add_module_keywords(&mut working_set);
+    working_set.add_decl(Signature::build("hide").predeclare());
+
+    let (_, err) = parse(&mut working_set, None, b"hide", false);
+    assert!(matches!(err, Some(ParseError::UnknownState(..))));
+}
+
+/// The variables `source` warns about, by name and the span of the name
+fn uninterpolated_variables(source: &str) -> Vec<(String, Span)> {
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    working_set.add_decl(
+        Signature::build("spam")
+            .required("text", SyntaxShape::String, "text")
+            .predeclare(),
+    );
+
+    let (_, err) = parse(&mut working_set, None, source.as_bytes(), true);
+    assert!(err.is_none(), "parse error: {:?}", err);
+
+    working_set
+        .warnings()
+        .iter()
+        .map(|warning| match warning {
+            ShellWarning::UninterpolatedVariable { name, span } => (name.clone(), *span),
+            other => panic!("unexpected warning: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+pub fn variables_in_double_quoted_strings_are_warned_about() {
+    let source = r#"spam "$name.txt and a$x""#;
+    assert_eq!(
+        uninterpolated_variables(source),
+        vec![
+            ("$name".to_string(), Span { start: 6, end: 11 }),
+            ("$x".to_string(), Span { start: 21, end: 23 }),
+        ]
+    );
+
+    // Externals get their strings as they're written
+    assert_eq!(
+        uninterpolated_variables(r#"cat "value: $x""#),
+        vec![("$x".to_string(), Span { start: 12, end: 14 })]
+    );
+}
+
+#[test]
+pub fn single_quoted_variables_are_not_warned_about() {
+    assert!(uninterpolated_variables(r#"spam "costs $5, or $""#).is_empty());
+    assert!(uninterpolated_variables("spam '$name'").is_empty());
+    assert!(uninterpolated_variables("cat '$name'").is_empty());
+}
+
+#[test]
+pub fn backslashes_dont_keep_variables_from_being_warned_about() {
+    let source = r#"spam "\$name""#;
+    assert_eq!(
+        uninterpolated_variables(source),
+        vec![("$name".to_string(), Span { start: 7, end: 12 })]
+    );
+
+    // Double-quoted strings are passed as they're written, backslash and all
+    let engine_state = EngineState::new();
+    let mut working_set = StateWorkingSet::new(&engine_state);
+    working_set.add_decl(
+        Signature::build("spam")
+            .required("text", SyntaxShape::String, "text")
+            .predeclare(),
+    );
+    let (block, err) = parse(&mut working_set, None, source.as_bytes(), true);
+    assert!(err.is_none(), "parse error: {:?}", err);
+
+    match &block[0] {
+        Statement::Pipeline(Pipeline { expressions }) => match &expressions[0].expr {
+            Expr::Call(call) => assert!(matches!(
+                &call.positional[0].expr,
+                Expr::String(text) if text == r"\$name"
+            )),
+            other => panic!("expected a call, got {:?}", other),
+        },
+        _ => panic!("No match"),
+    }
+}
+
+#[test]
+pub fn warnings_are_raised_when_the_delta_is_merged() {
+    let mut engine_state = EngineState::new();
+    let delta = {
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        let (_, err) = parse(&mut working_set, None, br#"cat "$x""#, true);
+        assert!(err.is_none(), "parse error: {:?}", err);
+        working_set.render()
+    };
+    assert!(engine_state.take_warnings().is_empty());
+
+    engine_state.merge_delta(delta);
+    assert_eq!(
+        engine_state.take_warnings(),
+        vec![ShellWarning::UninterpolatedVariable {
+            name: "$x".into(),
+            span: Span { start: 5, end: 7 },
+        }]
+    );
+}
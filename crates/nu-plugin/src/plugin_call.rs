@@ -155,7 +155,7 @@ mod tests {
     use crate::plugin::{PluginCall, PluginResponse};
     use nu_protocol::{
         ast::{Call, Expr, Expression},
-        Signature, Span, Spanned, SyntaxShape, Value,
+        DeclId, Signature, Span, Spanned, SyntaxShape, Value,
     };
 
     fn compare_expressions(lhs: &Expression, rhs: &Expression) {
@@ -192,7 +192,7 @@ mod tests {
         };
 
         let call = Call {
-            decl_id: 1,
+            decl_id: DeclId::new(1),
             head: Span { start: 0, end: 10 },
             positional: vec![
                 Expression {
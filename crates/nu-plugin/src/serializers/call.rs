@@ -2,7 +2,7 @@ use crate::plugin::PluginError;
 use crate::plugin_capnp::{call, expression};
 use nu_protocol::{
     ast::{Call, Expr, Expression},
-    Span, Spanned, Type,
+    DeclId, Span, Spanned, Type,
 };
 
 pub(crate) fn serialize_call(call: &Call, mut builder: call::Builder) -> Result<(), PluginError> {
@@ -84,7 +84,7 @@ pub(crate) fn deserialize_call(reader: call::Reader) -> Result<Call, PluginError
     let named = deserialize_named(head, reader)?;
 
     Ok(Call {
-        decl_id: 0,
+        decl_id: DeclId::new(0),
         head,
         positional,
         named,
@@ -228,7 +228,7 @@ mod tests {
     #[test]
     fn call_round_trip() {
         let call = Call {
-            decl_id: 1,
+            decl_id: DeclId::new(1),
             head: Span { start: 0, end: 10 },
             positional: vec![
                 Expression {
@@ -1,6 +1,6 @@
 use crate::plugin::PluginError;
 use crate::plugin_capnp::{argument, flag, signature, Shape};
-use nu_protocol::{Flag, PositionalArg, Signature, SyntaxShape};
+use nu_protocol::{Category, Flag, PositionalArg, Signature, SyntaxShape, Type};
 
 pub(crate) fn serialize_signature(signature: &Signature, mut builder: signature::Builder) {
     builder.set_name(signature.name.as_str());
@@ -139,8 +139,13 @@ pub(crate) fn deserialize_signature(reader: signature::Reader) -> Result<Signatu
         optional_positional,
         rest_positional,
         named,
+        flag_constraints: vec![],
         is_filter,
         creates_scope: false,
+        category: Category::Default,
+        input_type: Type::Unknown,
+        output_type: Type::Unknown,
+        deprecated: None,
     })
 }
 
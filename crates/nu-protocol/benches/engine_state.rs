@@ -0,0 +1,125 @@
+//! Benchmarks for the engine state paths that are hit once per command or per iteration, to have a
+//! baseline for changes to how the state is stored (it's kept in `im` collections for cheap clones)
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use nu_protocol::engine::{EngineState, ScopeFrame, StateDelta, StateWorkingSet};
+use nu_protocol::{DeclId, Signature, Span};
+
+/// An engine state with `decls` commands and `files` files of 100 bytes each
+fn state_with(decls: usize, files: usize) -> EngineState {
+    let mut engine_state = EngineState::new();
+    engine_state.merge_delta(delta_with(&engine_state, decls, files));
+    engine_state
+}
+
+/// A delta adding `decls` commands and `files` files of 100 bytes each on top of `engine_state`
+fn delta_with(engine_state: &EngineState, decls: usize, files: usize) -> StateDelta {
+    let offset = engine_state.num_decls();
+    let mut working_set = StateWorkingSet::new(engine_state);
+
+    for idx in 0..decls {
+        working_set.add_decl(Signature::build(format!("cmd {}", offset + idx)).predeclare());
+    }
+    for idx in 0..files {
+        working_set.add_file(format!("file{}.nu", idx), &[b'a'; 100]);
+    }
+
+    working_set.render()
+}
+
+/// An engine state with its commands spread over `frames` nested scope frames
+fn state_with_frames(frames: usize, per_frame: usize) -> EngineState {
+    let mut engine_state = state_with(frames * per_frame, 0);
+
+    for frame in 1..frames {
+        let mut scope = ScopeFrame::new();
+        for idx in 0..per_frame {
+            let decl_id = DeclId::new(frame * per_frame + idx);
+            scope.decls.insert(
+                format!("cmd {}", frame * per_frame + idx).into_bytes(),
+                decl_id,
+            );
+        }
+        engine_state.scope.push_back(scope);
+    }
+
+    // Merging refreshes the lookup cache for the new frames
+    let delta = StateWorkingSet::new(&engine_state).render();
+    engine_state.merge_delta(delta);
+
+    engine_state
+}
+
+fn clone(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clone");
+
+    for size in [100, 1000, 10000] {
+        let engine_state = state_with(size, size);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &engine_state,
+            |b, state| b.iter(|| state.clone()),
+        );
+    }
+
+    group.finish();
+}
+
+fn merge_delta(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merge_delta");
+    let engine_state = state_with(1000, 100);
+
+    for size in [100, 1000, 10000] {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter_batched(
+                || (engine_state.clone(), delta_with(&engine_state, size, size)),
+                |(mut engine_state, delta)| engine_state.merge_delta(delta),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+fn find_decl(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_decl");
+
+    for frames in [1, 10, 100] {
+        let engine_state = state_with_frames(frames, 100);
+        // The outermost command, so lookups that walk the frames walk all of them
+        let name = b"cmd 0";
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(frames),
+            &engine_state,
+            |b, state| b.iter(|| state.find_decl(name)),
+        );
+    }
+
+    group.finish();
+}
+
+fn get_span_contents(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_span_contents");
+
+    for files in [10, 100, 1000] {
+        let engine_state = state_with(0, files);
+        // The last file, so lookups that scan the files scan all of them
+        let end = engine_state.next_span_start();
+        let span = Span {
+            start: end - 10,
+            end,
+        };
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(files),
+            &engine_state,
+            |b, state| b.iter(|| state.get_span_contents(&span).len()),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, clone, merge_delta, find_decl, get_span_contents);
+criterion_main!(benches);
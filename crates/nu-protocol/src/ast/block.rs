@@ -1,15 +1,20 @@
 use std::ops::{Index, IndexMut};
 
-use crate::{DeclId, Signature, VarId};
+use crate::engine::Overlay;
+use crate::{Signature, Span, VarId};
 
 use super::Statement;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Block {
     pub signature: Box<Signature>,
     pub stmts: Vec<Statement>,
-    pub exports: Vec<(Vec<u8>, DeclId)>, // Assuming just defs for now
+    pub exports: Overlay,
     pub captures: Vec<VarId>,
+    /// Where the block was written, braces included. Blocks that weren't parsed from a `{ }`,
+    /// like the one of a whole file, have none
+    pub span: Option<Span>,
 }
 
 impl Block {
@@ -47,17 +52,19 @@ impl Block {
         Self {
             signature: Box::new(Signature::new("")),
             stmts: vec![],
-            exports: vec![],
+            exports: Overlay::new(),
             captures: vec![],
+            span: None,
         }
     }
 
-    pub fn with_exports(self, exports: Vec<(Vec<u8>, DeclId)>) -> Self {
+    pub fn with_exports(self, exports: Overlay) -> Self {
         Self {
             signature: self.signature,
             stmts: self.stmts,
             exports,
             captures: self.captures,
+            span: self.span,
         }
     }
 }
@@ -70,8 +77,9 @@ where
         Self {
             signature: Box::new(Signature::new("")),
             stmts: stmts.collect(),
-            exports: vec![],
+            exports: Overlay::new(),
             captures: vec![],
+            span: None,
         }
     }
 }
@@ -1,7 +1,8 @@
 use super::Expression;
 use crate::{DeclId, Span, Spanned};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Call {
     /// identifier of the declaration to call
     pub decl_id: DeclId,
@@ -19,7 +20,7 @@ impl Default for Call {
 impl Call {
     pub fn new() -> Call {
         Self {
-            decl_id: 0,
+            decl_id: DeclId::new(0),
             head: Span::unknown(),
             positional: vec![],
             named: vec![],
@@ -36,6 +37,14 @@ impl Call {
         false
     }
 
+    /// Where the flag was given, eg to point errors about a switch at it
+    pub fn get_flag_span(&self, flag_name: &str) -> Option<Span> {
+        self.named
+            .iter()
+            .find(|(name, _)| flag_name == name.item)
+            .map(|(name, _)| name.span)
+    }
+
     pub fn get_flag_expr(&self, flag_name: &str) -> Option<Expression> {
         for name in &self.named {
             if flag_name == name.0.item {
@@ -31,7 +31,7 @@ impl CellPath {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FullCellPath {
     pub head: Expression,
     pub tail: Vec<PathMember>,
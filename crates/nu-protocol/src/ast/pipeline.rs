@@ -1,6 +1,7 @@
 use crate::ast::Expression;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pipeline {
     pub expressions: Vec<Expression>,
 }
@@ -1,7 +1,8 @@
 use super::Pipeline;
 use crate::DeclId;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Statement {
     Declaration(DeclId),
     Pipeline(Pipeline),
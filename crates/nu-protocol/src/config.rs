@@ -1,11 +1,32 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-use crate::{ShellError, Value};
+use crate::{BlockId, ShellError, Value};
+
+/// How an env var is converted when it crosses the process boundary, set per variable in the
+/// config's `env_conversions`. Each block takes the variable as its input
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct EnvConversion {
+    /// Turns the string from the process environment into the value the shell works with
+    pub from_string: Option<BlockId>,
+    /// Turns the value back into the string external commands get
+    pub to_string: Option<BlockId>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Config {
     pub filesize_metric: bool,
     pub table_mode: String,
+    pub table_show_index: bool,
+    pub case_insensitive_lookup: bool,
+    pub use_ls_colors: bool,
+    pub rm_protected_paths: Vec<String>,
+    pub use_pager: bool,
+    /// What's done with output of external commands that isn't valid UTF-8: "bytes" passes it on
+    /// as binary, "lossy" replaces what's invalid and "error" stops the output with an error
+    pub external_invalid_utf8: String,
+    pub env_conversions: HashMap<String, EnvConversion>,
 }
 
 impl Default for Config {
@@ -13,6 +34,13 @@ impl Default for Config {
         Config {
             filesize_metric: false,
             table_mode: "rounded".into(),
+            table_show_index: true,
+            case_insensitive_lookup: false,
+            use_ls_colors: false,
+            rm_protected_paths: vec![],
+            use_pager: false,
+            external_invalid_utf8: "bytes".into(),
+            env_conversions: HashMap::new(),
         }
     }
 }
@@ -31,6 +59,38 @@ impl Value {
                 "table_mode" => {
                     config.table_mode = value.as_string()?;
                 }
+                "table_show_index" => {
+                    config.table_show_index = value.as_bool()?;
+                }
+                "case_insensitive_lookup" => {
+                    config.case_insensitive_lookup = value.as_bool()?;
+                }
+                "use_ls_colors" => {
+                    config.use_ls_colors = value.as_bool()?;
+                }
+                "rm_protected_paths" => {
+                    config.rm_protected_paths = match value {
+                        Value::List { vals, .. } => vals
+                            .iter()
+                            .map(|path| path.as_string())
+                            .collect::<Result<_, _>>()?,
+                        path => vec![path.as_string()?],
+                    };
+                }
+                "use_pager" => {
+                    config.use_pager = value.as_bool()?;
+                }
+                "external_invalid_utf8" => {
+                    config.external_invalid_utf8 = value.as_string()?;
+                }
+                "env_conversions" => {
+                    let (names, conversions) = value.as_record()?;
+                    for (name, conversion) in names.iter().zip(conversions) {
+                        config
+                            .env_conversions
+                            .insert(name.clone(), env_conversion(conversion)?);
+                    }
+                }
                 _ => {}
             }
         }
@@ -38,3 +98,18 @@ impl Value {
         Ok(config)
     }
 }
+
+fn env_conversion(value: &Value) -> Result<EnvConversion, ShellError> {
+    let (keys, vals) = value.as_record()?;
+    let mut conversion = EnvConversion::default();
+
+    for (key, value) in keys.iter().zip(vals) {
+        match key.as_str() {
+            "from_string" => conversion.from_string = Some(value.as_block()?),
+            "to_string" => conversion.to_string = Some(value.as_block()?),
+            _ => {}
+        }
+    }
+
+    Ok(conversion)
+}
@@ -0,0 +1,75 @@
+use crate::Span;
+use serde::{Deserialize, Serialize};
+
+/// What an alias expands to: the words of its replacement, and where the arguments of a call to
+/// the alias go. Without a placeholder (`{}` in the definition) they go at the end
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alias {
+    /// The replacement, without the placeholder
+    pub replacement: Vec<Span>,
+    /// The index into `replacement` the arguments are spliced in at
+    pub placeholder: Option<usize>,
+}
+
+impl Alias {
+    pub fn new(replacement: Vec<Span>) -> Self {
+        Self {
+            replacement,
+            placeholder: None,
+        }
+    }
+
+    pub fn with_placeholder(replacement: Vec<Span>, placeholder: usize) -> Self {
+        Self {
+            replacement,
+            placeholder: Some(placeholder),
+        }
+    }
+
+    /// The words a call to the alias with `args` expands to
+    pub fn expand(&self, args: &[Span]) -> Vec<Span> {
+        let at = self.placeholder.unwrap_or(self.replacement.len());
+
+        let mut expansion = Vec::with_capacity(self.replacement.len() + args.len());
+        expansion.extend(&self.replacement[..at]);
+        expansion.extend(args);
+        expansion.extend(&self.replacement[at..]);
+
+        expansion
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spans(starts: &[usize]) -> Vec<Span> {
+        starts
+            .iter()
+            .map(|start| Span {
+                start: *start,
+                end: start + 1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn appends_arguments_without_a_placeholder() {
+        let alias = Alias::new(spans(&[0, 1]));
+
+        assert_eq!(alias.expand(&[]), spans(&[0, 1]));
+        assert_eq!(alias.expand(&spans(&[10, 11])), spans(&[0, 1, 10, 11]));
+    }
+
+    #[test]
+    fn splices_arguments_at_the_placeholder() {
+        let alias = Alias::with_placeholder(spans(&[0, 1, 2]), 1);
+
+        assert_eq!(alias.expand(&[]), spans(&[0, 1, 2]));
+        assert_eq!(alias.expand(&spans(&[10])), spans(&[0, 10, 1, 2]));
+        assert_eq!(
+            alias.expand(&spans(&[10, 11, 12])),
+            spans(&[0, 10, 11, 12, 1, 2])
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{ast::Call, BlockId, Example, PipelineData, ShellError, Signature};
+use crate::{ast::Call, BlockId, DeprecationNotice, Example, PipelineData, ShellError, Signature};
 
 use super::{EngineState, Stack};
 
@@ -36,6 +36,11 @@ pub trait Command: Send + Sync + CommandClone {
         Vec::new()
     }
 
+    // Commands that still run but shouldn't be used any more. Running one warns, once a session
+    fn deprecated(&self) -> Option<DeprecationNotice> {
+        None
+    }
+
     // This is a built-in command
     fn is_builtin(&self) -> bool {
         true
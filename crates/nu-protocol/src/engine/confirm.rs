@@ -0,0 +1,27 @@
+use crate::ShellError;
+
+/// How commands ask the user to confirm an action (eg, `rm --interactive`)
+///
+/// The engine state carries one of these so that commands don't need to know whether they're
+/// running in an interactive session, a script, or a test.
+pub trait Confirm: Send + Sync {
+    /// Whether there is someone to ask. Commands with an `--interactive` flag should refuse to
+    /// run instead of silently proceeding when this is false
+    fn is_interactive(&self) -> bool;
+
+    /// Ask the question, returning whether the user agreed
+    fn confirm(&self, prompt: &str) -> Result<bool, ShellError>;
+}
+
+/// Agrees to everything without asking. Used when there is no terminal (scripts, tests)
+pub struct AlwaysYes;
+
+impl Confirm for AlwaysYes {
+    fn is_interactive(&self) -> bool {
+        false
+    }
+
+    fn confirm(&self, _prompt: &str) -> Result<bool, ShellError> {
+        Ok(true)
+    }
+}
@@ -1,60 +1,124 @@
-use super::Command;
-use crate::{ast::Block, BlockId, DeclId, Example, Signature, Span, Type, VarId};
+use super::{
+    Alias, AlwaysYes, Command, Confirm, File, FileSource, Output, Overlay, RegexCache, Stack,
+    StdOutput, WarningSink,
+};
+use crate::{
+    ast::{Block, Call, Expr, Expression, Statement},
+    BlockId, DeclId, Example, PipelineData, ShellError, ShellWarning, Signature, Span, Type, Value,
+    VarId,
+};
 use core::panic;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
-    sync::{atomic::AtomicBool, Arc},
+    collections::{BTreeSet, HashMap, HashSet},
+    hash::Hash,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 
-// Tells whether a decl etc. is visible or not
-// TODO: When adding new exportables (env vars, aliases, etc.), parametrize the ID type with generics
-#[derive(Debug, Clone)]
-struct Visibility {
-    ids: HashMap<DeclId, bool>,
+/// Tells whether a decl, alias etc. is visible or not, by the id (or name) it's known by
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Eq + Hash + Deserialize<'de>"))]
+struct Visibility<T> {
+    ids: HashMap<T, bool>,
 }
 
-impl Visibility {
+impl<T: Eq + Hash + Clone> Visibility<T> {
     fn new() -> Self {
         Visibility {
             ids: HashMap::new(),
         }
     }
 
-    fn is_id_visible(&self, id: &DeclId) -> bool {
+    fn is_id_visible(&self, id: &T) -> bool {
         *self.ids.get(id).unwrap_or(&true) // by default it's visible
     }
 
-    fn hide_id(&mut self, id: &DeclId) {
-        self.ids.insert(*id, false);
+    fn hide_id(&mut self, id: &T) {
+        self.ids.insert(id.clone(), false);
     }
 
-    fn use_id(&mut self, id: &DeclId) {
-        self.ids.insert(*id, true);
+    fn use_id(&mut self, id: &T) {
+        self.ids.insert(id.clone(), true);
     }
 
-    fn merge_with(&mut self, other: Visibility) {
+    fn merge_with(&mut self, other: Visibility<T>) {
         // overwrite own values with the other
         self.ids.extend(other.ids);
     }
 
-    fn append(&mut self, other: &Visibility) {
+    fn append(&mut self, other: &Visibility<T>) {
         // take new values from other but keep own values
         for (id, visible) in other.ids.iter() {
             if !self.ids.contains_key(id) {
-                self.ids.insert(*id, *visible);
+                self.ids.insert(id.clone(), *visible);
             }
         }
     }
+
+    /// Renumber the ids, leaving out the ones `new_id` has no new id for
+    fn remap(&mut self, new_id: impl Fn(&T) -> Option<T>) {
+        self.ids = self
+            .ids
+            .drain()
+            .filter_map(|(id, visible)| Some((new_id(&id)?, visible)))
+            .collect();
+    }
+}
+
+impl Visibility<DeclId> {
+    /// Whether this makes any of the decls with an id below `num_decls` visible
+    fn shows_any_below(&self, num_decls: usize) -> bool {
+        self.ids
+            .iter()
+            .any(|(id, visible)| *visible && id.get() < num_decls)
+    }
+}
+
+/// Which decls are visible after walking some scope frames, innermost first: by id, and by the
+/// names that were hidden without hiding the decl they're bound to
+struct DeclVisibility {
+    ids: Visibility<DeclId>,
+    names: Visibility<Vec<u8>>,
+}
+
+impl DeclVisibility {
+    fn new() -> Self {
+        DeclVisibility {
+            ids: Visibility::new(),
+            names: Visibility::new(),
+        }
+    }
+
+    fn append(&mut self, frame: &ScopeFrame) {
+        self.ids.append(&frame.visibility);
+        self.names.append(&frame.decl_name_visibility);
+    }
+
+    /// Whether `name`, bound to `decl_id` in one of the frames, still resolves to it
+    fn is_visible(&self, name: &[u8], decl_id: &DeclId) -> bool {
+        self.ids.is_id_visible(decl_id) && self.names.is_id_visible(&name.to_vec())
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScopeFrame {
     pub vars: HashMap<Vec<u8>, VarId>,
     predecls: HashMap<Vec<u8>, DeclId>, // temporary storage for predeclarations
     pub decls: HashMap<Vec<u8>, DeclId>,
-    pub aliases: HashMap<Vec<u8>, Vec<Span>>,
+    pub aliases: HashMap<Vec<u8>, Alias>,
     pub modules: HashMap<Vec<u8>, BlockId>,
-    visibility: Visibility,
+    pub env_vars: HashMap<Vec<u8>, Value>,
+    visibility: Visibility<DeclId>,
+    // Names hidden on their own, eg the ones of a deactivated overlay, while other names for the
+    // same decl stay visible
+    decl_name_visibility: Visibility<Vec<u8>>,
+    // Aliases have no ids, so they are hidden by name
+    alias_visibility: Visibility<Vec<u8>>,
+    var_visibility: Visibility<VarId>,
+    module_visibility: Visibility<BlockId>,
 }
 
 impl ScopeFrame {
@@ -65,13 +129,34 @@ impl ScopeFrame {
             decls: HashMap::new(),
             aliases: HashMap::new(),
             modules: HashMap::new(),
+            env_vars: HashMap::new(),
             visibility: Visibility::new(),
+            decl_name_visibility: Visibility::new(),
+            alias_visibility: Visibility::new(),
+            var_visibility: Visibility::new(),
+            module_visibility: Visibility::new(),
         }
     }
 
     pub fn get_var(&self, var_name: &[u8]) -> Option<&VarId> {
         self.vars.get(var_name)
     }
+
+    /// Take in the names another frame binds, as if it was nested in this one: where both bind
+    /// a name, or say whether something is visible, the other frame wins
+    fn absorb(&mut self, other: ScopeFrame) {
+        self.decls.extend(other.decls);
+        self.vars.extend(other.vars);
+        self.aliases.extend(other.aliases);
+        self.modules.extend(other.modules);
+        self.env_vars.extend(other.env_vars);
+        self.visibility.merge_with(other.visibility);
+        self.decl_name_visibility
+            .merge_with(other.decl_name_visibility);
+        self.alias_visibility.merge_with(other.alias_visibility);
+        self.var_visibility.merge_with(other.var_visibility);
+        self.module_visibility.merge_with(other.module_visibility);
+    }
 }
 
 impl Default for ScopeFrame {
@@ -124,19 +209,52 @@ impl Default for ScopeFrame {
 ///
 #[derive(Clone)]
 pub struct EngineState {
-    files: im::Vector<(String, usize, usize)>,
+    files: im::Vector<File>,
     file_contents: im::Vector<(Vec<u8>, usize, usize)>,
     vars: im::Vector<Type>,
     decls: im::Vector<Box<dyn Command + 'static>>,
+    /// Where each decl was defined, indexed by DeclId. Builtins have no source to point at
+    decl_spans: im::Vector<Option<Span>>,
     blocks: im::Vector<Block>,
     pub scope: im::Vector<ScopeFrame>,
+    /// The decls visible from the innermost scope frame by name, so lookups don't have to walk
+    /// the frames. Rebuilt by `merge_delta`
+    visible_decls: Arc<HashMap<Vec<u8>, DeclId>>,
     pub ctrlc: Option<Arc<AtomicBool>>,
+    /// When set, command lookups that miss retry ignoring ASCII case (mirrors the
+    /// `case_insensitive_lookup` config option)
+    pub case_insensitive_lookup: bool,
+    /// Asks the user before commands take actions that need confirmation
+    pub confirm: Arc<dyn Confirm>,
+    /// Where rendered results and reported errors are written
+    pub output: Arc<dyn Output>,
+    /// Named values the user stashed away during this session. Shared between the clones of the
+    /// engine state, and only touched by the `stash` commands
+    pub stash: Arc<Mutex<HashMap<String, Value>>>,
+    /// The regexes compiled by `=~`, `!~` and the commands, shared between the clones of the
+    /// engine state so that a pattern used on each row, or in each iteration of `par-each`, is
+    /// compiled once
+    pub regex_cache: Arc<RegexCache>,
+    /// The warnings waiting to be shown after the output. Shared between the clones of the engine
+    /// state, so that the commands of a pipeline all raise them in the same place
+    warnings: Arc<Mutex<WarningSink>>,
+    /// The error a stream stopped at, eg the row `each` failed on, waiting to fail what drains
+    /// the stream. Shared between the clones of the engine state, which the streams hold
+    aborted: Arc<Mutex<Option<ShellError>>>,
 }
 
-pub const NU_VARIABLE_ID: usize = 0;
-pub const SCOPE_VARIABLE_ID: usize = 1;
-pub const IN_VARIABLE_ID: usize = 2;
-pub const CONFIG_VARIABLE_ID: usize = 3;
+pub const NU_VARIABLE_ID: VarId = VarId::new(0);
+pub const SCOPE_VARIABLE_ID: VarId = VarId::new(1);
+pub const IN_VARIABLE_ID: VarId = VarId::new(2);
+pub const CONFIG_VARIABLE_ID: VarId = VarId::new(3);
+
+/// The variables the shell provides, by the names they're used with
+const BUILTIN_VARIABLES: [(&[u8], VarId); 4] = [
+    (b"$nu", NU_VARIABLE_ID),
+    (b"$scope", SCOPE_VARIABLE_ID),
+    (b"$in", IN_VARIABLE_ID),
+    (b"$config", CONFIG_VARIABLE_ID),
+];
 
 impl EngineState {
     pub fn new() -> Self {
@@ -145,12 +263,69 @@ impl EngineState {
             file_contents: im::vector![],
             vars: im::vector![Type::Unknown, Type::Unknown, Type::Unknown, Type::Unknown],
             decls: im::vector![],
+            decl_spans: im::vector![],
             blocks: im::vector![],
             scope: im::vector![ScopeFrame::new()],
+            visible_decls: Arc::new(HashMap::new()),
             ctrlc: None,
+            case_insensitive_lookup: false,
+            confirm: Arc::new(AlwaysYes),
+            output: Arc::new(StdOutput),
+            stash: Arc::new(Mutex::new(HashMap::new())),
+            regex_cache: Arc::new(RegexCache::default()),
+            warnings: Arc::new(Mutex::new(WarningSink::new())),
+            aborted: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Install the flag the Ctrl-C handler sets. Streams and commands check it to stop early
+    pub fn set_ctrlc(&mut self, ctrlc: Arc<AtomicBool>) {
+        self.ctrlc = Some(ctrlc);
+    }
+
+    /// Whether Ctrl-C was pressed since the flag was last reset
+    pub fn ctrlc_pressed(&self) -> bool {
+        self.ctrlc
+            .as_ref()
+            .map_or(false, |ctrlc| ctrlc.load(Ordering::SeqCst))
+    }
+
+    /// Clear the Ctrl-C flag, so that an interrupt of one pipeline doesn't stop the next. Done at
+    /// the start of each top-level pipeline. The flag is shared with the streams already started,
+    /// and their clones of the engine state
+    pub fn reset_ctrlc(&self) {
+        if let Some(ctrlc) = &self.ctrlc {
+            ctrlc.store(false, Ordering::SeqCst);
         }
     }
 
+    /// Raise a warning, to be shown after the pipeline's output. It doesn't affect the pipeline
+    pub fn warn(&self, warning: ShellWarning) {
+        self.warnings
+            .lock()
+            .expect("warning sink poisoned")
+            .push(warning);
+    }
+
+    /// The warnings raised since they were last taken, to be shown
+    pub fn take_warnings(&self) -> Vec<ShellWarning> {
+        self.warnings.lock().expect("warning sink poisoned").take()
+    }
+
+    /// Fail the pipeline with `error`, for a stream that has already been handed on and can only
+    /// stop. Whatever drains it takes the error with `take_abort`. The first error stands
+    pub fn abort(&self, error: ShellError) {
+        self.aborted
+            .lock()
+            .expect("abort slot poisoned")
+            .get_or_insert(error);
+    }
+
+    /// The error a stream was aborted with since this was last asked, to fail with in its place
+    pub fn take_abort(&self) -> Option<ShellError> {
+        self.aborted.lock().expect("abort slot poisoned").take()
+    }
+
     /// Merges a `StateDelta` onto the current state. These deltas come from a system, like the parser, that
     /// creates a new set of definitions and visible symbols in the current scope. We make this transactional
     /// as there are times when we want to run the parser and immediately throw away the results (namely:
@@ -158,30 +333,211 @@ impl EngineState {
     ///
     /// When we want to preserve what the parser has created, we can take its output (the `StateDelta`) and
     /// use this function to merge it into the global state.
-    pub fn merge_delta(&mut self, mut delta: StateDelta) {
+    pub fn merge_delta(&mut self, delta: StateDelta) {
+        let num_decls = self.num_decls();
+        let num_blocks = self.num_blocks();
+
         // Take the mutable reference and extend the permanent state from the working set
         self.files.extend(delta.files);
         self.file_contents.extend(delta.file_contents);
         self.decls.extend(delta.decls);
+        self.decl_spans.extend(delta.decl_spans);
         self.vars.extend(delta.vars);
         self.blocks.extend(delta.blocks);
+        for warning in delta.warnings {
+            self.warn(warning);
+        }
+
+        // What the delta brings has to point into the files, now that they're merged
+        if cfg!(debug_assertions) {
+            if let Err(err) = self.validate_spans_since(num_decls, num_blocks, &delta.scope) {
+                panic!("internal error: {:?}", err);
+            }
+        }
+
+        let mut new_decls = None;
+        let mut frames = delta.scope.into_iter();
+        if let (Some(last), Some(mut first)) = (self.scope.back_mut(), frames.next()) {
+            // Frames the delta still has open, eg because the parser bailed out of a block, are
+            // folded in too. Otherwise the decls they name would be added, but unreachable
+            for frame in frames {
+                first.absorb(frame);
+            }
+
+            // A delta that only adds decls, without hiding or showing the existing ones, doesn't
+            // change what the other names resolve to. Its decls can go straight into the cache,
+            // without flattening every frame again
+            if first.visibility.ids.values().all(|visible| *visible)
+                && !first.visibility.shows_any_below(num_decls)
+                && first
+                    .decl_name_visibility
+                    .ids
+                    .iter()
+                    .all(|(name, visible)| *visible && first.decls.contains_key(name))
+            {
+                new_decls = Some(first.decls.clone());
+            }
+            last.absorb(first);
+
+            if let Some(decls) = &new_decls {
+                if !decls.iter().all(|(name, decl_id)| {
+                    last.visibility.is_id_visible(decl_id)
+                        && last.decl_name_visibility.is_id_visible(name)
+                }) {
+                    new_decls = None;
+                }
+            }
+        }
+
+        match new_decls {
+            Some(new_decls) => Arc::make_mut(&mut self.visible_decls).extend(new_decls),
+            None => self.update_visible_decls(),
+        }
+    }
+
+    /// Flatten the scope frames into `visible_decls`
+    fn update_visible_decls(&mut self) {
+        let mut visibility = DeclVisibility::new();
+        let mut visible_decls = HashMap::new();
+
+        for scope in self.scope.iter().rev() {
+            visibility.append(scope);
+
+            for (name, decl_id) in &scope.decls {
+                if !visible_decls.contains_key(name) && visibility.is_visible(name, decl_id) {
+                    visible_decls.insert(name.clone(), *decl_id);
+                }
+            }
+        }
+
+        self.visible_decls = Arc::new(visible_decls);
+    }
+
+    /// Drop the decls and blocks nothing can reach any more, eg the ones of commands that were
+    /// defined again, and renumber the rest. They're reachable from the decls and modules the
+    /// scope frames make visible, and from the values in the frames' environment and the stash
+    ///
+    /// Everything holding ids has to be renumbered along, so this is only safe when nothing else
+    /// does: no clones of the engine state, working sets or deltas made from it, nor stacks with
+    /// blocks in their values (see `compact_with_stack`). Parse caches made before it don't fit
+    /// after, even if they still pass the size check
+    pub fn compact(&mut self) {
+        self.compact_reachable_from(None)
+    }
+
+    /// Like `compact`, also keeping the blocks in the values of the stack, and renumbering them
+    pub fn compact_with_stack(&mut self, stack: &mut Stack) {
+        self.compact_reachable_from(Some(stack))
+    }
+
+    fn compact_reachable_from(&mut self, stack: Option<&mut Stack>) {
+        let mut pending = vec![];
+
+        let mut visibility = Visibility::new();
+        let mut module_visibility = Visibility::new();
+        for frame in self.scope.iter().rev() {
+            visibility.append(&frame.visibility);
+            module_visibility.append(&frame.module_visibility);
+
+            for decl_id in frame.decls.values() {
+                if visibility.is_id_visible(decl_id) {
+                    pending.push(Reference::Decl(*decl_id));
+                }
+            }
+            for block_id in frame.modules.values() {
+                if module_visibility.is_id_visible(block_id) {
+                    pending.push(Reference::Block(*block_id));
+                }
+            }
+            for value in frame.env_vars.values() {
+                pending.extend(value_references(value));
+            }
+        }
+        for value in self.stash.lock().expect("stash poisoned").values() {
+            pending.extend(value_references(value));
+        }
+        if let Some(stack) = &stack {
+            for value in stack.vars.values().chain(stack.env_vars.values()) {
+                pending.extend(value_references(value));
+            }
+        }
 
-        if let Some(last) = self.scope.back_mut() {
-            let first = delta.scope.remove(0);
-            for item in first.decls.into_iter() {
-                last.decls.insert(item.0, item.1);
+        // Follow the references until everything reachable is marked
+        let mut kept_decls = vec![false; self.num_decls()];
+        let mut kept_blocks = vec![false; self.num_blocks()];
+        while let Some(reference) = pending.pop() {
+            match reference {
+                Reference::Decl(decl_id) => {
+                    if kept_decls.get(decl_id.get()) == Some(&false) {
+                        kept_decls[decl_id.get()] = true;
+                        if let Some(block_id) = self.decls[decl_id.get()].get_block_id() {
+                            pending.push(Reference::Block(block_id));
+                        }
+                    }
+                }
+                Reference::Block(block_id) => {
+                    if kept_blocks.get(block_id.get()) == Some(&false) {
+                        kept_blocks[block_id.get()] = true;
+                        pending.extend(block_references(&self.blocks[block_id.get()]));
+                    }
+                }
             }
-            for item in first.vars.into_iter() {
-                last.vars.insert(item.0, item.1);
+        }
+
+        let remap = IdRemap::new(&kept_decls, &kept_blocks);
+
+        let mut decls = im::Vector::new();
+        let mut decl_spans = im::Vector::new();
+        for ((decl, span), _) in self
+            .decls
+            .iter()
+            .zip(self.decl_spans.iter())
+            .zip(&kept_decls)
+            .filter(|(_, kept)| **kept)
+        {
+            decls.push_back(match decl.get_block_id() {
+                Some(block_id) => decl.signature().into_block_command(
+                    remap
+                        .block(block_id)
+                        .expect("internal error: a kept decl refers to a dropped block"),
+                ),
+                None => decl.clone(),
+            });
+            decl_spans.push_back(*span);
+        }
+
+        let mut blocks = im::Vector::new();
+        for (block, kept) in self.blocks.iter().zip(&kept_blocks) {
+            if *kept {
+                let mut block = block.clone();
+                block_ids_mut(&mut block, &mut |id| remap.renumber(id));
+                blocks.push_back(block);
             }
-            for item in first.aliases.into_iter() {
-                last.aliases.insert(item.0, item.1);
+        }
+
+        for frame in self.scope.iter_mut() {
+            frame.decls = remap_ids(frame.decls.drain(), |id| remap.decl(id));
+            frame.predecls = remap_ids(frame.predecls.drain(), |id| remap.decl(id));
+            frame.visibility.remap(|id| remap.decl(*id));
+            frame.modules = remap_ids(frame.modules.drain(), |id| remap.block(id));
+            frame.module_visibility.remap(|id| remap.block(*id));
+            for value in frame.env_vars.values_mut() {
+                value_ids_mut(value, &mut |id| remap.renumber(id));
             }
-            for item in first.modules.into_iter() {
-                last.modules.insert(item.0, item.1);
+        }
+        for value in self.stash.lock().expect("stash poisoned").values_mut() {
+            value_ids_mut(value, &mut |id| remap.renumber(id));
+        }
+        if let Some(stack) = stack {
+            for value in stack.vars.values_mut().chain(stack.env_vars.values_mut()) {
+                value_ids_mut(value, &mut |id| remap.renumber(id));
             }
-            last.visibility.merge_with(first.visibility);
         }
+
+        self.decls = decls;
+        self.decl_spans = decl_spans;
+        self.blocks = blocks;
+        self.update_visible_decls();
     }
 
     pub fn num_files(&self) -> usize {
@@ -226,13 +582,29 @@ impl EngineState {
     }
 
     pub fn find_decl(&self, name: &[u8]) -> Option<DeclId> {
-        let mut visibility: Visibility = Visibility::new();
+        if let Some(decl_id) = self.visible_decls.get(name) {
+            return Some(*decl_id);
+        }
+
+        if self.case_insensitive_lookup {
+            self.find_decl_ignoring_case(name)
+        } else {
+            None
+        }
+    }
 
+    /// Look up a decl by walking the scope frames, with `visibility` applied on top of theirs.
+    /// Slower than `find_decl`, but takes visibility changes that aren't merged yet into account
+    fn find_decl_with_visibility(
+        &self,
+        name: &[u8],
+        mut visibility: DeclVisibility,
+    ) -> Option<DeclId> {
         for scope in self.scope.iter().rev() {
-            visibility.append(&scope.visibility);
+            visibility.append(scope);
 
             if let Some(decl_id) = scope.decls.get(name) {
-                if visibility.is_id_visible(decl_id) {
+                if visibility.is_visible(name, decl_id) {
                     return Some(*decl_id);
                 }
             }
@@ -241,12 +613,78 @@ impl EngineState {
         None
     }
 
+    /// The slow path of `find_decl`, only taken after the exact lookup missed
+    fn find_decl_ignoring_case(&self, name: &[u8]) -> Option<DeclId> {
+        let mut visibility = DeclVisibility::new();
+
+        for scope in self.scope.iter().rev() {
+            visibility.append(scope);
+
+            if let Some(decl_id) = get_ignoring_case(&scope.decls, name, |decl_name, decl_id| {
+                visibility.is_visible(decl_name, decl_id)
+            }) {
+                return Some(*decl_id);
+            }
+        }
+
+        None
+    }
+
+    /// Look up a decl the way `find_decl` does, and if that misses, again ignoring ASCII case and
+    /// taking `-`, `_` and spaces for one another, eg to find `str downcase` for `Str_Downcase`.
+    /// Returns the name the decl is known by too, so that the caller can point the user to the
+    /// right spelling rather than run it
+    pub fn find_decl_relaxed(&self, name: &[u8]) -> Option<(DeclId, Vec<u8>)> {
+        if let Some(decl_id) = self.find_decl(name) {
+            let known_as = self.find_decl_name(decl_id).unwrap_or(name);
+            return Some((decl_id, known_as.to_vec()));
+        }
+
+        let mut visibility = DeclVisibility::new();
+
+        for scope in self.scope.iter().rev() {
+            visibility.append(scope);
+
+            if let Some((decl_name, decl_id)) =
+                get_relaxed(&scope.decls, name, |decl_name, decl_id| {
+                    visibility.is_visible(decl_name, decl_id)
+                })
+            {
+                return Some((*decl_id, decl_name.clone()));
+            }
+        }
+
+        None
+    }
+
+    /// The name a decl can be called by, which isn't always the name it was defined with (eg, after
+    /// `use`). If it can be called by several, the innermost binding wins
+    pub fn find_decl_name(&self, decl_id: DeclId) -> Option<&[u8]> {
+        for scope in self.scope.iter().rev() {
+            if let Some(name) = name_in_frame(scope, decl_id, |name| self.find_decl(name)) {
+                return Some(name);
+            }
+        }
+
+        None
+    }
+
     pub fn find_commands_by_prefix(&self, name: &[u8]) -> Vec<Vec<u8>> {
+        let output = self.commands_by_prefix(name, false);
+
+        if output.is_empty() && self.case_insensitive_lookup {
+            self.commands_by_prefix(name, true)
+        } else {
+            output
+        }
+    }
+
+    fn commands_by_prefix(&self, name: &[u8], ignore_case: bool) -> Vec<Vec<u8>> {
         let mut output = vec![];
 
         for scope in self.scope.iter().rev() {
             for decl in &scope.decls {
-                if decl.0.starts_with(name) {
+                if has_prefix(decl.0, name, ignore_case) {
                     output.push(decl.0.clone());
                 }
             }
@@ -255,36 +693,222 @@ impl EngineState {
         output
     }
 
-    pub fn get_span_contents(&self, span: &Span) -> &[u8] {
-        for (contents, start, finish) in &self.file_contents {
-            if span.start >= *start && span.end <= *finish {
-                return &contents[(span.start - start)..(span.end - start)];
+    pub fn find_variable(&self, name: &[u8]) -> Option<VarId> {
+        let mut visibility: Visibility<VarId> = Visibility::new();
+
+        for scope in self.scope.iter().rev() {
+            visibility.append(&scope.var_visibility);
+
+            if let Some(var_id) = scope.vars.get(name) {
+                if visibility.is_id_visible(var_id) {
+                    return Some(*var_id);
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn find_alias(&self, name: &[u8]) -> Option<&Alias> {
+        let mut visibility: Visibility<Vec<u8>> = Visibility::new();
+
+        for scope in self.scope.iter().rev() {
+            visibility.append(&scope.alias_visibility);
+
+            if let Some(alias) = scope.aliases.get(name) {
+                if visibility.is_id_visible(&name.to_vec()) {
+                    return Some(alias);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// The replacement of a visible alias as text, eg `ls -la` for `alias ll = ls -la`
+    pub fn get_alias_expansion(&self, name: &[u8]) -> Option<String> {
+        let alias = self.find_alias(name)?;
+
+        Some(render_alias(alias, |span| {
+            self.try_get_span_contents(&span)
+        }))
+    }
+
+    /// The visible aliases with their replacements as text, sorted by name
+    pub fn list_aliases(&self) -> Vec<(Vec<u8>, String)> {
+        let names: BTreeSet<&Vec<u8>> = self
+            .scope
+            .iter()
+            .flat_map(|scope| scope.aliases.keys())
+            .collect();
+
+        names
+            .into_iter()
+            .filter_map(|name| Some((name.clone(), self.get_alias_expansion(name)?)))
+            .collect()
+    }
+
+    pub fn find_module(&self, name: &[u8]) -> Option<BlockId> {
+        let mut visibility: Visibility<BlockId> = Visibility::new();
+
+        for scope in self.scope.iter().rev() {
+            visibility.append(&scope.module_visibility);
+
+            if let Some(block_id) = scope.modules.get(name) {
+                if visibility.is_id_visible(block_id) {
+                    return Some(*block_id);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// The names of the visible modules, sorted and without duplicates, eg for completions
+    pub fn get_module_names(&self) -> Vec<Vec<u8>> {
+        let names: BTreeSet<&Vec<u8>> = self
+            .scope
+            .iter()
+            .flat_map(|scope| scope.modules.keys())
+            .collect();
+
+        names
+            .into_iter()
+            .filter(|name| self.find_module(name).is_some())
+            .cloned()
+            .collect()
+    }
+
+    pub fn find_env_var(&self, name: &[u8]) -> Option<&Value> {
+        for scope in self.scope.iter().rev() {
+            if let Some(value) = scope.env_vars.get(name) {
+                return Some(value);
+            }
+        }
+
+        None
+    }
+
+    /// The source code a span covers. Spans that outlived the files they point into, eg because
+    /// they were kept from another engine state, are an error
+    pub fn try_get_span_contents(&self, span: &Span) -> Result<&[u8], ShellError> {
+        let file = last_file_starting_at(self.file_contents.len(), span.start, |idx| {
+            self.file_contents[idx].1
+        });
+
+        match file.map(|idx| &self.file_contents[idx]) {
+            Some((contents, start, finish)) if span.end <= *finish && span.start <= span.end => {
+                Ok(&contents[(span.start - start)..(span.end - start)])
             }
+            _ => Err(ShellError::SpanNotFound(span.start, span.end)),
         }
+    }
 
-        panic!("internal error: span missing in file contents cache")
+    /// The source code a span covers, for spans that are known to be from this engine state
+    pub fn get_span_contents(&self, span: &Span) -> &[u8] {
+        self.try_get_span_contents(span)
+            .expect("internal error: span missing in file contents cache")
     }
 
     pub fn get_var(&self, var_id: VarId) -> &Type {
         self.vars
-            .get(var_id)
+            .get(var_id.get())
             .expect("internal error: missing variable")
     }
 
     #[allow(clippy::borrowed_box)]
     pub fn get_decl(&self, decl_id: DeclId) -> &Box<dyn Command> {
         self.decls
-            .get(decl_id)
+            .get(decl_id.get())
             .expect("internal error: missing declaration")
     }
 
-    pub fn get_signatures(&self) -> Vec<Signature> {
+    /// The source a decl was defined by, eg the whole `def` of a custom command
+    pub fn get_decl_span(&self, decl_id: DeclId) -> Option<Span> {
+        self.decl_spans.get(decl_id.get()).copied().flatten()
+    }
+
+    /// The source text of a block, braces included, if it was parsed from one
+    pub fn get_block_source(&self, block_id: BlockId) -> Option<String> {
+        let span = self.get_block(block_id).span?;
+
+        self.try_get_span_contents(&span)
+            .ok()
+            .map(|contents| String::from_utf8_lossy(contents).to_string())
+    }
+
+    /// Check that the spans kept for later, those of aliases, custom commands and parsed blocks,
+    /// point into the files. Fails with the first one that doesn't
+    pub fn validate_spans(&self) -> Result<(), ShellError> {
+        let frames: Vec<&ScopeFrame> = self.scope.iter().collect();
+        self.validate_spans_since(0, 0, frames)
+    }
+
+    /// `validate_spans` for the decls and blocks from `first_decl` and `first_block` on, and the
+    /// aliases of `frames`
+    fn validate_spans_since<'f>(
+        &self,
+        first_decl: usize,
+        first_block: usize,
+        frames: impl IntoIterator<Item = &'f ScopeFrame>,
+    ) -> Result<(), ShellError> {
+        let check = |span: Span, what: &dyn Fn() -> String| {
+            if span == Span::unknown() || self.try_get_span_contents(&span).is_err() {
+                Err(ShellError::InternalError(format!(
+                    "{} has the span {}..{}, which isn't in any file",
+                    what(),
+                    span.start,
+                    span.end
+                )))
+            } else {
+                Ok(())
+            }
+        };
+
+        for frame in frames {
+            for (name, alias) in &frame.aliases {
+                for span in &alias.replacement {
+                    check(*span, &|| {
+                        format!("the alias '{}'", String::from_utf8_lossy(name))
+                    })?;
+                }
+            }
+        }
+
+        // Indexing, since skipping to the first one would walk all of the others
+        for decl_id in first_decl..self.num_decls() {
+            if let Some(span) = self.decl_spans[decl_id] {
+                check(span, &|| {
+                    format!("the definition of '{}'", self.decls[decl_id].name())
+                })?;
+            }
+        }
+
+        for block_id in first_block..self.num_blocks() {
+            for stmt in &self.blocks[block_id].stmts {
+                if let Statement::Pipeline(pipeline) = stmt {
+                    for expr in &pipeline.expressions {
+                        check(expr.span, &|| {
+                            format!("an expression of block {}", block_id)
+                        })?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The signatures of the declarations, with their usage. Custom commands, the ones defined
+    /// with `def`, are only included when asked for
+    pub fn get_signatures(&self, include_custom: bool) -> Vec<Signature> {
         let mut output = vec![];
-        for decl in self.decls.iter() {
-            if decl.get_block_id().is_none() {
+        for decl in self.decls.iter().filter(|decl| !decl.is_private()) {
+            if include_custom || decl.get_block_id().is_none() {
                 let mut signature = (*decl).signature();
                 signature.usage = decl.usage().to_string();
                 signature.extra_usage = decl.extra_usage().to_string();
+                signature.deprecated = decl.deprecated();
 
                 output.push(signature);
             }
@@ -293,13 +917,17 @@ impl EngineState {
         output
     }
 
-    pub fn get_signatures_with_examples(&self) -> Vec<(Signature, Vec<Example>)> {
+    pub fn get_signatures_with_examples(
+        &self,
+        include_custom: bool,
+    ) -> Vec<(Signature, Vec<Example>)> {
         let mut output = vec![];
-        for decl in self.decls.iter() {
-            if decl.get_block_id().is_none() {
+        for decl in self.decls.iter().filter(|decl| !decl.is_private()) {
+            if include_custom || decl.get_block_id().is_none() {
                 let mut signature = (*decl).signature();
                 signature.usage = decl.usage().to_string();
                 signature.extra_usage = decl.extra_usage().to_string();
+                signature.deprecated = decl.deprecated();
 
                 output.push((signature, decl.examples()));
             }
@@ -310,7 +938,7 @@ impl EngineState {
 
     pub fn get_block(&self, block_id: BlockId) -> &Block {
         self.blocks
-            .get(block_id)
+            .get(block_id.get())
             .expect("internal error: missing block")
     }
 
@@ -322,30 +950,25 @@ impl EngineState {
         }
     }
 
-    pub fn files(&self) -> impl Iterator<Item = &(String, usize, usize)> {
+    pub fn files(&self) -> impl Iterator<Item = &File> {
         self.files.iter()
     }
 
+    pub fn get_file(&self, file_id: usize) -> Option<&File> {
+        self.files.get(file_id)
+    }
+
     pub fn get_filename(&self, file_id: usize) -> String {
-        for file in self.files.iter().enumerate() {
-            if file.0 == file_id {
-                return file.1 .0.clone();
-            }
+        match self.get_file(file_id) {
+            Some(file) => file.name.clone(),
+            None => "<unknown>".into(),
         }
-
-        "<unknown>".into()
     }
 
     pub fn get_file_source(&self, file_id: usize) -> String {
-        for file in self.files.iter().enumerate() {
-            if file.0 == file_id {
-                let contents = self.get_span_contents(&Span {
-                    start: file.1 .1,
-                    end: file.1 .2,
-                });
-                let output = String::from_utf8_lossy(contents).to_string();
-
-                return output;
+        if let Some(file) = self.get_file(file_id) {
+            if let Ok(contents) = self.try_get_span_contents(&file.span) {
+                return String::from_utf8_lossy(contents).to_string();
             }
         }
 
@@ -354,14 +977,30 @@ impl EngineState {
 
     #[allow(unused)]
     pub(crate) fn add_file(&mut self, filename: String, contents: Vec<u8>) -> usize {
+        self.add_file_with_source(filename, contents, FileSource::Internal)
+    }
+
+    #[allow(unused)]
+    pub(crate) fn add_file_with_source(
+        &mut self,
+        filename: String,
+        contents: Vec<u8>,
+        source: FileSource,
+    ) -> usize {
         let next_span_start = self.next_span_start();
         let next_span_end = next_span_start + contents.len();
 
         self.file_contents
             .push_back((contents, next_span_start, next_span_end));
 
-        self.files
-            .push_back((filename, next_span_start, next_span_end));
+        self.files.push_back(File::new(
+            filename,
+            Span {
+                start: next_span_start,
+                end: next_span_end,
+            },
+            source,
+        ));
 
         self.num_files() - 1
     }
@@ -387,12 +1026,16 @@ pub struct StateWorkingSet<'a> {
 /// can be applied to the global state to update it to contain both previous state and the state held
 /// within the delta.
 pub struct StateDelta {
-    files: Vec<(String, usize, usize)>,
+    base: DeltaBase,
+    files: Vec<File>,
     pub(crate) file_contents: Vec<(Vec<u8>, usize, usize)>,
-    vars: Vec<Type>,              // indexed by VarId
-    decls: Vec<Box<dyn Command>>, // indexed by DeclId
-    blocks: Vec<Block>,           // indexed by BlockId
+    vars: Vec<Type>,               // indexed by VarId
+    decls: Vec<Box<dyn Command>>,  // indexed by DeclId
+    decl_spans: Vec<Option<Span>>, // indexed by DeclId
+    blocks: Vec<Block>,            // indexed by BlockId
     pub scope: Vec<ScopeFrame>,
+    /// Raised by the parser, they're raised on the engine state when the delta is merged
+    warnings: Vec<ShellWarning>,
 }
 
 impl StateDelta {
@@ -412,8 +1055,180 @@ impl StateDelta {
         self.scope.push(ScopeFrame::new());
     }
 
+    /// Leave the innermost scope. The predeclarations it still holds never got their definition,
+    /// eg because parsing failed before it, so nothing is left that can refer to them. Those at the
+    /// end of the delta are removed, the others keep their ids for the decls after them, as
+    /// tombstones that aren't listed anywhere
     pub fn exit_scope(&mut self) {
-        self.scope.pop();
+        let frame = match self.scope.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        let mut abandoned: Vec<usize> = frame
+            .predecls
+            .values()
+            .filter_map(|decl_id| decl_id.get().checked_sub(self.base.num_decls))
+            .collect();
+        abandoned.sort_unstable();
+
+        for idx in abandoned.into_iter().rev() {
+            if idx + 1 == self.decls.len() {
+                self.decls.pop();
+                self.decl_spans.pop();
+            } else {
+                let name = self.decls[idx].name().to_string();
+                self.decls[idx] = Box::new(AbandonedDecl { name });
+                self.decl_spans[idx] = None;
+            }
+        }
+    }
+}
+
+/// What's left of a predeclaration that never got its definition, when later decls need its id
+/// to stay taken
+#[derive(Clone)]
+struct AbandonedDecl {
+    name: String,
+}
+
+impl Command for AbandonedDecl {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn usage(&self) -> &str {
+        ""
+    }
+
+    fn run(
+        &self,
+        _engine_state: &EngineState,
+        _stack: &mut Stack,
+        _call: &Call,
+        _input: PipelineData,
+    ) -> Result<PipelineData, ShellError> {
+        Err(ShellError::InternalError(format!(
+            "{} was never defined",
+            self.name
+        )))
+    }
+
+    fn is_private(&self) -> bool {
+        true
+    }
+}
+
+/// How much the engine state held when a delta was started. The ids and spans in the delta carry
+/// on from there, so the delta only fits onto an engine state of that size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct DeltaBase {
+    num_files: usize,
+    num_vars: usize,
+    num_decls: usize,
+    num_blocks: usize,
+    next_span_start: usize,
+}
+
+impl DeltaBase {
+    fn of(engine_state: &EngineState) -> Self {
+        DeltaBase {
+            num_files: engine_state.num_files(),
+            num_vars: engine_state.num_vars(),
+            num_decls: engine_state.num_decls(),
+            num_blocks: engine_state.num_blocks(),
+            next_span_start: engine_state.next_span_start(),
+        }
+    }
+}
+
+/// What a parse cache keeps of a delta
+#[derive(Serialize, Deserialize)]
+struct CachedDelta {
+    base: DeltaBase,
+    files: Vec<File>,
+    file_contents: Vec<(Vec<u8>, usize, usize)>,
+    vars: Vec<Type>,
+    decls: Vec<CachedDecl>,
+    decl_spans: Vec<Option<Span>>,
+    blocks: Vec<Block>,
+    scope: Vec<ScopeFrame>,
+}
+
+/// A decl of a cached delta. Custom commands are kept as their signature and block. Builtins have
+/// no block to keep, so only their name is, to say which one keeps the delta from being loaded
+#[derive(Serialize, Deserialize)]
+enum CachedDecl {
+    Custom(Signature, BlockId),
+    Builtin(String),
+}
+
+impl StateDelta {
+    /// The delta as a blob for a parse cache, to load with [`StateDelta::deserialize`] instead of
+    /// parsing the same source again. Only custom commands can be loaded back, so a delta that adds
+    /// builtins can't be cached
+    pub fn serialize(&self) -> Vec<u8> {
+        let cached = CachedDelta {
+            base: self.base,
+            files: self.files.clone(),
+            file_contents: self.file_contents.clone(),
+            vars: self.vars.clone(),
+            decls: self
+                .decls
+                .iter()
+                .map(|decl| match decl.get_block_id() {
+                    Some(block_id) => CachedDecl::Custom(decl.signature(), block_id),
+                    None => CachedDecl::Builtin(decl.name().to_string()),
+                })
+                .collect(),
+            decl_spans: self.decl_spans.clone(),
+            blocks: self.blocks.clone(),
+            scope: self.scope.clone(),
+        };
+
+        bincode::serialize(&cached).expect("internal error: a delta is plain data")
+    }
+
+    /// Load a delta from a parse cache. It has to be merged onto an engine state the size of the
+    /// one it was parsed against, or its ids would point at the wrong decls, blocks and files
+    pub fn deserialize(bytes: &[u8], engine_state: &EngineState) -> Result<StateDelta, ShellError> {
+        let cached: CachedDelta = bincode::deserialize(bytes)
+            .map_err(|err| ShellError::StaleParseCache(format!("it can't be read ({})", err)))?;
+
+        let base = DeltaBase::of(engine_state);
+        if cached.base != base {
+            return Err(ShellError::StaleParseCache(format!(
+                "it was parsed after {} commands and {} blocks, but there are {} commands and {} \
+                 blocks now",
+                cached.base.num_decls, cached.base.num_blocks, base.num_decls, base.num_blocks
+            )));
+        }
+
+        let decls = cached
+            .decls
+            .into_iter()
+            .map(|decl| match decl {
+                CachedDecl::Custom(signature, block_id) => {
+                    Ok(signature.into_block_command(block_id))
+                }
+                CachedDecl::Builtin(name) => Err(ShellError::StaleParseCache(format!(
+                    "it adds the builtin command `{}`, which can't be loaded back",
+                    name
+                ))),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(StateDelta {
+            base,
+            files: cached.files,
+            file_contents: cached.file_contents,
+            vars: cached.vars,
+            decls,
+            decl_spans: cached.decl_spans,
+            blocks: cached.blocks,
+            scope: cached.scope,
+            warnings: vec![],
+        })
     }
 }
 
@@ -421,12 +1236,15 @@ impl<'a> StateWorkingSet<'a> {
     pub fn new(permanent_state: &'a EngineState) -> Self {
         Self {
             delta: StateDelta {
+                base: DeltaBase::of(permanent_state),
                 files: vec![],
                 file_contents: vec![],
                 vars: vec![],
                 decls: vec![],
+                decl_spans: vec![],
                 blocks: vec![],
                 scope: vec![ScopeFrame::new()],
+                warnings: vec![],
             },
             permanent_state,
         }
@@ -445,10 +1263,16 @@ impl<'a> StateWorkingSet<'a> {
     }
 
     pub fn add_decl(&mut self, decl: Box<dyn Command>) -> DeclId {
+        self.add_decl_with_span(decl, None)
+    }
+
+    /// Add a decl, with the source that defines it if it has one
+    pub fn add_decl_with_span(&mut self, decl: Box<dyn Command>, span: Option<Span>) -> DeclId {
         let name = decl.name().as_bytes().to_vec();
 
         self.delta.decls.push(decl);
-        let decl_id = self.num_decls() - 1;
+        self.delta.decl_spans.push(span);
+        let decl_id = DeclId::new(self.num_decls() - 1);
 
         let scope_frame = self
             .delta
@@ -456,17 +1280,50 @@ impl<'a> StateWorkingSet<'a> {
             .last_mut()
             .expect("internal error: missing required scope frame");
 
+        scope_frame.decl_name_visibility.use_id(&name);
         scope_frame.decls.insert(name, decl_id);
         scope_frame.visibility.use_id(&decl_id);
 
         decl_id
     }
 
-    pub fn add_predecl(&mut self, decl: Box<dyn Command>) -> Option<DeclId> {
-        let name = decl.name().as_bytes().to_vec();
-
+    /// Define `name` as `decl`, defined by the source at `span` if it has one. When the innermost
+    /// scope of the working set binds exactly `name` to a decl of the working set, its
+    /// implementation and span are swapped in place and it keeps its id, so what already refers
+    /// to it, like a parsed call or an alias, runs the new definition. Otherwise `decl` is added,
+    /// shadowing any older `name`, eg the one of an outer scope, which stays as it was
+    pub fn replace_decl(
+        &mut self,
+        name: &[u8],
+        decl: Box<dyn Command>,
+        span: Option<Span>,
+    ) -> DeclId {
+        let num_permanent_decls = self.permanent_state.num_decls();
+        let bound = self
+            .delta
+            .scope
+            .last()
+            .and_then(|frame| frame.decls.get(name))
+            .copied();
+
+        match bound {
+            Some(decl_id) if decl_id.get() >= num_permanent_decls => {
+                *self.get_decl_mut(decl_id) = decl;
+                self.delta.decl_spans[decl_id.get() - num_permanent_decls] = span;
+                decl_id
+            }
+            _ => self.add_decl_with_span(decl, span),
+        }
+    }
+
+    /// Declare a decl that's defined by the source at `span`, without making it visible yet.
+    /// See `merge_predecl`
+    pub fn add_predecl(&mut self, decl: Box<dyn Command>, span: Span) -> Option<DeclId> {
+        let name = decl.name().as_bytes().to_vec();
+
         self.delta.decls.push(decl);
-        let decl_id = self.num_decls() - 1;
+        self.delta.decl_spans.push(Some(span));
+        let decl_id = DeclId::new(self.num_decls() - 1);
 
         let scope_frame = self
             .delta
@@ -485,6 +1342,7 @@ impl<'a> StateWorkingSet<'a> {
             .expect("internal error: missing required scope frame");
 
         if let Some(decl_id) = scope_frame.predecls.remove(name) {
+            scope_frame.decl_name_visibility.use_id(&name.to_vec());
             scope_frame.decls.insert(name.into(), decl_id);
             scope_frame.visibility.use_id(&decl_id);
 
@@ -495,11 +1353,11 @@ impl<'a> StateWorkingSet<'a> {
     }
 
     pub fn hide_decl(&mut self, name: &[u8]) -> Option<DeclId> {
-        let mut visibility: Visibility = Visibility::new();
+        let mut visibility = DeclVisibility::new();
 
         // Since we can mutate scope frames in delta, remove the id directly
         for scope in self.delta.scope.iter_mut().rev() {
-            visibility.append(&scope.visibility);
+            visibility.append(scope);
 
             if let Some(decl_id) = scope.decls.remove(name) {
                 return Some(decl_id);
@@ -514,10 +1372,10 @@ impl<'a> StateWorkingSet<'a> {
             .expect("internal error: missing required scope frame");
 
         for scope in self.permanent_state.scope.iter().rev() {
-            visibility.append(&scope.visibility);
+            visibility.append(scope);
 
             if let Some(decl_id) = scope.decls.get(name) {
-                if visibility.is_id_visible(decl_id) {
+                if visibility.is_visible(name, decl_id) {
                     // Hide decl only if it's not already hidden
                     last_scope_frame.visibility.hide_id(decl_id);
                     return Some(*decl_id);
@@ -528,17 +1386,168 @@ impl<'a> StateWorkingSet<'a> {
         None
     }
 
+    /// Hide an alias. Aliases in the delta are removed, the ones in the permanent state are hidden
+    /// from the current scope frame on. Returns whether there was an alias to hide
+    pub fn hide_alias(&mut self, name: &[u8]) -> bool {
+        let mut visibility: Visibility<Vec<u8>> = Visibility::new();
+        let name = name.to_vec();
+
+        // Since we can mutate scope frames in delta, remove the alias directly
+        for scope in self.delta.scope.iter_mut().rev() {
+            visibility.append(&scope.alias_visibility);
+
+            if scope.aliases.remove(&name).is_some() {
+                return true;
+            }
+        }
+
+        // We cannot mutate the permanent state => store the information in the current scope frame
+        let last_scope_frame = self
+            .delta
+            .scope
+            .last_mut()
+            .expect("internal error: missing required scope frame");
+
+        for scope in self.permanent_state.scope.iter().rev() {
+            visibility.append(&scope.alias_visibility);
+
+            if scope.aliases.contains_key(&name) && visibility.is_id_visible(&name) {
+                last_scope_frame.alias_visibility.hide_id(&name);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Hide a variable, eg `$x` or `x`. Variables in the delta are removed, the ones in the
+    /// permanent state are hidden from the current scope frame on
+    pub fn hide_variable(&mut self, name: &[u8]) -> Option<VarId> {
+        let mut visibility: Visibility<VarId> = Visibility::new();
+        let name = if name.starts_with(b"$") {
+            name.to_vec()
+        } else {
+            [b"$", name].concat()
+        };
+
+        // Since we can mutate scope frames in delta, remove the id directly
+        for scope in self.delta.scope.iter_mut().rev() {
+            visibility.append(&scope.var_visibility);
+
+            if let Some(var_id) = scope.vars.remove(&name) {
+                return Some(var_id);
+            }
+        }
+
+        // We cannot mutate the permanent state => store the information in the current scope frame
+        let last_scope_frame = self
+            .delta
+            .scope
+            .last_mut()
+            .expect("internal error: missing required scope frame");
+
+        for scope in self.permanent_state.scope.iter().rev() {
+            visibility.append(&scope.var_visibility);
+
+            if let Some(var_id) = scope.vars.get(&name) {
+                if visibility.is_id_visible(var_id) {
+                    last_scope_frame.var_visibility.hide_id(var_id);
+                    return Some(*var_id);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Hide a module from the current scope frame on. Unlike commands, modules in the delta are
+    /// hidden rather than removed, so that `unhide_module` can bring them back
+    pub fn hide_module(&mut self, name: &[u8]) -> Option<BlockId> {
+        let block_id = self.find_module(name)?;
+
+        self.delta
+            .scope
+            .last_mut()
+            .expect("internal error: missing required scope frame")
+            .module_visibility
+            .hide_id(&block_id);
+
+        Some(block_id)
+    }
+
+    /// Make a hidden module visible again from the current scope frame on, eg when it's `use`d.
+    /// Returns the innermost module with that name, hidden or not
+    pub fn unhide_module(&mut self, name: &[u8]) -> Option<BlockId> {
+        let block_id = self
+            .delta
+            .scope
+            .iter()
+            .rev()
+            .chain(self.permanent_state.scope.iter().rev())
+            .find_map(|scope| scope.modules.get(name))
+            .copied()?;
+
+        self.delta
+            .scope
+            .last_mut()
+            .expect("internal error: missing required scope frame")
+            .module_visibility
+            .use_id(&block_id);
+
+        Some(block_id)
+    }
+
+    /// Hide a command like `hide_decl`, but with an error for commands that are in scope and still
+    /// can't be hidden because they're only predeclared. Returns `Ok(None)` for names that aren't
+    /// known at all.
+    pub fn resolve_and_hide_decl(
+        &mut self,
+        name: &[u8],
+        span: Span,
+    ) -> Result<Option<DeclId>, ShellError> {
+        if let Some(decl_id) = self.hide_decl(name) {
+            return Ok(Some(decl_id));
+        }
+
+        let name_str = || String::from_utf8_lossy(name).to_string();
+
+        if self
+            .delta
+            .scope
+            .iter()
+            .any(|scope| scope.predecls.contains_key(name))
+        {
+            Err(ShellError::CantHidePredecl(name_str(), span))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub fn add_block(&mut self, block: Block) -> BlockId {
         self.delta.blocks.push(block);
 
-        self.num_blocks() - 1
+        BlockId::new(self.num_blocks() - 1)
+    }
+
+    /// Raise a warning about the source being parsed. It's raised on the engine state once the
+    /// delta is merged, so parses that are thrown away, like the highlighter's, don't raise any
+    pub fn warn(&mut self, warning: ShellWarning) {
+        // The parser may try the same span more than once, eg against several shapes
+        if !self.delta.warnings.contains(&warning) {
+            self.delta.warnings.push(warning);
+        }
+    }
+
+    /// The warnings raised while parsing so far
+    pub fn warnings(&self) -> &[ShellWarning] {
+        &self.delta.warnings
     }
 
     pub fn add_module(&mut self, name: &str, block: Block) -> BlockId {
         let name = name.as_bytes().to_vec();
 
         self.delta.blocks.push(block);
-        let block_id = self.num_blocks() - 1;
+        let block_id = BlockId::new(self.num_blocks() - 1);
 
         let scope_frame = self
             .delta
@@ -547,21 +1556,103 @@ impl<'a> StateWorkingSet<'a> {
             .expect("internal error: missing required scope frame");
 
         scope_frame.modules.insert(name, block_id);
+        scope_frame.module_visibility.use_id(&block_id);
 
         block_id
     }
 
-    pub fn activate_overlay(&mut self, overlay: Vec<(Vec<u8>, DeclId)>) {
+    pub fn activate_overlay(&mut self, overlay: Overlay) {
         let scope_frame = self
             .delta
             .scope
             .last_mut()
             .expect("internal error: missing required scope frame");
 
-        for (name, decl_id) in overlay {
+        for (name, decl_id) in overlay.decls {
+            scope_frame.decl_name_visibility.use_id(&name);
             scope_frame.decls.insert(name, decl_id);
             scope_frame.visibility.use_id(&decl_id);
         }
+
+        for (name, alias) in overlay.aliases {
+            scope_frame.alias_visibility.use_id(&name);
+            scope_frame.aliases.insert(name, alias);
+        }
+
+        scope_frame.env_vars.extend(overlay.env_vars);
+    }
+
+    /// Undo an `activate_overlay`. Names the current scope frame got from the overlay are removed,
+    /// and names that resolve to the overlay's decls and aliases in the permanent state are hidden
+    /// from here on. A name that was redefined after the overlay was activated resolves to
+    /// something else and is left alone. Environment variables can't be hidden, so the ones that
+    /// are already in the permanent state stay
+    pub fn deactivate_overlay(&mut self, overlay: &Overlay) {
+        for (name, decl_id) in &overlay.decls {
+            let scope_frame = self
+                .delta
+                .scope
+                .last_mut()
+                .expect("internal error: missing required scope frame");
+
+            match scope_frame.decls.get(name) {
+                Some(id) if id == decl_id => {
+                    scope_frame.decls.remove(name);
+                }
+                Some(_) => {}
+                None => {
+                    // Only this name goes, the decl can be bound to others too
+                    if self.find_decl(name) == Some(*decl_id)
+                        && self.permanent_state.find_decl(name) == Some(*decl_id)
+                    {
+                        self.delta
+                            .scope
+                            .last_mut()
+                            .expect("internal error: missing required scope frame")
+                            .decl_name_visibility
+                            .hide_id(name);
+                    }
+                }
+            }
+        }
+
+        for (name, alias) in &overlay.aliases {
+            let scope_frame = self
+                .delta
+                .scope
+                .last_mut()
+                .expect("internal error: missing required scope frame");
+
+            match scope_frame.aliases.get(name) {
+                Some(found) if found == alias => {
+                    scope_frame.aliases.remove(name);
+                }
+                Some(_) => {}
+                None => {
+                    if self.find_alias(name) == Some(alias)
+                        && self.permanent_state.find_alias(name) == Some(alias)
+                    {
+                        self.delta
+                            .scope
+                            .last_mut()
+                            .expect("internal error: missing required scope frame")
+                            .alias_visibility
+                            .hide_id(name);
+                    }
+                }
+            }
+        }
+
+        let scope_frame = self
+            .delta
+            .scope
+            .last_mut()
+            .expect("internal error: missing required scope frame");
+        for (name, value) in &overlay.env_vars {
+            if scope_frame.env_vars.get(name) == Some(value) {
+                scope_frame.env_vars.remove(name);
+            }
+        }
     }
 
     pub fn next_span_start(&self) -> usize {
@@ -578,37 +1669,55 @@ impl<'a> StateWorkingSet<'a> {
         self.permanent_state.next_span_start()
     }
 
-    pub fn files(&'a self) -> impl Iterator<Item = &(String, usize, usize)> {
+    pub fn files(&'a self) -> impl Iterator<Item = &File> {
         self.permanent_state.files().chain(self.delta.files.iter())
     }
 
-    pub fn get_filename(&self, file_id: usize) -> String {
-        for file in self.files().enumerate() {
-            if file.0 == file_id {
-                return file.1 .0.clone();
-            }
+    pub fn get_file(&self, file_id: usize) -> Option<&File> {
+        match file_id.checked_sub(self.permanent_state.num_files()) {
+            Some(delta_id) => self.delta.files.get(delta_id),
+            None => self.permanent_state.get_file(file_id),
         }
+    }
 
-        "<unknown>".into()
+    pub fn get_filename(&self, file_id: usize) -> String {
+        match self.get_file(file_id) {
+            Some(file) => file.name.clone(),
+            None => "<unknown>".into(),
+        }
     }
 
     pub fn get_file_source(&self, file_id: usize) -> String {
-        for file in self.files().enumerate() {
-            if file.0 == file_id {
-                let output = String::from_utf8_lossy(self.get_span_contents(Span {
-                    start: file.1 .1,
-                    end: file.1 .2,
-                }))
-                .to_string();
-
-                return output;
+        if let Some(file) = self.get_file(file_id) {
+            if let Ok(contents) = self.try_get_span_contents(file.span) {
+                return String::from_utf8_lossy(contents).to_string();
             }
         }
 
         "<unknown>".into()
     }
 
+    /// Add a file's contents to the ones spans can point into, and get its file id. The contents
+    /// are taken to be made up by the shell: see [`StateWorkingSet::add_file_with_source`] to say
+    /// where they came from
     pub fn add_file(&mut self, filename: String, contents: &[u8]) -> usize {
+        self.add_file_with_source(filename, contents, FileSource::Internal)
+    }
+
+    /// Add a file's contents, which came from `source`, to the ones spans can point into, and get
+    /// its file id. A file that's already there with the same name and contents, eg a script
+    /// sourced twice, isn't copied again: its id is returned, and its span range is the one to
+    /// parse against
+    pub fn add_file_with_source(
+        &mut self,
+        filename: String,
+        contents: &[u8],
+        source: FileSource,
+    ) -> usize {
+        if let Some(file_id) = self.find_file(&filename, contents) {
+            return file_id;
+        }
+
         let next_span_start = self.next_span_start();
         let next_span_end = next_span_start + contents.len();
 
@@ -616,26 +1725,63 @@ impl<'a> StateWorkingSet<'a> {
             .file_contents
             .push((contents.to_vec(), next_span_start, next_span_end));
 
-        self.delta
-            .files
-            .push((filename, next_span_start, next_span_end));
+        self.delta.files.push(File::new(
+            filename,
+            Span {
+                start: next_span_start,
+                end: next_span_end,
+            },
+            source,
+        ));
 
         self.num_files() - 1
     }
 
-    pub fn get_span_contents(&self, span: Span) -> &[u8] {
+    fn find_file(&self, filename: &str, contents: &[u8]) -> Option<usize> {
+        self.files()
+            .enumerate()
+            .find(|(_, file)| {
+                file.name == filename
+                    && file.span.end - file.span.start == contents.len()
+                    && matches!(
+                        self.try_get_span_contents(file.span),
+                        Ok(existing) if existing == contents
+                    )
+            })
+            .map(|(file_id, _)| file_id)
+    }
+
+    /// The span range a file's contents take up
+    pub fn get_span_for_file(&self, file_id: usize) -> Span {
+        self.get_file(file_id)
+            .expect("internal error: missing file")
+            .span
+    }
+
+    /// The source code a span covers, in either the permanent state or the files parsed since.
+    /// Spans that aren't in any of them, eg because they were kept from another engine state,
+    /// are an error
+    pub fn try_get_span_contents(&self, span: Span) -> Result<&[u8], ShellError> {
         let permanent_end = self.permanent_state.next_span_start();
-        if permanent_end <= span.start {
-            for (contents, start, finish) in &self.delta.file_contents {
-                if (span.start >= *start) && (span.end <= *finish) {
-                    return &contents[(span.start - start)..(span.end - start)];
-                }
+        if permanent_end > span.start {
+            return self.permanent_state.try_get_span_contents(&span);
+        }
+
+        let files = &self.delta.file_contents;
+        let file = last_file_starting_at(files.len(), span.start, |idx| files[idx].1);
+
+        match file.map(|idx| &files[idx]) {
+            Some((contents, start, finish)) if span.end <= *finish && span.start <= span.end => {
+                Ok(&contents[(span.start - start)..(span.end - start)])
             }
-        } else {
-            return self.permanent_state.get_span_contents(&span);
+            _ => Err(ShellError::SpanNotFound(span.start, span.end)),
         }
+    }
 
-        panic!("internal error: missing span contents in file cache")
+    /// The source code a span covers, for spans the parser made from this working set
+    pub fn get_span_contents(&self, span: Span) -> &[u8] {
+        self.try_get_span_contents(span)
+            .expect("internal error: missing span contents in file cache")
     }
 
     pub fn enter_scope(&mut self) {
@@ -647,10 +1793,10 @@ impl<'a> StateWorkingSet<'a> {
     }
 
     pub fn find_decl(&self, name: &[u8]) -> Option<DeclId> {
-        let mut visibility: Visibility = Visibility::new();
+        let mut visibility = DeclVisibility::new();
 
         for scope in self.delta.scope.iter().rev() {
-            visibility.append(&scope.visibility);
+            visibility.append(scope);
 
             if let Some(decl_id) = scope.predecls.get(name) {
                 return Some(*decl_id);
@@ -661,35 +1807,134 @@ impl<'a> StateWorkingSet<'a> {
             }
         }
 
+        // The permanent state knows which of its decls are visible, unless the delta hid the one
+        // it has or showed one it hides
+        let permanent_decls = self.permanent_state.num_decls();
+        match self.permanent_state.visible_decls.get(name) {
+            Some(decl_id) if visibility.is_visible(name, decl_id) => return Some(*decl_id),
+            None if !visibility.ids.shows_any_below(permanent_decls) => {}
+            _ => {
+                let found = self
+                    .permanent_state
+                    .find_decl_with_visibility(name, visibility);
+                if found.is_some() {
+                    return found;
+                }
+            }
+        }
+
+        if self.permanent_state.case_insensitive_lookup {
+            self.find_decl_ignoring_case(name)
+        } else {
+            None
+        }
+    }
+
+    /// The slow path of `find_decl`, only taken after the exact lookup missed
+    fn find_decl_ignoring_case(&self, name: &[u8]) -> Option<DeclId> {
+        let mut visibility = DeclVisibility::new();
+
+        for scope in self.delta.scope.iter().rev() {
+            visibility.append(scope);
+
+            if let Some(decl_id) = get_ignoring_case(&scope.predecls, name, |_, _| true) {
+                return Some(*decl_id);
+            }
+
+            if let Some(decl_id) = get_ignoring_case(&scope.decls, name, |_, _| true) {
+                return Some(*decl_id);
+            }
+        }
+
         for scope in self.permanent_state.scope.iter().rev() {
-            visibility.append(&scope.visibility);
+            visibility.append(scope);
 
-            if let Some(decl_id) = scope.decls.get(name) {
-                if visibility.is_id_visible(decl_id) {
-                    return Some(*decl_id);
-                }
+            if let Some(decl_id) = get_ignoring_case(&scope.decls, name, |decl_name, decl_id| {
+                visibility.is_visible(decl_name, decl_id)
+            }) {
+                return Some(*decl_id);
             }
         }
 
         None
     }
 
-    pub fn find_module(&self, name: &[u8]) -> Option<BlockId> {
+    /// See `EngineState::find_decl_relaxed`
+    pub fn find_decl_relaxed(&self, name: &[u8]) -> Option<(DeclId, Vec<u8>)> {
+        if let Some(decl_id) = self.find_decl(name) {
+            let known_as = self.find_decl_name(decl_id).unwrap_or(name);
+            return Some((decl_id, known_as.to_vec()));
+        }
+
+        let mut visibility = DeclVisibility::new();
+
         for scope in self.delta.scope.iter().rev() {
-            if let Some(block_id) = scope.modules.get(name) {
-                return Some(*block_id);
+            visibility.append(scope);
+
+            let found = get_relaxed(&scope.predecls, name, |_, _| true).or_else(|| {
+                get_relaxed(&scope.decls, name, |decl_name, decl_id| {
+                    visibility.is_visible(decl_name, decl_id)
+                })
+            });
+            if let Some((decl_name, decl_id)) = found {
+                return Some((*decl_id, decl_name.clone()));
             }
         }
 
         for scope in self.permanent_state.scope.iter().rev() {
+            visibility.append(scope);
+
+            if let Some((decl_name, decl_id)) =
+                get_relaxed(&scope.decls, name, |decl_name, decl_id| {
+                    visibility.is_visible(decl_name, decl_id)
+                })
+            {
+                return Some((*decl_id, decl_name.clone()));
+            }
+        }
+
+        None
+    }
+
+    pub fn find_module(&self, name: &[u8]) -> Option<BlockId> {
+        let mut visibility: Visibility<BlockId> = Visibility::new();
+
+        for scope in self
+            .delta
+            .scope
+            .iter()
+            .rev()
+            .chain(self.permanent_state.scope.iter().rev())
+        {
+            visibility.append(&scope.module_visibility);
+
             if let Some(block_id) = scope.modules.get(name) {
-                return Some(*block_id);
+                if visibility.is_id_visible(block_id) {
+                    return Some(*block_id);
+                }
             }
         }
 
         None
     }
 
+    /// The names of the visible modules, like `EngineState::get_module_names` but with the delta
+    pub fn get_module_names(&self) -> Vec<Vec<u8>> {
+        let names: BTreeSet<&Vec<u8>> = self
+            .permanent_state
+            .scope
+            .iter()
+            .chain(self.delta.scope.iter())
+            .flat_map(|scope| scope.modules.keys())
+            .collect();
+
+        names
+            .into_iter()
+            .filter(|name| self.find_module(name).is_some())
+            .cloned()
+            .collect()
+    }
+
     // pub fn update_decl(&mut self, decl_id: usize, block: Option<BlockId>) {
     //     let decl = self.get_decl_mut(decl_id);
     //     decl.body = block;
@@ -717,41 +1962,154 @@ impl<'a> StateWorkingSet<'a> {
 
     pub fn next_var_id(&self) -> VarId {
         let num_permanent_vars = self.permanent_state.num_vars();
-        num_permanent_vars + self.delta.vars.len()
+        VarId::new(num_permanent_vars + self.delta.vars.len())
     }
 
     pub fn find_variable(&self, name: &[u8]) -> Option<VarId> {
+        let mut visibility: Visibility<VarId> = Visibility::new();
+
         for scope in self.delta.scope.iter().rev() {
+            visibility.append(&scope.var_visibility);
+
             if let Some(var_id) = scope.vars.get(name) {
-                return Some(*var_id);
+                if visibility.is_id_visible(var_id) {
+                    return Some(*var_id);
+                }
             }
         }
 
         for scope in self.permanent_state.scope.iter().rev() {
+            visibility.append(&scope.var_visibility);
+
             if let Some(var_id) = scope.vars.get(name) {
-                return Some(*var_id);
+                if visibility.is_id_visible(var_id) {
+                    return Some(*var_id);
+                }
             }
         }
 
         None
     }
 
-    pub fn find_alias(&self, name: &[u8]) -> Option<&[Span]> {
+    /// The variables visible from here whose name starts with `prefix`, eg to complete a `$...`
+    /// word, sorted by name. Each name is the variable `find_variable` resolves it to, and the
+    /// variables the shell provides, like `$nu`, are always there
+    pub fn find_variables_by_prefix(&self, prefix: &[u8]) -> Vec<(Vec<u8>, VarId, Type)> {
+        let mut visibility: Visibility<VarId> = Visibility::new();
+        let mut seen = HashSet::new();
+        let mut output = vec![];
+
+        let frames = self
+            .delta
+            .scope
+            .iter()
+            .rev()
+            .chain(self.permanent_state.scope.iter().rev());
+        for scope in frames {
+            visibility.append(&scope.var_visibility);
+
+            for (name, var_id) in &scope.vars {
+                // A hidden variable leaves its name to the ones further out
+                if name.starts_with(prefix)
+                    && visibility.is_id_visible(var_id)
+                    && seen.insert(name.clone())
+                {
+                    output.push((name.clone(), *var_id));
+                }
+            }
+        }
+
+        for (name, var_id) in BUILTIN_VARIABLES {
+            if name.starts_with(prefix) && seen.insert(name.to_vec()) {
+                output.push((name.to_vec(), var_id));
+            }
+        }
+
+        output.sort();
+        output
+            .into_iter()
+            .map(|(name, var_id)| {
+                let ty = self.get_variable(var_id).clone();
+                (name, var_id, ty)
+            })
+            .collect()
+    }
+
+    pub fn find_env_var(&self, name: &[u8]) -> Option<&Value> {
         for scope in self.delta.scope.iter().rev() {
-            if let Some(spans) = scope.aliases.get(name) {
-                return Some(spans);
+            if let Some(value) = scope.env_vars.get(name) {
+                return Some(value);
+            }
+        }
+
+        self.permanent_state.find_env_var(name)
+    }
+
+    pub fn find_alias(&self, name: &[u8]) -> Option<&Alias> {
+        let mut visibility: Visibility<Vec<u8>> = Visibility::new();
+
+        for scope in self.delta.scope.iter().rev() {
+            visibility.append(&scope.alias_visibility);
+
+            if let Some(alias) = scope.aliases.get(name) {
+                if visibility.is_id_visible(&name.to_vec()) {
+                    return Some(alias);
+                }
             }
         }
 
         for scope in self.permanent_state.scope.iter().rev() {
-            if let Some(spans) = scope.aliases.get(name) {
-                return Some(spans);
+            visibility.append(&scope.alias_visibility);
+
+            if let Some(alias) = scope.aliases.get(name) {
+                if visibility.is_id_visible(&name.to_vec()) {
+                    return Some(alias);
+                }
             }
         }
 
         None
     }
 
+    /// An alias the way it was defined, eg `git checkout {}`
+    pub fn find_alias_template(&self, name: &[u8]) -> Option<String> {
+        let alias = self.find_alias(name)?;
+        let mut words: Vec<String> = alias
+            .replacement
+            .iter()
+            .map(|span| String::from_utf8_lossy(self.get_span_contents(*span)).to_string())
+            .collect();
+        if let Some(placeholder) = alias.placeholder {
+            words.insert(placeholder, "{}".into());
+        }
+
+        Some(words.join(" "))
+    }
+
+    /// The replacement of a visible alias as text, eg `ls -la` for `alias ll = ls -la`
+    pub fn get_alias_expansion(&self, name: &[u8]) -> Option<String> {
+        let alias = self.find_alias(name)?;
+
+        Some(render_alias(alias, |span| self.try_get_span_contents(span)))
+    }
+
+    /// The visible aliases, in the permanent state and the delta, with their replacements as text,
+    /// sorted by name
+    pub fn list_aliases(&self) -> Vec<(Vec<u8>, String)> {
+        let names: BTreeSet<&Vec<u8>> = self
+            .permanent_state
+            .scope
+            .iter()
+            .chain(self.delta.scope.iter())
+            .flat_map(|scope| scope.aliases.keys())
+            .collect();
+
+        names
+            .into_iter()
+            .filter_map(|name| Some((name.clone(), self.get_alias_expansion(name)?)))
+            .collect()
+    }
+
     pub fn add_variable(&mut self, mut name: Vec<u8>, ty: Type) -> VarId {
         let next_id = self.next_var_id();
 
@@ -767,39 +2125,51 @@ impl<'a> StateWorkingSet<'a> {
             .expect("internal error: missing stack frame");
 
         last.vars.insert(name, next_id);
+        last.var_visibility.use_id(&next_id);
 
         self.delta.vars.push(ty);
 
         next_id
     }
 
-    pub fn add_alias(&mut self, name: Vec<u8>, replacement: Vec<Span>) {
+    pub fn add_alias(&mut self, name: Vec<u8>, alias: Alias) {
+        let last = self
+            .delta
+            .scope
+            .last_mut()
+            .expect("internal error: missing stack frame");
+
+        last.alias_visibility.use_id(&name);
+        last.aliases.insert(name, alias);
+    }
+
+    pub fn add_env_var(&mut self, name: Vec<u8>, value: Value) {
         let last = self
             .delta
             .scope
             .last_mut()
             .expect("internal error: missing stack frame");
 
-        last.aliases.insert(name, replacement);
+        last.env_vars.insert(name, value);
     }
 
     pub fn set_variable_type(&mut self, var_id: VarId, ty: Type) {
         let num_permanent_vars = self.permanent_state.num_vars();
-        if var_id < num_permanent_vars {
+        if var_id.get() < num_permanent_vars {
             panic!("Internal error: attempted to set into permanent state from working set")
         } else {
-            self.delta.vars[var_id - num_permanent_vars] = ty;
+            self.delta.vars[var_id.get() - num_permanent_vars] = ty;
         }
     }
 
     pub fn get_variable(&self, var_id: VarId) -> &Type {
         let num_permanent_vars = self.permanent_state.num_vars();
-        if var_id < num_permanent_vars {
+        if var_id.get() < num_permanent_vars {
             self.permanent_state.get_var(var_id)
         } else {
             self.delta
                 .vars
-                .get(var_id - num_permanent_vars)
+                .get(var_id.get() - num_permanent_vars)
                 .expect("internal error: missing variable")
         }
     }
@@ -807,66 +2177,146 @@ impl<'a> StateWorkingSet<'a> {
     #[allow(clippy::borrowed_box)]
     pub fn get_decl(&self, decl_id: DeclId) -> &Box<dyn Command> {
         let num_permanent_decls = self.permanent_state.num_decls();
-        if decl_id < num_permanent_decls {
+        if decl_id.get() < num_permanent_decls {
             self.permanent_state.get_decl(decl_id)
         } else {
             self.delta
                 .decls
-                .get(decl_id - num_permanent_decls)
+                .get(decl_id.get() - num_permanent_decls)
                 .expect("internal error: missing declaration")
         }
     }
 
+    /// The source a decl was defined by, eg the whole `def` of a custom command
+    pub fn get_decl_span(&self, decl_id: DeclId) -> Option<Span> {
+        let num_permanent_decls = self.permanent_state.num_decls();
+        if decl_id.get() < num_permanent_decls {
+            self.permanent_state.get_decl_span(decl_id)
+        } else {
+            self.delta
+                .decl_spans
+                .get(decl_id.get() - num_permanent_decls)
+                .copied()
+                .flatten()
+        }
+    }
+
     pub fn get_decl_mut(&mut self, decl_id: DeclId) -> &mut Box<dyn Command> {
         let num_permanent_decls = self.permanent_state.num_decls();
-        if decl_id < num_permanent_decls {
+        if decl_id.get() < num_permanent_decls {
             panic!("internal error: can only mutate declarations in working set")
         } else {
             self.delta
                 .decls
-                .get_mut(decl_id - num_permanent_decls)
+                .get_mut(decl_id.get() - num_permanent_decls)
                 .expect("internal error: missing declaration")
         }
     }
 
+    pub fn find_decl_name(&self, decl_id: DeclId) -> Option<&[u8]> {
+        for scope in self.delta.scope.iter().rev() {
+            if let Some(name) = name_in_frame(scope, decl_id, |name| self.find_decl(name)) {
+                return Some(name);
+            }
+        }
+
+        for scope in self.permanent_state.scope.iter().rev() {
+            if let Some(name) = name_in_frame(scope, decl_id, |name| self.find_decl(name)) {
+                return Some(name);
+            }
+        }
+
+        None
+    }
+
     pub fn find_commands_by_prefix(&self, name: &[u8]) -> Vec<Vec<u8>> {
+        let output = self.commands_by_prefix(name, false);
+
+        if output.is_empty() && self.permanent_state.case_insensitive_lookup {
+            self.commands_by_prefix(name, true)
+        } else {
+            output
+        }
+    }
+
+    fn commands_by_prefix(&self, name: &[u8], ignore_case: bool) -> Vec<Vec<u8>> {
         let mut output = vec![];
 
         for scope in self.delta.scope.iter().rev() {
             for decl in &scope.decls {
-                if decl.0.starts_with(name) {
+                if has_prefix(decl.0, name, ignore_case) {
                     output.push(decl.0.clone());
                 }
             }
         }
 
-        let mut permanent = self.permanent_state.find_commands_by_prefix(name);
+        let mut permanent = self.permanent_state.commands_by_prefix(name, ignore_case);
 
         output.append(&mut permanent);
 
         output
     }
 
+    /// The visible decls whose name starts with `prefix`, with their signatures, sorted by name.
+    /// When a name is declared more than once, the decl `find_decl` would pick is the one listed
+    pub fn find_decls_with_signatures(&self, prefix: &[u8]) -> Vec<(Vec<u8>, Signature)> {
+        let delta_names = self
+            .delta
+            .scope
+            .iter()
+            .flat_map(|scope| scope.predecls.keys().chain(scope.decls.keys()));
+        let names: BTreeSet<&Vec<u8>> = self
+            .permanent_state
+            .scope
+            .iter()
+            .flat_map(|scope| scope.decls.keys())
+            .chain(delta_names)
+            .filter(|name| has_prefix(name, prefix, false))
+            .collect();
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let decl = self.get_decl(self.find_decl(name)?);
+                let mut signature = decl.signature();
+                signature.usage = decl.usage().to_string();
+                signature.extra_usage = decl.extra_usage().to_string();
+                signature.deprecated = decl.deprecated();
+
+                Some((name.clone(), signature))
+            })
+            .collect()
+    }
+
     pub fn get_block(&self, block_id: BlockId) -> &Block {
         let num_permanent_blocks = self.permanent_state.num_blocks();
-        if block_id < num_permanent_blocks {
+        if block_id.get() < num_permanent_blocks {
             self.permanent_state.get_block(block_id)
         } else {
             self.delta
                 .blocks
-                .get(block_id - num_permanent_blocks)
+                .get(block_id.get() - num_permanent_blocks)
                 .expect("internal error: missing block")
         }
     }
 
+    /// The source text of a block, braces included, if it was parsed from one
+    pub fn get_block_source(&self, block_id: BlockId) -> Option<String> {
+        let span = self.get_block(block_id).span?;
+
+        self.try_get_span_contents(span)
+            .ok()
+            .map(|contents| String::from_utf8_lossy(contents).to_string())
+    }
+
     pub fn get_block_mut(&mut self, block_id: BlockId) -> &mut Block {
         let num_permanent_blocks = self.permanent_state.num_blocks();
-        if block_id < num_permanent_blocks {
+        if block_id.get() < num_permanent_blocks {
             panic!("Attempt to mutate a block that is in the permanent (immutable) state")
         } else {
             self.delta
                 .blocks
-                .get_mut(block_id - num_permanent_blocks)
+                .get_mut(block_id.get() - num_permanent_blocks)
                 .expect("internal error: missing block")
         }
     }
@@ -888,26 +2338,25 @@ impl<'a> miette::SourceCode for &StateWorkingSet<'a> {
             let finding_span = "Finding span in StateWorkingSet";
             dbg!(finding_span, span);
         }
-        for (filename, start, end) in self.files() {
+        for file in self.files() {
+            let (start, end) = (file.span.start, file.span.end);
             if debugging {
-                dbg!(&filename, start, end);
+                dbg!(&file.name, start, end);
             }
-            if span.offset() >= *start && span.offset() + span.len() <= *end {
+            if span.offset() >= start && span.offset() + span.len() <= end {
                 if debugging {
                     let found_file = "Found matching file";
                     dbg!(found_file);
                 }
-                let our_span = Span {
-                    start: *start,
-                    end: *end,
-                };
                 // We need to move to a local span because we're only reading
                 // the specific file contents via self.get_span_contents.
-                let local_span = (span.offset() - *start, span.len()).into();
+                let local_span = (span.offset() - start, span.len()).into();
                 if debugging {
                     dbg!(&local_span);
                 }
-                let span_contents = self.get_span_contents(our_span);
+                let span_contents = self
+                    .try_get_span_contents(file.span)
+                    .map_err(|_| miette::MietteError::OutOfBounds)?;
                 if debugging {
                     dbg!(String::from_utf8_lossy(span_contents));
                 }
@@ -924,7 +2373,8 @@ impl<'a> miette::SourceCode for &StateWorkingSet<'a> {
                 }
 
                 let data = span_contents.data();
-                if filename == "<cli>" {
+                // What was typed at the prompt has no file name to show
+                if file.source == FileSource::Cli {
                     if debugging {
                         let success_cli = "Successfully read CLI span";
                         dbg!(success_cli, String::from_utf8_lossy(data));
@@ -942,7 +2392,7 @@ impl<'a> miette::SourceCode for &StateWorkingSet<'a> {
                         dbg!(success_file);
                     }
                     return Ok(Box::new(miette::MietteSpanContents::new_named(
-                        filename.clone(),
+                        file.name.clone(),
                         data,
                         retranslated,
                         span_contents.line(),
@@ -956,9 +2406,308 @@ impl<'a> miette::SourceCode for &StateWorkingSet<'a> {
     }
 }
 
+/// The index of the last of `len` files that starts at or before `offset`, given where each one
+/// starts. Files are added one after the other, so they are sorted by start and can be bisected
+fn last_file_starting_at(
+    len: usize,
+    offset: usize,
+    start_of: impl Fn(usize) -> usize,
+) -> Option<usize> {
+    let (mut low, mut high) = (0, len);
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if start_of(mid) <= offset {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    low.checked_sub(1)
+}
+
+/// Look up `name` in a scope's decls ignoring ASCII case. If several names only differ by case,
+/// the smallest one wins so that the result doesn't depend on the hash map's iteration order
+fn get_ignoring_case<'a>(
+    decls: &'a HashMap<Vec<u8>, DeclId>,
+    name: &[u8],
+    is_visible: impl Fn(&[u8], &DeclId) -> bool,
+) -> Option<&'a DeclId> {
+    decls
+        .iter()
+        .filter(|(decl_name, decl_id)| {
+            decl_name.eq_ignore_ascii_case(name) && is_visible(decl_name, decl_id)
+        })
+        .min_by_key(|(decl_name, _)| *decl_name)
+        .map(|(_, decl_id)| decl_id)
+}
+
+/// The words of an alias' replacement joined by spaces. A span that doesn't resolve any more is
+/// shown as `<missing>` rather than failing the whole alias
+fn render_alias<'a>(
+    alias: &Alias,
+    span_contents: impl Fn(Span) -> Result<&'a [u8], ShellError>,
+) -> String {
+    let words: Vec<String> = alias
+        .replacement
+        .iter()
+        .map(|span| match span_contents(*span) {
+            Ok(contents) => String::from_utf8_lossy(contents).to_string(),
+            Err(_) => "<missing>".into(),
+        })
+        .collect();
+
+    words.join(" ")
+}
+
+/// Look up `name` in a scope's decls ignoring ASCII case and taking `-`, `_` and spaces for one
+/// another, among the decls `is_visible` lets through. If several match, the smallest name wins,
+/// like in `get_ignoring_case`
+fn get_relaxed<'a>(
+    decls: &'a HashMap<Vec<u8>, DeclId>,
+    name: &[u8],
+    is_visible: impl Fn(&[u8], &DeclId) -> bool,
+) -> Option<(&'a Vec<u8>, &'a DeclId)> {
+    let fold = |byte: &u8| match byte {
+        b'-' | b'_' | b' ' => b'-',
+        byte => byte.to_ascii_lowercase(),
+    };
+
+    decls
+        .iter()
+        .filter(|(decl_name, decl_id)| {
+            decl_name.len() == name.len()
+                && decl_name.iter().map(fold).eq(name.iter().map(fold))
+                && is_visible(decl_name, decl_id)
+        })
+        .min_by_key(|(decl_name, _)| *decl_name)
+}
+
+/// A name the scope binds to `decl_id` that still resolves to it, ie it isn't hidden or shadowed.
+/// If there are several, the smallest one wins, like in `get_ignoring_case`
+fn name_in_frame(
+    scope: &ScopeFrame,
+    decl_id: DeclId,
+    find_decl: impl Fn(&[u8]) -> Option<DeclId>,
+) -> Option<&[u8]> {
+    scope
+        .decls
+        .iter()
+        .filter(|(name, id)| **id == decl_id && find_decl(name) == Some(decl_id))
+        .map(|(name, _)| name.as_slice())
+        .min()
+}
+
+fn has_prefix(decl_name: &[u8], prefix: &[u8], ignore_case: bool) -> bool {
+    if ignore_case {
+        decl_name.len() >= prefix.len() && decl_name[..prefix.len()].eq_ignore_ascii_case(prefix)
+    } else {
+        decl_name.starts_with(prefix)
+    }
+}
+
+/// A decl or block something in the engine state refers to
+enum Reference {
+    Decl(DeclId),
+    Block(BlockId),
+}
+
+/// An id in a block or value, to be read or renumbered
+enum IdMut<'a> {
+    Decl(&'a mut DeclId),
+    Block(&'a mut BlockId),
+}
+
+impl IdMut<'_> {
+    fn reference(&self) -> Reference {
+        match self {
+            IdMut::Decl(decl_id) => Reference::Decl(**decl_id),
+            IdMut::Block(block_id) => Reference::Block(**block_id),
+        }
+    }
+}
+
+/// The new ids of the decls and blocks `compact` keeps, by their old ids
+struct IdRemap {
+    decls: Vec<Option<DeclId>>,
+    blocks: Vec<Option<BlockId>>,
+}
+
+impl IdRemap {
+    fn new(kept_decls: &[bool], kept_blocks: &[bool]) -> Self {
+        Self {
+            decls: renumber_kept(kept_decls, DeclId::new),
+            blocks: renumber_kept(kept_blocks, BlockId::new),
+        }
+    }
+
+    fn decl(&self, decl_id: DeclId) -> Option<DeclId> {
+        self.decls.get(decl_id.get()).copied().flatten()
+    }
+
+    fn block(&self, block_id: BlockId) -> Option<BlockId> {
+        self.blocks.get(block_id.get()).copied().flatten()
+    }
+
+    /// Give a reference from something kept its new id. What's kept only refers to what's kept
+    fn renumber(&self, id: IdMut) {
+        match id {
+            IdMut::Decl(decl_id) => {
+                *decl_id = self
+                    .decl(*decl_id)
+                    .expect("internal error: something kept refers to a dropped decl")
+            }
+            IdMut::Block(block_id) => {
+                *block_id = self
+                    .block(*block_id)
+                    .expect("internal error: something kept refers to a dropped block")
+            }
+        }
+    }
+}
+
+/// Number what's kept from 0 up, in the order it was in
+fn renumber_kept<Id>(kept: &[bool], new_id: impl Fn(usize) -> Id) -> Vec<Option<Id>> {
+    let mut next = 0;
+
+    kept.iter()
+        .map(|kept| {
+            if *kept {
+                next += 1;
+                Some(new_id(next - 1))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn remap_ids<Id>(
+    ids: impl Iterator<Item = (Vec<u8>, Id)>,
+    new_id: impl Fn(Id) -> Option<Id>,
+) -> HashMap<Vec<u8>, Id> {
+    ids.filter_map(|(name, id)| Some((name, new_id(id)?)))
+        .collect()
+}
+
+/// The decls and blocks a block refers to directly
+fn block_references(block: &Block) -> Vec<Reference> {
+    let mut references = vec![];
+    // The walk is shared with the renumbering, which needs the ids mutably, so it goes over a copy
+    block_ids_mut(&mut block.clone(), &mut |id| {
+        references.push(id.reference())
+    });
+
+    references
+}
+
+fn value_references(value: &Value) -> Vec<Reference> {
+    let mut references = vec![];
+    value_ids_mut(&mut value.clone(), &mut |id| {
+        references.push(id.reference())
+    });
+
+    references
+}
+
+/// Call `f` with each decl and block id in the block, its own statements' and exports', but not
+/// the ones in the blocks it refers to
+fn block_ids_mut(block: &mut Block, f: &mut dyn FnMut(IdMut)) {
+    for stmt in &mut block.stmts {
+        match stmt {
+            Statement::Declaration(decl_id) => f(IdMut::Decl(decl_id)),
+            Statement::Pipeline(pipeline) => {
+                for expression in &mut pipeline.expressions {
+                    expression_ids_mut(expression, f);
+                }
+            }
+        }
+    }
+
+    for (_, decl_id) in &mut block.exports.decls {
+        f(IdMut::Decl(decl_id));
+    }
+    for (_, value) in &mut block.exports.env_vars {
+        value_ids_mut(value, f);
+    }
+}
+
+fn expression_ids_mut(expression: &mut Expression, f: &mut dyn FnMut(IdMut)) {
+    match &mut expression.expr {
+        Expr::Call(call) => {
+            f(IdMut::Decl(&mut call.decl_id));
+            for positional in &mut call.positional {
+                expression_ids_mut(positional, f);
+            }
+            for (_, named) in &mut call.named {
+                if let Some(named) = named {
+                    expression_ids_mut(named, f);
+                }
+            }
+        }
+        Expr::Subexpression(block_id) | Expr::Block(block_id) => f(IdMut::Block(block_id)),
+        Expr::Range(from, next, to, _) => {
+            for part in from.iter_mut().chain(next.iter_mut()).chain(to.iter_mut()) {
+                expression_ids_mut(part, f);
+            }
+        }
+        Expr::ExternalCall(_, _, expressions) | Expr::List(expressions) => {
+            for expression in expressions {
+                expression_ids_mut(expression, f);
+            }
+        }
+        Expr::RowCondition(_, inner)
+        | Expr::Keyword(_, _, inner)
+        | Expr::ValueWithUnit(inner, _) => expression_ids_mut(inner, f),
+        Expr::BinaryOp(lhs, op, rhs) => {
+            expression_ids_mut(lhs, f);
+            expression_ids_mut(op, f);
+            expression_ids_mut(rhs, f);
+        }
+        Expr::Table(headers, rows) => {
+            for expression in headers.iter_mut().chain(rows.iter_mut().flatten()) {
+                expression_ids_mut(expression, f);
+            }
+        }
+        Expr::Record(pairs) => {
+            for (key, value) in pairs {
+                expression_ids_mut(key, f);
+                expression_ids_mut(value, f);
+            }
+        }
+        Expr::FullCellPath(path) => expression_ids_mut(&mut path.head, f),
+        Expr::Bool(_)
+        | Expr::Int(_)
+        | Expr::Float(_)
+        | Expr::Var(_)
+        | Expr::VarDecl(_)
+        | Expr::Operator(_)
+        | Expr::Filepath(_)
+        | Expr::GlobPattern(_)
+        | Expr::String(_)
+        | Expr::CellPath(_)
+        | Expr::Signature(_)
+        | Expr::Garbage => {}
+    }
+}
+
+fn value_ids_mut(value: &mut Value, f: &mut dyn FnMut(IdMut)) {
+    match value {
+        Value::Block { val, .. } => f(IdMut::Block(val)),
+        Value::List { vals, .. } | Value::Record { vals, .. } => {
+            for val in vals {
+                value_ids_mut(val, f);
+            }
+        }
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod engine_state_tests {
     use super::*;
+    use crate::ast::Call;
 
     #[test]
     fn add_file_gives_id() {
@@ -981,6 +2730,419 @@ mod engine_state_tests {
         assert_eq!(working_set_id, 1);
     }
 
+    #[test]
+    fn files_keep_where_they_came_from() {
+        use miette::SourceCode;
+
+        let mut engine_state = EngineState::new();
+        engine_state.add_file_with_source(
+            "spam.nu".into(),
+            b"ls".to_vec(),
+            FileSource::Script("spam.nu".into()),
+        );
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        let typed = working_set.add_file_with_source("entry #1".into(), b"ps", FileSource::Cli);
+
+        assert_eq!(
+            working_set.get_file(0).map(|file| &file.source),
+            Some(&FileSource::Script("spam.nu".into()))
+        );
+        assert_eq!(
+            working_set.get_file(typed).map(|file| &file.source),
+            Some(&FileSource::Cli)
+        );
+
+        // Only files that weren't typed in at the prompt are named in errors
+        let name = |file_id| {
+            let span = working_set.get_span_for_file(file_id);
+            (&working_set)
+                .read_span(&(span.start, span.end - span.start).into(), 0, 0)
+                .expect("the span is in a file")
+                .name()
+                .map(|name| name.to_string())
+        };
+        assert_eq!(name(0), Some("spam.nu".into()));
+        assert_eq!(name(typed), None);
+    }
+
+    #[test]
+    fn sourcing_the_same_file_again_reuses_it() {
+        let mut engine_state = EngineState::new();
+        let contents = b"def helper [] { 42 }";
+
+        let mut file_ids = vec![];
+        for _ in 0..3 {
+            let delta = {
+                let mut working_set = StateWorkingSet::new(&engine_state);
+                file_ids.push(working_set.add_file("helper.nu".into(), contents));
+                working_set.render()
+            };
+            engine_state.merge_delta(delta);
+        }
+
+        assert_eq!(file_ids, vec![0, 0, 0]);
+        assert_eq!(engine_state.num_files(), 1);
+        assert_eq!(engine_state.next_span_start(), contents.len());
+
+        let working_set = StateWorkingSet::new(&engine_state);
+        let span = working_set.get_span_for_file(file_ids[2]);
+        assert_eq!(working_set.get_span_contents(span), contents);
+    }
+
+    #[test]
+    fn files_are_reused_within_a_working_set() {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+
+        let first = working_set.add_file("helper.nu".into(), b"ls");
+        let changed = working_set.add_file("helper.nu".into(), b"ps");
+        let renamed = working_set.add_file("other.nu".into(), b"ls");
+        let again = working_set.add_file("helper.nu".into(), b"ls");
+
+        assert_eq!((first, changed, renamed, again), (0, 1, 2, 0));
+        assert_eq!(working_set.num_files(), 3);
+    }
+
+    #[test]
+    fn custom_commands_are_listed_when_asked_for() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_decl(Signature::build("builtin").predeclare());
+            let block_id = working_set.add_block(Block::new());
+            working_set.add_decl(
+                Signature::build("custom")
+                    .desc("a custom command")
+                    .into_block_command(block_id),
+            );
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let names = |signatures: Vec<Signature>| -> Vec<String> {
+            signatures.into_iter().map(|sig| sig.name).collect()
+        };
+        assert_eq!(names(engine_state.get_signatures(false)), vec!["builtin"]);
+        assert_eq!(
+            names(engine_state.get_signatures(true)),
+            vec!["builtin", "custom"]
+        );
+
+        let (custom, _) = engine_state
+            .get_signatures_with_examples(true)
+            .pop()
+            .expect("the custom command is listed");
+        assert_eq!(custom.usage, "a custom command");
+    }
+
+    #[test]
+    fn ctrlc_is_shared_and_reset() {
+        let mut engine_state = EngineState::new();
+        assert!(!engine_state.ctrlc_pressed());
+        // Without a flag there's nothing to reset
+        engine_state.reset_ctrlc();
+
+        let ctrlc = Arc::new(AtomicBool::new(false));
+        engine_state.set_ctrlc(ctrlc.clone());
+        let stream_ctrlc = engine_state.ctrlc.clone();
+        assert!(!engine_state.ctrlc_pressed());
+
+        ctrlc.store(true, Ordering::SeqCst);
+        assert!(engine_state.ctrlc_pressed());
+        assert!(engine_state.clone().ctrlc_pressed());
+
+        engine_state.reset_ctrlc();
+        assert!(!engine_state.ctrlc_pressed());
+        assert!(!stream_ctrlc
+            .expect("the flag is set")
+            .load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn signatures_carry_pipeline_types() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_decl(
+                Signature::build("typed")
+                    .input_type(Type::String)
+                    .output_type(Type::Int)
+                    .predeclare(),
+            );
+            working_set.add_decl(Signature::build("untyped").predeclare());
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let types: Vec<(Type, Type)> = engine_state
+            .get_signatures(false)
+            .into_iter()
+            .map(|sig| (sig.input_type, sig.output_type))
+            .collect();
+        assert_eq!(
+            types,
+            vec![(Type::String, Type::Int), (Type::Unknown, Type::Unknown)]
+        );
+    }
+
+    #[test]
+    fn finds_decls_with_signatures() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_decl(Signature::build("str upcase").predeclare());
+            working_set.add_decl(Signature::build("str trim").predeclare());
+            working_set.add_decl(Signature::build("ls").predeclare());
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.enter_scope();
+        working_set.add_decl(
+            Signature::build("str trim")
+                .switch("all", "trim everything", None)
+                .predeclare(),
+        );
+        working_set.add_predecl(Signature::build("str spam").predeclare(), Span::unknown());
+        working_set.hide_decl(b"str upcase");
+
+        let found = working_set.find_decls_with_signatures(b"str ");
+        let names: Vec<&[u8]> = found.iter().map(|(name, _)| name.as_slice()).collect();
+        assert_eq!(names, vec![&b"str spam"[..], b"str trim"]);
+        // The innermost `str trim` is the one found
+        assert!(found[1].1.get_long_flag("all").is_some());
+
+        assert_eq!(working_set.find_decls_with_signatures(b"").len(), 3);
+    }
+
+    #[test]
+    fn finds_variables_by_prefix() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_variable(b"$spam".to_vec(), Type::Int);
+            working_set.add_variable(b"$eggs".to_vec(), Type::String);
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.enter_scope();
+        let shadowing = working_set.add_variable(b"$spam".to_vec(), Type::Bool);
+        let sausage = working_set.add_variable(b"$sausage".to_vec(), Type::Float);
+        working_set.hide_variable(b"$eggs");
+
+        let found = working_set.find_variables_by_prefix(b"$s");
+        assert_eq!(
+            found,
+            vec![
+                (b"$sausage".to_vec(), sausage, Type::Float),
+                (b"$scope".to_vec(), SCOPE_VARIABLE_ID, Type::Unknown),
+                (b"$spam".to_vec(), shadowing, Type::Bool),
+            ]
+        );
+
+        let names: Vec<Vec<u8>> = working_set
+            .find_variables_by_prefix(b"$")
+            .into_iter()
+            .map(|(name, ..)| name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                b"$config".to_vec(),
+                b"$in".to_vec(),
+                b"$nu".to_vec(),
+                b"$sausage".to_vec(),
+                b"$scope".to_vec(),
+                b"$spam".to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_scope_frames_left_open() {
+        let mut engine_state = EngineState::new();
+        let (outer, shadowed, inner, shadowing, delta) = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            let outer = working_set.add_decl(Signature::build("outer").predeclare());
+            let shadowed = working_set.add_decl(Signature::build("both").predeclare());
+            working_set.enter_scope();
+            let inner = working_set.add_decl(Signature::build("inner").predeclare());
+            let shadowing = working_set.add_decl(Signature::build("both").predeclare());
+            assert_eq!(working_set.delta.scope.len(), 2);
+
+            (outer, shadowed, inner, shadowing, working_set.render())
+        };
+        engine_state.merge_delta(delta);
+
+        assert_eq!(engine_state.num_decls(), 4);
+        assert_eq!(engine_state.find_decl(b"outer"), Some(outer));
+        assert_eq!(engine_state.find_decl(b"inner"), Some(inner));
+        assert_eq!(engine_state.find_decl(b"both"), Some(shadowing));
+        assert_ne!(shadowed, shadowing);
+
+        let working_set = StateWorkingSet::new(&engine_state);
+        assert_eq!(working_set.find_decl(b"inner"), Some(inner));
+    }
+
+    #[test]
+    fn decls_remember_where_they_are_defined() {
+        let mut engine_state = EngineState::new();
+        let (builtin, custom, delta) = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            let builtin = working_set.add_decl(Signature::build("builtin").predeclare());
+            working_set.add_file("spam.nu".into(), b"ls; def spam [] { 1 }");
+            let def_span = Span { start: 4, end: 21 };
+            assert!(working_set
+                .add_predecl(Signature::build("spam").predeclare(), def_span)
+                .is_none());
+            let custom = working_set
+                .merge_predecl(b"spam")
+                .expect("spam is declared");
+
+            assert_eq!(working_set.get_decl_span(custom), Some(def_span));
+            (builtin, custom, working_set.render())
+        };
+        engine_state.merge_delta(delta);
+
+        assert_eq!(engine_state.get_decl_span(builtin), None);
+        let working_set = StateWorkingSet::new(&engine_state);
+        let span = working_set
+            .get_decl_span(custom)
+            .expect("custom commands have a span");
+        assert_eq!(working_set.get_span_contents(span), b"def spam [] { 1 }");
+    }
+
+    #[test]
+    fn replaced_decls_keep_their_id() {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        let first_body = working_set.add_block(Block::new());
+        let decl_id = working_set.add_decl(Signature::build("foo").into_block_command(first_body));
+
+        // A call parsed against the first definition
+        let call = Call {
+            decl_id,
+            ..Call::new()
+        };
+
+        let second_body = working_set.add_block(Block::new());
+        let second_span = Span { start: 10, end: 20 };
+        let replaced = working_set.replace_decl(
+            b"foo",
+            Signature::build("foo").into_block_command(second_body),
+            Some(second_span),
+        );
+
+        assert_eq!(replaced, decl_id);
+        assert_eq!(working_set.find_decl(b"foo"), Some(decl_id));
+        assert_eq!(
+            working_set.get_decl(call.decl_id).get_block_id(),
+            Some(second_body)
+        );
+        assert_eq!(working_set.get_decl_span(decl_id), Some(second_span));
+    }
+
+    #[test]
+    fn replacing_in_an_inner_scope_leaves_the_outer_decl() {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        let outer_body = working_set.add_block(Block::new());
+        let outer = working_set.add_decl(Signature::build("foo").into_block_command(outer_body));
+
+        working_set.enter_scope();
+        let inner_body = working_set.add_block(Block::new());
+        let inner = working_set.replace_decl(
+            b"foo",
+            Signature::build("foo").into_block_command(inner_body),
+            None,
+        );
+
+        assert_ne!(inner, outer);
+        assert_eq!(working_set.find_decl(b"foo"), Some(inner));
+
+        working_set.exit_scope();
+        assert_eq!(working_set.find_decl(b"foo"), Some(outer));
+        assert_eq!(working_set.get_decl(outer).get_block_id(), Some(outer_body));
+    }
+
+    #[test]
+    fn replacing_needs_the_exact_name() {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        let lower_body = working_set.add_block(Block::new());
+        let lower = working_set.add_decl(Signature::build("foo").into_block_command(lower_body));
+
+        let upper_body = working_set.add_block(Block::new());
+        let upper = working_set.replace_decl(
+            b"FOO",
+            Signature::build("FOO").into_block_command(upper_body),
+            None,
+        );
+
+        assert_ne!(upper, lower);
+        assert_eq!(working_set.get_decl(lower).get_block_id(), Some(lower_body));
+    }
+
+    #[test]
+    fn replacing_a_permanent_decl_shadows_it() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_decl(Signature::build("foo").predeclare());
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+        let old = engine_state.find_decl(b"foo").expect("foo is defined");
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        let body = working_set.add_block(Block::new());
+        let span = Span { start: 0, end: 5 };
+        let new = working_set.replace_decl(
+            b"foo",
+            Signature::build("foo").into_block_command(body),
+            Some(span),
+        );
+
+        assert_ne!(new, old);
+        assert_eq!(working_set.find_decl(b"foo"), Some(new));
+        assert_eq!(working_set.get_decl(old).get_block_id(), None);
+        assert_eq!(working_set.get_decl_span(new), Some(span));
+    }
+
+    #[test]
+    fn spans_outside_the_files_fail_validation() {
+        let mut engine_state = EngineState::new();
+        engine_state.add_file("test.nu".into(), b"ls -l".to_vec());
+        engine_state.scope[0].aliases.insert(
+            b"ll".to_vec(),
+            Alias::new(vec![Span { start: 0, end: 2 }, Span { start: 3, end: 5 }]),
+        );
+        assert!(engine_state.validate_spans().is_ok());
+
+        engine_state.scope[0]
+            .aliases
+            .insert(b"la".to_vec(), Alias::new(vec![Span::unknown()]));
+        assert!(engine_state.validate_spans().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "the alias 'll'")]
+    #[cfg(debug_assertions)]
+    fn merging_spans_outside_the_files_panics() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_file("test.nu".into(), b"ls");
+            working_set.add_alias(b"ll".to_vec(), Alias::new(vec![Span { start: 0, end: 5 }]));
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+    }
+
     #[test]
     fn merge_states() {
         let mut engine_state = EngineState::new();
@@ -995,7 +3157,926 @@ mod engine_state_tests {
         engine_state.merge_delta(delta);
 
         assert_eq!(engine_state.num_files(), 2);
-        assert_eq!(&engine_state.files[0].0, "test.nu");
-        assert_eq!(&engine_state.files[1].0, "child.nu");
+        assert_eq!(&engine_state.files[0].name, "test.nu");
+        assert_eq!(&engine_state.files[1].name, "child.nu");
+    }
+
+    #[test]
+    fn spans_outside_the_files_are_errors() {
+        let mut engine_state = EngineState::new();
+        engine_state.add_file("test.nu".into(), b"ls".to_vec());
+
+        assert_eq!(
+            engine_state
+                .try_get_span_contents(&Span { start: 0, end: 2 })
+                .ok(),
+            Some(&b"ls"[..])
+        );
+        assert!(matches!(
+            engine_state.try_get_span_contents(&Span { start: 1, end: 5 }),
+            Err(ShellError::SpanNotFound(1, 5))
+        ));
+
+        let working_set = StateWorkingSet::new(&engine_state);
+        assert!(working_set
+            .try_get_span_contents(Span { start: 10, end: 12 })
+            .is_err());
+        assert!(working_set
+            .try_get_span_contents(Span { start: 2, end: 1 })
+            .is_err());
+    }
+
+    #[test]
+    fn reading_spans_across_files_is_out_of_bounds() {
+        use miette::SourceCode;
+
+        let mut engine_state = EngineState::new();
+        engine_state.add_file("first.nu".into(), b"ls".to_vec());
+        engine_state.add_file("second.nu".into(), b"ps".to_vec());
+        let working_set = StateWorkingSet::new(&engine_state);
+
+        assert!((&working_set).read_span(&(0, 2).into(), 0, 0).is_ok());
+        assert!(matches!(
+            (&working_set).read_span(&(1, 2).into(), 0, 0),
+            Err(miette::MietteError::OutOfBounds)
+        ));
+    }
+
+    #[test]
+    fn span_contents_at_file_boundaries() {
+        const FILES: usize = 5000;
+        // Files of 1 to 7 bytes, each made of its own index so a wrong file is noticed
+        let contents = |idx: usize| vec![b'a' + (idx % 26) as u8; idx % 7 + 1];
+
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            for idx in 0..FILES / 2 {
+                working_set.add_file(format!("file{}.nu", idx), &contents(idx));
+            }
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        for idx in FILES / 2..FILES {
+            working_set.add_file(format!("file{}.nu", idx), &contents(idx));
+        }
+
+        let mut start = 0;
+        for idx in 0..FILES {
+            let file = contents(idx);
+            let end = start + file.len();
+
+            for (span, expected) in [
+                ((start, end), &file[..]),
+                ((start, start + 1), &file[..1]),
+                ((end - 1, end), &file[file.len() - 1..]),
+                ((start, start), &[][..]),
+            ] {
+                let span = Span {
+                    start: span.0,
+                    end: span.1,
+                };
+                assert_eq!(working_set.get_span_contents(span), expected);
+                if idx < FILES / 2 {
+                    assert_eq!(engine_state.get_span_contents(&span), expected);
+                }
+            }
+
+            start = end;
+        }
+
+        assert_eq!(start, working_set.next_span_start());
+        assert!(working_set
+            .try_get_span_contents(Span {
+                start: start - 1,
+                end: start + 1
+            })
+            .is_err());
+        assert!(working_set
+            .try_get_span_contents(Span { start: 5, end: 3 })
+            .is_err());
+        let permanent_end = engine_state.next_span_start();
+        assert!(engine_state
+            .try_get_span_contents(&Span {
+                start: permanent_end,
+                end: permanent_end + 1
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn env_vars_are_merged_into_the_permanent_state() {
+        let mut engine_state = EngineState::new();
+
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_env_var(b"FOO".to_vec(), Value::test_string("bar"));
+            assert_eq!(
+                working_set.find_env_var(b"FOO"),
+                Some(&Value::test_string("bar"))
+            );
+            working_set.render()
+        };
+        assert_eq!(engine_state.find_env_var(b"FOO"), None);
+
+        engine_state.merge_delta(delta);
+
+        assert_eq!(
+            engine_state.find_env_var(b"FOO"),
+            Some(&Value::test_string("bar"))
+        );
+        assert_eq!(engine_state.find_env_var(b"BAZ"), None);
+    }
+
+    #[test]
+    fn env_vars_in_the_delta_shadow_the_permanent_ones() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_env_var(b"FOO".to_vec(), Value::test_string("outer"));
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.enter_scope();
+        working_set.add_env_var(b"FOO".to_vec(), Value::test_string("inner"));
+        assert_eq!(
+            working_set.find_env_var(b"FOO"),
+            Some(&Value::test_string("inner"))
+        );
+
+        working_set.exit_scope();
+        assert_eq!(
+            working_set.find_env_var(b"FOO"),
+            Some(&Value::test_string("outer"))
+        );
+    }
+
+    fn state_with_alias(name: &str) -> EngineState {
+        let mut engine_state = EngineState::new();
+        engine_state.add_file("aliases.nu".into(), b"ls -la".to_vec());
+
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_alias(name.into(), Alias::new(vec![Span { start: 0, end: 6 }]));
+            working_set.render()
+        };
+
+        engine_state.merge_delta(delta);
+        engine_state
+    }
+
+    #[test]
+    fn hides_aliases_from_the_permanent_state() {
+        let mut engine_state = state_with_alias("ll");
+
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            assert!(working_set.find_alias(b"ll").is_some());
+            assert!(working_set.hide_alias(b"ll"));
+            assert!(working_set.find_alias(b"ll").is_none());
+            assert!(!working_set.hide_alias(b"ll"));
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let working_set = StateWorkingSet::new(&engine_state);
+        assert!(working_set.find_alias(b"ll").is_none());
+    }
+
+    #[test]
+    fn hides_aliases_from_the_delta() {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.add_alias(b"ll".to_vec(), Alias::new(vec![]));
+
+        assert!(working_set.hide_alias(b"ll"));
+        assert!(working_set.find_alias(b"ll").is_none());
+        assert!(!working_set.hide_alias(b"la"));
+    }
+
+    #[test]
+    fn alias_templates_show_the_placeholder() {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.add_file("test.nu".into(), b"git checkout");
+        working_set.add_alias(
+            b"gco".to_vec(),
+            Alias::with_placeholder(
+                vec![Span { start: 0, end: 3 }, Span { start: 4, end: 12 }],
+                1,
+            ),
+        );
+
+        assert_eq!(
+            working_set.find_alias_template(b"gco"),
+            Some("git {} checkout".to_string())
+        );
+        assert_eq!(working_set.find_alias_template(b"gc"), None);
+    }
+
+    #[test]
+    fn lists_aliases_with_their_expansions() {
+        let engine_state = state_with_alias("ll");
+        assert_eq!(
+            engine_state.get_alias_expansion(b"ll"),
+            Some("ls -la".to_string())
+        );
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        let start = working_set.next_span_start();
+        working_set.add_file("more.nu".into(), b"git status");
+        working_set.add_alias(
+            b"gs".to_vec(),
+            Alias::new(vec![
+                Span {
+                    start,
+                    end: start + 3,
+                },
+                Span {
+                    start: start + 4,
+                    end: start + 10,
+                },
+            ]),
+        );
+        working_set.add_alias(
+            b"gone".to_vec(),
+            Alias::new(vec![Span {
+                start: 1000,
+                end: 1004,
+            }]),
+        );
+
+        assert_eq!(
+            working_set.list_aliases(),
+            vec![
+                (b"gone".to_vec(), "<missing>".to_string()),
+                (b"gs".to_vec(), "git status".to_string()),
+                (b"ll".to_vec(), "ls -la".to_string()),
+            ]
+        );
+        assert_eq!(working_set.get_alias_expansion(b"la"), None);
+    }
+
+    #[test]
+    fn redefined_aliases_are_visible_again() {
+        let mut engine_state = state_with_alias("ll");
+
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.hide_alias(b"ll");
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_alias(b"ll".to_vec(), Alias::new(vec![Span { start: 0, end: 2 }]));
+            assert_eq!(
+                working_set.find_alias(b"ll"),
+                Some(&Alias::new(vec![Span { start: 0, end: 2 }]))
+            );
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let working_set = StateWorkingSet::new(&engine_state);
+        assert_eq!(
+            working_set.find_alias(b"ll"),
+            Some(&Alias::new(vec![Span { start: 0, end: 2 }]))
+        );
+    }
+
+    fn state_with_decls(names: &[&str]) -> EngineState {
+        let mut engine_state = EngineState::new();
+
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            for name in names {
+                working_set.add_decl(Signature::build(*name).predeclare());
+            }
+            working_set.render()
+        };
+
+        engine_state.merge_delta(delta);
+        engine_state
+    }
+
+    #[test]
+    fn lookup_is_case_sensitive_by_default() {
+        let engine_state = state_with_decls(&["str kebab-case"]);
+
+        assert!(engine_state.find_decl(b"str kebab-case").is_some());
+        assert!(engine_state.find_decl(b"STR KEBAB-CASE").is_none());
+        assert!(engine_state.find_commands_by_prefix(b"STR").is_empty());
+    }
+
+    #[test]
+    fn case_insensitive_lookup_falls_back_on_miss() {
+        let mut engine_state = state_with_decls(&["str kebab-case"]);
+        engine_state.case_insensitive_lookup = true;
+
+        let decl_id = engine_state.find_decl(b"str kebab-case");
+        assert!(decl_id.is_some());
+        assert_eq!(engine_state.find_decl(b"STR KEBAB-CASE"), decl_id);
+        assert_eq!(
+            engine_state.find_commands_by_prefix(b"Str Keb"),
+            vec![b"str kebab-case".to_vec()]
+        );
+
+        let working_set = StateWorkingSet::new(&engine_state);
+        assert_eq!(working_set.find_decl(b"Str Kebab-Case"), decl_id);
+    }
+
+    #[test]
+    fn case_insensitive_lookup_prefers_exact_match() {
+        let mut engine_state = state_with_decls(&["foo", "FOO"]);
+        engine_state.case_insensitive_lookup = true;
+
+        // The exact lookup runs first, so differently cased commands stay distinct
+        let lower = engine_state.find_decl(b"foo").expect("foo is defined");
+        let upper = engine_state.find_decl(b"FOO").expect("FOO is defined");
+        assert_ne!(lower, upper);
+        assert_eq!(engine_state.get_decl(upper).name(), "FOO");
+
+        let mut prefixed = engine_state.find_commands_by_prefix(b"f");
+        prefixed.sort();
+        assert_eq!(prefixed, vec![b"foo".to_vec()]);
+    }
+
+    #[test]
+    fn relaxed_lookup_finds_misspelled_commands() {
+        let engine_state = state_with_decls(&["str downcase", "into-bool", "ls"]);
+        let downcase = engine_state.find_decl(b"str downcase");
+
+        for name in [&b"Str Downcase"[..], b"str_downcase", b"STR-DOWNCASE"] {
+            assert_eq!(
+                engine_state.find_decl_relaxed(name),
+                downcase.map(|decl_id| (decl_id, b"str downcase".to_vec()))
+            );
+        }
+        assert_eq!(
+            engine_state
+                .find_decl_relaxed(b"Into_Bool")
+                .map(|(_, name)| name),
+            Some(b"into-bool".to_vec())
+        );
+        assert_eq!(engine_state.find_decl_relaxed(b"str downcas"), None);
+
+        // The default lookup is unchanged
+        assert_eq!(engine_state.find_decl(b"Str Downcase"), None);
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.add_decl(Signature::build("ls-all").predeclare());
+        assert_eq!(
+            working_set
+                .find_decl_relaxed(b"LS_ALL")
+                .map(|(_, name)| name),
+            Some(b"ls-all".to_vec())
+        );
+        assert_eq!(
+            working_set.find_decl_relaxed(b"ls"),
+            engine_state
+                .find_decl(b"ls")
+                .map(|decl_id| (decl_id, b"ls".to_vec()))
+        );
+    }
+
+    #[test]
+    fn relaxed_lookup_skips_hidden_decls() {
+        let engine_state = state_with_decls(&["foo"]);
+        let foo = engine_state.find_decl(b"foo").expect("foo is defined");
+
+        // The same decl, reachable from the delta under another name
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        let mut overlay = Overlay::new();
+        overlay.decls.push((b"spam-foo".to_vec(), foo));
+        working_set.activate_overlay(overlay);
+        assert_eq!(
+            working_set.find_decl_relaxed(b"Spam_Foo"),
+            Some((foo, b"spam-foo".to_vec()))
+        );
+
+        working_set.hide_decl(b"foo");
+        assert_eq!(working_set.find_decl_relaxed(b"Spam_Foo"), None);
+        assert_eq!(working_set.find_decl_relaxed(b"FOO"), None);
+    }
+
+    #[test]
+    fn hides_decl() {
+        let engine_state = state_with_decls(&["foo"]);
+        let mut working_set = StateWorkingSet::new(&engine_state);
+
+        let hidden = working_set
+            .resolve_and_hide_decl(b"foo", Span::unknown())
+            .expect("foo can be hidden");
+        assert_eq!(hidden, engine_state.find_decl(b"foo"));
+        assert!(working_set.find_decl(b"foo").is_none());
+
+        let hidden_again = working_set
+            .resolve_and_hide_decl(b"foo", Span::unknown())
+            .expect("unknown names aren't an error");
+        assert!(hidden_again.is_none());
+    }
+
+    #[test]
+    fn hides_variables_from_the_delta() {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        let outer = working_set.add_variable(b"$x".to_vec(), Type::Int);
+        working_set.enter_scope();
+        let inner = working_set.add_variable(b"$x".to_vec(), Type::String);
+
+        assert_eq!(working_set.hide_variable(b"x"), Some(inner));
+        assert_eq!(working_set.find_variable(b"$x"), Some(outer));
+        assert_eq!(working_set.hide_variable(b"$x"), Some(outer));
+        assert_eq!(working_set.find_variable(b"$x"), None);
+        assert_eq!(working_set.hide_variable(b"$x"), None);
+    }
+
+    #[test]
+    fn hidden_variables_stay_hidden_after_merging() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_variable(b"$helper".to_vec(), Type::Int);
+            working_set.add_variable(b"$kept".to_vec(), Type::Int);
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+        let helper = engine_state.find_variable(b"$helper");
+        assert!(helper.is_some());
+
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            assert_eq!(working_set.hide_variable(b"$helper"), helper);
+            assert_eq!(working_set.find_variable(b"$helper"), None);
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        assert_eq!(engine_state.find_variable(b"$helper"), None);
+        assert!(engine_state.find_variable(b"$kept").is_some());
+        let working_set = StateWorkingSet::new(&engine_state);
+        assert_eq!(working_set.find_variable(b"$helper"), None);
+    }
+
+    #[test]
+    fn hidden_variables_can_be_defined_again() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_variable(b"$x".to_vec(), Type::Int);
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+        let first = engine_state.find_variable(b"$x").expect("$x is defined");
+
+        let (second, delta) = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.hide_variable(b"$x");
+            let second = working_set.add_variable(b"$x".to_vec(), Type::String);
+            assert_eq!(working_set.find_variable(b"$x"), Some(second));
+            (second, working_set.render())
+        };
+        engine_state.merge_delta(delta);
+
+        assert_ne!(first, second);
+        assert_eq!(engine_state.find_variable(b"$x"), Some(second));
+        assert_eq!(
+            StateWorkingSet::new(&engine_state).find_variable(b"$x"),
+            Some(second)
+        );
+    }
+
+    fn overlay_of(decls: Vec<(Vec<u8>, DeclId)>) -> Overlay {
+        Overlay {
+            decls,
+            ..Overlay::new()
+        }
+    }
+
+    #[test]
+    fn deactivates_overlay_in_the_delta() {
+        let engine_state = state_with_decls(&["spam foo", "spam bar"]);
+        let overlay = overlay_of(vec![
+            (
+                b"foo".to_vec(),
+                engine_state.find_decl(b"spam foo").unwrap(),
+            ),
+            (
+                b"bar".to_vec(),
+                engine_state.find_decl(b"spam bar").unwrap(),
+            ),
+        ]);
+        let mut working_set = StateWorkingSet::new(&engine_state);
+
+        working_set.activate_overlay(overlay.clone());
+        assert_eq!(working_set.find_decl(b"foo"), Some(overlay.decls[0].1));
+
+        working_set.deactivate_overlay(&overlay);
+        assert!(working_set.find_decl(b"foo").is_none());
+        assert!(working_set.find_decl(b"bar").is_none());
+        // The module's own names are untouched
+        assert!(working_set.find_decl(b"spam foo").is_some());
+    }
+
+    #[test]
+    fn deactivates_overlay_from_the_permanent_state() {
+        let mut engine_state = state_with_decls(&["spam foo"]);
+        let overlay = overlay_of(vec![(
+            b"foo".to_vec(),
+            engine_state.find_decl(b"spam foo").unwrap(),
+        )]);
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.activate_overlay(overlay.clone());
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+        assert!(engine_state.find_decl(b"foo").is_some());
+
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.deactivate_overlay(&overlay);
+            assert!(working_set.find_decl(b"foo").is_none());
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        assert!(engine_state.find_decl(b"foo").is_none());
+    }
+
+    #[test]
+    fn deactivating_overlay_keeps_other_names_of_its_decls() {
+        let mut engine_state = state_with_decls(&["spam foo"]);
+        let spam_foo = engine_state.find_decl(b"spam foo").unwrap();
+        let overlay = overlay_of(vec![(b"foo".to_vec(), spam_foo)]);
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.activate_overlay(overlay.clone());
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.deactivate_overlay(&overlay);
+            assert_eq!(working_set.find_decl(b"foo"), None);
+            assert_eq!(working_set.find_decl(b"spam foo"), Some(spam_foo));
+            assert_eq!(working_set.find_decl_relaxed(b"FOO"), None);
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        assert_eq!(engine_state.find_decl(b"foo"), None);
+        assert_eq!(engine_state.find_decl(b"spam foo"), Some(spam_foo));
+
+        // Defining the name again makes it visible again
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.activate_overlay(overlay.clone());
+        assert_eq!(working_set.find_decl(b"foo"), Some(spam_foo));
+    }
+
+    #[test]
+    fn deactivating_overlay_keeps_shadowing_decls() {
+        let mut engine_state = state_with_decls(&["spam foo", "spam bar"]);
+        let overlay = overlay_of(vec![
+            (
+                b"foo".to_vec(),
+                engine_state.find_decl(b"spam foo").unwrap(),
+            ),
+            (
+                b"bar".to_vec(),
+                engine_state.find_decl(b"spam bar").unwrap(),
+            ),
+        ]);
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.activate_overlay(overlay.clone());
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        // `foo` is redefined in the delta, `bar` in an inner scope
+        let foo = working_set.add_decl(Signature::build("foo").predeclare());
+        working_set.enter_scope();
+        let bar = working_set.add_decl(Signature::build("bar").predeclare());
+
+        working_set.deactivate_overlay(&overlay);
+        assert_eq!(working_set.find_decl(b"foo"), Some(foo));
+        assert_eq!(working_set.find_decl(b"bar"), Some(bar));
+
+        working_set.exit_scope();
+        assert_eq!(working_set.find_decl(b"foo"), Some(foo));
+        assert_eq!(working_set.find_decl(b"bar"), Some(overlay.decls[1].1));
+    }
+
+    #[test]
+    fn overlays_bring_aliases_and_env_vars() {
+        let mut engine_state = state_with_decls(&["spam foo"]);
+        engine_state.add_file("spam.nu".into(), b"ls -l".to_vec());
+        let alias = Alias::new(vec![Span { start: 0, end: 2 }, Span { start: 3, end: 5 }]);
+        let overlay = Overlay {
+            decls: vec![(
+                b"foo".to_vec(),
+                engine_state.find_decl(b"spam foo").unwrap(),
+            )],
+            aliases: vec![(b"ll".to_vec(), alias.clone())],
+            env_vars: vec![(b"SPAM".to_vec(), Value::test_string("eggs"))],
+        };
+
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.activate_overlay(overlay.clone());
+            assert_eq!(working_set.find_alias(b"ll"), Some(&alias));
+            assert_eq!(
+                working_set.find_env_var(b"SPAM"),
+                Some(&Value::test_string("eggs"))
+            );
+
+            working_set.deactivate_overlay(&overlay);
+            assert_eq!(working_set.find_alias(b"ll"), None);
+            assert_eq!(working_set.find_env_var(b"SPAM"), None);
+
+            working_set.activate_overlay(overlay.clone());
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+        assert_eq!(engine_state.find_alias(b"ll"), Some(&alias));
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.deactivate_overlay(&overlay);
+        assert_eq!(working_set.find_alias(b"ll"), None);
+        assert!(working_set.find_decl(b"foo").is_none());
+    }
+
+    #[test]
+    fn finds_the_name_a_decl_is_bound_to() {
+        let engine_state = state_with_decls(&["spam foo"]);
+        let decl_id = engine_state.find_decl(b"spam foo").unwrap();
+
+        assert_eq!(engine_state.find_decl_name(decl_id), Some(&b"spam foo"[..]));
+        assert_eq!(engine_state.find_decl_name(DeclId::new(1000)), None);
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.enter_scope();
+        working_set.activate_overlay(overlay_of(vec![(b"foo".to_vec(), decl_id)]));
+        assert_eq!(working_set.find_decl_name(decl_id), Some(&b"foo"[..]));
+
+        working_set.exit_scope();
+        assert_eq!(working_set.find_decl_name(decl_id), Some(&b"spam foo"[..]));
+    }
+
+    #[test]
+    fn decl_names_respect_visibility() {
+        let mut engine_state = state_with_decls(&["spam foo", "foo"]);
+        let spam_foo = engine_state.find_decl(b"spam foo").unwrap();
+        let foo = engine_state.find_decl(b"foo").unwrap();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.activate_overlay(overlay_of(vec![(b"foo".to_vec(), spam_foo)]));
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        // `foo` now calls `spam foo`, so the other decl can't be called at all
+        assert_eq!(engine_state.find_decl_name(spam_foo), Some(&b"foo"[..]));
+        assert_eq!(engine_state.find_decl_name(foo), None);
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.hide_decl(b"foo");
+        assert_eq!(working_set.find_decl_name(spam_foo), None);
+    }
+
+    #[test]
+    fn refuses_to_hide_predecl() {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.add_predecl(Signature::build("foo").predeclare(), Span::unknown());
+
+        let result = working_set.resolve_and_hide_decl(b"foo", Span::unknown());
+        assert!(matches!(result, Err(ShellError::CantHidePredecl(name, _)) if name == "foo"));
+    }
+
+    #[test]
+    fn abandons_predecls_left_when_their_scope_ends() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            let start = working_set.next_span_start();
+            working_set.add_file("test.nu".into(), b"never defined last");
+            let word = |from: usize, to: usize| Span::new(start + from, start + to);
+
+            working_set.enter_scope();
+            working_set.add_predecl(Signature::build("never").predeclare(), word(0, 5));
+            working_set.add_predecl(Signature::build("defined").predeclare(), word(6, 13));
+            working_set.add_predecl(Signature::build("last").predeclare(), word(14, 18));
+            let defined = working_set
+                .merge_predecl(b"defined")
+                .expect("defined is predeclared");
+            working_set.exit_scope();
+
+            // The decl at the end is gone, the one before `defined` only keeps `defined` in place
+            assert_eq!(working_set.num_decls(), 2);
+            assert_eq!(working_set.get_decl(defined).name(), "defined");
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+
+        let names: Vec<String> = engine_state
+            .get_signatures(true)
+            .into_iter()
+            .map(|signature| signature.name)
+            .collect();
+        assert_eq!(names, vec!["defined"]);
+    }
+
+    #[test]
+    fn hides_module_from_delta() {
+        let engine_state = EngineState::new();
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        let block_id = working_set.add_module("spam", Block::new());
+
+        working_set.enter_scope();
+        assert_eq!(working_set.hide_module(b"spam"), Some(block_id));
+        assert_eq!(working_set.find_module(b"spam"), None);
+        assert_eq!(working_set.hide_module(b"spam"), None);
+
+        assert_eq!(working_set.unhide_module(b"spam"), Some(block_id));
+        assert_eq!(working_set.find_module(b"spam"), Some(block_id));
+
+        working_set.hide_module(b"spam");
+        working_set.exit_scope();
+        assert_eq!(working_set.find_module(b"spam"), Some(block_id));
+    }
+
+    #[test]
+    fn hides_module_from_permanent_state() {
+        let mut engine_state = EngineState::new();
+        let block_id = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            let block_id = working_set.add_module("spam", Block::new());
+            engine_state.merge_delta(working_set.render());
+            block_id
+        };
+        assert_eq!(engine_state.find_module(b"spam"), Some(block_id));
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        assert_eq!(working_set.hide_module(b"spam"), Some(block_id));
+        assert_eq!(working_set.find_module(b"spam"), None);
+
+        // Hiding it in the delta doesn't touch the permanent state until the delta is merged
+        assert_eq!(engine_state.find_module(b"spam"), Some(block_id));
+        let delta = working_set.render();
+        engine_state.merge_delta(delta);
+        assert_eq!(engine_state.find_module(b"spam"), None);
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        assert_eq!(working_set.unhide_module(b"spam"), Some(block_id));
+        assert_eq!(working_set.find_module(b"spam"), Some(block_id));
+        let delta = working_set.render();
+        engine_state.merge_delta(delta);
+        assert_eq!(engine_state.find_module(b"spam"), Some(block_id));
+    }
+
+    #[test]
+    fn lists_visible_module_names() {
+        let mut engine_state = EngineState::new();
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_module("spam", Block::new());
+            working_set.add_module("eggs", Block::new());
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+        assert_eq!(
+            engine_state.get_module_names(),
+            vec![b"eggs".to_vec(), b"spam".to_vec()]
+        );
+
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.enter_scope();
+        working_set.add_module("spam", Block::new());
+        working_set.add_module("bacon", Block::new());
+        working_set.hide_module(b"eggs");
+        assert_eq!(
+            working_set.get_module_names(),
+            vec![b"bacon".to_vec(), b"spam".to_vec()]
+        );
+
+        let delta = working_set.render();
+        engine_state.merge_delta(delta);
+        assert_eq!(
+            engine_state.get_module_names(),
+            vec![b"bacon".to_vec(), b"spam".to_vec()]
+        );
+    }
+
+    /// An engine state with `frames` scope frames of `per_frame` decls each. Every frame has its
+    /// own `cmd N` names shadowing the outer frames' ones, plus a name only it has. Each frame also
+    /// hides one decl of the frame just outside it.
+    fn state_with_frames(frames: usize, per_frame: usize) -> EngineState {
+        let mut engine_state = EngineState::new();
+        engine_state.scope.clear();
+
+        for frame in 0..frames {
+            let mut scope = ScopeFrame::new();
+            for idx in 0..per_frame {
+                let decl_id = DeclId::new(frame * per_frame + idx);
+                scope
+                    .decls
+                    .insert(format!("cmd {}", idx).into_bytes(), decl_id);
+            }
+            scope.decls.insert(
+                format!("frame {}", frame).into_bytes(),
+                DeclId::new(frames * per_frame + frame),
+            );
+            if frame > 0 {
+                let outer_decl = DeclId::new((frame - 1) * per_frame + frame % per_frame);
+                scope.visibility.hide_id(&outer_decl);
+            }
+
+            engine_state.scope.push_back(scope);
+        }
+
+        engine_state.update_visible_decls();
+        engine_state
+    }
+
+    fn all_names(frames: usize, per_frame: usize) -> Vec<Vec<u8>> {
+        (0..per_frame)
+            .map(|idx| format!("cmd {}", idx))
+            .chain((0..frames).map(|frame| format!("frame {}", frame)))
+            .chain(std::iter::once("missing".to_string()))
+            .map(String::into_bytes)
+            .collect()
+    }
+
+    #[test]
+    fn cached_lookup_matches_walking_the_frames() {
+        let engine_state = state_with_frames(50, 20);
+        let working_set = StateWorkingSet::new(&engine_state);
+
+        for name in all_names(50, 20) {
+            let walked = engine_state.find_decl_with_visibility(&name, DeclVisibility::new());
+
+            assert_eq!(engine_state.find_decl(&name), walked);
+            assert_eq!(working_set.find_decl(&name), walked);
+        }
+    }
+
+    #[test]
+    fn hidden_decls_stay_hidden() {
+        let mut engine_state = EngineState::new();
+        for name in ["foo", "bar", "foo"] {
+            engine_state
+                .decls
+                .push_back(Signature::build(name).predeclare());
+        }
+        let mut outer = ScopeFrame::new();
+        outer.decls.insert(b"foo".to_vec(), DeclId::new(0));
+        outer.decls.insert(b"bar".to_vec(), DeclId::new(1));
+        let mut inner = ScopeFrame::new();
+        inner.decls.insert(b"foo".to_vec(), DeclId::new(2));
+        inner.visibility.hide_id(&DeclId::new(1));
+        engine_state.scope = im::vector![outer, inner];
+        engine_state.update_visible_decls();
+
+        assert_eq!(engine_state.find_decl(b"foo"), Some(DeclId::new(2)));
+        assert_eq!(engine_state.find_decl(b"bar"), None);
+
+        // Hiding the inner foo in a working set shows the outer one again
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        assert_eq!(working_set.hide_decl(b"foo"), Some(DeclId::new(2)));
+        assert_eq!(working_set.find_decl(b"foo"), Some(DeclId::new(0)));
+
+        // And a working set can show a decl the permanent state hides
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.activate_overlay(overlay_of(vec![(b"baz".to_vec(), DeclId::new(1))]));
+        assert_eq!(working_set.find_decl(b"bar"), Some(DeclId::new(1)));
+    }
+
+    #[test]
+    fn lookups_dont_walk_the_frames() {
+        let mut engine_state = state_with_frames(500, 10);
+        let names = all_names(500, 10);
+        let walked: Vec<_> = names
+            .iter()
+            .map(|name| engine_state.find_decl_with_visibility(name, DeclVisibility::new()))
+            .collect();
+
+        // Without the frames, only the cache is left to answer from
+        engine_state.scope.clear();
+        let cached: Vec<_> = names
+            .iter()
+            .map(|name| engine_state.find_decl(name))
+            .collect();
+
+        assert_eq!(cached, walked);
     }
 }
@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Span;
+
+/// Where the contents of a file came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileSource {
+    /// Typed in at the prompt. Errors in it are shown without a file name
+    Cli,
+    /// A script, or a config or other startup file, read from this path
+    Script(PathBuf),
+    /// Piped in on stdin
+    Stdin,
+    /// Made up by the shell or the embedder, eg the source of a completion or an example
+    Internal,
+}
+
+/// A file spans can point into: the name it's shown with, the span range its contents take up,
+/// and where the contents came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct File {
+    pub name: String,
+    pub span: Span,
+    pub source: FileSource,
+}
+
+impl File {
+    pub fn new(name: String, span: Span, source: FileSource) -> Self {
+        Self { name, span, source }
+    }
+}
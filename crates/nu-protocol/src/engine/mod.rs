@@ -1,9 +1,24 @@
+mod alias;
 mod call_info;
 mod command;
+mod confirm;
 mod engine_state;
+mod file;
+mod output;
+mod overlay;
+mod regex_cache;
+mod scope_snapshot;
 mod stack;
+mod warnings;
 
+pub use alias::*;
 pub use call_info::*;
 pub use command::*;
+pub use confirm::*;
 pub use engine_state::*;
+pub use file::*;
+pub use output::*;
+pub use overlay::*;
+pub use regex_cache::*;
 pub use stack::*;
+pub use warnings::*;
@@ -0,0 +1,157 @@
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+/// Where the shell writes what it shows: rendered results on one side, reported errors on the other
+///
+/// The engine state carries one of these so that output can be captured (eg, by tests) or
+/// redirected without the commands and renderers knowing about it.
+pub trait Output: Send + Sync {
+    /// Write rendered output, like the table showing a pipeline's result
+    fn write_out(&self, bytes: &[u8]) -> io::Result<()>;
+
+    /// Write a diagnostic, like a reported error
+    fn write_err(&self, bytes: &[u8]) -> io::Result<()>;
+
+    /// Whether diagnostics are shown on a terminal, where a line can be redrawn as it changes,
+    /// eg to show progress
+    fn err_is_terminal(&self) -> bool {
+        false
+    }
+}
+
+/// The process's stdout and stderr. All writes to the terminal go through here, so this is the place
+/// for anything the console needs to know about them
+pub struct StdOutput;
+
+impl StdOutput {
+    /// The Windows console only understands ANSI escapes, like the colors of a table or the line
+    /// `save` redraws for its progress, once asked to. That is done before the first write
+    #[cfg(windows)]
+    fn prepare_console() {
+        static ENABLE_ANSI: std::sync::Once = std::sync::Once::new();
+
+        ENABLE_ANSI.call_once(|| {
+            // Older consoles can't, and get the escapes as they are
+            let _ = nu_ansi_term::enable_ansi_support();
+        });
+    }
+
+    #[cfg(not(windows))]
+    fn prepare_console() {}
+}
+
+impl Output for StdOutput {
+    fn write_out(&self, bytes: &[u8]) -> io::Result<()> {
+        Self::prepare_console();
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        stdout.write_all(bytes)?;
+        stdout.flush()
+    }
+
+    fn write_err(&self, bytes: &[u8]) -> io::Result<()> {
+        Self::prepare_console();
+        let stderr = io::stderr();
+        let mut stderr = stderr.lock();
+        stderr.write_all(bytes)?;
+        stderr.flush()
+    }
+
+    fn err_is_terminal(&self) -> bool {
+        atty::is(atty::Stream::Stderr)
+    }
+}
+
+/// Keeps everything written in memory, to be read back later. Used to check rendered output
+#[derive(Default)]
+pub struct BufferedOutput {
+    out: Mutex<Vec<u8>>,
+    err: Mutex<Vec<u8>>,
+    terminal: bool,
+}
+
+impl BufferedOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A buffer that passes for a terminal, to check what is only shown on one
+    pub fn terminal() -> Self {
+        Self {
+            terminal: true,
+            ..Self::default()
+        }
+    }
+
+    /// Everything written as output so far, leaving the buffer empty
+    pub fn take_out(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.out.lock().expect("output buffer poisoned"))
+    }
+
+    /// Everything written as diagnostics so far, leaving the buffer empty
+    pub fn take_err(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.err.lock().expect("output buffer poisoned"))
+    }
+}
+
+impl Output for BufferedOutput {
+    fn write_out(&self, bytes: &[u8]) -> io::Result<()> {
+        self.out
+            .lock()
+            .expect("output buffer poisoned")
+            .extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn write_err(&self, bytes: &[u8]) -> io::Result<()> {
+        self.err
+            .lock()
+            .expect("output buffer poisoned")
+            .extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn err_is_terminal(&self) -> bool {
+        self.terminal
+    }
+}
+
+/// The diagnostics side of an output as a writer, for code that writes as it goes
+pub struct ErrWriter(pub Arc<dyn Output>);
+
+impl Write for ErrWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write_err(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn buffers_output_and_errors_apart() {
+        let output = BufferedOutput::new();
+        output.write_out(b"a table").expect("buffers don't fail");
+        output.write_err(b"an error").expect("buffers don't fail");
+        output.write_out(b"\n").expect("buffers don't fail");
+
+        assert_eq!(output.take_out(), b"a table\n");
+        assert_eq!(output.take_err(), b"an error");
+        assert!(output.take_out().is_empty());
+    }
+
+    #[test]
+    fn writes_diagnostics_through_the_output() {
+        let output = Arc::new(BufferedOutput::new());
+        let mut writer = ErrWriter(output.clone());
+        writeln!(writer, "0: {}", 42).expect("buffers don't fail");
+
+        assert_eq!(output.take_err(), b"0: 42\n");
+    }
+}
@@ -0,0 +1,86 @@
+use super::Alias;
+use crate::{DeclId, Value};
+use serde::{Deserialize, Serialize};
+
+/// What a module exports, and what `use` brings into scope from it: commands, aliases and
+/// environment variables
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Overlay {
+    pub decls: Vec<(Vec<u8>, DeclId)>,
+    pub aliases: Vec<(Vec<u8>, Alias)>,
+    pub env_vars: Vec<(Vec<u8>, Value)>,
+}
+
+impl Overlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decls.is_empty() && self.aliases.is_empty() && self.env_vars.is_empty()
+    }
+
+    /// The names of the commands and aliases. Environment variables aren't called by name, so
+    /// they're left out
+    pub fn callable_names(&self) -> impl Iterator<Item = &Vec<u8>> {
+        self.decls
+            .iter()
+            .map(|(name, _)| name)
+            .chain(self.aliases.iter().map(|(name, _)| name))
+    }
+
+    /// The commands and aliases renamed to `<prefix> <name>`, the way `use <module>` imports
+    /// them. Environment variables keep their names
+    pub fn with_prefix(self, prefix: &[u8]) -> Self {
+        let prefixed = |name: Vec<u8>| {
+            let mut new_name = prefix.to_vec();
+            new_name.push(b' ');
+            new_name.extend(name);
+            new_name
+        };
+
+        Self {
+            decls: self
+                .decls
+                .into_iter()
+                .map(|(name, decl_id)| (prefixed(name), decl_id))
+                .collect(),
+            aliases: self
+                .aliases
+                .into_iter()
+                .map(|(name, alias)| (prefixed(name), alias))
+                .collect(),
+            env_vars: self.env_vars,
+        }
+    }
+
+    /// Only what's exported as `name`
+    pub fn only(&self, name: &[u8]) -> Self {
+        Self {
+            decls: self
+                .decls
+                .iter()
+                .filter(|(n, _)| n == name)
+                .cloned()
+                .collect(),
+            aliases: self
+                .aliases
+                .iter()
+                .filter(|(n, _)| n == name)
+                .cloned()
+                .collect(),
+            env_vars: self
+                .env_vars
+                .iter()
+                .filter(|(n, _)| n == name)
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn extend(&mut self, other: Overlay) {
+        self.decls.extend(other.decls);
+        self.aliases.extend(other.aliases);
+        self.env_vars.extend(other.env_vars);
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::{ShellError, Span};
+use regex::Regex;
+
+/// How many compiled regexes the engine's cache keeps
+const CACHE_SIZE: usize = 64;
+
+type Compile = dyn Fn(&str) -> Result<Regex, regex::Error> + Send + Sync;
+
+/// Compiled regexes by pattern, dropping the least recently used one when it's full. Only
+/// patterns that compile are kept, so that a bad pattern is an error at the argument it was given
+/// as every time it's used
+pub struct RegexCache {
+    capacity: usize,
+    compile: Box<Compile>,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    /// Each regex, and the tick it was last used at
+    regexes: HashMap<String, (Regex, u64)>,
+    ticks: u64,
+}
+
+impl Entries {
+    fn tick(&mut self) -> u64 {
+        self.ticks += 1;
+        self.ticks
+    }
+}
+
+impl Default for RegexCache {
+    fn default() -> Self {
+        RegexCache::new(CACHE_SIZE)
+    }
+}
+
+impl RegexCache {
+    pub fn new(capacity: usize) -> RegexCache {
+        RegexCache::with_compiler(capacity, Regex::new)
+    }
+
+    /// A cache compiling patterns with `compile`, eg to count the compilations in tests
+    pub fn with_compiler(
+        capacity: usize,
+        compile: impl Fn(&str) -> Result<Regex, regex::Error> + Send + Sync + 'static,
+    ) -> RegexCache {
+        RegexCache {
+            capacity: capacity.max(1),
+            compile: Box::new(compile),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    /// The compiled `pattern`, which is an error at `span` if it doesn't compile. Regexes share
+    /// their compiled form, so the clone handed out is cheap
+    pub fn get(&self, pattern: &str, span: Span) -> Result<Regex, ShellError> {
+        {
+            let mut entries = self.entries.lock().expect("regex cache poisoned");
+            let now = entries.tick();
+            if let Some((regex, last_used)) = entries.regexes.get_mut(pattern) {
+                *last_used = now;
+                return Ok(regex.clone());
+            }
+        }
+
+        // Compiling can take a while, so other patterns can be looked up in the meantime
+        let regex = (self.compile)(pattern)
+            .map_err(|err| ShellError::InvalidRegex(err.to_string(), span))?;
+
+        let mut entries = self.entries.lock().expect("regex cache poisoned");
+        if entries.regexes.len() >= self.capacity && !entries.regexes.contains_key(pattern) {
+            let least_recent = entries
+                .regexes
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(pattern, _)| pattern.clone());
+            if let Some(least_recent) = least_recent {
+                entries.regexes.remove(&least_recent);
+            }
+        }
+        let now = entries.tick();
+        entries
+            .regexes
+            .insert(pattern.to_string(), (regex.clone(), now));
+
+        Ok(regex)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// A cache that counts the patterns it compiles
+    fn counting_cache(capacity: usize) -> (RegexCache, Arc<AtomicUsize>) {
+        let compiled = Arc::new(AtomicUsize::new(0));
+        let counter = compiled.clone();
+        let cache = RegexCache::with_compiler(capacity, move |pattern| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Regex::new(pattern)
+        });
+
+        (cache, compiled)
+    }
+
+    fn cached(cache: &RegexCache) -> usize {
+        cache
+            .entries
+            .lock()
+            .expect("regex cache poisoned")
+            .regexes
+            .len()
+    }
+
+    #[test]
+    fn compiles_each_pattern_once() {
+        let (cache, compiled) = counting_cache(4);
+
+        for _ in 0..100 {
+            let regex = cache.get("b+", Span::unknown()).expect("a valid regex");
+            assert!(regex.is_match("abbc"));
+        }
+
+        assert_eq!(compiled.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn drops_the_least_recently_used() {
+        let (cache, compiled) = counting_cache(2);
+        let get = |pattern| cache.get(pattern, Span::unknown()).expect("a valid regex");
+
+        get("a");
+        get("b");
+        get("a");
+        get("c");
+        assert_eq!(cached(&cache), 2);
+        assert_eq!(compiled.load(Ordering::SeqCst), 3);
+
+        // "b" was dropped for "c", "a" is still there
+        get("a");
+        assert_eq!(compiled.load(Ordering::SeqCst), 3);
+        get("b");
+        assert_eq!(compiled.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn bad_patterns_are_an_error_at_each_use() {
+        let (cache, compiled) = counting_cache(4);
+
+        for span in [Span::new(1, 4), Span::new(10, 13)] {
+            match cache.get("a(b", span) {
+                Err(ShellError::InvalidRegex(_, at)) => assert_eq!(at, span),
+                Err(other) => panic!("expected an invalid regex error, got {:?}", other),
+                Ok(_) => panic!("expected an error"),
+            }
+        }
+
+        assert_eq!(cached(&cache), 0);
+        assert_eq!(compiled.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn can_be_shared_between_threads() {
+        let (cache, compiled) = counting_cache(8);
+        let cache = Arc::new(cache);
+
+        let threads: Vec<_> = (0..8)
+            .map(|thread| {
+                let cache = cache.clone();
+                std::thread::spawn(move || {
+                    for idx in 0..500 {
+                        let pattern = format!("^row{}$", (thread + idx) % 4);
+                        let regex = cache.get(&pattern, Span::unknown()).expect("a valid regex");
+                        assert!(regex.is_match(&pattern[1..pattern.len() - 1]));
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().expect("no thread panicked");
+        }
+
+        assert_eq!(cached(&cache), 4);
+        // Threads that miss at the same time may each compile, but never many times over
+        assert!(compiled.load(Ordering::SeqCst) <= 4 * 8);
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::BTreeSet;
+
+use super::{EngineState, ScopeFrame, StateWorkingSet};
+use crate::{PositionalArg, Signature, Span, Value};
+
+impl EngineState {
+    /// What's in scope, as `$scope` evaluates to it. See `StateWorkingSet::get_scope_snapshot`
+    pub fn get_scope_snapshot(&self, span: Span) -> Value {
+        StateWorkingSet::new(self).get_scope_snapshot(span)
+    }
+}
+
+impl<'a> StateWorkingSet<'a> {
+    /// What's in scope, as a record of `vars` (name -> type), `commands` (with their signatures),
+    /// `aliases` and `modules`. Only what can be called by name is listed: hidden commands and
+    /// modules are left out, and a name bound in several scope frames is listed once, for its
+    /// innermost binding
+    pub fn get_scope_snapshot(&self, span: Span) -> Value {
+        let vars = self.names_in_scope(|frame| frame.vars.keys());
+        let (var_names, var_types) = vars
+            .into_iter()
+            .filter_map(|name| {
+                let var_id = self.find_variable(&name)?;
+                Some((
+                    String::from_utf8_lossy(&name).to_string(),
+                    Value::string(self.get_variable(var_id).to_string(), span),
+                ))
+            })
+            .unzip();
+
+        let commands = self
+            .names_in_scope(|frame| frame.decls.keys())
+            .into_iter()
+            .filter_map(|name| {
+                let decl = self.get_decl(self.find_decl(&name)?);
+                Some(command_to_value(
+                    &String::from_utf8_lossy(&name),
+                    &decl.signature(),
+                    decl.usage(),
+                    span,
+                ))
+            })
+            .collect();
+
+        let aliases = self
+            .names_in_scope(|frame| frame.aliases.keys())
+            .into_iter()
+            .filter(|name| self.find_alias(name).is_some())
+            .map(|name| Value::string(String::from_utf8_lossy(&name), span))
+            .collect();
+
+        let modules = self
+            .get_module_names()
+            .into_iter()
+            .map(|name| Value::string(String::from_utf8_lossy(&name), span))
+            .collect();
+
+        Value::Record {
+            cols: vec![
+                "vars".into(),
+                "commands".into(),
+                "aliases".into(),
+                "modules".into(),
+            ],
+            vals: vec![
+                Value::Record {
+                    cols: var_names,
+                    vals: var_types,
+                    span,
+                },
+                Value::List {
+                    vals: commands,
+                    span,
+                },
+                Value::List {
+                    vals: aliases,
+                    span,
+                },
+                Value::List {
+                    vals: modules,
+                    span,
+                },
+            ],
+            span,
+        }
+    }
+
+    /// The names any scope frame binds, sorted and without duplicates
+    fn names_in_scope<'b, F, I>(&'b self, names: F) -> BTreeSet<Vec<u8>>
+    where
+        F: Fn(&'b ScopeFrame) -> I,
+        I: Iterator<Item = &'b Vec<u8>>,
+    {
+        self.permanent_state
+            .scope
+            .iter()
+            .chain(self.delta.scope.iter())
+            .flat_map(names)
+            .cloned()
+            .collect()
+    }
+}
+
+fn command_to_value(name: &str, signature: &Signature, usage: &str, span: Span) -> Value {
+    let flags = signature
+        .named
+        .iter()
+        .map(|flag| Value::Record {
+            cols: vec![
+                "long".into(),
+                "short".into(),
+                "arg".into(),
+                "required".into(),
+                "desc".into(),
+            ],
+            vals: vec![
+                Value::string(&flag.long, span),
+                flag.short.map_or(Value::Nothing { span }, |short| {
+                    Value::string(short.to_string(), span)
+                }),
+                flag.arg.as_ref().map_or(Value::Nothing { span }, |arg| {
+                    Value::string(arg.to_type().to_string(), span)
+                }),
+                Value::Bool {
+                    val: flag.required,
+                    span,
+                },
+                Value::string(&flag.desc, span),
+            ],
+            span,
+        })
+        .collect();
+
+    let positionals = signature
+        .required_positional
+        .iter()
+        .map(|arg| positional_to_value(arg, "required", span))
+        .chain(
+            signature
+                .optional_positional
+                .iter()
+                .map(|arg| positional_to_value(arg, "optional", span)),
+        )
+        .chain(
+            signature
+                .rest_positional
+                .iter()
+                .map(|arg| positional_to_value(arg, "rest", span)),
+        )
+        .collect();
+
+    Value::Record {
+        cols: vec![
+            "name".into(),
+            "usage".into(),
+            "flags".into(),
+            "positionals".into(),
+        ],
+        vals: vec![
+            Value::string(name, span),
+            Value::string(usage, span),
+            Value::List { vals: flags, span },
+            Value::List {
+                vals: positionals,
+                span,
+            },
+        ],
+        span,
+    }
+}
+
+fn positional_to_value(arg: &PositionalArg, kind: &str, span: Span) -> Value {
+    Value::Record {
+        cols: vec!["name".into(), "kind".into(), "shape".into(), "desc".into()],
+        vals: vec![
+            Value::string(&arg.name, span),
+            Value::string(kind, span),
+            Value::string(arg.shape.to_type().to_string(), span),
+            Value::string(&arg.desc, span),
+        ],
+        span,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_support::{column, engine_state_with, items};
+
+    fn state_with_decls(names: &[&str]) -> EngineState {
+        engine_state_with(
+            names
+                .iter()
+                .map(|name| Signature::build(*name).predeclare())
+                .collect(),
+        )
+    }
+
+    fn command_names(snapshot: &Value) -> Vec<String> {
+        items(column(snapshot, "commands"))
+            .iter()
+            .map(|command| column(command, "name").as_string().expect("a name"))
+            .collect()
+    }
+
+    #[test]
+    fn renders_command_signatures() {
+        let engine_state = engine_state_with(vec![Signature::build("str pad")
+            .required("width", crate::SyntaxShape::Int, "how wide")
+            .switch("left", "pad on the left", Some('l'))
+            .predeclare()]);
+
+        let snapshot = engine_state.get_scope_snapshot(Span::unknown());
+        let command = &items(column(&snapshot, "commands"))[0];
+
+        assert_eq!(column(command, "name"), &Value::test_string("str pad"));
+        let flags = items(column(command, "flags"));
+        // `help` comes with every signature
+        assert_eq!(flags.len(), 2);
+        assert_eq!(column(&flags[1], "short"), &Value::test_string("l"));
+        let positionals = items(column(command, "positionals"));
+        assert_eq!(column(&positionals[0], "shape"), &Value::test_string("int"));
+        assert_eq!(
+            column(&positionals[0], "kind"),
+            &Value::test_string("required")
+        );
+    }
+
+    #[test]
+    fn hidden_commands_are_left_out() {
+        let engine_state = state_with_decls(&["foo", "bar"]);
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.hide_decl(b"foo");
+
+        assert_eq!(
+            command_names(&working_set.get_scope_snapshot(Span::unknown())),
+            vec!["bar"]
+        );
+        assert_eq!(
+            command_names(&engine_state.get_scope_snapshot(Span::unknown())),
+            vec!["bar", "foo"]
+        );
+    }
+
+    #[test]
+    fn shadowed_commands_are_listed_once() {
+        let engine_state = state_with_decls(&["foo"]);
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        working_set.enter_scope();
+        let inner =
+            working_set.add_decl(Signature::build("foo").desc("the inner foo").predeclare());
+        assert_eq!(working_set.find_decl(b"foo"), Some(inner));
+
+        let snapshot = working_set.get_scope_snapshot(Span::unknown());
+        assert_eq!(command_names(&snapshot), vec!["foo"]);
+        let commands = items(column(&snapshot, "commands"));
+        assert_eq!(
+            column(&commands[0], "usage"),
+            &Value::test_string("the inner foo")
+        );
+    }
+}
@@ -1,6 +1,12 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
-use crate::{Config, ShellError, Value, VarId, CONFIG_VARIABLE_ID};
+use crate::{Config, ShellError, Span, Value, VarId, CONFIG_VARIABLE_ID};
+
+/// The env var that holds how long the last pipeline took, in milliseconds
+pub const CMD_DURATION_ENV: &str = "CMD_DURATION_MS";
+/// The env var that holds the status of the last pipeline: 0 if it succeeded
+pub const LAST_EXIT_CODE_ENV: &str = "LAST_EXIT_CODE";
 
 /// A runtime value stack used during evaluation
 ///
@@ -22,7 +28,9 @@ use crate::{Config, ShellError, Value, VarId, CONFIG_VARIABLE_ID};
 #[derive(Debug, Clone)]
 pub struct Stack {
     pub vars: HashMap<VarId, Value>,
-    pub env_vars: HashMap<String, String>,
+    /// The environment, as the shell works with it. Variables with an env conversion are
+    /// structured here, and only turned into strings for external commands
+    pub env_vars: HashMap<String, Value>,
 }
 
 impl Default for Stack {
@@ -50,10 +58,28 @@ impl Stack {
         self.vars.insert(var_id, value);
     }
 
-    pub fn add_env_var(&mut self, var: String, value: String) {
+    pub fn add_env_var(&mut self, var: String, value: Value) {
         self.env_vars.insert(var, value);
     }
 
+    /// Record how the last pipeline went, for the prompt and the next pipeline to read
+    pub fn record_pipeline(&mut self, duration: Duration, exit_code: i64) {
+        self.add_env_var(
+            CMD_DURATION_ENV.into(),
+            Value::Int {
+                val: duration.as_millis() as i64,
+                span: Span::unknown(),
+            },
+        );
+        self.add_env_var(
+            LAST_EXIT_CODE_ENV.into(),
+            Value::Int {
+                val: exit_code,
+                span: Span::unknown(),
+            },
+        );
+    }
+
     pub fn collect_captures(&self, captures: &[VarId]) -> Stack {
         let mut output = Stack::new();
 
@@ -76,15 +102,12 @@ impl Stack {
         output
     }
 
-    pub fn get_env_vars(&self) -> HashMap<String, String> {
+    pub fn get_env_vars(&self) -> HashMap<String, Value> {
         self.env_vars.clone()
     }
 
-    pub fn get_env_var(&self, name: &str) -> Option<String> {
-        if let Some(v) = self.env_vars.get(name) {
-            return Some(v.to_string());
-        }
-        None
+    pub fn get_env_var(&self, name: &str) -> Option<Value> {
+        self.env_vars.get(name).cloned()
     }
 
     pub fn get_config(&self) -> Result<Config, ShellError> {
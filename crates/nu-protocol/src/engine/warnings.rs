@@ -0,0 +1,65 @@
+use std::collections::HashSet;
+
+use crate::ShellWarning;
+
+/// The warnings raised while pipelines run, kept until they're shown after the output. A warning
+/// is only kept the first time it's raised in a session, so that eg a deprecated command used in a
+/// loop is warned about once
+#[derive(Debug, Default)]
+pub struct WarningSink {
+    pending: Vec<ShellWarning>,
+    /// The messages of the warnings raised so far
+    seen: HashSet<String>,
+}
+
+impl WarningSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, warning: ShellWarning) {
+        if self.seen.insert(warning.to_string()) {
+            self.pending.push(warning);
+        }
+    }
+
+    /// The warnings raised since the last time they were taken
+    pub fn take(&mut self) -> Vec<ShellWarning> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Span;
+
+    fn deprecated(span: Span) -> ShellWarning {
+        ShellWarning::Message("spam is deprecated, use eggs instead".into(), span)
+    }
+
+    #[test]
+    fn keeps_each_warning_once_a_session() {
+        let mut sink = WarningSink::new();
+        sink.push(deprecated(Span { start: 0, end: 4 }));
+        sink.push(deprecated(Span { start: 10, end: 14 }));
+
+        assert_eq!(sink.take(), vec![deprecated(Span { start: 0, end: 4 })]);
+        assert!(sink.take().is_empty());
+
+        sink.push(deprecated(Span { start: 20, end: 24 }));
+        assert!(sink.take().is_empty());
+    }
+
+    #[test]
+    fn keeps_different_warnings() {
+        let mut sink = WarningSink::new();
+        sink.push(deprecated(Span::unknown()));
+        sink.push(ShellWarning::EnvConversionFailed {
+            name: "FOO".into(),
+            reason: "not json".into(),
+        });
+
+        assert_eq!(sink.take().len(), 2);
+    }
+}
@@ -1,7 +1,25 @@
 use crate::Value;
 
+/// An example of a command, shown in its help and run by its tests. Fields that most examples
+/// leave out can be filled in with `..Default::default()`
+#[derive(Default)]
 pub struct Example {
     pub example: &'static str,
     pub description: &'static str,
+    /// What the example gives back, when the tests should check it. A stream is given as the list
+    /// of its values. A `Value::Error` stands for an error of the same kind, whose labels contain
+    /// the expected labels' text, either failing the example or given back as a value
     pub result: Option<Value>,
+    /// Files and directories to create in a scratch directory before the example is run.
+    /// When present, the example is evaluated with that directory as its PWD
+    pub fixture: Option<&'static [FixtureEntry]>,
+}
+
+/// One entry of an example's filesystem fixture, with a path relative to the scratch directory
+pub enum FixtureEntry {
+    File {
+        path: &'static str,
+        contents: &'static str,
+    },
+    Dir(&'static str),
 }
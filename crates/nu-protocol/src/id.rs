@@ -1,3 +1,67 @@
-pub type VarId = usize;
-pub type DeclId = usize;
-pub type BlockId = usize;
+use serde::{Deserialize, Serialize};
+
+macro_rules! id_type {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[repr(transparent)]
+        #[derive(
+            Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(usize);
+
+        impl $name {
+            pub const fn new(index: usize) -> Self {
+                Self(index)
+            }
+
+            /// The position of the item in its engine state table
+            pub const fn get(self) -> usize {
+                self.0
+            }
+        }
+
+        impl From<usize> for $name {
+            fn from(index: usize) -> Self {
+                Self(index)
+            }
+        }
+
+        impl From<$name> for usize {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    };
+}
+
+id_type!(
+    /// Index of a variable in the engine state
+    ///
+    /// The different kinds of ids can't be mixed up:
+    ///
+    /// ```compile_fail
+    /// use nu_protocol::{DeclId, VarId};
+    ///
+    /// fn lookup_var(var_id: VarId) -> usize {
+    ///     var_id.get()
+    /// }
+    ///
+    /// lookup_var(DeclId::new(0));
+    /// ```
+    VarId
+);
+id_type!(
+    /// Index of a declaration (command) in the engine state
+    DeclId
+);
+id_type!(
+    /// Index of a block in the engine state
+    BlockId
+);
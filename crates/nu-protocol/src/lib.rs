@@ -5,9 +5,12 @@ mod example;
 mod id;
 mod pipeline_data;
 mod shell_error;
+mod shell_warning;
 mod signature;
 mod span;
 mod syntax_shape;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 mod ty;
 mod value;
 pub use value::Value;
@@ -18,6 +21,7 @@ pub use example::*;
 pub use id::*;
 pub use pipeline_data::*;
 pub use shell_error::*;
+pub use shell_warning::*;
 pub use signature::*;
 pub use span::*;
 pub use syntax_shape::*;
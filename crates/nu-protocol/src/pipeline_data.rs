@@ -1,4 +1,7 @@
-use std::sync::{atomic::AtomicBool, Arc};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
 
 use crate::{ast::PathMember, Config, ShellError, Span, Value, ValueStream};
 
@@ -36,11 +39,98 @@ pub enum PipelineData {
     Stream(ValueStream),
 }
 
+/// Information about where the data in a pipeline came from, so that viewers can render it
+/// appropriately (eg, coloring the file names from `ls`) even after it went through filters
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PipelineMetadata {
+    pub data_source: Option<DataSource>,
+    /// Where the rows were before something up the pipeline, like `where`, left some of them out
+    pub positions: Option<RowPositions>,
+    /// How many bytes a stream of binary chunks adds up to, when its source says, eg the
+    /// Content-Length of a download
+    pub byte_len: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataSource {
+    Ls,
+}
+
+/// The positions the rows of a filtered stream had in its input, recorded as the rows are pulled
+/// through the filter, so that errors about a row can name it by where it was to begin with
+#[derive(Debug, Clone, Default)]
+pub struct RowPositions(Arc<Mutex<Vec<usize>>>);
+
+impl RowPositions {
+    /// Record where the next row let through was
+    pub fn push(&self, position: usize) {
+        if let Ok(mut positions) = self.0.lock() {
+            positions.push(position);
+        }
+    }
+
+    /// Where the `idx`th row let through was, or `idx` if that wasn't recorded
+    pub fn get(&self, idx: usize) -> usize {
+        self.0
+            .lock()
+            .ok()
+            .and_then(|positions| positions.get(idx).copied())
+            .unwrap_or(idx)
+    }
+}
+
+impl PartialEq for RowPositions {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
 impl PipelineData {
     pub fn new(span: Span) -> PipelineData {
         PipelineData::Value(Value::Nothing { span })
     }
 
+    pub fn metadata(&self) -> Option<PipelineMetadata> {
+        match self {
+            PipelineData::Stream(stream) => stream.metadata.clone(),
+            PipelineData::Value(..) => None,
+        }
+    }
+
+    /// Attach metadata to the stream. Single values don't carry metadata, so for those this is a no-op
+    pub fn set_metadata(mut self, metadata: Option<PipelineMetadata>) -> PipelineData {
+        if let PipelineData::Stream(stream) = &mut self {
+            stream.metadata = metadata;
+        }
+
+        self
+    }
+
+    /// Whether the data never ends, being a range without an end or a stream coming from one
+    pub fn is_endless(&self) -> bool {
+        match self {
+            PipelineData::Value(Value::Range { val, .. }) => val.is_unbounded(),
+            PipelineData::Value(..) => false,
+            PipelineData::Stream(stream) => stream.endless.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Mark the stream as coming from a range without an end. Single values are what they are, so
+    /// for those this is a no-op
+    pub fn set_endless(self, endless: bool) -> PipelineData {
+        self.share_endless(Arc::new(AtomicBool::new(endless)))
+    }
+
+    /// Like `set_endless`, with a flag the maker of the rows keeps, so that it can clear it when
+    /// the stream stops early after all, eg at a failed row
+    pub fn share_endless(mut self, endless: Arc<AtomicBool>) -> PipelineData {
+        if let PipelineData::Stream(stream) = &mut self {
+            stream.endless = endless;
+        }
+
+        self
+    }
+
     pub fn into_value(self, span: Span) -> Value {
         match self {
             PipelineData::Value(v) => v,
@@ -96,7 +186,8 @@ impl PipelineData {
         Self: Sized,
         F: FnMut(Value) -> Value + 'static + Send,
     {
-        match self {
+        let endless = self.is_endless();
+        let output = match self {
             PipelineData::Value(Value::List { vals, .. }) => {
                 Ok(vals.into_iter().map(f).into_pipeline_data(ctrlc))
             }
@@ -111,7 +202,9 @@ impl PipelineData {
                     v => Ok(v.into_pipeline_data()),
                 }
             }
-        }
+        };
+
+        output.map(|output| output.set_endless(endless))
     }
 
     /// Simplified flatmapper. For full iterator support use `.into_iter()` instead
@@ -126,7 +219,8 @@ impl PipelineData {
         <U as IntoIterator>::IntoIter: 'static + Send,
         F: FnMut(Value) -> U + 'static + Send,
     {
-        match self {
+        let endless = self.is_endless();
+        let output = match self {
             PipelineData::Value(Value::List { vals, .. }) => {
                 Ok(vals.into_iter().map(f).flatten().into_pipeline_data(ctrlc))
             }
@@ -136,7 +230,9 @@ impl PipelineData {
                 Err(error) => Err(error),
             },
             PipelineData::Value(v) => Ok(f(v).into_iter().into_pipeline_data(ctrlc)),
-        }
+        };
+
+        output.map(|output| output.set_endless(endless))
     }
 
     pub fn filter<F>(
@@ -148,11 +244,24 @@ impl PipelineData {
         Self: Sized,
         F: FnMut(&Value) -> bool + 'static + Send,
     {
-        match self {
+        let endless = self.is_endless();
+        let output = match self {
             PipelineData::Value(Value::List { vals, .. }) => {
                 Ok(vals.into_iter().filter(f).into_pipeline_data(ctrlc))
             }
-            PipelineData::Stream(stream) => Ok(stream.filter(f).into_pipeline_data(ctrlc)),
+            PipelineData::Stream(mut stream) => {
+                // Filtering keeps the shape of the rows, so where they came from still applies.
+                // Where they were doesn't, once some are left out
+                let metadata = stream.metadata.take().map(|metadata| PipelineMetadata {
+                    positions: None,
+                    byte_len: None,
+                    ..metadata
+                });
+                Ok(stream
+                    .filter(f)
+                    .into_pipeline_data(ctrlc)
+                    .set_metadata(metadata))
+            }
             PipelineData::Value(Value::Range { val, .. }) => match val.into_range_iter() {
                 Ok(iter) => Ok(iter.filter(f).into_pipeline_data(ctrlc)),
                 Err(error) => Err(error),
@@ -164,7 +273,9 @@ impl PipelineData {
                     Ok(Value::Nothing { span: v.span()? }.into_pipeline_data())
                 }
             }
-        }
+        };
+
+        output.map(|output| output.set_endless(endless))
     }
 }
 
@@ -187,18 +298,27 @@ impl IntoIterator for PipelineData {
                 PipelineIterator(PipelineData::Stream(ValueStream {
                     stream: Box::new(vals.into_iter()),
                     ctrlc: None,
+                    metadata: None,
+                    endless: Arc::new(AtomicBool::new(false)),
                 }))
             }
-            PipelineData::Value(Value::Range { val, .. }) => match val.into_range_iter() {
-                Ok(val) => PipelineIterator(PipelineData::Stream(ValueStream {
-                    stream: Box::new(val),
-                    ctrlc: None,
-                })),
-                Err(e) => PipelineIterator(PipelineData::Stream(ValueStream {
-                    stream: Box::new(vec![Value::Error { error: e }].into_iter()),
-                    ctrlc: None,
-                })),
-            },
+            PipelineData::Value(Value::Range { val, .. }) => {
+                let endless = val.is_unbounded();
+                match val.into_range_iter() {
+                    Ok(val) => PipelineIterator(PipelineData::Stream(ValueStream {
+                        stream: Box::new(val),
+                        ctrlc: None,
+                        metadata: None,
+                        endless: Arc::new(AtomicBool::new(endless)),
+                    })),
+                    Err(e) => PipelineIterator(PipelineData::Stream(ValueStream {
+                        stream: Box::new(vec![Value::Error { error: e }].into_iter()),
+                        ctrlc: None,
+                        metadata: None,
+                        endless: Arc::new(AtomicBool::new(false)),
+                    })),
+                }
+            }
             x => PipelineIterator(x),
         }
     }
@@ -241,6 +361,8 @@ where
         PipelineData::Stream(ValueStream {
             stream: Box::new(self),
             ctrlc,
+            metadata: None,
+            endless: Arc::new(AtomicBool::new(false)),
         })
     }
 }
@@ -7,7 +7,7 @@ use crate::{ast::Operator, Span, Type};
 /// The fundamental error type for the evaluation engine. These cases represent different kinds of errors
 /// the evaluator might face, along with helpful spans to label. An error renderer will take this error value
 /// and pass it into an error viewer to display to the user.
-#[derive(Debug, Clone, Error, Diagnostic, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Error, Diagnostic, Serialize, Deserialize)]
 pub enum ShellError {
     #[error("Type mismatch during operation.")]
     #[diagnostic(code(nu::shell::type_mismatch), url(docsrs))]
@@ -60,6 +60,18 @@ pub enum ShellError {
         right_span: Span,
     },
 
+    #[error("Missing flag.")]
+    #[diagnostic(
+        code(nu::shell::flag_requires_flag),
+        url(docsrs),
+        help("add --{1}, or drop --{0}")
+    )]
+    FlagRequiresFlag(
+        String,
+        String,
+        #[label("--{0} only works together with --{1}")] Span,
+    ),
+
     #[error("Delimiter error")]
     #[diagnostic(code(nu::shell::delimiter_error), url(docsrs))]
     DelimiterError(String, #[label("{0}")] Span),
@@ -80,6 +92,14 @@ pub enum ShellError {
     #[diagnostic(code(nu::shell::internal_error), url(docsrs))]
     InternalError(String),
 
+    #[error("No source code for the span {0}..{1}.")]
+    #[diagnostic(
+        code(nu::shell::span_not_found),
+        url(docsrs),
+        help("the span may be from an engine state that has changed since")
+    )]
+    SpanNotFound(usize, usize),
+
     #[error("Variable not found!!!")]
     #[diagnostic(code(nu::shell::variable_not_found), url(docsrs))]
     VariableNotFoundAtRuntime(#[label = "variable not found"] Span),
@@ -88,6 +108,17 @@ pub enum ShellError {
     #[diagnostic(code(nu::shell::cant_convert), url(docsrs))]
     CantConvert(String, String, #[label("can't convert {1} to {0}")] Span),
 
+    #[error("Can't convert to {to}.")]
+    #[diagnostic(code(nu::shell::cant_convert_nested), url(docsrs))]
+    CantConvertNested {
+        to: String,
+        from: String,
+        /// Where the value is in the input, as a cell path, eg `rows.3.payload`
+        path: String,
+        #[label("can't convert {from} at {path} to {to}")]
+        span: Span,
+    },
+
     #[error("Division by zero.")]
     #[diagnostic(code(nu::shell::division_by_zero), url(docsrs))]
     DivisionByZero(#[label("division by zero")] Span),
@@ -96,6 +127,14 @@ pub enum ShellError {
     #[diagnostic(code(nu::shell::range_to_countable), url(docsrs))]
     CannotCreateRange(#[label = "can't convert to countable values"] Span),
 
+    #[error("Can't collect a range without an end")]
+    #[diagnostic(
+        code(nu::shell::unbounded_range),
+        url(docsrs),
+        help("give the range an end, or take part of it first, eg with `first`")
+    )]
+    UnboundedRange(#[label("needs all of its input, but the range never ends")] Span),
+
     #[error("Row number too large (max: {0}).")]
     #[diagnostic(code(nu::shell::access_beyond_end), url(docsrs))]
     AccessBeyondEnd(usize, #[label = "too large"] Span),
@@ -146,6 +185,34 @@ pub enum ShellError {
     #[diagnostic(code(nu::shell::file_not_found), url(docsrs))]
     FileNotFoundCustom(String, #[label("{0}")] Span),
 
+    #[error("Non-UTF8 output")]
+    #[diagnostic(
+        code(nu::shell::non_utf8),
+        url(docsrs),
+        help("set `external_invalid_utf8` in the config to \"bytes\" or \"lossy\" to accept it")
+    )]
+    NonUtf8(#[label("gave output that isn't valid UTF-8")] Span),
+
+    #[error("Permission denied")]
+    #[diagnostic(code(nu::shell::permission_denied), url(docsrs))]
+    PermissionDenied(String, #[label("can't read {0}")] Span),
+
+    #[error("Broken link")]
+    #[diagnostic(
+        code(nu::shell::broken_link),
+        url(docsrs),
+        help("the link points to a path that doesn't exist")
+    )]
+    BrokenLink(String, #[label("{0} is a broken link")] Span),
+
+    #[error("Invalid glob pattern")]
+    #[diagnostic(code(nu::shell::invalid_glob_pattern), url(docsrs))]
+    InvalidGlobPattern(String, #[label("{0}")] Span),
+
+    #[error("Invalid regex")]
+    #[diagnostic(code(nu::shell::invalid_regex), url(docsrs), help("{0}"))]
+    InvalidRegex(String, #[label("the regex doesn't compile")] Span),
+
     #[error("Directory not found")]
     #[diagnostic(code(nu::shell::directory_not_found), url(docsrs))]
     DirectoryNotFound(#[label("directory not found")] Span),
@@ -154,6 +221,14 @@ pub enum ShellError {
     #[diagnostic(code(nu::shell::file_not_found), url(docsrs))]
     DirectoryNotFoundCustom(String, #[label("{0}")] Span),
 
+    #[error("The current directory {0} doesn't exist")]
+    #[diagnostic(
+        code(nu::shell::current_dir_not_found),
+        url(docsrs),
+        help("cd to a directory that exists")
+    )]
+    CurrentDirNotFound(String),
+
     #[error("Move not possible")]
     #[diagnostic(code(nu::shell::move_not_possible), url(docsrs))]
     MoveNotPossible {
@@ -177,6 +252,90 @@ pub enum ShellError {
     #[diagnostic(code(nu::shell::remove_not_possible), url(docsrs))]
     RemoveNotPossible(String, #[label("{0}")] Span),
 
+    #[error("Refusing to remove a protected path")]
+    #[diagnostic(
+        code(nu::shell::remove_protected_path),
+        url(docsrs),
+        help("use --force together with --allow-protected if you really mean it")
+    )]
+    RemoveProtectedPath(String, #[label("{0}")] Span),
+
+    #[error("Can't ask for confirmation")]
+    #[diagnostic(
+        code(nu::shell::confirmation_not_available),
+        url(docsrs),
+        help("prompting only works in an interactive session, drop --interactive to go ahead without asking")
+    )]
+    ConfirmationNotAvailable(#[label("there is no terminal to ask on")] Span),
+
+    #[error("Stashed value not found")]
+    #[diagnostic(
+        code(nu::shell::stash_not_found),
+        url(docsrs),
+        help("`stash list` shows the names in use")
+    )]
+    StashNotFound(String, #[label("nothing is stashed as '{0}'")] Span),
+
+    #[error("Stashed value already exists")]
+    #[diagnostic(
+        code(nu::shell::stash_exists),
+        url(docsrs),
+        help("drop --no-clobber to overwrite it")
+    )]
+    StashExists(String, #[label("'{0}' is already stashed")] Span),
+
+    #[error("Can't hide a command while it is being defined")]
+    #[diagnostic(
+        code(nu::shell::cant_hide_predecl),
+        url(docsrs),
+        help("hide the command after its definition")
+    )]
+    CantHidePredecl(String, #[label("'{0}' is still being defined")] Span),
+
+    #[error("Error while processing row {row}")]
+    #[diagnostic(code(nu::shell::row_error), url(docsrs))]
+    InRow {
+        row: usize,
+        #[label("while processing row {row}")]
+        span: Span,
+        /// What went wrong with the row, rendered after the row it happened in
+        #[related]
+        cause: Vec<ShellError>,
+    },
+
+    #[error("Error while processing {path} in row {row}")]
+    #[diagnostic(code(nu::shell::cell_error), url(docsrs))]
+    InCell {
+        row: usize,
+        /// The column path of the cell, as it was written
+        path: String,
+        #[label("while processing {path} in row {row}")]
+        span: Span,
+        /// What went wrong with the cell, rendered after the cell it happened in
+        #[related]
+        cause: Vec<ShellError>,
+    },
+
+    #[error("Timed out")]
+    #[diagnostic(
+        code(nu::shell::timed_out),
+        url(docsrs),
+        help("use --partial to keep what the block gave back in time")
+    )]
+    TimedOut(String, #[label("the block didn't finish within {0}")] Span),
+
+    #[error("Network failure")]
+    #[diagnostic(code(nu::shell::network_failure), url(docsrs))]
+    NetworkFailure(String, #[label("{0}")] Span),
+
+    #[error("Can't use the parse cache: {0}.")]
+    #[diagnostic(
+        code(nu::shell::stale_parse_cache),
+        url(docsrs),
+        help("parse the source again, and cache the new delta")
+    )]
+    StaleParseCache(String),
+
     #[error("No file to be removed")]
     NoFileToBeRemoved(),
     #[error("No file to be moved")]
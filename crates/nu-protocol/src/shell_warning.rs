@@ -0,0 +1,52 @@
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::Span;
+
+/// Something the user should know about that doesn't fail the pipeline. Warnings are raised with
+/// `EngineState::warn`, and rendered like a `ShellError` after the pipeline's output, but as
+/// warnings
+#[derive(Debug, Clone, PartialEq, Error, Diagnostic)]
+pub enum ShellWarning {
+    #[error("Can't convert environment variable {name}.")]
+    #[diagnostic(
+        code(nu::shell::env_conversion_failed),
+        url(docsrs),
+        severity(Warning),
+        help("it's kept as it is, the conversion failed with: {reason}")
+    )]
+    EnvConversionFailed { name: String, reason: String },
+
+    #[error("{name} is deprecated.")]
+    #[diagnostic(
+        code(nu::shell::deprecated_command),
+        url(docsrs),
+        severity(Warning),
+        help("{notice}")
+    )]
+    DeprecatedCommand {
+        name: String,
+        notice: String,
+        #[label("deprecated")]
+        span: Span,
+    },
+
+    #[error("{name} isn't substituted in double-quoted strings.")]
+    #[diagnostic(
+        code(nu::shell::uninterpolated_variable),
+        url(docsrs),
+        severity(Warning),
+        help(
+            "interpolate it with $\"...({name})...\". Only single quotes keep it as it is written"
+        )
+    )]
+    UninterpolatedVariable {
+        name: String,
+        #[label("passed as it is")]
+        span: Span,
+    },
+
+    #[error("{0}")]
+    #[diagnostic(code(nu::shell::warning), url(docsrs), severity(Warning))]
+    Message(String, #[label("here")] Span),
+}
@@ -5,9 +5,11 @@ use crate::engine::Stack;
 use crate::BlockId;
 use crate::PipelineData;
 use crate::SyntaxShape;
+use crate::Type;
 use crate::VarId;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Flag {
     pub long: String,
     pub short: Option<char>,
@@ -18,7 +20,7 @@ pub struct Flag {
     pub var_id: Option<VarId>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PositionalArg {
     pub name: String,
     pub desc: String,
@@ -27,7 +29,72 @@ pub struct PositionalArg {
     pub var_id: Option<VarId>,
 }
 
-#[derive(Clone, Debug)]
+/// A rule about which flags can be used together, checked before the command runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlagConstraint {
+    /// The two flags can't be used together
+    ConflictsWith(String, String),
+    /// The first flag can only be used together with the second
+    Requires(String, String),
+}
+
+impl FlagConstraint {
+    /// The long names of the flags the rule is about
+    pub fn flags(&self) -> [&str; 2] {
+        match self {
+            FlagConstraint::ConflictsWith(flag, other) => [flag, other],
+            FlagConstraint::Requires(flag, required) => [flag, required],
+        }
+    }
+}
+
+/// What kind of work a command does, to group commands by in help and documentation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Category {
+    Default,
+    Conversions,
+    Core,
+    Date,
+    Env,
+    Experimental,
+    FileSystem,
+    Filters,
+    Formats,
+    Math,
+    Network,
+    Path,
+    Platform,
+    Strings,
+    System,
+    Viewers,
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Category::Default => "default",
+            Category::Conversions => "conversions",
+            Category::Core => "core",
+            Category::Date => "date",
+            Category::Env => "env",
+            Category::Experimental => "experimental",
+            Category::FileSystem => "filesystem",
+            Category::Filters => "filters",
+            Category::Formats => "formats",
+            Category::Math => "math",
+            Category::Network => "network",
+            Category::Path => "path",
+            Category::Platform => "platform",
+            Category::Strings => "strings",
+            Category::System => "system",
+            Category::Viewers => "viewers",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Signature {
     pub name: String,
     pub usage: String,
@@ -36,8 +103,48 @@ pub struct Signature {
     pub optional_positional: Vec<PositionalArg>,
     pub rest_positional: Option<PositionalArg>,
     pub named: Vec<Flag>,
+    pub flag_constraints: Vec<FlagConstraint>,
     pub is_filter: bool,
     pub creates_scope: bool,
+    pub category: Category,
+    /// What the command takes from the pipeline, `Type::Unknown` unless it says
+    pub input_type: Type,
+    /// What the command gives to the pipeline, `Type::Unknown` unless it says
+    pub output_type: Type,
+    /// Why the command shouldn't be used any more. Filled in from `Command::deprecated` when the
+    /// signatures are listed, for help to flag it
+    pub deprecated: Option<DeprecationNotice>,
+}
+
+/// Why a command shouldn't be used any more, and the command to use instead if there is one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeprecationNotice {
+    pub message: String,
+    pub replacement: Option<String>,
+}
+
+impl DeprecationNotice {
+    pub fn new(message: impl Into<String>) -> DeprecationNotice {
+        DeprecationNotice {
+            message: message.into(),
+            replacement: None,
+        }
+    }
+
+    /// Point users of the command to `replacement`
+    pub fn replaced_by(mut self, replacement: impl Into<String>) -> DeprecationNotice {
+        self.replacement = Some(replacement.into());
+        self
+    }
+}
+
+impl std::fmt::Display for DeprecationNotice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.replacement {
+            Some(replacement) => write!(f, "{} Use `{}` instead.", self.message, replacement),
+            None => write!(f, "{}", self.message),
+        }
+    }
 }
 
 impl PartialEq for Signature {
@@ -73,8 +180,13 @@ impl Signature {
             optional_positional: vec![],
             rest_positional: None,
             named: vec![flag],
+            flag_constraints: vec![],
             is_filter: false,
             creates_scope: false,
+            category: Category::Default,
+            input_type: Type::Unknown,
+            output_type: Type::Unknown,
+            deprecated: None,
         }
     }
     pub fn build(name: impl Into<String>) -> Signature {
@@ -202,6 +314,51 @@ impl Signature {
         self
     }
 
+    /// Refuse calls that use both `--flag` and `--other`
+    pub fn conflicts_with(
+        mut self,
+        flag: impl Into<String>,
+        other: impl Into<String>,
+    ) -> Signature {
+        self.flag_constraints
+            .push(FlagConstraint::ConflictsWith(flag.into(), other.into()));
+        self
+    }
+
+    /// Refuse calls that use `--flag` without `--required`
+    pub fn requires(mut self, flag: impl Into<String>, required: impl Into<String>) -> Signature {
+        self.flag_constraints
+            .push(FlagConstraint::Requires(flag.into(), required.into()));
+        self
+    }
+
+    /// The flags named in the flag constraints that the signature doesn't have
+    pub fn unknown_constraint_flags(&self) -> Vec<&str> {
+        self.flag_constraints
+            .iter()
+            .flat_map(|constraint| constraint.flags())
+            .filter(|name| self.get_long_flag(name).is_none())
+            .collect()
+    }
+
+    /// Set the category the command is listed under in help and documentation
+    pub fn category(mut self, category: Category) -> Signature {
+        self.category = category;
+        self
+    }
+
+    /// Set the type of the values the command takes from the pipeline
+    pub fn input_type(mut self, input_type: Type) -> Signature {
+        self.input_type = input_type;
+        self
+    }
+
+    /// Set the type of the values the command gives to the pipeline
+    pub fn output_type(mut self, output_type: Type) -> Signature {
+        self.output_type = output_type;
+        self
+    }
+
     /// Sets that signature will create a scope as it parses
     pub fn creates_scope(mut self) -> Signature {
         self.creates_scope = true;
@@ -2,7 +2,7 @@ use miette::SourceSpan;
 use serde::{Deserialize, Serialize};
 
 /// A spanned area of interest, generic over what kind of thing is of interest
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Spanned<T>
 where
     T: Clone + std::fmt::Debug,
@@ -45,6 +45,21 @@ impl Span {
     pub fn contains(&self, pos: usize) -> bool {
         pos >= self.start && pos < self.end
     }
+
+    /// The span from the start of the first of the two spans to the end of the last, whichever
+    /// order they're in. An unknown span adds nothing
+    pub fn merge(self, other: Span) -> Span {
+        if self == Span::unknown() {
+            other
+        } else if other == Span::unknown() {
+            self
+        } else {
+            Span {
+                start: self.start.min(other.start),
+                end: self.end.max(other.end),
+            }
+        }
+    }
 }
 
 pub fn span(spans: &[Span]) -> Span {
@@ -0,0 +1,62 @@
+//! Helpers for tests: engine states with a few commands in them, the stack evaluation expects,
+//! and ways into the values commands give back. Used by the tests of this crate, and by the
+//! crates further up through the `test-support` feature
+
+use crate::engine::{Command, EngineState, Stack, StateWorkingSet};
+use crate::{Span, Value, CONFIG_VARIABLE_ID};
+
+/// A fresh engine state with only `decls` in it
+pub fn engine_state_with(decls: Vec<Box<dyn Command>>) -> EngineState {
+    let mut engine_state = EngineState::new();
+    add_decls(&mut engine_state, decls);
+
+    engine_state
+}
+
+/// Add `decls` to `engine_state`, as if they had been parsed
+pub fn add_decls(engine_state: &mut EngineState, decls: Vec<Box<dyn Command>>) {
+    let delta = {
+        let mut working_set = StateWorkingSet::new(engine_state);
+        for decl in decls {
+            working_set.add_decl(decl);
+        }
+        working_set.render()
+    };
+    engine_state.merge_delta(delta);
+}
+
+/// A stack with the empty `$config` that evaluating blocks expects to find
+pub fn stack_with_config() -> Stack {
+    let mut stack = Stack::new();
+    stack.add_var(
+        CONFIG_VARIABLE_ID,
+        Value::Record {
+            cols: vec![],
+            vals: vec![],
+            span: Span::unknown(),
+        },
+    );
+
+    stack
+}
+
+/// The column `name` of `record`, failing the test if there's no such column
+pub fn column<'v>(record: &'v Value, name: &str) -> &'v Value {
+    match record {
+        Value::Record { cols, vals, .. } => {
+            &vals[cols
+                .iter()
+                .position(|col| col == name)
+                .expect("missing column")]
+        }
+        _ => panic!("not a record"),
+    }
+}
+
+/// The items of `list`, failing the test if it isn't a list
+pub fn items(list: &Value) -> &[Value] {
+    match list {
+        Value::List { vals, .. } => vals,
+        _ => panic!("not a list"),
+    }
+}
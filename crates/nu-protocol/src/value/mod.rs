@@ -5,6 +5,7 @@ mod unit;
 
 use chrono::{DateTime, FixedOffset};
 use chrono_humanize::HumanTime;
+use miette::Diagnostic;
 pub use range::*;
 pub use row::*;
 use serde::{Deserialize, Serialize};
@@ -15,7 +16,8 @@ use std::collections::HashMap;
 use std::{cmp::Ordering, fmt::Debug};
 
 use crate::ast::{CellPath, PathMember};
-use crate::{did_you_mean, span, BlockId, Config, Span, Spanned, Type};
+use crate::engine::RegexCache;
+use crate::{did_you_mean, BlockId, Config, Span, Spanned, Type};
 
 use crate::ShellError;
 
@@ -117,6 +119,9 @@ impl Value {
         }
     }
 
+    /// The boolean the value is. This is also how conditions, like the ones of `if` and `where`,
+    /// are read: only booleans are true or false, and there's no implicit truthiness, eg of numbers
+    /// or strings. `into bool` is the way to use other values as booleans
     pub fn as_bool(&self) -> Result<bool, ShellError> {
         match self {
             Value::Bool { val, .. } => Ok(*val),
@@ -149,6 +154,14 @@ impl Value {
         }
     }
 
+    /// Get the span of the value, or `fallback` if the value doesn't know where it came from
+    pub fn span_or(&self, fallback: Span) -> Span {
+        match self.span() {
+            Ok(span) if span != Span::unknown() => span,
+            _ => fallback,
+        }
+    }
+
     /// Update the value with a new span
     pub fn with_span(mut self, new_span: Span) -> Value {
         match &mut self {
@@ -280,7 +293,8 @@ impl Value {
         Value::Nothing { span }
     }
 
-    /// Follow a given column path into the value: for example accessing nth elements in a stream or list
+    /// Follow a given column path into the value: for example accessing nth elements in a stream or list.
+    /// What's found keeps its own span, where it was written, rather than the span of the path
     pub fn follow_cell_path(self, cell_path: &[PathMember]) -> Result<Value, ShellError> {
         let mut current = self;
         for member in cell_path {
@@ -300,11 +314,12 @@ impl Value {
                                 return Err(ShellError::AccessBeyondEnd(val.len(), *origin_span));
                             }
                         }
-                        Value::Binary { val, .. } => {
+                        Value::Binary { val, span } => {
                             if let Some(item) = val.get(*count) {
+                                // A byte was written where the binary it's in was
                                 current = Value::Int {
                                     val: *item as i64,
-                                    span: *origin_span,
+                                    span: *span,
                                 };
                             } else {
                                 return Err(ShellError::AccessBeyondEnd(val.len(), *origin_span));
@@ -449,6 +464,51 @@ impl Value {
         Ok(())
     }
 
+    /// Remove the data at the given cell path, for example a column from each row of a table
+    pub fn remove_data_at_cell_path(&mut self, cell_path: &[PathMember]) -> Result<(), ShellError> {
+        match cell_path {
+            [] => Ok(()),
+            [PathMember::String {
+                val: col_name,
+                span,
+            }, rest @ ..] => match self {
+                Value::List { vals, .. } => {
+                    for val in vals.iter_mut() {
+                        val.remove_data_at_cell_path(cell_path)?;
+                    }
+                    Ok(())
+                }
+                Value::Record {
+                    cols,
+                    vals,
+                    span: record_span,
+                } => match cols.iter().position(|col| col == col_name) {
+                    Some(index) if rest.is_empty() => {
+                        cols.remove(index);
+                        vals.remove(index);
+                        Ok(())
+                    }
+                    Some(index) => vals[index].remove_data_at_cell_path(rest),
+                    None => Err(ShellError::CantFindColumn(*span, *record_span)),
+                },
+                v => Err(ShellError::CantFindColumn(*span, v.span()?)),
+            },
+            [PathMember::Int { val: row_num, span }, rest @ ..] => match self {
+                Value::List { vals, .. } => {
+                    if *row_num >= vals.len() {
+                        Err(ShellError::AccessBeyondEnd(vals.len(), *span))
+                    } else if rest.is_empty() {
+                        vals.remove(*row_num);
+                        Ok(())
+                    } else {
+                        vals[*row_num].remove_data_at_cell_path(rest)
+                    }
+                }
+                v => Err(ShellError::NotAList(*span, v.span()?)),
+            },
+        }
+    }
+
     pub fn is_true(&self) -> bool {
         matches!(self, Value::Bool { val: true, .. })
     }
@@ -486,6 +546,14 @@ impl Value {
             span: Span::unknown(),
         }
     }
+
+    // Only use these for test data. Span::unknown() should not be used in user data
+    pub fn test_bool(val: bool) -> Value {
+        Value::Bool {
+            val,
+            span: Span::unknown(),
+        }
+    }
 }
 
 impl Default for Value {
@@ -565,9 +633,90 @@ impl PartialEq for Value {
     }
 }
 
+impl Value {
+    /// Compare two values, treating runs of digits inside strings as numbers (natural sort order),
+    /// so that "file2" comes before "file10". Non-string values fall back to the default ordering.
+    pub fn natural_cmp(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::String { val: lhs, .. }, Value::String { val: rhs, .. }) => {
+                Some(compare_natural(lhs, rhs))
+            }
+            (Value::List { vals: lhs, .. }, Value::List { vals: rhs, .. }) => {
+                for (lhs, rhs) in lhs.iter().zip(rhs) {
+                    match lhs.natural_cmp(rhs) {
+                        Some(Ordering::Equal) => {}
+                        other => return other,
+                    }
+                }
+                Some(lhs.len().cmp(&rhs.len()))
+            }
+            (lhs, rhs) => lhs.partial_cmp(rhs),
+        }
+    }
+}
+
+/// Natural ordering of two strings: digit runs are compared by their numeric value and everything
+/// else is compared character by character.
+///
+/// Digit runs are never parsed into an integer, so arbitrarily long runs are fine. When two runs
+/// have the same numeric value, the one with fewer leading zeros sorts first.
+pub fn compare_natural(lhs: &str, rhs: &str) -> Ordering {
+    let mut lhs = lhs.chars().peekable();
+    let mut rhs = rhs.chars().peekable();
+
+    loop {
+        match (lhs.peek().copied(), rhs.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(l), Some(r)) if l.is_ascii_digit() && r.is_ascii_digit() => {
+                let lhs_digits = take_digits(&mut lhs);
+                let rhs_digits = take_digits(&mut rhs);
+
+                let lhs_trimmed = lhs_digits.trim_start_matches('0');
+                let rhs_trimmed = rhs_digits.trim_start_matches('0');
+
+                // With leading zeros removed, a longer run is a larger number, and runs of the
+                // same length compare the same way as their digits do
+                let ordering = lhs_trimmed
+                    .len()
+                    .cmp(&rhs_trimmed.len())
+                    .then_with(|| lhs_trimmed.cmp(rhs_trimmed))
+                    .then_with(|| lhs_digits.len().cmp(&rhs_digits.len()));
+
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(l), Some(r)) => {
+                if l != r {
+                    return l.cmp(&r);
+                }
+                lhs.next();
+                rhs.next();
+            }
+        }
+    }
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+
+    while let Some(c) = chars.peek() {
+        if c.is_ascii_digit() {
+            digits.push(*c);
+            chars.next();
+        } else {
+            break;
+        }
+    }
+
+    digits
+}
+
 impl Value {
     pub fn add(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
             (Value::Int { val: lhs, .. }, Value::Int { val: rhs, .. }) => {
@@ -636,7 +785,7 @@ impl Value {
         }
     }
     pub fn sub(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
             (Value::Int { val: lhs, .. }, Value::Int { val: rhs, .. }) => {
@@ -701,7 +850,7 @@ impl Value {
         }
     }
     pub fn mul(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
             (Value::Int { val: lhs, .. }, Value::Int { val: rhs, .. }) => {
@@ -737,7 +886,7 @@ impl Value {
         }
     }
     pub fn div(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
             (Value::Int { val: lhs, .. }, Value::Int { val: rhs, .. }) => {
@@ -798,7 +947,7 @@ impl Value {
         }
     }
     pub fn lt(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match self.partial_cmp(rhs) {
             Some(ordering) => Ok(Value::Bool {
@@ -815,7 +964,7 @@ impl Value {
         }
     }
     pub fn lte(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match self.partial_cmp(rhs) {
             Some(ordering) => Ok(Value::Bool {
@@ -832,7 +981,7 @@ impl Value {
         }
     }
     pub fn gt(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match self.partial_cmp(rhs) {
             Some(ordering) => Ok(Value::Bool {
@@ -849,7 +998,7 @@ impl Value {
         }
     }
     pub fn gte(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match self.partial_cmp(rhs) {
             Some(ordering) => Ok(Value::Bool {
@@ -866,7 +1015,7 @@ impl Value {
         }
     }
     pub fn eq(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match self.partial_cmp(rhs) {
             Some(ordering) => Ok(Value::Bool {
@@ -883,7 +1032,7 @@ impl Value {
         }
     }
     pub fn ne(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match self.partial_cmp(rhs) {
             Some(ordering) => Ok(Value::Bool {
@@ -901,7 +1050,7 @@ impl Value {
     }
 
     pub fn r#in(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
             (lhs, Value::Range { val: rhs, .. }) => Ok(Value::Bool {
@@ -931,7 +1080,7 @@ impl Value {
     }
 
     pub fn not_in(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
             (lhs, Value::Range { val: rhs, .. }) => Ok(Value::Bool {
@@ -960,14 +1109,43 @@ impl Value {
         }
     }
 
-    pub fn contains(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+    /// Whether the string matches the regex `rhs`, compiled through `regexes`
+    pub fn regex_match(
+        &self,
+        op: Span,
+        rhs: &Value,
+        regexes: &RegexCache,
+    ) -> Result<Value, ShellError> {
+        // An error has no span, but what it says can be searched, eg to find failed rows
+        if let (
+            Value::Error { error },
+            Value::String {
+                val: pattern,
+                span: at,
+            },
+        ) = (self, rhs)
+        {
+            return Ok(Value::Bool {
+                val: regexes.get(pattern, *at)?.is_match(&error_message(error)),
+                span: *at,
+            });
+        }
+
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
-            (Value::String { val: lhs, .. }, Value::String { val: rhs, .. }) => Ok(Value::Bool {
-                val: lhs.contains(rhs),
+            (
+                Value::String { val: lhs, .. },
+                Value::String {
+                    val: pattern,
+                    span: at,
+                },
+            ) => Ok(Value::Bool {
+                val: regexes.get(pattern, *at)?.is_match(lhs),
                 span,
             }),
+            // Next to the rows that failed, the ones that didn't say nothing to search
+            (Value::Record { .. }, Value::String { .. }) => Ok(Value::Bool { val: false, span }),
             _ => Err(ShellError::OperatorMismatch {
                 op_span: op,
                 lhs_ty: self.get_type(),
@@ -978,14 +1156,42 @@ impl Value {
         }
     }
 
-    pub fn not_contains(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+    pub fn not_regex_match(
+        &self,
+        op: Span,
+        rhs: &Value,
+        regexes: &RegexCache,
+    ) -> Result<Value, ShellError> {
+        // An error has no span, but what it says can be searched, eg to find failed rows
+        if let (
+            Value::Error { error },
+            Value::String {
+                val: pattern,
+                span: at,
+            },
+        ) = (self, rhs)
+        {
+            return Ok(Value::Bool {
+                val: !regexes.get(pattern, *at)?.is_match(&error_message(error)),
+                span: *at,
+            });
+        }
+
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
-            (Value::String { val: lhs, .. }, Value::String { val: rhs, .. }) => Ok(Value::Bool {
-                val: !lhs.contains(rhs),
+            (
+                Value::String { val: lhs, .. },
+                Value::String {
+                    val: pattern,
+                    span: at,
+                },
+            ) => Ok(Value::Bool {
+                val: !regexes.get(pattern, *at)?.is_match(lhs),
                 span,
             }),
+            // Next to the rows that failed, the ones that didn't say nothing to search
+            (Value::Record { .. }, Value::String { .. }) => Ok(Value::Bool { val: true, span }),
             _ => Err(ShellError::OperatorMismatch {
                 op_span: op,
                 lhs_ty: self.get_type(),
@@ -997,7 +1203,7 @@ impl Value {
     }
 
     pub fn modulo(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
             (Value::Int { val: lhs, .. }, Value::Int { val: rhs, .. }) => {
@@ -1052,7 +1258,7 @@ impl Value {
     }
 
     pub fn and(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
             (Value::Bool { val: lhs, .. }, Value::Bool { val: rhs, .. }) => Ok(Value::Bool {
@@ -1070,7 +1276,7 @@ impl Value {
     }
 
     pub fn or(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
             (Value::Bool { val: lhs, .. }, Value::Bool { val: rhs, .. }) => Ok(Value::Bool {
@@ -1088,7 +1294,7 @@ impl Value {
     }
 
     pub fn pow(&self, op: Span, rhs: &Value) -> Result<Value, ShellError> {
-        let span = span(&[self.span()?, rhs.span()?]);
+        let span = self.span()?.merge(rhs.span()?);
 
         match (self, rhs) {
             (Value::Int { val: lhs, .. }, Value::Int { val: rhs, .. }) => {
@@ -1142,6 +1348,16 @@ impl From<Spanned<HashMap<String, Value>>> for Value {
     }
 }
 
+/// What an error says, followed by what the errors that caused it say
+fn error_message(error: &ShellError) -> String {
+    let mut message = error.to_string();
+    for cause in error.related().into_iter().flatten() {
+        message.push('\n');
+        message.push_str(&cause.to_string());
+    }
+    message
+}
+
 /// Format a duration in nanoseconds into a string
 pub fn format_duration(duration: i64) -> String {
     let (sign, duration) = if duration >= 0 {
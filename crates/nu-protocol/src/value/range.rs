@@ -23,8 +23,8 @@ impl Range {
         to: Value,
         operator: &RangeOperator,
     ) -> Result<Range, ShellError> {
-        // Select from & to values if they're not specified
-        // TODO: Replace the placeholder values with proper min/max for range based on data type
+        // Select the start if it's not specified. A missing end is kept as `Nothing`: the range
+        // doesn't end, and is only ever iterated lazily
         let from = if let Value::Nothing { .. } = from {
             Value::Int {
                 val: 0i64,
@@ -34,25 +34,13 @@ impl Range {
             from
         };
 
-        let to = if let Value::Nothing { .. } = to {
-            if let Ok(Value::Bool { val: true, .. }) = next.lt(expr_span, &from) {
-                Value::Int {
-                    val: -100i64,
-                    span: Span::unknown(),
-                }
-            } else {
-                Value::Int {
-                    val: 100i64,
-                    span: Span::unknown(),
-                }
-            }
+        // Check if the range counts up or down
+        let moves_up = if let Value::Nothing { .. } = to {
+            !matches!(next.lt(expr_span, &from), Ok(Value::Bool { val: true, .. }))
         } else {
-            to
+            matches!(from.lte(expr_span, &to), Ok(Value::Bool { val: true, .. }))
         };
 
-        // Check if the range counts up or down
-        let moves_up = matches!(from.lte(expr_span, &to), Ok(Value::Bool { val: true, .. }));
-
         // Convert the next value into the inctement
         let incr = if let Value::Nothing { .. } = next {
             if moves_up {
@@ -80,6 +68,15 @@ impl Range {
             return Err(ShellError::CannotCreateRange(expr_span));
         }
 
+        if let Value::Nothing { .. } = to {
+            return Ok(Range {
+                from,
+                incr,
+                to,
+                inclusion: operator.inclusion,
+            });
+        }
+
         // If to > from, then incr > 0, otherwise we iterate forever
         if let (Value::Bool { val: true, .. }, Value::Bool { val: false, .. }) = (
             to.gt(operator.span, &from)?,
@@ -106,7 +103,20 @@ impl Range {
 
     #[inline]
     fn moves_up(&self) -> bool {
-        self.from <= self.to
+        if self.is_unbounded() {
+            self.incr
+                > Value::Int {
+                    val: 0,
+                    span: Span::unknown(),
+                }
+        } else {
+            self.from <= self.to
+        }
+    }
+
+    /// Whether the range goes on without end, like `1..`
+    pub fn is_unbounded(&self) -> bool {
+        matches!(self.to, Value::Nothing { .. })
     }
 
     #[inline]
@@ -115,6 +125,14 @@ impl Range {
     }
 
     pub fn contains(&self, item: &Value) -> bool {
+        if self.is_unbounded() {
+            return match item.partial_cmp(&self.from) {
+                Some(Ordering::Greater | Ordering::Equal) => self.moves_up(),
+                Some(Ordering::Less) => !self.moves_up(),
+                None => false,
+            };
+        }
+
         match (item.partial_cmp(&self.from), item.partial_cmp(&self.to)) {
             (Some(Ordering::Greater | Ordering::Equal), Some(Ordering::Less)) => self.moves_up(),
             (Some(Ordering::Less | Ordering::Equal), Some(Ordering::Greater)) => !self.moves_up(),
@@ -151,10 +169,14 @@ impl RangeIterator {
         };
 
         let end = match range.to {
-            Value::Nothing { .. } => Value::Int {
+            Value::Nothing { .. } if moves_up => Value::Int {
                 val: i64::MAX,
                 span,
             },
+            Value::Nothing { .. } => Value::Int {
+                val: i64::MIN,
+                span,
+            },
             x => x,
         };
 
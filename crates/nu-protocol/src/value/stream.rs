@@ -16,6 +16,10 @@ use std::{
 pub struct ValueStream {
     pub stream: Box<dyn Iterator<Item = Value> + Send + 'static>,
     pub ctrlc: Option<Arc<AtomicBool>>,
+    pub metadata: Option<PipelineMetadata>,
+    /// Whether the stream comes from a range without an end, so that it never ends on its own.
+    /// Shared with what makes the rows, so that it can clear it when it stops early anyway
+    pub endless: Arc<AtomicBool>,
 }
 
 impl ValueStream {
@@ -32,6 +36,8 @@ impl ValueStream {
         ValueStream {
             stream: Box::new(input),
             ctrlc,
+            metadata: None,
+            endless: Arc::new(AtomicBool::new(false)),
         }
     }
 }
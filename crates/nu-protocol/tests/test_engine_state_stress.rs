@@ -0,0 +1,132 @@
+use nu_protocol::engine::{EngineState, StateWorkingSet};
+use nu_protocol::{Signature, Span};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// The system allocator, keeping count of the bytes in use so tests can bound memory
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Tests run in parallel, and would count each other's memory. Each one holds this while it runs
+fn serial() -> MutexGuard<'static, ()> {
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    SERIAL
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Fail if a step took longer than its (generous) budget. Debug builds are slow, so budgets are
+/// meant to catch a step going quadratic, not small regressions. Use the benches for those
+fn assert_within(what: &str, elapsed: Duration, budget: Duration) {
+    assert!(
+        elapsed <= budget,
+        "{} took {:?}, more than its budget of {:?}",
+        what,
+        elapsed,
+        budget
+    );
+}
+
+/// Fail if the memory in use grew by more than `budget` bytes
+fn assert_memory_within(what: &str, grown: usize, budget: usize) {
+    assert!(
+        grown <= budget,
+        "{} grew memory by {} bytes, more than its budget of {} bytes",
+        what,
+        grown,
+        budget
+    );
+}
+
+#[test]
+fn merging_many_small_deltas() {
+    const DELTAS: usize = 10_000;
+    let _serial = serial();
+
+    let allocated_before = ALLOCATED.load(Ordering::SeqCst);
+    let start = Instant::now();
+
+    let mut engine_state = EngineState::new();
+    for idx in 0..DELTAS {
+        let delta = {
+            let mut working_set = StateWorkingSet::new(&engine_state);
+            working_set.add_decl(Signature::build(format!("cmd {}", idx)).predeclare());
+            working_set.add_file(format!("file{}.nu", idx), format!("cmd {}", idx).as_bytes());
+            working_set.render()
+        };
+        engine_state.merge_delta(delta);
+    }
+
+    assert_within(
+        "merging 10k deltas",
+        start.elapsed(),
+        Duration::from_secs(60),
+    );
+    // About a command and a file name and contents per delta, with plenty of room for overhead
+    let grown = ALLOCATED
+        .load(Ordering::SeqCst)
+        .saturating_sub(allocated_before);
+    assert_memory_within("merging 10k deltas", grown, 64 * 1024 * 1024);
+
+    assert_eq!(engine_state.num_decls(), DELTAS);
+    assert_eq!(engine_state.num_files(), DELTAS);
+    assert!(engine_state.find_decl(b"cmd 0").is_some());
+    assert!(engine_state.find_decl(b"cmd 9999").is_some());
+
+    let end = engine_state.next_span_start();
+    assert_eq!(
+        engine_state.get_span_contents(&Span {
+            start: end - 8,
+            end
+        }),
+        b"cmd 9999"
+    );
+}
+
+#[test]
+fn clones_share_their_contents() {
+    let _serial = serial();
+    let mut engine_state = EngineState::new();
+    let delta = {
+        let mut working_set = StateWorkingSet::new(&engine_state);
+        for idx in 0..1000 {
+            working_set.add_decl(Signature::build(format!("cmd {}", idx)).predeclare());
+            working_set.add_file(format!("file{}.nu", idx), &[b'a'; 1000]);
+        }
+        working_set.render()
+    };
+    engine_state.merge_delta(delta);
+
+    let allocated_before = ALLOCATED.load(Ordering::SeqCst);
+    let start = Instant::now();
+
+    let clones: Vec<EngineState> = (0..1000).map(|_| engine_state.clone()).collect();
+
+    assert_within("cloning 1k times", start.elapsed(), Duration::from_secs(10));
+    // The file contents alone are 1MB, so clones that copied them would need a gigabyte
+    let grown = ALLOCATED
+        .load(Ordering::SeqCst)
+        .saturating_sub(allocated_before);
+    assert_memory_within("cloning 1k times", grown, 64 * 1024 * 1024);
+
+    assert_eq!(clones.len(), 1000);
+}
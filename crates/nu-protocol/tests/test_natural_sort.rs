@@ -0,0 +1,68 @@
+use std::cmp::Ordering;
+
+use nu_protocol::{compare_natural, Value};
+
+#[test]
+fn natural_sort_of_filenames() {
+    let mut files = vec![
+        "file10.txt",
+        "file2.txt",
+        "file1.txt",
+        "file20.txt",
+        "file3.txt",
+        "file100.txt",
+    ];
+
+    files.sort_by(|a, b| compare_natural(a, b));
+
+    assert_eq!(
+        files,
+        vec![
+            "file1.txt",
+            "file2.txt",
+            "file3.txt",
+            "file10.txt",
+            "file20.txt",
+            "file100.txt",
+        ]
+    );
+}
+
+#[test]
+fn natural_sort_mixed_segments() {
+    assert_eq!(compare_natural("a1b2", "a1b10"), Ordering::Less);
+    assert_eq!(compare_natural("a10b1", "a2b10"), Ordering::Greater);
+    assert_eq!(compare_natural("abc", "abc1"), Ordering::Less);
+    assert_eq!(compare_natural("1abc", "abc"), Ordering::Less);
+    assert_eq!(compare_natural("", ""), Ordering::Equal);
+}
+
+#[test]
+fn natural_sort_leading_zeros() {
+    assert_eq!(compare_natural("file007", "file7"), Ordering::Greater);
+    assert_eq!(compare_natural("file007", "file8"), Ordering::Less);
+    assert_eq!(compare_natural("file000", "file0"), Ordering::Greater);
+}
+
+#[test]
+fn natural_sort_long_digit_runs() {
+    let huge = "v184467440737095516150000000000";
+    let huger = "v184467440737095516150000000001";
+
+    assert_eq!(compare_natural(huge, huger), Ordering::Less);
+    assert_eq!(compare_natural(huger, "v9"), Ordering::Greater);
+    assert_eq!(compare_natural(huge, huge), Ordering::Equal);
+}
+
+#[test]
+fn natural_cmp_on_values() {
+    let lhs = Value::test_string("img12.png");
+    let rhs = Value::test_string("img2.png");
+
+    assert_eq!(lhs.partial_cmp(&rhs), Some(Ordering::Less));
+    assert_eq!(lhs.natural_cmp(&rhs), Some(Ordering::Greater));
+    assert_eq!(
+        Value::test_int(3).natural_cmp(&Value::test_int(4)),
+        Some(Ordering::Less)
+    );
+}
@@ -0,0 +1,104 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use nu_protocol::ast::{RangeInclusion, RangeOperator};
+use nu_protocol::{PipelineData, Range, Span, Value};
+
+fn range(from: Value, next: Value, to: Value) -> Range {
+    let operator = RangeOperator {
+        inclusion: RangeInclusion::Inclusive,
+        span: Span::unknown(),
+        next_op_span: Span::unknown(),
+    };
+
+    Range::new(Span::unknown(), from, next, to, &operator).expect("valid range")
+}
+
+fn nothing() -> Value {
+    Value::nothing(Span::unknown())
+}
+
+fn float(val: f64) -> Value {
+    Value::Float {
+        val,
+        span: Span::unknown(),
+    }
+}
+
+fn take(range: Range, n: usize) -> Vec<Value> {
+    PipelineData::Value(Value::Range {
+        val: Box::new(range),
+        span: Span::unknown(),
+    })
+    .into_iter()
+    .take(n)
+    .collect()
+}
+
+#[test]
+fn unbounded_range_is_mapped_lazily() {
+    let steps = Arc::new(AtomicUsize::new(0));
+    let counter = steps.clone();
+
+    let input = PipelineData::Value(Value::Range {
+        val: Box::new(range(Value::test_int(1), nothing(), nothing())),
+        span: Span::unknown(),
+    });
+
+    let output: Vec<_> = input
+        .map(
+            move |value| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                value
+            },
+            None,
+        )
+        .expect("ranges can be mapped")
+        .into_iter()
+        .take(3)
+        .collect();
+
+    assert_eq!(
+        output,
+        vec![Value::test_int(1), Value::test_int(2), Value::test_int(3)]
+    );
+    assert_eq!(steps.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn unbounded_range_counts_down() {
+    let range = range(Value::test_int(10), Value::test_int(8), nothing());
+
+    assert!(range.is_unbounded());
+    assert_eq!(
+        take(range, 3),
+        vec![Value::test_int(10), Value::test_int(8), Value::test_int(6)]
+    );
+}
+
+#[test]
+fn bounded_range_counts_down() {
+    assert_eq!(
+        take(range(Value::test_int(3), nothing(), Value::test_int(1)), 10),
+        vec![Value::test_int(3), Value::test_int(2), Value::test_int(1)]
+    );
+}
+
+#[test]
+fn float_range_steps_by_its_increment() {
+    assert_eq!(
+        take(range(float(1.0), float(1.5), float(2.5)), 10),
+        vec![float(1.0), float(1.5), float(2.0), float(2.5)]
+    );
+}
+
+#[test]
+fn unbounded_range_contains() {
+    let up = range(Value::test_int(1), nothing(), nothing());
+    assert!(up.contains(&Value::test_int(1_000_000)));
+    assert!(!up.contains(&Value::test_int(0)));
+
+    let down = range(Value::test_int(1), Value::test_int(0), nothing());
+    assert!(down.contains(&Value::test_int(-1_000_000)));
+    assert!(!down.contains(&Value::test_int(2)));
+}
@@ -1,4 +1,4 @@
-use nu_protocol::{Flag, PositionalArg, Signature, SyntaxShape};
+use nu_protocol::{Category, Flag, FlagConstraint, PositionalArg, Signature, SyntaxShape, Type};
 
 #[test]
 fn test_signature() {
@@ -120,3 +120,44 @@ fn test_signature_same_name() {
         )
         .named("name", SyntaxShape::String, "named description", Some('n'));
 }
+
+#[test]
+fn test_signature_flag_constraints() {
+    let signature = Signature::new("new_signature")
+        .switch("left", "left switch", Some('l'))
+        .switch("right", "right switch", Some('r'))
+        .conflicts_with("left", "right")
+        .requires("right", "middle");
+
+    assert_eq!(
+        signature.flag_constraints,
+        vec![
+            FlagConstraint::ConflictsWith("left".into(), "right".into()),
+            FlagConstraint::Requires("right".into(), "middle".into()),
+        ]
+    );
+    assert_eq!(signature.unknown_constraint_flags(), vec!["middle"]);
+}
+
+#[test]
+fn test_signature_category() {
+    let signature = Signature::build("spam");
+    assert_eq!(signature.category, Category::Default);
+
+    let signature = Signature::build("spam").category(Category::Strings);
+    assert_eq!(signature.category, Category::Strings);
+    assert_eq!(signature.category.to_string(), "strings");
+}
+
+#[test]
+fn test_signature_pipeline_types() {
+    let signature = Signature::build("spam");
+    assert_eq!(signature.input_type, Type::Unknown);
+    assert_eq!(signature.output_type, Type::Unknown);
+
+    let signature = Signature::build("spam")
+        .input_type(Type::String)
+        .output_type(Type::List(Box::new(Type::Int)));
+    assert_eq!(signature.input_type, Type::String);
+    assert_eq!(signature.output_type, Type::List(Box::new(Type::Int)));
+}
@@ -2,6 +2,7 @@ use crate::table::TextStyle;
 use nu_ansi_term::Style;
 use std::collections::HashMap;
 use std::{fmt::Display, iter::Iterator};
+use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 #[derive(Debug, Clone, Copy)]
@@ -64,8 +65,10 @@ pub fn split_sublines(input: &str) -> Vec<Vec<Subline>> {
                         // and x.chars().count() with all types of combinations. Currently, it appears that
                         // getting the max of char count and Unicode width seems to produce the best layout.
                         // However, it's not perfect.
+                        // Escape sequences (eg, colored file names) don't take up any room.
+                        let x = strip_ansi(x);
                         let c = x.chars().count();
-                        let u = UnicodeWidthStr::width(x);
+                        let u = UnicodeWidthStr::width(x.as_str());
                         std::cmp::max(c, u)
                     },
                 })
@@ -74,6 +77,135 @@ pub fn split_sublines(input: &str) -> Vec<Vec<Subline>> {
         .collect::<Vec<_>>()
 }
 
+/// Remove ANSI escape sequences (like color codes) from the text
+fn strip_ansi(input: &str) -> String {
+    AnsiSegments::new(input)
+        .filter_map(|segment| match segment {
+            Segment::Text(text) => Some(text),
+            Segment::Escape(_) => None,
+        })
+        .collect()
+}
+
+/// A piece of text that may contain ANSI escape sequences
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    /// Text to show, free of escape sequences
+    Text(&'a str),
+    /// A whole escape sequence, which takes up no room
+    Escape(&'a str),
+}
+
+/// Splits text into runs of plain text and whole escape sequences: CSI sequences (like colors)
+/// and OSC sequences (like hyperlinks)
+struct AnsiSegments<'a> {
+    input: &'a str,
+}
+
+impl<'a> AnsiSegments<'a> {
+    fn new(input: &'a str) -> Self {
+        AnsiSegments { input }
+    }
+}
+
+impl<'a> Iterator for AnsiSegments<'a> {
+    type Item = Segment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+
+        let len = if self.input.starts_with('\u{1b}') {
+            escape_len(self.input)
+        } else {
+            self.input.find('\u{1b}').unwrap_or(self.input.len())
+        };
+        let (segment, rest) = self.input.split_at(len);
+        self.input = rest;
+
+        if segment.starts_with('\u{1b}') {
+            Some(Segment::Escape(segment))
+        } else {
+            Some(Segment::Text(segment))
+        }
+    }
+}
+
+/// The length of the escape sequence at the start of the input, up to the end of the input if
+/// it's cut short
+fn escape_len(input: &str) -> usize {
+    let mut chars = input.char_indices().skip(1);
+
+    match chars.next() {
+        // CSI: parameters up to and including a final byte
+        Some((_, '[')) => chars
+            .find(|(_, c)| ('@'..='~').contains(c))
+            .map_or(input.len(), |(idx, c)| idx + c.len_utf8()),
+        // OSC: up to a BEL or a string terminator (ESC \)
+        Some((_, ']')) => {
+            let mut prev = ' ';
+            chars
+                .find(|&(_, c)| {
+                    let end = c == '\u{7}' || (prev == '\u{1b}' && c == '\\');
+                    prev = c;
+                    end
+                })
+                .map_or(input.len(), |(idx, c)| idx + c.len_utf8())
+        }
+        Some((idx, c)) => idx + c.len_utf8(),
+        None => input.len(),
+    }
+}
+
+/// Cut text down to fit in `width` columns, ending it with an ellipsis if anything was cut.
+///
+/// The cut falls between graphemes, so characters built from several code points stay whole, and
+/// escape sequences are never split. If the text is colored at the cut, a reset follows the
+/// ellipsis so the color doesn't bleed into the rest of the row.
+pub fn truncate_to_width(input: &str, width: usize) -> String {
+    if UnicodeWidthStr::width(strip_ansi(input).as_str()) <= width {
+        return input.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    // Leave room for the ellipsis
+    let room = width - 1;
+    let mut output = String::new();
+    let mut used = 0;
+    let mut styled = false;
+
+    'segments: for segment in AnsiSegments::new(input) {
+        match segment {
+            Segment::Escape(escape) => {
+                if escape.starts_with("\u{1b}[") && escape.ends_with('m') {
+                    styled = !matches!(escape, "\u{1b}[m" | "\u{1b}[0m");
+                }
+                output.push_str(escape);
+            }
+            Segment::Text(text) => {
+                for grapheme in text.graphemes(true) {
+                    let grapheme_width = UnicodeWidthStr::width(grapheme);
+                    if used + grapheme_width > room {
+                        break 'segments;
+                    }
+                    used += grapheme_width;
+                    output.push_str(grapheme);
+                }
+            }
+        }
+    }
+
+    output.push('…');
+    if styled {
+        output.push_str(nu_ansi_term::ansi::RESET);
+    }
+
+    output
+}
+
 pub fn column_width(input: &[Vec<Subline>]) -> usize {
     let mut max = 0;
 
@@ -101,27 +233,30 @@ pub fn column_width(input: &[Vec<Subline>]) -> usize {
 }
 
 fn split_word(cell_width: usize, word: &str) -> Vec<Subline> {
-    use unicode_width::UnicodeWidthChar;
-
     let mut output = vec![];
     let mut current_width = 0;
     let mut start_index = 0;
-    let mut end_index;
-
-    for c in word.char_indices() {
-        if let Some(width) = c.1.width() {
-            end_index = c.0;
-            if current_width + width > cell_width {
-                output.push(Subline {
-                    subline: &word[start_index..end_index],
-                    width: current_width,
-                });
-
-                start_index = c.0;
-                current_width = width;
-            } else {
-                current_width += width;
+    let mut end_index = 0;
+
+    for segment in AnsiSegments::new(word) {
+        match segment {
+            Segment::Text(text) => {
+                for grapheme in text.graphemes(true) {
+                    let width = UnicodeWidthStr::width(grapheme);
+                    if current_width + width > cell_width && current_width > 0 {
+                        output.push(Subline {
+                            subline: &word[start_index..end_index],
+                            width: current_width,
+                        });
+
+                        start_index = end_index;
+                        current_width = 0;
+                    }
+                    current_width += width;
+                    end_index += grapheme.len();
+                }
             }
+            Segment::Escape(escape) => end_index += escape.len(),
         }
     }
 
@@ -272,3 +407,76 @@ pub fn wrap<'a>(
 
     (output, current_max)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn splits_out_escape_sequences() {
+        let segments: Vec<_> = AnsiSegments::new(
+            "\u{1b}[1;31mred\u{1b}[0m \u{1b}]8;;https://nushell.sh\u{1b}\\nu\u{7}",
+        )
+        .collect();
+
+        assert_eq!(
+            segments,
+            vec![
+                Segment::Escape("\u{1b}[1;31m"),
+                Segment::Text("red"),
+                Segment::Escape("\u{1b}[0m"),
+                Segment::Text(" "),
+                Segment::Escape("\u{1b}]8;;https://nushell.sh\u{1b}\\"),
+                Segment::Text("nu\u{7}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_text_that_fits() {
+        assert_eq!(truncate_to_width("hello", 5), "hello");
+        assert_eq!(
+            truncate_to_width("\u{1b}[31mhello\u{1b}[0m", 5),
+            "\u{1b}[31mhello\u{1b}[0m"
+        );
+    }
+
+    #[test]
+    fn truncates_wide_characters() {
+        // Each of these takes up two columns, so only three fit next to the ellipsis
+        assert_eq!(truncate_to_width("日本語のテキスト", 7), "日本語…");
+        assert_eq!(truncate_to_width("日本語のテキスト", 8), "日本語…");
+    }
+
+    #[test]
+    fn truncates_between_graphemes() {
+        assert_eq!(truncate_to_width("👍👍👍", 4), "👍…");
+        assert_eq!(
+            truncate_to_width("e\u{301}e\u{301}e\u{301}", 2),
+            "e\u{301}…"
+        );
+    }
+
+    #[test]
+    fn resets_colors_left_open() {
+        assert_eq!(
+            truncate_to_width("\u{1b}[31mhello world\u{1b}[0m", 5),
+            "\u{1b}[31mhell…\u{1b}[0m"
+        );
+        assert_eq!(
+            truncate_to_width("\u{1b}[31mred\u{1b}[0m and plain", 6),
+            "\u{1b}[31mred\u{1b}[0m a…"
+        );
+    }
+
+    #[test]
+    fn splits_long_words_around_escapes() {
+        let word = "\u{1b}[31mabcd\u{1b}[0m";
+        let sublines: Vec<_> = split_word(2, word)
+            .into_iter()
+            .map(|subline| (subline.subline, subline.width))
+            .collect();
+
+        assert_eq!(sublines, vec![("\u{1b}[31mab", 2), ("cd\u{1b}[0m", 2)]);
+    }
+}
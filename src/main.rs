@@ -1,5 +1,5 @@
 use std::{
-    io::Write,
+    path::PathBuf,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -12,14 +12,17 @@ use dialoguer::{
     Select,
 };
 use miette::{IntoDiagnostic, Result};
-use nu_cli::{report_error, NuCompleter, NuHighlighter, NuValidator, NushellPrompt};
+use nu_cli::{
+    report_error, report_warnings, NuCompleter, NuHighlighter, NuValidator, NushellPrompt,
+};
 use nu_command::create_default_context;
-use nu_engine::eval_block;
-use nu_parser::parse;
+use nu_engine::{
+    convert_env_values, eval_block, eval_block_recording, print_value, TerminalPrompt,
+};
+use nu_parser::{parse, parse_with_source};
 use nu_protocol::{
-    ast::Call,
-    engine::{EngineState, Stack, StateWorkingSet},
-    IntoPipelineData, PipelineData, ShellError, Span, Value, CONFIG_VARIABLE_ID,
+    engine::{EngineState, FileSource, Stack, StateWorkingSet},
+    PipelineData, Span, Value, CONFIG_VARIABLE_ID,
 };
 use reedline::{Completer, CompletionActionHandler, DefaultPrompt, LineBuffer, Prompt};
 
@@ -94,22 +97,42 @@ fn main() -> Result<()> {
     // Ctrl-c protection section
     let ctrlc = Arc::new(AtomicBool::new(false));
     let handler_ctrlc = ctrlc.clone();
-    let engine_state_ctrlc = ctrlc.clone();
 
     ctrlc::set_handler(move || {
         handler_ctrlc.store(true, Ordering::SeqCst);
     })
     .expect("Error setting Ctrl-C handler");
 
-    engine_state.ctrlc = Some(engine_state_ctrlc);
+    engine_state.set_ctrlc(ctrlc);
     // End ctrl-c protection section
 
-    if let Some(path) = std::env::args().nth(1) {
-        let file = std::fs::read(&path).into_diagnostic()?;
+    let options = match StartupOptions::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            let _ = engine_state
+                .output
+                .write_err(format!("Error: {}\n", message).as_bytes());
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(path) = &options.script {
+        let mut stack = initial_stack();
+        load_startup_files(&mut engine_state, &mut stack, &options, false);
+        convert_env_values(&engine_state, &mut stack);
+        report_warnings(&engine_state);
+
+        let file = std::fs::read(path).into_diagnostic()?;
 
         let (block, delta) = {
             let mut working_set = StateWorkingSet::new(&engine_state);
-            let (output, err) = parse(&mut working_set, Some(&path), &file, false);
+            let (output, err) = parse_with_source(
+                &mut working_set,
+                path,
+                FileSource::Script(path.into()),
+                &file,
+                false,
+            );
             if let Some(err) = err {
                 report_error(&working_set, &err);
 
@@ -119,34 +142,37 @@ fn main() -> Result<()> {
         };
 
         engine_state.merge_delta(delta);
+        engine_state.reset_ctrlc();
 
-        let mut stack = nu_protocol::engine::Stack::new();
-
-        for (k, v) in std::env::vars() {
-            stack.env_vars.insert(k, v);
-        }
-
-        // Set up our initial config to start from
-        stack.vars.insert(
-            CONFIG_VARIABLE_ID,
-            Value::Record {
-                cols: vec![],
-                vals: vec![],
-                span: Span::unknown(),
-            },
-        );
-
-        match eval_block(
+        // A pipeline that is an error fails the script, and so does a stream that stops at one,
+        // like the first row `each` fails on, so the output is drained before it's written.
+        // Errors in rows are output like any other row, so that eg `each --keep-going` can carry
+        // on past them
+        let result = eval_block_recording(
             &engine_state,
             &mut stack,
             &block,
             PipelineData::new(Span::unknown()),
-        ) {
-            Ok(pipeline_data) => {
-                let config = stack.get_config()?;
-                println!("{}", pipeline_data.collect_string("\n", &config));
+            |stack, pipeline_data| match pipeline_data {
+                PipelineData::Value(Value::Error { error }) => Err(error),
+                x => {
+                    let config = stack.get_config()?;
+
+                    Ok(format!("{}\n", x.collect_string("\n", &config)))
+                }
+            },
+        );
+
+        match result {
+            Ok(output) => {
+                engine_state
+                    .output
+                    .write_out(output.as_bytes())
+                    .into_diagnostic()?;
+                report_warnings(&engine_state);
             }
             Err(err) => {
+                report_warnings(&engine_state);
                 let working_set = StateWorkingSet::new(&engine_state);
 
                 report_error(&working_set, &err);
@@ -159,43 +185,18 @@ fn main() -> Result<()> {
     } else {
         use reedline::{FileBackedHistory, Reedline, Signal};
 
-        let completer = NuCompleter::new(engine_state.clone());
         let mut entry_num = 0;
 
         let default_prompt = DefaultPrompt::new(1);
         let mut nu_prompt = NushellPrompt::new();
-        let mut stack = nu_protocol::engine::Stack::new();
-
-        for (k, v) in std::env::vars() {
-            stack.env_vars.insert(k, v);
-        }
-
-        // Set up our initial config to start from
-        stack.vars.insert(
-            CONFIG_VARIABLE_ID,
-            Value::Record {
-                cols: vec![],
-                vals: vec![],
-                span: Span::unknown(),
-            },
-        );
-
-        // Load config startup file
-        if let Some(mut config_path) = nu_path::config_dir() {
-            config_path.push("nushell");
-            config_path.push("config.nu");
+        let mut stack = initial_stack();
 
-            // FIXME: remove this message when we're ready
-            println!("Loading config from: {:?}", config_path);
+        // Only the REPL has someone to answer `--interactive` prompts
+        engine_state.confirm = Arc::new(TerminalPrompt);
 
-            if config_path.exists() {
-                let config_filename = config_path.to_string_lossy().to_owned();
-
-                if let Ok(contents) = std::fs::read_to_string(&config_path) {
-                    eval_source(&mut engine_state, &mut stack, &contents, &config_filename);
-                }
-            }
-        }
+        load_startup_files(&mut engine_state, &mut stack, &options, true);
+        convert_env_values(&engine_state, &mut stack);
+        report_warnings(&engine_state);
 
         let history_path = if let Some(mut history_path) = nu_path::config_dir() {
             history_path.push("nushell");
@@ -207,13 +208,18 @@ fn main() -> Result<()> {
         };
 
         loop {
-            //Reset the ctrl-c handler
-            ctrlc.store(false, Ordering::SeqCst);
+            // The prompt is evaluated before the line is, so it needs the flag reset too
+            engine_state.reset_ctrlc();
+
+            // Pick up config changes so that the parser and completer resolve commands the same way
+            if let Ok(config) = stack.get_config() {
+                engine_state.case_insensitive_lookup = config.case_insensitive_lookup;
+            }
 
             let line_editor = Reedline::create()
                 .into_diagnostic()?
                 .with_completion_action_handler(Box::new(FuzzyCompletion {
-                    completer: Box::new(completer.clone()),
+                    completer: Box::new(NuCompleter::new(engine_state.clone())),
                 }))
                 .with_highlighter(Box::new(NuHighlighter {
                     engine_state: engine_state.clone(),
@@ -258,6 +264,7 @@ fn main() -> Result<()> {
                         &mut stack,
                         &s,
                         &format!("entry #{}", entry_num),
+                        FileSource::Cli,
                     );
                 }
                 Ok(Signal::CtrlC) => {
@@ -272,7 +279,8 @@ fn main() -> Result<()> {
                 Err(err) => {
                     let message = err.to_string();
                     if !message.contains("duration") {
-                        println!("Error: {:?}", err);
+                        let message = format!("Error: {:?}\n", err);
+                        let _ = engine_state.output.write_err(message.as_bytes());
                     }
                 }
             }
@@ -282,36 +290,126 @@ fn main() -> Result<()> {
     }
 }
 
-fn print_value(
-    value: Value,
-    engine_state: &EngineState,
-    stack: &mut Stack,
-) -> Result<(), ShellError> {
-    // If the table function is in the declarations, then we can use it
-    // to create the table value that will be printed in the terminal
-
-    let config = stack.get_config()?;
+/// What the command line asks for: a script to run instead of the REPL, and which startup files
+/// to load before
+#[derive(Debug, Default, PartialEq)]
+struct StartupOptions {
+    script: Option<String>,
+    /// Skip the startup files, including the ones given with `--config` and `--env-config`
+    no_config: bool,
+    config: Option<PathBuf>,
+    env_config: Option<PathBuf>,
+}
 
-    let output = match engine_state.find_decl("table".as_bytes()) {
-        Some(decl_id) => {
-            let table = engine_state.get_decl(decl_id).run(
-                engine_state,
-                stack,
-                &Call::new(),
-                value.into_pipeline_data(),
-            )?;
-            table.collect_string("\n", &config)
+impl StartupOptions {
+    /// Read the flags up to the script path. What comes after it is left to the script
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = StartupOptions::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--no-config" => options.no_config = true,
+                "--config" | "--env-config" => {
+                    let path = args
+                        .next()
+                        .ok_or_else(|| format!("{} needs the path of a file", arg))?;
+                    if arg == "--config" {
+                        options.config = Some(path.into());
+                    } else {
+                        options.env_config = Some(path.into());
+                    }
+                }
+                flag if flag.starts_with("--") => {
+                    return Err(format!("unknown flag {}", flag));
+                }
+                _ => {
+                    options.script = Some(arg);
+                    break;
+                }
+            }
         }
-        None => value.into_string(", ", &config),
-    };
-    let stdout = std::io::stdout();
 
-    match stdout.lock().write_all(output.as_bytes()) {
-        Ok(_) => (),
-        Err(err) => eprintln!("{}", err),
+        Ok(options)
+    }
+}
+
+/// A stack with the environment of the process, and an empty config
+fn initial_stack() -> Stack {
+    let mut stack = Stack::new();
+
+    for (k, v) in std::env::vars() {
+        stack.env_vars.insert(k, Value::string(v, Span::unknown()));
+    }
+    // The PWD inherited from the parent process may be stale, so start from where we are
+    if let Ok(cwd) = std::env::current_dir() {
+        stack.env_vars.insert(
+            "PWD".into(),
+            Value::string(cwd.to_string_lossy(), Span::unknown()),
+        );
+    }
+
+    // Set up our initial config to start from
+    stack.vars.insert(
+        CONFIG_VARIABLE_ID,
+        Value::Record {
+            cols: vec![],
+            vals: vec![],
+            span: Span::unknown(),
+        },
+    );
+
+    stack
+}
+
+/// Evaluate the env file, which sets up what the prompt and PATH need, and then the config file.
+/// The REPL looks for them in the config directory, scripts only load the ones they're given.
+/// A startup file that fails is reported, and the shell goes on without what it defines
+fn load_startup_files(
+    engine_state: &mut EngineState,
+    stack: &mut Stack,
+    options: &StartupOptions,
+    interactive: bool,
+) {
+    if options.no_config {
+        return;
+    }
+
+    let default_path = |name: &str| {
+        let mut path = nu_path::config_dir()?;
+        path.push("nushell");
+        path.push(name);
+
+        Some(path).filter(|path| interactive && path.exists())
     };
 
-    Ok(())
+    let startup_files = [
+        options
+            .env_config
+            .clone()
+            .or_else(|| default_path("env.nu")),
+        options.config.clone().or_else(|| default_path("config.nu")),
+    ];
+
+    for path in startup_files.iter().flatten() {
+        let filename = path.to_string_lossy().to_string();
+
+        match std::fs::read_to_string(path) {
+            Ok(contents) => {
+                eval_source(
+                    engine_state,
+                    stack,
+                    &contents,
+                    &filename,
+                    FileSource::Script(path.clone()),
+                );
+            }
+            Err(err) => {
+                let _ = engine_state.output.write_err(
+                    format!("Error: can't read startup file {}: {}\n", filename, err).as_bytes(),
+                );
+            }
+        }
+    }
 }
 
 fn update_prompt<'prompt>(
@@ -321,7 +419,10 @@ fn update_prompt<'prompt>(
     nu_prompt: &'prompt mut NushellPrompt,
     default_prompt: &'prompt DefaultPrompt,
 ) -> &'prompt dyn Prompt {
-    let prompt_command = match stack.get_env_var(env_variable) {
+    let prompt_command = match stack
+        .get_env_var(env_variable)
+        .and_then(|prompt| prompt.as_string().ok())
+    {
         Some(prompt) => prompt,
         None => return default_prompt as &dyn Prompt,
     };
@@ -371,12 +472,14 @@ fn eval_source(
     stack: &mut Stack,
     source: &str,
     fname: &str,
+    origin: FileSource,
 ) -> bool {
     let (block, delta) = {
         let mut working_set = StateWorkingSet::new(engine_state);
-        let (output, err) = parse(
+        let (output, err) = parse_with_source(
             &mut working_set,
-            Some(fname), // format!("entry #{}", entry_num)
+            fname, // format!("entry #{}", entry_num)
+            origin,
             source.as_bytes(),
             false,
         );
@@ -389,30 +492,32 @@ fn eval_source(
 
     engine_state.merge_delta(delta);
 
-    match eval_block(
+    // A Ctrl-C that stopped the last pipeline doesn't stop this one
+    engine_state.reset_ctrlc();
+
+    let engine_state: &EngineState = engine_state;
+    let result = eval_block_recording(
         engine_state,
         stack,
         &block,
         PipelineData::new(Span::unknown()),
-    ) {
-        Ok(pipeline_data) => {
-            if let Err(err) = print_value(
+        |stack, pipeline_data| {
+            print_value(
                 pipeline_data.into_value(Span::unknown()),
                 engine_state,
                 stack,
-            ) {
-                let working_set = StateWorkingSet::new(engine_state);
+            )
+        },
+    );
 
-                report_error(&working_set, &err);
-                return false;
-            }
-        }
-        Err(err) => {
-            let working_set = StateWorkingSet::new(engine_state);
+    // Warnings come after the output, and before an error that ended the pipeline
+    report_warnings(engine_state);
 
-            report_error(&working_set, &err);
-            return false;
-        }
+    if let Err(err) = result {
+        let working_set = StateWorkingSet::new(engine_state);
+
+        report_error(&working_set, &err);
+        return false;
     }
 
     true
@@ -172,6 +172,33 @@ fn if_elseif4() -> TestResult {
     run_test("if 2 > 3 { 5 } else if 6 < 7 { 4 } else { 8 } ", "4")
 }
 
+#[test]
+fn if_only_takes_booleans() -> TestResult {
+    fail_test("if 1 { 2 }", "Can't convert to boolean")?;
+    fail_test("if 'true' { 2 }", "Can't convert to boolean")
+}
+
+#[test]
+fn if_takes_values_into_bool() -> TestResult {
+    run_test("if (1 | into bool) { 2 } else { 3 }", "2")
+}
+
+#[test]
+fn into_bool_converts_a_column() -> TestResult {
+    run_test(
+        "[[name done]; [spam 'False'] [eggs 1] [bacon 0.0]] | into bool done | get done | str collect ','",
+        "false,true,false",
+    )
+}
+
+#[test]
+fn into_bool_shows_strings_it_cant_convert() -> TestResult {
+    fail_test(
+        "'maybe' | into bool",
+        "can't convert string 'maybe' to bool",
+    )
+}
+
 #[test]
 fn no_scope_leak1() -> TestResult {
     fail_test(
@@ -352,6 +379,127 @@ fn cell_path_var2() -> TestResult {
     run_test("let x = [[lang, gems]; [nu, 100]]; $x.lang.0", "nu")
 }
 
+#[test]
+fn cell_path_quoted_members() -> TestResult {
+    run_test(
+        r#"{"Last Modified": {"a.b": 7}}."Last Modified"."a.b""#,
+        "7",
+    )
+}
+
+#[test]
+fn cell_path_quoted_unicode_member() -> TestResult {
+    run_test(r#"let x = {"héllo wörld": [nu]}; $x."héllo wörld".0"#, "nu")
+}
+
+#[test]
+fn cell_path_quoted_argument() -> TestResult {
+    run_test(
+        r#"[["Last Modified"]; ["ABC"]] | str downcase "Last Modified" | get "Last Modified".0"#,
+        "abc",
+    )
+}
+
+#[test]
+fn select_runtime_columns() -> TestResult {
+    run_test(
+        "let cols = [a c]; [[a b c]; [1 2 3]] | select $cols | get c.0",
+        "3",
+    )
+}
+
+#[test]
+fn select_runtime_columns_split_on_dots() -> TestResult {
+    run_test(
+        r#"let cols = [a.b]; [[a]; [{b: 5}]] | select $cols | get "a.b".0"#,
+        "5",
+    )
+}
+
+#[test]
+fn select_runtime_columns_literal() -> TestResult {
+    run_test(
+        r#"let cols = ["a.b"]; [["a.b"]; [7]] | select --literal $cols | get "a.b".0"#,
+        "7",
+    )
+}
+
+#[test]
+fn select_subexpression_columns() -> TestResult {
+    run_test("[[id name]; [1 nu]] | select ([name]) | get name.0", "nu")
+}
+
+#[test]
+fn select_mixed_columns_fails() -> TestResult {
+    fail_test(
+        "let cols = [a]; [[a b]; [1 2]] | select b $cols",
+        "can't be combined",
+    )
+}
+
+#[test]
+fn reject_runtime_columns() -> TestResult {
+    run_test(
+        "let cols = [a]; [[a b]; [1 2]] | reject $cols | get b.0",
+        "2",
+    )
+}
+
+#[test]
+fn get_runtime_paths() -> TestResult {
+    run_test(
+        "let paths = [a b.c]; {a: 1, b: {c: 2}} | get $paths | get 1",
+        "2",
+    )
+}
+
+#[test]
+fn record_columns_iterate() -> TestResult {
+    run_test(
+        r#"{a: 1, b: 2} | columns | each { |c| build-string $c "!" } | get 1"#,
+        "b!",
+    )
+}
+
+#[test]
+fn table_columns_all() -> TestResult {
+    run_test("[{a: 1} {b: 2}] | columns --all | length", "2")
+}
+
+#[test]
+fn empty_record_values() -> TestResult {
+    run_test("{} | values | length", "0")
+}
+
+#[test]
+fn stash_nested_record_round_trip() -> TestResult {
+    run_test(
+        "{a: {b: [1 2]}} | stash set x; stash get x | get a.b.1",
+        "2",
+    )
+}
+
+#[test]
+fn stash_collects_streams() -> TestResult {
+    run_test(
+        "[1 2 3] | each { |x| $x * 2 } | stash set doubled; stash get doubled | get 2",
+        "6",
+    )
+}
+
+#[test]
+fn stash_no_clobber() -> TestResult {
+    fail_test(
+        "1 | stash set x; 2 | stash set x --no-clobber",
+        "already stashed",
+    )
+}
+
+#[test]
+fn stash_remove() -> TestResult {
+    fail_test("1 | stash set x; stash remove x; stash get x", "not found")
+}
+
 #[test]
 fn custom_rest_var() -> TestResult {
     run_test("def foo [...x] { $x.0 + $x.1 }; foo 10 80", "90")
@@ -442,6 +590,63 @@ fn module_import_uses_internal_command() -> TestResult {
     )
 }
 
+#[test]
+fn module_imports_alias() -> TestResult {
+    run_test(
+        r#"module foo { export alias a = echo 3 }; use foo; foo a"#,
+        "3",
+    )
+}
+
+#[test]
+fn module_imports_alias_by_name() -> TestResult {
+    run_test(
+        r#"module foo { export def a [] { 1 }; export alias b = echo 4 }; use foo *; b"#,
+        "4",
+    )
+}
+
+#[test]
+fn hide_module_alias() -> TestResult {
+    fail_test(
+        r#"module foo { export alias a = echo 3 }; use foo a; hide a; a"#,
+        not_found_msg(),
+    )
+}
+
+const SPAM_EGGS: &str =
+    r#"def spam [] { "spam" }; def "spam eggs" [x] { $x }; def "spam eggs bacon" [] { "longest" }"#;
+
+#[test]
+fn multi_word_commands_prefer_the_longest_name() -> TestResult {
+    run_test(&format!("{}; spam eggs bacon", SPAM_EGGS), "longest")
+}
+
+#[test]
+fn multi_word_commands_fall_back_to_shorter_names() -> TestResult {
+    run_test(&format!("{}; spam eggs ham", SPAM_EGGS), "ham")?;
+    run_test(&format!("{}; spam", SPAM_EGGS), "spam")
+}
+
+#[test]
+fn multi_word_commands_ignore_the_spacing_between_words() -> TestResult {
+    run_test(&format!("{}; spam   eggs  bacon", SPAM_EGGS), "longest")
+}
+
+#[test]
+fn hidden_multi_word_commands_fall_back_to_shorter_names() -> TestResult {
+    run_test(
+        &format!(r#"{}; hide "spam eggs bacon"; spam eggs bacon"#, SPAM_EGGS),
+        "bacon",
+    )
+}
+
+#[test]
+fn misspelled_commands_point_to_the_right_spelling() -> TestResult {
+    fail_test(r#"'SPAM' | Str Downcase"#, "did you mean 'str downcase'?")?;
+    fail_test(r#"'SPAM' | str_downcase"#, "did you mean 'str downcase'?")
+}
+
 // TODO: Test the use/hide tests also as separate lines in REPL (i.e., with  merging the delta in between)
 #[test]
 fn hides_def() -> TestResult {
@@ -496,6 +701,46 @@ fn hide_twice_not_allowed() -> TestResult {
     )
 }
 
+#[test]
+fn hide_keeps_other_defs() -> TestResult {
+    run_test(
+        r#"def foo [] { "foo" }; def bar [] { "bar" }; hide foo; bar"#,
+        "bar",
+    )
+}
+
+#[test]
+fn hide_predecl_not_allowed() -> TestResult {
+    fail_test(
+        r#"hide foo; def foo [] { "foo" }"#,
+        "while it is being defined",
+    )
+}
+
+#[test]
+fn hide_own_def_not_allowed() -> TestResult {
+    fail_test(
+        r#"def foo [] { hide foo; "foo" }"#,
+        "while it is being defined",
+    )
+}
+
+#[test]
+fn hides_module() -> TestResult {
+    fail_test(
+        r#"module spam { export def foo [] { "foo" } }; hide spam; hide spam"#,
+        "unknown command",
+    )
+}
+
+#[test]
+fn hides_module_then_uses_it_again() -> TestResult {
+    run_test(
+        r#"module spam { export def foo [] { "foo" } }; hide spam; use spam; spam foo"#,
+        "foo",
+    )
+}
+
 #[test]
 fn hides_import_1() -> TestResult {
     fail_test(
@@ -645,6 +890,67 @@ fn par_each() -> TestResult {
     )
 }
 
+#[test]
+fn each_stops_at_failing_row() -> TestResult {
+    fail_test(
+        r#"[1 0 2] | each { 10 / $it }"#,
+        "Error while processing row 1",
+    )
+}
+
+#[test]
+fn each_keep_going() -> TestResult {
+    run_test(r#"([1 0 2] | each --keep-going { 10 / $it }).2"#, "5")
+}
+
+#[test]
+fn par_each_stops_at_failing_row() -> TestResult {
+    fail_test(
+        r#"[1 0 2] | par-each { 10 / $it }"#,
+        "Error while processing row 1",
+    )
+}
+
+#[test]
+fn par_each_keep_going() -> TestResult {
+    run_test(r#"[1 0 2] | par-each -k { 10 / $it } | length"#, "3")
+}
+
+#[test]
+fn zip_with_block() -> TestResult {
+    run_test(r#"([1 2] | zip { [3 4] }).1.1"#, "4")
+}
+
+#[test]
+fn merge_with_block() -> TestResult {
+    run_test(r#"([[a]; [1]] | merge { [[b]; [2]] }).0.b"#, "2")
+}
+
+#[test]
+fn timeout_fast_block() -> TestResult {
+    run_test(r#"timeout 10sec { 1 + 1 }"#, "2")
+}
+
+#[test]
+fn timeout_slow_block() -> TestResult {
+    fail_test(r#"timeout 100ms { 1.. | each { $it } }"#, "Timed out")
+}
+
+#[test]
+fn timeout_partial() -> TestResult {
+    run_test(r#"(timeout --partial 100ms { 1.. | each { $it } }).0"#, "1")
+}
+
+#[cfg(not(windows))]
+#[test]
+fn timeout_kills_externals() -> TestResult {
+    let start = std::time::Instant::now();
+    fail_test(r#"timeout 100ms { ^sleep 10 }"#, "Timed out")?;
+
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    Ok(())
+}
+
 #[test]
 fn type_in_list_of_this_type() -> TestResult {
     run_test(r#"42 in [41 42 43]"#, "true")
@@ -784,6 +1090,27 @@ fn custom_switch4() -> TestResult {
     )
 }
 
+#[test]
+fn switch_with_value() -> TestResult {
+    fail_test(r#"mkdir --verbose=5 foo"#, "doesn't take a value")
+}
+
+#[test]
+fn flag_param_wrong_shape() -> TestResult {
+    fail_test(
+        r#"'a' | str lpad --length abc --character 0"#,
+        "expected int",
+    )
+}
+
+#[test]
+fn flag_param_missing_before_flag() -> TestResult {
+    fail_test(
+        r#"'a' | str lpad --length --character 0"#,
+        "flag missing int param",
+    )
+}
+
 #[test]
 fn bad_var_name() -> TestResult {
     fail_test(r#"let $"foo bar" = 4"#, "can't contain")
@@ -832,6 +1159,14 @@ fn shorthand_env_4() -> TestResult {
     fail_test(r#"FOO=BAZ FOO= $nu.env.FOO"#, "did you mean")
 }
 
+#[test]
+fn pipelines_see_how_the_last_one_went() -> TestResult {
+    run_test(
+        r#"ls | length; [$nu.env.LAST_EXIT_CODE ($nu.env.CMD_DURATION_MS >= 0)] | str collect ','"#,
+        "0,true",
+    )
+}
+
 #[test]
 fn update_cell_path_1() -> TestResult {
     run_test(
@@ -855,6 +1190,56 @@ fn where_on_ranges() -> TestResult {
     run_test(r#"1..10 | where $it > 8 | math sum"#, "19")
 }
 
+#[test]
+fn each_on_unbounded_range() -> TestResult {
+    run_test(r#"1.. | each { |x| $x * 2 } | first 3 | math sum"#, "12")
+}
+
+#[test]
+fn where_and_skip_on_unbounded_range() -> TestResult {
+    run_test(r#"1.. | where $it mod 7 == 0 | skip 2 | first"#, "21")
+}
+
+#[test]
+fn descending_unbounded_range() -> TestResult {
+    run_test(r#"10..8.. | first 3 | math sum"#, "24")
+}
+
+#[test]
+fn float_range_length() -> TestResult {
+    run_test(r#"1.0..1.5..3.0 | length"#, "5")
+}
+
+#[test]
+fn last_of_unbounded_range_fails() -> TestResult {
+    fail_test(r#"1.. | last"#, "never ends")
+}
+
+#[test]
+fn sort_unbounded_range_fails() -> TestResult {
+    fail_test(r#"1.. | sort-by"#, "never ends")
+}
+
+#[test]
+fn unbounded_range_in_a_variable_fails() -> TestResult {
+    fail_test(r#"let r = 1..; $r | last"#, "never ends")
+}
+
+#[test]
+fn unbounded_range_from_a_subexpression_fails() -> TestResult {
+    fail_test(r#"(1..) | sort-by"#, "never ends")
+}
+
+#[test]
+fn rows_from_unbounded_range_fail() -> TestResult {
+    fail_test(r#"1.. | each { |x| $x * 2 } | last"#, "never ends")
+}
+
+#[test]
+fn filtered_unbounded_range_fails() -> TestResult {
+    fail_test(r#"1.. | where $it > 3 | skip 1 | length"#, "never ends")
+}
+
 #[test]
 fn index_on_list() -> TestResult {
     run_test(r#"[1, 2, 3].1"#, "2")
@@ -925,3 +1310,837 @@ fn config_var_2() -> TestResult {
         "40.0 KB",
     )
 }
+
+#[test]
+fn sort_by_natural() -> TestResult {
+    run_test(
+        r#"[file10 file2 file1] | sort-by --natural | str collect ','"#,
+        "file1,file2,file10",
+    )
+}
+
+#[test]
+fn filesystem_commands_report_identical_columns() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let path = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+    let row =
+        |path: String, action: &str| format!("{{path: {}, action: {}, status: ok}}", path, action);
+
+    run_test(
+        &format!("mkdir -v '{}'", path("dir")),
+        &row(path("dir"), "create"),
+    )?;
+    run_test(
+        &format!("touch -v '{}'", path("file")),
+        &row(path("file"), "create"),
+    )?;
+    run_test(
+        &format!("cp -v '{}' '{}'", path("file"), path("copy")),
+        &row(path("file"), "copy"),
+    )?;
+    run_test(
+        &format!("mv -v '{}' '{}'", path("copy"), path("moved")),
+        &row(path("copy"), "move"),
+    )?;
+    run_test(
+        &format!("rm -v '{}'", path("moved")),
+        &row(path("moved"), "remove"),
+    )
+}
+
+#[test]
+fn rm_and_mkdir_report_paths_as_before() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let path = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+
+    run_test(
+        &format!("mkdir -s '{}' | get 0", path("dir")),
+        &path("dir"),
+    )?;
+    run_test(
+        &format!("rm '{}' | get 0", path("dir")),
+        &format!("deleted {}", path("dir")),
+    )
+}
+
+#[test]
+fn filesystem_failures_as_rows() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("file").to_string_lossy().to_string();
+
+    run_test(
+        &format!(
+            "touch '{0}'; mkdir --continue-on-error '{0}/sub' | get 0.status",
+            file
+        ),
+        "error",
+    )
+}
+
+#[test]
+fn help_generates_markdown_docs() -> TestResult {
+    let dir = tempfile::tempdir()?;
+
+    run_test(
+        &format!(
+            "help --generate-docs '{}' | where $it =~ 'str_lpad.md' | length",
+            dir.path().to_string_lossy()
+        ),
+        "1",
+    )?;
+
+    let str_doc = std::fs::read_to_string(dir.path().join("str.md"))?;
+    assert!(str_doc.contains("# `str`"));
+    assert!(str_doc.contains("[`str lpad`](str_lpad.md)"));
+
+    let lpad_doc = std::fs::read_to_string(dir.path().join("str_lpad.md"))?;
+    assert!(lpad_doc.contains("## Flags"));
+    assert!(lpad_doc.contains("| `--length` | `-l` |"));
+
+    let downcase_doc = std::fs::read_to_string(dir.path().join("str_downcase.md"))?;
+    assert!(downcase_doc.contains("```shell\n> 'NU' | str downcase\nnu\n```"));
+
+    Ok(())
+}
+
+#[test]
+fn rm_refuses_current_directory_ancestor() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    std::fs::create_dir_all(dir.path().join("a/b"))?;
+
+    fail_test(
+        &format!(
+            "cd '{}'; rm -r -f ../..",
+            dir.path().join("a/b").to_string_lossy()
+        ),
+        "protected",
+    )?;
+
+    assert!(dir.path().join("a/b").exists());
+    Ok(())
+}
+
+#[test]
+fn rm_refuses_current_directory() -> TestResult {
+    let dir = tempfile::tempdir()?;
+
+    fail_test(
+        &format!("cd '{}'; rm -r .", dir.path().to_string_lossy()),
+        "protected",
+    )?;
+
+    assert!(dir.path().exists());
+    Ok(())
+}
+
+#[test]
+fn rm_force_removes_unprotected_path() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    std::fs::create_dir_all(dir.path().join("a/b"))?;
+
+    run_test(
+        &format!(
+            "cd '{}'; rm -r -f b -v | get 0.status",
+            dir.path().join("a").to_string_lossy()
+        ),
+        "ok",
+    )?;
+
+    assert!(!dir.path().join("a/b").exists());
+    Ok(())
+}
+
+#[test]
+fn rm_allow_protected_needs_force() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    std::fs::create_dir_all(dir.path().join("a/b"))?;
+
+    fail_test(
+        &format!(
+            "cd '{}'; rm -r --allow-protected ../b",
+            dir.path().join("a/b").to_string_lossy()
+        ),
+        "protected",
+    )?;
+
+    run_test(
+        &format!(
+            "cd '{}'; rm -r -f --allow-protected ../b -v | get 0.status",
+            dir.path().join("a/b").to_string_lossy()
+        ),
+        "ok",
+    )?;
+
+    assert!(!dir.path().join("a/b").exists());
+    Ok(())
+}
+
+#[test]
+fn str_predicates_reject_filesize_by_default() -> TestResult {
+    fail_test("2kb | str ends-with KB", "only works with strings")
+}
+
+#[test]
+fn str_predicates_render_filesize_column() -> TestResult {
+    run_test(
+        "[[size]; [2kb]] | str ends-with KB size --render | get size.0",
+        "true",
+    )
+}
+
+#[test]
+fn str_predicates_reject_date_by_default() -> TestResult {
+    fail_test("date now | str starts-with 20", "only works with strings")
+}
+
+#[test]
+fn str_predicates_render_date_column() -> TestResult {
+    run_test(
+        "[[modified]; [(date now)]] | str starts-with 20 modified -r | get modified.0",
+        "true",
+    )
+}
+
+#[test]
+fn format_filesize_column() -> TestResult {
+    run_test(
+        "[[size]; [1mib] [10b]] | format filesize KiB size | get size | str collect ' '",
+        "1024.00 KiB 0.01 KiB",
+    )
+}
+
+#[test]
+fn format_filesize_unknown_unit() -> TestResult {
+    fail_test("1kb | format filesize XB", "expected one of B, KB, KiB")
+}
+
+#[test]
+fn ansi_gradient() -> TestResult {
+    run_test(
+        r#"'ab' | ansi gradient --fgstart 0x000000 --fgend '#ff0000'"#,
+        "\x1b[38;2;0;0;0ma\x1b[38;2;255;0;0mb\x1b[0m",
+    )
+}
+
+#[test]
+fn ansi_gradient_invalid_color() -> TestResult {
+    fail_test(r#"'ab' | ansi gradient --fgstart red"#, "isn't a hex color")
+}
+
+#[test]
+fn ansi_link_column() -> TestResult {
+    run_test(
+        r#"[[url]; ['https://www.nushell.sh']] | ansi link --text nu url | get 0.url"#,
+        "\x1b]8;;https://www.nushell.sh\x1b\\nu\x1b]8;;\x1b\\",
+    )
+}
+
+#[test]
+fn str_commands_keep_the_input_span() -> TestResult {
+    // The type error points at the literal the string came from, several lines away from where
+    // it was downcased
+    fail_test(
+        r#"let name = 'MIXED_CASE_LITERAL'
+
+
+
+let lower = ($name | str downcase)
+
+
+
+$lower + 1"#,
+        "'MIXED_CASE_LITERAL'",
+    )
+}
+
+#[test]
+fn grid_truncates_wide_names() -> TestResult {
+    run_test(r#"[abcdefghij] | grid --width 5"#, "abcd…")
+}
+
+#[test]
+fn filesystem_commands_follow_pwd() -> TestResult {
+    let dir = tempfile::tempdir()?;
+
+    // The process stays in its own directory, only PWD moves
+    run_test(
+        &format!(
+            "let-env PWD = '{}'; mkdir sub; touch sub/file; ls sub | get name.0",
+            dir.path().to_string_lossy()
+        ),
+        &std::path::Path::new("sub").join("file").to_string_lossy(),
+    )?;
+
+    assert!(dir.path().join("sub").join("file").is_file());
+    assert!(!std::path::Path::new("sub").exists());
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn ls_shows_broken_links_in_their_row() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    std::os::unix::fs::symlink(dir.path().join("missing"), dir.path().join("dangling"))?;
+    std::fs::write(dir.path().join("file"), "")?;
+    let pwd = dir.path().to_string_lossy();
+
+    run_test(
+        &format!(
+            "let-env PWD = '{}'; ls | where type =~ 'Broken link' | get 0 | columns | str collect ','",
+            pwd
+        ),
+        "name,type,size,modified",
+    )?;
+    // The row of the link has an error for its type, which doesn't stop the filter
+    run_test(
+        &format!(
+            "let-env PWD = '{}'; ls | where type == file | get name | str collect ','",
+            pwd
+        ),
+        "file",
+    )?;
+    fail_test(
+        &format!("let-env PWD = '{}'; ls --strict", pwd),
+        "Broken link",
+    )
+}
+
+#[cfg(unix)]
+#[test]
+fn ls_shows_unreadable_entries_in_their_row() -> TestResult {
+    use std::fs::Permissions;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir()?;
+    let locked = dir.path().join("locked");
+    std::fs::create_dir(&locked)?;
+    std::fs::write(locked.join("file"), "")?;
+    // Without search permission, the names in the directory can be listed but not looked up
+    std::fs::set_permissions(&locked, Permissions::from_mode(0o600))?;
+    if std::fs::metadata(locked.join("file")).is_ok() {
+        // Permissions don't hold everyone back, eg root
+        std::fs::set_permissions(&locked, Permissions::from_mode(0o755))?;
+        return Ok(());
+    }
+
+    let pwd = dir.path().to_string_lossy();
+    let result = run_test(
+        &format!(
+            "let-env PWD = '{}'; ls locked | where size =~ 'Permission denied' | get 0 | columns | str collect ','",
+            pwd
+        ),
+        "name,type,size,modified",
+    )
+    .and_then(|_| {
+        run_test(
+            &format!(
+                "let-env PWD = '{}'; ls locked | where type == file | length",
+                pwd
+            ),
+            "0",
+        )
+    })
+    .and_then(|_| {
+        fail_test(
+            &format!("let-env PWD = '{}'; ls --strict locked", pwd),
+            "Permission denied",
+        )
+    });
+
+    // The directory can only be cleaned up once it can be searched again
+    std::fs::set_permissions(&locked, Permissions::from_mode(0o755))?;
+    result
+}
+
+#[test]
+fn ls_expands_braces_and_character_classes() -> TestResult {
+    let dir = tempfile::tempdir()?;
+    std::fs::create_dir(dir.path().join("sub"))?;
+    for name in [
+        "a.rs",
+        "a.txt",
+        "b.txt",
+        "c.txt",
+        "c.toml",
+        "{x}.txt",
+        "sub/deep.rs",
+    ] {
+        std::fs::write(dir.path().join(name), "")?;
+    }
+    let in_dir = |script: &str| {
+        format!(
+            "let-env PWD = '{}'; {}",
+            dir.path().to_string_lossy(),
+            script
+        )
+    };
+
+    run_test(
+        &in_dir("ls *.{rs,toml} | get name | str collect ','"),
+        "a.rs,c.toml",
+    )?;
+    run_test(
+        &in_dir("ls [ab]*.txt | get name | str collect ','"),
+        "a.txt,b.txt",
+    )?;
+    run_test(&in_dir("ls **/*.rs | length"), "2")?;
+    run_test(&in_dir("ls *.{md,json} | length"), "0")?;
+    // Quoted braces are matched as they are
+    run_test(&in_dir("ls '{x}.txt' | get name.0"), "{x}.txt")?;
+    fail_test(&in_dir("ls *.rs}"), "there is a '}' without a '{'")
+}
+
+#[test]
+fn where_numbered_keeps_the_row_index() -> TestResult {
+    run_test(
+        r##"[[name]; [a] [b] [c]] | where --numbered name != b | get 1."#""##,
+        "2",
+    )
+}
+
+#[test]
+fn hide_alias_reveals_the_command() -> TestResult {
+    run_test(
+        r#"def foo [] { 'command' }; alias foo = echo 'alias'; hide foo; foo"#,
+        "command",
+    )
+}
+
+#[test]
+fn hide_alias_then_redefine() -> TestResult {
+    run_test(
+        r#"alias foo = echo 'first'; hide foo; alias foo = echo 'second'; foo"#,
+        "second",
+    )
+}
+
+#[test]
+fn rm_trash_conflicts_with_permanent() -> TestResult {
+    fail_test(
+        "rm --trash --permanent nothing-to-remove",
+        "can't use --trash",
+    )?;
+    fail_test(
+        "rm -tp nothing-to-remove",
+        "can't use --trash with --permanent",
+    )
+}
+
+#[test]
+fn rm_allow_protected_requires_force() -> TestResult {
+    fail_test(
+        "rm --allow-protected nothing-to-remove",
+        "--allow-protected only works together with --force",
+    )
+}
+
+#[test]
+fn diff_reports_the_changed_path() -> TestResult {
+    run_test(
+        "{a: {b: [1 2]}} | diff {a: {b: [1 3]}} | get 0.path",
+        "a.b.1",
+    )
+}
+
+#[test]
+fn scope_commands_have_signatures() -> TestResult {
+    run_test(
+        r#"def spam [x: int, --loud] { $x }; $scope.commands | where name =~ "spam" | get 0.flags.long | str collect ","#,
+        "help,loud",
+    )
+}
+
+#[test]
+fn scope_commands_leave_hidden_ones_out() -> TestResult {
+    run_test(
+        r#"def spam [] { 1 }; hide spam; $scope.commands | where name == "spam" | length"#,
+        "0",
+    )
+}
+
+#[test]
+fn alias_without_placeholder_appends_arguments() -> TestResult {
+    run_test(r#"alias e = echo a; e b c | str collect ",""#, "a,b,c")
+}
+
+#[test]
+fn alias_placeholder_without_arguments() -> TestResult {
+    run_test(r#"alias e = echo a {} z; e | str collect ",""#, "a,z")
+}
+
+#[test]
+fn alias_placeholder_with_one_argument() -> TestResult {
+    run_test(r#"alias e = echo a {} z; e b | str collect ",""#, "a,b,z")
+}
+
+#[test]
+fn alias_placeholder_with_several_arguments() -> TestResult {
+    run_test(
+        r#"alias e = echo a {} z; e b c d | str collect ",""#,
+        "a,b,c,d,z",
+    )
+}
+
+#[test]
+fn alias_placeholder_passes_flags_through() -> TestResult {
+    run_test(
+        r#"def foo [x: int, --twice] { if $twice { $x * 2 } else { $x } }; alias f = foo {} --twice; f 21"#,
+        "42",
+    )
+}
+
+#[test]
+fn alias_with_two_placeholders_fails() -> TestResult {
+    fail_test(r#"alias e = echo {} {}; e a"#, "more than one placeholder")
+}
+
+#[test]
+fn help_shows_alias_template() -> TestResult {
+    run_test(
+        r#"alias e = echo a {} z; help e | str contains "echo a {} z""#,
+        "true",
+    )
+}
+
+#[test]
+fn ast_lists_defined_commands() -> TestResult {
+    run_test(
+        r#"ast 'def spam [] { 1 }; def eggs [] { 2 }' | get decls | str collect ",""#,
+        "spam,eggs",
+    )
+}
+
+#[test]
+fn ast_names_outer_variables() -> TestResult {
+    run_test(
+        r#"ast 'let x = 3' | get vars | where name == '$x' | get 0.type"#,
+        "int",
+    )
+}
+
+#[test]
+fn ast_shows_expression_kinds() -> TestResult {
+    run_test(
+        r#"ast 'ls | get name' | get pipelines.0.kind | str collect ",""#,
+        "call,call",
+    )
+}
+
+#[test]
+fn ast_reports_parse_errors() -> TestResult {
+    run_test(r#"(ast 'let' | get error | str length) > 0"#, "true")
+}
+
+#[test]
+fn help_lists_custom_commands() -> TestResult {
+    run_test(
+        r#"# Say hello
+def spam [] { "hello" }
+help commands | where name == "spam" | get 0.usage"#,
+        "Say hello",
+    )
+}
+
+#[test]
+fn help_shows_custom_command_usage() -> TestResult {
+    run_test(
+        r#"# Say hello
+def spam [] { "hello" }
+help spam | str contains "Say hello""#,
+        "true",
+    )
+}
+
+#[test]
+fn sort_plain_list() -> TestResult {
+    run_test(r#"[3 1 2] | sort | str collect ",""#, "1,2,3")
+}
+
+#[test]
+fn sort_reverse_insensitive() -> TestResult {
+    run_test(r#"[b A c] | sort -r -i | str collect ",""#, "c,b,A")
+}
+
+#[test]
+fn sort_mixed_types_by_group() -> TestResult {
+    run_test(r#"[b 2 a 1] | sort | str collect ",""#, "1,2,a,b")
+}
+
+#[test]
+fn sort_record_by_keys() -> TestResult {
+    run_test(
+        r#"{b: 1, c: 0, a: 2} | sort | columns | str collect ",""#,
+        "a,b,c",
+    )
+}
+
+#[test]
+fn sort_record_by_values() -> TestResult {
+    run_test(
+        r#"{b: 1, c: 0, a: 2} | sort --values | columns | str collect ",""#,
+        "c,b,a",
+    )
+}
+
+/// Run `input` as a script with the env and config startup files given, `extra_args` before the
+/// script, and `process_env` added to the environment it starts in
+fn run_with_startup_files(
+    env: &str,
+    config: &str,
+    extra_args: &[&str],
+    process_env: &[(&str, &str)],
+    input: &str,
+) -> Result<std::process::Output, Box<dyn std::error::Error>> {
+    let mut env_file = NamedTempFile::new()?;
+    writeln!(env_file, "{}", env)?;
+    let mut config_file = NamedTempFile::new()?;
+    writeln!(config_file, "{}", config)?;
+    let mut script = NamedTempFile::new()?;
+    writeln!(script, "{}", input)?;
+
+    let mut cmd = Command::cargo_bin("engine-q")?;
+    cmd.args(extra_args)
+        .envs(process_env.iter().copied())
+        .arg("--env-config")
+        .arg(env_file.path())
+        .arg("--config")
+        .arg(config_file.path())
+        .arg(script.path());
+
+    Ok(cmd.output()?)
+}
+
+#[test]
+fn startup_files_define_commands_for_the_script() -> TestResult {
+    let output = run_with_startup_files(
+        r#"def from_env [] { "env" }"#,
+        r#"def from_config [] { from_env }"#,
+        &[],
+        &[],
+        r#"[(from_env) (from_config)] | str collect ",""#,
+    )?;
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "env,env");
+
+    Ok(())
+}
+
+#[test]
+fn no_config_skips_startup_files() -> TestResult {
+    let output = run_with_startup_files(
+        r#"def from_env [] { "env" }"#,
+        r#"def from_config [] { "config" }"#,
+        &["--no-config"],
+        &[],
+        "from_config",
+    )?;
+
+    assert!(String::from_utf8_lossy(&output.stderr).contains(not_found_msg()));
+
+    Ok(())
+}
+
+#[test]
+fn failing_startup_file_does_not_stop_the_script() -> TestResult {
+    let output = run_with_startup_files(
+        r#"def from_env [] { "env" }"#,
+        "def broken [x: int] { $x }; broken not-a-number",
+        &[],
+        &[],
+        "from_env",
+    )?;
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "env");
+    assert!(!output.stderr.is_empty());
+
+    Ok(())
+}
+
+const FOO_AS_JSON: &str = r#"let config = {
+    env_conversions: {FOO: {from_string: { from json }, to_string: { to json }}}
+}"#;
+
+#[test]
+fn env_conversions_convert_from_the_process_environment() -> TestResult {
+    let output = run_with_startup_files(
+        "",
+        FOO_AS_JSON,
+        &[],
+        &[("FOO", r#"{"a": [1, 2, 3]}"#)],
+        "$nu.env.FOO.a | math sum",
+    )?;
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "6");
+
+    Ok(())
+}
+
+#[test]
+fn failed_env_conversions_leave_the_string() -> TestResult {
+    let output = run_with_startup_files("", FOO_AS_JSON, &[], &[("FOO", "[1, 2")], "$nu.env.FOO")?;
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "[1, 2");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("environment variable FOO"));
+
+    Ok(())
+}
+
+#[test]
+fn str_trim_format_collapses_whitespace() -> TestResult {
+    run_test(r#"" a  	 b   c " | str trim -f"#, "a b c")
+}
+
+#[test]
+fn str_trim_all_with_left_is_rejected() -> TestResult {
+    fail_test(r#"" a  b " | str trim --all --left"#, "can't use")
+}
+
+#[test]
+fn str_trim_char_must_be_one_character() -> TestResult {
+    fail_test(r#""--a--" | str trim --char "--""#, "single character")
+}
+
+#[test]
+fn str_downcase_names_the_failing_row_and_column() -> TestResult {
+    fail_test(
+        r#"[[ColA]; [a] [b] [c] [d] [e] [f] [g] [7] [i] [j]] | str downcase ColA | get 7"#,
+        "ColA in row 7",
+    )
+}
+
+#[test]
+fn column_commands_name_the_failing_row_and_column() -> TestResult {
+    fail_test(
+        r#"[[ColA]; [a] [b] [c] [d] [e] [f] [g] [7] [i] [j]] | str upcase ColA | get 7"#,
+        "ColA in row 7",
+    )?;
+    fail_test(
+        r#"[[ColA]; [1] [2] [3] [x] [5]] | where ColA != 2 | into int ColA | get 2"#,
+        "ColA in row 3",
+    )?;
+    fail_test(
+        r#"[[ColA ColB]; [a 1] [b x]] | str length ColA ColB | get 0"#,
+        "ColB in row 0",
+    )
+}
+
+#[test]
+fn failed_rows_can_be_found_with_where() -> TestResult {
+    run_test(
+        r#"[[ColA]; [A] [B] [C] [D] [E] [F] [G] [7] [I] [J]] | str camel-case ColA | where $it =~ "row 7" | length"#,
+        "1",
+    )
+}
+
+#[test]
+fn failed_rows_are_numbered_as_they_come_after_where() -> TestResult {
+    fail_test(
+        r#"[[ColA]; [A] [B] [C] [D] [E] [F] [G] [7] [I] [J]] | where ColA != C | str downcase ColA | get 6"#,
+        "ColA in row 7",
+    )
+}
+
+#[test]
+fn group_by_date_format() -> TestResult {
+    run_test(
+        r#"['2021-10-23 09:00:00 +00:00' '2021-10-22 23:59:59 +00:00' '2021-10-23 18:45:00 +00:00'] | group-by --format '%Y-%m-%d' --sorted | get 2021-10-23 | length"#,
+        "2",
+    )
+}
+
+#[test]
+fn group_by_blocks() -> TestResult {
+    run_test(
+        r#"[[size]; [30] [3] [12] [27] [0] [20]] | group-by size --blocks 3 | columns | str collect ' '"#,
+        "20..30 0..10 10..20",
+    )
+}
+
+#[test]
+fn group_by_blocks_needs_numbers() -> TestResult {
+    fail_test(r#"[1 spam] | group-by --blocks 2"#, "groups numbers")
+}
+
+#[test]
+fn str_case_matches_case_commands() -> TestResult {
+    run_test(
+        r#"let w = 'this-is_a test NuShell SCREAMING_SNAKE';
+(($w | str case kebab) == ($w | str kebab-case)) && (($w | str case snake) == ($w | str snake-case)) && (($w | str case camel) == ($w | str camel-case)) && (($w | str case pascal) == ($w | str pascal-case)) && (($w | str case screaming-snake) == ($w | str screaming-snake-case))"#,
+        "true",
+    )
+}
+
+#[test]
+fn str_case_column() -> TestResult {
+    run_test(
+        r#"[[name]; [spam_eggs]] | str case pascal name | get name.0"#,
+        "SpamEggs",
+    )
+}
+
+#[test]
+fn str_case_unknown_style() -> TestResult {
+    fail_test(r#"'spam' | str case sponge"#, "expected one of kebab")
+}
+
+#[test]
+fn to_yaml_names_the_path_to_unsupported_values() -> TestResult {
+    fail_test(
+        r#"{rows: [[payload]; [1] [2] [3] [{ 4 }]]} | to yaml"#,
+        "can't convert block at rows.3.payload to YAML",
+    )
+}
+
+#[test]
+fn help_commands_lists_categories() -> TestResult {
+    run_test(
+        r#"help commands | where name == "str kebab-case" | get 0.category"#,
+        "strings",
+    )
+}
+
+#[test]
+fn help_shows_category() -> TestResult {
+    run_test(
+        r#"help mkdir | str contains "Category: filesystem""#,
+        "true",
+    )
+}
+
+#[test]
+fn help_lists_examples() -> TestResult {
+    run_test(r#"help str kebab-case --examples | length"#, "4")
+}
+
+#[test]
+fn help_lists_example_sources() -> TestResult {
+    run_test(
+        r#"help str kebab-case --examples | get 0.example"#,
+        "'NuShell' | str kebab-case",
+    )
+}
+
+#[test]
+fn help_lists_example_results() -> TestResult {
+    run_test(
+        r#"help str kebab-case --examples | get 3.result | where gems == 100 | get 0.lang"#,
+        "nu-test",
+    )
+}
+
+#[test]
+fn help_lists_no_examples_as_an_empty_table() -> TestResult {
+    run_test(
+        r#"def spam [] { "hello" }; help spam --examples | length"#,
+        "0",
+    )
+}
+
+#[test]
+fn page_without_terminal_outputs_the_table() -> TestResult {
+    run_test(r#"'spam' | page"#, "spam")
+}